@@ -0,0 +1,144 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::{
+    hex,
+    primitives::{keccak256, Address, Signature, B256},
+};
+use axum::http::HeaderMap;
+
+/// The header carrying the UNIX timestamp in seconds at which a request was signed.
+pub const AUTH_TIMESTAMP_HEADER: &str = "x-bolt-timestamp";
+
+/// The header carrying the ECDSA signature of a request by the sidecar that sent it.
+pub const AUTH_SIGNATURE_HEADER: &str = "x-bolt-signature";
+
+/// An error authenticating a request to a constraints submission endpoint.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("missing {0} header")]
+    MissingHeader(&'static str),
+    #[error("malformed {0} header")]
+    MalformedHeader(&'static str),
+    #[error("request signed {0}s away from the current time, check the clock of the sidecar")]
+    ClockSkew(u64),
+    #[error("request signed by {0}, which is not an authorized sidecar")]
+    Unauthorized(Address),
+}
+
+/// The digest signed by sidecars to authenticate a request to bolt-boost:
+///
+/// `keccak256(path | be_bytes(timestamp) | keccak256(body))`
+///
+/// The path binds the signature to the endpoint, and the timestamp bounds the window
+/// in which the request can be replayed.
+pub fn auth_digest(path: &str, timestamp: u64, body: &[u8]) -> B256 {
+    let mut data = Vec::with_capacity(path.len() + 8 + 32);
+    data.extend_from_slice(path.as_bytes());
+    data.extend_from_slice(&timestamp.to_be_bytes());
+    data.extend_from_slice(keccak256(body).as_slice());
+    keccak256(data)
+}
+
+/// Verifies that a request to `path` is signed by one of the `authorized` sidecars less
+/// than `max_skew_secs` seconds away from now, returning its signer.
+pub fn verify_request(
+    headers: &HeaderMap,
+    path: &str,
+    body: &[u8],
+    authorized: &[Address],
+    max_skew_secs: u64,
+) -> Result<Address, AuthError> {
+    let timestamp = header(headers, AUTH_TIMESTAMP_HEADER)?
+        .parse::<u64>()
+        .map_err(|_| AuthError::MalformedHeader(AUTH_TIMESTAMP_HEADER))?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let skew = now.abs_diff(timestamp);
+    if skew > max_skew_secs {
+        return Err(AuthError::ClockSkew(skew));
+    }
+
+    let signature = hex::decode(header(headers, AUTH_SIGNATURE_HEADER)?)
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+        .ok_or(AuthError::MalformedHeader(AUTH_SIGNATURE_HEADER))?;
+
+    let signer = signature
+        .recover_address_from_prehash(&auth_digest(path, timestamp, body))
+        .map_err(|_| AuthError::MalformedHeader(AUTH_SIGNATURE_HEADER))?;
+
+    if !authorized.contains(&signer) {
+        return Err(AuthError::Unauthorized(signer));
+    }
+
+    Ok(signer)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<&'a str, AuthError> {
+    headers
+        .get(name)
+        .ok_or(AuthError::MissingHeader(name))?
+        .to_str()
+        .map_err(|_| AuthError::MalformedHeader(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+
+    fn signed_headers(
+        signer: &PrivateKeySigner,
+        path: &str,
+        timestamp: u64,
+        body: &[u8],
+    ) -> HeaderMap {
+        let signature = signer.sign_hash_sync(&auth_digest(path, timestamp, body)).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTH_TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(
+            AUTH_SIGNATURE_HEADER,
+            hex::encode_prefixed(signature.as_bytes()).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_verify_request() {
+        let sidecar = PrivateKeySigner::random();
+        let authorized = [sidecar.address()];
+        let (path, body) = ("/constraints/v1/builder/constraints", b"[]");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        let headers = signed_headers(&sidecar, path, now, body);
+        assert_eq!(
+            verify_request(&headers, path, body, &authorized, 30).unwrap(),
+            sidecar.address()
+        );
+
+        // Tampered bodies and other endpoints recover another signer
+        let err = verify_request(&headers, path, b"[{}]", &authorized, 30).unwrap_err();
+        assert!(matches!(err, AuthError::Unauthorized(_)));
+        let err = verify_request(&headers, "/constraints/v1/builder/revoke", body, &authorized, 30);
+        assert!(matches!(err, Err(AuthError::Unauthorized(_))));
+
+        let headers = signed_headers(&PrivateKeySigner::random(), path, now, body);
+        assert!(matches!(
+            verify_request(&headers, path, body, &authorized, 30),
+            Err(AuthError::Unauthorized(_))
+        ));
+
+        let headers = signed_headers(&sidecar, path, now - 60, body);
+        assert!(matches!(
+            verify_request(&headers, path, body, &authorized, 30),
+            Err(AuthError::ClockSkew(_))
+        ));
+
+        assert!(matches!(
+            verify_request(&HeaderMap::new(), path, body, &authorized, 30),
+            Err(AuthError::MissingHeader(AUTH_TIMESTAMP_HEADER))
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use alloy::primitives::U256;
+
+/// The tracing target of the decision log, which records the value of every bid received
+/// for a constrained slot, its adjusted value and the bid returned to the proposer.
+pub const DECISION_LOG_TARGET: &str = "bolt_boost::decisions";
+
+/// A bid valued on a like-for-like basis with bids for unconstrained blocks, as defined by
+/// [bolt_primitives::adjusted_bid_value] and shared with the sidecar.
+///
+/// Bids are compared and checked against the minimum bid on their adjusted value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjustedBid {
+    /// The value of the bid, in wei.
+    pub value: U256,
+    /// The gas used by the block of the bid.
+    pub gas_used: u64,
+    /// The gas of the constrained transactions, capped to the gas used by the block.
+    pub constrained_gas: u64,
+    /// The value of the bid adjusted for the constrained gas, in wei.
+    pub adjusted_value: U256,
+}
+
+impl AdjustedBid {
+    /// Adjusts the value of a bid for the gas of the constrained transactions of its slot.
+    pub fn new(value: U256, gas_used: u64, constrained_gas: u64) -> Self {
+        let adjusted_value = U256::from(bolt_primitives::adjusted_bid_value(
+            value.saturating_to(),
+            gas_used,
+            constrained_gas,
+        ));
+
+        Self { value, gas_used, constrained_gas: constrained_gas.min(gas_used), adjusted_value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjusted_bid() {
+        let value = U256::from(1_000_000);
+
+        // Unconstrained bids keep their value
+        assert_eq!(AdjustedBid::new(value, 15_000_000, 0).adjusted_value, value);
+
+        // A quarter of the gas is constrained
+        let bid = AdjustedBid::new(value, 20_000_000, 5_000_000);
+        assert_eq!(bid.adjusted_value, U256::from(1_333_333));
+
+        // A smaller block with the same constraints and value is worth more
+        let smaller = AdjustedBid::new(value, 10_000_000, 5_000_000);
+        assert!(smaller.adjusted_value > bid.adjusted_value);
+
+        // Constrained gas is capped to the gas used
+        let full = AdjustedBid::new(value, 5_000_000, 6_000_000);
+        assert_eq!(full.constrained_gas, 5_000_000);
+        assert_eq!(full.adjusted_value, value);
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::MAX_CONSTRAINTS_PER_SLOT;
+
+/// The commitment type of plain inclusion constraints.
+pub(crate) const INCLUSION_COMMITMENT: &str = "inclusion";
+
+/// The encoding of constraints submissions as JSON.
+pub(crate) const JSON_ENCODING: &str = "json";
+
+/// The features supported by a Constraints API server, served by its capabilities endpoint
+/// so that sidecars only offer the commitments that can be enforced.
+///
+/// Fields missing from the capabilities of a relay take the value of
+/// [Capabilities::default], which describes a relay implementing the Constraints API before
+/// the endpoint was introduced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Capabilities {
+    /// The types of commitments whose constraints are enforced.
+    pub commitment_types: Vec<String>,
+    /// The max number of constraints accepted per slot, if capped.
+    pub max_constraints_per_slot: Option<usize>,
+    /// The max gas that can be constrained per slot, if capped.
+    pub max_constrained_gas_per_slot: Option<u64>,
+    /// Whether top-of-block constraints are enforced.
+    pub top_of_block: bool,
+    /// The encodings of constraints submissions accepted.
+    pub encodings: Vec<String>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            commitment_types: vec![INCLUSION_COMMITMENT.to_string()],
+            max_constraints_per_slot: None,
+            max_constrained_gas_per_slot: None,
+            top_of_block: true,
+            encodings: vec![JSON_ENCODING.to_string()],
+        }
+    }
+}
+
+impl Capabilities {
+    /// Returns the capabilities of bolt-boost itself, before those of its relays.
+    pub fn local(max_constrained_gas_per_slot: Option<u64>) -> Self {
+        Self {
+            max_constraints_per_slot: Some(MAX_CONSTRAINTS_PER_SLOT),
+            max_constrained_gas_per_slot,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if the given encoding of constraints submissions is accepted.
+    pub fn supports_encoding(&self, encoding: &str) -> bool {
+        self.encodings.iter().any(|e| e.eq_ignore_ascii_case(encoding))
+    }
+
+    /// Restricts the capabilities to the commitments that a relay also enforces, since
+    /// constraints are forwarded to every relay.
+    ///
+    /// The encodings are left untouched, as bolt-boost negotiates them with each relay.
+    pub fn restrict_to(&mut self, relay: &Capabilities) {
+        self.commitment_types
+            .retain(|t| relay.commitment_types.iter().any(|r| r.eq_ignore_ascii_case(t)));
+        self.max_constraints_per_slot =
+            min_cap(self.max_constraints_per_slot, relay.max_constraints_per_slot);
+        self.max_constrained_gas_per_slot =
+            min_cap(self.max_constrained_gas_per_slot, relay.max_constrained_gas_per_slot);
+        self.top_of_block &= relay.top_of_block;
+    }
+}
+
+/// Returns the lowest of two optional caps, where `None` means uncapped.
+fn min_cap<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restrict_capabilities() {
+        let mut capabilities = Capabilities::local(None);
+
+        let relay: Capabilities = serde_json::from_str(
+            r#"{"maxConstraintsPerSlot":256,"maxConstrainedGasPerSlot":1000000,
+                "topOfBlock":false,"encodings":["json","hash-refs"]}"#,
+        )
+        .unwrap();
+        assert_eq!(relay.commitment_types, vec![INCLUSION_COMMITMENT.to_string()]);
+        capabilities.restrict_to(&relay);
+
+        assert_eq!(capabilities.max_constraints_per_slot, Some(MAX_CONSTRAINTS_PER_SLOT));
+        assert_eq!(capabilities.max_constrained_gas_per_slot, Some(1_000_000));
+        assert!(!capabilities.top_of_block);
+        assert!(capabilities.supports_encoding(JSON_ENCODING));
+        assert!(!capabilities.supports_encoding("hash-refs"));
+
+        // Commitment types that a relay doesn't enforce are withdrawn
+        capabilities.restrict_to(&Capabilities { commitment_types: vec![], ..relay });
+        assert!(capabilities.commitment_types.is_empty());
+    }
+}
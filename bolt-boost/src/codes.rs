@@ -0,0 +1,91 @@
+use std::fmt;
+
+/// The header carrying the [ErrorCode] of an error response.
+pub const ERROR_CODE_HEADER: &str = "x-bolt-error-code";
+
+/// Stable machine-readable codes of the errors returned by the bolt components.
+///
+/// This is the subset of the `api::codes` table of the sidecar that bolt-boost returns, which
+/// must be kept in sync with it, so that gateways see the same code for the same condition
+/// whichever component rejected the request. bolt-boost sends the codes in the
+/// [ERROR_CODE_HEADER] of error responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// The request is not valid JSON or JSON-RPC.
+    InvalidRequest,
+    /// The request was already received.
+    Duplicate,
+    /// The component failed to process a valid request.
+    Internal,
+    /// The request is not signed.
+    MissingSignature,
+    /// The signature of the request is invalid.
+    InvalidSignature,
+    /// The request failed a validation without a more specific code.
+    ValidationFailed,
+    /// A header of the request is malformed.
+    MalformedHeader,
+    /// The target slot has no commitment, gas or blob capacity left.
+    SlotFull,
+    /// The request is signed for or targets another chain.
+    WrongChain,
+    /// The signer of the request is not allowed to submit it.
+    Unauthorized,
+    /// The request is too large.
+    PayloadTooLarge,
+    /// An upstream service, e.g. a relay or the beacon node, is unavailable.
+    UpstreamUnavailable,
+}
+
+impl ErrorCode {
+    /// The table of the error codes: their name, JSON-RPC code and HTTP status.
+    ///
+    /// The codes are stable: existing entries must never change, new ones get the next
+    /// free JSON-RPC code.
+    pub const fn spec(self) -> (&'static str, i32, u16) {
+        match self {
+            Self::InvalidRequest => ("INVALID_REQUEST", -32600, 400),
+            Self::Duplicate => ("DUPLICATE", -32001, 400),
+            Self::Internal => ("INTERNAL", -32002, 500),
+            Self::MissingSignature => ("MISSING_SIGNATURE", -32003, 400),
+            Self::InvalidSignature => ("INVALID_SIGNATURE", -32004, 400),
+            Self::ValidationFailed => ("VALIDATION_FAILED", -32006, 400),
+            Self::MalformedHeader => ("MALFORMED_HEADER", -32007, 400),
+            Self::SlotFull => ("SLOT_FULL", -32011, 400),
+            Self::WrongChain => ("WRONG_CHAIN", -32017, 400),
+            Self::Unauthorized => ("UNAUTHORIZED", -32018, 401),
+            Self::PayloadTooLarge => ("PAYLOAD_TOO_LARGE", -32019, 413),
+            Self::UpstreamUnavailable => ("UPSTREAM_UNAVAILABLE", -32022, 503),
+        }
+    }
+
+    /// Returns the name of the code, e.g. "SLOT_FULL".
+    pub const fn as_str(self) -> &'static str {
+        self.spec().0
+    }
+
+    /// Returns the header to attach to an error response with the code.
+    pub const fn header(self) -> [(&'static str, &'static str); 1] {
+        [(ERROR_CODE_HEADER, self.as_str())]
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_match_the_sidecar() {
+        // A sample of the table of the sidecar, which bolt-boost can't depend on
+        assert_eq!(ErrorCode::SlotFull.spec(), ("SLOT_FULL", -32011, 400));
+        assert_eq!(ErrorCode::WrongChain.spec(), ("WRONG_CHAIN", -32017, 400));
+        assert_eq!(ErrorCode::Unauthorized.spec(), ("UNAUTHORIZED", -32018, 401));
+        assert_eq!(ErrorCode::PayloadTooLarge.spec(), ("PAYLOAD_TOO_LARGE", -32019, 413));
+    }
+}
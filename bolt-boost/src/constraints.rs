@@ -1,26 +1,133 @@
-use alloy::eips::eip2718::Eip2718Error;
+use alloy::{eips::eip2718::Eip2718Error, primitives::Address, rpc::types::beacon::BlsPublicKey};
 use parking_lot::RwLock;
-use std::{collections::HashMap, sync::Arc};
-use tracing::error;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
 
-use crate::metrics;
+use crate::{
+    constraints_store::{ConstraintsStore, StoreError, StoredConstraints},
+    metrics,
+    slot_scoped::SlotScoped,
+};
 
 use super::types::{ConstraintsMessage, ConstraintsWithProofData};
 
 pub(crate) const MAX_CONSTRAINTS_PER_SLOT: usize = 128;
 
-/// A concurrent cache of constraints.
-#[derive(Clone, Default, Debug)]
+/// The number of events buffered for each subscriber of the cache. Subscribers lagging
+/// further behind miss the oldest events.
+pub(crate) const EVENTS_CAPACITY: usize = 1024;
+
+/// The default gas limit of a block, which the constrained transactions of a slot must fit in.
+pub(crate) const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// The default max number of blobs per block, as of Deneb.
+pub(crate) const DEFAULT_MAX_BLOBS_PER_BLOCK: u64 = 6;
+
+/// A concurrent cache of constraints, partitioned by the proposers they bind.
+///
+/// One bolt-boost instance can serve the validators of several sidecars. The constraints of a
+/// slot are therefore only checked for conflicts with, and verified against the headers of,
+/// the constraints of the same proposer. Constraints whose signer can't be attributed to any
+/// known proposer are shared by every proposer of the slot.
+#[derive(Clone, Debug)]
 pub struct ConstraintsCache {
-    cache: Arc<RwLock<HashMap<u64, Vec<ConstraintsWithProofData>>>>,
+    cache: Arc<RwLock<SlotScoped<Vec<ProposerConstraints>>>>,
+    /// The max gas that can be constrained per proposer and slot, if capped.
+    max_gas_per_slot: Option<u64>,
+    /// The gas limit of a block, which the constraints of a proposer in a slot can't exceed.
+    block_gas_limit: u64,
+    /// The max number of blobs per block, which the constraints of a proposer in a slot can't
+    /// exceed.
+    max_blobs_per_block: u64,
+    /// The sidecars submitting constraints for the same proposers, by descending precedence.
+    precedence: Arc<Vec<Address>>,
+    /// The write-ahead log of the accepted constraints, if they are persisted.
+    store: Option<ConstraintsStore>,
+    /// The sender of the changes to the cache, streamed to the subscribed builders.
+    events: broadcast::Sender<ConstraintsEvent>,
+}
+
+/// A change to the constraints cache, streamed to the builders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConstraintsEvent {
+    /// Constraints were accepted for a slot.
+    Constraints { slot: u64, message: ConstraintsMessage },
+    /// Constraints of a slot were dropped in favor of those of a sidecar with a higher
+    /// precedence.
+    Removed { slot: u64, message: ConstraintsMessage },
+    /// The slot passed and its constraints were cleaned up.
+    SlotCleared { slot: u64 },
+}
+
+/// The outcome of the submission of constraints by a sidecar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    /// The constraints were added to the constraints of their slot.
+    Inserted,
+    /// The same constraints were already submitted by another sidecar, so they were not added.
+    Duplicate,
+    /// The constraints diverge from those of sidecars with a lower precedence for the same
+    /// proposers, which were dropped in their favor. Holds the number of dropped constraints.
+    Superseded(usize),
+}
+
+/// The constraints received for a proposer in an upcoming slot, against the caps of the
+/// cache, to compare the view of the relays with the template accounting of the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotCapacity {
+    /// The slot of the constraints.
+    pub slot: u64,
+    /// The proposer the constraints bind. None for the constraints whose signer can't be
+    /// attributed to any known proposer, when no known proposer has constraints in the slot.
+    pub proposer: Option<BlsPublicKey>,
+    /// The number of constraints received.
+    pub constraints: usize,
+    /// The max number of constraints per proposer and slot.
+    pub max_constraints: usize,
+    /// The total gas limit of the constrained transactions.
+    pub constrained_gas: u64,
+    /// The max gas that can be constrained per proposer and slot, if capped.
+    pub max_constrained_gas: Option<u64>,
+}
+
+/// Constraints along with the proposers they bind.
+#[derive(Debug)]
+struct ProposerConstraints {
+    /// The proposers the signer of the constraints can sign for. Empty if unknown.
+    proposers: Vec<BlsPublicKey>,
+    /// The authenticated sidecar that submitted the constraints, if known.
+    source: Option<Address>,
+    constraints: ConstraintsWithProofData,
+    /// The id of the constraints in the store, if persisted.
+    id: Option<u64>,
+}
+
+impl ProposerConstraints {
+    /// Returns true if the constraints bind the given proposer.
+    fn binds(&self, proposer: &BlsPublicKey) -> bool {
+        self.proposers.is_empty() || self.proposers.contains(proposer)
+    }
+
+    /// Returns true if the constraints bind any of the given proposers.
+    fn shares_proposer(&self, proposers: &[BlsPublicKey]) -> bool {
+        proposers.is_empty() || self.proposers.is_empty() || proposers.iter().any(|p| self.binds(p))
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Conflict {
-    #[error("Multiple ToB constraints per slot")]
+    #[error("ToB constraints overlap the positions of other ToB constraints in the same slot")]
     TopOfBlock,
     #[error("Duplicate transaction in the same slot")]
     DuplicateTransaction,
+    #[error("Constraints in the same slot exceed the block gas limit of {0}")]
+    GasLimitExceeded(u64),
+    #[error("Constraints in the same slot exceed the max of {0} blobs per block")]
+    BlobLimitExceeded(u64),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -29,34 +136,164 @@ pub enum Error {
     Conflict(#[from] Conflict),
     #[error(transparent)]
     Decode(#[from] Eip2718Error),
+    #[error("Only ToB constraints can have an index, got {0}")]
+    IndexWithoutTopOfBlock(u64),
     #[error("Max constraints per slot reached for slot {0}")]
     LimitReached(u64),
+    #[error("Max constrained gas per slot reached for slot {0}: {1}")]
+    GasLimitReached(u64, u64),
+    #[error("Constraints for slot {0} diverge from those of a sidecar with higher precedence")]
+    Diverged(u64),
+    #[error(transparent)]
+    Persistence(#[from] StoreError),
+}
+
+impl Default for ConstraintsCache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ConstraintsCache {
     pub fn new() -> Self {
-        Self { cache: Default::default() }
+        Self {
+            cache: Default::default(),
+            max_gas_per_slot: None,
+            block_gas_limit: DEFAULT_BLOCK_GAS_LIMIT,
+            max_blobs_per_block: DEFAULT_MAX_BLOBS_PER_BLOCK,
+            precedence: Default::default(),
+            store: None,
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+        }
+    }
+
+    /// Subscribes to the changes to the cache: the accepted constraints, the superseded ones
+    /// and the slots cleaned up.
+    pub fn subscribe(&self) -> broadcast::Receiver<ConstraintsEvent> {
+        self.events.subscribe()
+    }
+
+    /// Sends an event to the subscribers, if any.
+    fn notify(&self, event: ConstraintsEvent) {
+        // Sending only fails without subscribers
+        let _ = self.events.send(event);
+    }
+
+    /// Persists the accepted constraints in the given store before adding them to the cache,
+    /// so that they can be [recovered](ConstraintsCache::recover) after a restart.
+    pub fn with_store(mut self, store: ConstraintsStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Recovers the persisted constraints of every slot from the given head slot, and deletes
+    /// those of the slots before it. Returns the number of recovered constraints.
+    ///
+    /// Recovered constraints were checked when they were accepted, so they are added as is.
+    pub fn recover(&self, head: u64) -> Result<usize, StoreError> {
+        let Some(store) = self.store.as_ref() else { return Ok(0) };
+        store.remove_before(head);
+
+        let mut cache = self.cache.write();
+        let mut recovered = 0;
+        for (slot, id, stored) in store.load_from(head)? {
+            let constraints = match ConstraintsWithProofData::try_from(stored.message) {
+                Ok(constraints) => constraints,
+                Err(err) => {
+                    warn!(slot, id, ?err, "Dropping undecodable persisted constraints");
+                    store.delete(slot, id);
+                    continue;
+                }
+            };
+
+            let entry = ProposerConstraints {
+                proposers: stored.proposers,
+                source: stored.source,
+                constraints,
+                id: Some(id),
+            };
+            match cache.get_mut(slot) {
+                Some(cs) => cs.push(entry),
+                None => {
+                    cache.insert(slot, vec![entry]);
+                }
+            }
+            recovered += 1;
+        }
+
+        metrics::CONSTRAINTS_CACHE_SIZE.add(recovered as i64);
+        info!(head, recovered, "Recovered persisted constraints");
+        Ok(recovered)
+    }
+
+    /// Deletes the given constraints of the slot from the store, if persisted.
+    fn forget(&self, slot: u64, removed: &[ProposerConstraints]) {
+        if let Some(store) = self.store.as_ref() {
+            removed.iter().filter_map(|c| c.id).for_each(|id| store.delete(slot, id));
+        }
+    }
+
+    /// Caps the gas that can be constrained per proposer and slot.
+    pub fn with_max_gas_per_slot(mut self, max_gas_per_slot: Option<u64>) -> Self {
+        self.max_gas_per_slot = max_gas_per_slot;
+        self
+    }
+
+    /// Sets the gas limit and the max number of blobs of a block, which the constraints of a
+    /// proposer in a slot must fit in.
+    pub fn with_block_limits(mut self, block_gas_limit: u64, max_blobs_per_block: u64) -> Self {
+        self.block_gas_limit = block_gas_limit;
+        self.max_blobs_per_block = max_blobs_per_block;
+        self
+    }
+
+    /// Reconciles the constraints submitted for the same proposers by several sidecars, e.g.
+    /// a primary and a backup one, with the given precedence: the first sidecar has the highest
+    /// one, and unlisted sidecars the lowest. If empty, constraints are not reconciled.
+    pub fn with_precedence(mut self, precedence: Vec<Address>) -> Self {
+        self.precedence = Arc::new(precedence);
+        self
+    }
+
+    /// Returns the rank of the given sidecar in the precedence, lower ranks taking precedence.
+    fn rank(&self, source: &Address) -> usize {
+        self.precedence.iter().position(|s| s == source).unwrap_or(self.precedence.len())
     }
 
-    /// Checks if the constraints for the given slot conflict with the existing constraints.
-    /// Returns a [Conflict] in case of a conflict, None otherwise.
+    /// Checks if the constraints for the given slot conflict with the existing constraints
+    /// of the same proposers. Returns a [Conflict] in case of a conflict, None otherwise.
     ///
     /// # Possible conflicts
-    /// - Multiple ToB constraints per slot
+    /// - ToB bundles overlapping each other's positions in the same slot
     /// - Duplicates of the same transaction per slot
-    pub fn conflicts_with(&self, slot: &u64, constraints: &ConstraintsMessage) -> Option<Conflict> {
-        if let Some(saved_constraints) = self.cache.read().get(slot) {
-            for saved_constraint in saved_constraints {
-                // Only 1 ToB constraint per slot
-                if constraints.top && saved_constraint.message.top {
+    ///
+    /// Constraints exceeding the gas limit or the max blobs of the block are only rejected
+    /// on [insertion](ConstraintsCache::insert), once their transactions are decoded.
+    pub fn conflicts_with(
+        &self,
+        slot: &u64,
+        constraints: &ConstraintsMessage,
+        proposers: &[BlsPublicKey],
+    ) -> Option<Conflict> {
+        let cache = self.cache.read();
+        let saved = cache.get(*slot)?.iter().filter(|saved| saved.shares_proposer(proposers));
+
+        for saved in saved {
+            let saved_constraint = &saved.constraints;
+
+            // ToB bundles can't take the same positions in the block
+            if let (Some(positions), Some(saved_positions)) =
+                (constraints.top_positions(), saved_constraint.message.top_positions())
+            {
+                if positions.start < saved_positions.end && saved_positions.start < positions.end {
                     return Some(Conflict::TopOfBlock);
                 }
+            }
 
-                // Check if the transactions are the same
-                for tx in &constraints.transactions {
-                    if saved_constraint.message.transactions.iter().any(|existing| tx == existing) {
-                        return Some(Conflict::DuplicateTransaction);
-                    }
+            // Check if the transactions are the same
+            for tx in &constraints.transactions {
+                if saved_constraint.message.transactions.iter().any(|existing| tx == existing) {
+                    return Some(Conflict::DuplicateTransaction);
                 }
             }
         }
@@ -64,46 +301,294 @@ impl ConstraintsCache {
         None
     }
 
-    /// Inserts the constraints for the given slot. Also decodes the raw transactions to save their
-    /// transaction hashes and hash tree roots for later use. Will first check for conflicts, and
-    /// return an error if there are any.
-    pub fn insert(&self, slot: u64, constraints: ConstraintsMessage) -> Result<(), Error> {
-        if let Some(conflict) = self.conflicts_with(&slot, &constraints) {
+    /// Inserts the constraints for the given slot, binding the given proposers (all of them if
+    /// empty). Also decodes the raw transactions to save their transaction hashes and hash tree
+    /// roots for later use. Will first check for conflicts, and return an error if there are any.
+    pub fn insert(
+        &self,
+        slot: u64,
+        constraints: ConstraintsMessage,
+        proposers: Vec<BlsPublicKey>,
+    ) -> Result<(), Error> {
+        self.insert_from(slot, constraints, proposers, None)
+    }
+
+    /// Submits the constraints of the given sidecar for the given slot, reconciling them with
+    /// those of the other sidecars submitting constraints for the same proposers, if a
+    /// [precedence](ConstraintsCache::with_precedence) is configured:
+    ///
+    /// - constraints identical to those of another sidecar are deduplicated;
+    /// - constraints diverging from those of sidecars with a lower precedence replace them;
+    /// - constraints diverging from those of a sidecar with a higher or equal precedence are
+    ///   rejected, rather than merging conflicting constraint sets.
+    ///
+    /// Divergences are logged and counted, to alert operators. The constraints are then
+    /// [inserted](ConstraintsCache::insert) as usual.
+    pub fn submit(
+        &self,
+        slot: u64,
+        constraints: ConstraintsMessage,
+        proposers: Vec<BlsPublicKey>,
+        source: Option<Address>,
+    ) -> Result<Submission, Error> {
+        let Some(source) = source.filter(|_| !self.precedence.is_empty()) else {
+            return self
+                .insert_from(slot, constraints, proposers, source)
+                .map(|_| Submission::Inserted);
+        };
+
+        let superseded = {
+            let mut cache = self.cache.write();
+            let others =
+                cache.get(slot).map_or(&[][..], |cs| cs.as_slice()).iter().filter(|c| {
+                    c.source.is_some_and(|s| s != source) && c.shares_proposer(&proposers)
+                });
+
+            let mut highest = None;
+            for other in others {
+                let message = &other.constraints.message;
+                if message.top == constraints.top &&
+                    message.index == constraints.index &&
+                    message.transactions == constraints.transactions
+                {
+                    metrics::CONSTRAINTS_SOURCE_OUTCOMES.with_label_values(&["duplicate"]).inc();
+                    return Ok(Submission::Duplicate);
+                }
+                let rank = self.rank(&other.source.expect("Filtered on source"));
+                highest = Some(highest.map_or(rank, |highest: usize| highest.min(rank)));
+            }
+
+            match highest {
+                None => 0,
+                Some(highest) if self.rank(&source) >= highest => {
+                    warn!(slot, %source, "Rejecting constraints diverging from a sidecar with higher precedence");
+                    metrics::CONSTRAINTS_SOURCE_OUTCOMES.with_label_values(&["diverged"]).inc();
+                    return Err(Error::Diverged(slot));
+                }
+                Some(_) => {
+                    let cs = cache.get_mut(slot).expect("Slot has constraints");
+                    let (superseded, kept): (Vec<_>, Vec<_>) =
+                        std::mem::take(cs).into_iter().partition(|c| {
+                            c.source.is_some_and(|s| s != source) && c.shares_proposer(&proposers)
+                        });
+                    *cs = kept;
+                    self.forget(slot, &superseded);
+                    for c in &superseded {
+                        let message = c.constraints.message.clone();
+                        self.notify(ConstraintsEvent::Removed { slot, message });
+                    }
+                    let dropped = superseded.len();
+                    metrics::CONSTRAINTS_CACHE_SIZE.sub(dropped as i64);
+
+                    warn!(slot, %source, dropped, "Constraints diverge from a sidecar with lower precedence, replacing them");
+                    metrics::CONSTRAINTS_SOURCE_OUTCOMES.with_label_values(&["superseded"]).inc();
+                    dropped
+                }
+            }
+        };
+
+        self.insert_from(slot, constraints, proposers, Some(source))?;
+        Ok(if superseded > 0 { Submission::Superseded(superseded) } else { Submission::Inserted })
+    }
+
+    fn insert_from(
+        &self,
+        slot: u64,
+        constraints: ConstraintsMessage,
+        proposers: Vec<BlsPublicKey>,
+        source: Option<Address>,
+    ) -> Result<(), Error> {
+        if let Some(index) = constraints.index.filter(|_| !constraints.top) {
+            return Err(Error::IndexWithoutTopOfBlock(index));
+        }
+
+        if let Some(conflict) = self.conflicts_with(&slot, &constraints, &proposers) {
             return Err(conflict.into());
         }
 
-        let message_with_data = ConstraintsWithProofData::try_from(constraints)?;
+        let constraints = ConstraintsWithProofData::try_from(constraints)?;
 
         let mut cache = self.cache.write();
-        if let Some(cs) = cache.get_mut(&slot) {
-            if cs.len() >= MAX_CONSTRAINTS_PER_SLOT {
-                error!("Max constraints per slot reached for slot {}", slot);
-                return Err(Error::LimitReached(slot));
+        let saved = cache.get(slot).map_or(&[][..], |cs| cs.as_slice());
+        let (count, gas, blobs) = saved.iter().filter(|c| c.shares_proposer(&proposers)).fold(
+            (0, constraints.gas_limit, constraints.blob_count),
+            |(count, gas, blobs), c| {
+                (
+                    count + 1,
+                    gas.saturating_add(c.constraints.gas_limit),
+                    blobs.saturating_add(c.constraints.blob_count),
+                )
+            },
+        );
+
+        if count >= MAX_CONSTRAINTS_PER_SLOT {
+            error!("Max constraints per slot reached for slot {}", slot);
+            return Err(Error::LimitReached(slot));
+        }
+
+        if gas > self.block_gas_limit {
+            error!(
+                slot,
+                gas,
+                block_gas_limit = self.block_gas_limit,
+                "Constraints exceed the block gas limit"
+            );
+            return Err(Conflict::GasLimitExceeded(self.block_gas_limit).into());
+        }
+
+        if blobs > self.max_blobs_per_block {
+            error!(
+                slot,
+                blobs,
+                max_blobs = self.max_blobs_per_block,
+                "Constraints exceed the max blobs per block"
+            );
+            return Err(Conflict::BlobLimitExceeded(self.max_blobs_per_block).into());
+        }
+
+        if let Some(max_gas) = self.max_gas_per_slot.filter(|max_gas| gas > *max_gas) {
+            error!(slot, gas, max_gas, "Max constrained gas per slot reached");
+            return Err(Error::GasLimitReached(slot, max_gas));
+        }
+
+        // Persist the constraints before accepting them, so that accepted constraints survive
+        // a restart
+        let id = match self.store.as_ref() {
+            Some(store) => {
+                let stored = StoredConstraints {
+                    proposers: proposers.clone(),
+                    source,
+                    message: constraints.message.clone(),
+                };
+                Some(store.append(slot, &stored)?)
             }
+            None => None,
+        };
 
-            cs.push(message_with_data);
-        } else {
-            cache.insert(slot, vec![message_with_data]);
+        let message = constraints.message.clone();
+        let entry = ProposerConstraints { proposers, source, constraints, id };
+        match cache.get_mut(slot) {
+            Some(cs) => cs.push(entry),
+            None => {
+                cache.insert(slot, vec![entry]);
+            }
         }
 
         metrics::CONSTRAINTS_CACHE_SIZE.inc();
+        self.notify(ConstraintsEvent::Constraints { slot, message });
 
         Ok(())
     }
 
     /// Removes all constraints before the given slot.
     pub fn remove_before(&self, slot: u64) {
-        self.cache.write().retain(|k, _| *k >= slot);
+        let cleared = self.cache.write().advance_to(slot);
+        if let Some(store) = self.store.as_ref() {
+            store.remove_before(slot);
+        }
+        for slot in cleared.into_keys() {
+            self.notify(ConstraintsEvent::SlotCleared { slot });
+        }
         metrics::CONSTRAINTS_CACHE_SIZE.set(self.total_constraints() as i64);
     }
 
-    /// Gets and removes the constraints for the given slot.
-    pub fn remove(&self, slot: u64) -> Option<Vec<ConstraintsWithProofData>> {
-        self.cache.write().remove(&slot).inspect(|c| {
-            metrics::CONSTRAINTS_CACHE_SIZE.sub(c.len() as i64);
+    /// Returns true if there are constraints for the given proposer in the given slot.
+    pub fn contains(&self, slot: u64, proposer: &BlsPublicKey) -> bool {
+        self.cache.read().get(slot).is_some_and(|cs| cs.iter().any(|c| c.binds(proposer)))
+    }
+
+    /// Returns the total gas limit of the transactions constrained for the given proposer
+    /// in the given slot.
+    pub fn constrained_gas(&self, slot: u64, proposer: &BlsPublicKey) -> u64 {
+        self.cache.read().get(slot).map_or(0, |cs| {
+            cs.iter()
+                .filter(|c| c.binds(proposer))
+                .fold(0, |gas, c| gas.saturating_add(c.constraints.gas_limit))
+        })
+    }
+
+    /// Returns the total gas limit of the transactions constrained for the given proposer in
+    /// the given slot by the given signer, e.g. a delegatee.
+    pub fn constrained_gas_by(
+        &self,
+        slot: u64,
+        proposer: &BlsPublicKey,
+        signer: &BlsPublicKey,
+    ) -> u64 {
+        self.cache.read().get(slot).map_or(0, |cs| {
+            cs.iter()
+                .filter(|c| c.binds(proposer) && c.constraints.message.pubkey == *signer)
+                .fold(0, |gas, c| gas.saturating_add(c.constraints.gas_limit))
         })
     }
 
+    /// Gets and removes the constraints for the given proposer in the given slot, keeping
+    /// those of the other proposers.
+    pub fn remove(
+        &self,
+        slot: u64,
+        proposer: &BlsPublicKey,
+    ) -> Option<Vec<ConstraintsWithProofData>> {
+        let mut cache = self.cache.write();
+        let cs = cache.get_mut(slot)?;
+
+        let (removed, kept): (Vec<_>, Vec<_>) =
+            std::mem::take(cs).into_iter().partition(|c| c.binds(proposer));
+        *cs = kept;
+        if cs.is_empty() {
+            cache.remove(slot);
+        }
+
+        if removed.is_empty() {
+            return None;
+        }
+
+        self.forget(slot, &removed);
+        metrics::CONSTRAINTS_CACHE_SIZE.sub(removed.len() as i64);
+        Some(removed.into_iter().map(|c| c.constraints).collect())
+    }
+
+    /// Returns the constraints received for each proposer in every slot from the given one,
+    /// against the caps of the cache, by ascending slot.
+    ///
+    /// The constraints that can't be attributed to a known proposer count against every
+    /// proposer of their slot, like when they are checked for conflicts.
+    pub fn capacity(&self, from_slot: u64) -> Vec<SlotCapacity> {
+        let cache = self.cache.read();
+        let mut capacity = Vec::new();
+
+        for (slot, cs) in cache.iter().filter(|(slot, _)| *slot >= from_slot) {
+            let mut proposers = cs.iter().flat_map(|c| c.proposers.iter()).collect::<Vec<_>>();
+            proposers.sort();
+            proposers.dedup();
+
+            let mut entry = |proposer: Option<&BlsPublicKey>| {
+                let bound = cs.iter().filter(|c| proposer.map_or(true, |p| c.binds(p)));
+                let (constraints, constrained_gas) = bound.fold((0, 0u64), |(count, gas), c| {
+                    (count + 1, gas.saturating_add(c.constraints.gas_limit))
+                });
+
+                capacity.push(SlotCapacity {
+                    slot,
+                    proposer: proposer.copied(),
+                    constraints,
+                    max_constraints: MAX_CONSTRAINTS_PER_SLOT,
+                    constrained_gas,
+                    max_constrained_gas: self.max_gas_per_slot,
+                });
+            };
+
+            if proposers.is_empty() {
+                entry(None);
+            }
+            for proposer in proposers {
+                entry(Some(proposer));
+            }
+        }
+
+        capacity
+    }
+
     fn total_constraints(&self) -> usize {
         self.cache.read().values().map(|v| v.len()).sum()
     }
@@ -111,7 +596,9 @@ impl ConstraintsCache {
 
 #[cfg(test)]
 mod tests {
-    use alloy::{primitives::bytes, rpc::types::beacon::BlsPublicKey};
+    use alloy::primitives::bytes;
+
+    use crate::testutil::{read_test_transactions, TEST_CONTRACT_CREATION_TX, TEST_TRANSFER_TX};
 
     use super::*;
 
@@ -119,24 +606,271 @@ mod tests {
     fn test_constraints_cache_conflict() {
         let cache = ConstraintsCache::new();
 
-        let tx = bytes!("f86481d8088302088a808090435b8080556001015a6161a8106001578718e5bb3abd109fa0ea5ad6553fb67639cec694e6697ac7b718bd7044fcdf5608fa64f6058e67db93a03953b5792d7d9ef7fc602fbe260e7a290760e8adc634f99ab1896e2c0d55afcb");
+        let tx = TEST_CONTRACT_CREATION_TX;
 
         let constraints = ConstraintsMessage {
             pubkey: BlsPublicKey::default(),
             slot: 0,
             top: false,
+            index: None,
             transactions: vec![tx],
         };
 
-        assert!(cache.conflicts_with(&0, &constraints).is_none());
+        assert!(cache.conflicts_with(&0, &constraints, &[]).is_none());
 
-        cache.insert(0, constraints.clone()).unwrap();
+        cache.insert(0, constraints.clone(), Vec::new()).unwrap();
 
         assert!(matches!(
-            cache.conflicts_with(&0, &constraints),
+            cache.conflicts_with(&0, &constraints, &[]),
             Some(Conflict::DuplicateTransaction)
         ));
 
-        assert!(cache.conflicts_with(&1, &constraints).is_none());
+        assert!(cache.conflicts_with(&1, &constraints, &[]).is_none());
+    }
+
+    #[test]
+    fn test_constraints_cache_per_proposer() {
+        let cache = ConstraintsCache::new();
+        let (alice, bob) = (BlsPublicKey::repeat_byte(1), BlsPublicKey::repeat_byte(2));
+
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let constraints = ConstraintsMessage {
+            pubkey: alice,
+            slot: 0,
+            top: true,
+            index: None,
+            transactions: vec![tx],
+        };
+
+        // The constraints of different proposers don't conflict with each other
+        cache.insert(0, constraints.clone(), vec![alice]).unwrap();
+        assert!(cache.conflicts_with(&0, &constraints, &[bob]).is_none());
+        cache.insert(0, constraints.clone(), vec![bob]).unwrap();
+        assert!(cache.conflicts_with(&0, &constraints, &[alice]).is_some());
+
+        // Removing the constraints of a proposer keeps those of the others
+        assert_eq!(cache.remove(0, &alice).map(|cs| cs.len()), Some(1));
+        assert!(!cache.contains(0, &alice));
+        assert!(cache.contains(0, &bob));
+        assert!(cache.constrained_gas(0, &bob) > 0);
+    }
+
+    #[test]
+    fn test_constraints_cache_indexed_bundles() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let other_tx = TEST_TRANSFER_TX;
+        let bundle = |index, transactions| ConstraintsMessage {
+            pubkey: BlsPublicKey::default(),
+            slot: 0,
+            top: true,
+            index,
+            transactions,
+        };
+        let cache = ConstraintsCache::new();
+
+        // The unindexed bundle takes the first position, the next one starts after it
+        cache.insert(0, bundle(None, vec![tx.clone()]), Vec::new()).unwrap();
+        let next = bundle(Some(1), vec![other_tx.clone()]);
+        assert_eq!(next.top_positions(), Some(1..2));
+        cache.insert(0, next, Vec::new()).unwrap();
+
+        // Bundles can't overlap the positions of the others
+        let overlapping = bundle(Some(1), vec![bytes!("01")]);
+        assert!(matches!(cache.conflicts_with(&0, &overlapping, &[]), Some(Conflict::TopOfBlock)));
+        assert!(cache.conflicts_with(&0, &bundle(Some(2), vec![bytes!("01")]), &[]).is_none());
+
+        // Only bundles have an index
+        let constraints = ConstraintsMessage { top: false, ..bundle(Some(3), vec![tx]) };
+        assert!(matches!(
+            cache.insert(1, constraints, Vec::new()),
+            Err(Error::IndexWithoutTopOfBlock(3))
+        ));
+    }
+
+    #[test]
+    fn test_constraints_cache_capacity() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let other_tx = TEST_TRANSFER_TX;
+        let message = |transactions| ConstraintsMessage {
+            pubkey: BlsPublicKey::default(),
+            slot: 0,
+            top: false,
+            index: None,
+            transactions,
+        };
+        let gas_limit =
+            ConstraintsWithProofData::try_from(message(vec![tx.clone()])).unwrap().gas_limit;
+
+        let cache = ConstraintsCache::new().with_max_gas_per_slot(Some(gas_limit));
+        let alice = BlsPublicKey::repeat_byte(1);
+
+        // Unattributed constraints are reported on their own, then against every proposer
+        cache.insert(5, message(vec![tx.clone()]), Vec::new()).unwrap();
+        let capacity = cache.capacity(0);
+        assert_eq!(capacity.len(), 1);
+        assert_eq!(capacity[0].proposer, None);
+        assert_eq!(capacity[0].constraints, 1);
+
+        cache.insert(5, message(Vec::new()), vec![alice]).unwrap();
+        let capacity = cache.capacity(0);
+        assert_eq!(capacity.len(), 1);
+        assert_eq!(capacity[0].proposer, Some(alice));
+        assert_eq!(capacity[0].constraints, 2);
+        assert_eq!(capacity[0].constrained_gas, gas_limit);
+        assert_eq!(capacity[0].max_constrained_gas, Some(gas_limit));
+
+        // The gas cap is enforced per proposer and slot
+        cache.insert(6, message(vec![tx]), vec![alice]).unwrap();
+        assert!(matches!(
+            cache.insert(6, message(vec![other_tx]), vec![alice]),
+            Err(Error::GasLimitReached(6, _))
+        ));
+
+        // Past slots are not reported
+        assert_eq!(cache.capacity(6).len(), 1);
+        assert!(cache.capacity(7).is_empty());
+    }
+
+    #[test]
+    fn test_constraints_cache_block_limits() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let other_tx = TEST_TRANSFER_TX;
+        // The test block holds a single blob transaction, with one blob
+        let (_, txs) = read_test_transactions();
+        let blob_tx = txs.into_iter().find(|tx| tx[0] == 0x03).unwrap();
+        let message = |transactions| ConstraintsMessage {
+            pubkey: BlsPublicKey::default(),
+            slot: 0,
+            top: false,
+            index: None,
+            transactions,
+        };
+        let with_data = ConstraintsWithProofData::try_from(message(vec![blob_tx.clone()])).unwrap();
+        assert_eq!(with_data.blob_count, 1);
+        let gas_limit =
+            ConstraintsWithProofData::try_from(message(vec![tx.clone()])).unwrap().gas_limit;
+
+        // The gas of the constraints of a slot is accumulated against the block gas limit
+        let cache = ConstraintsCache::new().with_block_limits(gas_limit, 0);
+        cache.insert(0, message(vec![tx]), Vec::new()).unwrap();
+        assert!(matches!(
+            cache.insert(0, message(vec![other_tx]), Vec::new()),
+            Err(Error::Conflict(Conflict::GasLimitExceeded(limit))) if limit == gas_limit
+        ));
+
+        // So are the blobs, against the max blobs per block
+        assert!(matches!(
+            cache.insert(1, message(vec![blob_tx.clone()]), Vec::new()),
+            Err(Error::Conflict(Conflict::BlobLimitExceeded(0)))
+        ));
+        let cache = ConstraintsCache::new().with_block_limits(DEFAULT_BLOCK_GAS_LIMIT, 1);
+        cache.insert(1, message(vec![blob_tx]), Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn test_constraints_cache_precedence() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let other_tx = TEST_TRANSFER_TX;
+        let alice = BlsPublicKey::repeat_byte(1);
+        let message = |transactions| ConstraintsMessage {
+            pubkey: alice,
+            slot: 0,
+            top: false,
+            index: None,
+            transactions,
+        };
+
+        let (primary, backup) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let cache = ConstraintsCache::new().with_precedence(vec![primary, backup]);
+
+        // Identical constraints from another sidecar are deduplicated
+        let submission = cache.submit(0, message(vec![tx.clone()]), vec![alice], Some(backup));
+        assert_eq!(submission.unwrap(), Submission::Inserted);
+        let submission = cache.submit(0, message(vec![tx.clone()]), vec![alice], Some(primary));
+        assert_eq!(submission.unwrap(), Submission::Duplicate);
+
+        // Diverging constraints of a sidecar with higher precedence replace the others
+        let submission =
+            cache.submit(0, message(vec![other_tx.clone()]), vec![alice], Some(primary));
+        assert_eq!(submission.unwrap(), Submission::Superseded(1));
+        assert_eq!(cache.remove(0, &alice).map(|cs| cs.len()), Some(1));
+
+        // Diverging constraints of a sidecar with lower precedence are rejected
+        cache.submit(1, message(vec![other_tx]), vec![alice], Some(primary)).unwrap();
+        assert!(matches!(
+            cache.submit(1, message(vec![tx]), vec![alice], Some(backup)),
+            Err(Error::Diverged(1))
+        ));
+    }
+
+    #[test]
+    fn test_constraints_cache_events() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let (primary, backup) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let alice = BlsPublicKey::repeat_byte(1);
+        let message = |slot, transactions| ConstraintsMessage {
+            pubkey: alice,
+            slot,
+            top: false,
+            index: None,
+            transactions,
+        };
+
+        let cache = ConstraintsCache::new().with_precedence(vec![primary, backup]);
+        let mut events = cache.subscribe();
+
+        cache.submit(4, message(4, vec![tx.clone()]), vec![alice], Some(backup)).unwrap();
+        cache.submit(4, message(4, Vec::new()), vec![alice], Some(primary)).unwrap();
+        cache.insert(5, message(5, vec![tx.clone()]), vec![alice]).unwrap();
+        cache.remove_before(5);
+
+        assert_eq!(
+            std::iter::from_fn(|| events.try_recv().ok()).collect::<Vec<_>>(),
+            vec![
+                ConstraintsEvent::Constraints { slot: 4, message: message(4, vec![tx.clone()]) },
+                ConstraintsEvent::Removed { slot: 4, message: message(4, vec![tx.clone()]) },
+                ConstraintsEvent::Constraints { slot: 4, message: message(4, Vec::new()) },
+                ConstraintsEvent::Constraints { slot: 5, message: message(5, vec![tx]) },
+                ConstraintsEvent::SlotCleared { slot: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constraints_cache_recovery() {
+        let tx = TEST_CONTRACT_CREATION_TX;
+        let (alice, bob) = (BlsPublicKey::repeat_byte(1), BlsPublicKey::repeat_byte(2));
+        let message = |slot| ConstraintsMessage {
+            pubkey: alice,
+            slot,
+            top: false,
+            index: None,
+            transactions: vec![tx.clone()],
+        };
+
+        let store = ConstraintsStore::temporary();
+        let cache = ConstraintsCache::new().with_store(store.clone());
+        cache.insert(4, message(4), vec![alice]).unwrap();
+        cache.insert(5, message(5), vec![alice]).unwrap();
+        cache.insert(5, message(5), vec![bob]).unwrap();
+        cache.insert(6, message(6), vec![alice]).unwrap();
+
+        // Constraints served to a builder are not recovered
+        assert!(cache.remove(6, &alice).is_some());
+
+        // Only the constraints from the head slot are recovered after a restart
+        let restarted = ConstraintsCache::new().with_store(store.clone());
+        assert_eq!(restarted.recover(5).unwrap(), 2);
+        assert!(!restarted.contains(4, &alice));
+        assert!(restarted.contains(5, &alice));
+        assert!(restarted.contains(5, &bob));
+        assert!(!restarted.contains(6, &alice));
+        assert!(store.load_from(0).unwrap().iter().all(|(slot, _, _)| *slot == 5));
+
+        // Recovered constraints are still checked for conflicts
+        assert!(matches!(
+            restarted.insert(5, message(5), vec![alice]),
+            Err(Error::Conflict(Conflict::DuplicateTransaction))
+        ));
     }
 }
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::types::ConstraintsMessage;
+
+/// The name of the tree of the accepted constraints in the database.
+const CONSTRAINTS_TREE: &str = "constraints";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("Constraints database error: {0}")]
+    Db(#[from] sled::Error),
+    #[error("Invalid stored constraints: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Accepted constraints, as persisted along with the proposers they bind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredConstraints {
+    pub proposers: Vec<BlsPublicKey>,
+    pub source: Option<Address>,
+    pub message: ConstraintsMessage,
+}
+
+/// A write-ahead log of the constraints accepted by the [ConstraintsCache], so that a restart
+/// in the middle of a slot doesn't lose them.
+///
+/// Constraints are keyed by slot then by a monotonic id, so that the constraints of past slots
+/// can be pruned with a single range scan. Clones share the same database.
+///
+/// [ConstraintsCache]: crate::constraints::ConstraintsCache
+#[derive(Debug, Clone)]
+pub struct ConstraintsStore {
+    db: sled::Db,
+    tree: sled::Tree,
+}
+
+impl ConstraintsStore {
+    /// Opens the database at the given path, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::from_db(sled::open(path)?)
+    }
+
+    fn from_db(db: sled::Db) -> Result<Self, StoreError> {
+        let tree = db.open_tree(CONSTRAINTS_TREE)?;
+        Ok(Self { db, tree })
+    }
+
+    /// Appends the given constraints of the slot, returning their id once they are durably
+    /// written to disk.
+    pub fn append(&self, slot: u64, constraints: &StoredConstraints) -> Result<u64, StoreError> {
+        let id = self.db.generate_id()?;
+        self.tree.insert(key(slot, id), serde_json::to_vec(constraints)?)?;
+        self.tree.flush()?;
+        Ok(id)
+    }
+
+    /// Deletes the constraints with the given id. They are only deleted from disk at the next
+    /// periodic flush: if bolt-boost stops before, they are recovered again.
+    pub fn delete(&self, slot: u64, id: u64) {
+        if let Err(err) = self.tree.remove(key(slot, id)) {
+            warn!(slot, id, ?err, "Failed to delete constraints from the database");
+        }
+    }
+
+    /// Deletes the constraints of every slot before the given one.
+    pub fn remove_before(&self, slot: u64) {
+        let mut batch = sled::Batch::default();
+        for entry in self.tree.range(..key(slot, 0)) {
+            match entry {
+                Ok((key, _)) => batch.remove(key),
+                Err(err) => {
+                    warn!(slot, ?err, "Failed to prune constraints from the database");
+                    return;
+                }
+            }
+        }
+
+        if let Err(err) = self.tree.apply_batch(batch) {
+            warn!(slot, ?err, "Failed to prune constraints from the database");
+        }
+    }
+
+    /// Loads the constraints of every slot from the given one, with their slot and id, by
+    /// ascending slot and in the order they were accepted.
+    pub fn load_from(&self, slot: u64) -> Result<Vec<(u64, u64, StoredConstraints)>, StoreError> {
+        let mut constraints = Vec::new();
+        for entry in self.tree.range(key(slot, 0)..) {
+            let (key, value) = entry?;
+            let (slot, id) = parse_key(&key);
+            constraints.push((slot, id, serde_json::from_slice(&value)?));
+        }
+        Ok(constraints)
+    }
+}
+
+/// Returns the key of the constraints with the given id, ordered by slot then by id.
+fn key(slot: u64, id: u64) -> [u8; 16] {
+    let mut key = [0; 16];
+    key[..8].copy_from_slice(&slot.to_be_bytes());
+    key[8..].copy_from_slice(&id.to_be_bytes());
+    key
+}
+
+fn parse_key(key: &[u8]) -> (u64, u64) {
+    let mut slot = [0; 8];
+    let mut id = [0; 8];
+    slot.copy_from_slice(&key[..8]);
+    id.copy_from_slice(&key[8..16]);
+    (u64::from_be_bytes(slot), u64::from_be_bytes(id))
+}
+
+#[cfg(test)]
+impl ConstraintsStore {
+    /// Opens a database deleted when its last handle is dropped.
+    pub fn temporary() -> Self {
+        Self::from_db(sled::Config::new().temporary(true).open().unwrap()).unwrap()
+    }
+}
@@ -1,5 +1,13 @@
 use axum::{http::StatusCode, response::IntoResponse};
 
+use crate::{
+    auth::AuthError,
+    codes::ErrorCode,
+    constraints::{Conflict, Error as ConstraintsError},
+    streaming::JsonStreamError,
+    types::{ScopeViolation, SignatureError},
+};
+
 #[derive(Debug)]
 /// Errors that the PbsService returns to client
 pub enum PbsClientError {
@@ -7,6 +15,15 @@ pub enum PbsClientError {
     #[allow(unused)]
     NoPayload,
     BadRequest,
+    PayloadTooLarge,
+    /// A submitted message is not signed for the configured chain by its signer.
+    InvalidSignature(SignatureError),
+    /// A submission is not signed by an authorized sidecar.
+    Unauthorized(AuthError),
+    /// Submitted constraints can't be added to the constraints of their slot.
+    Constraints(ConstraintsError),
+    /// Submitted constraints are beyond the scope of the delegation to their signer.
+    OutOfScope(ScopeViolation),
 }
 
 impl PbsClientError {
@@ -15,18 +32,91 @@ impl PbsClientError {
             PbsClientError::NoResponse => StatusCode::SERVICE_UNAVAILABLE,
             PbsClientError::NoPayload => StatusCode::BAD_GATEWAY,
             PbsClientError::BadRequest => StatusCode::BAD_REQUEST,
+            PbsClientError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            PbsClientError::InvalidSignature(_) => StatusCode::BAD_REQUEST,
+            PbsClientError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            PbsClientError::Constraints(ConstraintsError::Persistence(_)) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            PbsClientError::Constraints(_) => StatusCode::BAD_REQUEST,
+            PbsClientError::OutOfScope(_) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    /// Returns the stable error code of the error, shared with the other bolt components.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            PbsClientError::NoResponse | PbsClientError::NoPayload => {
+                ErrorCode::UpstreamUnavailable
+            }
+            PbsClientError::BadRequest => ErrorCode::ValidationFailed,
+            PbsClientError::PayloadTooLarge => ErrorCode::PayloadTooLarge,
+            PbsClientError::InvalidSignature(SignatureError::WrongChain { .. }) => {
+                ErrorCode::WrongChain
+            }
+            PbsClientError::InvalidSignature(SignatureError::Digest(_)) => {
+                ErrorCode::InvalidRequest
+            }
+            PbsClientError::InvalidSignature(SignatureError::Invalid) => {
+                ErrorCode::InvalidSignature
+            }
+            PbsClientError::Unauthorized(AuthError::MissingHeader(_)) => {
+                ErrorCode::MissingSignature
+            }
+            PbsClientError::Unauthorized(AuthError::MalformedHeader(_)) => {
+                ErrorCode::MalformedHeader
+            }
+            PbsClientError::Unauthorized(_) => ErrorCode::Unauthorized,
+            PbsClientError::Constraints(ConstraintsError::LimitReached(_)) |
+            PbsClientError::Constraints(ConstraintsError::GasLimitReached(_, _)) |
+            PbsClientError::Constraints(ConstraintsError::Conflict(
+                Conflict::TopOfBlock |
+                Conflict::GasLimitExceeded(_) |
+                Conflict::BlobLimitExceeded(_),
+            )) => ErrorCode::SlotFull,
+            PbsClientError::Constraints(ConstraintsError::Conflict(
+                Conflict::DuplicateTransaction,
+            )) => ErrorCode::Duplicate,
+            PbsClientError::Constraints(ConstraintsError::Decode(_)) |
+            PbsClientError::Constraints(ConstraintsError::IndexWithoutTopOfBlock(_)) => {
+                ErrorCode::InvalidRequest
+            }
+            PbsClientError::Constraints(ConstraintsError::Diverged(_)) => {
+                ErrorCode::ValidationFailed
+            }
+            PbsClientError::Constraints(ConstraintsError::Persistence(_)) => ErrorCode::Internal,
+            PbsClientError::OutOfScope(ScopeViolation::GasLimitReached(_)) => ErrorCode::SlotFull,
+            PbsClientError::OutOfScope(_) => ErrorCode::Unauthorized,
         }
     }
 }
 
 impl IntoResponse for PbsClientError {
     fn into_response(self) -> axum::response::Response {
+        let status = self.status_code();
+        let code = self.error_code();
         let msg = match self {
-            PbsClientError::NoResponse => "no response from relays",
-            PbsClientError::NoPayload => "no payload from relays",
-            PbsClientError::BadRequest => "bad request",
+            PbsClientError::NoResponse => "no response from relays".to_string(),
+            PbsClientError::NoPayload => "no payload from relays".to_string(),
+            PbsClientError::BadRequest => "bad request".to_string(),
+            PbsClientError::PayloadTooLarge => "payload too large".to_string(),
+            PbsClientError::InvalidSignature(err) => err.to_string(),
+            PbsClientError::Unauthorized(err) => err.to_string(),
+            PbsClientError::Constraints(err) => err.to_string(),
+            PbsClientError::OutOfScope(err) => err.to_string(),
         };
 
-        (self.status_code(), msg).into_response()
+        (status, code.header(), msg).into_response()
+    }
+}
+
+impl From<JsonStreamError> for PbsClientError {
+    fn from(err: JsonStreamError) -> Self {
+        match err {
+            JsonStreamError::TooLarge(_) | JsonStreamError::TooManyItems(_) => {
+                PbsClientError::PayloadTooLarge
+            }
+            _ => PbsClientError::BadRequest,
+        }
     }
 }
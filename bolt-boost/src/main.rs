@@ -4,17 +4,31 @@ use tracing_subscriber::EnvFilter;
 use cb_common::config::load_pbs_custom_config;
 use cb_pbs::{PbsService, PbsState};
 
+mod auth;
+mod bids;
+mod capabilities;
+mod codes;
 mod constraints;
+mod constraints_store;
 mod error;
 mod metrics;
+mod payloads;
+mod payments;
 mod proofs;
+mod proposers;
+mod relay_checks;
+mod reports;
 mod server;
+mod slot_scoped;
+mod streaming;
+mod tx_refs;
 mod types;
 
 #[cfg(test)]
 mod testutil;
 
 use crate::{
+    relay_checks::{check_relays, RelayCheckMode},
     server::{BuilderState, ConstraintsApi},
     types::Config,
 };
@@ -31,7 +45,11 @@ async fn main() -> Result<()> {
         tracing::info!("ID: {} - URI: {}", relay.id, relay.config.entry.url);
     }
 
-    let custom_state = BuilderState::from_config(extra);
+    if extra.relay_checks != RelayCheckMode::Disabled {
+        check_relays(&pbs_config.relays, chain).await.enforce(extra.relay_checks)?;
+    }
+
+    let custom_state = BuilderState::from_config(extra, chain)?;
     let state = PbsState::new(pbs_config).with_data(custom_state);
 
     metrics::init_metrics()?;
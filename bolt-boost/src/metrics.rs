@@ -1,13 +1,15 @@
 use lazy_static::lazy_static;
 use prometheus::{
     register_histogram_vec_with_registry, register_int_counter_vec_with_registry,
-    register_int_gauge_with_registry, HistogramVec, IntCounterVec, IntGauge, Registry,
+    register_int_counter_with_registry, register_int_gauge_with_registry, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, Registry,
 };
 
 use cb_pbs::PbsService;
 
 pub(crate) const TIMEOUT_ERROR_CODE_STR: &str = "555";
 pub(crate) const GET_HEADER_WP_TAG: &str = "get_header_with_proofs";
+pub(crate) const GET_HEADER_TAG: &str = "get_header";
 
 pub(crate) fn init_metrics() -> eyre::Result<()> {
     // Initialize metrics
@@ -15,6 +17,14 @@ pub(crate) fn init_metrics() -> eyre::Result<()> {
     PbsService::register_metric(Box::new(RELAY_STATUS_CODE.clone()));
     PbsService::register_metric(Box::new(RELAY_INVALID_BIDS.clone()));
     PbsService::register_metric(Box::new(CONSTRAINTS_CACHE_SIZE.clone()));
+    PbsService::register_metric(Box::new(PROOF_VERIFICATION_LATENCY.clone()));
+    PbsService::register_metric(Box::new(KNOWN_PROPOSERS.clone()));
+    PbsService::register_metric(Box::new(GET_HEADER_FALLBACKS.clone()));
+    PbsService::register_metric(Box::new(RELAY_PAYMENT_DISCREPANCIES.clone()));
+    PbsService::register_metric(Box::new(UNAUTHORIZED_REQUESTS.clone()));
+    PbsService::register_metric(Box::new(HASH_REF_FALLBACKS.clone()));
+    PbsService::register_metric(Box::new(CONSTRAINTS_SOURCE_OUTCOMES.clone()));
+    PbsService::register_metric(Box::new(PAYLOAD_CACHE_HITS.clone()));
 
     PbsService::init_metrics()
 }
@@ -49,6 +59,15 @@ lazy_static! {
     )
     .unwrap();
 
+    /// Latency of inclusion proof verification by relay
+    pub static ref PROOF_VERIFICATION_LATENCY: HistogramVec = register_histogram_vec_with_registry!(
+        "proof_verification_latency",
+        "Inclusion proof verification latency by relay",
+        &["relay_id"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
     /// Invalid bids per relay
     pub static ref RELAY_INVALID_BIDS: IntCounterVec = register_int_counter_vec_with_registry!(
         "relay_invalid_bids",
@@ -58,4 +77,64 @@ lazy_static! {
     )
     .unwrap();
 
+    /// The number of proposers that registered or delegated through bolt-boost
+    pub static ref KNOWN_PROPOSERS: IntGauge = register_int_gauge_with_registry!(
+        "known_proposers",
+        "number of proposers that registered or delegated through bolt-boost",
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Header requests served with plain mev-boost behavior, by endpoint
+    pub static ref GET_HEADER_FALLBACKS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "get_header_fallbacks",
+        "Header requests served without constraints by endpoint",
+        &["endpoint"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Payloads whose payment to the proposer doesn't match the bid, per relay
+    pub static ref RELAY_PAYMENT_DISCREPANCIES: IntCounterVec = register_int_counter_vec_with_registry!(
+        "relay_payment_discrepancies",
+        "Payloads paying the proposer less than the bid value per relay",
+        &["relay_id"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Submissions rejected for not being signed by an authorized sidecar, by endpoint
+    pub static ref UNAUTHORIZED_REQUESTS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "unauthorized_requests",
+        "Submissions not signed by an authorized sidecar by endpoint",
+        &["endpoint"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Constraints submissions referencing transactions by hash re-sent in full, per relay
+    pub static ref HASH_REF_FALLBACKS: IntCounterVec = register_int_counter_vec_with_registry!(
+        "constraints_hash_ref_fallbacks",
+        "Constraints submissions by hash rejected and re-sent in full per relay",
+        &["relay_id"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Constraints reconciled with those of another sidecar for the same proposers, by outcome
+    pub static ref CONSTRAINTS_SOURCE_OUTCOMES: IntCounterVec = register_int_counter_vec_with_registry!(
+        "constraints_source_outcomes",
+        "Constraints reconciled with those of another sidecar by outcome",
+        &["outcome"],
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
+
+    /// Retried blinded block submissions answered with a cached payload
+    pub static ref PAYLOAD_CACHE_HITS: IntCounter = register_int_counter_with_registry!(
+        "payload_cache_hits",
+        "Blinded block submissions answered from the payload cache",
+        BOLT_BOOST_METRICS
+    )
+    .unwrap();
 }
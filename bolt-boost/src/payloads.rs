@@ -0,0 +1,80 @@
+use alloy::primitives::B256;
+use cb_common::pbs::SubmitBlindedBlockResponse;
+use parking_lot::RwLock;
+use std::{collections::HashMap, sync::Arc};
+
+use crate::slot_scoped::SlotScoped;
+
+/// A concurrent cache of the payloads returned by the relays, by slot and block hash.
+///
+/// Beacon nodes retry `submitBlindedBlock` when it times out, often once the relay already
+/// revealed the payload. The retries are answered from the cache instead of the relays, which
+/// may not serve the same payload twice. Payloads are dropped once their slot has passed, like
+/// the constraints they were built against.
+#[derive(Clone, Debug)]
+pub struct PayloadCache<P = SubmitBlindedBlockResponse> {
+    payloads: Arc<RwLock<SlotScoped<HashMap<B256, P>>>>,
+}
+
+impl<P> Default for PayloadCache<P> {
+    fn default() -> Self {
+        Self { payloads: Default::default() }
+    }
+}
+
+impl<P: Clone> PayloadCache<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches the payload of the given block, dropping the payloads of the previous slots.
+    pub fn insert(&self, slot: u64, block_hash: B256, payload: P) {
+        let mut payloads = self.payloads.write();
+        payloads.advance_to(slot);
+        match payloads.get_mut(slot) {
+            Some(slot_payloads) => {
+                slot_payloads.insert(block_hash, payload);
+            }
+            None => {
+                payloads.insert(slot, HashMap::from([(block_hash, payload)]));
+            }
+        }
+    }
+
+    /// Returns the cached payload of the given block, if any.
+    pub fn get(&self, slot: u64, block_hash: &B256) -> Option<P> {
+        self.payloads.read().get(slot)?.get(block_hash).cloned()
+    }
+
+    /// Removes all the payloads before the given slot.
+    pub fn remove_before(&self, slot: u64) {
+        self.payloads.write().advance_to(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_cache() {
+        let cache = PayloadCache::<u64>::new();
+        let (hash, other) = (B256::repeat_byte(1), B256::repeat_byte(2));
+
+        cache.insert(10, hash, 1);
+        cache.insert(10, other, 2);
+        assert_eq!(cache.get(10, &hash), Some(1));
+        assert_eq!(cache.get(10, &other), Some(2));
+
+        // Payloads are only served for their own slot
+        assert_eq!(cache.get(11, &hash), None);
+
+        // Caching a payload of a later slot drops the previous ones
+        cache.insert(11, hash, 3);
+        assert_eq!(cache.get(10, &hash), None);
+        assert_eq!(cache.get(11, &hash), Some(3));
+
+        cache.remove_before(12);
+        assert_eq!(cache.get(11, &hash), None);
+    }
+}
@@ -0,0 +1,228 @@
+use alloy::{
+    consensus::{Transaction, TxEnvelope},
+    eips::eip2718::{Decodable2718, Eip2718Error},
+    primitives::{Address, B256, U256},
+};
+use parking_lot::RwLock;
+use std::{collections::HashMap, ops::Deref, sync::Arc};
+
+/// The relay id recorded for bids whose relay is not known, e.g. the ones
+/// signed by a key of none of the configured relays.
+pub(crate) const UNKNOWN_RELAY: &str = "unknown";
+
+/// A bid returned to a proposer, to verify the payment of its payload against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidRecord {
+    pub slot: u64,
+    /// The relay that served the bid.
+    pub relay_id: String,
+    /// The value of the bid, in wei.
+    pub value: U256,
+    /// The fee recipient the proposer registered through bolt-boost, if any.
+    pub fee_recipient: Option<Address>,
+}
+
+/// A concurrent cache of the bids returned to proposers, by block hash.
+#[derive(Clone, Default, Debug)]
+pub struct BidCache {
+    bids: Arc<RwLock<HashMap<B256, BidRecord>>>,
+}
+
+impl BidCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the bid returned to the proposer for the given block hash.
+    pub fn insert(&self, block_hash: B256, bid: BidRecord) {
+        self.bids.write().insert(block_hash, bid);
+    }
+
+    /// Gets and removes the bid for the given block hash.
+    pub fn remove(&self, block_hash: &B256) -> Option<BidRecord> {
+        self.bids.write().remove(block_hash)
+    }
+
+    /// Removes all bids before the given slot.
+    pub fn remove_before(&self, slot: u64) {
+        self.bids.write().retain(|_, bid| bid.slot >= slot);
+    }
+}
+
+/// The relays that overstated a bid, i.e. delivered a payload paying the proposer less than
+/// the bid value, with the slot until which their bids are skipped.
+///
+/// The value of a bid can only be checked against the payment of its payload, which is only
+/// revealed once the proposer signed the header. Relays caught overstating a bid have their
+/// bids skipped at `get_header` time for a while, so that proposers aren't misled again.
+#[derive(Clone, Default, Debug)]
+pub struct RelayPenalties {
+    until: Arc<RwLock<HashMap<String, u64>>>,
+}
+
+impl RelayPenalties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips the bids of the relay up to the given slot, included.
+    pub fn penalize(&self, relay_id: &str, until_slot: u64) {
+        let mut until = self.until.write();
+        let slot = until.entry(relay_id.to_string()).or_default();
+        *slot = until_slot.max(*slot);
+    }
+
+    /// Returns true if the bids of the relay are skipped in the given slot.
+    pub fn is_penalized(&self, relay_id: &str, slot: u64) -> bool {
+        self.until.read().get(relay_id).is_some_and(|until| slot <= *until)
+    }
+
+    /// Removes the penalties that ended before the given slot.
+    pub fn remove_before(&self, slot: u64) {
+        self.until.write().retain(|_, until| *until >= slot);
+    }
+}
+
+/// A discrepancy between a bid and the payment of its payload.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PaymentDiscrepancy {
+    #[error("Payload has no transactions to pay the proposer")]
+    MissingPayment,
+    #[error("Payment sent to {found:?} instead of the fee recipient {expected}")]
+    WrongRecipient { expected: Address, found: Option<Address> },
+    #[error("Payment of {paid} wei is lower than the bid value of {value} wei")]
+    Underpaid { value: U256, paid: U256 },
+    #[error("Failed to decode the payment transaction: {0}")]
+    Decode(String),
+}
+
+impl From<Eip2718Error> for PaymentDiscrepancy {
+    fn from(err: Eip2718Error) -> Self {
+        Self::Decode(err.to_string())
+    }
+}
+
+/// Verifies that the payload pays the proposer the value of its bid, within a tolerance
+/// in basis points of the bid value.
+///
+/// Builders either set the fee recipient of the proposer as the fee recipient of the
+/// payload, in which case the proposer is paid directly, or pay it with the last
+/// transaction of the payload. Payments can only be verified for proposers whose fee
+/// recipient is known to bolt-boost.
+pub fn verify_payment<T: Deref<Target = [u8]>>(
+    bid: &BidRecord,
+    payload_fee_recipient: Address,
+    transactions: &[T],
+    tolerance_bps: u64,
+) -> Result<(), PaymentDiscrepancy> {
+    let Some(expected) = bid.fee_recipient else { return Ok(()) };
+
+    if payload_fee_recipient == expected {
+        return Ok(());
+    }
+
+    let raw_tx = transactions.last().ok_or(PaymentDiscrepancy::MissingPayment)?;
+    let tx = TxEnvelope::decode_2718(&mut raw_tx.deref())?;
+
+    let recipient = tx.to().to().copied();
+    if recipient != Some(expected) {
+        return Err(PaymentDiscrepancy::WrongRecipient { expected, found: recipient });
+    }
+
+    let tolerance = bid.value * U256::from(tolerance_bps) / U256::from(10_000);
+    let paid = tx.value();
+    if paid < bid.value.saturating_sub(tolerance) {
+        return Err(PaymentDiscrepancy::Underpaid { value: bid.value, paid });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559},
+        eips::eip2718::Encodable2718,
+        primitives::{Bytes, Signature, TxKind},
+    };
+
+    use super::*;
+
+    fn payment(to: Address, value: u64) -> Bytes {
+        let tx = TxEip1559 { to: TxKind::Call(to), value: U256::from(value), ..Default::default() };
+        let envelope = TxEnvelope::from(tx.into_signed(Signature::test_signature()));
+        envelope.encoded_2718().into()
+    }
+
+    #[test]
+    fn test_verify_payment() {
+        let (proposer, builder, other) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let bid = BidRecord {
+            slot: 1,
+            relay_id: "relay".to_string(),
+            value: U256::from(10_000),
+            fee_recipient: Some(proposer),
+        };
+
+        // Paid directly through the fee recipient of the payload
+        assert!(verify_payment::<Bytes>(&bid, proposer, &[], 0).is_ok());
+
+        assert!(verify_payment(&bid, builder, &[payment(proposer, 10_000)], 0).is_ok());
+        assert_eq!(
+            verify_payment::<Bytes>(&bid, builder, &[], 0),
+            Err(PaymentDiscrepancy::MissingPayment)
+        );
+        assert_eq!(
+            verify_payment(&bid, builder, &[payment(other, 10_000)], 0),
+            Err(PaymentDiscrepancy::WrongRecipient { expected: proposer, found: Some(other) })
+        );
+
+        let underpaid = [payment(proposer, 9_990)];
+        assert_eq!(
+            verify_payment(&bid, builder, &underpaid, 0),
+            Err(PaymentDiscrepancy::Underpaid { value: bid.value, paid: U256::from(9_990) })
+        );
+        assert!(verify_payment(&bid, builder, &underpaid, 10).is_ok());
+
+        // Payments to unknown fee recipients can't be verified
+        let unknown = BidRecord { fee_recipient: None, ..bid };
+        assert!(verify_payment(&unknown, builder, &underpaid, 0).is_ok());
+    }
+
+    #[test]
+    fn test_relay_penalties() {
+        let penalties = RelayPenalties::new();
+        assert!(!penalties.is_penalized("relay", 10));
+
+        penalties.penalize("relay", 42);
+        // A shorter penalty doesn't shorten the current one
+        penalties.penalize("relay", 20);
+        assert!(penalties.is_penalized("relay", 10));
+        assert!(penalties.is_penalized("relay", 42));
+        assert!(!penalties.is_penalized("relay", 43));
+        assert!(!penalties.is_penalized("other", 10));
+
+        penalties.remove_before(43);
+        assert!(!penalties.is_penalized("relay", 10));
+    }
+
+    #[test]
+    fn test_bid_cache() {
+        let cache = BidCache::new();
+        let bid = |slot| BidRecord {
+            slot,
+            relay_id: UNKNOWN_RELAY.to_string(),
+            value: U256::ZERO,
+            fee_recipient: None,
+        };
+
+        cache.insert(B256::repeat_byte(1), bid(1));
+        cache.insert(B256::repeat_byte(2), bid(2));
+        cache.remove_before(2);
+
+        assert!(cache.remove(&B256::repeat_byte(1)).is_none());
+        assert_eq!(cache.remove(&B256::repeat_byte(2)), Some(bid(2)));
+        assert!(cache.remove(&B256::repeat_byte(2)).is_none());
+    }
+}
@@ -10,17 +10,34 @@ pub enum ProofError {
     LeavesMismatch,
     #[error("Hash not found in constraints cache: {0:?}")]
     MissingHash(TxHash),
+    #[error("Transaction {hash:?} of a top-of-block bundle not proven at position {expected}")]
+    WrongPosition { hash: TxHash, expected: u64 },
     #[error("Proof verification failed")]
     VerificationFailed,
 }
 
+/// The generalized index of the first transaction in the transactions tree of a payload: the
+/// list of up to 2^20 transactions is the left child of its length mix-in.
+const FIRST_TRANSACTION_GINDEX: usize = 1 << 21;
+
+/// Returns the position in the block of the transaction at the given generalized index, if
+/// it is the index of a transaction.
+fn transaction_position(gindex: usize) -> Option<u64> {
+    gindex
+        .checked_sub(FIRST_TRANSACTION_GINDEX)
+        .filter(|position| *position < (1 << 20))
+        .map(|position| position as u64)
+}
+
 /// Returns the length of the leaves that need to be proven (i.e. all transactions).
 fn total_leaves(constraints: &[ConstraintsWithProofData]) -> usize {
     constraints.iter().map(|c| c.proof_data.len()).sum()
 }
 
 /// Verifies the provided multiproofs against the constraints & transactions root.
-/// TODO: support bundle proof verification a.k.a. relative ordering!
+///
+/// The transactions of top-of-block bundles must be proven at the positions of the bundle, in
+/// order. The relative ordering of the other constraints is not verified.
 pub fn verify_multiproofs(
     constraints: &[ConstraintsWithProofData],
     proofs: &InclusionProofs,
@@ -43,11 +60,18 @@ pub fn verify_multiproofs(
 
     // NOTE: Get the leaves from the constraints cache by matching the saved hashes. We need the
     // leaves in order to verify the multiproof.
-    for hash in &proofs.transaction_hashes {
+    for (hash, gindex) in proofs.transaction_hashes.iter().zip(&proofs.generalized_indeces) {
         let mut found = false;
         for constraint in constraints {
-            for (saved_hash, leaf) in &constraint.proof_data {
+            for (offset, (saved_hash, leaf)) in constraint.proof_data.iter().enumerate() {
                 if saved_hash == hash {
+                    if let Some(positions) = constraint.message.top_positions() {
+                        let expected = positions.start.saturating_add(offset as u64);
+                        if transaction_position(*gindex) != Some(expected) {
+                            return Err(ProofError::WrongPosition { hash: *hash, expected });
+                        }
+                    }
+
                     found = true;
                     leaves.push(B256::from(leaf.0));
                     break;
@@ -81,10 +105,13 @@ mod tests {
     use alloy::{
         hex::FromHex,
         primitives::{Bytes, B256},
+        rpc::types::beacon::BlsPublicKey,
     };
     use ssz_rs::{HashTreeRoot, List, PathElement, Prove};
 
-    use crate::testutil::*;
+    use crate::{testutil::*, types::ConstraintsMessage};
+
+    use super::*;
 
     /// NOTE: This test is disabled because multiproof support has not landed in ssz-rs main yet.
     // #[test]
@@ -170,6 +197,46 @@ mod tests {
         // assert!(verify_multiproofs(&[c1_with_data], proofs, root).is_ok());
     }
 
+    #[test]
+    fn test_top_of_block_positions() {
+        let (root, transactions) = read_test_transactions();
+        let transactions_list =
+            transactions_to_ssz_list::<1073741824, 1048576>(transactions.clone());
+
+        // The block places a bundle of a single transaction at position 1
+        let position = 1;
+        let (proof, witness) = transactions_list.prove(&path_from_indeces(&[position])).unwrap();
+        assert_eq!(root, witness);
+
+        let bundle = |index| {
+            let message = ConstraintsMessage {
+                pubkey: BlsPublicKey::default(),
+                slot: 0,
+                top: true,
+                index,
+                transactions: vec![transactions[position].clone()],
+            };
+            ConstraintsWithProofData::try_from(message).unwrap()
+        };
+        let proofs = InclusionProofs {
+            transaction_hashes: vec![bundle(None).proof_data[0].0],
+            generalized_indeces: vec![proof.index],
+            merkle_hashes: proof.branch,
+        };
+
+        assert!(verify_multiproofs(&[bundle(Some(1))], &proofs, root).is_ok());
+
+        // Bundles expected at another position are rejected, even with a valid proof
+        for index in [None, Some(0), Some(2)] {
+            let err = verify_multiproofs(&[bundle(index)], &proofs, root).unwrap_err();
+            let expected_position = index.unwrap_or_default();
+            assert!(matches!(
+                err,
+                ProofError::WrongPosition { expected, .. } if expected == expected_position
+            ));
+        }
+    }
+
     #[test]
     /// Testdata from https://github.com/ferranbt/fastssz/blob/455b54c08c81c3a270b6a7160f92ce68408491d4/tests/codetrie_test.go#L195
     fn test_fastssz_multiproof() {
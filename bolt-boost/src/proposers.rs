@@ -0,0 +1,165 @@
+use alloy::{primitives::Address, rpc::types::beacon::BlsPublicKey};
+use parking_lot::RwLock;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    metrics,
+    types::{DelegationScope, ScopeViolation},
+};
+
+/// The proposers known to bolt-boost, either because they registered through it
+/// or because they delegated their constraint signing rights through it.
+///
+/// Headers are only requested with constraints for known proposers, the others
+/// get plain mev-boost behavior so that mixed fleets can share one bolt-boost instance.
+#[derive(Clone, Default, Debug)]
+pub struct ProposerRegistry {
+    inner: Arc<RwLock<Proposers>>,
+}
+
+#[derive(Default, Debug)]
+struct Proposers {
+    /// Validators that registered through bolt-boost.
+    registered: HashSet<BlsPublicKey>,
+    /// The fee recipients of the validators that registered through bolt-boost.
+    fee_recipients: HashMap<BlsPublicKey, Address>,
+    /// Validators that delegated through bolt-boost, with their delegatees and the scopes
+    /// of their delegations.
+    delegated: HashMap<BlsPublicKey, HashMap<BlsPublicKey, DelegationScope>>,
+}
+
+impl ProposerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the validators that registered through bolt-boost, with their fee recipients.
+    pub fn register(&self, registrations: impl IntoIterator<Item = (BlsPublicKey, Address)>) {
+        let mut inner = self.inner.write();
+        for (pubkey, fee_recipient) in registrations {
+            inner.registered.insert(pubkey);
+            inner.fee_recipients.insert(pubkey, fee_recipient);
+        }
+        metrics::KNOWN_PROPOSERS.set(inner.len() as i64);
+    }
+
+    /// Records a delegation from the validator to the delegatee, replacing the scope of a
+    /// previous one.
+    pub fn delegate(
+        &self,
+        validator: BlsPublicKey,
+        delegatee: BlsPublicKey,
+        scope: DelegationScope,
+    ) {
+        let mut inner = self.inner.write();
+        inner.delegated.entry(validator).or_default().insert(delegatee, scope);
+        metrics::KNOWN_PROPOSERS.set(inner.len() as i64);
+    }
+
+    /// Removes a delegation from the validator to the delegatee. The validator is no
+    /// longer known through delegations once all of them are revoked.
+    pub fn revoke(&self, validator: &BlsPublicKey, delegatee: &BlsPublicKey) {
+        let mut inner = self.inner.write();
+        if let Some(delegatees) = inner.delegated.get_mut(validator) {
+            delegatees.remove(delegatee);
+            if delegatees.is_empty() {
+                inner.delegated.remove(validator);
+            }
+        }
+        metrics::KNOWN_PROPOSERS.set(inner.len() as i64);
+    }
+
+    /// Returns true if the proposer registered or delegated through bolt-boost.
+    pub fn is_known(&self, pubkey: &BlsPublicKey) -> bool {
+        let inner = self.inner.read();
+        inner.registered.contains(pubkey) || inner.delegated.contains_key(pubkey)
+    }
+
+    /// Returns the known proposers the given key signs constraints for: the key itself if it
+    /// is a known proposer, and the validators that delegated to it through bolt-boost within
+    /// the scope of their delegation, as checked by `within_scope`.
+    ///
+    /// Fails if the key only signs for validators whose delegations don't cover the
+    /// constraints, since constraints of unknown signers bind every proposer.
+    pub fn proposers_of(
+        &self,
+        signer: &BlsPublicKey,
+        within_scope: impl Fn(&BlsPublicKey, &DelegationScope) -> Result<(), ScopeViolation>,
+    ) -> Result<Vec<BlsPublicKey>, ScopeViolation> {
+        let inner = self.inner.read();
+
+        let mut proposers = Vec::new();
+        let mut violation = None;
+        for (validator, delegatees) in &inner.delegated {
+            let Some(scope) = delegatees.get(signer) else { continue };
+            match within_scope(validator, scope) {
+                Ok(()) => proposers.push(*validator),
+                Err(err) => violation = Some(err),
+            }
+        }
+        if inner.registered.contains(signer) || inner.delegated.contains_key(signer) {
+            proposers.push(*signer);
+        }
+
+        match violation {
+            Some(err) if proposers.is_empty() => Err(err),
+            _ => Ok(proposers),
+        }
+    }
+
+    /// Returns the fee recipient the proposer registered through bolt-boost, if any.
+    pub fn fee_recipient(&self, pubkey: &BlsPublicKey) -> Option<Address> {
+        self.inner.read().fee_recipients.get(pubkey).copied()
+    }
+}
+
+impl Proposers {
+    fn len(&self) -> usize {
+        self.registered.len() +
+            self.delegated.keys().filter(|pubkey| !self.registered.contains(*pubkey)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_proposers() {
+        let registry = ProposerRegistry::new();
+        let (registered, validator, delegatee) = (
+            BlsPublicKey::repeat_byte(1),
+            BlsPublicKey::repeat_byte(2),
+            BlsPublicKey::repeat_byte(3),
+        );
+
+        assert!(!registry.is_known(&registered));
+        registry.register([(registered, Address::repeat_byte(4))]);
+        assert!(registry.is_known(&registered));
+        assert_eq!(registry.fee_recipient(&registered), Some(Address::repeat_byte(4)));
+        assert_eq!(registry.fee_recipient(&validator), None);
+
+        let unscoped = |_: &BlsPublicKey, _: &DelegationScope| Ok(());
+        registry.delegate(validator, delegatee, DelegationScope::default());
+        assert!(registry.is_known(&validator));
+        assert!(!registry.is_known(&delegatee));
+        assert_eq!(registry.proposers_of(&delegatee, unscoped), Ok(vec![validator]));
+        assert_eq!(registry.proposers_of(&registered, unscoped), Ok(vec![registered]));
+
+        // Constraints beyond the scope of the only delegation of their signer are refused
+        let scope = DelegationScope { expiry_slot: 10, ..Default::default() };
+        registry.delegate(validator, delegatee, scope);
+        let within_scope = |_: &BlsPublicKey, scope: &DelegationScope| scope.check(11, false, || 0);
+        assert_eq!(
+            registry.proposers_of(&delegatee, within_scope),
+            Err(ScopeViolation::Expired(10))
+        );
+
+        registry.revoke(&validator, &delegatee);
+        assert!(!registry.is_known(&validator));
+        assert!(registry.is_known(&registered));
+    }
+}
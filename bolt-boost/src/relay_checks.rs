@@ -0,0 +1,266 @@
+//! Startup checks of the configured relays: that each one is reachable, serves the Constraints
+//! API and runs on the same chain as bolt-boost, so that misconfigured relays are reported
+//! before any constraints are forwarded to them.
+
+use std::{collections::HashMap, fmt, time::Duration};
+
+use alloy::{eips::merge::SLOT_DURATION_SECS, hex};
+use axum::http::StatusCode;
+use futures::future::join_all;
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use cb_common::{
+    constants::COMMIT_BOOST_DOMAIN, pbs::RelayClient, signature::compute_domain, types::Chain,
+};
+
+use crate::{
+    capabilities::{Capabilities, INCLUSION_COMMITMENT},
+    server::CAPABILITIES_PATH,
+};
+
+/// The status endpoint of the builder API, served by every relay.
+const STATUS_PATH: &str = "/eth/v1/builder/status";
+
+/// The endpoint of the chain specification of a relay.
+const SPEC_PATH: &str = "/eth/v1/config/spec";
+
+/// The timeout of each request of the startup checks.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How to handle relays failing the startup checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayCheckMode {
+    /// Refuse to start, with a report of the failures of every relay.
+    Strict,
+    /// Start anyway, logging a warning for each failure.
+    #[default]
+    Lenient,
+    /// Don't check the relays.
+    Disabled,
+}
+
+/// A startup check failed by a relay.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RelayCheckFailure {
+    #[error("unreachable: {0}")]
+    Unreachable(String),
+    #[error("status endpoint returned {0}")]
+    Unhealthy(StatusCode),
+    #[error("constraints API not supported: {0}")]
+    ConstraintsApi(String),
+    #[error("genesis fork version {0} doesn't match the configured chain")]
+    WrongChain(String),
+    #[error("slot time of {0}s doesn't match the {SLOT_DURATION_SECS}s of the configured chain")]
+    WrongSlotTime(u64),
+}
+
+/// The outcome of the startup checks of a relay.
+#[derive(Debug, Clone)]
+pub struct RelayCheck {
+    /// The id of the relay.
+    pub relay_id: String,
+    /// The URL of the relay.
+    pub url: Url,
+    /// The checks the relay failed.
+    pub failures: Vec<RelayCheckFailure>,
+    /// The checks that couldn't be completed, e.g. because the relay doesn't serve its chain
+    /// specification.
+    pub warnings: Vec<String>,
+}
+
+/// The outcome of the startup checks of every relay.
+#[derive(Debug, Clone, Default)]
+pub struct RelayChecksReport {
+    pub relays: Vec<RelayCheck>,
+}
+
+impl RelayChecksReport {
+    /// Returns the relays that failed at least one check.
+    pub fn failed(&self) -> impl Iterator<Item = &RelayCheck> {
+        self.relays.iter().filter(|relay| !relay.failures.is_empty())
+    }
+
+    /// Logs the outcome of the checks and, in strict mode, returns an error with the report if
+    /// any relay failed a check.
+    pub fn enforce(&self, mode: RelayCheckMode) -> eyre::Result<()> {
+        for relay in &self.relays {
+            for warning in &relay.warnings {
+                warn!(relay_id = %relay.relay_id, url = %relay.url, "Relay check skipped: {warning}");
+            }
+            if relay.failures.is_empty() {
+                info!(relay_id = %relay.relay_id, "Relay passed the startup checks");
+            } else if mode == RelayCheckMode::Lenient {
+                for failure in &relay.failures {
+                    warn!(relay_id = %relay.relay_id, url = %relay.url, "Relay check failed: {failure}");
+                }
+            }
+        }
+
+        if mode == RelayCheckMode::Strict && self.failed().next().is_some() {
+            eyre::bail!("Relays failed the startup checks:\n{self}");
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RelayChecksReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for relay in self.failed() {
+            writeln!(f, "- {} ({}):", relay.relay_id, relay.url)?;
+            for failure in &relay.failures {
+                writeln!(f, "  - {failure}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The chain specification of a relay, as served by its spec endpoint.
+#[derive(Debug, Deserialize)]
+struct SpecResponse {
+    data: HashMap<String, String>,
+}
+
+/// Checks every relay concurrently.
+pub async fn check_relays(relays: &[RelayClient], chain: Chain) -> RelayChecksReport {
+    let checks = relays.iter().map(|relay| check_relay(relay, chain));
+    RelayChecksReport { relays: join_all(checks).await }
+}
+
+/// Checks that the relay is reachable, serves the Constraints API and runs on the chain.
+async fn check_relay(relay: &RelayClient, chain: Chain) -> RelayCheck {
+    let mut check = RelayCheck {
+        relay_id: relay.id.to_string(),
+        url: relay.config.entry.url.clone(),
+        failures: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    // An unreachable relay fails every other check too
+    match get(relay, STATUS_PATH).await {
+        Ok(response) if response.status() == StatusCode::OK => {}
+        Ok(response) => check.failures.push(RelayCheckFailure::Unhealthy(response.status())),
+        Err(err) => {
+            check.failures.push(RelayCheckFailure::Unreachable(err));
+            return check;
+        }
+    }
+
+    // Relays implementing the Constraints API before the capabilities endpoint don't serve
+    // it, and support the default capabilities
+    match get(relay, CAPABILITIES_PATH).await {
+        Ok(response) if response.status() == StatusCode::OK => {
+            match response.json::<Capabilities>().await {
+                Ok(capabilities) => check.failures.extend(check_capabilities(&capabilities)),
+                Err(err) => check.failures.push(RelayCheckFailure::ConstraintsApi(format!(
+                    "invalid capabilities: {err}"
+                ))),
+            }
+        }
+        Ok(response) if response.status() == StatusCode::NOT_FOUND => {}
+        Ok(response) => check.failures.push(RelayCheckFailure::ConstraintsApi(format!(
+            "capabilities endpoint returned {}",
+            response.status()
+        ))),
+        Err(err) => check.failures.push(RelayCheckFailure::ConstraintsApi(err)),
+    }
+
+    match get(relay, SPEC_PATH).await {
+        Ok(response) if response.status() == StatusCode::OK => {
+            match response.json::<SpecResponse>().await {
+                Ok(spec) => check.failures.extend(check_spec(&spec.data, chain)),
+                Err(err) => check.warnings.push(format!("invalid chain specification: {err}")),
+            }
+        }
+        Ok(response) => check
+            .warnings
+            .push(format!("chain specification not served, status {}", response.status())),
+        Err(err) => check.warnings.push(format!("chain specification not served: {err}")),
+    }
+
+    check
+}
+
+/// Sends a GET request to the endpoint of the relay.
+async fn get(relay: &RelayClient, path: &str) -> Result<reqwest::Response, String> {
+    let url = relay.get_url(path).map_err(|err| err.to_string())?;
+    relay.client.get(url).timeout(CHECK_TIMEOUT).send().await.map_err(|err| err.to_string())
+}
+
+/// Checks that the relay enforces inclusion constraints.
+fn check_capabilities(capabilities: &Capabilities) -> Option<RelayCheckFailure> {
+    let inclusion =
+        capabilities.commitment_types.iter().any(|t| t.eq_ignore_ascii_case(INCLUSION_COMMITMENT));
+    (!inclusion).then(|| RelayCheckFailure::ConstraintsApi("inclusion not enforced".to_string()))
+}
+
+/// Checks the chain specification of a relay against the configured chain. The genesis fork
+/// version is compared through the commit-boost signing domain it derives, which is what
+/// messages forwarded to the relay are verified against.
+fn check_spec(spec: &HashMap<String, String>, chain: Chain) -> Vec<RelayCheckFailure> {
+    let mut failures = Vec::new();
+
+    if let Some(version) = spec.get("GENESIS_FORK_VERSION") {
+        let matches =
+            hex::decode(version).ok().and_then(|v| <[u8; 4]>::try_from(v).ok()).is_some_and(|v| {
+                bolt_primitives::compute_domain(COMMIT_BOOST_DOMAIN, v) ==
+                    compute_domain(chain, COMMIT_BOOST_DOMAIN)
+            });
+        if !matches {
+            failures.push(RelayCheckFailure::WrongChain(version.clone()));
+        }
+    }
+
+    if let Some(slot_time) = spec.get("SECONDS_PER_SLOT").and_then(|s| s.parse().ok()) {
+        if slot_time != SLOT_DURATION_SECS {
+            failures.push(RelayCheckFailure::WrongSlotTime(slot_time));
+        }
+    }
+
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_relay_spec() {
+        let spec = |version: &str, slot_time: &str| {
+            HashMap::from([
+                ("GENESIS_FORK_VERSION".to_string(), version.to_string()),
+                ("SECONDS_PER_SLOT".to_string(), slot_time.to_string()),
+            ])
+        };
+
+        assert!(check_spec(&spec("0x00000000", "12"), Chain::Mainnet).is_empty());
+        assert_eq!(
+            check_spec(&spec("0x01017000", "12"), Chain::Mainnet),
+            vec![RelayCheckFailure::WrongChain("0x01017000".to_string())]
+        );
+        assert_eq!(
+            check_spec(&spec("0x01017000", "6"), Chain::Holesky),
+            vec![RelayCheckFailure::WrongSlotTime(6)]
+        );
+
+        // Relays that don't enforce inclusion constraints fail the Constraints API check
+        let capabilities = Capabilities { commitment_types: Vec::new(), ..Default::default() };
+        assert!(check_capabilities(&capabilities).is_some());
+        assert!(check_capabilities(&Capabilities::default()).is_none());
+
+        let report = RelayChecksReport {
+            relays: vec![RelayCheck {
+                relay_id: "relay".to_string(),
+                url: Url::parse("http://relay.local").unwrap(),
+                failures: vec![RelayCheckFailure::Unhealthy(StatusCode::SERVICE_UNAVAILABLE)],
+                warnings: Vec::new(),
+            }],
+        };
+        assert!(report.enforce(RelayCheckMode::Lenient).is_ok());
+        let err = report.enforce(RelayCheckMode::Strict).unwrap_err().to_string();
+        assert!(err.contains("- relay (http://relay.local/):"));
+    }
+}
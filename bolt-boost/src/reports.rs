@@ -0,0 +1,142 @@
+use alloy::{primitives::B256, rpc::types::beacon::BlsPublicKey};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::slot_scoped::SlotScoped;
+
+/// The number of slots that the reports are kept for after their slot, to attribute
+/// the missed inclusions once the blocks are published.
+pub(crate) const REPORT_RETENTION_SLOTS: u64 = 64;
+
+/// The delivery of the constraints of a slot to a relay, from which builders fetch them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryReceipt {
+    /// The relay the constraints were sent to.
+    pub relay_id: String,
+    /// The digests of the constraints of the slot that were sent.
+    pub constraints: Vec<B256>,
+    /// The time the relay answered, or the request failed, in unix milliseconds.
+    pub timestamp_ms: u64,
+    /// The HTTP status of the relay, if it answered. Only `200` acknowledges the delivery.
+    pub status: Option<u16>,
+}
+
+/// A bid received for a constrained slot, with the builder that made it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BidReceipt {
+    /// The relay that served the bid.
+    pub relay_id: String,
+    /// The public key of the builder of the bid.
+    pub builder_pubkey: BlsPublicKey,
+    /// The block hash of the bid.
+    pub block_hash: B256,
+    /// Whether the inclusion proofs of the bid were valid against the constraints.
+    pub proofs_valid: bool,
+}
+
+/// What happened to the constraints of a slot, to attribute a missed inclusion either to
+/// the relays that never got the constraints, or to the builders that ignored them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotReport {
+    /// The slot of the report.
+    pub slot: u64,
+    /// The deliveries of the constraints to the relays, in order.
+    pub deliveries: Vec<DeliveryReceipt>,
+    /// The bids received for the slot.
+    pub bids: Vec<BidReceipt>,
+    /// The block hash of the bid returned to the proposer, if any.
+    pub selected_block_hash: Option<B256>,
+}
+
+/// A concurrent store of the reports of the recent slots.
+#[derive(Clone, Default, Debug)]
+pub struct SlotReports {
+    reports: Arc<RwLock<SlotScoped<SlotReport>>>,
+}
+
+impl SlotReports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the delivery of the constraints of a slot to a relay.
+    pub fn record_delivery(&self, slot: u64, receipt: DeliveryReceipt) {
+        self.update(slot, |report| report.deliveries.push(receipt));
+    }
+
+    /// Records a bid received for a constrained slot.
+    pub fn record_bid(&self, slot: u64, receipt: BidReceipt) {
+        self.update(slot, |report| report.bids.push(receipt));
+    }
+
+    /// Records the bid returned to the proposer for a constrained slot.
+    pub fn record_selected(&self, slot: u64, block_hash: B256) {
+        self.update(slot, |report| report.selected_block_hash = Some(block_hash));
+    }
+
+    /// Returns the report of the given slot, if any constraints were delivered for it.
+    pub fn get(&self, slot: u64) -> Option<SlotReport> {
+        self.reports.read().get(slot).cloned()
+    }
+
+    /// Removes all the reports before the given slot.
+    pub fn remove_before(&self, slot: u64) {
+        self.reports.write().advance_to(slot);
+    }
+
+    fn update(&self, slot: u64, f: impl FnOnce(&mut SlotReport)) {
+        let mut reports = self.reports.write();
+        match reports.get_mut(slot) {
+            Some(report) => f(report),
+            None => {
+                let mut report = SlotReport { slot, ..Default::default() };
+                f(&mut report);
+                reports.insert(slot, report);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_reports() {
+        let reports = SlotReports::new();
+        let delivery = |relay_id: &str, status| DeliveryReceipt {
+            relay_id: relay_id.to_string(),
+            constraints: vec![B256::repeat_byte(1)],
+            timestamp_ms: 1_000,
+            status,
+        };
+
+        reports.record_delivery(10, delivery("titan", Some(200)));
+        reports.record_delivery(10, delivery("aestus", None));
+        reports.record_bid(
+            10,
+            BidReceipt {
+                relay_id: "titan".to_string(),
+                builder_pubkey: BlsPublicKey::repeat_byte(2),
+                block_hash: B256::repeat_byte(3),
+                proofs_valid: true,
+            },
+        );
+        reports.record_selected(10, B256::repeat_byte(3));
+        reports.record_delivery(11, delivery("titan", Some(200)));
+
+        let report = reports.get(10).unwrap();
+        assert_eq!(report.slot, 10);
+        assert_eq!(report.deliveries.len(), 2);
+        assert_eq!(report.bids.len(), 1);
+        assert_eq!(report.selected_block_hash, Some(B256::repeat_byte(3)));
+
+        reports.remove_before(11);
+        assert!(reports.get(10).is_none());
+        assert!(reports.get(11).is_some());
+    }
+}
@@ -1,24 +1,33 @@
 use alloy::{
-    eips::merge::EPOCH_SLOTS,
-    primitives::{utils::format_ether, B256, U256},
+    eips::merge::{EPOCH_SLOTS, SLOT_DURATION_SECS},
+    primitives::{utils::format_ether, Address, B256, U256},
     rpc::types::beacon::{relay::ValidatorRegistration, BlsPublicKey},
 };
 use async_trait::async_trait;
 use axum::{
+    body::{to_bytes, Body},
     extract::{Path, State},
     http::{header::USER_AGENT, HeaderMap, HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{get, post},
-    Json, Router,
+    Router,
 };
 use eyre::Result;
-use futures::{future::join_all, stream::FuturesUnordered, StreamExt};
+use futures::{
+    future::join_all,
+    stream::{self, FuturesUnordered},
+    Stream, StreamExt,
+};
+use reqwest::Url;
 use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     time::{Duration, Instant},
 };
-use tokio::time::sleep;
+use tokio::{sync::broadcast::error::RecvError, time::sleep};
 use tracing::{debug, error, info, warn, Instrument};
 
 use cb_common::{
@@ -26,23 +35,36 @@ use cb_common::{
     constants::APPLICATION_BUILDER_DOMAIN,
     pbs::{
         error::{PbsError, ValidationError},
-        GetHeaderResponse, RelayClient, SignedExecutionPayloadHeader, EMPTY_TX_ROOT_HASH,
-        HEADER_SLOT_UUID_KEY, HEADER_START_TIME_UNIX_MS,
+        GetHeaderParams as VanillaGetHeaderParams, GetHeaderResponse, RelayClient,
+        SignedBlindedBeaconBlock, SignedExecutionPayloadHeader, SubmitBlindedBlockResponse,
+        EMPTY_TX_ROOT_HASH, HEADER_SLOT_UUID_KEY, HEADER_START_TIME_UNIX_MS,
     },
     signature::verify_signed_message,
     types::Chain,
     utils::{get_user_agent, get_user_agent_with_version, ms_into_slot, utcnow_ms},
 };
-use cb_pbs::{register_validator, BuilderApi, BuilderApiState, PbsState};
+use cb_pbs::{get_header, register_validator, submit_block, BuilderApi, BuilderApiState, PbsState};
 
 use crate::metrics::{
-    GET_HEADER_WP_TAG, RELAY_INVALID_BIDS, RELAY_LATENCY, RELAY_STATUS_CODE, TIMEOUT_ERROR_CODE_STR,
+    GET_HEADER_FALLBACKS, GET_HEADER_TAG, GET_HEADER_WP_TAG, HASH_REF_FALLBACKS,
+    PAYLOAD_CACHE_HITS, PROOF_VERIFICATION_LATENCY, RELAY_INVALID_BIDS, RELAY_LATENCY,
+    RELAY_PAYMENT_DISCREPANCIES, RELAY_STATUS_CODE, TIMEOUT_ERROR_CODE_STR, UNAUTHORIZED_REQUESTS,
 };
 
 use super::{
-    constraints::ConstraintsCache,
+    auth::verify_request,
+    bids::{AdjustedBid, DECISION_LOG_TARGET},
+    capabilities::Capabilities,
+    constraints::{ConstraintsCache, Submission},
+    constraints_store::ConstraintsStore,
     error::PbsClientError,
+    payloads::PayloadCache,
+    payments::{verify_payment, BidCache, BidRecord, RelayPenalties, UNKNOWN_RELAY},
     proofs::verify_multiproofs,
+    proposers::ProposerRegistry,
+    reports::{BidReceipt, DeliveryReceipt, SlotReports, REPORT_RETENTION_SLOTS},
+    streaming::{parse_json_array, ArrayLimits},
+    tx_refs::{HashRefConstraints, HashRefRelays, CONSTRAINTS_ENCODING_HEADER, HASH_REFS_ENCODING},
     types::{
         Config, GetHeaderParams, GetHeaderWithProofsResponse, RequestConfig, SignedConstraints,
         SignedDelegation, SignedExecutionPayloadHeaderWithProofs, SignedRevocation,
@@ -50,26 +72,70 @@ use super::{
 };
 
 const SUBMIT_CONSTRAINTS_PATH: &str = "/constraints/v1/builder/constraints";
+const CONSTRAINTS_STREAM_PATH: &str = "/constraints/v1/builder/constraints/stream";
 const DELEGATE_PATH: &str = "/constraints/v1/builder/delegate";
 const REVOKE_PATH: &str = "/constraints/v1/builder/revoke";
 const GET_HEADER_WITH_PROOFS_PATH: &str =
     "/eth/v1/builder/header_with_proofs/:slot/:parent_hash/:pubkey";
+const SLOT_REPORT_PATH: &str = "/bolt/v1/slot_report/:slot";
+const CAPACITY_PATH: &str = "/bolt/v1/capacity";
+pub(crate) const CAPABILITIES_PATH: &str = "/constraints/v1/capabilities";
 
 const TIMEOUT_ERROR_CODE: u16 = 555;
 
+/// The timeout of the requests of the capabilities of the relays.
+const CAPABILITIES_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The size caps of constraint batches, which carry whole transactions.
+const CONSTRAINTS_LIMITS: ArrayLimits =
+    ArrayLimits { max_length: 32 * 1024 * 1024, max_items: 4096 };
+
+/// The size caps of delegation and revocation lists, which can cover a whole fleet.
+const DELEGATIONS_LIMITS: ArrayLimits =
+    ArrayLimits { max_length: 64 * 1024 * 1024, max_items: 100_000 };
+
 // Extra state available at runtime
 #[derive(Clone)]
 pub struct BuilderState {
-    #[allow(unused)]
     config: Config,
     constraints: ConstraintsCache,
+    proposers: ProposerRegistry,
+    bids: BidCache,
+    penalties: RelayPenalties,
+    payloads: PayloadCache,
+    reports: SlotReports,
+    hash_ref_relays: HashRefRelays,
 }
 
 impl BuilderApiState for BuilderState {}
 
 impl BuilderState {
-    pub fn from_config(config: Config) -> Self {
-        Self { config, constraints: ConstraintsCache::new() }
+    /// Creates the state from the given configuration, recovering the persisted constraints
+    /// of the current and upcoming slots of the chain if they are persisted.
+    pub fn from_config(config: Config, chain: Chain) -> Result<Self> {
+        let mut constraints = ConstraintsCache::new()
+            .with_max_gas_per_slot(config.max_constrained_gas_per_slot)
+            .with_block_limits(config.block_gas_limit, config.max_blobs_per_block)
+            .with_precedence(config.sidecar_precedence.clone());
+
+        if let Some(path) = config.constraints_db_path.as_ref() {
+            info!(path = %path.display(), "Persisting constraints");
+            constraints = constraints.with_store(ConstraintsStore::open(path)?);
+
+            let head = ms_into_slot(0, chain) / (SLOT_DURATION_SECS * 1000);
+            constraints.recover(head)?;
+        }
+
+        Ok(Self {
+            config,
+            constraints,
+            proposers: ProposerRegistry::new(),
+            bids: BidCache::new(),
+            penalties: RelayPenalties::new(),
+            payloads: PayloadCache::new(),
+            reports: SlotReports::new(),
+            hash_ref_relays: HashRefRelays::new(),
+        })
     }
 }
 
@@ -81,10 +147,41 @@ pub struct ConstraintsApi;
 
 #[async_trait]
 impl BuilderApi<BuilderState> for ConstraintsApi {
+    /// Get a header for the given slot and parent hash.
+    ///
+    /// Only proposers known to bolt-boost with constraints for the slot get a header
+    /// verified against them, the others get plain mev-boost behavior. In both cases, the
+    /// bids of relays that recently overstated a bid are skipped.
+    async fn get_header(
+        params: VanillaGetHeaderParams,
+        req_headers: HeaderMap,
+        state: PbsState<BuilderState>,
+    ) -> eyre::Result<Option<GetHeaderResponse>> {
+        if !state.data.proposers.is_known(&params.pubkey) ||
+            !state.data.constraints.contains(params.slot, &params.pubkey)
+        {
+            GET_HEADER_FALLBACKS.with_label_values(&[GET_HEADER_TAG]).inc();
+            let (slot, pubkey) = (params.slot, params.pubkey);
+            let header = get_header(params, req_headers, state.clone()).await?;
+
+            return Ok(header.filter(|header| accept_plain_bid(&state, slot, &pubkey, &header.data)));
+        }
+
+        let params = GetHeaderParams {
+            slot: params.slot,
+            parent_hash: params.parent_hash,
+            pubkey: params.pubkey,
+        };
+        let header = fetch_header_with_proofs(params, req_headers, state).await;
+
+        Ok(header
+            .map(|header| GetHeaderResponse { version: header.version, data: header.data.header }))
+    }
+
     /// Register a validator with the builder.
     ///
     /// We intercept this call since it happens periodically and we use it to clean
-    /// up old constraints.
+    /// up old constraints and slot reports, and to record the validators known to bolt-boost.
     async fn register_validator(
         registrations: Vec<ValidatorRegistration>,
         req_headers: HeaderMap,
@@ -94,18 +191,84 @@ impl BuilderApi<BuilderState> for ConstraintsApi {
 
         info!("Cleaning up constraints before slot {slot}");
         state.data.constraints.remove_before(slot);
+        state.data.bids.remove_before(slot);
+        state.data.penalties.remove_before(slot);
+        state.data.payloads.remove_before(slot);
+        state.data.reports.remove_before(slot.saturating_sub(REPORT_RETENTION_SLOTS));
+
+        state
+            .data
+            .proposers
+            .register(registrations.iter().map(|r| (r.message.pubkey, r.message.fee_recipient)));
 
         register_validator(registrations, req_headers, state).await
     }
 
+    /// Submit a signed blinded block to the relays and return its payload.
+    ///
+    /// The payment of the payload to the proposer is verified against the bid returned
+    /// for it, and discrepancies are recorded per relay, whose bids are then skipped for
+    /// `overstated_bid_penalty_slots`. The payload is returned regardless, since the proposer
+    /// already signed the header and can only publish this payload.
+    ///
+    /// Payloads are cached until their slot passes, so that the retries of the beacon node
+    /// after a timeout are answered without fetching the payload from the relays again.
+    async fn submit_block(
+        signed_blinded_block: SignedBlindedBeaconBlock,
+        req_headers: HeaderMap,
+        state: PbsState<BuilderState>,
+    ) -> eyre::Result<SubmitBlindedBlockResponse> {
+        let slot = signed_blinded_block.message.slot;
+        let block_hash = signed_blinded_block.message.body.execution_payload_header.block_hash;
+        if let Some(response) = state.data.payloads.get(slot, &block_hash) {
+            info!(slot, %block_hash, "Returning the cached payload of a retried submission");
+            PAYLOAD_CACHE_HITS.inc();
+            return Ok(response);
+        }
+
+        let bid = state.data.bids.remove(&block_hash);
+
+        let response = submit_block(signed_blinded_block, req_headers, state.clone()).await?;
+        state.data.payloads.insert(slot, block_hash, response.clone());
+
+        if let Some(bid) = bid {
+            let payload = &response.data.execution_payload;
+            let tolerance_bps = state.data.config.payment_tolerance_bps;
+
+            if let Err(e) =
+                verify_payment(&bid, payload.fee_recipient, &payload.transactions, tolerance_bps)
+            {
+                warn!(
+                    relay_id = bid.relay_id,
+                    slot = bid.slot,
+                    %block_hash,
+                    error = %e,
+                    "Payload payment doesn't match the bid"
+                );
+                RELAY_PAYMENT_DISCREPANCIES.with_label_values(&[bid.relay_id.as_str()]).inc();
+
+                let penalty_slots = state.data.config.overstated_bid_penalty_slots;
+                if penalty_slots > 0 && bid.relay_id != UNKNOWN_RELAY {
+                    state.data.penalties.penalize(&bid.relay_id, bid.slot + penalty_slots);
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
     /// Gets the extra routes for supporting the constraints API as defined in
     /// the spec: <https://chainbound.github.io/bolt-docs/api/builder>.
     fn extra_routes() -> Option<Router<PbsState<BuilderState>>> {
         let mut router = Router::new();
         router = router.route(SUBMIT_CONSTRAINTS_PATH, post(submit_constraints));
+        router = router.route(CONSTRAINTS_STREAM_PATH, get(stream_constraints));
         router = router.route(DELEGATE_PATH, post(delegate));
         router = router.route(REVOKE_PATH, post(revoke));
         router = router.route(GET_HEADER_WITH_PROOFS_PATH, get(get_header_with_proofs));
+        router = router.route(SLOT_REPORT_PATH, get(get_slot_report));
+        router = router.route(CAPACITY_PATH, get(get_capacity));
+        router = router.route(CAPABILITIES_PATH, get(get_capabilities));
         Some(router)
     }
 }
@@ -115,13 +278,24 @@ impl BuilderApi<BuilderState> for ConstraintsApi {
 #[tracing::instrument(skip_all)]
 async fn submit_constraints(
     State(state): State<PbsState<BuilderState>>,
-    Json(constraints): Json<Vec<SignedConstraints>>,
+    req_headers: HeaderMap,
+    body: Body,
 ) -> Result<impl IntoResponse, PbsClientError> {
+    let (body, sidecar) =
+        authenticate(&state, &req_headers, SUBMIT_CONSTRAINTS_PATH, CONSTRAINTS_LIMITS, body)
+            .await?;
+    let constraints: Vec<SignedConstraints> =
+        parse_json_array(body, CONSTRAINTS_LIMITS).await.inspect_err(|e| {
+            error!(error = %e, "Failed to parse constraints");
+        })?;
+
     info!("Submitting {} constraints to relays", constraints.len());
     let (current_slot, _) = state.get_slot_and_uuid();
 
-    // Save constraints for the slot to verify proofs against later.
-    for signed_constraints in &constraints {
+    // Save constraints for the slot to verify proofs against later. Constraints already
+    // submitted by another sidecar are not forwarded to relays again.
+    let mut forwarded = Vec::with_capacity(constraints.len());
+    for signed_constraints in constraints {
         let slot = signed_constraints.message.slot;
 
         // Only accept constraints for the current or next epoch.
@@ -130,25 +304,198 @@ async fn submit_constraints(
             return Err(PbsClientError::BadRequest);
         }
 
-        if let Err(e) = state.data.constraints.insert(slot, signed_constraints.message.clone()) {
-            error!(slot, error = %e, "Failed to save constraints");
-            return Err(PbsClientError::BadRequest);
+        if let Err(e) = signed_constraints.verify_signature(state.config.chain) {
+            warn!(slot, error = %e, "Rejecting constraints with an invalid signature");
+            return Err(PbsClientError::InvalidSignature(e));
+        }
+
+        // Partition the constraints by the proposers their signer signs for, within the
+        // scopes of the delegations to the signer
+        let message = signed_constraints.message.clone();
+        let proposers = state
+            .data
+            .proposers
+            .proposers_of(&message.pubkey, |proposer, scope| {
+                scope.check(slot, message.top, || {
+                    let constrained =
+                        state.data.constraints.constrained_gas_by(slot, proposer, &message.pubkey);
+                    message.gas_limit().unwrap_or(u64::MAX).saturating_add(constrained)
+                })
+            })
+            .map_err(|e| {
+                warn!(slot, error = %e, "Rejecting constraints beyond the scope of the delegation");
+                PbsClientError::OutOfScope(e)
+            })?;
+        match state.data.constraints.submit(slot, message, proposers, sidecar) {
+            Ok(Submission::Duplicate) => {
+                debug!(slot, "Constraints already submitted by another sidecar");
+            }
+            Ok(_) => forwarded.push(signed_constraints),
+            Err(e) => {
+                error!(slot, error = %e, "Failed to save constraints");
+                return Err(PbsClientError::Constraints(e));
+            }
+        }
+    }
+
+    if forwarded.is_empty() {
+        return Ok(StatusCode::OK);
+    }
+    let constraints = forwarded;
+
+    let outcomes = send_constraints_to_relays(&state, &constraints).await?;
+
+    // Record the delivery of the constraints of each slot to each relay, acknowledged or not
+    let mut digests = BTreeMap::<u64, Vec<B256>>::new();
+    for signed_constraints in &constraints {
+        if let Ok(digest) = signed_constraints.message.digest() {
+            digests.entry(signed_constraints.message.slot).or_default().push(digest.into());
+        }
+    }
+    for (relay_id, status, timestamp_ms) in &outcomes {
+        for (slot, constraints) in &digests {
+            let receipt = DeliveryReceipt {
+                relay_id: relay_id.clone(),
+                constraints: constraints.clone(),
+                timestamp_ms: *timestamp_ms,
+                status: status.map(|s| s.as_u16()),
+            };
+            state.data.reports.record_delivery(*slot, receipt);
         }
     }
 
-    post_request(state, SUBMIT_CONSTRAINTS_PATH, &constraints).await?;
+    require_acknowledged(&outcomes)?;
     Ok(StatusCode::OK)
 }
 
+/// Stream the changes to the constraints to a builder as server-sent events, as they are
+/// cached: the accepted constraints, the superseded ones and the slots cleaned up.
+///
+/// Only the changes after the subscription are streamed. A builder lagging too far behind
+/// misses the oldest events, and should re-subscribe from a clean state.
+#[tracing::instrument(skip_all)]
+async fn stream_constraints(
+    State(state): State<PbsState<BuilderState>>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let events = state.data.constraints.subscribe();
+
+    let stream = stream::unfold(events, |mut events| async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => return Some((Event::default().json_data(&event), events)),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Constraints stream subscriber lagged behind");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Get the report of what happened to the constraints of a recent slot.
+#[tracing::instrument(skip_all)]
+async fn get_slot_report(
+    State(state): State<PbsState<BuilderState>>,
+    Path(slot): Path<u64>,
+) -> impl IntoResponse {
+    match state.data.reports.get(slot) {
+        Some(report) => (StatusCode::OK, axum::Json(report)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Get the constraints received for each proposer in the current and upcoming slots,
+/// against the configured caps.
+#[tracing::instrument(skip_all)]
+async fn get_capacity(State(state): State<PbsState<BuilderState>>) -> impl IntoResponse {
+    let (current_slot, _) = state.get_slot_and_uuid();
+    axum::Json(state.data.constraints.capacity(current_slot))
+}
+
+/// Get the capabilities of bolt-boost, restricted to the commitments that all of its
+/// relays enforce. Relays that don't serve the endpoint are assumed to support the
+/// [default](Capabilities::default) capabilities.
+///
+/// The encodings advertised by the relays are also recorded, to send constraints
+/// referencing their transactions by hash to the relays that accept them.
+#[tracing::instrument(skip_all)]
+async fn get_capabilities(State(state): State<PbsState<BuilderState>>) -> impl IntoResponse {
+    let mut capabilities = Capabilities::local(state.data.config.max_constrained_gas_per_slot);
+
+    let requests = state.relays().iter().map(|relay| async move {
+        fetch_relay_capabilities(relay).await.map(|c| (relay.id.to_string(), c))
+    });
+
+    for (relay_id, relay_capabilities) in join_all(requests).await.into_iter().flatten() {
+        let hash_refs = relay_capabilities.supports_encoding(HASH_REFS_ENCODING);
+        state.data.hash_ref_relays.record_advertised(&relay_id, hash_refs);
+        capabilities.restrict_to(&relay_capabilities);
+    }
+
+    axum::Json(capabilities)
+}
+
+/// Fetches the capabilities of a relay, or the default ones if it doesn't serve the endpoint.
+async fn fetch_relay_capabilities(relay: &RelayClient) -> Option<Capabilities> {
+    let url = relay.get_url(CAPABILITIES_PATH).ok()?;
+    let response = match relay.client.get(url).timeout(CAPABILITIES_TIMEOUT).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(relay_id = %relay.id, error = %e, "Failed to get relay capabilities");
+            return None;
+        }
+    };
+
+    match response.status() {
+        StatusCode::OK => response
+            .json::<Capabilities>()
+            .await
+            .inspect_err(|e| warn!(relay_id = %relay.id, error = %e, "Invalid relay capabilities"))
+            .ok(),
+        StatusCode::NOT_FOUND => Some(Capabilities::default()),
+        status => {
+            warn!(relay_id = %relay.id, %status, "Failed to get relay capabilities");
+            None
+        }
+    }
+}
+
 /// Delegate constraint submission rights to another BLS key.
 /// Spec: <https://chainbound.github.io/bolt-docs/api/builder#delegate>
 #[tracing::instrument(skip_all)]
 async fn delegate(
     State(state): State<PbsState<BuilderState>>,
-    Json(delegations): Json<Vec<SignedDelegation>>,
+    req_headers: HeaderMap,
+    body: Body,
 ) -> Result<impl IntoResponse, PbsClientError> {
+    let (body, _) =
+        authenticate(&state, &req_headers, DELEGATE_PATH, DELEGATIONS_LIMITS, body).await?;
+    let delegations: Vec<SignedDelegation> =
+        parse_json_array(body, DELEGATIONS_LIMITS).await.inspect_err(|e| {
+            error!(error = %e, "Failed to parse delegations");
+        })?;
+
+    for delegation in &delegations {
+        if let Err(e) = delegation.verify_signature(state.config.chain) {
+            warn!(error = %e, "Rejecting delegation with an invalid signature");
+            return Err(PbsClientError::InvalidSignature(e));
+        }
+    }
+
     info!(count = %delegations.len(), "Delegating signing rights");
-    post_request(state, DELEGATE_PATH, &delegations).await?;
+    post_request(state.clone(), DELEGATE_PATH, &delegations).await?;
+
+    for delegation in delegations {
+        let message = delegation.message;
+        state.data.proposers.delegate(
+            message.validator_pubkey,
+            message.delegatee_pubkey,
+            message.scope,
+        );
+    }
+
     Ok(StatusCode::OK)
 }
 
@@ -157,21 +504,126 @@ async fn delegate(
 #[tracing::instrument(skip_all)]
 async fn revoke(
     State(state): State<PbsState<BuilderState>>,
-    Json(revocations): Json<Vec<SignedRevocation>>,
+    req_headers: HeaderMap,
+    body: Body,
 ) -> Result<impl IntoResponse, PbsClientError> {
+    let (body, _) =
+        authenticate(&state, &req_headers, REVOKE_PATH, DELEGATIONS_LIMITS, body).await?;
+    let revocations: Vec<SignedRevocation> =
+        parse_json_array(body, DELEGATIONS_LIMITS).await.inspect_err(|e| {
+            error!(error = %e, "Failed to parse revocations");
+        })?;
+
+    for revocation in &revocations {
+        if let Err(e) = revocation.verify_signature(state.config.chain) {
+            warn!(error = %e, "Rejecting revocation with an invalid signature");
+            return Err(PbsClientError::InvalidSignature(e));
+        }
+    }
+
     info!(count = %revocations.len(), "Revoking signing rights");
-    post_request(state, REVOKE_PATH, &revocations).await?;
+    post_request(state.clone(), REVOKE_PATH, &revocations).await?;
+
+    for revocation in revocations {
+        let message = revocation.message;
+        state.data.proposers.revoke(&message.validator_pubkey, &message.delegatee_pubkey);
+    }
+
     Ok(StatusCode::OK)
 }
 
+/// Authenticates a submission to `path` if bolt-boost only accepts submissions from
+/// authorized sidecars, returning its body to be parsed and the authenticated sidecar.
+///
+/// The body is buffered, within the size caps of the endpoints, since the signature
+/// commits to its hash.
+async fn authenticate(
+    state: &PbsState<BuilderState>,
+    req_headers: &HeaderMap,
+    path: &'static str,
+    limits: ArrayLimits,
+    body: Body,
+) -> Result<(Body, Option<Address>), PbsClientError> {
+    let config = &state.data.config;
+    if config.authorized_sidecars.is_empty() {
+        return Ok((body, None));
+    }
+
+    let bytes =
+        to_bytes(body, limits.max_length).await.map_err(|_| PbsClientError::PayloadTooLarge)?;
+
+    match verify_request(
+        req_headers,
+        path,
+        &bytes,
+        &config.authorized_sidecars,
+        config.auth_max_clock_skew_secs,
+    ) {
+        Ok(sidecar) => {
+            debug!(%sidecar, path, "Authenticated submission");
+            Ok((Body::from(bytes), Some(sidecar)))
+        }
+        Err(e) => {
+            warn!(path, error = %e, "Rejecting unauthenticated submission");
+            UNAUTHORIZED_REQUESTS.with_label_values(&[path]).inc();
+            Err(PbsClientError::Unauthorized(e))
+        }
+    }
+}
+
 /// Get a header with proofs for a given slot and parent hash.
 /// Spec: <https://chainbound.github.io/bolt-docs/api/builder#get_header_with_proofs>
+///
+/// Proposers unknown to bolt-boost get plain mev-boost behavior, with empty proofs.
 #[tracing::instrument(skip_all, fields(slot = params.slot))]
 async fn get_header_with_proofs(
     State(state): State<PbsState<BuilderState>>,
     Path(params): Path<GetHeaderParams>,
     req_headers: HeaderMap,
 ) -> Result<impl IntoResponse, PbsClientError> {
+    let header = if state.data.proposers.is_known(&params.pubkey) {
+        fetch_header_with_proofs(params, req_headers, state).await
+    } else {
+        info!(validator_pubkey = %params.pubkey, "Unknown proposer, requesting a plain header");
+        GET_HEADER_FALLBACKS.with_label_values(&[GET_HEADER_WP_TAG]).inc();
+
+        let params = VanillaGetHeaderParams {
+            slot: params.slot,
+            parent_hash: params.parent_hash,
+            pubkey: params.pubkey,
+        };
+        match get_header(params, req_headers, state.clone()).await {
+            Ok(header) => header
+                .filter(|header| {
+                    accept_plain_bid(&state, params.slot, &params.pubkey, &header.data)
+                })
+                .map(|header| GetHeaderWithProofsResponse {
+                    version: header.version,
+                    data: SignedExecutionPayloadHeaderWithProofs {
+                        header: header.data,
+                        proofs: Default::default(),
+                    },
+                }),
+            Err(err) => {
+                error!(?err, "Failed to get a plain header");
+                None
+            }
+        }
+    };
+
+    match header {
+        Some(header) => Ok((StatusCode::OK, axum::Json(header)).into_response()),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+/// Request headers with proofs from all relays, and return the best bid whose proofs
+/// are valid against the constraints of the slot, if any.
+async fn fetch_header_with_proofs(
+    params: GetHeaderParams,
+    req_headers: HeaderMap,
+    state: PbsState<BuilderState>,
+) -> Option<GetHeaderWithProofsResponse> {
     let slot_uuid = state.get_or_update_slot_uuid(params.slot);
 
     let ua = get_user_agent(&req_headers);
@@ -191,7 +643,7 @@ async fn get_header_with_proofs(
             "late in slot, skipping relay requests"
         );
 
-        return Ok(StatusCode::NO_CONTENT.into_response());
+        return None;
     }
 
     // prepare headers, except for start time which is set in `send_one_get_header`
@@ -201,6 +653,9 @@ async fn get_header_with_proofs(
         .insert(HEADER_SLOT_UUID_KEY, HeaderValue::from_str(&slot_uuid.to_string()).unwrap());
     send_headers.insert(USER_AGENT, get_user_agent_with_version(&req_headers).unwrap());
 
+    // The minimum bid applies to the bids adjusted for the gas of the constraints
+    let constrained_gas = state.data.constraints.constrained_gas(params.slot, &params.pubkey);
+
     let relays = state.relays();
     let mut handles = Vec::with_capacity(relays.len());
     for relay in relays.iter() {
@@ -212,20 +667,28 @@ async fn get_header_with_proofs(
             send_headers.clone(),
             ms_into_slot,
             max_timeout_ms,
+            constrained_gas,
         ));
     }
 
     let results = join_all(handles).await;
     let mut relay_bids = Vec::with_capacity(relays.len());
     let mut hash_to_proofs = HashMap::new();
+    let mut hash_to_relay = HashMap::new();
 
-    // Get and remove the constraints for this slot
-    let maybe_constraints = state.data.constraints.remove(params.slot);
+    // Get and remove the constraints of the proposer for this slot
+    let maybe_constraints = state.data.constraints.remove(params.slot, &params.pubkey);
+    let proof_verification_budget =
+        Duration::from_millis(state.data.config.proof_verification_budget_ms);
 
     for (i, res) in results.into_iter().enumerate() {
         let relay_id = relays[i].id.as_ref();
 
         match res {
+            Ok(Some(_)) if state.data.penalties.is_penalized(relay_id, params.slot) => {
+                warn!(relay_id, "Relay overstated a bid recently, skipping bid");
+                RELAY_INVALID_BIDS.with_label_values(&[relay_id]).inc();
+            }
             Ok(Some(res)) => {
                 let root = res.data.header.message.header.transactions_root;
 
@@ -234,19 +697,46 @@ async fn get_header_with_proofs(
                 // If we have constraints to verify, do that here in order to validate the bid
                 if let Some(ref constraints) = maybe_constraints {
                     // Verify the multiproofs and continue if not valid
-                    if let Err(e) = verify_multiproofs(constraints, &res.data.proofs, root) {
+                    let verification = verify_multiproofs(constraints, &res.data.proofs, root);
+
+                    let elapsed = start.elapsed();
+                    PROOF_VERIFICATION_LATENCY
+                        .with_label_values(&[relay_id])
+                        .observe(elapsed.as_secs_f64());
+
+                    if elapsed > proof_verification_budget {
+                        warn!(
+                            relay_id,
+                            ?elapsed,
+                            budget = ?proof_verification_budget,
+                            "Proof verification exceeded its latency budget"
+                        );
+                    }
+
+                    let message = &res.data.header.message;
+                    let receipt = BidReceipt {
+                        relay_id: relay_id.to_string(),
+                        builder_pubkey: message.pubkey,
+                        block_hash: message.header.block_hash,
+                        proofs_valid: verification.is_ok(),
+                    };
+                    state.data.reports.record_bid(params.slot, receipt);
+
+                    if let Err(e) = verification {
                         error!(?e, relay_id, "Failed to verify multiproof, skipping bid");
                         RELAY_INVALID_BIDS.with_label_values(&[relay_id]).inc();
                         continue;
                     }
 
-                    tracing::debug!("Verified multiproof in {:?}", start.elapsed());
+                    tracing::debug!("Verified multiproof in {:?}", elapsed);
 
                     // Save the proofs per block hash
                     hash_to_proofs
                         .insert(res.data.header.message.header.block_hash, res.data.proofs);
                 }
 
+                hash_to_relay.insert(res.data.header.message.header.block_hash, relay_id);
+
                 let vanilla_response =
                     GetHeaderResponse { version: res.version, data: res.data.header };
 
@@ -258,26 +748,105 @@ async fn get_header_with_proofs(
         }
     }
 
-    if let Some(winning_bid) = state.add_bids(params.slot, relay_bids) {
-        let header_with_proofs = GetHeaderWithProofsResponse {
-            data: SignedExecutionPayloadHeaderWithProofs {
-                // If there are no proofs, default to empty. This should never happen unless there
-                // were no constraints to verify.
-                proofs: hash_to_proofs
-                    .get(&winning_bid.data.message.header.block_hash)
-                    .cloned()
-                    .unwrap_or_default(),
-                header: winning_bid.data,
-            },
-            version: winning_bid.version,
-        };
+    // Compare the bids on their value adjusted for the gas of the constraints
+    let mut winning_bid: Option<(GetHeaderResponse, AdjustedBid)> = None;
+    for bid in relay_bids {
+        let header = &bid.data.message;
+        let adjusted = AdjustedBid::new(header.value, header.header.gas_used, constrained_gas);
+        let relay_id = hash_to_relay.get(&header.header.block_hash).copied();
+        info!(
+            target: DECISION_LOG_TARGET,
+            slot = params.slot,
+            relay_id = relay_id.unwrap_or(UNKNOWN_RELAY),
+            block_hash = %header.header.block_hash,
+            value_eth = format_ether(adjusted.value),
+            adjusted_value_eth = format_ether(adjusted.adjusted_value),
+            gas_used = adjusted.gas_used,
+            constrained_gas = adjusted.constrained_gas,
+            "Bid received"
+        );
 
-        Ok((StatusCode::OK, axum::Json(header_with_proofs)).into_response())
-    } else {
-        Ok(StatusCode::NO_CONTENT.into_response())
+        if winning_bid
+            .as_ref()
+            .map_or(true, |(_, best)| adjusted.adjusted_value > best.adjusted_value)
+        {
+            winning_bid = Some((bid, adjusted));
+        }
+    }
+
+    let (winning_bid, adjusted) = winning_bid?;
+    let block_hash = winning_bid.data.message.header.block_hash;
+
+    let relay_id = hash_to_relay.get(&block_hash).copied().unwrap_or(UNKNOWN_RELAY);
+    info!(
+        target: DECISION_LOG_TARGET,
+        slot = params.slot,
+        relay_id,
+        %block_hash,
+        value_eth = format_ether(adjusted.value),
+        adjusted_value_eth = format_ether(adjusted.adjusted_value),
+        "Bid selected"
+    );
+    record_bid(&state, params.slot, &params.pubkey, &winning_bid.data, relay_id);
+    if maybe_constraints.is_some() {
+        state.data.reports.record_selected(params.slot, block_hash);
+    }
+
+    Some(GetHeaderWithProofsResponse {
+        data: SignedExecutionPayloadHeaderWithProofs {
+            // If there are no proofs, default to empty. This should never happen unless there
+            // were no constraints to verify.
+            proofs: hash_to_proofs.get(&block_hash).cloned().unwrap_or_default(),
+            header: winning_bid.data,
+        },
+        version: winning_bid.version,
+    })
+}
+
+/// Checks the bid selected with plain mev-boost behavior, attributed to the relay that signed
+/// it, and records it if it can be returned to the proposer. Bids of relays that recently
+/// overstated a bid are skipped, in which case the proposer gets no header.
+fn accept_plain_bid(
+    state: &PbsState<BuilderState>,
+    slot: u64,
+    pubkey: &BlsPublicKey,
+    header: &SignedExecutionPayloadHeader,
+) -> bool {
+    // Relays sign their bids with their own key, which is checked against the relay
+    let relays = state.relays();
+    let relay = relays.iter().find(|relay| relay.pubkey() == header.message.pubkey);
+    let relay_id = relay.map_or(UNKNOWN_RELAY, |relay| relay.id.as_ref());
+
+    if state.data.penalties.is_penalized(relay_id, slot) {
+        warn!(relay_id, slot, "Relay overstated a bid recently, skipping bid");
+        RELAY_INVALID_BIDS.with_label_values(&[relay_id]).inc();
+        return false;
     }
+
+    record_bid(state, slot, pubkey, header, relay_id);
+    true
+}
+
+/// Record the bid returned to the proposer, to verify the payment of its payload against
+/// once the proposer submits the signed blinded block.
+fn record_bid(
+    state: &PbsState<BuilderState>,
+    slot: u64,
+    pubkey: &BlsPublicKey,
+    header: &SignedExecutionPayloadHeader,
+    relay_id: &str,
+) {
+    let bid = BidRecord {
+        slot,
+        relay_id: relay_id.to_string(),
+        value: header.message.value,
+        fee_recipient: state.data.proposers.fee_recipient(pubkey),
+    };
+
+    state.data.bids.insert(header.message.header.block_hash, bid);
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tracing::instrument(skip_all, name = "handler", fields(relay_id = relay.id.as_ref()))]
 async fn send_timed_get_header(
     params: GetHeaderParams,
@@ -287,6 +856,7 @@ async fn send_timed_get_header(
     headers: HeaderMap,
     ms_into_slot: u64,
     mut timeout_left_ms: u64,
+    constrained_gas: u64,
 ) -> Result<Option<GetHeaderWithProofsResponse>, PbsError> {
     let url = relay.get_url(&format!(
         "/eth/v1/builder/header_with_proofs/{}/{}/{}",
@@ -320,6 +890,7 @@ async fn send_timed_get_header(
                         chain,
                         pbs_config.skip_sigverify,
                         pbs_config.min_bid_wei,
+                        constrained_gas,
                         RequestConfig {
                             timeout_ms: timeout_left_ms,
                             url: url.clone(),
@@ -380,6 +951,7 @@ async fn send_timed_get_header(
         chain,
         pbs_config.skip_sigverify,
         pbs_config.min_bid_wei,
+        constrained_gas,
         RequestConfig { timeout_ms: timeout_left_ms, url, headers },
     )
     .await
@@ -392,6 +964,7 @@ async fn send_one_get_header(
     chain: Chain,
     skip_sigverify: bool,
     min_bid_wei: U256,
+    constrained_gas: u64,
     mut req_config: RequestConfig,
 ) -> Result<(u64, Option<GetHeaderWithProofsResponse>), PbsError> {
     // the timestamp in the header is the consensus block time which is fixed,
@@ -460,6 +1033,7 @@ async fn send_one_get_header(
         params.parent_hash,
         skip_sigverify,
         min_bid_wei,
+        constrained_gas,
     )?;
 
     Ok((start_request_time, Some(get_header_response)))
@@ -472,11 +1046,14 @@ fn validate_header(
     parent_hash: B256,
     skip_sig_verify: bool,
     minimum_bid_wei: U256,
+    constrained_gas: u64,
 ) -> Result<(), ValidationError> {
     let block_hash = signed_header.message.header.block_hash;
     let received_relay_pubkey = signed_header.message.pubkey;
     let tx_root = signed_header.message.header.transactions_root;
-    let value = signed_header.message.value;
+    let gas_used = signed_header.message.header.gas_used;
+    let value =
+        AdjustedBid::new(signed_header.message.value, gas_used, constrained_gas).adjusted_value;
 
     if block_hash == B256::ZERO {
         return Err(ValidationError::EmptyBlockhash);
@@ -519,12 +1096,29 @@ fn validate_header(
     Ok(())
 }
 
+/// The outcome of a POST request to a relay: its id, its HTTP status if it answered,
+/// and the time of the outcome in unix milliseconds.
+type RelayOutcome = (String, Option<StatusCode>, u64);
+
 /// Send a POST request to all relays. Only returns an error if all of the requests fail.
 async fn post_request<T>(
     state: PbsState<BuilderState>,
     path: &str,
     body: &T,
 ) -> Result<(), PbsClientError>
+where
+    T: Serialize,
+{
+    let outcomes = send_to_relays(&state, path, body).await?;
+    require_acknowledged(&outcomes)
+}
+
+/// Sends a POST request to all relays, returning the outcome of each.
+async fn send_to_relays<T>(
+    state: &PbsState<BuilderState>,
+    path: &str,
+    body: &T,
+) -> Result<Vec<RelayOutcome>, PbsClientError>
 where
     T: Serialize,
 {
@@ -534,11 +1128,12 @@ where
 
     for relay in state.relays() {
         let url = relay.get_url(path).map_err(|_| PbsClientError::BadRequest)?;
-        responses.push(relay.client.post(url).json(&body).send());
+        let request = relay.client.post(url).json(&body).send();
+        responses.push(async move { (relay.id.to_string(), request.await) });
     }
 
-    let mut success = false;
-    while let Some(res) = responses.next().await {
+    let mut outcomes = Vec::with_capacity(responses.len());
+    while let Some((relay_id, res)) = responses.next().await {
         match res {
             Ok(response) => {
                 let url = response.url().clone();
@@ -548,14 +1143,115 @@ where
                     error!(%status, %url, "Failed to POST to relay: {body:?}");
                 } else {
                     debug!(%url, "Successfully sent POST request to relay");
-                    success = true;
                 }
+                outcomes.push((relay_id, Some(status), utcnow_ms()));
+            }
+            Err(e) => {
+                error!(error = ?e, relay_id, "Failed to POST to relay");
+                outcomes.push((relay_id, None, utcnow_ms()));
+            }
+        }
+    }
+
+    Ok(outcomes)
+}
+
+/// Sends constraints to all relays, returning the outcome of each.
+///
+/// The relays that negotiated it get the constraints with their transactions referenced by
+/// hash. If they reject them, e.g. because they don't hold all of the transactions, the
+/// constraints are re-sent in full.
+async fn send_constraints_to_relays(
+    state: &PbsState<BuilderState>,
+    constraints: &[SignedConstraints],
+) -> Result<Vec<RelayOutcome>, PbsClientError> {
+    debug!("Sending {} constraints to {} relays", constraints.len(), state.relays().len());
+    let hash_refs = if state.data.hash_ref_relays.any() {
+        constraints.iter().map(HashRefConstraints::try_from).collect::<Result<Vec<_>, _>>().ok()
+    } else {
+        None
+    };
+
+    let mut responses = FuturesUnordered::new();
+    for relay in state.relays() {
+        let url = relay.get_url(SUBMIT_CONSTRAINTS_PATH).map_err(|_| PbsClientError::BadRequest)?;
+        let hash_refs =
+            hash_refs.as_deref().filter(|_| state.data.hash_ref_relays.supports(&relay.id));
+        responses.push(send_constraints_to_relay(state, relay, url, constraints, hash_refs));
+    }
+
+    let mut outcomes = Vec::with_capacity(responses.len());
+    while let Some(outcome) = responses.next().await {
+        outcomes.push(outcome);
+    }
+
+    Ok(outcomes)
+}
+
+/// Sends constraints to a relay, referencing their transactions by hash if given the
+/// hash references, and falling back to the full constraints if the relay rejects them.
+async fn send_constraints_to_relay(
+    state: &PbsState<BuilderState>,
+    relay: &RelayClient,
+    url: Url,
+    constraints: &[SignedConstraints],
+    hash_refs: Option<&[HashRefConstraints]>,
+) -> RelayOutcome {
+    let relay_id = relay.id.to_string();
+    let hash_ref_relays = &state.data.hash_ref_relays;
+
+    if let Some(hash_refs) = hash_refs {
+        let request = relay
+            .client
+            .post(url.clone())
+            .header(CONSTRAINTS_ENCODING_HEADER, HASH_REFS_ENCODING)
+            .json(&hash_refs);
+
+        match request.send().await {
+            Ok(response) if response.status() == StatusCode::OK => {
+                debug!(%url, "Successfully sent constraints by hash to relay");
+                hash_ref_relays.record_response(&relay_id, response.headers());
+                return (relay_id, Some(StatusCode::OK), utcnow_ms());
+            }
+            Ok(response) => {
+                let status = response.status();
+                if status == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                    hash_ref_relays.forget(&relay_id);
+                }
+                let body = response.text().await.ok();
+                warn!(%status, %url, "Relay rejected constraints by hash, sending in full: {body:?}");
+                HASH_REF_FALLBACKS.with_label_values(&[&relay_id]).inc();
+            }
+            Err(e) => {
+                error!(error = ?e, relay_id, "Failed to POST constraints by hash to relay");
+                return (relay_id, None, utcnow_ms());
             }
-            Err(e) => error!(error = ?e, "Failed to POST to relay"),
         }
     }
 
-    if success {
+    match relay.client.post(url).json(constraints).send().await {
+        Ok(response) => {
+            let url = response.url().clone();
+            let status = response.status();
+            if status != StatusCode::OK {
+                let body = response.text().await.ok();
+                error!(%status, %url, "Failed to POST to relay: {body:?}");
+            } else {
+                debug!(%url, "Successfully sent POST request to relay");
+                hash_ref_relays.record_response(&relay_id, response.headers());
+            }
+            (relay_id, Some(status), utcnow_ms())
+        }
+        Err(e) => {
+            error!(error = ?e, relay_id, "Failed to POST to relay");
+            (relay_id, None, utcnow_ms())
+        }
+    }
+}
+
+/// Succeeds if at least one relay acknowledged the request.
+fn require_acknowledged(outcomes: &[RelayOutcome]) -> Result<(), PbsClientError> {
+    if outcomes.iter().any(|(_, status, _)| *status == Some(StatusCode::OK)) {
         Ok(())
     } else {
         Err(PbsClientError::NoResponse)
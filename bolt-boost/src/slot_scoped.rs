@@ -0,0 +1,61 @@
+use std::collections::{btree_map, BTreeMap};
+
+/// In-memory state scoped to slots, torn down as the chain advances.
+///
+/// Mirrors the `SlotScoped` cache of the sidecar, but is advanced to the current slot of the
+/// wall clock rather than to the head slot. Every cache keyed by slot should use this instead
+/// of pruning its entries by hand.
+#[derive(Debug)]
+pub struct SlotScoped<T> {
+    entries: BTreeMap<u64, T>,
+}
+
+impl<T> Default for SlotScoped<T> {
+    fn default() -> Self {
+        Self { entries: BTreeMap::new() }
+    }
+}
+
+impl<T> SlotScoped<T> {
+    /// Returns the state of the given slot, if any.
+    pub fn get(&self, slot: u64) -> Option<&T> {
+        self.entries.get(&slot)
+    }
+
+    /// Returns a mutable reference to the state of the given slot, if any.
+    pub fn get_mut(&mut self, slot: u64) -> Option<&mut T> {
+        self.entries.get_mut(&slot)
+    }
+
+    /// Sets the state of the given slot, returning the previous one if any.
+    pub fn insert(&mut self, slot: u64, value: T) -> Option<T> {
+        self.entries.insert(slot, value)
+    }
+
+    /// Removes and returns the state of the given slot, if any.
+    pub fn remove(&mut self, slot: u64) -> Option<T> {
+        self.entries.remove(&slot)
+    }
+
+    /// Returns true if the given slot has state.
+    pub fn contains(&self, slot: u64) -> bool {
+        self.entries.contains_key(&slot)
+    }
+
+    /// Returns an iterator over the states, by ascending slot.
+    pub fn values(&self) -> btree_map::Values<'_, u64, T> {
+        self.entries.values()
+    }
+
+    /// Returns an iterator over the slots and their states, by ascending slot.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.entries.iter().map(|(slot, value)| (*slot, value))
+    }
+
+    /// Drops the state of every slot before the given one. Returns the dropped states,
+    /// by ascending slot.
+    pub fn advance_to(&mut self, slot: u64) -> BTreeMap<u64, T> {
+        let kept = self.entries.split_off(&slot);
+        std::mem::replace(&mut self.entries, kept)
+    }
+}
@@ -1,4 +1,4 @@
-use alloy::primitives::{Bytes, B256};
+use alloy::primitives::{bytes, Bytes, B256};
 use ssz_compat::Decode;
 use types::{ExecPayload, MainnetEthSpec, SignedBeaconBlockDeneb};
 
@@ -7,6 +7,12 @@ const TEST_BLOCK: &[u8] = include_bytes!(concat!(
     "/testdata/signed-mainnet-beacon-block.bin.ssz"
 ));
 
+/// A signed legacy contract creation, EIP-2718 encoded.
+pub const TEST_CONTRACT_CREATION_TX: Bytes = bytes!("f86481d8088302088a808090435b8080556001015a6161a8106001578718e5bb3abd109fa0ea5ad6553fb67639cec694e6697ac7b718bd7044fcdf5608fa64f6058e67db93a03953b5792d7d9ef7fc602fbe260e7a290760e8adc634f99ab1896e2c0d55afcb");
+
+/// A signed legacy transfer to the `0xdead` address, EIP-2718 encoded.
+pub const TEST_TRANSFER_TX: Bytes = bytes!("f86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471");
+
 /// Reads and decodes a signed beacon block from `testdata`.
 pub fn read_test_block() -> SignedBeaconBlockDeneb<MainnetEthSpec> {
     SignedBeaconBlockDeneb::from_ssz_bytes(TEST_BLOCK).unwrap()
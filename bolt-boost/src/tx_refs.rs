@@ -0,0 +1,161 @@
+use alloy::{
+    eips::eip2718::{Eip2718Error, Eip2718Result},
+    primitives::TxHash,
+    rpc::types::beacon::{BlsPublicKey, BlsSignature},
+};
+use axum::http::HeaderMap;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::types::SignedConstraints;
+
+/// The header naming the encoding of a constraints submission sent to a relay.
+pub(crate) const CONSTRAINTS_ENCODING_HEADER: &str = "x-bolt-constraints-encoding";
+
+/// The header with which relays advertise the encodings of constraints submissions they accept,
+/// as a comma-separated list.
+pub(crate) const ACCEPT_CONSTRAINTS_ENCODING_HEADER: &str = "x-bolt-accept-constraints-encoding";
+
+/// The encoding of constraints referencing their transactions by hash.
+pub(crate) const HASH_REFS_ENCODING: &str = "hash-refs";
+
+/// Signed constraints referencing their transactions by hash rather than by raw bytes,
+/// for relays that already hold the transactions.
+///
+/// The digest of a constraints message only commits to the hashes of its transactions,
+/// so relays can verify the signature before resolving them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HashRefConstraints {
+    pub message: HashRefConstraintsMessage,
+    pub signature: BlsSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HashRefConstraintsMessage {
+    pub pubkey: BlsPublicKey,
+    pub slot: u64,
+    pub top: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
+    pub transaction_hashes: Vec<TxHash>,
+}
+
+impl TryFrom<&SignedConstraints> for HashRefConstraints {
+    type Error = Eip2718Error;
+
+    fn try_from(value: &SignedConstraints) -> Eip2718Result<Self> {
+        let message = HashRefConstraintsMessage {
+            pubkey: value.message.pubkey,
+            slot: value.message.slot,
+            top: value.message.top,
+            index: value.message.index,
+            transaction_hashes: value.message.transaction_hashes()?,
+        };
+
+        Ok(Self { message, signature: value.signature })
+    }
+}
+
+/// The relays that negotiated constraints submissions referencing transactions by hash.
+///
+/// A relay is added once it advertises the [HASH_REFS_ENCODING] in the
+/// [ACCEPT_CONSTRAINTS_ENCODING_HEADER] of a response to a constraints submission, and
+/// removed as soon as a response doesn't advertise it anymore.
+#[derive(Clone, Default, Debug)]
+pub struct HashRefRelays {
+    relays: Arc<RwLock<HashSet<String>>>,
+}
+
+impl HashRefRelays {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the given relay accepts constraints referencing transactions by hash.
+    pub fn supports(&self, relay_id: &str) -> bool {
+        self.relays.read().contains(relay_id)
+    }
+
+    /// Returns true if any relay accepts constraints referencing transactions by hash.
+    pub fn any(&self) -> bool {
+        !self.relays.read().is_empty()
+    }
+
+    /// Records the encodings advertised in the headers of a response of the given relay to
+    /// a constraints submission.
+    pub fn record_response(&self, relay_id: &str, headers: &HeaderMap) {
+        let advertised = headers
+            .get_all(ACCEPT_CONSTRAINTS_ENCODING_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|encoding| encoding.trim().eq_ignore_ascii_case(HASH_REFS_ENCODING));
+
+        self.record_advertised(relay_id, advertised);
+    }
+
+    /// Records whether the given relay advertises the [HASH_REFS_ENCODING], e.g. among the
+    /// encodings of its capabilities.
+    pub fn record_advertised(&self, relay_id: &str, advertised: bool) {
+        if advertised {
+            self.relays.write().insert(relay_id.to_string());
+        } else {
+            self.forget(relay_id);
+        }
+    }
+
+    /// Stops sending hash references to the given relay until it advertises them again.
+    pub fn forget(&self, relay_id: &str) {
+        self.relays.write().remove(relay_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use crate::{testutil::read_test_transactions, types::ConstraintsMessage};
+
+    use super::*;
+
+    #[test]
+    fn test_hash_ref_negotiation() {
+        let relays = HashRefRelays::new();
+        assert!(!relays.any());
+
+        let mut headers = HeaderMap::new();
+        headers
+            .insert(ACCEPT_CONSTRAINTS_ENCODING_HEADER, HeaderValue::from_static("raw, hash-refs"));
+        relays.record_response("titan", &headers);
+        relays.record_response("aestus", &HeaderMap::new());
+
+        assert!(relays.supports("titan"));
+        assert!(!relays.supports("aestus"));
+
+        // Support is withdrawn by any response that doesn't advertise it
+        relays.record_response("titan", &HeaderMap::new());
+        assert!(!relays.any());
+    }
+
+    #[test]
+    fn test_hash_ref_constraints() {
+        let (_, transactions) = read_test_transactions();
+        let message = ConstraintsMessage {
+            pubkey: BlsPublicKey::repeat_byte(1),
+            slot: 10,
+            top: false,
+            index: None,
+            transactions: transactions[..2].to_vec(),
+        };
+        let signed = SignedConstraints { message, signature: BlsSignature::repeat_byte(2) };
+
+        let compact = HashRefConstraints::try_from(&signed).unwrap();
+        assert_eq!(compact.message.slot, 10);
+        assert_eq!(compact.signature, signed.signature);
+        assert_eq!(
+            compact.message.transaction_hashes,
+            signed.message.transaction_hashes().unwrap()
+        );
+    }
+}
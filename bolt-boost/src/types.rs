@@ -1,16 +1,18 @@
 use alloy::{
-    consensus::{TxEip4844Variant, TxEnvelope},
+    consensus::{Transaction as _, TxEip4844Variant, TxEnvelope},
     eips::eip2718::{Decodable2718, Eip2718Error, Eip2718Result},
-    primitives::{Bytes, TxHash, B256},
+    primitives::{Address, Bytes, TxHash, B256},
     rpc::types::beacon::{BlsPublicKey, BlsSignature},
-    signers::k256::sha2::{Digest, Sha256},
 };
 use alloy_rlp::{BufMut, Encodable};
 use axum::http::HeaderMap;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
-use std::ops::Deref;
+use std::{
+    ops::{Deref, Range},
+    path::PathBuf,
+};
 use tracing::error;
 
 use cb_common::{
@@ -21,11 +23,99 @@ use cb_common::{
     types::Chain,
 };
 
+use crate::{
+    constraints::{DEFAULT_BLOCK_GAS_LIMIT, DEFAULT_MAX_BLOBS_PER_BLOCK},
+    relay_checks::RelayCheckMode,
+};
+
 /// A hash tree root.
 pub type HashTreeRoot = tree_hash::Hash256;
 
+/// Default latency budget for verifying the inclusion proofs of a single bid.
+const DEFAULT_PROOF_VERIFICATION_BUDGET_MS: u64 = 50;
+
+/// Default number of slots during which the bids of a relay that overstated a bid are skipped,
+/// one epoch.
+const DEFAULT_OVERSTATED_BID_PENALTY_SLOTS: u64 = 32;
+
+/// Default tolerance between the clocks of bolt-boost and of the sidecars signing requests.
+const DEFAULT_AUTH_MAX_CLOCK_SKEW_SECS: u64 = 30;
+
+/// The networks bolt runs on, which messages failing verification against the
+/// configured chain are checked against to report cross-network submissions.
+const KNOWN_CHAINS: [Chain; 3] = [Chain::Mainnet, Chain::Holesky, Chain::Helder];
+
 #[derive(Debug, Clone, Deserialize)]
-pub struct Config {}
+pub struct Config {
+    /// Latency budget in milliseconds for verifying the inclusion proofs of a bid.
+    /// Exceeding it only emits a warning, the bid is not discarded.
+    #[serde(default = "default_proof_verification_budget_ms")]
+    pub proof_verification_budget_ms: u64,
+    /// Tolerance in basis points of the bid value under which the payment of a payload
+    /// to the proposer is recorded as a discrepancy of the relay that served the bid.
+    #[serde(default)]
+    pub payment_tolerance_bps: u64,
+    /// Number of slots after a payload paying the proposer less than its bid during which
+    /// the bids of the relay that served it are skipped. Set to 0 to never skip them.
+    #[serde(default = "default_overstated_bid_penalty_slots")]
+    pub overstated_bid_penalty_slots: u64,
+    /// The commitment signer addresses of the sidecars allowed to submit constraints,
+    /// delegations and revocations. If empty, submissions are not authenticated.
+    #[serde(default)]
+    pub authorized_sidecars: Vec<Address>,
+    /// Max difference in seconds between the signing time of an authenticated request
+    /// and the time it is received, bounding the window in which it can be replayed.
+    #[serde(default = "default_auth_max_clock_skew_secs")]
+    pub auth_max_clock_skew_secs: u64,
+    /// Max gas that can be constrained per proposer and slot, e.g. the max committed gas of
+    /// the sidecars. Constraints exceeding it are rejected. If not set, the gas is not capped.
+    #[serde(default)]
+    pub max_constrained_gas_per_slot: Option<u64>,
+    /// The gas limit of a block. Constraints whose transactions exceed it along with the other
+    /// constraints of the same proposer in the slot are rejected.
+    #[serde(default = "default_block_gas_limit")]
+    pub block_gas_limit: u64,
+    /// The max number of blobs per block. Constraints whose transactions exceed it along with
+    /// the other constraints of the same proposer in the slot are rejected.
+    #[serde(default = "default_max_blobs_per_block")]
+    pub max_blobs_per_block: u64,
+    /// The commitment signer addresses of the authorized sidecars submitting constraints for
+    /// the same validators, e.g. a primary and a backup sidecar, by descending precedence.
+    /// Identical constraints are deduplicated, and diverging ones are resolved in favor of
+    /// the sidecar with the highest precedence. If empty, constraints are not reconciled.
+    #[serde(default)]
+    pub sidecar_precedence: Vec<Address>,
+    /// Path to a database in which the accepted constraints are persisted before being
+    /// forwarded, to recover those of the current and upcoming slots after a restart.
+    /// If not set, constraints are only kept in memory.
+    #[serde(default)]
+    pub constraints_db_path: Option<PathBuf>,
+    /// How to handle relays failing the startup checks of their reachability, Constraints
+    /// API support and chain: `strict` refuses to start, `lenient` only logs warnings, and
+    /// `disabled` skips the checks.
+    #[serde(default)]
+    pub relay_checks: RelayCheckMode,
+}
+
+fn default_proof_verification_budget_ms() -> u64 {
+    DEFAULT_PROOF_VERIFICATION_BUDGET_MS
+}
+
+fn default_overstated_bid_penalty_slots() -> u64 {
+    DEFAULT_OVERSTATED_BID_PENALTY_SLOTS
+}
+
+fn default_auth_max_clock_skew_secs() -> u64 {
+    DEFAULT_AUTH_MAX_CLOCK_SKEW_SECS
+}
+
+fn default_block_gas_limit() -> u64 {
+    DEFAULT_BLOCK_GAS_LIMIT
+}
+
+fn default_max_blobs_per_block() -> u64 {
+    DEFAULT_MAX_BLOBS_PER_BLOCK
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct GetHeaderParams {
@@ -41,22 +131,59 @@ pub struct SignedConstraints {
 }
 
 impl SignedConstraints {
-    /// Verifies the signature on this message against the provided BLS public key.
+    /// Verifies the signature on this message against the public key of its signer.
     /// The `chain` and `COMMIT_BOOST_DOMAIN` are used to compute the signing root.
-    #[allow(unused)]
-    pub fn verify_signature(&self, chain: Chain, pubkey: &BlsPublicKey) -> bool {
-        let domain = compute_domain(chain, COMMIT_BOOST_DOMAIN);
-        let digest = match self.message.digest() {
-            Ok(digest) => digest,
-            Err(e) => {
-                error!(err = ?e, "Failed to compute digest");
-                return false;
-            }
-        };
+    pub fn verify_signature(&self, chain: Chain) -> Result<(), SignatureError> {
+        let digest = self.message.digest().inspect_err(|e| {
+            error!(err = ?e, "Failed to compute digest");
+        })?;
+
+        verify_commit_boost_signature(chain, &self.message.pubkey, digest, &self.signature)
+    }
+}
+
+/// An error verifying the signature of a constraints, delegation or revocation message.
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("failed to compute the message digest: {0}")]
+    Digest(#[from] Eip2718Error),
+    #[error("message is signed for {signed:?}, but bolt-boost is configured for {configured:?}")]
+    WrongChain { signed: Chain, configured: Chain },
+    #[error("invalid signature")]
+    Invalid,
+}
 
+/// Verifies a signature over the digest of a message with the commit-boost domain of
+/// the configured chain.
+///
+/// The signing domain commits to the fork version of the chain, so a message signed for
+/// another network never verifies. If it does with the domain of another known chain,
+/// [SignatureError::WrongChain] is returned instead of a generic invalid signature.
+pub fn verify_commit_boost_signature(
+    chain: Chain,
+    pubkey: &BlsPublicKey,
+    digest: [u8; 32],
+    signature: &BlsSignature,
+) -> Result<(), SignatureError> {
+    let domain = compute_domain(chain, COMMIT_BOOST_DOMAIN);
+    let verify = |domain| {
         let signing_root = compute_signing_root(digest, domain);
-        verify_bls_signature(pubkey, &signing_root, &self.signature).is_ok()
+        verify_bls_signature(pubkey, &signing_root, signature).is_ok()
+    };
+
+    if verify(domain) {
+        return Ok(());
     }
+
+    KNOWN_CHAINS
+        .into_iter()
+        .find(|other| {
+            let other_domain = compute_domain(*other, COMMIT_BOOST_DOMAIN);
+            other_domain != domain && verify(other_domain)
+        })
+        .map_or(Err(SignatureError::Invalid), |signed| {
+            Err(SignatureError::WrongChain { signed, configured: chain })
+        })
 }
 
 #[derive(Debug, Clone, Serialize, Eq, PartialEq, Deserialize, Encode, Decode)]
@@ -64,23 +191,52 @@ pub struct ConstraintsMessage {
     pub pubkey: BlsPublicKey,
     pub slot: u64,
     pub top: bool,
+    /// The position in the block of the first transaction of a top-of-block bundle, the
+    /// others taking the next positions in order. Top-of-block bundles without an index start
+    /// at the top of the block. Constraints are only accepted as JSON, so the derived SSZ
+    /// encoding skips it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ssz(skip_serializing, skip_deserializing)]
+    pub index: Option<u64>,
     pub transactions: Vec<Bytes>,
 }
 
 impl ConstraintsMessage {
     /// Returns the digest of this message.
     pub fn digest(&self) -> Eip2718Result<[u8; 32]> {
-        let mut hasher = Sha256::new();
-        hasher.update(self.pubkey);
-        hasher.update(self.slot.to_le_bytes());
-        hasher.update((self.top as u8).to_le_bytes());
+        let tx_hashes = self.transaction_hashes()?;
+        Ok(bolt_primitives::constraints_digest(
+            self.pubkey.as_slice(),
+            self.slot,
+            self.top,
+            self.index,
+            tx_hashes,
+        ))
+    }
 
-        for bytes in &self.transactions {
-            let tx = TxEnvelope::decode_2718(&mut bytes.as_ref())?;
-            hasher.update(tx.tx_hash());
-        }
+    /// Returns the positions in the block taken by the transactions of a top-of-block
+    /// bundle, or None if the constraints are not top-of-block.
+    pub fn top_positions(&self) -> Option<Range<u64>> {
+        self.top.then(|| {
+            let start = self.index.unwrap_or_default();
+            start..start.saturating_add(self.transactions.len() as u64)
+        })
+    }
+
+    /// Returns the hashes of the transactions of this message, in order.
+    pub fn transaction_hashes(&self) -> Eip2718Result<Vec<TxHash>> {
+        self.transactions
+            .iter()
+            .map(|bytes| Ok(*TxEnvelope::decode_2718(&mut bytes.as_ref())?.tx_hash()))
+            .collect()
+    }
 
-        Ok(hasher.finalize().into())
+    /// Returns the total gas limit of the transactions of this message.
+    pub fn gas_limit(&self) -> Eip2718Result<u64> {
+        self.transactions.iter().try_fold(0u64, |gas, bytes| {
+            let envelope = TxEnvelope::decode_2718(&mut bytes.as_ref())?;
+            Ok(gas.saturating_add(envelope.gas_limit().try_into().unwrap_or(u64::MAX)))
+        })
     }
 }
 
@@ -90,27 +246,48 @@ pub struct ConstraintsWithProofData {
     /// List of transaction hashes and corresponding hash tree roots. Same order
     /// as the transactions in the `message`.
     pub proof_data: Vec<(TxHash, HashTreeRoot)>,
+    /// The total gas limit of the transactions, which the builder can't monetize.
+    pub gas_limit: u64,
+    /// The total number of blobs of the transactions.
+    pub blob_count: u64,
 }
 
 impl TryFrom<ConstraintsMessage> for ConstraintsWithProofData {
     type Error = Eip2718Error;
 
     fn try_from(value: ConstraintsMessage) -> Result<Self, Self::Error> {
+        let mut gas_limit = 0u64;
+        let mut blob_count = 0u64;
         let transactions = value
             .transactions
             .iter()
             .map(|tx| {
                 let envelope = TxEnvelope::decode_2718(&mut tx.as_ref())?;
                 let tx_hash_tree_root = calculate_tx_hash_tree_root(&envelope, tx)?;
+                gas_limit =
+                    gas_limit.saturating_add(envelope.gas_limit().try_into().unwrap_or(u64::MAX));
+                blob_count += count_blobs(&envelope);
 
                 Ok((*envelope.tx_hash(), tx_hash_tree_root))
             })
             .collect::<Result<Vec<_>, Eip2718Error>>()?;
 
-        Ok(Self { message: value, proof_data: transactions })
+        Ok(Self { message: value, proof_data: transactions, gas_limit, blob_count })
     }
 }
 
+/// Returns the number of blobs of a transaction, zero unless it's a type 3 transaction.
+fn count_blobs(envelope: &TxEnvelope) -> u64 {
+    let hashes = match envelope {
+        TxEnvelope::Eip4844(tx) => match tx.tx() {
+            TxEip4844Variant::TxEip4844(tx) => &tx.blob_versioned_hashes,
+            TxEip4844Variant::TxEip4844WithSidecar(tx) => &tx.tx.blob_versioned_hashes,
+        },
+        _ => return 0,
+    };
+    hashes.len() as u64
+}
+
 /// Calculate the SSZ hash tree root of a transaction, starting from its enveloped form.
 /// For type 3 transactions, the hash tree root of the inner transaction is taken (without blobs).
 fn calculate_tx_hash_tree_root(
@@ -153,19 +330,68 @@ pub struct SignedDelegation {
     pub signature: BlsSignature,
 }
 
+impl SignedDelegation {
+    /// Verifies the signature of the validator on this message.
+    /// The `chain` and `COMMIT_BOOST_DOMAIN` are used to compute the signing root.
+    pub fn verify_signature(&self, chain: Chain) -> Result<(), SignatureError> {
+        let digest = self.message.digest();
+        verify_commit_boost_signature(
+            chain,
+            &self.message.validator_pubkey,
+            digest,
+            &self.signature,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct DelegationMessage {
     action: u8,
     pub validator_pubkey: BlsPublicKey,
     pub delegatee_pubkey: BlsPublicKey,
+    /// The limits of the constraints the delegatee can sign. Unscoped if missing.
+    #[serde(default, skip_serializing_if = "DelegationScope::is_unscoped")]
+    pub scope: DelegationScope,
+}
+
+impl DelegationMessage {
+    /// Returns the digest of this message. The scope is only part of the digest if set.
+    pub fn digest(&self) -> [u8; 32] {
+        bolt_primitives::delegation_digest(
+            self.action,
+            self.validator_pubkey.as_slice(),
+            self.delegatee_pubkey.as_slice(),
+            &self.scope,
+        )
+    }
 }
 
+/// The limits of the constraints a delegatee can sign on behalf of a validator, and their
+/// violations, shared with the sidecar and the CLI.
+pub use bolt_primitives::delegation::{
+    DelegationScope, ScopeViolation, INCLUSION_SCOPE, TOP_OF_BLOCK_SCOPE,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct SignedRevocation {
     pub message: RevocationMessage,
     pub signature: BlsSignature,
 }
 
+impl SignedRevocation {
+    /// Verifies the signature of the validator on this message.
+    /// The `chain` and `COMMIT_BOOST_DOMAIN` are used to compute the signing root.
+    pub fn verify_signature(&self, chain: Chain) -> Result<(), SignatureError> {
+        let digest = self.message.digest();
+        verify_commit_boost_signature(
+            chain,
+            &self.message.validator_pubkey,
+            digest,
+            &self.signature,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct RevocationMessage {
     action: u8,
@@ -173,6 +399,17 @@ pub struct RevocationMessage {
     pub delegatee_pubkey: BlsPublicKey,
 }
 
+impl RevocationMessage {
+    /// Returns the digest of this message.
+    pub fn digest(&self) -> [u8; 32] {
+        bolt_primitives::revocation_digest(
+            self.action,
+            self.validator_pubkey.as_slice(),
+            self.delegatee_pubkey.as_slice(),
+        )
+    }
+}
+
 pub type GetHeaderWithProofsResponse = VersionedResponse<SignedExecutionPayloadHeaderWithProofs>;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
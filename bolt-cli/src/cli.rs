@@ -1,3 +1,7 @@
+use std::str::FromStr;
+
+use alloy::primitives::{Address, B256};
+use bolt_sidecar::ChainConfig;
 use clap::{
     builder::styling::{AnsiColor, Color, Style},
     Parser, Subcommand, ValueEnum,
@@ -25,6 +29,24 @@ pub enum Cmd {
 
     /// Send a preconfirmation request to a Bolt proposer.
     Send(Box<SendCommand>),
+
+    /// Run an in-process devnet and send a preconfirmation request through it.
+    Devnet(DevnetCommand),
+
+    /// Generate deterministic constraints and inclusion proof fixtures.
+    Fixtures(FixturesCommand),
+
+    /// Dump or verify interop test vectors of the Constraints API messages.
+    Vectors(VectorsCommand),
+
+    /// Run time-sliced benchmarks of the sidecar hot paths, optionally compared to a baseline.
+    Bench(BenchCommand),
+
+    /// Continuously probe the proposal slots of a validator set with inclusion requests.
+    Canary(CanaryCommand),
+
+    /// Verify a signed commitment and whether a block satisfies it.
+    VerifyCommitment(VerifyCommitmentCommand),
 }
 
 impl Cmd {
@@ -34,6 +56,12 @@ impl Cmd {
             Cmd::Delegate(cmd) => cmd.run().await,
             Cmd::Pubkeys(cmd) => cmd.run().await,
             Cmd::Send(cmd) => cmd.run().await,
+            Cmd::Devnet(cmd) => cmd.run().await,
+            Cmd::Fixtures(cmd) => cmd.run().await,
+            Cmd::Vectors(cmd) => cmd.run().await,
+            Cmd::Bench(cmd) => cmd.run().await,
+            Cmd::Canary(cmd) => cmd.run().await,
+            Cmd::VerifyCommitment(cmd) => cmd.run().await,
         }
     }
 }
@@ -49,6 +77,11 @@ pub struct DelegateCommand {
     #[clap(long, env = "OUTPUT_FILE_PATH", default_value = "delegations.json")]
     pub out: String,
 
+    /// The password to encrypt the output file with.
+    /// If not provided, the file is written in plaintext.
+    #[clap(long, env = "DELEGATIONS_PASSWORD")]
+    pub password: Option<String>,
+
     /// The chain for which the delegation message is intended.
     #[clap(long, env = "CHAIN", default_value = "mainnet")]
     pub chain: Chain,
@@ -58,6 +91,21 @@ pub struct DelegateCommand {
     #[clap(long, env = "ACTION", default_value = "delegate")]
     pub action: Action,
 
+    /// The max gas the delegatee can constrain per slot.
+    /// If not provided, the gas is not limited.
+    #[clap(long, env = "DELEGATION_MAX_GAS_PER_SLOT")]
+    pub max_gas_per_slot: Option<u64>,
+
+    /// The commitment types the delegatee can sign constraints for, comma-separated.
+    /// If not provided, all commitment types are allowed.
+    #[clap(long, env = "DELEGATION_COMMITMENT_TYPES", value_delimiter = ',')]
+    pub commitment_types: Vec<CommitmentType>,
+
+    /// The last slot the delegatee can sign constraints for.
+    /// If not provided, the delegation doesn't expire.
+    #[clap(long, env = "DELEGATION_EXPIRY_SLOT")]
+    pub expiry_slot: Option<u64>,
+
     /// The source of the private key.
     #[clap(subcommand)]
     pub source: KeySource,
@@ -101,6 +149,11 @@ pub struct SendCommand {
     #[clap(long, env = "BLOB", default_value = "false")]
     pub blob: bool,
 
+    /// If set, requests rejected for their target slot are sent again for the first slot
+    /// suggested by the sidecar in its rejection, if any.
+    #[clap(long, env = "AUTO_RETARGET", default_value = "false")]
+    pub auto_retarget: bool,
+
     /// If set, the transaction will target the devnet environment.
     /// This is only used in Kurtosis for internal testing purposes
     #[clap(long, hide = true, env = "DEVNET", default_value = "false")]
@@ -119,6 +172,230 @@ pub struct SendCommand {
     pub devnet_sidecar_url: Option<Url>,
 }
 
+/// Command for running an in-process devnet, made of a mock beacon node, a mock relay
+/// and a Bolt sidecar, and sending a preconfirmation request through it.
+#[derive(Debug, Clone, Parser)]
+pub struct DevnetCommand {
+    /// The URL of the execution client to validate and fill transactions with.
+    #[clap(long, env = "EXECUTION_URL", default_value = "http://localhost:8545")]
+    pub execution_url: Url,
+
+    /// The URL of the Engine API of the execution client, used by the sidecar
+    /// to build fallback blocks.
+    #[clap(long, env = "ENGINE_URL", default_value = "http://localhost:8551")]
+    pub engine_url: Url,
+
+    /// The JWT secret to authenticate to the Engine API, in hex format.
+    /// If not provided, fallback block building is disabled.
+    #[clap(long, env = "ENGINE_JWT_HEX", hide_env_values = true)]
+    pub engine_jwt_hex: Option<String>,
+
+    /// The private key to sign the transaction with.
+    /// Defaults to the first pre-funded account of Anvil and Hardhat.
+    #[clap(
+        long,
+        env = "PRIVATE_KEY",
+        hide_env_values = true,
+        default_value = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+    )]
+    pub private_key: String,
+
+    /// The slot time of the devnet, in seconds.
+    #[clap(long, env = "SLOT_TIME", default_value = "4")]
+    pub slot_time: u64,
+
+    /// The port of the sidecar commitments JSON-RPC API.
+    #[clap(long, env = "RPC_PORT", default_value = "8000")]
+    pub rpc_port: u16,
+
+    /// The port of the sidecar builder proxy.
+    #[clap(long, env = "CONSTRAINTS_PROXY_PORT", default_value = "18551")]
+    pub constraints_proxy_port: u16,
+
+    /// If set, keep the devnet running after the flow has completed, until CTRL+C.
+    #[clap(long, env = "KEEP_ALIVE", default_value = "false")]
+    pub keep_alive: bool,
+}
+
+/// Command for generating deterministic constraints and inclusion proof fixtures.
+#[derive(Debug, Clone, Parser)]
+pub struct FixturesCommand {
+    /// The seed from which keys and transactions are derived.
+    #[clap(long, env = "SEED", default_value = "0")]
+    pub seed: u64,
+
+    /// The slot of the constraints.
+    #[clap(long, env = "SLOT", default_value = "1")]
+    pub slot: u64,
+
+    /// The number of signed constraints messages.
+    #[clap(long, env = "CONSTRAINTS", default_value = "2")]
+    pub constraints: usize,
+
+    /// The number of transactions in each constraints message.
+    #[clap(long, env = "TXS_PER_CONSTRAINT", default_value = "2")]
+    pub txs_per_constraint: usize,
+
+    /// The number of unconstrained transactions in the payload.
+    #[clap(long, env = "UNCONSTRAINED_TXS", default_value = "4")]
+    pub unconstrained_txs: usize,
+
+    /// The chain whose signing domain and chain ID are used.
+    #[clap(long, env = "CHAIN", default_value = "mainnet")]
+    pub chain: Chain,
+
+    /// The output directory for the fixture files.
+    #[clap(long, env = "OUTPUT_DIR", default_value = "fixtures")]
+    pub out: String,
+}
+
+/// Command for dumping or verifying interop test vectors of the Constraints API messages.
+#[derive(Debug, Clone, Parser)]
+pub struct VectorsCommand {
+    /// The action to perform on the test vectors.
+    #[clap(subcommand)]
+    pub action: VectorsAction,
+}
+
+/// The action to perform on the test vectors.
+#[derive(Debug, Clone, Subcommand)]
+pub enum VectorsAction {
+    /// Generate the test vectors and write them to a directory.
+    Dump {
+        /// The seed from which keys and transactions are derived.
+        #[clap(long, env = "SEED", default_value = "0")]
+        seed: u64,
+
+        /// The chain whose signing domain is used.
+        #[clap(long, env = "CHAIN", default_value = "mainnet")]
+        chain: Chain,
+
+        /// The output directory for the test vector files.
+        #[clap(long, env = "OUTPUT_DIR", default_value = "vectors")]
+        out: String,
+    },
+
+    /// Verify test vectors produced by another implementation.
+    Verify {
+        /// The path to the `vectors.json` file.
+        #[clap(long, env = "VECTORS_PATH", default_value = "vectors/vectors.json")]
+        path: String,
+
+        /// The chain whose signing domain is expected.
+        #[clap(long, env = "CHAIN", default_value = "mainnet")]
+        chain: Chain,
+    },
+}
+
+/// Command for running time-sliced benchmarks of the sidecar hot paths on a synthetic load.
+#[derive(Debug, Clone, Parser)]
+pub struct BenchCommand {
+    /// The number of inclusion requests of the synthetic load.
+    #[clap(long, env = "REQUESTS", default_value = "50")]
+    pub requests: usize,
+
+    /// The number of transactions in each inclusion request.
+    #[clap(long, env = "TXS_PER_REQUEST", default_value = "2")]
+    pub txs_per_request: usize,
+
+    /// The time slice during which each hot path is run, in milliseconds.
+    #[clap(long, env = "SLICE_MS", default_value = "1000")]
+    pub slice_ms: u64,
+
+    /// The path of the JSON report to write, if any.
+    #[clap(long, env = "OUTPUT_PATH")]
+    pub out: Option<String>,
+
+    /// The path of a JSON report of a previous run to compare against, if any.
+    #[clap(long, env = "BASELINE_PATH")]
+    pub baseline: Option<String>,
+
+    /// The maximum slowdown of the median of a hot path compared to the baseline, in percent.
+    #[clap(long, env = "MAX_SLOWDOWN_PCT", default_value = "10")]
+    pub max_slowdown_pct: f64,
+}
+
+/// Command for continuously probing the proposal slots of a validator set with small
+/// inclusion requests, and exporting the results as Prometheus metrics.
+#[derive(Debug, Clone, Parser)]
+pub struct CanaryCommand {
+    /// Bolt RPC URL to send requests to and fetch lookahead info from.
+    #[clap(long, env = "BOLT_RPC_URL", default_value = "http://135.181.191.125:58017/rpc")]
+    pub bolt_rpc_url: Url,
+
+    /// The Bolt Sidecar URL to send requests to, overriding the canonical bolt RPC URL.
+    #[clap(long, env = "OVERRIDE_BOLT_SIDECAR_URL")]
+    pub override_bolt_sidecar_url: Option<Url>,
+
+    /// The URL of the beacon node to check the blocks of the probed slots with.
+    #[clap(long, env = "BEACON_URL", default_value = "http://localhost:5052")]
+    pub beacon_url: Url,
+
+    /// The private key to sign the probe transactions with.
+    #[clap(long, env = "PRIVATE_KEY", hide_env_values = true)]
+    pub private_key: String,
+
+    /// The indexes of the validators whose proposal slots are probed.
+    /// Multiple indexes must be separated by commas.
+    #[clap(long, env = "VALIDATOR_INDEXES", value_delimiter = ',', required = true)]
+    pub validator_indexes: Vec<u64>,
+
+    /// The port to serve the Prometheus metrics of the probes on.
+    #[clap(long, env = "METRICS_PORT", default_value = "9091")]
+    pub metrics_port: u16,
+
+    /// The interval at which the lookahead is polled for slots to probe, in seconds.
+    #[clap(long, env = "POLL_INTERVAL_SECS", default_value = "12")]
+    pub poll_interval_secs: u64,
+}
+
+/// Command for verifying a signed commitment and whether a block satisfies it.
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyCommitmentCommand {
+    /// The path to the signed commitment JSON, as returned by the sidecar, or `-` to read it
+    /// from the standard input.
+    #[clap(long, env = "COMMITMENT")]
+    pub commitment: String,
+
+    /// The block to check the commitment against, by execution block hash or by slot.
+    #[clap(long, env = "BLOCK")]
+    pub block: BlockRef,
+
+    /// The expected address of the commitment signer of the sidecar. If not provided, the
+    /// recovered signer is printed without being checked.
+    #[clap(long, env = "COMMITMENT_SIGNER")]
+    pub signer: Option<Address>,
+
+    /// The URL of the execution client to fetch the block and receipts from.
+    #[clap(long, env = "EXECUTION_URL", default_value = "http://localhost:8545")]
+    pub execution_url: Url,
+
+    /// The URL of the beacon node to map slots to blocks with.
+    #[clap(long, env = "BEACON_URL", default_value = "http://localhost:5052")]
+    pub beacon_url: Url,
+}
+
+/// A block, by execution block hash or by slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockRef {
+    /// The execution block with the given hash.
+    Hash(B256),
+    /// The block proposed at the given slot.
+    Slot(u64),
+}
+
+impl FromStr for BlockRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            s.parse().map(Self::Hash).map_err(|e| format!("invalid block hash: {e}"))
+        } else {
+            s.parse().map(Self::Slot).map_err(|e| format!("invalid slot: {e}"))
+        }
+    }
+}
+
 /// The action to perform.
 #[derive(Debug, Clone, ValueEnum)]
 #[clap(rename_all = "kebab_case")]
@@ -129,6 +406,16 @@ pub enum Action {
     Revoke,
 }
 
+/// The commitment types a delegation can be scoped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "kebab_case")]
+pub enum CommitmentType {
+    /// Inclusion constraints.
+    Inclusion,
+    /// Top-of-block constraints.
+    TopOfBlock,
+}
+
 #[derive(Debug, Clone, Parser)]
 pub enum KeySource {
     /// Use local secret keys to generate the signed messages.
@@ -152,6 +439,13 @@ pub enum KeySource {
         #[clap(flatten)]
         opts: DirkOpts,
     },
+
+    /// Use a remote Web3Signer to generate the signed messages.
+    Web3signer {
+        /// The options for connecting to the Web3Signer.
+        #[clap(flatten)]
+        opts: Web3SignerOpts,
+    },
 }
 
 /// Options for reading a keystore folder.
@@ -202,6 +496,18 @@ pub struct DirkOpts {
     pub tls_credentials: TlsCredentials,
 }
 
+/// Options for connecting to a Web3Signer.
+#[derive(Debug, Clone, Parser)]
+pub struct Web3SignerOpts {
+    /// The URL of the Web3Signer.
+    #[clap(long, env = "WEB3SIGNER_URL")]
+    pub url: Url,
+
+    /// The TLS credentials for connecting to the Web3Signer.
+    #[clap(flatten)]
+    pub tls_credentials: TlsCredentials,
+}
+
 /// TLS credentials for connecting to a remote server.
 #[derive(Debug, Clone, PartialEq, Eq, Parser)]
 pub struct TlsCredentials {
@@ -236,6 +542,17 @@ impl Chain {
             Chain::Kurtosis => [16, 0, 0, 56],
         }
     }
+
+    /// Get the sidecar chain config for the given chain. Only the chain ID and
+    /// signing domain are meaningful, not the slot timings.
+    pub fn config(&self) -> ChainConfig {
+        match self {
+            Chain::Mainnet => ChainConfig::mainnet(),
+            Chain::Holesky => ChainConfig::holesky(),
+            Chain::Helder => ChainConfig::helder(),
+            Chain::Kurtosis => ChainConfig::kurtosis(12, 8_000),
+        }
+    }
 }
 
 /// Styles for the CLI application.
@@ -0,0 +1,102 @@
+use std::{fs, time::Duration};
+
+use bolt_sidecar::bench::{regressions, run_hot_paths, BenchReport, Workload};
+use eyre::{bail, Context, Result};
+use tracing::info;
+
+use crate::cli::BenchCommand;
+
+impl BenchCommand {
+    /// Run the `bench` command.
+    pub async fn run(self) -> Result<()> {
+        let workload = Workload::generate(self.requests, self.txs_per_request)?;
+        let slice = Duration::from_millis(self.slice_ms);
+
+        info!(
+            requests = self.requests,
+            txs_per_request = self.txs_per_request,
+            "Running hot paths"
+        );
+        let reports = run_hot_paths(&workload, slice);
+
+        println!(
+            "{:<24} {:>12} {:>12} {:>12} {:>12}",
+            "hot path", "iterations", "p50 (ns)", "p99 (ns)", "mean (ns)"
+        );
+        for report in &reports {
+            println!(
+                "{:<24} {:>12} {:>12} {:>12} {:>12}",
+                report.name, report.iterations, report.p50_ns, report.p99_ns, report.mean_ns
+            );
+        }
+
+        if let Some(out) = &self.out {
+            fs::write(out, serde_json::to_string_pretty(&reports)?)
+                .wrap_err_with(|| format!("Failed to write report to {out}"))?;
+            println!("Report written to {out}");
+        }
+
+        let Some(baseline) = &self.baseline else { return Ok(()) };
+        let baseline: Vec<BenchReport> = serde_json::from_str(
+            &fs::read_to_string(baseline)
+                .wrap_err_with(|| format!("Failed to read baseline {baseline}"))?,
+        )?;
+
+        let regressions = regressions(&reports, &baseline, self.max_slowdown_pct);
+        if !regressions.is_empty() {
+            let list = regressions
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{}: {} ns -> {} ns (+{:.1}%)",
+                        r.name, r.baseline_p50_ns, r.p50_ns, r.slowdown_pct
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!("Hot paths slower than {}% over the baseline: {list}", self.max_slowdown_pct);
+        }
+
+        println!("No regression over the baseline");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use bolt_sidecar::bench::BenchReport;
+
+    use crate::cli::BenchCommand;
+
+    #[tokio::test]
+    async fn test_bench_against_baseline() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let out = format!("{}/report.json", dir.path().to_string_lossy());
+
+        let bench = |out, baseline| BenchCommand {
+            requests: 2,
+            txs_per_request: 1,
+            slice_ms: 1,
+            out,
+            baseline,
+            max_slowdown_pct: 10.0,
+        };
+        bench(Some(out.clone()), None).run().await?;
+
+        // A run is never slower than its own report by orders of magnitude
+        let mut cmd = bench(None, Some(out.clone()));
+        cmd.max_slowdown_pct = 1e9;
+        cmd.run().await?;
+
+        // An impossibly fast baseline is always regressed from
+        let mut baseline: Vec<BenchReport> = serde_json::from_str(&fs::read_to_string(&out)?)?;
+        baseline.iter_mut().for_each(|report| report.p50_ns = 0);
+        let baseline_path = format!("{}/baseline.json", dir.path().to_string_lossy());
+        fs::write(&baseline_path, serde_json::to_string(&baseline)?)?;
+        assert!(bench(None, Some(baseline_path)).run().await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
+
+use alloy::{
+    eips::eip2718::Encodable2718,
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::B256,
+    providers::{Provider, ProviderBuilder, SendableTx},
+    signers::local::PrivateKeySigner,
+};
+use eyre::{bail, Context, ContextCompat, Result};
+use metrics::{counter, describe_counter, describe_gauge, gauge};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::{
+    cli::CanaryCommand,
+    commands::send::{
+        create_tx_request, prepare_rpc_request, request_current_slot_number, sign_request,
+        LookaheadSlot, BOLT_LOOKAHEAD_PATH,
+    },
+};
+
+/// Counter for the number of canary probes, by result.
+const CANARY_PROBES: &str = "bolt_canary_probes";
+/// Gauge for the last slot probed by the canary.
+const CANARY_LAST_PROBED_SLOT: &str = "bolt_canary_last_probed_slot";
+/// Gauge for the time taken by the sidecar to respond to the last probe, in milliseconds.
+const CANARY_COMMITMENT_LATENCY_MS: &str = "bolt_canary_commitment_latency_ms";
+
+/// The interval at which the head slot is polled while waiting for a probed slot.
+const HEAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The result of a canary probe of a proposal slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// The request was committed to and its transaction included in the probed slot.
+    Pass,
+    /// The sidecar rejected the request.
+    Rejected,
+    /// The request was committed to, but the probed slot was missed.
+    Missed,
+    /// The request was committed to, but its transaction wasn't included in the probed slot.
+    NotIncluded,
+    /// The probe couldn't complete, e.g. because the sidecar was unreachable.
+    Error,
+}
+
+impl ProbeResult {
+    /// Returns the label of the result in the metrics.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Rejected => "rejected",
+            Self::Missed => "missed",
+            Self::NotIncluded => "not_included",
+            Self::Error => "error",
+        }
+    }
+
+    /// Returns whether the probe passed.
+    pub const fn is_pass(&self) -> bool {
+        matches!(self, Self::Pass)
+    }
+}
+
+impl CanaryCommand {
+    /// Run the `canary` command.
+    pub async fn run(self) -> Result<()> {
+        let wallet: PrivateKeySigner = self.private_key.parse().wrap_err("invalid private key")?;
+
+        let metrics_addr = SocketAddr::from(([0, 0, 0, 0], self.metrics_port));
+        if let Err(err) = PrometheusBuilder::new().with_http_listener(metrics_addr).install() {
+            bail!("failed to install Prometheus recorder: {:?}", err);
+        }
+        describe_counter!(CANARY_PROBES, "Canary probes of proposal slots, by result");
+        describe_gauge!(CANARY_LAST_PROBED_SLOT, "Last proposal slot probed by the canary");
+        describe_gauge!(CANARY_COMMITMENT_LATENCY_MS, "Commitment latency of the last probe");
+        info!("Serving canary metrics at: http://{}", metrics_addr);
+
+        let validators = self.validator_indexes.iter().copied().collect::<HashSet<_>>();
+        let mut last_probed = 0;
+
+        loop {
+            let slot = match self.next_probe_slot(&validators, last_probed).await {
+                Ok(Some(slot)) => slot,
+                Ok(None) => {
+                    tokio::time::sleep(Duration::from_secs(self.poll_interval_secs)).await;
+                    continue;
+                }
+                Err(err) => {
+                    warn!(?err, "Failed to fetch the lookahead");
+                    tokio::time::sleep(Duration::from_secs(self.poll_interval_secs)).await;
+                    continue;
+                }
+            };
+
+            last_probed = slot;
+            let result = match self.probe(&wallet, slot).await {
+                Ok(result) => result,
+                Err(err) => {
+                    warn!(?err, slot, "Canary probe failed to complete");
+                    ProbeResult::Error
+                }
+            };
+
+            counter!(CANARY_PROBES, &[("result", result.as_str())]).increment(1);
+            gauge!(CANARY_LAST_PROBED_SLOT).set(slot as f64);
+
+            if result.is_pass() {
+                info!(slot, "Canary probe passed");
+            } else {
+                warn!(slot, result = result.as_str(), "Canary probe failed");
+            }
+        }
+    }
+
+    /// Returns the next upcoming proposal slot of the configured validators after
+    /// `last_probed`, if any is in the lookahead.
+    async fn next_probe_slot(
+        &self,
+        validators: &HashSet<u64>,
+        last_probed: u64,
+    ) -> Result<Option<u64>> {
+        let mut lookahead_url = self.bolt_rpc_url.join(BOLT_LOOKAHEAD_PATH)?;
+        lookahead_url.set_query(Some("activeOnly=true&futureOnly=true"));
+
+        let lookahead = reqwest::get(lookahead_url).await?.json::<Vec<LookaheadSlot>>().await?;
+        Ok(lookahead
+            .into_iter()
+            .filter(|slot| slot.slot > last_probed && validators.contains(&slot.validator_index))
+            .map(|slot| slot.slot)
+            .min())
+    }
+
+    /// Probes the given proposal slot: requests the inclusion of a small self-transfer in
+    /// it, then waits for the slot to be proposed and checks that the transaction is part of
+    /// its block.
+    async fn probe(&self, wallet: &PrivateKeySigner, slot: u64) -> Result<ProbeResult> {
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(wallet.clone()))
+            .on_http(self.bolt_rpc_url.clone());
+        let chain_id = provider.get_chain_id().await?;
+
+        // Always use the latest nonce, as the transaction of a failed probe may never land
+        let nonce = provider.get_transaction_count(wallet.address()).await?;
+        let req = create_tx_request(wallet.address(), false).with_nonce(nonce);
+        let (raw_tx, tx_hash) = match provider.fill(req).await.wrap_err("failed to fill")? {
+            SendableTx::Builder(_) => bail!("expected a raw transaction"),
+            SendableTx::Envelope(raw) => (raw.encoded_2718(), *raw.tx_hash()),
+        };
+
+        let target_url = self.override_bolt_sidecar_url.as_ref().unwrap_or(&self.bolt_rpc_url);
+        let request = prepare_rpc_request(
+            "bolt_requestInclusion",
+            serde_json::json!({ "slot": slot, "txs": vec![hex::encode(&raw_tx)] }),
+        );
+        let signature = sign_request(&[tx_hash], slot, chain_id, wallet).await?;
+
+        let start = Instant::now();
+        let response = reqwest::Client::new()
+            .post(target_url.clone())
+            .header("content-type", "application/json")
+            .header("x-bolt-signature", signature)
+            .body(serde_json::to_string(&request)?)
+            .send()
+            .await
+            .wrap_err("failed to send POST request")?
+            .json::<Value>()
+            .await?;
+        gauge!(CANARY_COMMITMENT_LATENCY_MS).set(start.elapsed().as_millis() as f64);
+
+        if let Some(error) = response.get("error") {
+            info!(slot, ?error, "Canary request rejected");
+            return Ok(ProbeResult::Rejected);
+        }
+
+        info!(slot, ?tx_hash, "Canary request committed, waiting for the slot");
+        while request_current_slot_number(&self.beacon_url).await? < slot {
+            tokio::time::sleep(HEAD_POLL_INTERVAL).await;
+        }
+
+        let Some(block_hash) = block_hash_at_slot(&self.beacon_url, slot).await? else {
+            return Ok(ProbeResult::Missed);
+        };
+
+        let receipt = provider.get_transaction_receipt(tx_hash).await?;
+        match receipt.and_then(|receipt| receipt.block_hash) {
+            Some(hash) if hash == block_hash => Ok(ProbeResult::Pass),
+            _ => Ok(ProbeResult::NotIncluded),
+        }
+    }
+}
+
+/// Returns the hash of the execution block proposed at the given slot, or `None` if the
+/// slot was missed.
+pub(crate) async fn block_hash_at_slot(beacon_url: &Url, slot: u64) -> Result<Option<B256>> {
+    let res = reqwest::get(beacon_url.join(&format!("eth/v2/beacon/blocks/{slot}"))?).await?;
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let res = res.error_for_status()?.json::<Value>().await?;
+    let hash = res
+        .pointer("/data/message/body/execution_payload/block_hash")
+        .and_then(Value::as_str)
+        .wrap_err("missing execution block hash")?;
+    Ok(Some(hash.parse()?))
+}
@@ -1,6 +1,14 @@
-use alloy::{
-    primitives::B256,
-    signers::k256::sha2::{Digest, Sha256},
+use std::path::Path;
+
+use alloy::primitives::B256;
+use bolt_primitives::SignedMessageAction;
+use bolt_sidecar::{
+    primitives::{
+        self,
+        delegation::{INCLUSION_SCOPE, TOP_OF_BLOCK_SCOPE},
+        DelegationFile, DelegationScope, SignedMessages,
+    },
+    Web3Signer,
 };
 use ethereum_consensus::crypto::{
     PublicKey as BlsPublicKey, SecretKey as BlsSecretKey, Signature as BlsSignature,
@@ -11,7 +19,7 @@ use serde::Serialize;
 use tracing::{debug, warn};
 
 use crate::{
-    cli::{Action, Chain, DelegateCommand, KeySource},
+    cli::{Action, Chain, CommitmentType, DelegateCommand, KeySource},
     common::{
         dirk::Dirk,
         keystore::{keystore_paths, KeystoreError, KeystoreSecret},
@@ -19,13 +27,15 @@ use crate::{
         signing::{
             compute_commit_boost_signing_root, compute_domain_from_mask, verify_commit_boost_root,
         },
-        write_to_file,
+        web3signer,
     },
 };
 
 impl DelegateCommand {
     /// Run the `delegate` command.
     pub async fn run(self) -> Result<()> {
+        let scope = self.scope()?;
+
         match self.source {
             KeySource::SecretKeys { secret_keys } => {
                 let delegatee_pubkey = parse_bls_public_key(&self.delegatee_pubkey)?;
@@ -34,6 +44,7 @@ impl DelegateCommand {
                     delegatee_pubkey,
                     self.chain,
                     self.action,
+                    scope,
                 )?;
                 debug!("Signed {} messages with local keys", signed_messages.len());
 
@@ -42,7 +53,12 @@ impl DelegateCommand {
                     verify_message_signature(message, self.chain)?;
                 }
 
-                write_to_file(&self.out, &signed_messages)?;
+                write_delegation_file(
+                    &self.out,
+                    &signed_messages,
+                    self.chain,
+                    self.password.as_deref(),
+                )?;
                 println!("Signed delegation messages generated and saved to {}", self.out);
             }
             KeySource::LocalKeystore { opts } => {
@@ -54,6 +70,7 @@ impl DelegateCommand {
                     delegatee_pubkey,
                     self.chain,
                     self.action,
+                    scope,
                 )?;
                 debug!("Signed {} messages with keystore", signed_messages.len());
 
@@ -62,7 +79,12 @@ impl DelegateCommand {
                     verify_message_signature(message, self.chain)?;
                 }
 
-                write_to_file(&self.out, &signed_messages)?;
+                write_delegation_file(
+                    &self.out,
+                    &signed_messages,
+                    self.chain,
+                    self.password.as_deref(),
+                )?;
                 println!("Signed delegation messages generated and saved to {}", self.out);
             }
             KeySource::Dirk { opts } => {
@@ -76,6 +98,7 @@ impl DelegateCommand {
                     opts.passphrases,
                     self.chain,
                     self.action,
+                    scope,
                 )
                 .await?;
                 debug!("Signed {} messages with Dirk", signed_messages.len());
@@ -85,13 +108,60 @@ impl DelegateCommand {
                     verify_message_signature(message, self.chain)?;
                 }
 
-                write_to_file(&self.out, &signed_messages)?;
+                write_delegation_file(
+                    &self.out,
+                    &signed_messages,
+                    self.chain,
+                    self.password.as_deref(),
+                )?;
+                println!("Signed delegation messages generated and saved to {}", self.out);
+            }
+            KeySource::Web3signer { opts } => {
+                let web3signer = web3signer::connect(opts, self.chain.config()).await?;
+
+                let delegatee_pubkey = parse_bls_public_key(&self.delegatee_pubkey)?;
+                let signed_messages =
+                    generate_from_web3signer(&web3signer, delegatee_pubkey, self.action, scope)
+                        .await?;
+                debug!("Signed {} messages with Web3Signer", signed_messages.len());
+
+                // Verify signatures
+                for message in &signed_messages {
+                    verify_message_signature(message, self.chain)?;
+                }
+
+                write_delegation_file(
+                    &self.out,
+                    &signed_messages,
+                    self.chain,
+                    self.password.as_deref(),
+                )?;
                 println!("Signed delegation messages generated and saved to {}", self.out);
             }
         }
 
         Ok(())
     }
+
+    /// Returns the scope of the delegations from the options, unscoped if none is set.
+    fn scope(&self) -> Result<DelegationScope> {
+        let commitment_types = self.commitment_types.iter().fold(0, |mask, t| {
+            mask | match t {
+                CommitmentType::Inclusion => INCLUSION_SCOPE,
+                CommitmentType::TopOfBlock => TOP_OF_BLOCK_SCOPE,
+            }
+        });
+        let scope = DelegationScope {
+            max_gas_per_slot: self.max_gas_per_slot.unwrap_or_default(),
+            commitment_types,
+            expiry_slot: self.expiry_slot.unwrap_or_default(),
+        };
+
+        if matches!(self.action, Action::Revoke) && !scope.is_unscoped() {
+            bail!("Delegation scopes can't be set on revocations");
+        }
+        Ok(scope)
+    }
 }
 
 /// Generate signed delegations/revocations using local BLS private keys
@@ -105,6 +175,7 @@ pub fn generate_from_local_keys(
     delegatee_pubkey: BlsPublicKey,
     chain: Chain,
     action: Action,
+    scope: DelegationScope,
 ) -> Result<Vec<SignedMessage>> {
     let mut signed_messages = Vec::with_capacity(secret_keys.len());
 
@@ -113,7 +184,8 @@ pub fn generate_from_local_keys(
 
         match action {
             Action::Delegate => {
-                let message = DelegationMessage::new(sk.public_key(), delegatee_pubkey.clone());
+                let message = DelegationMessage::new(sk.public_key(), delegatee_pubkey.clone())
+                    .with_scope(scope);
                 let signing_root = compute_commit_boost_signing_root(message.digest(), &chain)?;
                 let signature = sk.sign(signing_root.0.as_ref());
                 let signed = SignedDelegation { message, signature };
@@ -145,6 +217,7 @@ pub fn generate_from_keystore(
     delegatee_pubkey: BlsPublicKey,
     chain: Chain,
     action: Action,
+    scope: DelegationScope,
 ) -> Result<Vec<SignedMessage>> {
     let keystores_paths = keystore_paths(keys_path)?;
     let mut signed_messages = Vec::with_capacity(keystores_paths.len());
@@ -159,7 +232,8 @@ pub fn generate_from_keystore(
 
         match action {
             Action::Delegate => {
-                let message = DelegationMessage::new(validator_pubkey, delegatee_pubkey.clone());
+                let message = DelegationMessage::new(validator_pubkey, delegatee_pubkey.clone())
+                    .with_scope(scope);
                 let signing_root = compute_commit_boost_signing_root(message.digest(), &chain)?;
                 let signature = validator_private_key.sign(signing_root.0.into());
                 let signature = BlsSignature::try_from(signature.serialize().as_ref())?;
@@ -188,6 +262,7 @@ pub async fn generate_from_dirk(
     passphrases: Option<Vec<String>>,
     chain: Chain,
     action: Action,
+    scope: DelegationScope,
 ) -> Result<Vec<SignedMessage>> {
     // first read the accounts from the remote keystore
     let accounts = dirk.list_accounts(account_path).await?;
@@ -215,7 +290,8 @@ pub async fn generate_from_dirk(
 
         match action {
             Action::Delegate => {
-                let message = DelegationMessage::new(pubkey.clone(), delegatee_pubkey.clone());
+                let message = DelegationMessage::new(pubkey.clone(), delegatee_pubkey.clone())
+                    .with_scope(scope);
                 let signing_root = message.digest().into(); // Dirk does the hash tree root internally
                 let signature = dirk.request_signature(&account, signing_root, domain).await?;
                 let signed = SignedDelegation { message, signature };
@@ -239,15 +315,44 @@ pub async fn generate_from_dirk(
     Ok(signed_messages)
 }
 
-/// Event types that can be emitted by the validator pubkey to
-/// signal some action on the Bolt protocol.
-#[derive(Debug, Clone, Copy)]
-#[repr(u8)]
-enum SignedMessageAction {
-    /// Signal delegation of a validator pubkey to a delegatee pubkey.
-    Delegation,
-    /// Signal revocation of a previously delegated pubkey.
-    Revocation,
+/// Generate signed delegations/revocations using a remote Web3Signer
+///
+/// The Web3Signer is connected to the chain of the messages, whose Commit-Boost
+/// signing roots are computed locally and signed remotely.
+pub async fn generate_from_web3signer(
+    web3signer: &Web3Signer,
+    delegatee_pubkey: BlsPublicKey,
+    action: Action,
+    scope: DelegationScope,
+) -> Result<Vec<SignedMessage>> {
+    let pubkeys = web3signer.pubkeys();
+    debug!("Found {} remote pubkeys to sign with", pubkeys.len());
+
+    let mut signed_messages = Vec::with_capacity(pubkeys.len());
+
+    for pubkey in pubkeys {
+        match action {
+            Action::Delegate => {
+                let message = DelegationMessage::new(pubkey.clone(), delegatee_pubkey.clone())
+                    .with_scope(scope);
+                let signature =
+                    web3signer.sign_commit_boost_root(message.digest(), &pubkey).await?;
+                let signature = BlsSignature::try_from(signature.as_slice())?;
+                let signed = SignedDelegation { message, signature };
+                signed_messages.push(SignedMessage::Delegation(signed));
+            }
+            Action::Revoke => {
+                let message = RevocationMessage::new(pubkey.clone(), delegatee_pubkey.clone());
+                let signature =
+                    web3signer.sign_commit_boost_root(message.digest(), &pubkey).await?;
+                let signature = BlsSignature::try_from(signature.as_slice())?;
+                let signed = SignedRevocation { message, signature };
+                signed_messages.push(SignedMessage::Revocation(signed));
+            }
+        }
+    }
+
+    Ok(signed_messages)
 }
 
 /// Transparent serialization of signed messages.
@@ -282,22 +387,36 @@ pub struct DelegationMessage {
     action: u8,
     pub validator_pubkey: BlsPublicKey,
     pub delegatee_pubkey: BlsPublicKey,
+    #[serde(skip_serializing_if = "DelegationScope::is_unscoped")]
+    pub scope: DelegationScope,
 }
 
 impl DelegationMessage {
     /// Create a new delegation message.
     pub fn new(validator_pubkey: BlsPublicKey, delegatee_pubkey: BlsPublicKey) -> Self {
-        Self { action: SignedMessageAction::Delegation as u8, validator_pubkey, delegatee_pubkey }
+        Self {
+            action: SignedMessageAction::Delegation as u8,
+            validator_pubkey,
+            delegatee_pubkey,
+            scope: DelegationScope::default(),
+        }
     }
 
-    /// Compute the digest of the delegation message.
-    pub fn digest(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update([self.action]);
-        hasher.update(self.validator_pubkey.to_vec());
-        hasher.update(self.delegatee_pubkey.to_vec());
+    /// Limit the constraints the delegatee can sign to the given scope.
+    pub fn with_scope(mut self, scope: DelegationScope) -> Self {
+        self.scope = scope;
+        self
+    }
 
-        hasher.finalize().into()
+    /// Compute the digest of the delegation message. The scope is only part of the
+    /// digest if set, so that unscoped delegations keep their original digest.
+    pub fn digest(&self) -> [u8; 32] {
+        bolt_primitives::delegation_digest(
+            self.action,
+            &self.validator_pubkey,
+            &self.delegatee_pubkey,
+            &self.scope.into(),
+        )
     }
 }
 
@@ -322,13 +441,56 @@ impl RevocationMessage {
 
     /// Compute the digest of the revocation message.
     pub fn digest(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update([self.action]);
-        hasher.update(self.validator_pubkey.to_vec());
-        hasher.update(self.delegatee_pubkey.to_vec());
+        bolt_primitives::revocation_digest(
+            self.action,
+            &self.validator_pubkey,
+            &self.delegatee_pubkey,
+        )
+    }
+}
+
+/// Write the signed messages to a delegation file for the given chain, encrypted with
+/// the password if provided.
+pub fn write_delegation_file(
+    out: &str,
+    signed_messages: &[SignedMessage],
+    chain: Chain,
+    password: Option<&str>,
+) -> Result<()> {
+    let mut messages = SignedMessages::default();
+    for signed_message in signed_messages {
+        match signed_message {
+            SignedMessage::Delegation(signed) => {
+                let message = &signed.message;
+                messages.delegations.push(primitives::SignedDelegation {
+                    message: primitives::DelegationMessage::new(
+                        message.validator_pubkey.clone(),
+                        message.delegatee_pubkey.clone(),
+                    )
+                    .with_scope(message.scope),
+                    signature: signed.signature.clone(),
+                });
+            }
+            SignedMessage::Revocation(signed) => {
+                let message = &signed.message;
+                messages.revocations.push(primitives::SignedRevocation {
+                    message: primitives::RevocationMessage::new(
+                        message.validator_pubkey.clone(),
+                        message.delegatee_pubkey.clone(),
+                    ),
+                    signature: signed.signature.clone(),
+                });
+            }
+        }
+    }
 
-        hasher.finalize().into()
+    let mut file = DelegationFile::new(&chain.config(), messages);
+    if let Some(password) = password {
+        file = file.encrypt(password.as_bytes())?;
     }
+
+    file.write(Path::new(out))?;
+    Ok(())
 }
 
 /// Verify the signature of a signed message
@@ -366,7 +528,12 @@ mod tests {
         common::{dirk, keystore, parse_bls_public_key},
     };
 
-    use super::{generate_from_dirk, generate_from_keystore, verify_message_signature};
+    use bolt_sidecar::primitives::{delegation::INCLUSION_SCOPE, DelegationFile, DelegationScope};
+
+    use super::{
+        generate_from_dirk, generate_from_keystore, verify_message_signature,
+        write_delegation_file, SignedMessage,
+    };
 
     #[test]
     fn test_delegation_keystore_signer_lighthouse() -> eyre::Result<()> {
@@ -386,6 +553,7 @@ mod tests {
             delegatee_pubkey.clone(),
             chain,
             Action::Delegate,
+            DelegationScope::default(),
         )?;
 
         let signed_message = signed_delegations.first().expect("to get signed delegation");
@@ -395,6 +563,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_write_encrypted_delegation_file() -> eyre::Result<()> {
+        let keys_path = env!("CARGO_MANIFEST_DIR").to_string() + "/test_data/lighthouse/validators";
+        let secrets_path = env!("CARGO_MANIFEST_DIR").to_string() + "/test_data/lighthouse/secrets";
+        let keystore_secret = keystore::KeystoreSecret::from_directory(&secrets_path)?;
+
+        let delegatee_pubkey = "0x83eeddfac5e60f8fe607ee8713efb8877c295ad9f8ca075f4d8f6f2ae241a30dd57f78f6f3863a9fe0d5b5db9d550b93";
+        let delegatee_pubkey = parse_bls_public_key(delegatee_pubkey)?;
+        let chain = Chain::Holesky;
+        let scope = DelegationScope {
+            commitment_types: INCLUSION_SCOPE,
+            expiry_slot: 1000,
+            ..Default::default()
+        };
+
+        let signed_messages = generate_from_keystore(
+            &keys_path,
+            keystore_secret,
+            delegatee_pubkey.clone(),
+            chain,
+            Action::Delegate,
+            scope,
+        )?;
+        verify_message_signature(&signed_messages[0], chain)?;
+
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().join("delegations.json");
+        write_delegation_file(out.to_str().unwrap(), &signed_messages, chain, Some("secret"))?;
+
+        let file = DelegationFile::read(&out, &chain.config())?;
+        assert!(file.is_encrypted());
+        assert!(DelegationFile::read(&out, &Chain::Mainnet.config()).is_err());
+
+        let delegations = file.into_messages(Some(b"secret"))?.delegations;
+        let SignedMessage::Delegation(expected) = &signed_messages[0] else {
+            panic!("expected a delegation");
+        };
+        assert_eq!(delegations.len(), signed_messages.len());
+        assert_eq!(delegations[0].message.delegatee_pubkey, delegatee_pubkey);
+        assert_eq!(delegations[0].message.scope, scope);
+        assert_eq!(delegations[0].signature, expected.signature);
+
+        Ok(())
+    }
+
     /// Test generating signed delegations using a remote Dirk signer.
     ///
     /// ```shell
@@ -418,6 +631,7 @@ mod tests {
             Some(vec!["secret".to_string()]),
             chain,
             Action::Delegate,
+            DelegationScope::default(),
         )
         .await?;
 
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use alloy::{
+    eips::eip2718::Encodable2718,
+    network::EthereumWallet,
+    primitives::keccak256,
+    providers::{Provider, ProviderBuilder, SendableTx},
+    signers::local::PrivateKeySigner,
+};
+use bolt_sidecar::devnet::{Devnet, DevnetConfig};
+use eyre::{bail, Context, Result};
+use tracing::info;
+
+use crate::{
+    cli::DevnetCommand,
+    commands::send::{create_tx_request, send_rpc_request},
+};
+
+/// Time to wait after the start of a slot for the sidecar to process it.
+const SLOT_PROCESSING_DELAY: Duration = Duration::from_millis(500);
+
+impl DevnetCommand {
+    /// Run the `devnet` command.
+    pub async fn run(self) -> Result<()> {
+        let wallet: PrivateKeySigner = self.private_key.parse().wrap_err("invalid private key")?;
+
+        let config = DevnetConfig {
+            execution_api_url: self.execution_url.clone(),
+            engine_api_url: self.engine_url.clone(),
+            engine_jwt_hex: self.engine_jwt_hex.clone(),
+            rpc_port: self.rpc_port,
+            constraints_proxy_port: self.constraints_proxy_port,
+            slot_time: self.slot_time,
+        };
+
+        let devnet = Devnet::start(config).await?;
+        println!("Devnet started, sidecar RPC at {}", devnet.rpc_url());
+
+        let result = self.run_flow(&devnet, wallet).await;
+
+        if self.keep_alive {
+            println!("Devnet is running, press CTRL+C to stop it");
+            tokio::signal::ctrl_c().await?;
+        }
+
+        devnet.shutdown().await?;
+        result
+    }
+
+    /// Run the full commitment flow on the devnet: send an inclusion request for the next
+    /// slot, then propose it and check that the transaction is part of the payload.
+    async fn run_flow(&self, devnet: &Devnet, wallet: PrivateKeySigner) -> Result<()> {
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(wallet.clone()))
+            .on_http(self.execution_url.clone());
+
+        // Start at the beginning of a slot, so that the request for the next one is
+        // well within the commitment deadline.
+        let current_slot = devnet.current_slot() + 1;
+        devnet.wait_for_slot(current_slot).await;
+        tokio::time::sleep(SLOT_PROCESSING_DELAY).await;
+
+        let target_slot = current_slot + 1;
+        info!(target_slot, "Sending inclusion request");
+
+        let chain_id = provider.get_chain_id().await?;
+        let req = create_tx_request(wallet.address(), false);
+        let (raw_tx, tx_hash) = match provider.fill(req).await.wrap_err("failed to fill")? {
+            SendableTx::Builder(_) => bail!("expected a raw transaction"),
+            SendableTx::Envelope(raw) => (raw.encoded_2718(), *raw.tx_hash()),
+        };
+
+        send_rpc_request(
+            vec![hex::encode(&raw_tx)],
+            vec![tx_hash],
+            target_slot,
+            chain_id,
+            devnet.rpc_url(),
+            &wallet,
+            false,
+        )
+        .await?;
+
+        // The constraints are submitted to the relay at the commitment deadline
+        // of the previous slot, so they are available once the target slot starts.
+        devnet.wait_for_slot(target_slot).await;
+        tokio::time::sleep(SLOT_PROCESSING_DELAY).await;
+
+        let constraints = devnet.relay().constraints(target_slot);
+        if constraints.is_empty() {
+            bail!("no constraints received by the relay for slot {target_slot}");
+        }
+        println!("Relay received {} constraints for slot {target_slot}", constraints.len());
+
+        let payload = devnet.propose(target_slot).await?;
+        let included = payload
+            .execution_payload()
+            .transactions()
+            .iter()
+            .any(|tx| keccak256(tx.as_ref()) == tx_hash);
+
+        if !included {
+            bail!("transaction {tx_hash} not included in the payload of slot {target_slot}");
+        }
+
+        println!(
+            "Transaction {tx_hash} included in block {} for slot {target_slot} 🎉",
+            payload.block_hash()
+        );
+
+        Ok(())
+    }
+}
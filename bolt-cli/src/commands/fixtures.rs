@@ -0,0 +1,31 @@
+use bolt_sidecar::devnet::{FixtureParams, ProofFixture};
+use eyre::Result;
+use tracing::info;
+
+use crate::cli::FixturesCommand;
+
+impl FixturesCommand {
+    /// Run the `fixtures` command.
+    pub async fn run(self) -> Result<()> {
+        let params = FixtureParams {
+            seed: self.seed,
+            slot: self.slot,
+            constraints_count: self.constraints,
+            txs_per_constraint: self.txs_per_constraint,
+            unconstrained_txs: self.unconstrained_txs,
+        };
+
+        let fixture = ProofFixture::generate(params, self.chain.config()).await?;
+        fixture.write_to_dir(&self.out)?;
+
+        info!(
+            transactions = fixture.transactions.len(),
+            constraints = fixture.constraints.len(),
+            root = %fixture.transactions_root,
+            "Generated proof fixture"
+        );
+        println!("Fixture written to {}", self.out);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,23 @@
+/// Module for the bolt `bench` command to run time-sliced
+/// benchmarks of the sidecar hot paths.
+pub mod bench;
+
+/// Module for the bolt `canary` command to continuously probe
+/// the preconfirmation pipeline end to end.
+pub mod canary;
+
 /// Module for the bolt `delegate` command to create
 /// signed delegation and revocation messages.
 pub mod delegate;
 
+/// Module for the bolt `devnet` command to run the full
+/// commitment flow against an in-process devnet.
+pub mod devnet;
+
+/// Module for the bolt `fixtures` command to generate deterministic
+/// constraints and inclusion proof fixtures.
+pub mod fixtures;
+
 /// Module for the bolt `pubkeys` command to generate
 /// lists of public keys from different key sources.
 pub mod pubkeys;
@@ -9,3 +25,11 @@ pub mod pubkeys;
 /// Module for the bolt `send` command to create and
 /// broadcast preconfirmations in Bolt.
 pub mod send;
+
+/// Module for the bolt `vectors` command to dump and verify interop
+/// test vectors of the Constraints API messages.
+pub mod vectors;
+
+/// Module for the bolt `verify-commitment` command to verify signed
+/// commitments against the blocks they target.
+pub mod verify;
@@ -1,3 +1,4 @@
+use bolt_sidecar::ChainConfig;
 use ethereum_consensus::crypto::bls::{PublicKey as BlsPublicKey, SecretKey as BlsSecretKey};
 use eyre::Result;
 use lighthouse_eth2_keystore::Keystore;
@@ -7,7 +8,7 @@ use crate::{
     common::{
         dirk::Dirk,
         keystore::{keystore_paths, KeystoreError, KeystoreSecret},
-        write_to_file,
+        web3signer, write_to_file,
     },
     pb::eth2_signer_api::Account,
 };
@@ -35,6 +36,14 @@ impl PubkeysCommand {
                 let accounts = dirk.list_accounts(opts.wallet_path).await?;
                 let pubkeys = list_from_dirk_accounts(&accounts)?;
 
+                write_to_file(&self.out, &pubkeys)?;
+                println!("Pubkeys generated and saved to {}", self.out);
+            }
+            KeySource::Web3signer { opts } => {
+                // Note: the chain only matters for signing, not for listing pubkeys
+                let web3signer = web3signer::connect(opts, ChainConfig::default()).await?;
+                let pubkeys = Vec::from_iter(web3signer.pubkeys());
+
                 write_to_file(&self.out, &pubkeys)?;
                 println!("Pubkeys generated and saved to {}", self.out);
             }
@@ -4,11 +4,12 @@ use alloy::{
     consensus::{BlobTransactionSidecar, SidecarBuilder, SimpleCoder, Transaction},
     eips::eip2718::Encodable2718,
     network::{EthereumWallet, TransactionBuilder, TransactionBuilder4844},
-    primitives::{keccak256, Address, B256, U256},
-    providers::{ProviderBuilder, SendableTx},
+    primitives::{Address, B256, U256},
+    providers::{Provider, ProviderBuilder, SendableTx},
     rpc::types::TransactionRequest,
     signers::{local::PrivateKeySigner, Signer},
 };
+use bolt_primitives::InclusionDigest;
 use eyre::{bail, Context, ContextCompat, Result};
 use rand::Rng;
 use reqwest::Url;
@@ -19,7 +20,10 @@ use tracing::info;
 use crate::cli::SendCommand;
 
 /// Path to the lookahead endpoint on the Bolt RPC server.
-const BOLT_LOOKAHEAD_PATH: &str = "proposers/lookahead";
+pub(crate) const BOLT_LOOKAHEAD_PATH: &str = "proposers/lookahead";
+
+/// The maximum number of times a request is retargeted to a slot suggested by the sidecar.
+const MAX_RETARGETS: usize = 3;
 
 impl SendCommand {
     /// Run the `send` command.
@@ -68,6 +72,8 @@ impl SendCommand {
         let target_slot = lookahead_res[0].slot;
         info!("Target slot: {}", target_slot);
 
+        let chain_id = provider.get_chain_id().await?;
+
         // Send the transactions to the Bolt sidecar
         let mut next_nonce = None;
         for _ in 0..self.count {
@@ -89,8 +95,10 @@ impl SendCommand {
                 vec![hex::encode(&raw_tx)],
                 vec![tx_hash],
                 target_slot,
+                chain_id,
                 target_url.clone(),
                 &wallet,
+                self.auto_retarget,
             )
             .await?;
 
@@ -115,6 +123,7 @@ impl SendCommand {
 
         // Fetch the current slot from the devnet beacon node
         let slot = request_current_slot_number(&cl_url).await?;
+        let chain_id = provider.get_chain_id().await?;
 
         // Send the transactions to the devnet sidecar
         let mut next_nonce = None;
@@ -136,8 +145,10 @@ impl SendCommand {
                 vec![hex::encode(&raw_tx)],
                 vec![tx_hash],
                 slot + 2,
+                chain_id,
                 sidecar_url.clone(),
                 &wallet,
+                self.auto_retarget,
             )
             .await?;
 
@@ -149,14 +160,14 @@ impl SendCommand {
     }
 }
 
-async fn request_current_slot_number(beacon_url: &Url) -> Result<u64> {
+pub(crate) async fn request_current_slot_number(beacon_url: &Url) -> Result<u64> {
     let res = reqwest::get(beacon_url.join("eth/v1/beacon/headers/head")?).await?;
     let res = res.json::<Value>().await?;
     let slot = res.pointer("/data/header/message/slot").wrap_err("missing slot")?;
     Ok(slot.as_u64().unwrap_or(slot.as_str().wrap_err("invalid slot type")?.parse()?))
 }
 
-fn create_tx_request(to: Address, with_blob: bool) -> TransactionRequest {
+pub(crate) fn create_tx_request(to: Address, with_blob: bool) -> TransactionRequest {
     let mut req = TransactionRequest::default();
     req = req.with_to(to).with_value(U256::from(100_000));
     req = req.with_input(rand::thread_rng().gen::<[u8; 32]>());
@@ -171,60 +182,96 @@ fn create_tx_request(to: Address, with_blob: bool) -> TransactionRequest {
     req
 }
 
-async fn send_rpc_request(
+/// Sends an inclusion request for the given transactions to the sidecar.
+///
+/// If `auto_retarget` is set and the sidecar rejects the request for its target slot while
+/// suggesting other slots, the request is signed and sent again for the first suggested slot
+/// not tried yet, up to [MAX_RETARGETS] times.
+pub(crate) async fn send_rpc_request(
     txs_rlp: Vec<String>,
     tx_hashes: Vec<B256>,
     target_slot: u64,
+    chain_id: u64,
     target_sidecar_url: Url,
     wallet: &PrivateKeySigner,
+    auto_retarget: bool,
 ) -> Result<()> {
-    let request = prepare_rpc_request(
-        "bolt_requestInclusion",
-        serde_json::json!({
-            "slot": target_slot,
-            "txs": txs_rlp,
-        }),
-    );
-
-    info!(?tx_hashes, target_slot, %target_sidecar_url);
-    let signature = sign_request(tx_hashes, target_slot, wallet).await?;
-
-    let response = reqwest::Client::new()
-        .post(target_sidecar_url)
-        .header("content-type", "application/json")
-        .header("x-bolt-signature", signature)
-        .body(serde_json::to_string(&request)?)
-        .send()
-        .await
-        .wrap_err("failed to send POST request")?;
-
-    let response = response.text().await?;
-
-    // strip out long series of zeros in the response (to avoid spamming blob contents)
-    let response = response.replace(&"0".repeat(32), ".").replace(&".".repeat(4), "");
-    info!("Response: {:?}", response);
-    Ok(())
+    let mut tried = vec![target_slot];
+    let mut target_slot = target_slot;
+
+    loop {
+        let request = prepare_rpc_request(
+            "bolt_requestInclusion",
+            serde_json::json!({
+                "slot": target_slot,
+                "txs": txs_rlp,
+            }),
+        );
+
+        info!(?tx_hashes, target_slot, %target_sidecar_url);
+        let signature = sign_request(&tx_hashes, target_slot, chain_id, wallet).await?;
+
+        let response = reqwest::Client::new()
+            .post(target_sidecar_url.clone())
+            .header("content-type", "application/json")
+            .header("x-bolt-signature", signature)
+            .body(serde_json::to_string(&request)?)
+            .send()
+            .await
+            .wrap_err("failed to send POST request")?;
+
+        let response = response.text().await?;
+
+        let next_slot = suggested_slots(&response).into_iter().find(|slot| !tried.contains(slot));
+        match next_slot {
+            Some(slot) if auto_retarget && tried.len() <= MAX_RETARGETS => {
+                info!(from = target_slot, to = slot, "Request rejected for its slot, retargeting");
+                tried.push(slot);
+                target_slot = slot;
+            }
+            _ => {
+                // strip out long series of zeros in the response (to avoid spamming blob contents)
+                let response = response.replace(&"0".repeat(32), ".").replace(&".".repeat(4), "");
+                info!("Response: {:?}", response);
+                return Ok(());
+            }
+        }
+    }
 }
 
-async fn sign_request(
-    tx_hashes: Vec<B256>,
+/// Returns the slots suggested by the sidecar in the error data of a JSON-RPC response, to
+/// retarget a request rejected for its target slot. Empty if none were suggested.
+fn suggested_slots(response: &str) -> Vec<u64> {
+    serde_json::from_str::<Value>(response)
+        .ok()
+        .and_then(|response| response.pointer("/error/data/suggestedSlots").cloned())
+        .and_then(|slots| serde_json::from_value(slots).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) async fn sign_request(
+    tx_hashes: &[B256],
     target_slot: u64,
+    chain_id: u64,
     wallet: &PrivateKeySigner,
 ) -> eyre::Result<String> {
-    let digest = {
-        let mut data = Vec::new();
-        let hashes = tx_hashes.iter().map(|hash| hash.as_slice()).collect::<Vec<_>>().concat();
-        data.extend_from_slice(&hashes);
-        data.extend_from_slice(target_slot.to_le_bytes().as_slice());
-        keccak256(data)
-    };
-
+    let digest = signing_digest(tx_hashes, target_slot, chain_id);
     let signature = hex::encode(wallet.sign_hash(&digest).await?.as_bytes());
 
     Ok(format!("{}:0x{}", wallet.address(), signature))
 }
 
-fn prepare_rpc_request(method: &str, params: Value) -> Value {
+/// Returns the canonical digest of an inclusion request, as verified by the sidecar:
+///
+/// digest = keccak256(
+///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | be_bytes(target_slot) |
+///     bytes(tx_hash1) | bytes(tx_hash2) | ...
+/// )
+fn signing_digest(tx_hashes: &[B256], target_slot: u64, chain_id: u64) -> B256 {
+    InclusionDigest::new(chain_id, target_slot).signing_digest(tx_hashes).into()
+}
+
+pub(crate) fn prepare_rpc_request(method: &str, params: Value) -> Value {
     serde_json::json!({
         "id": "1",
         "jsonrpc": "2.0",
@@ -246,3 +293,56 @@ pub struct LookaheadSlot {
     /// Optional URL of the Bolt sidecar associated with the proposer
     pub sidecar_url: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        hex,
+        primitives::{b256, B256},
+    };
+    use bolt_sidecar::primitives::InclusionRequest;
+
+    use super::{signing_digest, suggested_slots};
+
+    /// An inclusion request and the digest to sign for it on Holesky, shared with the sidecar.
+    const TX: &str = "0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471";
+    const SLOT: u64 = 633067;
+    const CHAIN_ID: u64 = 17000;
+
+    #[test]
+    fn test_signing_digest_vector() {
+        let tx_hash = b256!("ea49f475608be9311fd787f4b30425464a48508b0890b27802d5501f050c6c1b");
+        let digest = signing_digest(&[tx_hash], SLOT, CHAIN_ID);
+        assert_eq!(
+            hex::encode(digest),
+            "b2804102e8bba61f18692361dbf738ac078ca871a8680ee33fb473f633e27e87"
+        );
+    }
+
+    #[test]
+    fn test_signing_digest_matches_sidecar() -> eyre::Result<()> {
+        let txs = [TX, "0x02f86c870c72dd9d5e883e4d0183408f2382520894d2e2adf7177b7a8afddbc12d1634cf23ea1a71020180c001a08556dcfea479b34675db3fe08e29486fe719c2b22f6b0c1741ecbbdce4575cc6a01cd48009ccafd6b9f1290bbe2ceea268f94101d1d322c787018423ebcbc87ab4"];
+        let request: InclusionRequest =
+            serde_json::from_value(serde_json::json!({ "slot": SLOT, "txs": txs }))?;
+
+        // The sidecar uses another version of alloy, so the types are compared as bytes
+        let tx_hashes =
+            request.txs.iter().map(|tx| B256::from_slice(tx.hash().as_slice())).collect::<Vec<_>>();
+        assert_eq!(
+            signing_digest(&tx_hashes, SLOT, CHAIN_ID).as_slice(),
+            request.signing_digest(CHAIN_ID).as_slice()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggested_slots() {
+        let rejected = r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32011,"message":"Max commitments reached for slot 12: 128","data":{"suggestedSlots":[14,17]}},"apiVersion":"v1"}"#;
+        assert_eq!(suggested_slots(rejected), vec![14, 17]);
+
+        let rejected = r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32012,"message":"Max priority fee per gas is too low"},"apiVersion":"v1"}"#;
+        assert!(suggested_slots(rejected).is_empty());
+        assert!(suggested_slots("not json").is_empty());
+    }
+}
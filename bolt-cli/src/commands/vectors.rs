@@ -0,0 +1,57 @@
+use bolt_sidecar::devnet::InteropVectors;
+use eyre::Result;
+use tracing::info;
+
+use crate::cli::{VectorsAction, VectorsCommand};
+
+impl VectorsCommand {
+    /// Run the `vectors` command.
+    pub async fn run(self) -> Result<()> {
+        match self.action {
+            VectorsAction::Dump { seed, chain, out } => {
+                let vectors = InteropVectors::generate(seed, chain.config()).await?;
+                vectors.write_to_dir(&out)?;
+
+                info!(
+                    chain = vectors.chain,
+                    constraints = vectors.constraints.len(),
+                    domain = %vectors.domain,
+                    "Generated interop test vectors"
+                );
+                println!("Test vectors written to {out}");
+            }
+            VectorsAction::Verify { path, chain } => {
+                let vectors = InteropVectors::from_file(&path)?;
+                vectors.verify(&chain.config())?;
+
+                println!("Test vectors in {path} are valid");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Chain, VectorsAction, VectorsCommand};
+
+    #[tokio::test]
+    async fn test_dump_and_verify_vectors() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().to_string_lossy().to_string();
+
+        let dump = VectorsAction::Dump { seed: 7, chain: Chain::Holesky, out: out.clone() };
+        VectorsCommand { action: dump }.run().await?;
+
+        let path = format!("{out}/vectors.json");
+        let verify = VectorsAction::Verify { path: path.clone(), chain: Chain::Holesky };
+        VectorsCommand { action: verify }.run().await?;
+
+        // Vectors signed for another chain are rejected
+        let verify = VectorsAction::Verify { path, chain: Chain::Mainnet };
+        assert!(VectorsCommand { action: verify }.run().await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use std::{fmt, fs, io::Read};
+
+use alloy::{
+    primitives::{TxHash, B256},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::BlockTransactionsKind,
+    transports::http::Http,
+};
+use bolt_sidecar::primitives::commitment::{InclusionCommitment, SignedCommitment};
+use eyre::{eyre, Context, ContextCompat, Result};
+use reqwest::{Client, Url};
+use serde_json::Value;
+
+use crate::{
+    cli::{BlockRef, VerifyCommitmentCommand},
+    commands::canary::block_hash_at_slot,
+};
+
+/// The verdict on a commitment, given a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every committed transaction was included in a committed slot, at the top of the
+    /// block for top-of-block commitments.
+    Honored,
+    /// Some committed transactions were not included in a committed slot as committed.
+    Violated,
+    /// No block was proposed at the committed slot.
+    Missed,
+    /// The block is not at a committed slot, so it can't honor or violate the commitment.
+    NotApplicable,
+    /// The commitment was not signed by the expected sidecar.
+    InvalidSignature,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = match self {
+            Self::Honored => "HONORED",
+            Self::Violated => "VIOLATED",
+            Self::Missed => "MISSED SLOT",
+            Self::NotApplicable => "NOT APPLICABLE",
+            Self::InvalidSignature => "INVALID SIGNATURE",
+        };
+        f.write_str(verdict)
+    }
+}
+
+/// Where a committed transaction landed on chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inclusion {
+    /// At the given index of the checked block.
+    InBlock(usize),
+    /// In another block, at the given slot.
+    Elsewhere { block_number: u64, block_hash: B256, slot: u64 },
+    /// Nowhere on chain.
+    NotIncluded,
+}
+
+impl VerifyCommitmentCommand {
+    /// Run the `verify-commitment` command.
+    pub async fn run(self) -> Result<()> {
+        let commitment = read_commitment(&self.commitment)?;
+        let request = commitment.request();
+
+        let signer = commitment
+            .recover_signer()
+            .map_err(|_| eyre!("failed to recover commitment signer"))?;
+        println!("Commitment signer: {signer}");
+        if let Some(expected) = self.signer.filter(|expected| *expected != signer) {
+            println!("Expected signer:   {expected}");
+            println!("Verdict: {}", Verdict::InvalidSignature);
+            return Ok(());
+        }
+
+        let slots = request.candidate_slots();
+        println!("Committed slots:   {}..={}", slots.start(), slots.end());
+        println!("Top of block:      {}", request.top);
+
+        let provider = ProviderBuilder::new().on_http(self.execution_url.clone());
+        let block_hash = match self.block {
+            BlockRef::Hash(hash) => hash,
+            BlockRef::Slot(slot) => match block_hash_at_slot(&self.beacon_url, slot).await? {
+                Some(hash) => hash,
+                None => {
+                    println!("Block:             none proposed at slot {slot}");
+                    let verdict = if slots.contains(&slot) {
+                        Verdict::Missed
+                    } else {
+                        Verdict::NotApplicable
+                    };
+                    println!("Verdict: {verdict}");
+                    return Ok(());
+                }
+            },
+        };
+
+        let block = provider
+            .get_block_by_hash(block_hash, BlockTransactionsKind::Hashes)
+            .await?
+            .wrap_err_with(|| format!("block {block_hash} not found"))?;
+        let clock = BeaconClock::fetch(&self.beacon_url).await?;
+        let slot = clock.slot_at(block.header.timestamp);
+        println!("Block:             #{} {block_hash} at slot {slot}", block.header.number);
+
+        if !slots.contains(&slot) {
+            println!("Verdict: {}", Verdict::NotApplicable);
+            return Ok(());
+        }
+
+        let block_txs = block.transactions.hashes().collect::<Vec<_>>();
+        let committed = commitment.committed_tx_hashes();
+        let mut inclusions = Vec::with_capacity(committed.len());
+        for (hash, index) in locate(&committed, &block_txs) {
+            let inclusion = match index {
+                Some(index) => Inclusion::InBlock(index),
+                None => find_elsewhere(&provider, &clock, hash).await?,
+            };
+            println!("Transaction {hash}: {}", describe(&inclusion));
+            inclusions.push(inclusion);
+        }
+
+        println!("Verdict: {}", judge(&inclusions, request.top, |slot| slots.contains(&slot)));
+        Ok(())
+    }
+}
+
+/// Reads a signed commitment from a file, or from the standard input if the path is `-`.
+fn read_commitment(path: &str) -> Result<InclusionCommitment> {
+    let json = if path == "-" {
+        let mut json = String::new();
+        std::io::stdin().read_to_string(&mut json)?;
+        json
+    } else {
+        fs::read_to_string(path).wrap_err_with(|| format!("failed to read {path}"))?
+    };
+
+    let commitment: SignedCommitment =
+        serde_json::from_str(&json).wrap_err("invalid signed commitment")?;
+    Ok(commitment.into())
+}
+
+/// Returns the index in the block of each committed transaction, if included in it.
+fn locate(committed: &[TxHash], block_txs: &[TxHash]) -> Vec<(TxHash, Option<usize>)> {
+    committed.iter().map(|hash| (*hash, block_txs.iter().position(|tx| tx == hash))).collect()
+}
+
+/// Judges a commitment from where its transactions landed: they must all be included in
+/// a committed slot, and for top-of-block commitments, first in the block in order.
+fn judge(inclusions: &[Inclusion], top: bool, committed_slot: impl Fn(u64) -> bool) -> Verdict {
+    let honored = inclusions.iter().enumerate().all(|(i, inclusion)| match *inclusion {
+        Inclusion::InBlock(index) => !top || index == i,
+        Inclusion::Elsewhere { slot, .. } => !top && committed_slot(slot),
+        Inclusion::NotIncluded => false,
+    });
+
+    if honored {
+        Verdict::Honored
+    } else {
+        Verdict::Violated
+    }
+}
+
+fn describe(inclusion: &Inclusion) -> String {
+    match inclusion {
+        Inclusion::InBlock(index) => format!("included at index {index}"),
+        Inclusion::Elsewhere { block_number, block_hash, slot } => {
+            format!(
+                "not in the block, included in block #{block_number} {block_hash} at slot {slot}"
+            )
+        }
+        Inclusion::NotIncluded => "not included on chain".to_string(),
+    }
+}
+
+/// Looks up the block that included a transaction missing from the checked block.
+async fn find_elsewhere(
+    provider: &RootProvider<Http<Client>>,
+    clock: &BeaconClock,
+    hash: TxHash,
+) -> Result<Inclusion> {
+    let Some(receipt) = provider.get_transaction_receipt(hash).await? else {
+        return Ok(Inclusion::NotIncluded);
+    };
+    let (Some(block_number), Some(block_hash)) = (receipt.block_number, receipt.block_hash) else {
+        return Ok(Inclusion::NotIncluded);
+    };
+
+    let block = provider
+        .get_block_by_hash(block_hash, BlockTransactionsKind::Hashes)
+        .await?
+        .wrap_err_with(|| format!("block {block_hash} not found"))?;
+    let slot = clock.slot_at(block.header.timestamp);
+
+    Ok(Inclusion::Elsewhere { block_number, block_hash, slot })
+}
+
+/// Maps the timestamps of execution blocks to the slots of the beacon chain.
+#[derive(Debug, Clone, Copy)]
+struct BeaconClock {
+    genesis_time: u64,
+    seconds_per_slot: u64,
+}
+
+impl BeaconClock {
+    /// Fetches the genesis time and slot time of the chain from the beacon node.
+    async fn fetch(beacon_url: &Url) -> Result<Self> {
+        let genesis = beacon_get(beacon_url, "eth/v1/beacon/genesis").await?;
+        let spec = beacon_get(beacon_url, "eth/v1/config/spec").await?;
+
+        let field = |value: &Value, pointer: &str| -> Result<u64> {
+            let field = value.pointer(pointer).and_then(Value::as_str);
+            Ok(field.wrap_err_with(|| format!("missing {pointer}"))?.parse()?)
+        };
+
+        Ok(Self {
+            genesis_time: field(&genesis, "/data/genesis_time")?,
+            seconds_per_slot: field(&spec, "/data/SECONDS_PER_SLOT")?,
+        })
+    }
+
+    fn slot_at(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_sub(self.genesis_time) / self.seconds_per_slot.max(1)
+    }
+}
+
+async fn beacon_get(beacon_url: &Url, path: &str) -> Result<Value> {
+    Ok(reqwest::get(beacon_url.join(path)?).await?.error_for_status()?.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_judge_commitment() {
+        let (a, b, c) = (TxHash::repeat_byte(1), TxHash::repeat_byte(2), TxHash::repeat_byte(3));
+        let in_block = |block_txs: &[TxHash]| {
+            locate(&[a, b], block_txs)
+                .into_iter()
+                .map(|(_, index)| index.map_or(Inclusion::NotIncluded, Inclusion::InBlock))
+                .collect::<Vec<_>>()
+        };
+        let any_slot = |_| true;
+
+        assert_eq!(judge(&in_block(&[c, a, b]), false, any_slot), Verdict::Honored);
+        assert_eq!(judge(&in_block(&[c, a]), false, any_slot), Verdict::Violated);
+
+        // Top-of-block commitments must open the block, in order
+        assert_eq!(judge(&in_block(&[a, b, c]), true, any_slot), Verdict::Honored);
+        assert_eq!(judge(&in_block(&[c, a, b]), true, any_slot), Verdict::Violated);
+        assert_eq!(judge(&in_block(&[b, a]), true, any_slot), Verdict::Violated);
+
+        // Transactions included in another committed slot honor a slot range
+        let elsewhere = |slot| Inclusion::Elsewhere {
+            block_number: 10,
+            block_hash: B256::repeat_byte(9),
+            slot,
+        };
+        let inclusions = [Inclusion::InBlock(0), elsewhere(105)];
+        assert_eq!(judge(&inclusions, false, |slot| (100..=110).contains(&slot)), Verdict::Honored);
+        assert_eq!(judge(&inclusions, false, |slot| slot == 100), Verdict::Violated);
+    }
+}
@@ -1,40 +1,24 @@
 use alloy::primitives::B256;
 use blst::{min_pk::Signature, BLST_ERROR};
-use ethereum_consensus::{
-    crypto::PublicKey as BlsPublicKey,
-    deneb::{compute_fork_data_root, compute_signing_root, Root},
-};
+use bolt_primitives::signing::{commit_boost_signing_root, compute_domain, compute_signing_root};
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
 use eyre::{eyre, Result};
 
 use crate::cli::Chain;
 
-/// The domain mask for the Commit Boost domain.
-pub const COMMIT_BOOST_DOMAIN_MASK: [u8; 4] = [109, 109, 111, 67];
+pub use bolt_primitives::signing::COMMIT_BOOST_DOMAIN_MASK;
 
 /// The BLS Domain Separator used in Ethereum 2.0.
 pub const BLS_DST_PREFIX: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
 
 /// Helper function to compute the signing root for a message
 pub fn compute_commit_boost_signing_root(message: [u8; 32], chain: &Chain) -> Result<B256> {
-    compute_signing_root(&message, compute_domain_from_mask(chain.fork_version()))
-        // Ethereum-consensus uses a different version of alloy so we need to do this cast
-        .map(|r| B256::from_slice(r.to_vec().as_slice()))
-        .map_err(|e| eyre!("Failed to compute signing root: {}", e))
+    Ok(commit_boost_signing_root(message, chain.fork_version()).into())
 }
 
 /// Compute the commit boost domain from the fork version
 pub fn compute_domain_from_mask(fork_version: [u8; 4]) -> [u8; 32] {
-    let mut domain = [0; 32];
-
-    // Note: the application builder domain specs require the genesis_validators_root
-    // to be 0x00 for any out-of-protocol message. The commit-boost domain follows the
-    // same rule.
-    let root = Root::default();
-    let fork_data_root = compute_fork_data_root(fork_version, root).expect("valid fork data");
-
-    domain[..4].copy_from_slice(&COMMIT_BOOST_DOMAIN_MASK);
-    domain[4..].copy_from_slice(&fork_data_root[..28]);
-    domain
+    compute_domain(COMMIT_BOOST_DOMAIN_MASK, fork_version)
 }
 
 /// Verify the signature with the public key of the signer using the Commit Boost domain.
@@ -55,7 +39,7 @@ pub fn verify_root(
     signature: &Signature,
     domain: [u8; 32],
 ) -> Result<()> {
-    let signing_root = compute_signing_root(&root, domain)?;
+    let signing_root = compute_signing_root(root, domain);
     let pk = blst::min_pk::PublicKey::from_bytes(pubkey.as_ref()).unwrap();
 
     let res = signature.verify(true, signing_root.as_ref(), BLS_DST_PREFIX, &[], &pk, true);
@@ -0,0 +1,17 @@
+use bolt_sidecar::{ChainConfig, Web3Signer, Web3SignerTlsCredentials};
+use eyre::Result;
+
+use crate::cli::Web3SignerOpts;
+
+/// Connect to the Web3Signer with the given options, listing the public keys it holds.
+///
+/// Signatures are computed over the Commit-Boost signing roots of the given chain.
+pub async fn connect(opts: Web3SignerOpts, chain: ChainConfig) -> Result<Web3Signer> {
+    let credentials = Web3SignerTlsCredentials {
+        client_cert_path: opts.tls_credentials.client_cert_path.into(),
+        client_key_path: opts.tls_credentials.client_key_path.into(),
+        ca_cert_path: opts.tls_credentials.ca_cert_path.map(Into::into),
+    };
+
+    Ok(Web3Signer::connect(opts.url, Some(&credentials), chain).await?)
+}
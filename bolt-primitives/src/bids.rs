@@ -0,0 +1,39 @@
+/// Returns the value of a bid adjusted for the gas of the constrained transactions of its
+/// slot, to compare it on a like-for-like basis with bids for unconstrained blocks.
+///
+/// Builders must include the constrained transactions but can't monetize their gas, so
+/// the value of a constrained bid is scaled up to the whole gas used by the block:
+///
+/// adjusted_value = value * gas_used / (gas_used - constrained_gas)
+///
+/// The constrained gas is capped to the gas used, and a block of constrained transactions
+/// only keeps its value as is.
+pub fn adjusted_bid_value(value: u128, gas_used: u64, constrained_gas: u64) -> u128 {
+    let constrained_gas = constrained_gas.min(gas_used);
+    let monetizable_gas = gas_used - constrained_gas;
+
+    if constrained_gas == 0 || monetizable_gas == 0 {
+        value
+    } else {
+        value.saturating_mul(gas_used as u128) / monetizable_gas as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjusted_bid_value() {
+        let value = 1_000_000;
+
+        // Unconstrained bids keep their value
+        assert_eq!(adjusted_bid_value(value, 15_000_000, 0), value);
+
+        // A quarter of the gas is constrained
+        assert_eq!(adjusted_bid_value(value, 20_000_000, 5_000_000), 1_333_333);
+
+        // A block of constrained transactions only keeps its value
+        assert_eq!(adjusted_bid_value(value, 5_000_000, 6_000_000), value);
+    }
+}
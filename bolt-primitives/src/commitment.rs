@@ -0,0 +1,162 @@
+use sha3::{Digest, Keccak256};
+
+/// The version of the canonical encoding of commitment requests signed by users.
+///
+/// It is part of the signed digest, and must be bumped whenever the encoding changes
+/// (e.g. when adding a field) so that signatures over another encoding never verify.
+pub const COMMITMENT_DIGEST_VERSION: u8 = 1;
+
+/// The type of a commitment request, as encoded in the signed digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CommitmentType {
+    /// Request of inclusion of transactions at a specific slot.
+    Inclusion = 1,
+    /// Request to reserve gas in every proposal slot of a period.
+    Reservation = 2,
+    /// Request of inclusion of transactions in any proposal slot up to a given slot.
+    InclusionBySlot = 3,
+    /// Payment voucher attached to a commitment request.
+    PaymentVoucher = 4,
+    /// Request of inclusion of transactions at the top of the block of a specific slot.
+    InclusionTopOfBlock = 5,
+    /// Request of inclusion of a rollup batch in the cheapest proposal slot within a delay.
+    InclusionBatch = 6,
+    /// Request of inclusion of encrypted transactions at a specific slot, decrypted with a key
+    /// revealed before the commitment deadline.
+    EncryptedInclusion = 7,
+}
+
+/// The fields of an inclusion request that are part of its canonical signed digest, besides
+/// the hashes of its transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InclusionDigest {
+    /// The chain the request is signed for.
+    pub chain_id: u64,
+    /// The target slot of the request.
+    pub slot: u64,
+    /// The last slot of the range of the request, if any.
+    pub max_slot: Option<u64>,
+    /// Whether the transactions are requested at the top of the block.
+    pub top: bool,
+    /// Whether the request is a rollup batch.
+    pub batch: bool,
+    /// Whether the request accepts a commitment to a prefix of its transactions.
+    pub partial: bool,
+}
+
+impl InclusionDigest {
+    /// Creates the digest fields of a plain inclusion request at the given slot.
+    pub fn new(chain_id: u64, slot: u64) -> Self {
+        Self { chain_id, slot, ..Default::default() }
+    }
+
+    /// Returns the commitment type encoded in the digest.
+    pub fn commitment_type(&self) -> CommitmentType {
+        match (self.top, self.batch, self.max_slot) {
+            (true, _, _) => CommitmentType::InclusionTopOfBlock,
+            (false, true, _) => CommitmentType::InclusionBatch,
+            (false, false, Some(_)) => CommitmentType::InclusionBySlot,
+            (false, false, None) => CommitmentType::Inclusion,
+        }
+    }
+
+    /// Returns the canonical digest of the request with the given transaction hashes:
+    ///
+    /// digest = keccak256(
+    ///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | be_bytes(target_slot) |
+    ///     bytes(tx_hash1) | bytes(tx_hash2) | ...
+    /// )
+    ///
+    /// which is equivalent to Solidity's
+    /// `keccak256(abi.encodePacked(uint8, uint8, uint64, uint64, bytes32[]))`.
+    ///
+    /// For requests with a slot range, `be_bytes(max_slot)` follows the target slot, and for
+    /// rollup batches `be_bytes(max_delay)`. For requests accepting partial commitment, `u8(1)`
+    /// follows the transaction hashes.
+    pub fn signing_digest<H: AsRef<[u8]>>(
+        &self,
+        tx_hashes: impl IntoIterator<Item = H>,
+    ) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update([COMMITMENT_DIGEST_VERSION, self.commitment_type() as u8]);
+        hasher.update(self.chain_id.to_be_bytes());
+        hasher.update(self.slot.to_be_bytes());
+        if let Some(max_slot) = self.max_slot {
+            let bound = if self.batch { max_slot.saturating_sub(self.slot) } else { max_slot };
+            hasher.update(bound.to_be_bytes());
+        }
+        for tx_hash in tx_hashes {
+            hasher.update(tx_hash);
+        }
+        if self.partial {
+            hasher.update([1]);
+        }
+
+        hasher.finalize().into()
+    }
+}
+
+/// Returns the legacy digest of an inclusion request:
+///
+/// digest = keccak256(bytes(tx_hash1) | bytes(tx_hash2) | ... | le_bytes(target_slot))
+///
+/// For requests with a slot range, `le_bytes(max_slot)` is appended.
+///
+/// NOTE: this is also the commitment ID verified by the `BoltChallenger` contract.
+pub fn legacy_inclusion_digest<H: AsRef<[u8]>>(
+    tx_hashes: impl IntoIterator<Item = H>,
+    slot: u64,
+    max_slot: Option<u64>,
+) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for tx_hash in tx_hashes {
+        hasher.update(tx_hash);
+    }
+    hasher.update(slot.to_le_bytes());
+    if let Some(max_slot) = max_slot {
+        hasher.update(max_slot.to_le_bytes());
+    }
+
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusion_signing_digest() {
+        let tx_hashes = [[1; 32], [2; 32]];
+        let digest = InclusionDigest::new(17000, 100);
+
+        // Matches `keccak256(abi.encodePacked(uint8(1), uint8(1), uint64, uint64, bytes32[]))`
+        let mut data = vec![COMMITMENT_DIGEST_VERSION, CommitmentType::Inclusion as u8];
+        data.extend_from_slice(&17000u64.to_be_bytes());
+        data.extend_from_slice(&100u64.to_be_bytes());
+        data.extend_from_slice(&[[1; 32], [2; 32]].concat());
+        assert_eq!(digest.signing_digest(tx_hashes), <[u8; 32]>::from(Keccak256::digest(&data)));
+
+        // Every flag changes the commitment type or the encoding
+        let variants = [
+            InclusionDigest { top: true, ..digest },
+            InclusionDigest { max_slot: Some(102), ..digest },
+            InclusionDigest { max_slot: Some(102), batch: true, ..digest },
+            InclusionDigest { partial: true, ..digest },
+        ];
+        for variant in variants {
+            assert_ne!(variant.signing_digest(tx_hashes), digest.signing_digest(tx_hashes));
+        }
+        assert_eq!(variants[2].commitment_type(), CommitmentType::InclusionBatch);
+    }
+
+    #[test]
+    fn test_legacy_inclusion_digest() {
+        let mut data = [[1; 32], [2; 32]].concat();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        assert_eq!(
+            legacy_inclusion_digest([[1; 32], [2; 32]], 100, None),
+            <[u8; 32]>::from(Keccak256::digest(&data))
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use sha2::{Digest, Sha256};
+
+/// Returns the digest of a constraints message, signed by the proposer or its delegatee:
+///
+/// digest = sha256(bytes(pubkey) | le_bytes(slot) | u8(top) | bytes(tx_hash1) | ...)
+///
+/// The index of a top-of-block bundle, if any, is appended after the top flag as
+/// `le_bytes(index)`, so that the digests of the messages without one are unchanged.
+pub fn constraints_digest<H: AsRef<[u8]>>(
+    pubkey: &[u8],
+    slot: u64,
+    top: bool,
+    index: Option<u64>,
+    tx_hashes: impl IntoIterator<Item = H>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(pubkey);
+    hasher.update(slot.to_le_bytes());
+    hasher.update([top as u8]);
+    if let Some(index) = index {
+        hasher.update(index.to_le_bytes());
+    }
+
+    for tx_hash in tx_hashes {
+        hasher.update(tx_hash);
+    }
+
+    hasher.finalize().into()
+}
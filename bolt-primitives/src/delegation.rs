@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Event types that can be emitted by the validator pubkey to
+/// signal some action on the Bolt protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SignedMessageAction {
+    /// Signal delegation of a validator pubkey to a delegatee pubkey.
+    Delegation,
+    /// Signal revocation of a previously delegated pubkey.
+    Revocation,
+    /// Attest the ECDSA key signing the commitments made on behalf of the validator pubkey.
+    CommitmentKey,
+}
+
+/// The bit of [DelegationScope::commitment_types] allowing inclusion constraints.
+pub const INCLUSION_SCOPE: u8 = 1 << 0;
+
+/// The bit of [DelegationScope::commitment_types] allowing top-of-block constraints.
+pub const TOP_OF_BLOCK_SCOPE: u8 = 1 << 1;
+
+/// The limits of the constraints a delegatee can sign on behalf of a validator, signed
+/// along with the delegation and enforced by bolt-boost on the constraints submitted by
+/// the delegatee.
+///
+/// Each limit is disabled when zero, so that the default scope is unscoped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssz", derive(ssz_derive::Encode, ssz_derive::Decode))]
+#[serde(default)]
+pub struct DelegationScope {
+    /// The max gas the delegatee can constrain per slot.
+    pub max_gas_per_slot: u64,
+    /// The bitmask of the commitment types the delegatee can sign constraints for, see
+    /// [INCLUSION_SCOPE] and [TOP_OF_BLOCK_SCOPE].
+    pub commitment_types: u8,
+    /// The last slot the delegatee can sign constraints for.
+    pub expiry_slot: u64,
+}
+
+/// Constraints signed by a delegatee beyond the scope of its delegation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScopeViolation {
+    #[error("Delegation expired at slot {0}")]
+    Expired(u64),
+    #[error("Delegation doesn't allow {0} constraints")]
+    CommitmentType(&'static str),
+    #[error("Max gas per slot of the delegation reached: {0}")]
+    GasLimitReached(u64),
+}
+
+impl DelegationScope {
+    /// Returns true if none of the limits is set.
+    pub fn is_unscoped(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if the delegation expired before the given slot.
+    pub fn is_expired(&self, slot: u64) -> bool {
+        self.expiry_slot != 0 && slot > self.expiry_slot
+    }
+
+    /// Returns true if the delegatee can sign constraints for the given slot, of the
+    /// top-of-block type or not.
+    pub fn allows(&self, slot: u64, top: bool) -> bool {
+        let commitment_type = if top { TOP_OF_BLOCK_SCOPE } else { INCLUSION_SCOPE };
+        !self.is_expired(slot) &&
+            (self.commitment_types == 0 || self.commitment_types & commitment_type != 0)
+    }
+
+    /// Checks constraints of the given slot and type against the scope, where `gas` returns
+    /// the gas the delegatee would constrain in the slot with them. It is only called if the
+    /// gas is limited.
+    pub fn check(
+        &self,
+        slot: u64,
+        top: bool,
+        gas: impl FnOnce() -> u64,
+    ) -> Result<(), ScopeViolation> {
+        if self.is_expired(slot) {
+            return Err(ScopeViolation::Expired(self.expiry_slot));
+        }
+
+        let (commitment_type, name) =
+            if top { (TOP_OF_BLOCK_SCOPE, "top-of-block") } else { (INCLUSION_SCOPE, "inclusion") };
+        if self.commitment_types != 0 && self.commitment_types & commitment_type == 0 {
+            return Err(ScopeViolation::CommitmentType(name));
+        }
+
+        if self.max_gas_per_slot != 0 && gas() > self.max_gas_per_slot {
+            return Err(ScopeViolation::GasLimitReached(self.max_gas_per_slot));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the digest of a delegation message with the given action byte:
+///
+/// digest = sha256(
+///     u8(action) | bytes(validator_pubkey) | bytes(delegatee_pubkey) |
+///     le_bytes(max_gas_per_slot) | u8(commitment_types) | le_bytes(expiry_slot)
+/// )
+///
+/// The scope is only part of the digest if set, so that unscoped delegations keep the
+/// digest they had before scopes were introduced.
+pub fn delegation_digest(
+    action: u8,
+    validator_pubkey: &[u8],
+    delegatee_pubkey: &[u8],
+    scope: &DelegationScope,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([action]);
+    hasher.update(validator_pubkey);
+    hasher.update(delegatee_pubkey);
+
+    if !scope.is_unscoped() {
+        hasher.update(scope.max_gas_per_slot.to_le_bytes());
+        hasher.update([scope.commitment_types]);
+        hasher.update(scope.expiry_slot.to_le_bytes());
+    }
+
+    hasher.finalize().into()
+}
+
+/// Returns the digest of a revocation message with the given action byte:
+///
+/// digest = sha256(u8(action) | bytes(validator_pubkey) | bytes(delegatee_pubkey))
+pub fn revocation_digest(action: u8, validator_pubkey: &[u8], delegatee_pubkey: &[u8]) -> [u8; 32] {
+    delegation_digest(action, validator_pubkey, delegatee_pubkey, &DelegationScope::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delegation_digest() {
+        let (validator, delegatee) = ([1; 48], [2; 48]);
+        let action = SignedMessageAction::Delegation as u8;
+        let unscoped =
+            delegation_digest(action, &validator, &delegatee, &DelegationScope::default());
+
+        // Unscoped delegations share the layout of revocations
+        assert_eq!(unscoped, revocation_digest(action, &validator, &delegatee));
+
+        let scope = DelegationScope { expiry_slot: 100, ..Default::default() };
+        assert_ne!(delegation_digest(action, &validator, &delegatee, &scope), unscoped);
+    }
+
+    #[test]
+    fn test_delegation_scope() {
+        let scope = DelegationScope {
+            max_gas_per_slot: 100,
+            commitment_types: INCLUSION_SCOPE,
+            expiry_slot: 10,
+        };
+
+        assert!(scope.allows(10, false));
+        assert!(!scope.allows(10, true));
+        assert!(!scope.allows(11, false));
+
+        assert_eq!(scope.check(10, false, || 100), Ok(()));
+        assert_eq!(scope.check(11, false, || 0), Err(ScopeViolation::Expired(10)));
+        assert_eq!(
+            scope.check(10, true, || 0),
+            Err(ScopeViolation::CommitmentType("top-of-block"))
+        );
+        assert_eq!(scope.check(10, false, || 101), Err(ScopeViolation::GasLimitReached(100)));
+    }
+}
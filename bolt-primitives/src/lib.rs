@@ -0,0 +1,33 @@
+//! Primitives shared by the bolt sidecar, bolt-boost and the bolt CLI.
+//!
+//! Each component has its own wire types, built on its own versions of alloy and of the BLS
+//! libraries, but every signature they produce or verify is made over the digests and signing
+//! roots defined here, so that the three implementations can't drift apart.
+
+/// Valuation of the bids for blocks with constrained transactions.
+pub mod bids;
+pub use bids::adjusted_bid_value;
+
+/// Digests of the constraints submitted to the relays.
+pub mod constraints;
+pub use constraints::constraints_digest;
+
+/// Digests of the commitment requests signed by users.
+pub mod commitment;
+pub use commitment::{
+    legacy_inclusion_digest, CommitmentType, InclusionDigest, COMMITMENT_DIGEST_VERSION,
+};
+
+/// Delegations, revocations and their scopes.
+pub mod delegation;
+pub use delegation::{
+    delegation_digest, revocation_digest, DelegationScope, ScopeViolation, SignedMessageAction,
+    INCLUSION_SCOPE, TOP_OF_BLOCK_SCOPE,
+};
+
+/// Signing domains and roots of the out-of-protocol messages.
+pub mod signing;
+pub use signing::{
+    commit_boost_signing_root, compute_domain, compute_signing_root,
+    APPLICATION_BUILDER_DOMAIN_MASK, COMMIT_BOOST_DOMAIN_MASK,
+};
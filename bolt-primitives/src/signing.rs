@@ -0,0 +1,69 @@
+use sha2::{Digest, Sha256};
+
+/// The domain mask for signing application-builder messages.
+pub const APPLICATION_BUILDER_DOMAIN_MASK: [u8; 4] = [0, 0, 0, 1];
+
+/// The domain mask for the Commit Boost domain.
+pub const COMMIT_BOOST_DOMAIN_MASK: [u8; 4] = [109, 109, 111, 67];
+
+/// Computes the signing domain of the given mask on the chain of the given fork version:
+///
+/// domain = mask | fork_data_root[..28]
+///
+/// Note: the application builder domain specs require the genesis_validators_root to be
+/// 0x00 for any out-of-protocol message. The commit-boost domain follows the same rule.
+pub fn compute_domain(mask: [u8; 4], fork_version: [u8; 4]) -> [u8; 32] {
+    // The hash tree root of `ForkData { current_version, genesis_validators_root }`
+    let mut version = [0; 32];
+    version[..4].copy_from_slice(&fork_version);
+    let fork_data_root = compute_signing_root(version, [0; 32]);
+
+    let mut domain = [0; 32];
+    domain[..4].copy_from_slice(&mask);
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain
+}
+
+/// Computes the signing root of an object root in the given domain, i.e. the hash tree root
+/// of `SigningData { object_root, domain }`.
+pub fn compute_signing_root(object_root: [u8; 32], domain: [u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(object_root);
+    hasher.update(domain);
+    hasher.finalize().into()
+}
+
+/// Computes the signing root of a message digest in the Commit Boost domain of the chain of
+/// the given fork version.
+pub fn commit_boost_signing_root(digest: [u8; 32], fork_version: [u8; 4]) -> [u8; 32] {
+    compute_signing_root(digest, compute_domain(COMMIT_BOOST_DOMAIN_MASK, fork_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_compute_domain() {
+        let mainnet = compute_domain(APPLICATION_BUILDER_DOMAIN_MASK, [0, 0, 0, 0]);
+        assert_eq!(
+            hex(&mainnet),
+            "00000001f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9"
+        );
+
+        let holesky = compute_domain(APPLICATION_BUILDER_DOMAIN_MASK, [1, 1, 112, 0]);
+        assert_eq!(
+            hex(&holesky),
+            "000000015b83a23759c560b2d0c64576e1dcfc34ea94c4988f3e0d9f77f05387"
+        );
+
+        // Only the mask differs between the domains of a chain
+        let commit_boost = compute_domain(COMMIT_BOOST_DOMAIN_MASK, [1, 1, 112, 0]);
+        assert_eq!(&commit_boost[..4], &COMMIT_BOOST_DOMAIN_MASK);
+        assert_eq!(&commit_boost[4..], &holesky[4..]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Benchmarks of the hottest paths of the sidecar, on a synthetic workload of 50 inclusion
+//! requests of 2 transactions each: commitment signature verification, constraint insertion
+//! into the block template, template accounting, and inclusion proof verification.
+//!
+//! Run with `cargo bench --bench hot_paths`, or `bolt bench` for a quick time-sliced run
+//! compared against a baseline.
+
+use bolt_sidecar::bench::Workload;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_hot_paths(c: &mut Criterion) {
+    let workload = Workload::generate(50, 2).expect("valid workload");
+    let template = workload.insert_constraints();
+
+    let mut group = c.benchmark_group("hot_paths_50_requests");
+
+    group.bench_function("commitment_verification", |b| {
+        b.iter(|| black_box(&workload).verify_commitments())
+    });
+
+    group.bench_function("constraint_insertion", |b| {
+        b.iter(|| black_box(&workload).insert_constraints())
+    });
+
+    group.bench_function("template_accounting", |b| {
+        b.iter(|| black_box(&workload).account_template(black_box(&template)))
+    });
+
+    group.bench_function("proof_verification", |b| b.iter(|| black_box(&workload).verify_proofs()));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_hot_paths);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! Benchmarks of the transaction handling in the commitment hot path, on a bundle of 100
+//! transactions: decoding an inclusion request, cloning its transactions into constraints,
+//! and encoding them again for the relay and for inclusion proofs.
+//!
+//! Run with `cargo bench --bench transactions`.
+
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{Address, TxKind, U256},
+    signers::local::PrivateKeySigner,
+};
+use bolt_sidecar::primitives::{
+    deserialize_txs, proofs::transaction_leaf, serialize_txs, FullTransaction,
+};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+const BUNDLE_SIZE: usize = 100;
+
+/// Returns the hex-encoded transactions of a bundle, as a JSON array.
+fn bundle_json() -> String {
+    let signer = PrivateKeySigner::random();
+
+    let txs = (0..BUNDLE_SIZE as u64)
+        .map(|nonce| {
+            let mut tx = TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit: 21_000,
+                max_fee_per_gas: 20_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+                to: TxKind::Call(Address::with_last_byte(nonce as u8)),
+                value: U256::from(nonce),
+                ..Default::default()
+            };
+            let signature = signer.sign_transaction_sync(&mut tx).expect("signed transaction");
+            let raw = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
+            format!("0x{}", hex::encode(raw))
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string(&txs).expect("valid JSON")
+}
+
+fn decode(json: &str) -> Vec<FullTransaction> {
+    deserialize_txs(&mut serde_json::Deserializer::from_str(json)).expect("valid transactions")
+}
+
+fn bench_bundle(c: &mut Criterion) {
+    let json = bundle_json();
+    let txs = decode(&json);
+
+    let mut group = c.benchmark_group("bundle_100_txs");
+
+    group.bench_function("decode", |b| b.iter(|| decode(black_box(&json))));
+
+    group.bench_function("clone", |b| b.iter(|| black_box(&txs).clone()));
+
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            let mut out = Vec::with_capacity(json.len());
+            serialize_txs(black_box(&txs), &mut serde_json::Serializer::new(&mut out))
+                .expect("serialized transactions");
+            out
+        })
+    });
+
+    group.bench_function("envelope_encoded", |b| {
+        b.iter(|| black_box(&txs).iter().map(|tx| tx.envelope_encoded()).collect::<Vec<_>>())
+    });
+
+    // The previous behaviour: re-encoding the decoded transactions every time
+    group.bench_function("envelope_reencoded", |b| {
+        b.iter(|| black_box(&txs).iter().map(|tx| (**tx).envelope_encoded()).collect::<Vec<_>>())
+    });
+
+    group.bench_function("transaction_leaves", |b| {
+        b.iter_batched(
+            // Start from freshly decoded transactions, whose payload encodings are not cached yet
+            || decode(&json),
+            |txs| txs.iter().map(transaction_leaf).collect::<Result<Vec<_>, _>>(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_bundle);
+criterion_main!(benches);
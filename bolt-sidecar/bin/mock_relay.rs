@@ -0,0 +1,99 @@
+use std::{net::IpAddr, time::Duration};
+
+use axum::http::StatusCode;
+use clap::Parser;
+use eyre::{bail, Result};
+use tracing::info;
+
+use bolt_sidecar::{
+    devnet::{faults::parse_endpoint_value, FaultInjection, MockRelay, RelayEndpoint},
+    telemetry::init_telemetry_stack,
+    ChainConfig,
+};
+
+/// A mock relay implementing the Constraints API, to rehearse how the Bolt sidecar
+/// behaves with a slow or unreliable relay.
+#[derive(Debug, Parser)]
+struct MockRelayOpts {
+    /// Address to listen on
+    #[clap(long, env = "BOLT_MOCK_RELAY_HOST", default_value = "127.0.0.1")]
+    host: IpAddr,
+    /// Port to listen on
+    #[clap(long, env = "BOLT_MOCK_RELAY_PORT", default_value_t = 3030)]
+    port: u16,
+    /// Artificial latency of an endpoint in milliseconds, as `<endpoint>=<millis>`.
+    /// Can be repeated or comma-separated, e.g. "get-header=800,get-payload=200".
+    ///
+    /// Endpoints: status, register-validators, submit-constraints, delegate, revoke,
+    /// get-header, get-payload
+    #[clap(
+        long = "latency",
+        env = "BOLT_MOCK_RELAY_LATENCIES",
+        value_delimiter = ',',
+        value_parser = parse_endpoint_value::<u64>
+    )]
+    latencies: Vec<(RelayEndpoint, u64)>,
+    /// Probability in [0, 1] that requests to an endpoint fail, as `<endpoint>=<rate>`.
+    /// Can be repeated or comma-separated, e.g. "submit-constraints=0.5".
+    #[clap(
+        long = "failure-rate",
+        env = "BOLT_MOCK_RELAY_FAILURE_RATES",
+        value_delimiter = ',',
+        value_parser = parse_endpoint_value::<f64>
+    )]
+    failure_rates: Vec<(RelayEndpoint, f64)>,
+    /// Number of upcoming requests to an endpoint that fail, as `<endpoint>=<count>`.
+    /// Can be repeated or comma-separated, e.g. "get-header=3".
+    #[clap(
+        long = "fail-next",
+        env = "BOLT_MOCK_RELAY_FAIL_NEXT",
+        value_delimiter = ',',
+        value_parser = parse_endpoint_value::<u64>
+    )]
+    fail_next: Vec<(RelayEndpoint, u64)>,
+    /// HTTP status code returned by failing requests
+    #[clap(long, env = "BOLT_MOCK_RELAY_FAILURE_STATUS", default_value_t = 500)]
+    failure_status: u16,
+    /// Chain config, used to sign the returned bids
+    #[clap(flatten)]
+    chain: ChainConfig,
+}
+
+impl MockRelayOpts {
+    fn faults(&self) -> Result<FaultInjection> {
+        let Ok(status) = StatusCode::from_u16(self.failure_status) else {
+            bail!("Invalid failure status code: {}", self.failure_status);
+        };
+
+        let mut faults = FaultInjection::default().with_failure_status(status);
+        for (endpoint, millis) in &self.latencies {
+            faults = faults.with_latency(*endpoint, Duration::from_millis(*millis));
+        }
+        for (endpoint, rate) in &self.failure_rates {
+            faults = faults.with_failure_rate(*endpoint, *rate);
+        }
+        for (endpoint, count) in &self.fail_next {
+            faults = faults.with_fail_next(*endpoint, *count);
+        }
+
+        Ok(faults)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let opts = MockRelayOpts::parse();
+
+    if let Err(err) = init_telemetry_stack(None, None) {
+        bail!("Failed to initialize telemetry stack: {:?}", err);
+    }
+
+    let faults = opts.faults()?;
+    let relay = MockRelay::start_on((opts.host, opts.port).into(), opts.chain).await?;
+    relay.set_faults(faults.clone());
+
+    info!(url = %relay.url(), ?faults, "Mock relay running, press CTRL+C to stop");
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -1,39 +1,188 @@
+use std::{collections::BTreeMap, net::SocketAddr};
+
 use clap::Parser;
 use eyre::{bail, Result};
-use tracing::info;
+use futures::FutureExt;
+use tracing::{error, info, warn};
+
+use bolt_sidecar::{
+    devnet::{DevMode, DevOpts},
+    read_networks_file, start_admin_server,
+    telemetry::{init_telemetry_stack, LogFilterHandle},
+    AdminServerConfig, Command, NetworkRouter, OperatorHandles, Opts, SidecarBuilder,
+    SidecarDriver,
+};
 
-use bolt_sidecar::{telemetry::init_telemetry_stack, Opts, SidecarDriver};
+#[cfg(unix)]
+use bolt_sidecar::service::SystemdNotifier;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let opts = Opts::parse();
+    if DevOpts::requested() {
+        return run_dev_mode(DevOpts::parse()).await;
+    }
+
+    let mut opts = Opts::parse();
+
+    match &opts.command {
+        None | Some(Command::Run) => {}
+        Some(command) => return command.execute(&opts).await,
+    }
+
+    if opts.print_config {
+        println!("{}", opts.to_pretty_json()?);
+    }
+
+    if let Err(errors) = opts.validate() {
+        bail!("{errors}");
+    }
 
-    if let Err(err) = init_telemetry_stack(opts.telemetry.metrics_port()) {
-        bail!("Failed to initialize telemetry stack: {:?}", err)
+    let networks = match &opts.networks_config_path {
+        Some(path) => read_networks_file(path, &opts)?,
+        None => BTreeMap::new(),
+    };
+
+    if opts.print_config {
+        return Ok(());
     }
 
+    let log_filter =
+        match init_telemetry_stack(opts.telemetry.metrics_port(), opts.telemetry.otlp_endpoint()) {
+            Ok(log_filter) => log_filter,
+            Err(err) => bail!("Failed to initialize telemetry stack: {:?}", err),
+        };
+
     info!(chain = opts.chain.name(), "Starting Bolt sidecar");
 
-    if opts.constraint_signing.constraint_private_key.is_some() {
+    // The additional networks run in the background, and the primary one in the foreground
+    if !networks.is_empty() {
+        let network_router = NetworkRouter::new(opts.chain.name());
+        for (network, mut network_opts) in networks {
+            info!(%network, chain = network_opts.chain.name(), "Starting additional network");
+            network_opts.network_router = Some(network_router.for_network(&network));
+            if let Err(err) = SidecarBuilder::new(network_opts).spawn().await {
+                bail!("Failed to start network {network}: {:?}", err)
+            }
+        }
+        opts.network_router = Some(network_router);
+    }
+
+    // The drivers of the signers differ in type, so each branch boxes its event loop
+    let (handles, event_loop) = if opts.constraint_signing.constraint_private_key.is_some() {
         match SidecarDriver::with_local_signer(&opts).await {
-            Ok(driver) => driver.run_forever().await,
+            Ok(driver) => (driver.operator_handles(), driver.run_forever().boxed_local()),
             Err(err) => {
                 bail!("Failed to initialize the sidecar driver with local signer: {:?}", err)
             }
         }
     } else if opts.constraint_signing.commit_boost_signer_url.is_some() {
         match SidecarDriver::with_commit_boost_signer(&opts).await {
-            Ok(driver) => driver.run_forever().await,
+            Ok(driver) => (driver.operator_handles(), driver.run_forever().boxed_local()),
             Err(err) => {
                 bail!("Failed to initialize the sidecar driver with commit boost: {:?}", err)
             }
         }
+    } else if opts.constraint_signing.web3signer_url.is_some() {
+        match SidecarDriver::with_web3signer(&opts).await {
+            Ok(driver) => (driver.operator_handles(), driver.run_forever().boxed_local()),
+            Err(err) => {
+                bail!("Failed to initialize the sidecar driver with Web3Signer: {:?}", err)
+            }
+        }
     } else {
         match SidecarDriver::with_keystore_signer(&opts).await {
-            Ok(driver) => driver.run_forever().await,
+            Ok(driver) => (driver.operator_handles(), driver.run_forever().boxed_local()),
             Err(err) => {
                 bail!("Failed to initialize the sidecar driver with keystore signer: {:?}", err)
             }
         }
+    };
+
+    start_operator_apis(&opts, log_filter, handles)?;
+    event_loop.await
+}
+
+/// Run the sidecar in localhost development mode, against an in-process devnet.
+async fn run_dev_mode(opts: DevOpts) -> Result<()> {
+    if let Err(err) = init_telemetry_stack(None, None) {
+        bail!("Failed to initialize telemetry stack: {:?}", err);
+    }
+
+    info!("Starting Bolt sidecar in development mode");
+    let dev = DevMode::start(opts).await?;
+    dev.print_summary();
+    dev.run().await
+}
+
+/// Start the admin API server, the SIGHUP configuration reloader, the whitelist file watcher
+/// and the multi-network commitments API, if enabled.
+fn start_operator_apis(
+    opts: &Opts,
+    log_filter: LogFilterHandle,
+    handles: OperatorHandles,
+) -> Result<()> {
+    let OperatorHandles {
+        reloader,
+        templates,
+        delegations,
+        relays,
+        submissions,
+        fee_recipients,
+        health,
+    } = handles;
+    let reloader = reloader.with_log_filter(log_filter.clone());
+
+    // Under systemd, readiness is notified once the sidecar can serve its first slot
+    #[cfg(unix)]
+    match SystemdNotifier::from_env() {
+        Ok(Some(notifier)) => {
+            info!(watchdog = ?notifier.watchdog(), "Notifying systemd of the sidecar health");
+            tokio::spawn(notifier.run(health));
+        }
+        Ok(None) => {}
+        Err(err) => warn!(?err, "Failed to set up the systemd notifications"),
+    }
+    #[cfg(not(unix))]
+    let _ = health;
+
+    #[cfg(unix)]
+    if let Some(path) = opts.reload_config_path.clone() {
+        info!(path = %path.display(), "Reloading the configuration file on SIGHUP");
+        bolt_sidecar::spawn_sighup_reloader(reloader.clone(), path)?;
     }
+
+    if let Some(path) = opts.whitelist.whitelist_path.clone() {
+        info!(path = %path.display(), "Reloading the whitelist when its file changes");
+        bolt_sidecar::spawn_whitelist_watcher(reloader.clone(), path);
+    }
+
+    if let Some(network_router) = opts.network_router.clone() {
+        let addr = SocketAddr::from(([0, 0, 0, 0], opts.port));
+        tokio::spawn(async move {
+            if let Err(err) = network_router.serve(addr).await {
+                error!(?err, "Multi-network commitments API server failed");
+            }
+        });
+    }
+
+    if let Some(server_port) = opts.admin_port {
+        let config = AdminServerConfig {
+            server_port,
+            log_filter,
+            reloader: Some(reloader),
+            templates: Some(templates),
+            delegations: Some(delegations),
+            relays: Some(relays),
+            submissions: Some(submissions),
+            fee_recipients: Some(fee_recipients),
+            retention: opts.vouchers.retention_policy(),
+        };
+        tokio::spawn(async move {
+            if let Err(err) = start_admin_server(config).await {
+                error!(?err, "Admin API server failed");
+            }
+        });
+    }
+
+    Ok(())
 }
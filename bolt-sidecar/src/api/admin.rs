@@ -0,0 +1,346 @@
+use std::{io, sync::Arc, time::SystemTime};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+use crate::{
+    builder::{TemplateReader, TemplateSnapshot},
+    config::{ConfigChange, ConfigReloader, ReloadError, ReloadSource, ReloadableConfig},
+    state::{CompactionReport, RetentionPolicy},
+    telemetry::{LogFilterError, LogFilterHandle},
+    DelegationTracker, FeeRecipientGuard, RelayDelegations, RelayFeeRecipients, RelayThrottle,
+    RelayThrottleState, SlotSubmissions, SubmissionLog,
+};
+
+/// The path to the admin API log filter endpoint.
+pub const LOG_FILTER_PATH: &str = "/admin/v1/log_filter";
+
+/// The path to the admin API configuration reload endpoint.
+pub const CONFIG_PATH: &str = "/admin/v1/config";
+
+/// The path to the admin API block template snapshot endpoint.
+pub const TEMPLATE_PATH: &str = "/admin/v1/template/:slot";
+
+/// The path to the admin API per-relay delegation state endpoint.
+pub const DELEGATIONS_PATH: &str = "/admin/v1/delegations";
+
+/// The path to the admin API per-relay rate limit state endpoint.
+pub const RELAYS_PATH: &str = "/admin/v1/relays";
+
+/// The path to the admin API endpoint of the constraints submitted for a slot.
+pub const SUBMISSIONS_PATH: &str = "/admin/v1/constraints/:slot";
+
+/// The path to the admin API per-relay fee recipient checks endpoint.
+pub const FEE_RECIPIENTS_PATH: &str = "/admin/v1/relays/fee_recipients";
+
+/// The path to the admin API endpoint archiving the files past their retention period.
+pub const RETENTION_PATH: &str = "/admin/v1/retention";
+
+/// Errors returned by the admin API.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum AdminApiError {
+    #[error(transparent)]
+    LogFilter(#[from] LogFilterError),
+    #[error(transparent)]
+    Reload(#[from] ReloadError),
+    #[error("Configuration reloads are not available")]
+    ReloadUnavailable,
+    #[error("Block template snapshots are not available")]
+    TemplateUnavailable,
+    #[error("No retention policy is configured")]
+    RetentionUnavailable,
+    #[error("The constraint submissions are not available")]
+    SubmissionsUnavailable,
+    #[error("No constraints were submitted for slot {0}")]
+    NoSubmissions(u64),
+    #[error("Failed to read the submission log: {0}")]
+    SubmissionLog(io::Error),
+    #[error("Failed to archive files: {0}")]
+    Retention(#[from] io::Error),
+}
+
+impl IntoResponse for AdminApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AdminApiError::LogFilter(LogFilterError::Parse(_)) => StatusCode::BAD_REQUEST,
+            AdminApiError::LogFilter(LogFilterError::Reload(_)) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AdminApiError::Reload(ReloadError::Invalid(_) | ReloadError::LogFilter(_)) => {
+                StatusCode::BAD_REQUEST
+            }
+            AdminApiError::Reload(ReloadError::DriverUnavailable) |
+            AdminApiError::ReloadUnavailable |
+            AdminApiError::TemplateUnavailable |
+            AdminApiError::RetentionUnavailable |
+            AdminApiError::SubmissionsUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            AdminApiError::NoSubmissions(_) => StatusCode::NOT_FOUND,
+            AdminApiError::Reload(_) |
+            AdminApiError::Retention(_) |
+            AdminApiError::SubmissionLog(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// The tracing filter directives, in the same format as `RUST_LOG`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFilter {
+    /// Comma-separated list of directives, e.g. `info,bolt_sidecar::client=debug`.
+    pub filter: String,
+}
+
+/// Configuration for the admin API server.
+#[derive(Debug, Clone)]
+pub struct AdminServerConfig {
+    /// The port on which the admin server should listen. The server is bound
+    /// to the loopback interface only.
+    pub server_port: u16,
+    /// The handle to the tracing filter of the sidecar.
+    pub log_filter: LogFilterHandle,
+    /// The handle to reload the configuration of the sidecar, if any.
+    pub reloader: Option<ConfigReloader>,
+    /// The handle to read the pending block templates of the sidecar, if any.
+    pub templates: Option<TemplateReader>,
+    /// The tracker of the delegations acknowledged by each relay, if any.
+    pub delegations: Option<DelegationTracker>,
+    /// The tracker of the rate limits of each relay, if any.
+    pub relays: Option<RelayThrottle>,
+    /// The log of the constraints submitted to each relay, if any.
+    pub submissions: Option<SubmissionLog>,
+    /// The checks of the fee recipients of the registrations and bids of each relay, if any.
+    pub fee_recipients: Option<FeeRecipientGuard>,
+    /// The retention policy of the files written by the sidecar, if any.
+    pub retention: Option<RetentionPolicy>,
+}
+
+/// The shared state of the admin API handlers.
+#[derive(Debug)]
+struct AdminState {
+    log_filter: LogFilterHandle,
+    reloader: Option<ConfigReloader>,
+    templates: Option<TemplateReader>,
+    delegations: Option<DelegationTracker>,
+    relays: Option<RelayThrottle>,
+    submissions: Option<SubmissionLog>,
+    fee_recipients: Option<FeeRecipientGuard>,
+    retention: Option<RetentionPolicy>,
+}
+
+/// Start the admin API server with the given configuration.
+///
+/// The admin API is meant for operators only and allows to adjust the sidecar
+/// at runtime without restarting it and losing its in-memory slot state.
+pub async fn start_admin_server(config: AdminServerConfig) -> eyre::Result<()> {
+    let addr = format!("127.0.0.1:{}", config.server_port);
+    info!(%addr, "Starting admin API server...");
+
+    let state = AdminState {
+        log_filter: config.log_filter,
+        reloader: config.reloader,
+        templates: config.templates,
+        delegations: config.delegations,
+        relays: config.relays,
+        submissions: config.submissions,
+        fee_recipients: config.fee_recipients,
+        retention: config.retention,
+    };
+    let router = make_router(Arc::new(state));
+
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, router).await?;
+
+    Ok(())
+}
+
+fn make_router(state: Arc<AdminState>) -> Router {
+    Router::new()
+        .route(LOG_FILTER_PATH, get(get_log_filter).put(set_log_filter).delete(reset_log_filter))
+        .route(CONFIG_PATH, put(reload_config))
+        .route(TEMPLATE_PATH, get(get_template_snapshot))
+        .route(DELEGATIONS_PATH, get(get_delegations))
+        .route(RELAYS_PATH, get(get_relays))
+        .route(FEE_RECIPIENTS_PATH, get(get_fee_recipients))
+        .route(SUBMISSIONS_PATH, get(get_submissions))
+        .route(RETENTION_PATH, post(compact_files))
+        .with_state(state)
+}
+
+/// Returns the tracing filter currently in use.
+async fn get_log_filter(
+    State(state): State<Arc<AdminState>>,
+) -> Result<Json<LogFilter>, AdminApiError> {
+    Ok(Json(LogFilter { filter: state.log_filter.current()? }))
+}
+
+/// Replaces the tracing filter with the provided directives.
+async fn set_log_filter(
+    State(state): State<Arc<AdminState>>,
+    Json(request): Json<LogFilter>,
+) -> Result<Json<LogFilter>, AdminApiError> {
+    if let Err(err) = state.log_filter.set(&request.filter) {
+        warn!(?err, filter = %request.filter, "Rejected invalid log filter");
+        return Err(err.into());
+    }
+
+    let filter = state.log_filter.current()?;
+    info!(%filter, "Updated log filter");
+    Ok(Json(LogFilter { filter }))
+}
+
+/// Restores the tracing filter that was in use at startup.
+async fn reset_log_filter(
+    State(state): State<Arc<AdminState>>,
+) -> Result<Json<LogFilter>, AdminApiError> {
+    state.log_filter.reset().inspect_err(|err| error!(?err, "Failed to reset log filter"))?;
+
+    let filter = state.log_filter.current()?;
+    info!(%filter, "Reset log filter");
+    Ok(Json(LogFilter { filter }))
+}
+
+/// Validates and applies the provided configuration, returning the settings that changed.
+async fn reload_config(
+    State(state): State<Arc<AdminState>>,
+    Json(config): Json<ReloadableConfig>,
+) -> Result<Json<Vec<ConfigChange>>, AdminApiError> {
+    let reloader = state.reloader.as_ref().ok_or(AdminApiError::ReloadUnavailable)?;
+    let changes = reloader.reload(config, ReloadSource::AdminApi).await?;
+    Ok(Json(changes))
+}
+
+/// Returns the pending block template for the given slot: the committed transactions
+/// in order, the remaining gas and blob budget, and the top-of-block reservations.
+async fn get_template_snapshot(
+    State(state): State<Arc<AdminState>>,
+    Path(slot): Path<u64>,
+) -> Result<Json<TemplateSnapshot>, AdminApiError> {
+    let templates = state.templates.as_ref().ok_or(AdminApiError::TemplateUnavailable)?;
+    let snapshot =
+        templates.snapshot(slot).await.map_err(|_| AdminApiError::TemplateUnavailable)?;
+    Ok(Json(snapshot))
+}
+
+/// Returns the state of the delegations on every relay they were sent to: acknowledged,
+/// pending or refused, along with the number of consecutive refusals.
+async fn get_delegations(State(state): State<Arc<AdminState>>) -> Json<Vec<RelayDelegations>> {
+    Json(state.delegations.as_ref().map(DelegationTracker::snapshot).unwrap_or_default())
+}
+
+/// Returns the throttling state of every relay that rate-limited requests of the sidecar:
+/// whether requests are held back, for how long, and how many were rate-limited so far.
+async fn get_relays(State(state): State<Arc<AdminState>>) -> Json<Vec<RelayThrottleState>> {
+    Json(state.relays.as_ref().map(RelayThrottle::snapshot).unwrap_or_default())
+}
+
+/// Returns the constraints submitted for the given slot with their signatures, and every
+/// submission of them to the relays: when they were sent, and when each relay acknowledged
+/// them or the error it returned.
+async fn get_submissions(
+    State(state): State<Arc<AdminState>>,
+    Path(slot): Path<u64>,
+) -> Result<Json<SlotSubmissions>, AdminApiError> {
+    let submissions = state.submissions.as_ref().ok_or(AdminApiError::SubmissionsUnavailable)?;
+    let submissions = submissions.slot(slot).map_err(AdminApiError::SubmissionLog)?;
+    submissions.map(Json).ok_or(AdminApiError::NoSubmissions(slot))
+}
+
+/// Returns the results of the checks of the fee recipients of the validator registrations
+/// forwarded to every relay and of its bids: how many were checked, how many were unexpected
+/// and the last unexpected one.
+async fn get_fee_recipients(State(state): State<Arc<AdminState>>) -> Json<Vec<RelayFeeRecipients>> {
+    Json(state.fee_recipients.as_ref().map(FeeRecipientGuard::snapshot).unwrap_or_default())
+}
+
+/// Archives the files past their retention period right away, instead of waiting for the
+/// next periodic compaction.
+async fn compact_files(
+    State(state): State<Arc<AdminState>>,
+) -> Result<Json<CompactionReport>, AdminApiError> {
+    let retention = state.retention.as_ref().ok_or(AdminApiError::RetentionUnavailable)?;
+    let report = retention.compact(SystemTime::now())?;
+    info!(compacted = report.compacted, archive = ?report.archive, "Archived files on request");
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::{reload, EnvFilter, Registry};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_log_filter() -> eyre::Result<()> {
+        let (_layer, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        let state = Arc::new(AdminState {
+            log_filter: LogFilterHandle::new(handle),
+            reloader: None,
+            templates: None,
+            delegations: Some(DelegationTracker::default()),
+            relays: Some(RelayThrottle::default()),
+            submissions: Some(SubmissionLog::default()),
+            fee_recipients: Some(FeeRecipientGuard::default()),
+            retention: None,
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}{LOG_FILTER_PATH}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, make_router(state)).await });
+
+        let client = reqwest::Client::new();
+
+        let filter = LogFilter { filter: "info,bolt_sidecar::client=debug".to_string() };
+        let res = client.put(&url).json(&filter).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let current = client.get(&url).send().await?.json::<LogFilter>().await?;
+        assert!(current.filter.contains("bolt_sidecar::client=debug"));
+
+        let invalid = LogFilter { filter: "bolt_sidecar=loud".to_string() };
+        let res = client.put(&url).json(&invalid).send().await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let config_url = url.replace(LOG_FILTER_PATH, CONFIG_PATH);
+        let res = client.put(&config_url).json(&ReloadableConfig::default()).send().await?;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let template_url = url.replace(LOG_FILTER_PATH, "/admin/v1/template/10");
+        let res = client.get(&template_url).send().await?;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let submissions_url = url.replace(LOG_FILTER_PATH, "/admin/v1/constraints/10");
+        let res = client.get(&submissions_url).send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let delegations_url = url.replace(LOG_FILTER_PATH, DELEGATIONS_PATH);
+        let delegations =
+            client.get(&delegations_url).send().await?.json::<Vec<RelayDelegations>>().await?;
+        assert!(delegations.is_empty());
+
+        let relays_url = url.replace(LOG_FILTER_PATH, RELAYS_PATH);
+        let relays =
+            client.get(&relays_url).send().await?.json::<Vec<RelayThrottleState>>().await?;
+        assert!(relays.is_empty());
+
+        let fee_recipients_url = url.replace(LOG_FILTER_PATH, FEE_RECIPIENTS_PATH);
+        let fee_recipients =
+            client.get(&fee_recipients_url).send().await?.json::<Vec<RelayFeeRecipients>>().await?;
+        assert!(fee_recipients.is_empty());
+
+        let retention_url = url.replace(LOG_FILTER_PATH, RETENTION_PATH);
+        let res = client.post(&retention_url).send().await?;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        Ok(())
+    }
+}
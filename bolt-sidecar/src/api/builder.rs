@@ -3,15 +3,14 @@ use std::{sync::Arc, time::Duration};
 use axum::{
     body::{self, Body},
     extract::{Path, Request, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Html,
     routing::{get, post},
     Json, Router,
 };
-use beacon_api_client::VersionedValue;
 use ethereum_consensus::{
     builder::SignedValidatorRegistration,
-    deneb::mainnet::SignedBlindedBeaconBlock,
+    deneb::presets::mainnet::ExecutionPayloadHeader,
     primitives::{BlsPublicKey, Hash32},
     Fork,
 };
@@ -21,19 +20,28 @@ use thiserror::Error;
 use tokio::net::TcpListener;
 use tracing::{debug, error, info, warn};
 
-use super::spec::{
-    BuilderApiError, ConstraintsApi, GET_HEADER_PATH, GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH,
-    STATUS_PATH,
+use super::{
+    spec::{
+        BuilderApiError, ConstraintsApi, CONSENSUS_VERSION_HEADER, GET_HEADER_PATH,
+        GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH, STATUS_PATH,
+    },
+    streaming::{parse_json_array, ArrayLimits},
 };
 use crate::{
     builder::payload_fetcher::PayloadFetcher,
-    client::constraints_client::ConstraintsClient,
-    primitives::{GetPayloadResponse, SignedBuilderBid},
-    telemetry::ApiMetrics,
+    client::multiplexer::RelayMultiplexer,
+    config::LatencyBudgetOpts,
+    primitives::{GetPayloadResponse, VersionedSignedBlindedBlock, VersionedSignedBuilderBid},
+    telemetry::{ApiMetrics, ProposalStage, StageTimer},
 };
 
 const MAX_BLINDED_BLOCK_LENGTH: usize = 1024 * 1024;
 
+/// The size caps of validator registration lists, which are parsed as they are received
+/// to keep the memory usage low during mass registrations.
+const REGISTRATIONS_LIMITS: ArrayLimits =
+    ArrayLimits { max_length: 64 * 1024 * 1024, max_items: 100_000 };
+
 /// TODO: determine value
 const GET_HEADER_WITH_PROOFS_TIMEOUT: Duration = Duration::from_millis(500);
 
@@ -45,9 +53,11 @@ pub struct BuilderProxyServer<T, P> {
     local_payload: Mutex<Option<GetPayloadResponse>>,
     /// The payload fetcher to get locally built payloads.
     payload_fetcher: P,
+    /// Latency budgets used to time the `get_header` and `get_payload` stages.
+    latency_budgets: LatencyBudgetOpts,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct GetHeaderParams {
     pub slot: u64,
     pub parent_hash: Hash32,
@@ -60,8 +70,8 @@ where
     T: ConstraintsApi,
     P: PayloadFetcher + Send + Sync,
 {
-    pub fn new(proxy_target: T, payload_fetcher: P) -> Self {
-        Self { proxy_target, local_payload: Mutex::new(None), payload_fetcher }
+    pub fn new(proxy_target: T, payload_fetcher: P, latency_budgets: LatencyBudgetOpts) -> Self {
+        Self { proxy_target, local_payload: Mutex::new(None), payload_fetcher, latency_budgets }
     }
 
     /// Gets the status. Just forwards the request to constraints client and returns the status.
@@ -89,9 +99,15 @@ where
     /// TODO: intercept this to register Bolt validators on-chain as well.
     pub async fn register_validators(
         State(server): State<Arc<BuilderProxyServer<T, P>>>,
-        Json(registrations): Json<Vec<SignedValidatorRegistration>>,
+        body: Body,
     ) -> Result<StatusCode, BuilderApiError> {
-        debug!("Received register validators request");
+        let registrations: Vec<SignedValidatorRegistration> =
+            parse_json_array(body, REGISTRATIONS_LIMITS).await.map_err(|e| {
+                error!(error = %e, "Failed to parse validator registrations");
+                e
+            })?;
+
+        debug!(count = registrations.len(), "Received register validators request");
         let response = server.proxy_target.register_validators(registrations).await;
         response.map(|_| StatusCode::OK)
     }
@@ -104,11 +120,10 @@ where
     pub async fn get_header(
         State(server): State<Arc<BuilderProxyServer<T, P>>>,
         Path(params): Path<GetHeaderParams>,
-    ) -> Result<Json<VersionedValue<SignedBuilderBid>>, BuilderApiError> {
-        let start = std::time::Instant::now();
-
+    ) -> Result<Json<VersionedSignedBuilderBid>, BuilderApiError> {
         debug!("Received get_header request");
         let slot = params.slot;
+        let timer = StageTimer::start(ProposalStage::GetHeader, slot, &server.latency_budgets);
 
         let err = match tokio::time::timeout(
             GET_HEADER_WITH_PROOFS_TIMEOUT,
@@ -125,7 +140,7 @@ where
                     let mut local_payload = server.local_payload.lock();
                     *local_payload = None;
 
-                    debug!(elapsed = ?start.elapsed(), "Returning signed builder bid");
+                    debug!(elapsed = ?timer.finish(), "Returning signed builder bid");
                     return Ok(Json(header));
                 }
             },
@@ -133,7 +148,7 @@ where
         };
 
         // On ANY error, we fall back to locally built block
-        warn!(slot, elapsed = ?start.elapsed(), err = ?err, "Proxy error, fetching local payload instead");
+        warn!(slot, elapsed = ?timer.elapsed(), err = ?err, "Proxy error, fetching local payload instead");
 
         let Some(payload_and_bid) = server.payload_fetcher.fetch_payload(slot).await else {
             // TODO: handle failure? In this case, we don't have a fallback block
@@ -143,9 +158,9 @@ where
             return Err(BuilderApiError::FailedToFetchLocalPayload(slot));
         };
 
-        let hash = payload_and_bid.bid.message.header.block_hash.clone();
-        let number = payload_and_bid.bid.message.header.block_number;
-        info!(elapsed = ?start.elapsed(), %hash, "Fetched local payload for slot {slot}");
+        let hash = payload_and_bid.bid.header().block_hash.clone();
+        let number = payload_and_bid.bid.header().block_number;
+        info!(elapsed = ?timer.elapsed(), %hash, "Fetched local payload for slot {slot}");
 
         {
             // Since we've signed a local header, set the payload for
@@ -154,13 +169,9 @@ where
             *local_payload = Some(payload_and_bid.payload);
         }
 
-        let versioned_bid = VersionedValue::<SignedBuilderBid> {
-            version: Fork::Deneb,
-            data: payload_and_bid.bid,
-            meta: Default::default(),
-        };
+        let versioned_bid = payload_and_bid.bid;
 
-        info!(elapsed = ?start.elapsed(), %hash, number, ?versioned_bid, "Returning locally built header");
+        info!(elapsed = ?timer.finish(), %hash, number, ?versioned_bid, "Returning locally built header");
         Ok(Json(versioned_bid))
     }
 
@@ -168,47 +179,55 @@ where
         State(server): State<Arc<BuilderProxyServer<T, P>>>,
         req: Request<Body>,
     ) -> Result<Json<GetPayloadResponse>, BuilderApiError> {
-        let start = std::time::Instant::now();
         debug!("Received get_payload request");
 
+        let fork = consensus_version(req.headers())?;
         let body_bytes =
             body::to_bytes(req.into_body(), MAX_BLINDED_BLOCK_LENGTH).await.map_err(|e| {
                 error!(error = %e, "Failed to read request body");
                 e
             })?;
 
-        // Convert to signed blinded beacon block
-        let signed_blinded_block = serde_json::from_slice::<SignedBlindedBeaconBlock>(&body_bytes)
+        // Decode the parts of the signed blinded beacon block we need, the block is
+        // forwarded to the relays as it was received
+        let signed_blinded_block = VersionedSignedBlindedBlock::from_json(fork, body_bytes)
             .map_err(|e| {
-                error!(error = %e, "Failed to parse signed blinded block");
+                error!(error = %e, %fork, "Failed to parse signed blinded block");
                 e
             })?;
 
+        let slot = signed_blinded_block.slot();
+        let timer = StageTimer::start(ProposalStage::GetPayload, slot, &server.latency_budgets);
+
         // If we have a locally built payload, it means we signed a local header.
         // Return it and clear the cache.
         if let Some(local_payload) = server.local_payload.lock().take() {
-            check_locally_built_payload_integrity(&signed_blinded_block, &local_payload)?;
+            check_locally_built_payload_integrity(
+                signed_blinded_block.execution_payload_header(),
+                &local_payload,
+            )?;
 
-            info!("Valid local block found, returning: {local_payload:?}");
+            info!(elapsed = ?timer.finish(), "Valid local block found, returning: {local_payload:?}");
             ApiMetrics::increment_local_blocks_proposed();
 
             return Ok(Json(local_payload));
         }
 
-        // TODO: how do we deal with failures here? What if we submit the signed blinded block but
-        // don't get a response? should we ignore the error or proceed with a local block
-        // (highly risky -> equivocation risk)
+        // NOTE: the proposer has signed the header of the relay, so proposing the local block
+        // instead would be an equivocation. The local fallback only applies when the relays fail
+        // at `get_header`, and a failure here can only be surfaced.
         let payload = server
             .proxy_target
             .get_payload(signed_blinded_block)
             .await
             .map(Json)
             .map_err(|e| {
-                error!(elapsed = ?start.elapsed(), error = %e, "Failed to get payload from constraints client");
+                error!(elapsed = ?timer.elapsed(), error = %e, "Failed to get payload from constraints client");
+                ApiMetrics::increment_remote_payload_failures();
                 e
             })?;
 
-        info!(elapsed = ?start.elapsed(), "Returning payload from constraints client");
+        info!(elapsed = ?timer.finish(), "Returning payload from constraints client");
         ApiMetrics::increment_remote_blocks_proposed();
 
         Ok(payload)
@@ -218,10 +237,12 @@ where
 /// Configuration for the builder proxy.
 #[derive(Debug, Clone)]
 pub struct BuilderProxyConfig {
-    /// The target constraints client server.
-    pub constraints_client: ConstraintsClient,
+    /// The relays supporting the Constraints API to proxy the requests to.
+    pub relays: RelayMultiplexer,
     /// The port on which the builder proxy should listen.
     pub server_port: u16,
+    /// Latency budgets for the `get_header` and `get_payload` stages.
+    pub latency_budgets: LatencyBudgetOpts,
 }
 
 /// Start the builder proxy with the given payload fetcher and configuration.
@@ -232,13 +253,11 @@ pub async fn start_builder_proxy_server<P>(
 where
     P: PayloadFetcher + Send + Sync + 'static,
 {
-    info!(
-        port = config.server_port,
-        target = config.constraints_client.url.to_string(),
-        "Starting builder proxy..."
-    );
+    let relays = config.relays.relays().iter().map(|relay| relay.url().to_string());
+    info!(port = config.server_port, relays = ?relays.collect::<Vec<_>>(), "Starting builder proxy...");
 
-    let server = Arc::new(BuilderProxyServer::new(config.constraints_client, payload_fetcher));
+    let server =
+        Arc::new(BuilderProxyServer::new(config.relays, payload_fetcher, config.latency_budgets));
 
     let router = Router::new()
         .route("/", get(index))
@@ -259,6 +278,18 @@ async fn index() -> Html<&'static str> {
     Html("Hello")
 }
 
+/// Returns the fork of a signed blinded block from its `Eth-Consensus-Version` header.
+/// Beacon nodes that don't send the header are assumed to be on Deneb.
+fn consensus_version(headers: &HeaderMap) -> Result<Fork, BuilderApiError> {
+    let Some(version) = headers.get(CONSENSUS_VERSION_HEADER) else { return Ok(Fork::Deneb) };
+
+    match version.to_str().map(str::to_lowercase).as_deref() {
+        Ok("deneb") => Ok(Fork::Deneb),
+        Ok("electra") => Ok(Fork::Electra),
+        _ => Err(BuilderApiError::InvalidFork(String::from_utf8_lossy(version.as_bytes()).into())),
+    }
+}
+
 #[derive(Error, Debug, Clone)]
 pub enum LocalPayloadIntegrityError {
     #[error(
@@ -292,10 +323,9 @@ macro_rules! assert_payload_fields_eq {
 /// when we submit the full payload.
 #[inline]
 fn check_locally_built_payload_integrity(
-    signed_blinded_block: &SignedBlindedBeaconBlock,
+    header_signed_by_cl: &ExecutionPayloadHeader,
     local_payload: &GetPayloadResponse,
 ) -> Result<(), LocalPayloadIntegrityError> {
-    let header_signed_by_cl = &signed_blinded_block.message.body.execution_payload_header;
     let local_execution_payload = local_payload.execution_payload();
 
     assert_payload_fields_eq!(
@@ -0,0 +1,224 @@
+use std::{fmt, str::FromStr};
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// The header carrying the [ErrorCode] of an error response, on every API of the sidecar
+/// and of bolt-boost.
+pub const ERROR_CODE_HEADER: &str = "x-bolt-error-code";
+
+/// Stable machine-readable codes of the errors returned by the bolt components.
+///
+/// The same condition gets the same code regardless of the component that rejected
+/// the request, e.g. [ErrorCode::SlotFull] from the commitments API of the sidecar and from
+/// bolt-boost. The codes are sent in the [ERROR_CODE_HEADER] of error responses, and
+/// JSON-RPC errors use the numeric code of the table in [ErrorCode::spec].
+///
+/// bolt-boost mirrors the entries it returns in its `codes` module, which must be kept in sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The request is not valid JSON or JSON-RPC.
+    InvalidRequest,
+    /// The JSON-RPC method does not exist.
+    MethodNotFound,
+    /// The parameters of the JSON-RPC method are missing, extra or malformed.
+    InvalidParams,
+    /// The request was rejected before validation.
+    Rejected,
+    /// The request was already received.
+    Duplicate,
+    /// The component failed to process a valid request.
+    Internal,
+    /// The request is not signed.
+    MissingSignature,
+    /// The signature of the request is invalid.
+    InvalidSignature,
+    /// The signature of the request can't be decoded or recovered.
+    MalformedSignature,
+    /// The request failed a validation without a more specific code.
+    ValidationFailed,
+    /// A header of the request is malformed.
+    MalformedHeader,
+    /// The requested API version is not supported.
+    UnsupportedApiVersion,
+    /// The blockspace reservation was rejected.
+    ReservationRejected,
+    /// The idempotency key was already used for another request.
+    IdempotencyKeyReused,
+    /// The target slot has no commitment, gas or blob capacity left.
+    SlotFull,
+    /// The fees of a transaction are too low.
+    FeeTooLow,
+    /// The nonce of a transaction conflicts with the account state or the mempool.
+    NonceConflict,
+    /// The sender can't pay for a transaction.
+    InsufficientBalance,
+    /// The deadline to commit to the target slot has passed.
+    DeadlinePassed,
+    /// No validator of the operator proposes the target slot.
+    NoProposer,
+    /// The request is signed for or targets another chain.
+    WrongChain,
+    /// The signer of the request is not allowed to submit it.
+    Unauthorized,
+    /// The request is too large.
+    PayloadTooLarge,
+    /// Too many requests were sent, retry later.
+    RateLimited,
+    /// The request must be paid for.
+    PaymentRequired,
+    /// An upstream service, e.g. a relay or the beacon node, is unavailable.
+    UpstreamUnavailable,
+    /// The request timed out.
+    Timeout,
+    /// A competing request won the auction of the requested blockspace.
+    Outbid,
+    /// A transaction of the request is refused by the policy of the operator.
+    PolicyViolation,
+}
+
+impl ErrorCode {
+    /// All the error codes.
+    pub const ALL: [Self; 29] = [
+        Self::InvalidRequest,
+        Self::MethodNotFound,
+        Self::InvalidParams,
+        Self::Rejected,
+        Self::Duplicate,
+        Self::Internal,
+        Self::MissingSignature,
+        Self::InvalidSignature,
+        Self::MalformedSignature,
+        Self::ValidationFailed,
+        Self::MalformedHeader,
+        Self::UnsupportedApiVersion,
+        Self::ReservationRejected,
+        Self::IdempotencyKeyReused,
+        Self::SlotFull,
+        Self::FeeTooLow,
+        Self::NonceConflict,
+        Self::InsufficientBalance,
+        Self::DeadlinePassed,
+        Self::NoProposer,
+        Self::WrongChain,
+        Self::Unauthorized,
+        Self::PayloadTooLarge,
+        Self::RateLimited,
+        Self::PaymentRequired,
+        Self::UpstreamUnavailable,
+        Self::Timeout,
+        Self::Outbid,
+        Self::PolicyViolation,
+    ];
+
+    /// The table of the error codes: their name, JSON-RPC code and HTTP status.
+    ///
+    /// The codes are stable: existing entries must never change, new ones get the next
+    /// free JSON-RPC code.
+    pub const fn spec(self) -> (&'static str, i32, u16) {
+        match self {
+            Self::InvalidRequest => ("INVALID_REQUEST", -32600, 400),
+            Self::MethodNotFound => ("METHOD_NOT_FOUND", -32601, 400),
+            Self::InvalidParams => ("INVALID_PARAMS", -32602, 400),
+            Self::Rejected => ("REJECTED", -32000, 400),
+            Self::Duplicate => ("DUPLICATE", -32001, 400),
+            Self::Internal => ("INTERNAL", -32002, 500),
+            Self::MissingSignature => ("MISSING_SIGNATURE", -32003, 400),
+            Self::InvalidSignature => ("INVALID_SIGNATURE", -32004, 400),
+            Self::MalformedSignature => ("MALFORMED_SIGNATURE", -32005, 400),
+            Self::ValidationFailed => ("VALIDATION_FAILED", -32006, 400),
+            Self::MalformedHeader => ("MALFORMED_HEADER", -32007, 400),
+            Self::UnsupportedApiVersion => ("UNSUPPORTED_API_VERSION", -32008, 406),
+            Self::ReservationRejected => ("RESERVATION_REJECTED", -32009, 400),
+            Self::IdempotencyKeyReused => ("IDEMPOTENCY_KEY_REUSED", -32010, 422),
+            Self::SlotFull => ("SLOT_FULL", -32011, 400),
+            Self::FeeTooLow => ("FEE_TOO_LOW", -32012, 400),
+            Self::NonceConflict => ("NONCE_CONFLICT", -32013, 400),
+            Self::InsufficientBalance => ("INSUFFICIENT_BALANCE", -32014, 400),
+            Self::DeadlinePassed => ("DEADLINE_PASSED", -32015, 400),
+            Self::NoProposer => ("NO_PROPOSER", -32016, 400),
+            Self::WrongChain => ("WRONG_CHAIN", -32017, 400),
+            Self::Unauthorized => ("UNAUTHORIZED", -32018, 401),
+            Self::PayloadTooLarge => ("PAYLOAD_TOO_LARGE", -32019, 413),
+            Self::RateLimited => ("RATE_LIMITED", -32020, 429),
+            Self::PaymentRequired => ("PAYMENT_REQUIRED", -32021, 402),
+            Self::UpstreamUnavailable => ("UPSTREAM_UNAVAILABLE", -32022, 503),
+            Self::Timeout => ("TIMEOUT", -32023, 504),
+            Self::Outbid => ("OUTBID", -32024, 409),
+            Self::PolicyViolation => ("POLICY_VIOLATION", -32025, 403),
+        }
+    }
+
+    /// Returns the name of the code, e.g. "SLOT_FULL".
+    pub const fn as_str(self) -> &'static str {
+        self.spec().0
+    }
+
+    /// Returns the JSON-RPC error code of the code.
+    pub const fn json_rpc_code(self) -> i32 {
+        self.spec().1
+    }
+
+    /// Returns the HTTP status of the responses with the code.
+    pub fn http_status(self) -> StatusCode {
+        StatusCode::from_u16(self.spec().2).expect("valid status code")
+    }
+
+    /// Returns the error code with the given JSON-RPC code, if any.
+    pub fn from_json_rpc_code(code: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.json_rpc_code() == code)
+    }
+
+    /// Returns true if the code rejects a request for its target slot only, so that the
+    /// request may be accepted for another slot.
+    pub const fn is_slot_related(self) -> bool {
+        matches!(self, Self::SlotFull | Self::DeadlinePassed | Self::NoProposer)
+    }
+
+    /// Returns true if the code rejects a request that may be accepted if sent again
+    /// unchanged later, e.g. once a rate limit resets or an upstream service recovers.
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimited | Self::UpstreamUnavailable | Self::Timeout)
+    }
+
+    /// Returns the header to attach to an error response with the code.
+    pub const fn header(self) -> [(&'static str, &'static str); 1] {
+        [(ERROR_CODE_HEADER, self.as_str())]
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|c| c.as_str() == s).ok_or(format!("unknown error code: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_unique() {
+        let names = ErrorCode::ALL.iter().map(|c| c.as_str()).collect::<HashSet<_>>();
+        let codes = ErrorCode::ALL.iter().map(|c| c.json_rpc_code()).collect::<HashSet<_>>();
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+
+        for code in ErrorCode::ALL {
+            assert_eq!(code.as_str().parse::<ErrorCode>(), Ok(code));
+            assert_eq!(ErrorCode::from_json_rpc_code(code.json_rpc_code()), Some(code));
+            assert_eq!(serde_json::to_string(&code).unwrap(), format!("\"{code}\""));
+        }
+    }
+}
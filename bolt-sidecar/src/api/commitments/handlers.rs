@@ -1,26 +1,47 @@
-use std::sync::Arc;
+use std::{convert::Infallible, sync::Arc};
 
+use alloy::primitives::{Address, TxHash};
 use axum::{
     body::Body,
-    extract::State,
-    http::{HeaderMap, Request},
-    response::Html,
+    extract::{Query, State},
+    http::{HeaderMap, Request, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
     Json,
 };
 use axum_extra::extract::WithRejection;
+use futures::{stream, Stream};
+use serde::Deserialize;
 use serde_json::Value;
-use tracing::{debug, error, info, instrument};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, instrument, warn};
 
 use crate::{
-    commitments::headers::auth_from_headers,
+    commitments::headers::{auth_from_headers, idempotency_key_from_headers},
     common::CARGO_PKG_VERSION,
-    primitives::{commitment::SignatureError, InclusionRequest},
+    primitives::{
+        commitment::{DigestVersion, SignatureError},
+        BatchInclusionRequest, DecryptionKeyReveal, EncryptedInclusionRequest, InclusionRequest,
+        InclusionRequestByHash, ReservationRequest,
+    },
+    service::Readiness,
+    state::commitment_status::{CommitmentStatus, CommitmentStatusQuery},
 };
 
 use super::{
     jsonrpc::{JsonPayload, JsonResponse},
+    params::{no_params, single_param, UploadParams},
     server::CommitmentsApiInner,
-    spec::{CommitmentsApi, Error, RejectionError, GET_VERSION_METHOD, REQUEST_INCLUSION_METHOD},
+    spec::{
+        CommitmentsApi, Error, GET_AVAILABILITY_ATTESTATIONS_METHOD, GET_AVAILABILITY_METHOD,
+        GET_COMMITMENT_STATUS_METHOD, GET_SIDECAR_IDENTITY_METHOD, GET_VERSION_METHOD,
+        LOOKUP_TRANSACTIONS_METHOD, REQUEST_BATCH_INCLUSION_METHOD,
+        REQUEST_ENCRYPTED_INCLUSION_METHOD, REQUEST_INCLUSION_BY_HASH_METHOD,
+        REQUEST_INCLUSION_METHOD, RESERVE_BLOCKSPACE_METHOD, REVEAL_DECRYPTION_KEY_METHOD,
+        SIMULATE_INCLUSION_METHOD, UPLOAD_TRANSACTIONS_METHOD,
+    },
 };
 
 /// Handler function for the root JSON-RPC path.
@@ -38,6 +59,7 @@ pub async fn rpc_entrypoint(
 
     match payload.method.as_str() {
         GET_VERSION_METHOD => {
+            no_params(&payload.params)?;
             let version_string = format!("bolt-sidecar-v{CARGO_PKG_VERSION}");
             Ok(Json(JsonResponse {
                 id: payload.id,
@@ -46,14 +68,48 @@ pub async fn rpc_entrypoint(
             }))
         }
 
-        REQUEST_INCLUSION_METHOD => {
-            let Some(request_json) = payload.params.first().cloned() else {
-                return Err(RejectionError::ValidationFailed("Bad params".to_string()).into());
-            };
+        GET_SIDECAR_IDENTITY_METHOD => {
+            no_params(&payload.params)?;
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(api.identity()).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        GET_AVAILABILITY_METHOD => {
+            no_params(&payload.params)?;
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(api.availability()).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        GET_AVAILABILITY_ATTESTATIONS_METHOD => {
+            no_params(&payload.params)?;
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(api.availability_attestations())
+                    .map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        GET_COMMITMENT_STATUS_METHOD => {
+            let query: CommitmentStatusQuery = single_param(&payload.params)?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(api.commitment_status(query.slot, query.tx_hash))
+                    .map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
 
+        REQUEST_INCLUSION_METHOD => {
             // Parse the inclusion request from the parameters
-            let mut inclusion_request: InclusionRequest = serde_json::from_value(request_json)
-                .map_err(|e| RejectionError::ValidationFailed(e.to_string()))
+            let mut inclusion_request: InclusionRequest = single_param(&payload.params)
                 .inspect_err(|e| error!("Failed to parse inclusion request: {:?}", e))?;
 
             debug!(?inclusion_request, "New inclusion request");
@@ -61,24 +117,19 @@ pub async fn rpc_entrypoint(
             // Set the signature here for later processing
             inclusion_request.set_signature(signature);
 
-            let digest = inclusion_request.digest();
-            let recovered_signer = signature.recover_address_from_prehash(&digest)?;
-
-            if recovered_signer != signer {
-                error!(
-                    ?recovered_signer,
-                    ?signer,
-                    "Recovered signer does not match the provided signer"
-                );
-
-                return Err(Error::InvalidSignature(SignatureError));
-            }
+            let verified = inclusion_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
 
             // Set the request signer
-            inclusion_request.set_signer(recovered_signer);
+            inclusion_request.set_signer(signer);
 
-            info!(signer = ?recovered_signer, %digest, "New valid inclusion request received");
-            let inclusion_commitment = api.request_inclusion(inclusion_request).await?;
+            info!(?signer, ?version, "New valid inclusion request received");
+            let inclusion_commitment = match idempotency_key_from_headers(&headers)? {
+                Some(key) => {
+                    api.request_inclusion_idempotent(inclusion_request, signer, key).await?
+                }
+                None => api.request_inclusion(inclusion_request).await?,
+            };
 
             // Create the JSON-RPC response
             let response = JsonResponse {
@@ -89,6 +140,174 @@ pub async fn rpc_entrypoint(
 
             Ok(Json(response))
         }
+
+        REQUEST_BATCH_INCLUSION_METHOD => {
+            let batch_request: BatchInclusionRequest = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse batch inclusion request: {:?}", e))?;
+
+            // The batch is committed to as an inclusion request over the slots of its delay
+            let mut inclusion_request = InclusionRequest::from(batch_request);
+            inclusion_request.set_signature(signature);
+            let verified = inclusion_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
+            inclusion_request.set_signer(signer);
+
+            info!(
+                ?signer,
+                ?version,
+                max_slot = ?inclusion_request.max_slot,
+                "New valid batch request received"
+            );
+            let inclusion_commitment = match idempotency_key_from_headers(&headers)? {
+                Some(key) => {
+                    api.request_inclusion_idempotent(inclusion_request, signer, key).await?
+                }
+                None => api.request_inclusion(inclusion_request).await?,
+            };
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(inclusion_commitment).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        UPLOAD_TRANSACTIONS_METHOD => {
+            // Transactions are signed, so they can be uploaded without a request signature
+            let UploadParams(txs) = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse uploaded transactions: {:?}", e))?;
+
+            debug!(?signer, count = txs.len(), "New transactions uploaded");
+            let uploaded = api.uploads().upload(txs)?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(uploaded).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        LOOKUP_TRANSACTIONS_METHOD => {
+            let hashes: Vec<TxHash> = single_param(&payload.params)?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(api.uploads().lookup(&hashes))
+                    .map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        REQUEST_INCLUSION_BY_HASH_METHOD => {
+            let by_hash: InclusionRequestByHash = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse inclusion request: {:?}", e))?;
+
+            // The digest only covers the transaction hashes, so the signature of the request
+            // is verified once its transactions are resolved
+            let txs = api.uploads().resolve(&by_hash.tx_hashes)?;
+            let mut inclusion_request = by_hash.into_request(txs);
+            inclusion_request.set_signature(signature);
+
+            let verified = inclusion_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
+            inclusion_request.set_signer(signer);
+
+            info!(?signer, ?version, "New valid inclusion request by hash received");
+            let inclusion_commitment = match idempotency_key_from_headers(&headers)? {
+                Some(key) => {
+                    api.request_inclusion_idempotent(inclusion_request, signer, key).await?
+                }
+                None => api.request_inclusion(inclusion_request).await?,
+            };
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(inclusion_commitment).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        RESERVE_BLOCKSPACE_METHOD => {
+            let mut reservation_request: ReservationRequest = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse reservation request: {:?}", e))?;
+
+            let verified = reservation_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
+
+            // The signer of the request is the subscriber of the reservation
+            reservation_request.signer = Some(signer);
+
+            info!(
+                ?signer,
+                ?version,
+                ?reservation_request,
+                "New valid reservation request received"
+            );
+            let reservation = api.reserve_blockspace(reservation_request).await?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(reservation).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+        REQUEST_ENCRYPTED_INCLUSION_METHOD => {
+            let mut encrypted_request: EncryptedInclusionRequest = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse encrypted inclusion request: {:?}", e))?;
+
+            let verified = encrypted_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
+            encrypted_request.signer = Some(signer);
+
+            info!(
+                ?signer,
+                ?version,
+                slot = encrypted_request.slot,
+                gas_limit = encrypted_request.gas_limit,
+                "New valid encrypted inclusion request received"
+            );
+            let commitment = api.request_encrypted_inclusion(encrypted_request).await?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(commitment).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
+
+        REVEAL_DECRYPTION_KEY_METHOD => {
+            // Keys are checked against the ciphertext, so anyone holding one can reveal it
+            let reveal: DecryptionKeyReveal = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse decryption key: {:?}", e))?;
+
+            debug!(?signer, ciphertext_hash = %reveal.ciphertext_hash, "Decryption key revealed");
+            let slot = api.reveal_decryption_key(reveal).await?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::json!({ "slot": slot }),
+                ..Default::default()
+            }))
+        }
+
+        SIMULATE_INCLUSION_METHOD => {
+            let mut inclusion_request: InclusionRequest = single_param(&payload.params)
+                .inspect_err(|e| error!("Failed to parse inclusion request: {:?}", e))?;
+
+            inclusion_request.set_signature(signature);
+            let verified = inclusion_request.verify_signature(&signature, signer, api.chain_id());
+            let version = authorize(&api, signer, verified)?;
+            inclusion_request.set_signer(signer);
+
+            debug!(?signer, ?version, "New inclusion request to simulate");
+            let simulation = api.simulate_inclusion(inclusion_request).await?;
+
+            Ok(Json(JsonResponse {
+                id: payload.id,
+                result: serde_json::to_value(simulation).map_err(|_| Error::Internal)?,
+                ..Default::default()
+            }))
+        }
         other => {
             error!("Unknown method: {}", other);
             Err(Error::UnknownMethod)
@@ -96,6 +315,32 @@ pub async fn rpc_entrypoint(
     }
 }
 
+/// Authorizes a request given the outcome of the verification of its signature by the signer
+/// of its auth header: the signer must be whitelisted and within its rate limit, and requests
+/// signed over the legacy digest are logged as deprecated.
+///
+/// The rate limit of the signer is only charged once the signature is verified, so that
+/// requests forging the header of another signer can't exhaust its limit.
+fn authorize(
+    api: &CommitmentsApiInner,
+    signer: Address,
+    verified: Result<DigestVersion, SignatureError>,
+) -> Result<DigestVersion, Error> {
+    let version = verified
+        .inspect_err(|_| error!(?signer, "Signature does not match the provided signer"))
+        .map_err(Error::InvalidSignature)?;
+    api.whitelist().check(signer).inspect_err(|_| warn!(?signer, "Signer is not whitelisted"))?;
+    api.rate_limits().check_signer(signer).inspect_err(|_| {
+        warn!(?signer, "Rate limit of the signer exceeded");
+    })?;
+
+    if version == DigestVersion::Legacy {
+        warn!(?signer, "Request signed over the deprecated legacy digest");
+    }
+
+    Ok(version)
+}
+
 /// Not found fallback handler for all non-matched routes.
 ///
 /// This handler returns a simple 404 page.
@@ -110,3 +355,81 @@ pub async fn not_found(req: Request<Body>) -> Html<&'static str> {
 pub async fn status() -> Html<&'static str> {
     Html("OK")
 }
+
+/// Filter of the commitment events to stream.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct CommitmentEventsFilter {
+    /// Only stream the events of the commitments to this transaction, if set.
+    pub tx_hash: Option<TxHash>,
+}
+
+impl CommitmentEventsFilter {
+    fn matches(&self, status: &CommitmentStatus) -> bool {
+        self.tx_hash.map_or(true, |hash| hash == status.tx_hash)
+    }
+}
+
+/// Handler streaming the changes of status of the commitments as server-sent events, from
+/// the time of the subscription on.
+#[instrument(skip_all, name = "GET /commitments/events")]
+pub async fn commitment_events(
+    State(api): State<Arc<CommitmentsApiInner>>,
+    Query(filter): Query<CommitmentEventsFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let updates = api.subscribe_commitment_statuses();
+
+    let events = stream::unfold(updates, move |mut updates| async move {
+        loop {
+            match updates.recv().await {
+                Ok(status) if filter.matches(&status) => {
+                    let event = Event::default()
+                        .event("status")
+                        .json_data(&status)
+                        .expect("serializable commitment status");
+                    return Some((Ok(event), updates));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(missed, "Commitment events subscriber lagged behind");
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Query of the commitment keys, resolving the key that signed the commitments of a slot.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct CommitmentKeysQuery {
+    /// Only return the key valid at this slot, if set.
+    pub slot: Option<u64>,
+}
+
+/// Handler returning the current and previous commitment keys of the sidecar, with their
+/// validity windows. With a slot, only the key that signed the commitments of the slot is
+/// returned, or a 404 if unknown.
+#[instrument(skip_all, name = "GET /commitment-keys")]
+pub async fn commitment_keys(
+    State(api): State<Arc<CommitmentsApiInner>>,
+    Query(query): Query<CommitmentKeysQuery>,
+) -> Response {
+    let keys = api.commitment_keys();
+    match query.slot {
+        None => Json(keys).into_response(),
+        Some(slot) => match keys.key_at(slot) {
+            Some(key) => Json(key).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+    }
+}
+
+/// Handler for the readiness probe of orchestrators, which succeeds once the sidecar can serve
+/// commitments for the upcoming slots.
+pub async fn ready(State(api): State<Arc<CommitmentsApiInner>>) -> (StatusCode, Json<Readiness>) {
+    let readiness = api.readiness();
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(readiness))
+}
@@ -5,7 +5,10 @@ use axum::http::HeaderMap;
 
 use crate::primitives::commitment::SignatureError;
 
-use super::spec::{Error, SIGNATURE_HEADER};
+use super::{
+    idempotency::MAX_IDEMPOTENCY_KEY_LEN,
+    spec::{Error, IDEMPOTENCY_KEY_HEADER, SIGNATURE_HEADER},
+};
 
 /// Extracts the signature ([SIGNATURE_HEADER]) from the HTTP headers.
 #[inline]
@@ -26,6 +29,19 @@ pub fn auth_from_headers(headers: &HeaderMap) -> Result<(Address, Signature), Er
     Ok((address, sig))
 }
 
+/// Extracts the optional idempotency key ([IDEMPOTENCY_KEY_HEADER]) from the HTTP headers.
+#[inline]
+pub fn idempotency_key_from_headers(headers: &HeaderMap) -> Result<Option<String>, Error> {
+    let Some(key) = headers.get(IDEMPOTENCY_KEY_HEADER) else { return Ok(None) };
+
+    let key = key.to_str().map_err(|_| Error::MalformedHeader)?;
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(Error::MalformedHeader);
+    }
+
+    Ok(Some(key.to_string()))
+}
+
 #[cfg(test)]
 mod test {
     use alloy::{
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::{Address, B256};
+use parking_lot::Mutex;
+use tokio::sync::OnceCell;
+
+use crate::primitives::commitment::InclusionCommitment;
+
+use super::spec::Error;
+
+/// The time an idempotency key is remembered for after its first use.
+pub(super) const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The maximum length of an idempotency key.
+pub(super) const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
+
+/// An idempotency key, scoped to the signer of the request.
+type Key = (Address, String);
+
+#[derive(Debug)]
+struct Entry {
+    /// The digest of the request the key was first used for.
+    digest: B256,
+    /// The instant after which the key is forgotten.
+    expires_at: Instant,
+    /// The commitment issued for the request, once it succeeds.
+    commitment: Arc<OnceCell<InclusionCommitment>>,
+}
+
+/// A TTL cache of the inclusion commitments issued per (signer, idempotency key).
+///
+/// Retries of a request with the same key get the original commitment back instead
+/// of being processed again, including retries that arrive while the original request
+/// is still being processed. Failed requests are not cached, so they can be retried.
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Key, Entry>>,
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(IDEMPOTENCY_KEY_TTL)
+    }
+}
+
+impl IdempotencyCache {
+    /// Create a new cache remembering keys for the given duration.
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: Default::default() }
+    }
+
+    /// Returns the commitment issued for the key, or issues it with `request`.
+    ///
+    /// Returns [Error::IdempotencyKeyReused] if the key was already used by the signer
+    /// for a request with a different digest.
+    pub async fn get_or_request<F, Fut>(
+        &self,
+        signer: Address,
+        key: String,
+        digest: B256,
+        request: F,
+    ) -> Result<InclusionCommitment, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<InclusionCommitment, Error>>,
+    {
+        let commitment = {
+            let mut entries = self.entries.lock();
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+
+            let entry = entries.entry((signer, key)).or_insert_with(|| Entry {
+                digest,
+                expires_at: now + self.ttl,
+                commitment: Default::default(),
+            });
+
+            if entry.digest != digest {
+                return Err(Error::IdempotencyKeyReused);
+            }
+
+            Arc::clone(&entry.commitment)
+        };
+
+        commitment.get_or_try_init(request).await.cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use alloy::signers::{local::PrivateKeySigner, Signer};
+
+    use crate::primitives::InclusionRequest;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idempotency_keys() {
+        let cache = IdempotencyCache::default();
+        let (signer, other) = (Address::random(), Address::random());
+        let (digest, key) = (B256::random(), "retry-1".to_string());
+
+        let request = InclusionRequest {
+            slot: 10,
+            max_slot: None,
+            top: false,
+            partial: false,
+            batch: false,
+            txs: Vec::new(),
+            signature: None,
+            signer: Some(signer),
+            voucher: None,
+        };
+        let signature = PrivateKeySigner::random().sign_hash(&digest).await.unwrap();
+        let commitment = InclusionCommitment { request, signature, outcomes: Vec::new() };
+
+        let calls = AtomicUsize::new(0);
+        let (counter, issued) = (&calls, &commitment);
+        let issue = move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, Error>(issued.clone())
+        };
+
+        // Failed requests are not cached
+        let res =
+            cache.get_or_request(signer, key.clone(), digest, || async { Err(Error::Internal) });
+        assert!(matches!(res.await, Err(Error::Internal)));
+
+        let first = cache.get_or_request(signer, key.clone(), digest, issue).await.unwrap();
+        let retry = cache.get_or_request(signer, key.clone(), digest, issue).await.unwrap();
+        assert_eq!(first, retry);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Keys are scoped to the signer and bound to the request they were first used for
+        let res = cache.get_or_request(signer, key.clone(), B256::random(), issue).await;
+        assert!(matches!(res, Err(Error::IdempotencyKeyReused)));
+        cache.get_or_request(other, key.clone(), B256::random(), issue).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Expired keys are forgotten
+        let cache = IdempotencyCache::new(Duration::ZERO);
+        cache.get_or_request(signer, key.clone(), digest, issue).await.unwrap();
+        cache.get_or_request(signer, key, digest, issue).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::spec::API_VERSION;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonPayload {
     /// The JSON-RPC version string. MUST be "2.0".
@@ -9,7 +11,8 @@ pub struct JsonPayload {
     pub method: String,
     /// Optional ID.
     pub id: Option<Value>,
-    /// The parameters object.
+    /// The positional parameters, which may be omitted by methods without any.
+    #[serde(default)]
     pub params: Vec<Value>,
 }
 
@@ -22,27 +25,35 @@ pub struct JsonResponse {
     pub result: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<JsonError>,
+    /// The version of the commitments API that produced the response.
+    #[serde(rename = "apiVersion", default)]
+    pub api_version: String,
 }
 
 impl Default for JsonResponse {
     fn default() -> Self {
-        Self { jsonrpc: "2.0".to_string(), id: None, result: Value::Null, error: None }
-    }
-}
-
-impl JsonResponse {
-    pub fn from_error(code: i32, message: String) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             id: None,
             result: Value::Null,
-            error: Some(JsonError { code, message }),
+            error: None,
+            api_version: API_VERSION.to_string(),
         }
     }
 }
 
+impl JsonResponse {
+    pub fn from_error(code: i32, message: String) -> Self {
+        Self { error: Some(JsonError { code, message, data: None }), ..Default::default() }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonError {
     pub code: i32,
     pub message: String,
+    /// Additional information about the error, e.g. the slots suggested to retarget a
+    /// request rejected for its target slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
 }
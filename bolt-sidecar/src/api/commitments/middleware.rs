@@ -1,18 +1,66 @@
 use crate::telemetry::ApiMetrics;
-use axum::{extract::Request, middleware::Next, response::IntoResponse};
-use std::time::Instant;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{net::SocketAddr, sync::Arc, time::Instant};
+use tracing::{info_span, Instrument};
+
+use super::{
+    server::CommitmentsApiInner,
+    spec::{Error, API_VERSION, API_VERSION_HEADER},
+};
 
 /// Middleware to track server metrics for each request.
+///
+/// Each request runs in its own span, whose trace is attached to its latency as exemplar.
 pub async fn track_server_metrics(req: Request, next: Next) -> impl IntoResponse {
     let path = req.uri().path().to_owned();
     let method = req.method().to_string();
+    let span = info_span!("http_request", %method, %path);
 
     let start = Instant::now();
-    let response = next.run(req).await;
+    let response = next.run(req).instrument(span.clone()).await;
     let latency = start.elapsed();
     let status = response.status().as_u16().to_string();
 
-    ApiMetrics::observe_http_request(latency, method, path, status);
+    span.in_scope(|| ApiMetrics::observe_http_request(latency, method, path, status));
+
+    response
+}
+
+/// Middleware to negotiate the version of the commitments API.
+///
+/// Requests can ask for a version with the [API_VERSION_HEADER] header, and are rejected
+/// if it is not supported. Responses always carry the version that produced them.
+pub async fn negotiate_api_version(req: Request, next: Next) -> Response {
+    if let Some(requested) = req.headers().get(API_VERSION_HEADER) {
+        let requested = String::from_utf8_lossy(requested.as_bytes()).trim().to_string();
+        if !requested.eq_ignore_ascii_case(API_VERSION) {
+            return Error::UnsupportedApiVersion(requested).into_response();
+        }
+    }
 
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(API_VERSION_HEADER, HeaderValue::from_static(API_VERSION));
     response
 }
+
+/// Middleware to limit the rate of the requests of each IP address.
+///
+/// Requests are only limited if the server is served with the connection info of its clients.
+pub async fn limit_ip_rate(
+    State(api): State<Arc<CommitmentsApiInner>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        if let Err(err) = api.rate_limits().check_ip(addr.ip()) {
+            return err.into_response();
+        }
+    }
+
+    next.run(req).await
+}
@@ -2,11 +2,25 @@
 mod handlers;
 /// The commitments-API headers and constants.
 mod headers;
+/// The cache of inclusion commitments by idempotency key.
+mod idempotency;
 /// JSON-RPC helper types and functions.
-mod jsonrpc;
+pub(crate) mod jsonrpc;
 /// The commitments-API middleware.
 mod middleware;
+/// Serving the commitments API of several networks from one process.
+pub mod networks;
+/// The typed extraction and bounds of the JSON-RPC method params.
+pub mod params;
+/// The rate limits of the senders of requests to the commitments API.
+pub mod rate_limit;
 /// The commitments-API JSON-RPC server implementation.
 pub mod server;
+/// Signing of the commitments-API responses with the commitment key.
+pub mod signing;
 /// The commitments-API specification and errors.
 pub mod spec;
+/// The cache of the raw transactions uploaded to be referenced by hash.
+pub mod uploads;
+/// The whitelist of the signers allowed to request commitments.
+pub mod whitelist;
@@ -0,0 +1,184 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::Router;
+use tokio::net::TcpListener;
+use tracing::info;
+
+use super::handlers;
+
+/// The commitments-API routers of the networks served by a single sidecar process.
+///
+/// Each network is mounted under its identifier, e.g. `/holesky/rpc/v1`, and the primary
+/// network is also served at the root for clients of single-network sidecars. The
+/// [CommitmentsApiServer](super::server::CommitmentsApiServer) of each network registers
+/// its router through its own handle instead of binding a port.
+#[derive(Clone)]
+pub struct NetworkRouter {
+    /// The network that this handle registers routers for.
+    network: String,
+    /// The network also served at the root path.
+    primary: String,
+    /// The routers of the registered networks, by identifier.
+    routers: Arc<Mutex<BTreeMap<String, Router>>>,
+}
+
+impl fmt::Debug for NetworkRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetworkRouter")
+            .field("network", &self.network)
+            .field("primary", &self.primary)
+            .field("networks", &self.networks())
+            .finish()
+    }
+}
+
+impl NetworkRouter {
+    /// Creates an empty router, returning the handle of the primary network.
+    pub fn new(primary: impl Into<String>) -> Self {
+        let primary = primary.into();
+        Self { network: primary.clone(), primary, routers: Arc::default() }
+    }
+
+    /// Returns a handle registering the router of the given network.
+    pub fn for_network(&self, network: impl Into<String>) -> Self {
+        Self {
+            network: network.into(),
+            primary: self.primary.clone(),
+            routers: self.routers.clone(),
+        }
+    }
+
+    /// Returns the network that this handle registers routers for.
+    pub fn network(&self) -> &str {
+        &self.network
+    }
+
+    /// Returns the identifiers of the registered networks.
+    pub fn networks(&self) -> Vec<String> {
+        self.routers.lock().expect("network routers lock").keys().cloned().collect()
+    }
+
+    /// Registers the router of the network of this handle, replacing any previous one.
+    pub(super) fn register(&self, router: Router) {
+        self.routers.lock().expect("network routers lock").insert(self.network.clone(), router);
+    }
+
+    /// Builds the router serving all the networks registered so far.
+    pub fn build(&self) -> Router {
+        let routers = self.routers.lock().expect("network routers lock").clone();
+
+        let mut app = routers
+            .get(&self.primary)
+            .cloned()
+            .unwrap_or_else(|| Router::new().fallback(handlers::not_found));
+        for (network, router) in routers {
+            app = app.nest(&format!("/{network}"), router);
+        }
+
+        app
+    }
+
+    /// Serves the networks registered so far on the given address until CTRL+C. Must be
+    /// called once the sidecars of all the networks are built.
+    pub async fn serve(self, addr: SocketAddr) -> eyre::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!(addr = %listener.local_addr()?, networks = ?self.networks(), "Multi-network commitments API listening");
+
+        axum::serve(listener, self.build().into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner};
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    use crate::{
+        commitments::{server::CommitmentsApiServer, spec::SIGNATURE_HEADER},
+        primitives::commitment::ECDSASignatureExt,
+        test_util::{create_signed_commitment_request, default_test_transaction},
+        ChainConfig,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_network_router() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let router = NetworkRouter::new("holesky");
+        let mut events = BTreeMap::new();
+        for network in ["holesky", "devnet"] {
+            let (events_tx, events_rx) = mpsc::channel(1);
+            events.insert(network, events_rx);
+            CommitmentsApiServer::new("0.0.0.0:0")
+                .with_chain_id(ChainConfig::default().chain_id())
+                .with_network_router(router.for_network(network))
+                .run(events_tx)
+                .await;
+        }
+        assert_eq!(router.networks(), ["devnet", "holesky"]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router.build();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        for (path, body) in [
+            ("/status", "OK"),
+            ("/holesky/status", "OK"),
+            ("/devnet/status", "OK"),
+            ("/mainnet/status", "404 - Not Found"),
+            ("/unknown", "404 - Not Found"),
+            ("/devnet/unknown", "404 - Not Found"),
+            ("/devnet/holesky/status", "404 - Not Found"),
+        ] {
+            let response = client.get(format!("http://{addr}{path}")).send().await.unwrap();
+            assert_eq!(response.text().await.unwrap(), body, "{path}");
+        }
+
+        // Requests are only forwarded to the sidecar of the network of their path
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let tx = default_test_transaction(signer.address(), None);
+        let req = create_signed_commitment_request(&[tx], &sk, 12).await.unwrap();
+        let sig = req.signature().unwrap().to_hex();
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": [req]
+        });
+
+        for (path, network) in [("/devnet", "devnet"), ("/", "holesky")] {
+            let request = client
+                .post(format!("http://{addr}{path}"))
+                .header(SIGNATURE_HEADER, format!("{}:{}", signer.address(), sig))
+                .json(&payload)
+                .send();
+            // The request stays pending until the event is answered, which it never is here
+            let pending = tokio::spawn(request);
+
+            let event = events.get_mut(network).unwrap().recv().await;
+            assert!(event.is_some(), "{path}");
+            for (other, events) in events.iter_mut().filter(|(other, _)| **other != network) {
+                assert!(events.try_recv().is_err(), "{path} reached {other}");
+            }
+            drop(event);
+            let _ = pending.await;
+        }
+    }
+}
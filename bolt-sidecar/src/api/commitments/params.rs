@@ -0,0 +1,181 @@
+use std::fmt::Write;
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+use crate::primitives::{transaction::deserialize_txs, FullTransaction};
+
+/// The max length of a string in the params, which fits a hex-encoded blob transaction with
+/// the max 6 blobs of a block, their commitments and proofs and 128KiB of other payload.
+pub const MAX_PARAM_STRING_LEN: usize = 2 + 2 * (6 * (131_072 + 48 + 48) + 131_072);
+
+/// The max nesting depth of the arrays and objects in the params.
+pub const MAX_PARAM_DEPTH: usize = 8;
+
+/// An error in the positional params of a JSON-RPC method, returned with the `-32602`
+/// JSON-RPC code.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParamsError {
+    /// The method takes another number of params.
+    #[error("Expected {expected} params, got {got}")]
+    Arity {
+        /// The number of params of the method.
+        expected: usize,
+        /// The number of params of the request.
+        got: usize,
+    },
+    /// A string of the params is too long.
+    #[error("String at {path} has {len} characters, max is {MAX_PARAM_STRING_LEN}")]
+    StringTooLong {
+        /// The path of the string in the params, e.g. `params[0].txs[1]`.
+        path: String,
+        /// The length of the string.
+        len: usize,
+    },
+    /// The arrays and objects of the params are nested too deep.
+    #[error("Value at {path} is nested deeper than {MAX_PARAM_DEPTH} levels")]
+    TooDeep {
+        /// The path of the first value past the max depth.
+        path: String,
+    },
+    /// A param doesn't match the type expected by the method.
+    #[error("Invalid {path}: {reason}")]
+    Invalid {
+        /// The path of the invalid param.
+        path: String,
+        /// The reason the param is invalid.
+        reason: String,
+    },
+}
+
+impl ParamsError {
+    /// Returns the path of the offending value in the params, if any.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Arity { .. } => None,
+            Self::StringTooLong { path, .. } |
+            Self::TooDeep { path } |
+            Self::Invalid { path, .. } => Some(path),
+        }
+    }
+}
+
+/// The params of the `bolt_uploadTransactions` method: a list of hex-encoded raw transactions.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct UploadParams(#[serde(deserialize_with = "deserialize_txs")] pub Vec<FullTransaction>);
+
+/// Checks that a method without params was called without any.
+pub fn no_params(params: &[Value]) -> Result<(), ParamsError> {
+    check_arity(params, 0)
+}
+
+/// Parses the single param of a method, rejecting requests with extra params and params
+/// with too long strings or too deep nesting before they are deserialized.
+pub fn single_param<T: DeserializeOwned>(params: &[Value]) -> Result<T, ParamsError> {
+    check_arity(params, 1)?;
+
+    let mut path = String::from("params[0]");
+    check_bounds(&params[0], &mut path, 0)?;
+
+    T::deserialize(&params[0])
+        .map_err(|e| ParamsError::Invalid { path: "params[0]".to_string(), reason: e.to_string() })
+}
+
+fn check_arity(params: &[Value], expected: usize) -> Result<(), ParamsError> {
+    if params.len() != expected {
+        return Err(ParamsError::Arity { expected, got: params.len() });
+    }
+
+    Ok(())
+}
+
+/// Walks the value, checking the length of its strings and the depth of its nesting.
+/// The path of the value is extended in place, so that errors point to the offending value.
+fn check_bounds(value: &Value, path: &mut String, depth: usize) -> Result<(), ParamsError> {
+    match value {
+        Value::String(s) if s.len() > MAX_PARAM_STRING_LEN => {
+            Err(ParamsError::StringTooLong { path: path.clone(), len: s.len() })
+        }
+        Value::Array(_) | Value::Object(_) if depth >= MAX_PARAM_DEPTH => {
+            Err(ParamsError::TooDeep { path: path.clone() })
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                let _ = write!(path, "[{i}]");
+                check_bounds(item, path, depth + 1)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if key.len() > MAX_PARAM_STRING_LEN {
+                    return Err(ParamsError::StringTooLong { path: path.clone(), len: key.len() });
+                }
+
+                let len = path.len();
+                let _ = write!(path, ".{key}");
+                check_bounds(field, path, depth + 1)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::state::commitment_status::CommitmentStatusQuery;
+
+    use super::*;
+
+    #[test]
+    fn test_params_arity() {
+        assert_eq!(no_params(&[]), Ok(()));
+        assert_eq!(no_params(&[json!(1)]), Err(ParamsError::Arity { expected: 0, got: 1 }));
+
+        let err = single_param::<Vec<u64>>(&[]).unwrap_err();
+        assert_eq!(err, ParamsError::Arity { expected: 1, got: 0 });
+
+        let err = single_param::<Vec<u64>>(&[json!([1]), json!([2])]).unwrap_err();
+        assert_eq!(err, ParamsError::Arity { expected: 1, got: 2 });
+
+        assert_eq!(single_param::<Vec<u64>>(&[json!([1, 2])]), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_params_bounds() {
+        let long = "f".repeat(MAX_PARAM_STRING_LEN + 1);
+        let err = single_param::<Value>(&[json!({ "txs": ["0x00", long] })]).unwrap_err();
+        assert_eq!(
+            err,
+            ParamsError::StringTooLong {
+                path: "params[0].txs[1]".to_string(),
+                len: MAX_PARAM_STRING_LEN + 1
+            }
+        );
+
+        let mut deep = json!(1);
+        for _ in 0..=MAX_PARAM_DEPTH {
+            deep = json!([deep]);
+        }
+        let err = single_param::<Value>(&[deep]).unwrap_err();
+        assert!(matches!(err, ParamsError::TooDeep { .. }));
+    }
+
+    #[test]
+    fn test_params_invalid() {
+        let err = single_param::<CommitmentStatusQuery>(&[json!({ "slot": "latest" })]);
+        let Err(ParamsError::Invalid { path, reason }) = err else { panic!("expected an error") };
+        assert_eq!(path, "params[0]");
+        assert!(reason.contains("invalid type"));
+
+        let err = single_param::<UploadParams>(&[json!(["0xzz"])]);
+        assert!(matches!(err, Err(ParamsError::Invalid { .. })));
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    num::NonZero,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::Address;
+use parking_lot::Mutex;
+
+use crate::config::RateLimitOpts;
+
+use super::spec::{Error, RateLimitScope};
+
+/// The number of senders whose buckets are tracked at once before the full ones are dropped.
+const MAX_TRACKED_SENDERS: usize = 65_536;
+
+/// A token bucket, holding the number of requests a sender can make right away.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Token bucket rate limits of the requests of each sender, keyed by IP address or signer.
+#[derive(Debug)]
+pub struct RateLimiter<K> {
+    /// The number of tokens added to each bucket per second.
+    rate: f64,
+    /// The capacity of each bucket.
+    burst: f64,
+    buckets: Mutex<HashMap<K, TokenBucket>>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    /// Creates a limiter of `rate` requests per second, with bursts of up to `burst` requests.
+    pub fn new(rate: NonZero<u32>, burst: NonZero<u32>) -> Self {
+        Self { rate: rate.get() as f64, burst: burst.get() as f64, buckets: Default::default() }
+    }
+
+    /// Takes a token from the bucket of the sender, or returns the time after which one is
+    /// available again.
+    pub fn check(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock();
+        if buckets.len() >= MAX_TRACKED_SENDERS && !buckets.contains_key(&key) {
+            buckets.retain(|_, bucket| self.refill(bucket, now) < self.burst);
+        }
+
+        let bucket =
+            buckets.entry(key).or_insert(TokenBucket { tokens: self.burst, updated_at: now });
+        let tokens = self.refill(bucket, now);
+        if tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - tokens) / self.rate));
+        }
+
+        *bucket = TokenBucket { tokens: tokens - 1.0, updated_at: now };
+        Ok(())
+    }
+
+    /// Returns the tokens of the bucket at the given time.
+    fn refill(&self, bucket: &TokenBucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.tokens + elapsed * self.rate).min(self.burst)
+    }
+}
+
+/// The rate limits of the commitments API, per IP address and per signer.
+#[derive(Debug, Default)]
+pub struct RateLimits {
+    per_ip: Option<RateLimiter<IpAddr>>,
+    per_signer: Option<RateLimiter<Address>>,
+}
+
+impl RateLimits {
+    /// Creates the rate limits enabled by the given options.
+    pub fn new(opts: &RateLimitOpts) -> Self {
+        let burst = opts.rate_limit_burst;
+        Self {
+            per_ip: opts.rate_limit_per_ip.map(|rate| RateLimiter::new(rate, burst)),
+            per_signer: opts.rate_limit_per_signer.map(|rate| RateLimiter::new(rate, burst)),
+        }
+    }
+
+    /// Checks the rate limit of the IP address the request was sent from.
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), Error> {
+        let Some(limiter) = &self.per_ip else { return Ok(()) };
+        limiter
+            .check(ip, Instant::now())
+            .map_err(|retry_after| Error::RateLimited { scope: RateLimitScope::Ip, retry_after })
+    }
+
+    /// Checks the rate limit of the signer of the request.
+    pub fn check_signer(&self, signer: Address) -> Result<(), Error> {
+        let Some(limiter) = &self.per_signer else { return Ok(()) };
+        limiter.check(signer, Instant::now()).map_err(|retry_after| Error::RateLimited {
+            scope: RateLimitScope::Signer,
+            retry_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(NonZero::new(2).unwrap(), NonZero::new(3).unwrap());
+        let (sender, other) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let now = Instant::now();
+
+        // Bursts are accepted up to the capacity of the bucket
+        for _ in 0..3 {
+            assert!(limiter.check(sender, now).is_ok());
+        }
+        assert_eq!(limiter.check(sender, now), Err(Duration::from_millis(500)));
+
+        // Senders have their own buckets
+        assert!(limiter.check(other, now).is_ok());
+
+        // Tokens are refilled at the rate of the limiter
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.check(sender, later).is_ok());
+        assert!(limiter.check(sender, later).is_err());
+    }
+}
@@ -1,5 +1,4 @@
 use std::{
-    collections::HashSet,
     fmt,
     future::Future,
     net::{SocketAddr, ToSocketAddrs},
@@ -7,7 +6,10 @@ use std::{
     sync::Arc,
 };
 
-use alloy::primitives::Address;
+use alloy::{
+    primitives::{Address, TxHash},
+    signers::local::PrivateKeySigner,
+};
 use axum::{
     middleware,
     routing::{get, post},
@@ -15,7 +17,7 @@ use axum::{
 };
 use tokio::{
     net::TcpListener,
-    sync::{mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot},
 };
 use tower_http::timeout::TimeoutLayer;
 use tracing::{error, info};
@@ -24,14 +26,28 @@ use crate::{
     commitments::handlers,
     primitives::{
         commitment::{InclusionCommitment, SignedCommitment},
-        CommitmentRequest, InclusionRequest,
+        CommitmentRequest, DecryptionKeyReveal, EncryptedInclusionRequest, InclusionRequest,
+        ReservationRequest, SidecarIdentity, SignedAvailability, SignedEncryptedCommitment,
+        SignedReservation,
+    },
+    service::{Readiness, SidecarHealth},
+    state::{
+        AvailabilityBoard, CommitmentKeys, CommitmentStatus, CommitmentStatuses,
+        DelegationSchedule, EncryptedRequestError, ReservationError, SlotAssignment,
     },
+    ChainConfig, RateLimitOpts,
 };
 
 use super::{
-    middleware::track_server_metrics,
+    idempotency::IdempotencyCache,
+    middleware::{limit_ip_rate, negotiate_api_version, track_server_metrics},
+    networks::NetworkRouter,
+    rate_limit::RateLimits,
+    signing::{sign_responses, ResponseSigner},
     spec,
-    spec::{CommitmentsApi, Error},
+    spec::{CommitmentsApi, Error, InclusionSimulation},
+    uploads::TxUploads,
+    whitelist::Whitelist,
 };
 
 /// Event type emitted by the commitments API.
@@ -43,21 +59,232 @@ pub struct Event {
     pub response: oneshot::Sender<Result<SignedCommitment, Error>>,
 }
 
+/// Event type emitted by the commitments API for blockspace reservations.
+#[derive(Debug)]
+pub struct ReservationEvent {
+    /// The reservation request to process.
+    pub request: ReservationRequest,
+    /// The response channel.
+    pub response: oneshot::Sender<Result<SignedReservation, Error>>,
+}
+
+/// Event type emitted by the commitments API for encrypted inclusion requests and the reveals
+/// of their decryption keys.
+#[derive(Debug)]
+pub enum EncryptedEvent {
+    /// An encrypted inclusion request to commit to.
+    Request {
+        /// The encrypted request.
+        request: EncryptedInclusionRequest,
+        /// The response channel.
+        response: oneshot::Sender<Result<SignedEncryptedCommitment, Error>>,
+    },
+    /// The decryption key of a pending encrypted request.
+    Reveal {
+        /// The revealed key.
+        reveal: DecryptionKeyReveal,
+        /// The response channel, answered with the target slot of the request.
+        response: oneshot::Sender<Result<u64, Error>>,
+    },
+}
+
+/// Event type emitted by the commitments API for the simulation of inclusion requests.
+#[derive(Debug)]
+pub struct SimulationEvent {
+    /// The inclusion request to simulate.
+    pub request: InclusionRequest,
+    /// The response channel.
+    pub response: oneshot::Sender<InclusionSimulation>,
+}
+
 /// The inner commitments-API handler that implements the [CommitmentsApi] spec.
 /// Should be wrapped by a [CommitmentsApiServer] JSON-RPC server to handle requests.
 #[derive(Debug)]
 pub struct CommitmentsApiInner {
     /// Event notification channel
     events: mpsc::Sender<Event>,
-    /// Optional whitelist of ECDSA public keys
-    #[allow(unused)]
-    whitelist: Option<HashSet<Address>>,
+    /// The signers allowed to request commitments
+    whitelist: Whitelist,
+    /// The chain ID that request signatures are bound to
+    chain_id: u64,
+    /// The identity of the sidecar, attesting its commitment key
+    identity: Arc<SidecarIdentity>,
+    /// The current and previous commitment keys of the sidecar
+    commitment_keys: Arc<CommitmentKeys>,
+    /// Reservation notification channel, if reservations are enabled
+    reservations: Option<mpsc::Sender<ReservationEvent>>,
+    /// Encrypted requests notification channel, if encrypted requests are enabled
+    encrypted: Option<mpsc::Sender<EncryptedEvent>>,
+    /// Simulation notification channel, if simulations are enabled
+    simulations: Option<mpsc::Sender<SimulationEvent>>,
+    /// The inclusion commitments issued per idempotency key
+    idempotency: IdempotencyCache,
+    /// The assignments of the upcoming proposal slots to their delegatees
+    delegation_schedule: DelegationSchedule,
+    /// The availability attestations of the upcoming proposal slots
+    availability_board: AvailabilityBoard,
+    /// The raw transactions uploaded to be referenced by hash
+    uploads: TxUploads,
+    /// The statuses of the commitments made
+    commitment_statuses: CommitmentStatuses,
+    /// The rate limits of the senders of requests
+    rate_limits: RateLimits,
+    /// The health of the sidecar driver
+    health: SidecarHealth,
 }
 
 impl CommitmentsApiInner {
-    /// Create a new API server with an optional whitelist of ECDSA public keys.
-    pub fn new(events: mpsc::Sender<Event>) -> Self {
-        Self { events, whitelist: None }
+    /// Create a new API server, accepting requests from any signer until a whitelist is set.
+    pub fn new(events: mpsc::Sender<Event>, chain_id: u64, identity: Arc<SidecarIdentity>) -> Self {
+        Self {
+            events,
+            whitelist: Whitelist::default(),
+            chain_id,
+            identity,
+            commitment_keys: Arc::default(),
+            reservations: None,
+            encrypted: None,
+            simulations: None,
+            idempotency: IdempotencyCache::default(),
+            delegation_schedule: DelegationSchedule::default(),
+            availability_board: AvailabilityBoard::default(),
+            uploads: TxUploads::default(),
+            commitment_statuses: CommitmentStatuses::default(),
+            rate_limits: RateLimits::default(),
+            health: SidecarHealth::default(),
+        }
+    }
+
+    /// Serves the given current and previous commitment keys.
+    pub fn with_commitment_keys(mut self, commitment_keys: Arc<CommitmentKeys>) -> Self {
+        self.commitment_keys = commitment_keys;
+        self
+    }
+
+    /// Enables blockspace reservations, sending their events to the provided channel.
+    pub fn with_reservations(mut self, reservations: mpsc::Sender<ReservationEvent>) -> Self {
+        self.reservations = Some(reservations);
+        self
+    }
+
+    /// Enables encrypted inclusion requests, sending their events to the provided channel.
+    pub fn with_encrypted_requests(mut self, encrypted: mpsc::Sender<EncryptedEvent>) -> Self {
+        self.encrypted = Some(encrypted);
+        self
+    }
+
+    /// Enables the simulation of inclusion requests, sending their events to the provided
+    /// channel.
+    pub fn with_simulations(mut self, simulations: mpsc::Sender<SimulationEvent>) -> Self {
+        self.simulations = Some(simulations);
+        self
+    }
+
+    /// Reports the assignments of the upcoming proposal slots from the given schedule.
+    pub fn with_delegation_schedule(mut self, delegation_schedule: DelegationSchedule) -> Self {
+        self.delegation_schedule = delegation_schedule;
+        self
+    }
+
+    /// Serves the availability attestations of the upcoming proposal slots from the given
+    /// board.
+    pub fn with_availability_board(mut self, availability_board: AvailabilityBoard) -> Self {
+        self.availability_board = availability_board;
+        self
+    }
+
+    /// Serves the statuses of the commitments made from the given tracker.
+    pub fn with_commitment_statuses(mut self, commitment_statuses: CommitmentStatuses) -> Self {
+        self.commitment_statuses = commitment_statuses;
+        self
+    }
+
+    /// Limits the rate of the requests of each sender.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Only accepts commitment requests from the signers of the given whitelist.
+    pub fn with_whitelist(mut self, whitelist: Whitelist) -> Self {
+        self.whitelist = whitelist;
+        self
+    }
+
+    /// Serves the readiness of the sidecar from the health of its driver.
+    pub fn with_health(mut self, health: SidecarHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Returns the chain ID that request signatures are bound to.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Returns the identity of the sidecar, attesting its commitment key.
+    pub fn identity(&self) -> &SidecarIdentity {
+        &self.identity
+    }
+
+    /// Returns the current and previous commitment keys of the sidecar.
+    pub fn commitment_keys(&self) -> &CommitmentKeys {
+        &self.commitment_keys
+    }
+
+    /// Returns the upcoming proposal slots of the sidecar, with the delegatee assigned
+    /// the authority over each.
+    pub fn availability(&self) -> Vec<SlotAssignment> {
+        self.delegation_schedule.upcoming()
+    }
+
+    /// Returns the signed availability attestations of the upcoming proposal slots.
+    pub fn availability_attestations(&self) -> Vec<SignedAvailability> {
+        self.availability_board.upcoming()
+    }
+
+    /// Returns the cache of the raw transactions uploaded to be referenced by hash.
+    pub fn uploads(&self) -> &TxUploads {
+        &self.uploads
+    }
+
+    /// Returns the rate limits of the senders of requests.
+    pub fn rate_limits(&self) -> &RateLimits {
+        &self.rate_limits
+    }
+
+    /// Returns the whitelist of the signers allowed to request commitments.
+    pub fn whitelist(&self) -> &Whitelist {
+        &self.whitelist
+    }
+
+    /// Returns the readiness of the sidecar to serve commitments.
+    pub fn readiness(&self) -> Readiness {
+        self.health.readiness()
+    }
+
+    /// Returns the status of the commitment to the transaction in the given slot.
+    pub fn commitment_status(&self, slot: u64, tx_hash: TxHash) -> CommitmentStatus {
+        self.commitment_statuses.get(slot, tx_hash)
+    }
+
+    /// Returns a receiver of the changes of status of the commitments.
+    pub fn subscribe_commitment_statuses(&self) -> broadcast::Receiver<CommitmentStatus> {
+        self.commitment_statuses.subscribe()
+    }
+
+    /// Requests the inclusion with an idempotency key. Retries of the request by the same
+    /// signer and with the same key get the original commitment back.
+    pub async fn request_inclusion_idempotent(
+        &self,
+        inclusion_request: InclusionRequest,
+        signer: Address,
+        key: String,
+    ) -> Result<InclusionCommitment, Error> {
+        let digest = inclusion_request.digest();
+        self.idempotency
+            .get_or_request(signer, key, digest, || self.request_inclusion(inclusion_request))
+            .await
     }
 }
 
@@ -78,6 +305,63 @@ impl CommitmentsApi for CommitmentsApiInner {
 
         response_rx.await.map_err(|_| Error::Internal)?.map(|c| c.into())
     }
+
+    async fn reserve_blockspace(
+        &self,
+        reservation_request: ReservationRequest,
+    ) -> Result<SignedReservation, Error> {
+        let Some(reservations) = &self.reservations else {
+            return Err(ReservationError::Disabled.into());
+        };
+
+        let (response_tx, response_rx) = oneshot::channel();
+        let event = ReservationEvent { request: reservation_request, response: response_tx };
+        reservations.send(event).await.map_err(|_| Error::Internal)?;
+
+        response_rx.await.map_err(|_| Error::Internal)?
+    }
+
+    async fn request_encrypted_inclusion(
+        &self,
+        encrypted_request: EncryptedInclusionRequest,
+    ) -> Result<SignedEncryptedCommitment, Error> {
+        let Some(encrypted) = &self.encrypted else {
+            return Err(EncryptedRequestError::Disabled.into());
+        };
+
+        let (response_tx, response_rx) = oneshot::channel();
+        let event = EncryptedEvent::Request { request: encrypted_request, response: response_tx };
+        encrypted.send(event).await.map_err(|_| Error::Internal)?;
+
+        response_rx.await.map_err(|_| Error::Internal)?
+    }
+
+    async fn reveal_decryption_key(&self, reveal: DecryptionKeyReveal) -> Result<u64, Error> {
+        let Some(encrypted) = &self.encrypted else {
+            return Err(EncryptedRequestError::Disabled.into());
+        };
+
+        let (response_tx, response_rx) = oneshot::channel();
+        let event = EncryptedEvent::Reveal { reveal, response: response_tx };
+        encrypted.send(event).await.map_err(|_| Error::Internal)?;
+
+        response_rx.await.map_err(|_| Error::Internal)?
+    }
+
+    async fn simulate_inclusion(
+        &self,
+        inclusion_request: InclusionRequest,
+    ) -> Result<InclusionSimulation, Error> {
+        let Some(simulations) = &self.simulations else {
+            return Err(Error::UnknownMethod);
+        };
+
+        let (response_tx, response_rx) = oneshot::channel();
+        let event = SimulationEvent { request: inclusion_request, response: response_tx };
+        simulations.send(event).await.map_err(|_| Error::Internal)?;
+
+        response_rx.await.map_err(|_| Error::Internal)
+    }
 }
 
 /// The outer commitments-API JSON-RPC server that wraps the [CommitmentsApiInner] handler.
@@ -87,11 +371,43 @@ pub struct CommitmentsApiServer {
     addr: SocketAddr,
     /// The shutdown signal.
     signal: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    /// The chain ID that request signatures are bound to.
+    chain_id: u64,
+    /// The identity of the sidecar, attesting its commitment key.
+    identity: Arc<SidecarIdentity>,
+    /// The current and previous commitment keys of the sidecar.
+    commitment_keys: Arc<CommitmentKeys>,
+    /// The channel to send reservation events to, if reservations are enabled.
+    reservations: Option<mpsc::Sender<ReservationEvent>>,
+    /// The channel to send encrypted request events to, if encrypted requests are enabled.
+    encrypted: Option<mpsc::Sender<EncryptedEvent>>,
+    /// The channel to send simulation events to, if simulations are enabled.
+    simulations: Option<mpsc::Sender<SimulationEvent>>,
+    /// The multi-network router to mount the server on instead of binding its address.
+    network_router: Option<NetworkRouter>,
+    /// The assignments of the upcoming proposal slots to their delegatees.
+    delegation_schedule: DelegationSchedule,
+    /// The availability attestations of the upcoming proposal slots.
+    availability_board: AvailabilityBoard,
+    /// The statuses of the commitments made.
+    commitment_statuses: CommitmentStatuses,
+    /// The rate limits of the senders of requests.
+    rate_limits: RateLimitOpts,
+    /// The signers allowed to request commitments.
+    whitelist: Whitelist,
+    /// The health of the sidecar driver.
+    health: SidecarHealth,
+    /// The key signing the responses, if any.
+    response_signer: Option<PrivateKeySigner>,
 }
 
 impl fmt::Debug for CommitmentsApiServer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("CommitmentsApiServer").field("addr", &self.addr).finish()
+        f.debug_struct("CommitmentsApiServer")
+            .field("addr", &self.addr)
+            .field("chain_id", &self.chain_id)
+            .field("commitment_signer", &self.identity.commitment_signer)
+            .finish()
     }
 }
 
@@ -103,6 +419,20 @@ impl CommitmentsApiServer {
             signal: Some(Box::pin(async {
                 let _ = tokio::signal::ctrl_c().await;
             })),
+            chain_id: ChainConfig::default().chain_id(),
+            identity: Arc::default(),
+            commitment_keys: Arc::default(),
+            reservations: None,
+            encrypted: None,
+            simulations: None,
+            network_router: None,
+            delegation_schedule: DelegationSchedule::default(),
+            availability_board: AvailabilityBoard::default(),
+            commitment_statuses: CommitmentStatuses::default(),
+            rate_limits: RateLimitOpts::default(),
+            whitelist: Whitelist::default(),
+            health: SidecarHealth::default(),
+            response_signer: None,
         }
     }
 
@@ -115,14 +445,145 @@ impl CommitmentsApiServer {
         Self {
             addr: addr.to_socket_addrs().unwrap().next().unwrap(),
             signal: Some(Box::pin(signal)),
+            chain_id: self.chain_id,
+            identity: self.identity,
+            commitment_keys: self.commitment_keys,
+            reservations: self.reservations,
+            encrypted: self.encrypted,
+            simulations: self.simulations,
+            network_router: self.network_router,
+            delegation_schedule: self.delegation_schedule,
+            availability_board: self.availability_board,
+            commitment_statuses: self.commitment_statuses,
+            rate_limits: self.rate_limits,
+            whitelist: self.whitelist,
+            health: self.health,
+            response_signer: self.response_signer,
         }
     }
 
+    /// Sets the chain ID that request signatures are bound to. Defaults to mainnet.
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Sets the identity of the sidecar, served to clients to attest its commitment key.
+    pub fn with_identity(mut self, identity: SidecarIdentity) -> Self {
+        self.identity = Arc::new(identity);
+        self
+    }
+
+    /// Sets the current and previous commitment keys of the sidecar, served to the verifiers
+    /// of its commitments.
+    pub fn with_commitment_keys(mut self, commitment_keys: CommitmentKeys) -> Self {
+        self.commitment_keys = Arc::new(commitment_keys);
+        self
+    }
+
+    /// Enables blockspace reservations, sending their events to the provided channel.
+    pub fn with_reservations(mut self, reservations: mpsc::Sender<ReservationEvent>) -> Self {
+        self.reservations = Some(reservations);
+        self
+    }
+
+    /// Enables encrypted inclusion requests, sending their events to the provided channel.
+    pub fn with_encrypted_requests(mut self, encrypted: mpsc::Sender<EncryptedEvent>) -> Self {
+        self.encrypted = Some(encrypted);
+        self
+    }
+
+    /// Enables the simulation of inclusion requests, sending their events to the provided
+    /// channel.
+    pub fn with_simulations(mut self, simulations: mpsc::Sender<SimulationEvent>) -> Self {
+        self.simulations = Some(simulations);
+        self
+    }
+
+    /// Reports the assignments of the upcoming proposal slots from the given schedule,
+    /// shared with the driver that records them.
+    pub fn with_delegation_schedule(mut self, delegation_schedule: DelegationSchedule) -> Self {
+        self.delegation_schedule = delegation_schedule;
+        self
+    }
+
+    /// Serves the availability attestations of the upcoming proposal slots from the given
+    /// board, shared with the driver that publishes them.
+    pub fn with_availability_board(mut self, availability_board: AvailabilityBoard) -> Self {
+        self.availability_board = availability_board;
+        self
+    }
+
+    /// Serves the statuses of the commitments made from the given tracker, shared with the
+    /// driver that records them.
+    pub fn with_commitment_statuses(mut self, commitment_statuses: CommitmentStatuses) -> Self {
+        self.commitment_statuses = commitment_statuses;
+        self
+    }
+
+    /// Limits the rate of the requests of each IP address and signer, as configured.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimitOpts) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Only accepts commitment requests from the signers of the given whitelist, shared with
+    /// the driver that reloads it.
+    pub fn with_whitelist(mut self, whitelist: Whitelist) -> Self {
+        self.whitelist = whitelist;
+        self
+    }
+
+    /// Serves the readiness of the sidecar from the health of its driver, shared with the
+    /// driver that records it.
+    pub fn with_health(mut self, health: SidecarHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Signs every response with the given key in the `x-bolt-response-signature` header,
+    /// so that clients can prove later what the sidecar returned.
+    pub fn with_response_signer(mut self, signer: PrivateKeySigner) -> Self {
+        self.response_signer = Some(signer);
+        self
+    }
+
+    /// Mounts the server on the given multi-network router under its network, instead of
+    /// binding its own address.
+    pub fn with_network_router(mut self, network_router: NetworkRouter) -> Self {
+        self.network_router = Some(network_router);
+        self
+    }
+
     /// Runs the JSON-RPC server, sending events to the provided channel.
     pub async fn run(&mut self, events_tx: mpsc::Sender<Event>) {
-        let api = Arc::new(CommitmentsApiInner::new(events_tx));
+        let mut api = CommitmentsApiInner::new(events_tx, self.chain_id, self.identity.clone())
+            .with_commitment_keys(self.commitment_keys.clone())
+            .with_delegation_schedule(self.delegation_schedule.clone())
+            .with_availability_board(self.availability_board.clone())
+            .with_commitment_statuses(self.commitment_statuses.clone())
+            .with_rate_limits(RateLimits::new(&self.rate_limits))
+            .with_whitelist(self.whitelist.clone())
+            .with_health(self.health.clone());
+        if let Some(reservations) = self.reservations.clone() {
+            api = api.with_reservations(reservations);
+        }
+        if let Some(encrypted) = self.encrypted.clone() {
+            api = api.with_encrypted_requests(encrypted);
+        }
+        if let Some(simulations) = self.simulations.clone() {
+            api = api.with_simulations(simulations);
+        }
 
-        let router = make_router(api);
+        let response_signer =
+            self.response_signer.clone().map(|signer| ResponseSigner::new(signer, self.chain_id));
+        let router = make_router(Arc::new(api), response_signer);
+
+        if let Some(network_router) = &self.network_router {
+            info!(network = network_router.network(), "Commitments RPC server mounted");
+            network_router.register(router);
+            return;
+        }
 
         let listener = match TcpListener::bind(self.addr).await {
             Ok(listener) => listener,
@@ -140,7 +601,8 @@ impl CommitmentsApiServer {
         let signal = self.signal.take().expect("Signal not set");
 
         tokio::spawn(async move {
-            if let Err(err) = axum::serve(listener, router).with_graceful_shutdown(signal).await {
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(err) = axum::serve(listener, service).with_graceful_shutdown(signal).await {
                 error!(?err, "Commitments API Server error");
             }
         });
@@ -157,20 +619,43 @@ impl CommitmentsApiServer {
 /// NOTE: Keeping the router separate from the server start method allows
 /// for easier integration testing through the [`tower::Service`] interface.
 #[inline]
-fn make_router(state: Arc<CommitmentsApiInner>) -> Router {
-    Router::new()
+fn make_router(state: Arc<CommitmentsApiInner>, response_signer: Option<ResponseSigner>) -> Router {
+    let router = Router::new()
         .route("/", post(handlers::rpc_entrypoint))
+        .route(spec::RPC_V1_PATH, post(handlers::rpc_entrypoint))
         .route("/status", get(handlers::status))
+        .route("/ready", get(handlers::ready))
+        .route(spec::COMMITMENT_KEYS_PATH, get(handlers::commitment_keys))
         .fallback(handlers::not_found)
         .layer(TimeoutLayer::new(spec::MAX_REQUEST_TIMEOUT))
+        .layer(middleware::from_fn_with_state(state.clone(), limit_ip_rate))
+        .layer(middleware::from_fn(negotiate_api_version))
         .route_layer(middleware::from_fn(track_server_metrics))
-        .with_state(state)
+        .with_state(state.clone());
+
+    // The event stream never ends, so it can't be buffered to sign the responses
+    let events = Router::new()
+        .route(spec::COMMITMENT_EVENTS_PATH, get(handlers::commitment_events))
+        .layer(middleware::from_fn(negotiate_api_version))
+        .with_state(state);
+
+    let router = match response_signer {
+        Some(signer) => router.layer(middleware::from_fn_with_state(signer, sign_responses)),
+        None => router,
+    };
+    router.merge(events)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::commitments::{jsonrpc::JsonResponse, spec::SIGNATURE_HEADER};
-    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner};
+    use std::num::NonZero;
+
+    use crate::commitments::{
+        jsonrpc::JsonResponse,
+        signing::{recover_response_signer, RESPONSE_SIGNATURE_HEADER},
+        spec::SIGNATURE_HEADER,
+    };
+    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner, Signer};
     use serde_json::json;
 
     use crate::{
@@ -281,4 +766,388 @@ mod test {
 
         rx.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_request_not_whitelisted() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let whitelist = Whitelist::new([Address::random()]);
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_whitelist(whitelist.clone());
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let tx = default_test_transaction(signer.address(), None);
+        let req = create_signed_commitment_request(&[tx], &sk, 12).await.unwrap();
+
+        let sig = req.signature().unwrap().to_hex();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": [req]
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}"))
+            .header(SIGNATURE_HEADER, format!("{}:{}", signer.address(), sig))
+            .json(&payload)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        // The request is rejected before it reaches the driver
+        let error = response.json::<JsonResponse>().await.unwrap().error.unwrap();
+        assert_eq!(error.code, -32018);
+    }
+
+    #[tokio::test]
+    async fn test_get_sidecar_identity() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let identity = SidecarIdentity {
+            commitment_signer: Address::random(),
+            chain_id: 17000,
+            attestations: Vec::new(),
+        };
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_identity(identity.clone());
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash(&alloy::primitives::B256::random()).await.unwrap().to_hex();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_getSidecarIdentity",
+            "params": []
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}"))
+            .header(SIGNATURE_HEADER, format!("{}:{}", signer.address(), sig))
+            .json(&payload)
+            .send()
+            .await
+            .unwrap()
+            .json::<JsonResponse>()
+            .await
+            .unwrap();
+
+        let served: SidecarIdentity = serde_json::from_value(response.result).unwrap();
+        assert_eq!(served, identity);
+    }
+
+    #[tokio::test]
+    async fn test_signed_responses() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let response_signer = PrivateKeySigner::random();
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0")
+            .with_chain_id(17000)
+            .with_response_signer(response_signer.clone());
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        // Rejections are signed as well, here for the missing request signature
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": []
+        })
+        .to_string();
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}"))
+            .header("content-type", "application/json")
+            .body(body.clone())
+            .send()
+            .await
+            .unwrap();
+
+        let header = response.headers()[RESPONSE_SIGNATURE_HEADER].to_str().unwrap().to_string();
+        let response_body = response.bytes().await.unwrap();
+
+        let signer =
+            recover_response_signer(&header, 17000, body.as_bytes(), &response_body).unwrap();
+        assert_eq!(signer, response_signer.address());
+    }
+
+    #[tokio::test]
+    async fn test_commitment_events() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // The events are streamed even though the other responses are signed
+        let statuses = CommitmentStatuses::default();
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0")
+            .with_commitment_statuses(statuses.clone())
+            .with_response_signer(PrivateKeySigner::random());
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let tx = TxHash::repeat_byte(1);
+        let mut response =
+            reqwest::get(format!("http://{addr}/commitments/events?tx_hash={tx}")).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        statuses.record_pending(10, TxHash::repeat_byte(2));
+        statuses.record_pending(10, tx);
+
+        let chunk = response.chunk().await.unwrap().unwrap();
+        let event = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(event.starts_with("event: status\n"));
+        assert!(event.contains(&tx.to_string()));
+        assert!(event.contains("\"status\":\"pending\""));
+    }
+
+    #[tokio::test]
+    async fn test_versioned_path_and_negotiation() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0");
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash(&alloy::primitives::B256::random()).await.unwrap().to_hex();
+        let auth = format!("{}:{}", signer.address(), sig);
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_getVersion",
+            "params": []
+        });
+
+        let client = reqwest::Client::new();
+        for path in ["/", spec::RPC_V1_PATH] {
+            let response = client
+                .post(format!("http://{addr}{path}"))
+                .header(SIGNATURE_HEADER, &auth)
+                .json(&payload)
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(response.headers()[spec::API_VERSION_HEADER], spec::API_VERSION);
+            let json = response.json::<JsonResponse>().await.unwrap();
+            assert!(json.error.is_none());
+            assert_eq!(json.api_version, spec::API_VERSION);
+        }
+
+        let response = client
+            .post(format!("http://{addr}{}", spec::RPC_V1_PATH))
+            .header(SIGNATURE_HEADER, &auth)
+            .header(spec::API_VERSION_HEADER, "v2")
+            .json(&payload)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(response.json::<JsonResponse>().await.unwrap().error.unwrap().code, -32008);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let rate_limits = RateLimitOpts {
+            rate_limit_per_signer: NonZero::new(1),
+            rate_limit_burst: NonZero::new(1).unwrap(),
+            ..Default::default()
+        };
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_rate_limits(rate_limits);
+
+        let (events_tx, mut events) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        tokio::spawn(async move {
+            let commitment_signer = PrivateKeySigner::random();
+            while let Some(Event { request, response }) = events.recv().await {
+                let commitment = request.commit_and_sign(&commitment_signer).await.unwrap();
+                let _ = response.send(Ok(commitment));
+            }
+        });
+
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let tx = default_test_transaction(signer.address(), None);
+        let req = create_signed_commitment_request(&[tx], &sk, 12).await.unwrap();
+        let auth = format!("{}:{}", signer.address(), req.signature().unwrap().to_hex());
+
+        // A header naming the signer with a signature of someone else
+        let forger = PrivateKeySigner::random();
+        let forged = forger.sign_hash(&alloy::primitives::B256::random()).await.unwrap().to_hex();
+        let forged_auth = format!("{}:{}", signer.address(), forged);
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": [req]
+        });
+
+        let client = reqwest::Client::new();
+        let send = |auth: &str| {
+            client
+                .post(format!("http://{addr}"))
+                .header(SIGNATURE_HEADER, auth.to_string())
+                .json(&payload)
+                .send()
+        };
+
+        // Forged requests are rejected without consuming the tokens of the signer
+        for _ in 0..3 {
+            let response = send(&forged_auth).await.unwrap();
+            let error = response.json::<JsonResponse>().await.unwrap().error.unwrap();
+            assert_eq!(error.code, -32004);
+        }
+
+        let response = send(&auth).await.unwrap();
+        assert!(response.json::<JsonResponse>().await.unwrap().error.is_none());
+
+        // The second request within the same second exceeds the limit of the signer
+        let response = send(&auth).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "1");
+
+        let error = response.json::<JsonResponse>().await.unwrap().error.unwrap();
+        assert_eq!(error.code, -32020);
+        assert_eq!(error.data.unwrap()["scope"], "signer");
+    }
+
+    #[tokio::test]
+    async fn test_reserve_blockspace() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let (reservations_tx, mut reservations) = mpsc::channel(1);
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_reservations(reservations_tx);
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let signer = PrivateKeySigner::random();
+        let request = ReservationRequest {
+            gas_per_slot: 100_000,
+            start_slot: 10,
+            end_slot: 19,
+            signer: None,
+        };
+        let digest = request.signing_digest(ChainConfig::default().chain_id());
+        let sig = signer.sign_hash(&digest).await.unwrap().to_hex();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_reserveBlockspace",
+            "params": [request]
+        });
+
+        let commitment_signer = PrivateKeySigner::random();
+        let subscriber = signer.address();
+        tokio::spawn(async move {
+            let ReservationEvent { request, response } = reservations.recv().await.unwrap();
+            assert_eq!(request.signer, Some(subscriber));
+
+            let reservation = crate::primitives::Reservation {
+                subscriber,
+                gas_per_slot: request.gas_per_slot,
+                start_slot: request.start_slot,
+                end_slot: request.end_slot,
+                price_per_slot: alloy::primitives::U256::from(1),
+            };
+            let signature = commitment_signer.sign_hash(&reservation.digest()).await.unwrap();
+            response.send(Ok(SignedReservation { reservation, signature })).unwrap();
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}"))
+            .header(SIGNATURE_HEADER, format!("{subscriber}:{sig}"))
+            .json(&payload)
+            .send()
+            .await
+            .unwrap()
+            .json::<JsonResponse>()
+            .await
+            .unwrap();
+
+        let reserved: SignedReservation = serde_json::from_value(response.result).unwrap();
+        assert_eq!(reserved.reservation.subscriber, subscriber);
+        assert_eq!(reserved.reservation.gas_per_slot, 100_000);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_inclusion() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let (simulations_tx, mut simulations) = mpsc::channel(1);
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_simulations(simulations_tx);
+
+        let (events_tx, mut events) = mpsc::channel(1);
+
+        server.run(events_tx).await;
+        let addr = server.local_addr();
+
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let tx = default_test_transaction(signer.address(), None);
+        let req = create_signed_commitment_request(&[tx], &sk, 12).await.unwrap();
+        let sig = req.signature().unwrap().to_hex();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_simulateInclusion",
+            "params": [req]
+        });
+
+        let address = signer.address();
+        tokio::spawn(async move {
+            let SimulationEvent { request, response } = simulations.recv().await.unwrap();
+            assert_eq!(request.signer, Some(address));
+            response.send(InclusionSimulation::accepted(request.slot)).unwrap();
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}"))
+            .header(SIGNATURE_HEADER, format!("{address}:{sig}"))
+            .json(&payload)
+            .send()
+            .await
+            .unwrap()
+            .json::<JsonResponse>()
+            .await
+            .unwrap();
+
+        let simulation: InclusionSimulation = serde_json::from_value(response.result).unwrap();
+        assert_eq!(simulation, InclusionSimulation::accepted(12));
+
+        // Simulations never reach the commitment flow
+        assert!(events.try_recv().is_err());
+    }
 }
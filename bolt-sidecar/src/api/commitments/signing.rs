@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use alloy::{
+    hex,
+    primitives::{keccak256, Address, Signature, B256},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use axum::{
+    body::{self, Body},
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+use crate::primitives::commitment::SignatureError;
+
+use super::spec::Error;
+
+/// The header carrying the signature of a response by the sidecar that returned it,
+/// as `<address>:<signature>`.
+pub const RESPONSE_SIGNATURE_HEADER: &str = "x-bolt-response-signature";
+
+/// The maximum length of the request bodies buffered to sign their responses, matching the
+/// default body limit of the JSON extractor.
+const MAX_REQUEST_BODY_LENGTH: usize = 2 * 1024 * 1024;
+
+/// The digest signed to attest that a response was returned to a request:
+///
+/// `keccak256(be_bytes(chain_id) | keccak256(request_body) | keccak256(response_body))`
+pub fn response_digest(chain_id: u64, request_body: &[u8], response_body: &[u8]) -> B256 {
+    let mut data = Vec::with_capacity(8 + 32 + 32);
+    data.extend_from_slice(&chain_id.to_be_bytes());
+    data.extend_from_slice(keccak256(request_body).as_slice());
+    data.extend_from_slice(keccak256(response_body).as_slice());
+    keccak256(data)
+}
+
+/// Recovers the address that signed a response from the value of its
+/// [RESPONSE_SIGNATURE_HEADER], checking that it matches the advertised one.
+pub fn recover_response_signer(
+    header: &str,
+    chain_id: u64,
+    request_body: &[u8],
+    response_body: &[u8],
+) -> Result<Address, Error> {
+    let (address, signature) = header.split_once(':').ok_or(Error::MalformedHeader)?;
+    let address = Address::from_str(address).map_err(|_| Error::MalformedHeader)?;
+    let signature = Signature::from_str(signature).map_err(|_| Error::MalformedHeader)?;
+
+    let digest = response_digest(chain_id, request_body, response_body);
+    let recovered = signature.recover_address_from_prehash(&digest)?;
+    if recovered != address {
+        return Err(Error::InvalidSignature(SignatureError));
+    }
+
+    Ok(recovered)
+}
+
+/// Signs the responses of the commitments API with the commitment key of the sidecar, so that
+/// gateways can prove later that the sidecar returned a given acceptance or rejection.
+#[derive(Debug, Clone)]
+pub struct ResponseSigner {
+    signer: PrivateKeySigner,
+    chain_id: u64,
+}
+
+impl ResponseSigner {
+    /// Creates a new response signer with the given key, for the given chain.
+    pub fn new(signer: PrivateKeySigner, chain_id: u64) -> Self {
+        Self { signer, chain_id }
+    }
+
+    /// Returns the value of the [RESPONSE_SIGNATURE_HEADER] of the response to a request.
+    pub fn sign(&self, request_body: &[u8], response_body: &[u8]) -> Option<HeaderValue> {
+        let digest = response_digest(self.chain_id, request_body, response_body);
+
+        match self.signer.sign_hash_sync(&digest) {
+            Ok(signature) => {
+                let value = format!(
+                    "{}:{}",
+                    self.signer.address(),
+                    hex::encode_prefixed(signature.as_bytes())
+                );
+                HeaderValue::from_str(&value).ok()
+            }
+            Err(err) => {
+                error!(?err, "Failed to sign commitments API response");
+                None
+            }
+        }
+    }
+}
+
+/// Middleware signing every response with the [ResponseSigner], over both the request
+/// and the response bodies.
+pub async fn sign_responses(
+    State(signer): State<ResponseSigner>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = req.into_parts();
+    let Ok(request_body) = body::to_bytes(body, MAX_REQUEST_BODY_LENGTH).await else {
+        return Error::PayloadTooLarge.into_response();
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(request_body.clone()))).await;
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(response_body) = body::to_bytes(body, usize::MAX).await else {
+        return Error::Internal.into_response();
+    };
+
+    if let Some(signature) = signer.sign(&request_body, &response_body) {
+        parts.headers.insert(RESPONSE_SIGNATURE_HEADER, signature);
+    }
+
+    Response::from_parts(parts, Body::from(response_body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_response() {
+        let signer = PrivateKeySigner::random();
+        let response_signer = ResponseSigner::new(signer.clone(), 17000);
+
+        let request = br#"{"jsonrpc":"2.0","id":1,"method":"bolt_requestInclusion"}"#;
+        let response = br#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        let header = response_signer.sign(request, response).unwrap();
+        let header = header.to_str().unwrap();
+
+        let recovered = recover_response_signer(header, 17000, request, response).unwrap();
+        assert_eq!(recovered, signer.address());
+
+        // The signature doesn't hold for another response, nor on another chain
+        assert!(recover_response_signer(header, 17000, request, b"{}").is_err());
+        assert!(recover_response_signer(header, 1, request, response).is_err());
+    }
+}
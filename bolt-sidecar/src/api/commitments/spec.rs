@@ -1,21 +1,79 @@
+use std::{fmt, time::Duration};
+
 use alloy::primitives::SignatureError;
-use axum::{extract::rejection::JsonRejection, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::rejection::JsonRejection,
+    http::{header::RETRY_AFTER, HeaderValue},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    primitives::{commitment::InclusionCommitment, InclusionRequest},
-    state::{consensus::ConsensusError, ValidationError},
+    api::codes::ErrorCode,
+    primitives::{
+        commitment::InclusionCommitment, DecryptionKeyReveal, EncryptedInclusionRequest,
+        InclusionRequest, ReservationRequest, SignedEncryptedCommitment, SignedReservation,
+    },
+    state::{
+        consensus::ConsensusError, pricing::PriceQuote, EncryptedRequestError, ReservationError,
+        ValidationError,
+    },
 };
 
-use super::jsonrpc::JsonResponse;
+use super::{jsonrpc::JsonResponse, params::ParamsError};
+
+pub(crate) const SIGNATURE_HEADER: &str = "x-bolt-signature";
+
+/// The optional header carrying the idempotency key of an inclusion request.
+pub(crate) const IDEMPOTENCY_KEY_HEADER: &str = "x-bolt-idempotency-key";
+
+/// The header carrying the version of the commitments API, on both requests and responses.
+pub(crate) const API_VERSION_HEADER: &str = "x-bolt-api-version";
+
+/// The current version of the commitments API.
+pub const API_VERSION: &str = "v1";
+
+/// The versioned path of the JSON-RPC endpoint. The root path is kept for legacy clients.
+pub(super) const RPC_V1_PATH: &str = "/rpc/v1";
+
+/// The path of the stream of the changes of status of the commitments, as server-sent events.
+pub(super) const COMMITMENT_EVENTS_PATH: &str = "/commitments/events";
+
+/// The path of the current and previous commitment keys of the sidecar, served without
+/// authentication to the verifiers of commitments.
+pub(super) const COMMITMENT_KEYS_PATH: &str = "/commitment-keys";
+
+pub(crate) const GET_VERSION_METHOD: &str = "bolt_getVersion";
+
+pub(crate) const REQUEST_INCLUSION_METHOD: &str = "bolt_requestInclusion";
+
+pub(crate) const GET_SIDECAR_IDENTITY_METHOD: &str = "bolt_getSidecarIdentity";
+
+pub(crate) const RESERVE_BLOCKSPACE_METHOD: &str = "bolt_reserveBlockspace";
+
+pub(crate) const SIMULATE_INCLUSION_METHOD: &str = "bolt_simulateInclusion";
+
+pub(crate) const GET_AVAILABILITY_METHOD: &str = "bolt_getAvailability";
+
+pub(crate) const GET_AVAILABILITY_ATTESTATIONS_METHOD: &str = "bolt_getAvailabilityAttestations";
+
+pub(crate) const REQUEST_BATCH_INCLUSION_METHOD: &str = "bolt_requestBatchInclusion";
+
+pub(crate) const UPLOAD_TRANSACTIONS_METHOD: &str = "bolt_uploadTransactions";
+
+pub(crate) const LOOKUP_TRANSACTIONS_METHOD: &str = "bolt_lookupTransactions";
+
+pub(crate) const REQUEST_INCLUSION_BY_HASH_METHOD: &str = "bolt_requestInclusionByHash";
 
-pub(super) const SIGNATURE_HEADER: &str = "x-bolt-signature";
+pub(crate) const GET_COMMITMENT_STATUS_METHOD: &str = "bolt_getCommitmentStatus";
 
-pub(super) const GET_VERSION_METHOD: &str = "bolt_getVersion";
+pub(crate) const REQUEST_ENCRYPTED_INCLUSION_METHOD: &str = "bolt_requestEncryptedInclusion";
 
-pub(super) const REQUEST_INCLUSION_METHOD: &str = "bolt_requestInclusion";
+pub(crate) const REVEAL_DECRYPTION_KEY_METHOD: &str = "bolt_revealDecryptionKey";
 
-pub(super) const MAX_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(6);
+pub(crate) const MAX_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(6);
 
 /// Error type for the commitments API.
 #[derive(Debug, Error)]
@@ -50,64 +108,333 @@ pub enum Error {
     /// Unknown method.
     #[error("Unknown method")]
     UnknownMethod,
+    /// Missing, extra or malformed params of the method.
+    #[error("Invalid params: {0}")]
+    InvalidParams(#[from] ParamsError),
     /// Invalid JSON.
     #[error(transparent)]
     InvalidJson(#[from] JsonRejection),
+    /// Request body too large.
+    #[error("Request body too large")]
+    PayloadTooLarge,
+    /// Unsupported API version.
+    #[error("Unsupported API version '{0}', supported: {API_VERSION}")]
+    UnsupportedApiVersion(String),
+    /// Blockspace reservation rejected.
+    #[error("Reservation rejected: {0}")]
+    Reservation(#[from] ReservationError),
+    /// Encrypted request or decryption key rejected.
+    #[error("Encrypted request rejected: {0}")]
+    Encrypted(#[from] EncryptedRequestError),
+    /// Idempotency key already used for a different request.
+    #[error("Idempotency key already used for a different request")]
+    IdempotencyKeyReused,
+    /// Request shed to protect an upcoming proposal.
+    #[error("Overloaded ahead of a proposal, retry later")]
+    Overloaded,
+    /// The authority over the slot is assigned to a delegatee whose key the sidecar doesn't hold.
+    #[error("The authority over slot {0} is assigned to another delegatee")]
+    SlotAssigned(u64),
+    /// Top-of-block request outbid by a competing one.
+    #[error(
+        "Outbid for the top of block of slot {slot}, the winning bid paid {winning_price} wei"
+    )]
+    Outbid {
+        /// The target slot of the request.
+        slot: u64,
+        /// The total tip paid by the winning request, in wei.
+        winning_price: u128,
+    },
+    /// Too many requests from the same IP address or signer.
+    #[error("Rate limit per {scope} exceeded, retry in {}ms", retry_after.as_millis())]
+    RateLimited {
+        /// The sender the exceeded limit applies to.
+        scope: RateLimitScope,
+        /// The time after which the request can be retried.
+        retry_after: Duration,
+    },
+    /// The signer of the request already holds the max number of commitments in its slot.
+    #[error("Max pending commitments of the signer reached for slot {slot}: {max}")]
+    PendingQuotaReached {
+        /// The target slot of the request.
+        slot: u64,
+        /// The max number of commitments of a signer per slot.
+        max: usize,
+    },
+    /// Request rejected for a slot-related reason, with the next slots it could target instead.
+    #[error("{err}")]
+    Retarget {
+        /// The rejection of the request for its target slot.
+        err: Box<Error>,
+        /// The next proposal slots of the sidecar that the request could be committed to.
+        suggested_slots: Vec<u64>,
+    },
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> axum::http::Response<axum::body::Body> {
+impl Error {
+    /// Returns the stable error code of the error, shared with the other bolt components.
+    pub fn error_code(&self) -> ErrorCode {
         match self {
-            Error::Rejected(err) => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32000, err.to_string())))
-                    .into_response()
-            }
-            Error::Duplicate => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32001, self.to_string())))
-                    .into_response()
-            }
-            Error::Internal => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(JsonResponse::from_error(-32002, self.to_string())),
-            )
-                .into_response(),
-            Error::NoSignature => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32003, self.to_string())))
-                    .into_response()
-            }
-            Error::InvalidSignature(err) => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32004, err.to_string())))
-                    .into_response()
+            Error::Rejected(_) => ErrorCode::Rejected,
+            Error::Consensus(err) => err.error_code(),
+            Error::Validation(err) => err.error_code(),
+            Error::Duplicate => ErrorCode::Duplicate,
+            Error::Internal => ErrorCode::Internal,
+            Error::NoSignature => ErrorCode::MissingSignature,
+            Error::InvalidSignature(_) => ErrorCode::InvalidSignature,
+            Error::MalformedHeader => ErrorCode::MalformedHeader,
+            Error::Signature(_) => ErrorCode::MalformedSignature,
+            Error::UnknownMethod => ErrorCode::MethodNotFound,
+            Error::InvalidParams(_) => ErrorCode::InvalidParams,
+            Error::InvalidJson(_) => ErrorCode::InvalidRequest,
+            Error::PayloadTooLarge => ErrorCode::PayloadTooLarge,
+            Error::UnsupportedApiVersion(_) => ErrorCode::UnsupportedApiVersion,
+            Error::Reservation(ReservationError::InsufficientCapacity(_)) => ErrorCode::SlotFull,
+            Error::Reservation(_) => ErrorCode::ReservationRejected,
+            Error::Encrypted(EncryptedRequestError::InsufficientCapacity(_)) => ErrorCode::SlotFull,
+            Error::Encrypted(
+                EncryptedRequestError::Decryption(_) |
+                EncryptedRequestError::TimeLock(_) |
+                EncryptedRequestError::MissingTimeLock,
+            ) => ErrorCode::ValidationFailed,
+            Error::Encrypted(EncryptedRequestError::TooManyWithheldKeys(..)) => {
+                ErrorCode::Unauthorized
             }
-            Error::Signature(err) => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32005, err.to_string())))
-                    .into_response()
-            }
-            Error::Consensus(err) => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32006, err.to_string())))
-                    .into_response()
+            Error::Encrypted(_) => ErrorCode::Rejected,
+            Error::IdempotencyKeyReused => ErrorCode::IdempotencyKeyReused,
+            Error::Overloaded => ErrorCode::RateLimited,
+            Error::SlotAssigned(_) => ErrorCode::NoProposer,
+            Error::Outbid { .. } => ErrorCode::Outbid,
+            Error::RateLimited { .. } => ErrorCode::RateLimited,
+            Error::PendingQuotaReached { .. } => ErrorCode::RateLimited,
+            Error::Retarget { err, .. } => err.error_code(),
+        }
+    }
+
+    /// Returns the message of the error sent to the user.
+    fn message(&self) -> String {
+        match self {
+            Error::Rejected(err) => err.to_string(),
+            Error::InvalidSignature(err) => err.to_string(),
+            Error::Signature(err) => err.to_string(),
+            Error::Consensus(err) => err.to_string(),
+            Error::Validation(err) => err.to_string(),
+            Error::Reservation(err) => err.to_string(),
+            Error::Encrypted(err) => err.to_string(),
+            Error::InvalidJson(err) => format!("Invalid request: {err}"),
+            Error::Retarget { err, .. } => err.message(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Returns the structured data of the JSON-RPC error.
+    pub fn data(&self) -> ErrorData {
+        let mut data = ErrorData::new(self.error_code(), self.message());
+        self.add_context(&mut data);
+        data
+    }
+
+    /// Adds the context of the error to its data.
+    fn add_context(&self, data: &mut ErrorData) {
+        match self {
+            Error::Retarget { err, suggested_slots } => {
+                err.add_context(data);
+                data.next_slot = suggested_slots.first().copied();
+                data.suggested_slots = Some(suggested_slots.clone());
             }
-            Error::Validation(err) => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32006, err.to_string())))
-                    .into_response()
+            Error::RateLimited { scope, retry_after } => {
+                data.scope = Some(*scope);
+                data.retry_after_ms = Some(retry_after.as_millis() as u64);
             }
-            Error::MalformedHeader => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32007, self.to_string())))
-                    .into_response()
+            Error::PendingQuotaReached { slot, max } => {
+                data.slot = Some(*slot);
+                data.max_pending_commitments = Some(*max);
             }
-            Error::UnknownMethod => {
-                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32601, self.to_string())))
-                    .into_response()
+            Error::Outbid { slot, winning_price } => {
+                data.slot = Some(*slot);
+                data.winning_price = Some(*winning_price);
             }
-            Error::InvalidJson(err) => (
-                StatusCode::BAD_REQUEST,
-                Json(JsonResponse::from_error(-32600, format!("Invalid request: {err}"))),
-            )
-                .into_response(),
+            Error::SlotAssigned(slot) => data.slot = Some(*slot),
+            Error::InvalidParams(err) => data.path = err.path().map(ToString::to_string),
+            Error::Consensus(ConsensusError::InvalidSlot(slot)) => data.slot = Some(*slot),
+            Error::Validation(err) => match err {
+                ValidationError::BaseFeeTooLow(fee) => data.required_max_fee_per_gas = Some(*fee),
+                ValidationError::BlobBaseFeeTooLow(fee) => {
+                    data.required_max_fee_per_blob_gas = Some(*fee)
+                }
+                ValidationError::MaxPriorityFeePerGasTooLow(quote) => {
+                    data.slot = Some(quote.slot);
+                    data.required_priority_fee_per_gas = Some(quote.min_priority_fee);
+                    data.quote = Some(*quote);
+                }
+                ValidationError::NonceTooLow(expected, _) |
+                ValidationError::NonceTooHigh(expected, _) => data.expected_nonce = Some(*expected),
+                ValidationError::SlotTooLow(slot) => data.next_slot = Some(*slot),
+                ValidationError::MaxCommitmentsReachedForSlot(slot, _) |
+                ValidationError::MaxCommittedGasReachedForSlot(slot, _) |
+                ValidationError::TopOfBlockAuctionClosed(slot) => data.slot = Some(*slot),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> axum::http::Response<axum::body::Body> {
+        let code = self.error_code();
+        let mut response = JsonResponse::from_error(code.json_rpc_code(), self.message());
+        if let Some(error) = response.error.as_mut() {
+            error.data = serde_json::to_value(self.data()).ok();
+        }
+
+        let mut response = (code.http_status(), code.header(), Json(response)).into_response();
+        if let Error::RateLimited { retry_after, .. } = self {
+            // The header is in whole seconds, rounded up so that retries are not limited again
+            let secs = retry_after.as_millis().div_ceil(1000);
+            response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(secs as u64));
+        }
+
+        response
+    }
+}
+
+/// The `data` of every JSON-RPC error of the commitments API, so that clients can branch on
+/// the error without parsing its message.
+///
+/// The code, message and retryable flag are always set. The context fields are only set by
+/// the errors they apply to, and omitted otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorData {
+    /// The stable code of the error, e.g. `SLOT_FULL`.
+    pub code: ErrorCode,
+    /// The human-readable message of the error, the same as the one of the JSON-RPC error.
+    pub message: String,
+    /// Whether the same request may be accepted if sent again later.
+    pub retryable: bool,
+    /// The slot the error applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// The earliest slot the request could target instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_slot: Option<u64>,
+    /// The next proposal slots of the sidecar that the request could be committed to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_slots: Option<Vec<u64>>,
+    /// The min max fee per gas of the transactions, in wei, to cover the max basefee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_max_fee_per_gas: Option<u128>,
+    /// The min max fee per blob gas of the transactions, in wei, to cover the max blob
+    /// basefee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_max_fee_per_blob_gas: Option<u128>,
+    /// The min priority fee per gas of the transactions, in wei.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_priority_fee_per_gas: Option<u128>,
+    /// The price quote of the target slot, for underpriced requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<PriceQuote>,
+    /// The nonce expected for the sender of a transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_nonce: Option<u64>,
+    /// The total tip paid by the winning top-of-block request, in wei.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winning_price: Option<u128>,
+    /// The sender the exceeded rate limit applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<RateLimitScope>,
+    /// The time after which the request can be retried, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// The max number of commitments of a signer per slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pending_commitments: Option<usize>,
+    /// The path of the invalid param, e.g. `params[0].txs[1]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl ErrorData {
+    /// Creates the data of an error without context, retryable if its code is.
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        Self {
+            code,
+            message,
+            retryable: code.is_retryable(),
+            slot: None,
+            next_slot: None,
+            suggested_slots: None,
+            required_max_fee_per_gas: None,
+            required_max_fee_per_blob_gas: None,
+            required_priority_fee_per_gas: None,
+            quote: None,
+            expected_nonce: None,
+            winning_price: None,
+            scope: None,
+            retry_after_ms: None,
+            max_pending_commitments: None,
+            path: None,
+        }
+    }
+}
+
+/// The sender a rate limit of the commitments API applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitScope {
+    /// The IP address the request was sent from.
+    Ip,
+    /// The signer of the request.
+    Signer,
+}
+
+impl fmt::Display for RateLimitScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip => f.write_str("IP address"),
+            Self::Signer => f.write_str("signer"),
         }
     }
 }
 
+/// The outcome of the simulation of an inclusion request, which is validated like a
+/// commitment request without being signed nor committed to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionSimulation {
+    /// Whether the request would be accepted if requested now.
+    pub accepted: bool,
+    /// The slot the request would be committed to, if accepted. Requests with a slot range
+    /// target the earliest proposal slot of the range they are valid for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// The error code of the rejection, if rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    /// The reason of the rejection, if rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl InclusionSimulation {
+    /// The simulation of a request that would be committed to the given slot.
+    pub fn accepted(slot: u64) -> Self {
+        Self { accepted: true, slot: Some(slot), code: None, reason: None }
+    }
+
+    /// The simulation of a request that would be rejected with the given error.
+    pub fn rejected(err: &Error) -> Self {
+        let reason = match err {
+            Error::Consensus(err) => err.to_string(),
+            Error::Validation(err) => err.to_string(),
+            err => err.to_string(),
+        };
+
+        Self { accepted: false, slot: None, code: Some(err.error_code()), reason: Some(reason) }
+    }
+}
+
 /// Error indicating the rejection of a commitment request. This should
 /// be returned to the user.
 #[derive(Debug, Error)]
@@ -125,4 +452,64 @@ pub trait CommitmentsApi {
         &self,
         inclusion_request: InclusionRequest,
     ) -> Result<InclusionCommitment, Error>;
+
+    /// Reserves gas in every proposal slot of the requested period.
+    async fn reserve_blockspace(
+        &self,
+        reservation_request: ReservationRequest,
+    ) -> Result<SignedReservation, Error>;
+
+    /// Commits to include the transactions of an encrypted request once decrypted, holding
+    /// its gas limit in its target slot.
+    async fn request_encrypted_inclusion(
+        &self,
+        encrypted_request: EncryptedInclusionRequest,
+    ) -> Result<SignedEncryptedCommitment, Error>;
+
+    /// Reveals the decryption key of a pending encrypted request, returning its target slot.
+    async fn reveal_decryption_key(&self, reveal: DecryptionKeyReveal) -> Result<u64, Error>;
+
+    /// Validates an inclusion request like [CommitmentsApi::request_inclusion] without
+    /// committing to it, returning whether it would be accepted.
+    async fn simulate_inclusion(
+        &self,
+        inclusion_request: InclusionRequest,
+    ) -> Result<InclusionSimulation, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_data() {
+        let err = Error::Retarget {
+            err: Box::new(ValidationError::MaxCommitmentsReachedForSlot(12, 128).into()),
+            suggested_slots: vec![14, 17],
+        };
+        let data = serde_json::to_value(err.data()).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "code": "SLOT_FULL",
+                "message": "Max commitments reached for slot 12: 128",
+                "retryable": false,
+                "slot": 12,
+                "nextSlot": 14,
+                "suggestedSlots": [14, 17],
+            })
+        );
+
+        let err = Error::RateLimited {
+            scope: RateLimitScope::Signer,
+            retry_after: Duration::from_millis(250),
+        };
+        let data = err.data();
+        assert!(data.retryable);
+        assert_eq!(data.retry_after_ms, Some(250));
+
+        let err = Error::from(ValidationError::BaseFeeTooLow(7_000_000_000));
+        assert_eq!(err.data().required_max_fee_per_gas, Some(7_000_000_000));
+        assert_eq!(Error::Internal.data().code, ErrorCode::Internal);
+    }
 }
@@ -0,0 +1,148 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::TxHash;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::FullTransaction;
+
+use super::spec::{Error, RejectionError};
+
+/// The time an uploaded transaction is kept for after its last upload: long enough for the
+/// requests referencing it to be retargeted over several epochs.
+pub(super) const TX_UPLOAD_TTL: Duration = Duration::from_secs(3 * 32 * 12);
+
+/// The maximum number of uploaded transactions kept at once.
+pub(super) const MAX_UPLOADED_TXS: usize = 4096;
+
+/// An uploaded transaction, as returned by the upload and lookup methods.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedTx {
+    /// The hash of the transaction, to reference it in inclusion requests.
+    pub hash: TxHash,
+    /// The number of seconds the transaction is still kept for, or none if it is unknown or
+    /// expired.
+    pub expires_in: Option<u64>,
+}
+
+/// A TTL cache of the raw transactions uploaded by gateways, keyed by hash.
+///
+/// Gateways upload the transactions once and then reference them by hash in the inclusion
+/// requests of every slot they target, instead of sending the raw transactions with each
+/// request. Transactions are signed, so they can't be forged by uploading them under the
+/// hash of another one.
+#[derive(Debug)]
+pub struct TxUploads {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<TxHash, (FullTransaction, Instant)>>,
+}
+
+impl Default for TxUploads {
+    fn default() -> Self {
+        Self::new(TX_UPLOAD_TTL, MAX_UPLOADED_TXS)
+    }
+}
+
+impl TxUploads {
+    /// Create a new cache keeping up to `capacity` transactions for the given duration.
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self { ttl, capacity, entries: Default::default() }
+    }
+
+    /// Stores the transactions, or extends their TTL if they were already uploaded.
+    ///
+    /// Rejects the whole upload if it doesn't fit in the cache.
+    pub fn upload(&self, txs: Vec<FullTransaction>) -> Result<Vec<UploadedTx>, Error> {
+        let mut entries = self.entries.lock();
+        let now = Instant::now();
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+
+        let new = txs.iter().filter(|tx| !entries.contains_key(tx.hash())).count();
+        if entries.len() + new > self.capacity {
+            return Err(RejectionError::ValidationFailed(format!(
+                "Too many uploaded transactions, at most {} are kept",
+                self.capacity
+            ))
+            .into());
+        }
+
+        let expires_at = now + self.ttl;
+        let uploaded = txs
+            .into_iter()
+            .map(|tx| {
+                let hash = *tx.hash();
+                entries.insert(hash, (tx, expires_at));
+                UploadedTx { hash, expires_in: Some(self.ttl.as_secs()) }
+            })
+            .collect();
+
+        Ok(uploaded)
+    }
+
+    /// Returns the uploaded transactions with the given hashes, in order.
+    pub fn resolve(&self, hashes: &[TxHash]) -> Result<Vec<FullTransaction>, Error> {
+        let entries = self.entries.lock();
+        let now = Instant::now();
+
+        hashes
+            .iter()
+            .map(|hash| match entries.get(hash) {
+                Some((tx, expires_at)) if *expires_at > now => Ok(tx.clone()),
+                _ => Err(RejectionError::ValidationFailed(format!(
+                    "Unknown transaction {hash}, it must be uploaded first"
+                ))
+                .into()),
+            })
+            .collect()
+    }
+
+    /// Returns whether each of the given transactions is uploaded, and for how long it is
+    /// still kept.
+    pub fn lookup(&self, hashes: &[TxHash]) -> Vec<UploadedTx> {
+        let entries = self.entries.lock();
+        let now = Instant::now();
+
+        hashes
+            .iter()
+            .map(|hash| UploadedTx {
+                hash: *hash,
+                expires_in: entries
+                    .get(hash)
+                    .and_then(|(_, expires_at)| expires_at.checked_duration_since(now))
+                    .map(|remaining| remaining.as_secs()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TEST_CONTRACT_CREATION_TX;
+
+    #[test]
+    fn test_tx_uploads() {
+        let raw = TEST_CONTRACT_CREATION_TX;
+        let tx = FullTransaction::from_raw(raw.0).unwrap();
+        let (hash, unknown) = (*tx.hash(), TxHash::repeat_byte(1));
+
+        let uploads = TxUploads::new(TX_UPLOAD_TTL, 1);
+        let uploaded = uploads.upload(vec![tx.clone()]).unwrap();
+        assert_eq!(uploaded, vec![UploadedTx { hash, expires_in: Some(TX_UPLOAD_TTL.as_secs()) }]);
+
+        // Uploading the same transaction again only extends its TTL
+        assert!(uploads.upload(vec![tx.clone()]).is_ok());
+
+        assert_eq!(uploads.resolve(&[hash]).unwrap(), vec![tx]);
+        assert!(uploads.resolve(&[hash, unknown]).is_err());
+
+        let lookup = uploads.lookup(&[hash, unknown]);
+        assert!(lookup[0].expires_in.is_some());
+        assert_eq!(lookup[1].expires_in, None);
+    }
+}
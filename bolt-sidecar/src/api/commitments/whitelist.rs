@@ -0,0 +1,70 @@
+use std::{collections::HashSet, sync::Arc};
+
+use alloy::primitives::Address;
+use parking_lot::RwLock;
+
+use crate::state::ValidationError;
+
+use super::spec::Error;
+
+/// The signers allowed to request commitments from the sidecar, shared between the commitments
+/// API that enforces it and the driver that reloads it.
+///
+/// An empty whitelist accepts requests from any signer.
+#[derive(Debug, Clone, Default)]
+pub struct Whitelist {
+    signers: Arc<RwLock<HashSet<Address>>>,
+}
+
+impl Whitelist {
+    /// Creates a whitelist of the given signers.
+    pub fn new(signers: impl IntoIterator<Item = Address>) -> Self {
+        Self { signers: Arc::new(RwLock::new(signers.into_iter().collect())) }
+    }
+
+    /// Replaces the signers of the whitelist.
+    pub fn set(&self, signers: impl IntoIterator<Item = Address>) {
+        *self.signers.write() = signers.into_iter().collect();
+    }
+
+    /// Returns the signers of the whitelist, sorted.
+    pub fn signers(&self) -> Vec<Address> {
+        let mut signers = self.signers.read().iter().copied().collect::<Vec<_>>();
+        signers.sort();
+        signers
+    }
+
+    /// Checks that the recovered signer of a request is allowed to request commitments.
+    pub fn check(&self, signer: Address) -> Result<(), Error> {
+        let signers = self.signers.read();
+        if signers.is_empty() || signers.contains(&signer) {
+            return Ok(());
+        }
+
+        Err(Error::Validation(ValidationError::SignerNotWhitelisted(signer)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitelist() {
+        let (allowed, other) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let whitelist = Whitelist::default();
+        assert!(whitelist.check(other).is_ok());
+
+        // Reloads are seen by every clone of the whitelist
+        whitelist.clone().set([allowed]);
+        assert!(whitelist.check(allowed).is_ok());
+        assert!(matches!(
+            whitelist.check(other),
+            Err(Error::Validation(ValidationError::SignerNotWhitelisted(signer))) if signer == other
+        ));
+
+        whitelist.set([]);
+        assert!(whitelist.check(other).is_ok());
+    }
+}
@@ -1,3 +1,6 @@
+/// Admin API endpoints for operators
+pub mod admin;
+
 /// Builder API endpoints
 /// Reference: <https://ethereum.github.io/builder-specs/#/>
 pub mod builder;
@@ -5,5 +8,11 @@ pub mod builder;
 /// Sidecar API spec and error handling
 pub mod spec;
 
+/// Stable error codes shared by the bolt components
+pub mod codes;
+
+/// Incremental parsing of large JSON request bodies
+pub mod streaming;
+
 /// Commitments-API spec and errors.
 pub mod commitments;
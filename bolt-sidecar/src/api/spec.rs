@@ -1,20 +1,20 @@
 use axum::{
-    http::StatusCode,
+    http::{HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use beacon_api_client::VersionedValue;
-use ethereum_consensus::{
-    builder::SignedValidatorRegistration, deneb::mainnet::SignedBlindedBeaconBlock,
-};
+use ethereum_consensus::builder::SignedValidatorRegistration;
 use serde::{Deserialize, Serialize, Serializer};
 
 use crate::primitives::{
     BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
-    SignedRevocation,
+    SignedRevocation, VersionedSignedBlindedBlock, VersionedSignedBuilderBid,
 };
 
-use super::builder::GetHeaderParams;
+use super::{
+    builder::GetHeaderParams,
+    codes::{ErrorCode, ERROR_CODE_HEADER},
+};
 
 /// The path to the builder API status endpoint.
 pub const STATUS_PATH: &str = "/eth/v1/builder/status";
@@ -22,14 +22,25 @@ pub const STATUS_PATH: &str = "/eth/v1/builder/status";
 pub const REGISTER_VALIDATORS_PATH: &str = "/eth/v1/builder/validators";
 /// The path to the builder API get header endpoint.
 pub const GET_HEADER_PATH: &str = "/eth/v1/builder/header/:slot/:parent_hash/:pubkey";
+/// The path to the constraints API get header with proofs endpoint.
+pub const GET_HEADER_WITH_PROOFS_PATH: &str =
+    "/eth/v1/builder/header_with_proofs/:slot/:parent_hash/:pubkey";
 /// The path to the builder API get payload endpoint.
 pub const GET_PAYLOAD_PATH: &str = "/eth/v1/builder/blinded_blocks";
+/// The header carrying the fork version of the signed blinded blocks and payloads.
+pub const CONSENSUS_VERSION_HEADER: &str = "Eth-Consensus-Version";
+/// The content type of JSON request and response bodies.
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+/// The content type of SSZ request and response bodies.
+pub const SSZ_CONTENT_TYPE: &str = "application/octet-stream";
 /// The path to the constraints API submit constraints endpoint.
 pub const SUBMIT_CONSTRAINTS_PATH: &str = "/constraints/v1/builder/constraints";
 /// The path to the constraints API delegate endpoint.
 pub const DELEGATE_PATH: &str = "/constraints/v1/builder/delegate";
 /// The path to the constraints API revoke endpoint.
 pub const REVOKE_PATH: &str = "/constraints/v1/builder/revoke";
+/// The path to the constraints API capabilities endpoint.
+pub const CAPABILITIES_PATH: &str = "/constraints/v1/capabilities";
 
 /// A response object for errors.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +50,24 @@ pub struct ErrorResponse {
     message: String,
 }
 
+impl ErrorResponse {
+    /// Create a new error response with the given status code and message.
+    pub fn new(code: StatusCode, message: impl Into<String>) -> Self {
+        Self { code: code.as_u16(), message: message.into() }
+    }
+
+    /// Returns the error code of a relay error response, from its status code.
+    pub fn error_code(&self) -> ErrorCode {
+        match self.code {
+            401 | 403 => ErrorCode::Unauthorized,
+            413 => ErrorCode::PayloadTooLarge,
+            429 => ErrorCode::RateLimited,
+            500..=599 => ErrorCode::UpstreamUnavailable,
+            _ => ErrorCode::Rejected,
+        }
+    }
+}
+
 /// Helper to serialize a status code as a string using the provided serializer.
 pub fn serialize_status_code<S>(value: &u16, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -63,27 +92,67 @@ pub enum BuilderApiError {
     FailedDelegating(ErrorResponse),
     #[error("Failed to revoke constraint submission rights: {0:?}")]
     FailedRevoking(ErrorResponse),
+    #[error("Failed getting capabilities: {0:?}")]
+    FailedGettingCapabilities(ErrorResponse),
     #[error("Failed to fetch local payload for slot {0}")]
     FailedToFetchLocalPayload(u64),
     #[error("Axum error: {0:?}")]
     AxumError(#[from] axum::Error),
     #[error("Json error: {0:?}")]
     JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    JsonStream(#[from] super::streaming::JsonStreamError),
+    #[error("SSZ error: {0}")]
+    SszError(#[from] crate::primitives::SszError),
     #[error("Reqwest error: {0:?}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("API request timed out : {0:?}")]
     Timeout(#[from] tokio::time::error::Elapsed),
     #[error("Invalid fork: {0}")]
     InvalidFork(String),
+    #[error("Unexpected fee recipient: {0}")]
+    FeeRecipientMismatch(alloy::primitives::Address),
+    #[error("Rate-limited by the relay, retry after {0:?}")]
+    RateLimited(std::time::Duration),
     #[error("Locally-built payload does not match expected signed header")]
     LocalPayloadIntegrity(#[from] super::builder::LocalPayloadIntegrityError),
     #[error("Generic error: {0}")]
     Generic(String),
 }
 
+impl BuilderApiError {
+    /// Returns the stable error code of the error, shared with the other bolt components.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            BuilderApiError::FailedRegisteringValidators(error) |
+            BuilderApiError::FailedGettingHeader(error) |
+            BuilderApiError::FailedGettingPayload(error) |
+            BuilderApiError::FailedSubmittingConstraints(error) |
+            BuilderApiError::FailedDelegating(error) |
+            BuilderApiError::FailedRevoking(error) |
+            BuilderApiError::FailedGettingCapabilities(error) => error.error_code(),
+            BuilderApiError::AxumError(_) |
+            BuilderApiError::JsonError(_) |
+            BuilderApiError::SszError(_) => ErrorCode::InvalidRequest,
+            BuilderApiError::JsonStream(err) if err.is_too_large() => ErrorCode::PayloadTooLarge,
+            BuilderApiError::JsonStream(_) => ErrorCode::InvalidRequest,
+            BuilderApiError::ReqwestError(_) => ErrorCode::UpstreamUnavailable,
+            BuilderApiError::Timeout(_) => ErrorCode::Timeout,
+            BuilderApiError::RateLimited(_) => ErrorCode::RateLimited,
+            BuilderApiError::InvalidFork(_) |
+            BuilderApiError::FeeRecipientMismatch(_) |
+            BuilderApiError::LocalPayloadIntegrity(_) => ErrorCode::ValidationFailed,
+            BuilderApiError::FailedToFetchLocalPayload(_) | BuilderApiError::Generic(_) => {
+                ErrorCode::Internal
+            }
+        }
+    }
+}
+
 impl IntoResponse for BuilderApiError {
     fn into_response(self) -> Response {
-        match self {
+        let code = self.error_code();
+        let mut response = match self {
             BuilderApiError::FailedRegisteringValidators(error) => {
                 (StatusCode::from_u16(error.code).unwrap(), Json(error)).into_response()
             }
@@ -102,12 +171,24 @@ impl IntoResponse for BuilderApiError {
             BuilderApiError::FailedRevoking(error) => {
                 (StatusCode::from_u16(error.code).unwrap(), Json(error)).into_response()
             }
+            BuilderApiError::FailedGettingCapabilities(error) => {
+                (StatusCode::from_u16(error.code).unwrap(), Json(error)).into_response()
+            }
             BuilderApiError::AxumError(err) => {
                 (StatusCode::BAD_REQUEST, err.to_string()).into_response()
             }
             BuilderApiError::JsonError(err) => {
                 (StatusCode::BAD_REQUEST, err.to_string()).into_response()
             }
+            BuilderApiError::SszError(err) => {
+                (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+            }
+            BuilderApiError::JsonStream(err) if err.is_too_large() => {
+                (StatusCode::PAYLOAD_TOO_LARGE, err.to_string()).into_response()
+            }
+            BuilderApiError::JsonStream(err) => {
+                (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+            }
             BuilderApiError::FailedToFetchLocalPayload(_) => {
                 (StatusCode::NO_CONTENT, self.to_string()).into_response()
             }
@@ -124,13 +205,22 @@ impl IntoResponse for BuilderApiError {
             BuilderApiError::InvalidFork(err) => {
                 (StatusCode::BAD_REQUEST, Json(err)).into_response()
             }
+            BuilderApiError::FeeRecipientMismatch(_) => {
+                (StatusCode::BAD_GATEWAY, self.to_string()).into_response()
+            }
+            BuilderApiError::RateLimited(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, self.to_string()).into_response()
+            }
             BuilderApiError::LocalPayloadIntegrity(err) => {
                 (StatusCode::BAD_REQUEST, err.to_string()).into_response()
             }
             BuilderApiError::Generic(err) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
             }
-        }
+        };
+
+        response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(code.as_str()));
+        response
     }
 }
 
@@ -152,7 +242,7 @@ pub trait BuilderApi {
     /// Implements: <https://ethereum.github.io/builder-specs/#/Builder/submitBlindedBlock>
     async fn get_payload(
         &self,
-        signed_block: SignedBlindedBeaconBlock,
+        signed_block: VersionedSignedBlindedBlock,
     ) -> Result<GetPayloadResponse, BuilderApiError>;
 }
 
@@ -169,7 +259,7 @@ pub trait ConstraintsApi: BuilderApi {
     async fn get_header_with_proofs(
         &self,
         params: GetHeaderParams,
-    ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError>;
+    ) -> Result<VersionedSignedBuilderBid, BuilderApiError>;
 
     /// Implements: <https://chainbound.github.io/bolt-docs/api/builder#delegate>
     async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError>;
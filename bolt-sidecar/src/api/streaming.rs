@@ -0,0 +1,196 @@
+use std::{cell::Cell, fmt, io, marker::PhantomData};
+
+use axum::body::{Body, Bytes};
+use futures::StreamExt;
+use serde::{
+    de::{DeserializeOwned, Error as _, IgnoredAny, SeqAccess, Visitor},
+    Deserializer as _,
+};
+use tokio::sync::mpsc;
+
+/// The number of body chunks buffered between the body stream and the parser.
+const CHUNK_BUFFER: usize = 16;
+
+/// The size caps of a JSON array body.
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayLimits {
+    /// The maximum length of the body, in bytes.
+    pub max_length: usize,
+    /// The maximum number of items of the array.
+    pub max_items: usize,
+}
+
+/// Errors that can occur while parsing a JSON array body.
+#[derive(Debug, thiserror::Error)]
+pub enum JsonStreamError {
+    #[error("Request body is larger than {0} bytes")]
+    TooLarge(usize),
+    #[error("Request body has more than {0} items")]
+    TooManyItems(usize),
+    #[error("Failed to read request body: {0}")]
+    Body(#[from] axum::Error),
+    #[error("Invalid JSON array: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("JSON parser task failed: {0}")]
+    Parser(#[from] tokio::task::JoinError),
+}
+
+impl JsonStreamError {
+    /// Returns true if the body exceeds the size caps.
+    pub fn is_too_large(&self) -> bool {
+        matches!(self, Self::TooLarge(_) | Self::TooManyItems(_))
+    }
+}
+
+/// Parse a JSON array body incrementally, as its chunks arrive.
+///
+/// The items are deserialized one by one from the body stream, so that neither the whole
+/// body nor an intermediate representation of it is ever buffered in memory. Bodies
+/// exceeding the given limits are rejected as soon as the limit is crossed.
+pub async fn parse_json_array<T>(body: Body, limits: ArrayLimits) -> Result<Vec<T>, JsonStreamError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (chunks_tx, chunks_rx) = mpsc::channel(CHUNK_BUFFER);
+
+    // The parser reads from a blocking reader, so it runs on the blocking thread pool
+    let parser = tokio::task::spawn_blocking(move || {
+        let exceeded = Cell::new(false);
+        let mut de = serde_json::Deserializer::from_reader(ChunkReader::new(chunks_rx));
+
+        let visitor = CappedSeqVisitor::new(limits.max_items, &exceeded);
+        match de.deserialize_seq(visitor).and_then(|items| de.end().map(|_| items)) {
+            Ok(items) => Ok(items),
+            Err(_) if exceeded.get() => Err(JsonStreamError::TooManyItems(limits.max_items)),
+            Err(err) => Err(err.into()),
+        }
+    });
+
+    let mut stream = body.into_data_stream();
+    let mut length = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+
+        length += chunk.len();
+        if length > limits.max_length {
+            // Dropping the sender ends the body for the parser, which then exits
+            return Err(JsonStreamError::TooLarge(limits.max_length));
+        }
+
+        // The parser only stops early on errors, which are returned below
+        if chunks_tx.send(chunk).await.is_err() {
+            break;
+        }
+    }
+    drop(chunks_tx);
+
+    parser.await?
+}
+
+/// A blocking reader over the chunks of a body received from a channel.
+struct ChunkReader {
+    chunks: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl ChunkReader {
+    fn new(chunks: mpsc::Receiver<Bytes>) -> Self {
+        Self { chunks, current: Bytes::new() }
+    }
+}
+
+impl io::Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current.split_to(len));
+        Ok(len)
+    }
+}
+
+/// A visitor of a JSON array that fails once it has more than `max_items` items,
+/// without deserializing the extra items.
+struct CappedSeqVisitor<'a, T> {
+    max_items: usize,
+    exceeded: &'a Cell<bool>,
+    _item: PhantomData<T>,
+}
+
+impl<'a, T> CappedSeqVisitor<'a, T> {
+    fn new(max_items: usize, exceeded: &'a Cell<bool>) -> Self {
+        Self { max_items, exceeded, _item: PhantomData }
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for CappedSeqVisitor<'_, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an array of at most {} items", self.max_items)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(self.max_items));
+
+        while items.len() < self.max_items {
+            match seq.next_element()? {
+                Some(item) => items.push(item),
+                None => return Ok(items),
+            }
+        }
+
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            self.exceeded.set(true);
+            return Err(A::Error::custom(format!("more than {} items", self.max_items)));
+        }
+
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn chunked_body(json: &str, chunk_size: usize) -> Body {
+        let chunks = json
+            .as_bytes()
+            .chunks(chunk_size)
+            .map(|chunk| Ok::<_, io::Error>(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+        Body::from_stream(stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn test_parse_json_array() {
+        let limits = ArrayLimits { max_length: 64, max_items: 4 };
+
+        let items: Vec<u64> =
+            parse_json_array(chunked_body("[1, 2, 3, 4]", 3), limits).await.unwrap();
+        assert_eq!(items, vec![1, 2, 3, 4]);
+
+        let empty: Vec<u64> = parse_json_array(chunked_body("[]", 1), limits).await.unwrap();
+        assert!(empty.is_empty());
+
+        let res = parse_json_array::<u64>(chunked_body("[1, 2, 3, 4, 5]", 3), limits).await;
+        assert!(matches!(res, Err(JsonStreamError::TooManyItems(4))));
+
+        let long = format!("[{}]", "1".repeat(100));
+        let res = parse_json_array::<u64>(chunked_body(&long, 8), limits).await;
+        assert!(matches!(res, Err(JsonStreamError::TooLarge(64))));
+
+        let res = parse_json_array::<u64>(chunked_body("[1, 2", 2), limits).await;
+        assert!(matches!(res, Err(JsonStreamError::Json(_))));
+
+        let res = parse_json_array::<u64>(chunked_body("[1] [2]", 2), limits).await;
+        assert!(matches!(res, Err(JsonStreamError::Json(_))));
+    }
+}
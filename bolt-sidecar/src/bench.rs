@@ -0,0 +1,277 @@
+use std::time::{Duration, Instant};
+
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{Address, Signature, TxKind, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builder::BlockTemplate,
+    crypto::bls::BLSSig,
+    primitives::{
+        proofs::{
+            compute_multiproof, transaction_leaf, transactions_root, verify_multiproof, ProofError,
+        },
+        BlsPublicKey, ConstraintsMessage, FullTransaction, InclusionRequest, SignedConstraints,
+    },
+};
+
+/// The chain ID the synthetic requests are signed for.
+const CHAIN_ID: u64 = 1;
+
+/// The slot the synthetic requests target.
+const SLOT: u64 = 10;
+
+/// The maximum number of transactions of a workload, bounded by the size of a multiproof.
+pub const MAX_WORKLOAD_TRANSACTIONS: usize = 300;
+
+/// The maximum committed gas of the synthetic block template.
+const MAX_COMMITTED_GAS: u64 = 30_000_000;
+
+/// A synthetic load for the hot paths of the sidecar: signed inclusion requests from
+/// distinct senders, and the payload transactions tree they are proven against.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    /// The signed inclusion requests, with their senders recovered.
+    requests: Vec<(InclusionRequest, Signature, Address)>,
+    /// The leaves of the transactions tree of the payload including every request.
+    leaves: Vec<B256>,
+    /// The root of the transactions tree.
+    root: B256,
+    /// The multiproof of every transaction against the root.
+    proof: Vec<B256>,
+    /// The generalized indices of the proven leaves.
+    indices: Vec<usize>,
+}
+
+impl Workload {
+    /// Generates `requests` inclusion requests of `txs_per_request` transactions each, up to
+    /// [MAX_WORKLOAD_TRANSACTIONS] transactions in total.
+    pub fn generate(requests: usize, txs_per_request: usize) -> eyre::Result<Self> {
+        if requests * txs_per_request > MAX_WORKLOAD_TRANSACTIONS {
+            eyre::bail!("A workload can't exceed {MAX_WORKLOAD_TRANSACTIONS} transactions");
+        }
+
+        let mut signed = Vec::with_capacity(requests);
+        for _ in 0..requests {
+            let signer = PrivateKeySigner::random();
+            let txs = (0..txs_per_request as u64)
+                .map(|nonce| synthetic_transaction(&signer, nonce))
+                .collect::<eyre::Result<Vec<_>>>()?;
+
+            let mut request = InclusionRequest {
+                slot: SLOT,
+                max_slot: None,
+                top: false,
+                partial: false,
+                batch: false,
+                txs,
+                signature: None,
+                signer: None,
+                voucher: None,
+            };
+            request.recover_signers()?;
+
+            let signature = signer.sign_hash_sync(&request.signing_digest(CHAIN_ID))?;
+            request.set_signature(signature);
+            request.set_signer(signer.address());
+            signed.push((request, signature, signer.address()));
+        }
+
+        let leaves = signed
+            .iter()
+            .flat_map(|(request, _, _)| request.txs.iter())
+            .map(transaction_leaf)
+            .collect::<Result<Vec<_>, ProofError>>()?;
+        let root = transactions_root(&leaves);
+
+        let hashes = signed
+            .iter()
+            .flat_map(|(request, _, _)| request.txs.iter())
+            .map(|tx| *tx.hash())
+            .collect::<Vec<_>>();
+        let positions = (0..leaves.len()).collect::<Vec<_>>();
+        let multiproof = compute_multiproof(&leaves, &hashes, &positions)?;
+        let proof = multiproof.merkle_hashes.iter().map(|h| B256::from_slice(h.as_ref())).collect();
+        let indices = multiproof.generalized_indexes.iter().map(|i| *i as usize).collect();
+
+        Ok(Self { requests: signed, leaves, root, proof, indices })
+    }
+
+    /// Verifies the signature of every request, as done on receipt of a commitment request.
+    pub fn verify_commitments(&self) -> bool {
+        self.requests.iter().all(|(request, signature, signer)| {
+            request.verify_signature(signature, *signer, CHAIN_ID).is_ok()
+        })
+    }
+
+    /// Inserts the constraints of every request into a fresh block template.
+    pub fn insert_constraints(&self) -> BlockTemplate {
+        let mut template = BlockTemplate::default();
+        for (request, _, _) in &self.requests {
+            let message = ConstraintsMessage::build(BlsPublicKey::default(), request.clone());
+            template.add_constraints(SignedConstraints { message, signature: BLSSig::default() });
+        }
+        template
+    }
+
+    /// Reads the accounting of the template as done while validating a request: the state
+    /// diff of every sender, the committed gas and blobs, and a snapshot of the template.
+    pub fn account_template(&self, template: &BlockTemplate) -> u64 {
+        let diffs =
+            self.requests.iter().filter_map(|(_, _, sender)| template.get_diff(sender)).count();
+
+        let snapshot = template.snapshot(SLOT, MAX_COMMITTED_GAS);
+        template.committed_gas() + template.blob_count() as u64 + diffs as u64 + snapshot.slot
+    }
+
+    /// Verifies the multiproof of every transaction against the transactions root, as done
+    /// on the headers returned by relays.
+    pub fn verify_proofs(&self) -> bool {
+        verify_multiproof(&self.leaves, &self.proof, &self.indices, self.root).unwrap_or(false)
+    }
+}
+
+/// Returns a signed EIP-1559 transfer from the signer with the given nonce.
+fn synthetic_transaction(signer: &PrivateKeySigner, nonce: u64) -> eyre::Result<FullTransaction> {
+    let mut tx = TxEip1559 {
+        chain_id: CHAIN_ID,
+        nonce,
+        gas_limit: 21_000,
+        max_fee_per_gas: 20_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(Address::with_last_byte(nonce as u8)),
+        value: U256::from(nonce),
+        ..Default::default()
+    };
+    let signature = signer.sign_transaction_sync(&mut tx)?;
+    let raw = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
+
+    FullTransaction::decode_enveloped(raw)
+}
+
+/// The timings of a hot path, run repeatedly for a time slice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// The name of the hot path.
+    pub name: String,
+    /// The number of iterations run within the time slice.
+    pub iterations: u64,
+    /// The mean duration of an iteration, in nanoseconds.
+    pub mean_ns: u64,
+    /// The median duration of an iteration, in nanoseconds.
+    pub p50_ns: u64,
+    /// The 99th percentile duration of an iteration, in nanoseconds.
+    pub p99_ns: u64,
+}
+
+/// A hot path whose median got slower than allowed compared to a baseline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Regression {
+    /// The name of the hot path.
+    pub name: String,
+    /// The median duration of the baseline, in nanoseconds.
+    pub baseline_p50_ns: u64,
+    /// The median duration of the current run, in nanoseconds.
+    pub p50_ns: u64,
+    /// The slowdown, in percent of the baseline.
+    pub slowdown_pct: f64,
+}
+
+/// Runs the function repeatedly for the given time slice, at least once, and reports the
+/// timings of its iterations.
+pub fn time_slice<T>(name: &str, slice: Duration, mut f: impl FnMut() -> T) -> BenchReport {
+    let mut samples = Vec::new();
+    let start = Instant::now();
+    while samples.is_empty() || start.elapsed() < slice {
+        let iteration = Instant::now();
+        std::hint::black_box(f());
+        samples.push(iteration.elapsed().as_nanos() as u64);
+    }
+
+    samples.sort_unstable();
+    let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+
+    BenchReport {
+        name: name.to_string(),
+        iterations: samples.len() as u64,
+        mean_ns: samples.iter().sum::<u64>() / samples.len() as u64,
+        p50_ns: percentile(50),
+        p99_ns: percentile(99),
+    }
+}
+
+/// Runs every hot path on the workload for the given time slice each.
+pub fn run_hot_paths(workload: &Workload, slice: Duration) -> Vec<BenchReport> {
+    let template = workload.insert_constraints();
+
+    vec![
+        time_slice("commitment_verification", slice, || workload.verify_commitments()),
+        time_slice("constraint_insertion", slice, || workload.insert_constraints()),
+        time_slice("template_accounting", slice, || workload.account_template(&template)),
+        time_slice("proof_verification", slice, || workload.verify_proofs()),
+    ]
+}
+
+/// Compares the reports to a baseline, returning the hot paths whose median is more than
+/// `max_slowdown_pct` percent slower than in the baseline. Hot paths missing from the baseline
+/// are not compared.
+pub fn regressions(
+    reports: &[BenchReport],
+    baseline: &[BenchReport],
+    max_slowdown_pct: f64,
+) -> Vec<Regression> {
+    reports
+        .iter()
+        .filter_map(|report| {
+            let base = baseline.iter().find(|base| base.name == report.name)?;
+            let slowdown_pct =
+                (report.p50_ns as f64 - base.p50_ns as f64) / base.p50_ns.max(1) as f64 * 100.0;
+
+            (slowdown_pct > max_slowdown_pct).then(|| Regression {
+                name: report.name.clone(),
+                baseline_p50_ns: base.p50_ns,
+                p50_ns: report.p50_ns,
+                slowdown_pct,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workload() {
+        let workload = Workload::generate(4, 3).unwrap();
+        assert!(workload.verify_commitments());
+        assert!(workload.verify_proofs());
+
+        let template = workload.insert_constraints();
+        assert_eq!(template.transactions_len(), 12);
+        assert!(workload.account_template(&template) > 0);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let report = |name: &str, p50_ns| BenchReport {
+            name: name.to_string(),
+            iterations: 1,
+            mean_ns: p50_ns,
+            p50_ns,
+            p99_ns: p50_ns,
+        };
+        let baseline = [report("a", 100), report("b", 100)];
+        let current = [report("a", 105), report("b", 150), report("c", 1000)];
+
+        let regressions = regressions(&current, &baseline, 10.0);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].name, "b");
+        assert_eq!(regressions[0].slowdown_pct, 50.0);
+    }
+}
@@ -1,9 +1,13 @@
 use alloy::{
-    eips::eip4895::Withdrawal,
+    consensus::TxEnvelope,
+    eips::{eip2718::Encodable2718, eip4895::Withdrawal},
     primitives::{Address, Bloom, B256, U256},
-    rpc::types::engine::{
-        ExecutionPayload as AlloyExecutionPayload, ExecutionPayloadV1, ExecutionPayloadV2,
-        ExecutionPayloadV3,
+    rpc::types::{
+        engine::{
+            ExecutionPayload as AlloyExecutionPayload, ExecutionPayloadV1, ExecutionPayloadV2,
+            ExecutionPayloadV3,
+        },
+        Transaction as AlloyTransaction,
     },
 };
 use ethereum_consensus::{
@@ -22,6 +26,8 @@ use ethereum_consensus::{
 };
 use reth_primitives::{SealedBlock, TransactionSigned, Withdrawals};
 
+use super::BuilderError;
+
 /// Compatibility: convert a sealed header into an ethereum-consensus execution payload header.
 /// This requires recalculating the withdrals and transactions roots as SSZ instead of MPT roots.
 pub(crate) fn to_execution_payload_header(
@@ -81,17 +87,7 @@ pub(crate) fn to_alloy_execution_payload(
     let alloy_withdrawals = block
         .withdrawals
         .as_ref()
-        .map(|withdrawals| {
-            withdrawals
-                .iter()
-                .map(|w| Withdrawal {
-                    index: w.index,
-                    validator_index: w.validator_index,
-                    address: w.address,
-                    amount: w.amount,
-                })
-                .collect::<Vec<_>>()
-        })
+        .map(|withdrawals| withdrawals.iter().map(to_alloy_withdrawal).collect::<Vec<_>>())
         .unwrap_or_default();
 
     AlloyExecutionPayload::V3(ExecutionPayloadV3 {
@@ -175,6 +171,29 @@ pub(crate) fn to_reth_withdrawal(
     }
 }
 
+/// Compatibility: convert a withdrawal from Reth to Alloy
+pub(crate) fn to_alloy_withdrawal(value: &reth_primitives::Withdrawal) -> Withdrawal {
+    Withdrawal {
+        index: value.index,
+        validator_index: value.validator_index,
+        address: value.address,
+        amount: value.amount,
+    }
+}
+
+/// Compatibility: convert a transaction from an Alloy RPC response, e.g. of the mempool,
+/// to a Reth signed transaction
+pub(crate) fn to_reth_transaction(
+    value: AlloyTransaction,
+) -> Result<TransactionSigned, BuilderError> {
+    let hash = value.hash;
+    let envelope = TxEnvelope::try_from(value)
+        .map_err(|e| BuilderError::Custom(format!("Invalid transaction {hash}: {e}")))?;
+
+    TransactionSigned::decode_enveloped(&mut envelope.encoded_2718().as_slice())
+        .map_err(|e| BuilderError::Custom(format!("Failed to decode transaction {hash}: {e}")))
+}
+
 /// Compatibility: convert a withdrawal from Reth to ethereum-consensus
 pub(crate) fn to_consensus_withdrawal(
     value: &reth_primitives::Withdrawal,
@@ -4,12 +4,15 @@ use ethereum_consensus::{
     crypto::{KzgCommitment, PublicKey},
     deneb::mainnet::ExecutionPayloadHeader,
     ssz::prelude::{List, MerkleizationError},
+    Fork,
 };
 
 use crate::{
     common::BlsSecretKeyWrapper,
     primitives::{
-        BuilderBid, GetPayloadResponse, PayloadAndBid, PayloadAndBlobs, SignedBuilderBid,
+        BuilderBid, ElectraBuilderBid, ExecutionRequests, GetPayloadResponse, PayloadAndBid,
+        PayloadAndBlobs, SignedBuilderBid, SignedElectraBuilderBid, SszError,
+        VersionedSignedBuilderBid,
     },
     ChainConfig, Opts,
 };
@@ -17,16 +20,16 @@ use crate::{
 /// Basic block template handler that can keep track of
 /// the local commitments according to protocol validity rules.
 pub mod template;
-pub use template::BlockTemplate;
+pub use template::{BlockTemplate, TemplateReader, TemplateSnapshot};
 
 /// Builder payload signing utilities
 pub mod signature;
 use signature::sign_builder_message;
 
 /// Fallback Payload builder agent that leverages the engine API's
-/// `engine_newPayloadV3` response error to produce a valid payload.
+/// `engine_newPayloadV3` and `engine_newPayloadV4` response errors to produce a valid payload.
 pub mod payload_builder;
-use payload_builder::FallbackPayloadBuilder;
+use payload_builder::{FallbackPayload, FallbackPayloadBuilder};
 
 /// Interface for fetching payloads from the beacon node.
 pub mod payload_fetcher;
@@ -54,10 +57,14 @@ pub enum BuilderError {
     Transport(#[from] alloy::transports::TransportError),
     #[error("Failed in SSZ merkleization: {0}")]
     Merkleization(#[from] MerkleizationError),
+    #[error("Failed to encode execution requests: {0}")]
+    ExecutionRequests(#[from] SszError),
     #[error("Failed while interacting with beacon client: {0}")]
     BeaconApi(#[from] beacon_api_client::Error),
     #[error("Failed to parse hint from engine response: {0}")]
     InvalidEngineHint(String),
+    #[error("Local payloads are not supported in the {0} fork")]
+    UnsupportedFork(Fork),
     #[error("Failed to build payload: {0}")]
     Custom(String),
 }
@@ -73,7 +80,7 @@ pub struct LocalBuilder {
     /// (necessary for signing messages with the correct domain)
     chain: ChainConfig,
     /// Async fallback payload builder to generate valid payloads with
-    /// the engine API's `engine_newPayloadV3` response error.
+    /// the engine API's `engine_newPayloadV3` and `engine_newPayloadV4` response errors.
     fallback_builder: FallbackPayloadBuilder,
     /// The last payload and bid that was built by the local builder.
     payload_and_bid: Option<PayloadAndBid>,
@@ -97,13 +104,21 @@ impl LocalBuilder {
         slot: u64,
         template: &BlockTemplate,
     ) -> Result<(), BuilderError> {
+        // The bids and payloads must be of the fork of the slot, or the beacon node
+        // would reject them
+        let fork = self.chain.fork_at_slot(slot);
+        if !VersionedSignedBuilderBid::SUPPORTED_FORKS.contains(&fork) {
+            return Err(BuilderError::UnsupportedFork(fork));
+        }
+
         let transactions = template.as_signed_transactions();
         let blobs_bundle = template.as_blobs_bundle();
         let kzg_commitments = blobs_bundle.commitments.clone();
 
         // 1. build a fallback payload with the given transactions, on top of
-        // the current head of the chain
-        let block = self.fallback_builder.build_fallback_payload(slot, &transactions).await?;
+        // the current head of the chain. It may include mempool transactions after them.
+        let FallbackPayload { block, execution_requests } =
+            self.fallback_builder.build_fallback_payload(slot, &transactions, fork).await?;
 
         // NOTE: we use a big value for the bid to ensure it gets chosen by constraints client.
         // the client has no way to actually verify this, and we don't need to trust
@@ -117,13 +132,31 @@ impl LocalBuilder {
         let payload_and_blobs = PayloadAndBlobs { execution_payload: eth_payload, blobs_bundle };
 
         // 2. create a signed builder bid with the sealed block header we just created
-        let eth_header = compat::to_execution_payload_header(&block, transactions);
-
-        // 3. sign the bid with the local builder's BLS key
-        let signed_bid = self.create_signed_builder_bid(value, eth_header, kzg_commitments)?;
+        let eth_header = compat::to_execution_payload_header(&block, block.body.clone());
 
-        // 4. prepare a get_payload response for when the beacon node will ask for it
-        let get_payload_response = GetPayloadResponse::from(payload_and_blobs);
+        // 3. sign the bid with the local builder's BLS key, and prepare a get_payload
+        // response of the same fork for when the beacon node will ask for it
+        let (signed_bid, get_payload_response) = match execution_requests {
+            Some(execution_requests) => {
+                let bid = self.create_signed_electra_builder_bid(
+                    value,
+                    eth_header,
+                    kzg_commitments,
+                    execution_requests,
+                )?;
+                (
+                    VersionedSignedBuilderBid::Electra(bid),
+                    GetPayloadResponse::Electra(payload_and_blobs),
+                )
+            }
+            None => {
+                let bid = self.create_signed_builder_bid(value, eth_header, kzg_commitments)?;
+                (
+                    VersionedSignedBuilderBid::Deneb(bid),
+                    GetPayloadResponse::Deneb(payload_and_blobs),
+                )
+            }
+        };
 
         self.payload_and_bid =
             Some(PayloadAndBid { bid: signed_bid, payload: get_payload_response });
@@ -157,4 +190,146 @@ impl LocalBuilder {
 
         Ok(SignedBuilderBid { message, signature })
     }
+
+    /// transform a sealed header and the execution requests of its payload into a signed
+    /// Electra builder bid using the local builder's BLS key.
+    fn create_signed_electra_builder_bid(
+        &self,
+        value: U256,
+        header: ExecutionPayloadHeader,
+        blob_kzg_commitments: Vec<KzgCommitment>,
+        execution_requests: ExecutionRequests,
+    ) -> Result<SignedElectraBuilderBid, BuilderError> {
+        let pubkey = self.secret_key.sk_to_pk().to_bytes();
+        let consensus_pubkey = PublicKey::try_from(pubkey.as_slice()).expect("valid pubkey bytes");
+        let blob_kzg_commitments = List::try_from(blob_kzg_commitments).expect("valid list");
+
+        let message = ElectraBuilderBid {
+            header,
+            blob_kzg_commitments,
+            execution_requests,
+            value,
+            public_key: consensus_pubkey,
+        };
+
+        let signature = sign_builder_message(&self.chain, &self.secret_key, &message)?;
+
+        Ok(SignedElectraBuilderBid { message, signature })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, Bytes, B256},
+        rpc::types::Block,
+    };
+    use axum::{routing::post, Json, Router};
+    use clap::Parser;
+    use ethereum_consensus::phase0::mainnet::SLOTS_PER_EPOCH;
+    use reqwest::Url;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        common::{EcdsaSecretKeyWrapper, JwtSecretConfig},
+        devnet::MockBeacon,
+        primitives::versioned::{DepositRequest, WithdrawalRequest},
+        signer::local::LocalSigner,
+    };
+
+    /// Start a mock execution client serving its latest block, and a payload with the given
+    /// execution requests on the engine API. New payloads are only valid if they are sent
+    /// with `engine_newPayloadV4` and the expected requests.
+    async fn mock_execution_client(requests: Vec<Bytes>, expected: Vec<Bytes>) -> Url {
+        let mut latest = serde_json::to_value(Block::default()).unwrap();
+        latest["hash"] = json!(B256::random());
+        latest["number"] = json!("0x1");
+        latest["gasLimit"] = json!("0x1c9c380");
+        latest["baseFeePerGas"] = json!("0x3b9aca00");
+
+        let rpc = move |Json(request): Json<Value>| {
+            let (latest, requests, expected) = (latest.clone(), requests.clone(), expected.clone());
+            async move {
+                let params = &request["params"];
+                let result = match request["method"].as_str() {
+                    Some("eth_getBlockByNumber") => latest,
+                    Some("engine_forkchoiceUpdatedV3") => json!({
+                        "payloadStatus": { "status": "VALID", "latestValidHash": null },
+                        "payloadId": "0x0000000000000001"
+                    }),
+                    Some("engine_getPayloadV4") => json!({ "executionRequests": requests }),
+                    Some("engine_newPayloadV4") if params[3] == json!(expected) => {
+                        json!({ "status": "VALID", "latestValidHash": params[0]["blockHash"] })
+                    }
+                    _ => {
+                        let error = json!({ "code": -32000, "message": "unexpected request" });
+                        return Json(
+                            json!({ "jsonrpc": "2.0", "id": request["id"], "error": error }),
+                        );
+                    }
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+            }
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/", post(rpc));
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        format!("http://{addr}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_electra_fallback_payload() -> eyre::Result<()> {
+        let mut requests = ExecutionRequests::default();
+        requests.deposits.push(DepositRequest::default());
+        requests.withdrawals.push(WithdrawalRequest { amount: 5, ..Default::default() });
+
+        // The deposits of the payload of the execution client come from its own transactions
+        let expected = requests.clone().without_deposits();
+        let engine_requests = |requests: &ExecutionRequests| {
+            requests.to_engine().unwrap().into_iter().map(Bytes::from).collect::<Vec<_>>()
+        };
+        let execution_client =
+            mock_execution_client(engine_requests(&requests), engine_requests(&expected)).await;
+        let beacon = MockBeacon::start(0, 12, LocalSigner::random().pubkey(), 0).await?;
+
+        let opts = Opts::try_parse_from([
+            "bolt-sidecar".to_string(),
+            format!("--execution-api-url={execution_client}"),
+            format!("--engine-api-url={execution_client}"),
+            format!("--beacon-api-url={}", beacon.url()),
+            "--validator-indexes=0".to_string(),
+            format!("--engine-jwt-hex={}", JwtSecretConfig::default()),
+            format!("--fee-recipient={}", Address::ZERO),
+            format!("--builder-private-key={}", BlsSecretKeyWrapper::random()),
+            format!("--commitment-private-key={}", EcdsaSecretKeyWrapper::random()),
+            format!("--constraint-private-key={}", BlsSecretKeyWrapper::random()),
+            "--chain=kurtosis".to_string(),
+            "--electra-fork-epoch=2".to_string(),
+        ])?;
+        let slot = 2 * SLOTS_PER_EPOCH;
+        assert_eq!(opts.chain.fork_at_slot(slot), Fork::Electra);
+
+        let beacon_client = BeaconClient::new(beacon.url());
+        let mut builder = LocalBuilder::new(&opts, beacon_client, beacon.genesis_time());
+        builder.build_new_local_payload(slot, &BlockTemplate::default()).await?;
+
+        let PayloadAndBid { bid, payload } = builder.get_cached_payload().expect("local payload");
+        let VersionedSignedBuilderBid::Electra(bid) = bid else {
+            panic!("expected an Electra bid, got {:?}", bid.fork());
+        };
+        assert_eq!(bid.message.execution_requests, expected);
+        assert_eq!(
+            bid.signature,
+            sign_builder_message(&opts.chain, &opts.builder_private_key, &bid.message)?
+        );
+
+        assert!(matches!(payload, GetPayloadResponse::Electra(_)));
+        assert_eq!(payload.block_hash(), &bid.message.header.block_hash);
+
+        Ok(())
+    }
 }
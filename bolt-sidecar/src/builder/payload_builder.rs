@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{collections::HashSet, fmt};
 
 use alloy::{
     eips::{calc_excess_blob_gas, calc_next_block_base_fee, eip1559::BaseFeeParams},
@@ -6,6 +6,7 @@ use alloy::{
     rpc::types::{engine::ExecutionPayload as AlloyExecutionPayload, Block},
 };
 use beacon_api_client::{BlockId, StateId};
+use ethereum_consensus::Fork;
 use hex::FromHex;
 use regex::Regex;
 use reqwest::Url;
@@ -14,14 +15,19 @@ use reth_primitives::{
     Withdrawal, Withdrawals, EMPTY_OMMER_ROOT_HASH,
 };
 use reth_rpc_layer::{secret_to_bearer_header, JwtSecret};
-use serde_json::Value;
-use tracing::trace;
+use serde_json::{json, Value};
+use tracing::{debug, trace, warn};
 
 use super::{
-    compat::{to_alloy_execution_payload, to_reth_withdrawal},
+    compat::{
+        to_alloy_execution_payload, to_alloy_withdrawal, to_reth_transaction, to_reth_withdrawal,
+    },
     BuilderError,
 };
-use crate::{BeaconClient, Opts, RpcClient};
+use crate::{
+    config::FallbackOpts, primitives::ExecutionRequests, state::mempool::select_fill_transactions,
+    BeaconClient, Opts, RpcClient,
+};
 
 /// Extra-data payload field used for locally built blocks, decoded in UTF-8.
 ///
@@ -50,6 +56,7 @@ pub struct FallbackPayloadBuilder {
     engine_hinter: EngineHinter,
     slot_time: u64,
     genesis_time: u64,
+    opts: FallbackOpts,
 }
 
 impl FallbackPayloadBuilder {
@@ -69,6 +76,7 @@ impl FallbackPayloadBuilder {
             slot_time: config.chain.slot_time(),
             genesis_time,
             beacon_api_client,
+            opts: config.fallback,
         }
     }
 }
@@ -77,7 +85,7 @@ impl FallbackPayloadBuilder {
 /// building a sealed block. Some of this data is fetched from the
 /// beacon chain, while others are calculated locally or from the
 /// transactions themselves.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 struct Context {
     extra_data: Bytes,
     base_fee: u64,
@@ -89,6 +97,19 @@ struct Context {
     withdrawals_root: B256,
     parent_beacon_block_root: B256,
     block_timestamp: u64,
+    /// The execution requests of the block as passed to `engine_newPayloadV4`, and their
+    /// hash in the header, from the Electra fork on.
+    execution_requests: Option<Vec<Bytes>>,
+    requests_hash: Option<B256>,
+}
+
+/// A payload built by the fallback payload builder.
+#[derive(Debug)]
+pub struct FallbackPayload {
+    /// The sealed block of the payload.
+    pub block: SealedBlock,
+    /// The execution requests of the payload, from the Electra fork on.
+    pub execution_requests: Option<ExecutionRequests>,
 }
 
 #[derive(Debug, Default)]
@@ -101,13 +122,21 @@ struct Hints {
 }
 
 impl FallbackPayloadBuilder {
-    /// Build a minimal payload to be used as a fallback in case PBS relays fail
-    /// to provide a valid payload that fulfills the commitments made by Bolt.
+    /// Build a payload to be used as a fallback in case PBS relays fail to provide
+    /// a valid payload that fulfills the commitments made by Bolt.
+    ///
+    /// If enabled, the remaining gas after the committed transactions is filled with
+    /// pending transactions of the public mempool. Should the block with them fail to
+    /// build, the minimal block with the committed transactions only is built instead.
+    ///
+    /// From the Electra fork on, the payload also carries the execution requests of the
+    /// block, and is checked with `engine_newPayloadV4` instead of `engine_newPayloadV3`.
     pub async fn build_fallback_payload(
         &self,
         target_slot: u64,
         transactions: &[TransactionSigned],
-    ) -> Result<SealedBlock, BuilderError> {
+        fork: Fork,
+    ) -> Result<FallbackPayload, BuilderError> {
         // We fetch the latest block to get the necessary parent values for the new block.
         // For the timestamp, we must use the one expected by the beacon chain instead, to
         // prevent edge cases where the proposer before us has missed their slot.
@@ -124,13 +153,6 @@ impl FallbackPayloadBuilder {
             self.beacon_api_client.get_beacon_block_root(BlockId::Head).await?;
         trace!(parent = ?parent_beacon_block_root, "got parent_beacon_block_root");
 
-        let versioned_hashes = transactions
-            .iter()
-            .flat_map(|tx| tx.blob_versioned_hashes())
-            .flatten()
-            .collect::<Vec<_>>();
-        trace!(amount = ?versioned_hashes.len(), "got versioned_hashes");
-
         let base_fee = calc_next_block_base_fee(
             latest_block.header.gas_used,
             latest_block.header.gas_limit,
@@ -143,26 +165,136 @@ impl FallbackPayloadBuilder {
             latest_block.header.blob_gas_used.unwrap_or_default(),
         ) as u64;
 
-        let blob_gas_used =
-            transactions.iter().fold(0, |acc, tx| acc + tx.blob_gas_used().unwrap_or_default());
-
         // We must calculate the next block timestamp manually rather than rely on the
         // previous execution block, to cover the edge case where any previous slots have
         // been missed by the proposers immediately before us.
         let block_timestamp = self.genesis_time + (target_slot * self.slot_time);
 
-        let ctx = Context {
+        let mut ctx = Context {
             base_fee,
-            blob_gas_used,
             excess_blob_gas,
             parent_beacon_block_root,
             prev_randao,
             extra_data: self.extra_data.clone(),
             fee_recipient: self.fee_recipient,
-            transactions_root: proofs::calculate_transaction_root(transactions),
             withdrawals_root: proofs::calculate_withdrawals_root(&withdrawals),
             block_timestamp,
+            ..Default::default()
+        };
+
+        let execution_requests = if fork == Fork::Electra {
+            let requests = self.fetch_execution_requests(&latest_block, &ctx, &withdrawals).await?;
+            trace!(?requests, "got execution requests");
+
+            ctx.execution_requests =
+                Some(requests.to_engine()?.into_iter().map(Bytes::from).collect());
+            ctx.requests_hash = Some(requests.requests_hash()?);
+            Some(requests)
+        } else {
+            None
+        };
+
+        if self.opts.fallback_mempool_fill {
+            match self.fetch_fill_transactions(&latest_block, base_fee, transactions).await {
+                Ok(fill) if fill.is_empty() => debug!("No mempool transactions to fill with"),
+                Ok(fill) => {
+                    let filled = [transactions, &fill].concat();
+                    match self.seal_block(&latest_block, &ctx, withdrawals.clone(), &filled).await {
+                        Ok(block) => return Ok(FallbackPayload { block, execution_requests }),
+                        Err(err) => warn!(
+                            ?err,
+                            filled = fill.len(),
+                            "Failed to build with mempool transactions, keeping committed ones only"
+                        ),
+                    }
+                }
+                Err(err) => warn!(?err, "Failed to fetch mempool transactions to fill with"),
+            }
+        }
+
+        let block = self.seal_block(&latest_block, &ctx, withdrawals, transactions).await?;
+        Ok(FallbackPayload { block, execution_requests })
+    }
+
+    /// Fetch the execution requests of the next block from a payload built by the execution
+    /// client with the same attributes, on top of the latest block. The withdrawal and
+    /// consolidation requests of the system contracts are the same in the fallback block,
+    /// but the deposits of the payload come from its own transactions, so they are dropped.
+    ///
+    /// NOTE: committed transactions that create execution requests themselves, e.g. deposits,
+    /// make the requests hash of the block invalid, and the fallback block fails to build.
+    async fn fetch_execution_requests(
+        &self,
+        latest_block: &Block,
+        ctx: &Context,
+        withdrawals: &[Withdrawal],
+    ) -> Result<ExecutionRequests, BuilderError> {
+        let attributes = json!({
+            "timestamp": format!("{:#x}", ctx.block_timestamp),
+            "prevRandao": ctx.prev_randao,
+            "suggestedFeeRecipient": ctx.fee_recipient,
+            "withdrawals": withdrawals.iter().map(to_alloy_withdrawal).collect::<Vec<_>>(),
+            "parentBeaconBlockRoot": ctx.parent_beacon_block_root,
+        });
+        let head = latest_block.header.hash.unwrap_or_default();
+
+        let requests = self.engine_hinter.fetch_execution_requests(head, attributes).await?;
+        Ok(ExecutionRequests::from_engine(&requests)?.without_deposits())
+    }
+
+    /// Fetch the pending transactions of the mempool to fill the block with, after the
+    /// committed transactions.
+    async fn fetch_fill_transactions(
+        &self,
+        latest_block: &Block,
+        base_fee: u64,
+        committed: &[TransactionSigned],
+    ) -> Result<Vec<TransactionSigned>, BuilderError> {
+        let pending = self.execution_rpc_client.get_txpool_pending().await?;
+        trace!(amount = pending.len(), "got pending mempool transactions");
+
+        let committed_senders =
+            committed.iter().filter_map(|tx| tx.recover_signer()).collect::<HashSet<_>>();
+        let committed_gas = committed.iter().map(|tx| tx.gas_limit()).sum::<u64>();
+        let gas_available = (latest_block.header.gas_limit as u64).saturating_sub(committed_gas);
+
+        select_fill_transactions(
+            pending,
+            &committed_senders,
+            base_fee as u128,
+            gas_available,
+            self.opts.fallback_max_mempool_txs,
+        )
+        .into_iter()
+        .map(to_reth_transaction)
+        .collect()
+    }
+
+    /// Seal a block with the given transactions on top of the latest block, fetching the
+    /// missing header values from the engine API.
+    async fn seal_block(
+        &self,
+        latest_block: &Block,
+        ctx: &Context,
+        withdrawals: Vec<Withdrawal>,
+        transactions: &[TransactionSigned],
+    ) -> Result<SealedBlock, BuilderError> {
+        let versioned_hashes = transactions
+            .iter()
+            .flat_map(|tx| tx.blob_versioned_hashes())
+            .flatten()
+            .collect::<Vec<_>>();
+        trace!(amount = ?versioned_hashes.len(), "got versioned_hashes");
+
+        let blob_gas_used =
+            transactions.iter().fold(0, |acc, tx| acc + tx.blob_gas_used().unwrap_or_default());
+
+        let ctx = Context {
+            blob_gas_used,
+            transactions_root: proofs::calculate_transaction_root(transactions),
+            ..ctx.clone()
         };
+        let parent_beacon_block_root = ctx.parent_beacon_block_root;
 
         let body = BlockBody {
             ommers: Vec::new(),
@@ -175,7 +307,7 @@ impl FallbackPayloadBuilder {
         let max_iterations = 20;
         let mut i = 0;
         loop {
-            let header = build_header_with_hints_and_context(&latest_block, &hints, &ctx);
+            let header = build_header_with_hints_and_context(latest_block, &hints, &ctx);
 
             let sealed_header = header.seal_slow();
             let sealed_block = SealedBlock::new(sealed_header, body.clone());
@@ -186,7 +318,12 @@ impl FallbackPayloadBuilder {
 
             let engine_hint = self
                 .engine_hinter
-                .fetch_next_payload_hint(&exec_payload, &versioned_hashes, parent_beacon_block_root)
+                .fetch_next_payload_hint(
+                    &exec_payload,
+                    &versioned_hashes,
+                    parent_beacon_block_root,
+                    ctx.execution_requests.as_deref(),
+                )
                 .await?;
 
             trace!("engine_hint: {:?}", engine_hint);
@@ -288,32 +425,30 @@ pub(crate) struct EngineHinter {
 }
 
 impl EngineHinter {
-    /// Fetch the next payload hint from the engine API to complete the sealed block.
+    /// Fetch the next payload hint from the engine API to complete the sealed block. The
+    /// payload is sent with `engine_newPayloadV4` if it has execution requests, and with
+    /// `engine_newPayloadV3` otherwise.
     pub async fn fetch_next_payload_hint(
         &self,
         exec_payload: &AlloyExecutionPayload,
         versioned_hashes: &[B256],
         parent_beacon_root: B256,
+        execution_requests: Option<&[Bytes]>,
     ) -> Result<EngineApiHint, BuilderError> {
-        let auth_jwt = secret_to_bearer_header(&JwtSecret::from_hex(&self.jwt_hex)?);
-
-        let body = format!(
-            r#"{{"id":1,"jsonrpc":"2.0","method":"engine_newPayloadV3","params":[{}, {}, "{:?}"]}}"#,
-            serde_json::to_string(&exec_payload)?,
-            serde_json::to_string(&versioned_hashes)?,
-            parent_beacon_root
-        );
+        let mut params = vec![
+            serde_json::to_value(exec_payload)?,
+            json!(versioned_hashes),
+            json!(parent_beacon_root),
+        ];
+        let method = match execution_requests {
+            Some(requests) => {
+                params.push(json!(requests));
+                "engine_newPayloadV4"
+            }
+            None => "engine_newPayloadV3",
+        };
 
-        let raw_hint = self
-            .client
-            .post(self.engine_rpc_url.as_str())
-            .header("Content-Type", "application/json")
-            .header("Authorization", auth_jwt.clone())
-            .body(body)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let raw_hint = self.call(method, Value::Array(params)).await?;
 
         let Some(hint_value) = parse_geth_response(&raw_hint) else {
             // If the hint is not found, it means that we likely got a VALID
@@ -338,12 +473,69 @@ impl EngineHinter {
             return Ok(EngineApiHint::ReceiptsRoot(B256::from_hex(hint_value)?));
         } else if raw_hint.contains("invalid bloom") {
             return Ok(EngineApiHint::LogsBloom(Bloom::from_hex(&hint_value)?));
+        } else if raw_hint.contains("invalid requests hash") {
+            // Unlike the other hints, the requests can't be recovered from their hash
+            return Err(BuilderError::Custom(format!(
+                "Execution requests of the block don't match, expected hash {hint_value}"
+            )));
         };
 
         Err(BuilderError::Custom(
             "Unexpected: failed to parse any hint from engine response".to_string(),
         ))
     }
+
+    /// Fetch the execution requests of a payload built by the execution client on top of the
+    /// given head, with `engine_forkchoiceUpdatedV3` and `engine_getPayloadV4`. The forkchoice
+    /// state leaves the safe and finalized blocks unset, so that they aren't updated.
+    pub async fn fetch_execution_requests(
+        &self,
+        head: B256,
+        attributes: Value,
+    ) -> Result<Vec<Bytes>, BuilderError> {
+        let forkchoice = json!({
+            "headBlockHash": head,
+            "safeBlockHash": B256::ZERO,
+            "finalizedBlockHash": B256::ZERO,
+        });
+        let response =
+            self.call("engine_forkchoiceUpdatedV3", json!([forkchoice, attributes])).await?;
+        let Some(payload_id) = serde_json::from_str::<Value>(&response)?
+            .pointer("/result/payloadId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            return Err(BuilderError::Custom(format!("No payload built by the engine: {response}")));
+        };
+
+        let response = self.call("engine_getPayloadV4", json!([payload_id])).await?;
+        let Some(requests) =
+            serde_json::from_str::<Value>(&response)?.pointer("/result/executionRequests").cloned()
+        else {
+            return Err(BuilderError::Custom(format!(
+                "No execution requests in payload: {response}"
+            )));
+        };
+
+        Ok(serde_json::from_value(requests)?)
+    }
+
+    /// Call a method of the engine API, returning its raw response.
+    async fn call(&self, method: &str, params: Value) -> Result<String, BuilderError> {
+        let auth_jwt = secret_to_bearer_header(&JwtSecret::from_hex(&self.jwt_hex)?);
+        let body = json!({ "id": 1, "jsonrpc": "2.0", "method": method, "params": params });
+
+        Ok(self
+            .client
+            .post(self.engine_rpc_url.as_str())
+            .header("Content-Type", "application/json")
+            .header("Authorization", auth_jwt)
+            .body(body.to_string())
+            .send()
+            .await?
+            .text()
+            .await?)
+    }
 }
 
 /// Parse the hint value from the engine response.
@@ -395,7 +587,8 @@ fn build_header_with_hints_and_context(
         blob_gas_used: Some(context.blob_gas_used),
         excess_blob_gas: Some(context.excess_blob_gas),
         parent_beacon_block_root: Some(context.parent_beacon_block_root),
-        requests_root: None,
+        // The requests hash of EIP-7685 replaced the requests root, at the same position
+        requests_root: context.requests_hash,
         extra_data: context.extra_data.clone(),
     }
 }
@@ -421,6 +614,7 @@ mod tests {
         signers::{k256::ecdsa::SigningKey, local::PrivateKeySigner},
     };
     use beacon_api_client::mainnet::Client as BeaconClient;
+    use ethereum_consensus::Fork;
     use reth_primitives::TransactionSigned;
     use tracing::warn;
 
@@ -458,8 +652,10 @@ mod tests {
             (SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / cfg.chain.slot_time()) +
             1;
 
-        let block = builder.build_fallback_payload(slot, &[tx_signed_reth]).await?;
-        assert_eq!(block.body.len(), 1);
+        let fork = cfg.chain.fork_at_slot(slot);
+        let payload = builder.build_fallback_payload(slot, &[tx_signed_reth], fork).await?;
+        assert_eq!(payload.block.body.len(), 1);
+        assert_eq!(payload.execution_requests.is_some(), fork == Fork::Electra);
 
         Ok(())
     }
@@ -1,3 +1,4 @@
+use crate::ChainConfig;
 use alloy::rpc::types::beacon::{constants::BLS_DST_SIG, BlsSignature};
 use blst::{
     min_pk::{PublicKey, SecretKey},
@@ -8,10 +9,8 @@ use ethereum_consensus::{
     deneb::{compute_fork_data_root, Domain, DomainType, Root},
     ssz::prelude::{HashTreeRoot, MerkleizationError},
 };
-use tree_hash::TreeHash;
-use tree_hash_derive::TreeHash;
 
-use crate::ChainConfig;
+pub use bolt_primitives::signing::compute_signing_root;
 
 /// Sign a SSZ object with a BLS secret key, using the Application Builder domain
 /// for signing arbitrary builder-api messages in the out-of-protocol specifications.
@@ -75,20 +74,6 @@ pub fn sign_message(secret_key: &SecretKey, msg: &[u8]) -> BlsSignature {
     BlsSignature::from_slice(&signature)
 }
 
-/// Helper struct to compute the signing root for a given object
-/// root and signing domain as defined in the Ethereum 2.0 specification.
-#[derive(Default, Debug, TreeHash)]
-struct SigningData {
-    object_root: [u8; 32],
-    signing_domain: [u8; 32],
-}
-
-/// Compute the signing root for a given object root and signing domain.
-pub fn compute_signing_root(object_root: [u8; 32], signing_domain: [u8; 32]) -> [u8; 32] {
-    let signing_data = SigningData { object_root, signing_domain };
-    signing_data.tree_hash_root().0
-}
-
 /// Compute the Application Builder domain for signing arbitrary
 /// builder-api messages in the out-of-protocol specifications
 ///
@@ -3,12 +3,17 @@
 //! new commitment requests.
 use std::collections::{HashMap, HashSet};
 
-use alloy::primitives::{Address, U256};
+use alloy::{
+    eips::eip4844::MAX_BLOBS_PER_BLOCK,
+    primitives::{Address, U256},
+};
 use ethereum_consensus::{
     crypto::{KzgCommitment, KzgProof},
     deneb::mainnet::{Blob, BlobsBundle},
 };
-use reth_primitives::{TransactionSigned, TxHash};
+use reth_primitives::{Bytes, TransactionSigned, TxHash};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
 use tracing::warn;
 
 use crate::{
@@ -51,9 +56,7 @@ impl BlockTemplate {
     pub fn as_signed_transactions(&self) -> Vec<TransactionSigned> {
         self.signed_constraints_list
             .iter()
-            .flat_map(|sc| {
-                sc.message.transactions.iter().map(|c| c.clone().into_inner().into_transaction())
-            })
+            .flat_map(|sc| sc.message.transactions.iter().map(|c| c.to_signed_transaction()))
             .collect()
     }
 
@@ -122,6 +125,42 @@ impl BlockTemplate {
         })
     }
 
+    /// Returns a snapshot of the template for the given slot, with the remaining
+    /// budget computed against the given committed gas limit.
+    pub fn snapshot(&self, slot: u64, max_committed_gas: u64) -> TemplateSnapshot {
+        let transactions = self
+            .signed_constraints_list
+            .iter()
+            .flat_map(|sc| {
+                sc.message.transactions.iter().map(|tx| TemplateTransaction {
+                    hash: *tx.hash(),
+                    sender: tx.sender().copied(),
+                    gas_limit: tx.gas_limit(),
+                    blob_count: tx
+                        .as_eip4844()
+                        .map(|tx| tx.blob_versioned_hashes.len())
+                        .unwrap_or(0),
+                    top: sc.message.top,
+                    raw: tx.envelope_encoded(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let top_of_block = transactions.iter().filter(|tx| tx.top).map(|tx| tx.hash).collect();
+        let committed_gas = self.committed_gas();
+        let blob_count = self.blob_count();
+
+        TemplateSnapshot {
+            slot,
+            transactions,
+            committed_gas,
+            remaining_gas: max_committed_gas.saturating_sub(committed_gas),
+            blob_count,
+            remaining_blobs: MAX_BLOBS_PER_BLOCK.saturating_sub(blob_count),
+            top_of_block,
+        }
+    }
+
     /// Adds a list of constraints to the block template and updates the state diff.
     pub fn add_constraints(&mut self, constraints: SignedConstraints) {
         for constraint in constraints.message.transactions.iter() {
@@ -154,6 +193,25 @@ impl BlockTemplate {
         }
     }
 
+    /// Removes the signed constraints with any of the given transactions, which were already
+    /// included in a block. Returns the number of transactions removed.
+    ///
+    /// Signed constraints are removed as a whole, as their signature covers all of their
+    /// transactions, and a bundle is no longer atomic once part of it is included.
+    pub fn remove_included(&mut self, included: &HashSet<TxHash>) -> usize {
+        let mut removed = 0;
+
+        for index in (0..self.signed_constraints_list.len()).rev() {
+            let transactions = &self.signed_constraints_list[index].message.transactions;
+            if transactions.iter().any(|tx| included.contains(tx.hash())) {
+                removed += transactions.len();
+                self.remove_constraints_at_index(index);
+            }
+        }
+
+        removed
+    }
+
     /// Retain removes any transactions that conflict with the given account state.
     pub fn retain(&mut self, address: Address, state: AccountState) {
         let mut indexes: Vec<usize> = Vec::new();
@@ -200,6 +258,79 @@ impl BlockTemplate {
     }
 }
 
+/// A snapshot of the pending block template of a slot, for builders colocated with
+/// the proposer to pre-build around the commitments made so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSnapshot {
+    /// The slot of the template.
+    pub slot: u64,
+    /// The committed transactions, in the order they must be included.
+    pub transactions: Vec<TemplateTransaction>,
+    /// The gas committed so far.
+    pub committed_gas: u64,
+    /// The gas that can still be committed in the slot.
+    pub remaining_gas: u64,
+    /// The number of blobs committed so far.
+    pub blob_count: usize,
+    /// The number of blobs that can still be committed in the slot.
+    pub remaining_blobs: usize,
+    /// The hashes of the transactions reserved for the top of the block.
+    pub top_of_block: Vec<TxHash>,
+}
+
+/// A committed transaction of a [TemplateSnapshot].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateTransaction {
+    /// The hash of the transaction.
+    pub hash: TxHash,
+    /// The recovered sender of the transaction.
+    pub sender: Option<Address>,
+    /// The gas limit of the transaction.
+    pub gas_limit: u64,
+    /// The number of blobs carried by the transaction.
+    pub blob_count: usize,
+    /// Whether the transaction is reserved for the top of the block.
+    pub top: bool,
+    /// The EIP-2718 network encoding of the transaction, including the blob sidecar if any.
+    pub raw: Bytes,
+}
+
+/// A request for the [TemplateSnapshot] of a slot, handled by the sidecar driver.
+#[derive(Debug)]
+pub(crate) struct TemplateSnapshotRequest {
+    /// The slot of the template.
+    pub slot: u64,
+    /// The channel to send the snapshot back on.
+    pub response: oneshot::Sender<TemplateSnapshot>,
+}
+
+/// Error returned when the sidecar driver is not running to serve template snapshots.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("The sidecar driver is not running")]
+pub struct DriverUnavailable;
+
+/// A handle to read snapshots of the pending block templates of a running sidecar.
+#[derive(Debug, Clone)]
+pub struct TemplateReader {
+    requests: mpsc::Sender<TemplateSnapshotRequest>,
+}
+
+impl TemplateReader {
+    /// Creates a reader sending requests to the sidecar driver.
+    pub(crate) fn new(requests: mpsc::Sender<TemplateSnapshotRequest>) -> Self {
+        Self { requests }
+    }
+
+    /// Returns a snapshot of the pending template for the given slot. Slots without
+    /// commitments have an empty template with the full budget remaining.
+    pub async fn snapshot(&self, slot: u64) -> Result<TemplateSnapshot, DriverUnavailable> {
+        let (response, snapshot) = oneshot::channel();
+        let request = TemplateSnapshotRequest { slot, response };
+        self.requests.send(request).await.map_err(|_| DriverUnavailable)?;
+        snapshot.await.map_err(|_| DriverUnavailable)
+    }
+}
+
 /// StateDiff tracks the intermediate changes to the state according to the block template.
 #[derive(Debug, Default)]
 pub struct StateDiff {
@@ -0,0 +1,82 @@
+//! Signing of the submissions to the Constraints API, for Constraints API servers such as
+//! bolt-boost that only accept submissions from the sidecars of their operator.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::{
+    hex,
+    primitives::{keccak256, B256},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use reqwest::RequestBuilder;
+use tracing::error;
+
+/// The header carrying the UNIX timestamp in seconds at which a request was signed.
+pub const AUTH_TIMESTAMP_HEADER: &str = "x-bolt-timestamp";
+
+/// The header carrying the ECDSA signature of a request by the sidecar that sent it.
+pub const AUTH_SIGNATURE_HEADER: &str = "x-bolt-signature";
+
+/// The digest signed to authenticate a request to `path`:
+///
+/// `keccak256(path | be_bytes(timestamp) | keccak256(body))`
+pub fn auth_digest(path: &str, timestamp: u64, body: &[u8]) -> B256 {
+    let mut data = Vec::with_capacity(path.len() + 8 + 32);
+    data.extend_from_slice(path.as_bytes());
+    data.extend_from_slice(&timestamp.to_be_bytes());
+    data.extend_from_slice(keccak256(body).as_slice());
+    keccak256(data)
+}
+
+/// Signs the requests to the Constraints API with the commitment key of the sidecar.
+#[derive(Debug, Clone)]
+pub struct RequestSigner {
+    signer: PrivateKeySigner,
+}
+
+impl RequestSigner {
+    /// Creates a new request signer with the given key.
+    pub fn new(signer: PrivateKeySigner) -> Self {
+        Self { signer }
+    }
+
+    /// Adds the authentication headers of a request to `path` with the given body, signed
+    /// at the current time.
+    pub fn sign(&self, request: RequestBuilder, path: &str, body: &[u8]) -> RequestBuilder {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        match self.signer.sign_hash_sync(&auth_digest(path, timestamp, body)) {
+            Ok(signature) => request
+                .header(AUTH_TIMESTAMP_HEADER, timestamp.to_string())
+                .header(AUTH_SIGNATURE_HEADER, hex::encode_prefixed(signature.as_bytes())),
+            Err(err) => {
+                // The relay will reject the request, which surfaces as a regular failure
+                error!(?err, path, "Failed to sign Constraints API request");
+                request
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_request() {
+        let signer = PrivateKeySigner::random();
+        let request = reqwest::Client::new().post("http://localhost:18550/constraints");
+        let request = RequestSigner::new(signer.clone())
+            .sign(request, "/constraints", b"[]")
+            .build()
+            .unwrap();
+
+        let headers = request.headers();
+        let timestamp = headers[AUTH_TIMESTAMP_HEADER].to_str().unwrap().parse::<u64>().unwrap();
+        let signature = hex::decode(headers[AUTH_SIGNATURE_HEADER].to_str().unwrap()).unwrap();
+        let signature = alloy::primitives::Signature::try_from(signature.as_slice()).unwrap();
+
+        let digest = auth_digest("/constraints", timestamp, b"[]");
+        assert_eq!(signature.recover_address_from_prehash(&digest).unwrap(), signer.address());
+    }
+}
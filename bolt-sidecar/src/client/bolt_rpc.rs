@@ -0,0 +1,452 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{keccak256, Address, TxHash, B256},
+    signers::Signer,
+};
+use futures::{stream, Stream};
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use crate::{
+    api::codes::ErrorCode,
+    commitments::{
+        jsonrpc::{JsonPayload, JsonResponse},
+        spec::{
+            ErrorData, InclusionSimulation, API_VERSION, API_VERSION_HEADER,
+            GET_AVAILABILITY_ATTESTATIONS_METHOD, GET_AVAILABILITY_METHOD,
+            GET_COMMITMENT_STATUS_METHOD, GET_SIDECAR_IDENTITY_METHOD, GET_VERSION_METHOD,
+            IDEMPOTENCY_KEY_HEADER, LOOKUP_TRANSACTIONS_METHOD, MAX_REQUEST_TIMEOUT,
+            REQUEST_BATCH_INCLUSION_METHOD, REQUEST_INCLUSION_BY_HASH_METHOD,
+            REQUEST_INCLUSION_METHOD, RESERVE_BLOCKSPACE_METHOD, SIGNATURE_HEADER,
+            SIMULATE_INCLUSION_METHOD, UPLOAD_TRANSACTIONS_METHOD,
+        },
+        uploads::UploadedTx,
+    },
+    primitives::{
+        commitment::{ECDSASignatureExt, InclusionCommitment},
+        transaction::serialize_txs,
+        BatchInclusionRequest, FullTransaction, InclusionRequest, InclusionRequestByHash,
+        ReservationRequest, SidecarIdentity, SignedAvailability, SignedReservation,
+    },
+    state::{
+        commitment_status::{CommitmentStatus, CommitmentStatusQuery},
+        SlotAssignment,
+    },
+};
+
+/// The retry and timeout policy of a [BoltRpcClient].
+///
+/// Only the failures that may succeed if retried are retried: timeouts, connection errors,
+/// and the [ErrorCode::RateLimited], [ErrorCode::UpstreamUnavailable] and
+/// [ErrorCode::Timeout] errors of the sidecar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The timeout of each attempt.
+    pub timeout: Duration,
+    /// The number of retries after the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled at every retry.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { timeout: MAX_REQUEST_TIMEOUT, max_retries: 2, backoff: Duration::from_millis(100) }
+    }
+}
+
+/// Errors of a [BoltRpcClient].
+#[derive(Debug, Error)]
+pub enum BoltRpcError {
+    /// The request could not be sent, or the response could not be read.
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The request could not be signed.
+    #[error("Failed to sign the request: {0}")]
+    Signer(#[from] alloy::signers::Error),
+    /// The sidecar returned a JSON-RPC error.
+    #[error("JSON-RPC error {code}: {message}")]
+    Rpc {
+        /// The JSON-RPC error code, see [ErrorCode].
+        code: i32,
+        /// The error message.
+        message: String,
+        /// The data of the error, if any.
+        data: Option<Value>,
+    },
+    /// The result of the response doesn't match the method.
+    #[error("Invalid response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+}
+
+impl BoltRpcError {
+    /// Returns the stable error code of a JSON-RPC error, if any.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Rpc { code, .. } => ErrorCode::from_json_rpc_code(*code),
+            _ => None,
+        }
+    }
+
+    /// Returns the structured data of a JSON-RPC error, if the sidecar sent it.
+    pub fn data(&self) -> Option<ErrorData> {
+        match self {
+            Self::Rpc { data: Some(data), .. } => serde_json::from_value(data.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the slots suggested by the sidecar to retarget a request rejected for its
+    /// target slot, in ascending order. Empty if none were suggested.
+    pub fn suggested_slots(&self) -> Vec<u64> {
+        match self {
+            Self::Rpc { data: Some(data), .. } => data
+                .get("suggestedSlots")
+                .and_then(|slots| serde_json::from_value(slots.clone()).ok())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns true if the request may succeed if retried. Sidecars that don't send the
+    /// structured data of their errors are answered from the error code.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(err) => err.is_timeout() || err.is_connect(),
+            Self::Rpc { .. } => match self.data() {
+                Some(data) => data.retryable,
+                None => self.error_code().is_some_and(ErrorCode::is_retryable),
+            },
+            _ => false,
+        }
+    }
+}
+
+/// A typed client of the commitments API, for wallets, RPC proxies and other integrators.
+///
+/// Every request is signed with the given alloy signer, over the canonical digest of the
+/// request on the chain of the sidecar, and sent in the `x-bolt-signature` header. The
+/// requests without a digest, like [BoltRpcClient::get_version], are signed over the hash of
+/// their method name. Inclusion requests carry an idempotency key derived from their digest,
+/// so that retrying them never results in two commitments.
+#[derive(Debug, Clone)]
+pub struct BoltRpcClient<S> {
+    client: Client,
+    url: Url,
+    chain_id: u64,
+    signer: S,
+    retry: RetryPolicy,
+}
+
+impl<S: Signer + Send + Sync> BoltRpcClient<S> {
+    /// Creates a client of the JSON-RPC endpoint at the given URL, e.g.
+    /// `http://localhost:8000/rpc/v1`, for a sidecar running on the given chain.
+    pub fn new(url: Url, chain_id: u64, signer: S) -> Self {
+        Self { client: Client::new(), url, chain_id, signer, retry: RetryPolicy::default() }
+    }
+
+    /// Sets the retry and timeout policy of the requests.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Returns the address of the signer of the requests.
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Returns the version of the sidecar, e.g. `bolt-sidecar-v0.3.0`.
+    pub async fn get_version(&self) -> Result<String, BoltRpcError> {
+        self.call(GET_VERSION_METHOD, Vec::new(), keccak256(GET_VERSION_METHOD), None).await
+    }
+
+    /// Returns the identity of the sidecar: its commitment key and the attestations of the
+    /// validators it commits for.
+    pub async fn get_sidecar_identity(&self) -> Result<SidecarIdentity, BoltRpcError> {
+        let digest = keccak256(GET_SIDECAR_IDENTITY_METHOD);
+        self.call(GET_SIDECAR_IDENTITY_METHOD, Vec::new(), digest, None).await
+    }
+
+    /// Returns the upcoming proposal slots of the sidecar, with the delegatee assigned the
+    /// authority over each.
+    pub async fn get_availability(&self) -> Result<Vec<SlotAssignment>, BoltRpcError> {
+        let digest = keccak256(GET_AVAILABILITY_METHOD);
+        self.call(GET_AVAILABILITY_METHOD, Vec::new(), digest, None).await
+    }
+
+    /// Returns the signed availability attestations of the upcoming proposal slots of the
+    /// sidecar. A slot without attestation should be routed to another sidecar.
+    pub async fn get_availability_attestations(
+        &self,
+    ) -> Result<Vec<SignedAvailability>, BoltRpcError> {
+        let digest = keccak256(GET_AVAILABILITY_ATTESTATIONS_METHOD);
+        self.call(GET_AVAILABILITY_ATTESTATIONS_METHOD, Vec::new(), digest, None).await
+    }
+
+    /// Requests the inclusion of the transactions of the request, returning the signed
+    /// commitment of the sidecar.
+    pub async fn request_inclusion(
+        &self,
+        request: &InclusionRequest,
+    ) -> Result<InclusionCommitment, BoltRpcError> {
+        let digest = request.signing_digest(self.chain_id);
+        let params = vec![serde_json::to_value(request)?];
+        self.call(REQUEST_INCLUSION_METHOD, params, digest, Some(digest.to_string())).await
+    }
+
+    /// Requests the inclusion of a rollup batch in the cheapest proposal slot of the sidecar
+    /// within its delay, returning the signed commitment to the chosen slot.
+    pub async fn request_batch_inclusion(
+        &self,
+        request: &BatchInclusionRequest,
+    ) -> Result<InclusionCommitment, BoltRpcError> {
+        let digest = InclusionRequest::from(request.clone()).signing_digest(self.chain_id);
+        let params = vec![serde_json::to_value(request)?];
+        self.call(REQUEST_BATCH_INCLUSION_METHOD, params, digest, Some(digest.to_string())).await
+    }
+
+    /// Uploads raw transactions to the sidecar, to be referenced by hash in the inclusion
+    /// requests of [Self::request_inclusion_by_hash] until they expire.
+    pub async fn upload_transactions(
+        &self,
+        txs: &[FullTransaction],
+    ) -> Result<Vec<UploadedTx>, BoltRpcError> {
+        let digest = keccak256(UPLOAD_TRANSACTIONS_METHOD);
+        let params = vec![serialize_txs(txs, serde_json::value::Serializer)?];
+        self.call(UPLOAD_TRANSACTIONS_METHOD, params, digest, None).await
+    }
+
+    /// Returns whether each of the transactions is uploaded, and for how long it is still kept.
+    pub async fn lookup_transactions(
+        &self,
+        hashes: &[TxHash],
+    ) -> Result<Vec<UploadedTx>, BoltRpcError> {
+        let digest = keccak256(LOOKUP_TRANSACTIONS_METHOD);
+        let params = vec![serde_json::to_value(hashes)?];
+        self.call(LOOKUP_TRANSACTIONS_METHOD, params, digest, None).await
+    }
+
+    /// Returns the status of the commitment to the transaction in the given slot.
+    pub async fn get_commitment_status(
+        &self,
+        slot: u64,
+        tx_hash: TxHash,
+    ) -> Result<CommitmentStatus, BoltRpcError> {
+        let digest = keccak256(GET_COMMITMENT_STATUS_METHOD);
+        let params = vec![serde_json::to_value(CommitmentStatusQuery { slot, tx_hash })?];
+        self.call(GET_COMMITMENT_STATUS_METHOD, params, digest, None).await
+    }
+
+    /// Requests the inclusion of the transactions of the request, sending only their hashes.
+    /// The transactions must have been uploaded first.
+    pub async fn request_inclusion_by_hash(
+        &self,
+        request: &InclusionRequest,
+    ) -> Result<InclusionCommitment, BoltRpcError> {
+        let digest = request.signing_digest(self.chain_id);
+        let by_hash = InclusionRequestByHash {
+            slot: request.slot,
+            max_slot: request.max_slot,
+            top: request.top,
+            partial: request.partial,
+            tx_hashes: request.txs.iter().map(|tx| *tx.hash()).collect(),
+            voucher: request.voucher.clone(),
+        };
+        let params = vec![serde_json::to_value(by_hash)?];
+        self.call(REQUEST_INCLUSION_BY_HASH_METHOD, params, digest, Some(digest.to_string())).await
+    }
+
+    /// Reserves gas in every proposal slot of the requested period.
+    pub async fn reserve_blockspace(
+        &self,
+        request: &ReservationRequest,
+    ) -> Result<SignedReservation, BoltRpcError> {
+        let digest = request.signing_digest(self.chain_id);
+        let params = vec![serde_json::to_value(request)?];
+        self.call(RESERVE_BLOCKSPACE_METHOD, params, digest, None).await
+    }
+
+    /// Returns whether an inclusion request would be accepted, without committing to it.
+    pub async fn simulate_inclusion(
+        &self,
+        request: &InclusionRequest,
+    ) -> Result<InclusionSimulation, BoltRpcError> {
+        let digest = request.signing_digest(self.chain_id);
+        let params = vec![serde_json::to_value(request)?];
+        self.call(SIMULATE_INCLUSION_METHOD, params, digest, None).await
+    }
+
+    /// Subscribes to the identity of the sidecar, yielding it on subscription and then on
+    /// every change, e.g. when its commitment key is rotated.
+    ///
+    /// The commitments API doesn't push events, so the identity is polled at the given
+    /// interval. Failed polls are yielded as errors, and the subscription goes on.
+    pub fn subscribe_identity(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<SidecarIdentity, BoltRpcError>> + '_ {
+        self.subscribe(GET_SIDECAR_IDENTITY_METHOD, interval)
+    }
+
+    /// Polls a method without parameters at the given interval, yielding its result
+    /// whenever it changes.
+    fn subscribe<T>(
+        &self,
+        method: &'static str,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<T, BoltRpcError>> + '_
+    where
+        T: DeserializeOwned + PartialEq + Clone,
+    {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        stream::unfold((ticker, None::<T>), move |(mut ticker, last)| async move {
+            loop {
+                ticker.tick().await;
+                match self.call::<T>(method, Vec::new(), keccak256(method), None).await {
+                    Ok(value) if last.as_ref() == Some(&value) => continue,
+                    Ok(value) => return Some((Ok(value.clone()), (ticker, Some(value)))),
+                    Err(err) => return Some((Err(err), (ticker, last))),
+                }
+            }
+        })
+    }
+
+    /// Signs and sends a JSON-RPC request, retrying it according to the retry policy.
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+        digest: B256,
+        idempotency_key: Option<String>,
+    ) -> Result<T, BoltRpcError> {
+        let signature = self.signer.sign_hash(&digest).await?;
+        let auth = format!("{}:{}", self.signer.address(), signature.to_hex());
+        let payload = JsonPayload {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            id: Some(Value::from(1)),
+            params,
+        };
+
+        let mut attempt = 0;
+        loop {
+            match self.send(&payload, &auth, idempotency_key.as_deref()).await {
+                Err(err) if err.is_retryable() && attempt < self.retry.max_retries => {
+                    let backoff = self.retry.backoff * 2u32.pow(attempt);
+                    warn!(
+                        ?err,
+                        method,
+                        attempt,
+                        ?backoff,
+                        "Commitments API request failed, retrying"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a signed JSON-RPC request once, returning its result.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        payload: &JsonPayload,
+        auth: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<T, BoltRpcError> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .timeout(self.retry.timeout)
+            .header(SIGNATURE_HEADER, auth)
+            .header(API_VERSION_HEADER, API_VERSION)
+            .json(payload);
+        if let Some(key) = idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+
+        // Errors are JSON-RPC responses too, whatever their HTTP status
+        let response: JsonResponse = request.send().await?.json().await?;
+        if let Some(error) = response.error {
+            debug!(method = %payload.method, code = error.code, "Commitments API error");
+            return Err(BoltRpcError::Rpc {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            });
+        }
+
+        Ok(serde_json::from_value(response.result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner};
+    use tokio::sync::mpsc;
+
+    use crate::{
+        commitments::server::{CommitmentsApiServer, Event},
+        primitives::CommitmentRequest,
+        test_util::{create_signed_commitment_request, default_test_transaction},
+        ChainConfig,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bolt_rpc_client() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let chain_id = ChainConfig::default().chain_id();
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_chain_id(chain_id);
+        let (events_tx, mut events) = mpsc::channel(1);
+        server.run(events_tx).await;
+
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let url = format!("http://{}/rpc/v1", server.local_addr()).parse().unwrap();
+        let client = BoltRpcClient::new(url, chain_id, signer.clone());
+
+        let version = client.get_version().await.unwrap();
+        assert!(version.starts_with("bolt-sidecar-v"));
+
+        let tx = default_test_transaction(signer.address(), None);
+        let CommitmentRequest::Inclusion(request) =
+            create_signed_commitment_request(&[tx], &sk, 12).await.unwrap();
+
+        // Answer the request as the driver would
+        tokio::spawn(async move {
+            let Event { request, response } = events.recv().await.unwrap();
+            let commitment = request.commit_and_sign(&PrivateKeySigner::random()).await;
+            let _ = response.send(Ok(commitment.unwrap()));
+        });
+
+        let commitment = client.request_inclusion(&request).await.unwrap();
+        assert_eq!(commitment.request.txs, request.txs);
+
+        // Errors of the sidecar are typed, reservations are disabled by default
+        let err = client.reserve_blockspace(&ReservationRequest {
+            gas_per_slot: 21_000,
+            start_slot: 12,
+            end_slot: 20,
+            signer: None,
+        });
+        let err = err.await.unwrap_err();
+        assert_eq!(err.error_code(), Some(ErrorCode::ReservationRejected));
+        assert!(!err.is_retryable());
+
+        let data = err.data().unwrap();
+        assert_eq!(data.code, ErrorCode::ReservationRejected);
+        assert!(!data.retryable);
+    }
+}
@@ -0,0 +1,127 @@
+//! Discovery of the features supported by each relay, from its capabilities endpoint,
+//! so that the sidecar only offers the commitments that the relay can enforce.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use parking_lot::RwLock;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// The interval at which the capabilities of the relays are fetched again, as they can
+/// change when a relay upgrades.
+pub const CAPABILITIES_REFRESH_INTERVAL: Duration = Duration::from_secs(384);
+
+/// The commitment type of plain inclusion constraints.
+pub const INCLUSION_COMMITMENT: &str = "inclusion";
+
+/// The encoding of requests as JSON.
+pub const JSON_ENCODING: &str = "json";
+
+/// The encoding of requests as SSZ.
+pub const SSZ_ENCODING: &str = "ssz";
+
+/// The features supported by a relay, as served by its capabilities endpoint.
+///
+/// Fields missing from the response take the value of [Capabilities::default], which
+/// describes a relay implementing the Constraints API before the endpoint was introduced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Capabilities {
+    /// The types of commitments whose constraints the relay enforces.
+    pub commitment_types: Vec<String>,
+    /// The max number of constraints the relay accepts per slot, if capped.
+    pub max_constraints_per_slot: Option<usize>,
+    /// The max gas the relay accepts to be constrained per slot, if capped.
+    pub max_constrained_gas_per_slot: Option<u64>,
+    /// Whether the relay enforces top-of-block constraints.
+    pub top_of_block: bool,
+    /// The encodings of constraints submissions, validator registrations and blinded blocks
+    /// the relay accepts.
+    pub encodings: Vec<String>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            commitment_types: vec![INCLUSION_COMMITMENT.to_string()],
+            max_constraints_per_slot: None,
+            max_constrained_gas_per_slot: None,
+            top_of_block: true,
+            encodings: vec![JSON_ENCODING.to_string()],
+        }
+    }
+}
+
+impl Capabilities {
+    /// Returns true if the relay enforces constraints of the given commitment type.
+    pub fn supports_commitment(&self, commitment_type: &str) -> bool {
+        self.commitment_types.iter().any(|t| t.eq_ignore_ascii_case(commitment_type))
+    }
+
+    /// Returns true if the relay accepts requests in the given encoding.
+    pub fn supports_encoding(&self, encoding: &str) -> bool {
+        self.encodings.iter().any(|e| e.eq_ignore_ascii_case(encoding))
+    }
+}
+
+/// Tracks the capabilities advertised by each relay.
+///
+/// Relays that were not asked yet, or that don't serve the endpoint, are assumed to
+/// support the [default](Capabilities::default) capabilities. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct RelayCapabilities {
+    relays: Arc<RwLock<HashMap<Url, Capabilities>>>,
+}
+
+impl RelayCapabilities {
+    /// Records the capabilities advertised by the relay, returning true if they changed.
+    pub fn record(&self, relay: &Url, capabilities: Capabilities) -> bool {
+        let previous = self.relays.write().insert(relay.clone(), capabilities.clone());
+        previous.unwrap_or_default() != capabilities
+    }
+
+    /// Returns the capabilities of the relay.
+    pub fn get(&self, relay: &Url) -> Capabilities {
+        self.relays.read().get(relay).cloned().unwrap_or_default()
+    }
+
+    /// Stops using an encoding that the relay advertised but rejected, until its capabilities
+    /// are fetched again.
+    pub fn disable_encoding(&self, relay: &Url, encoding: &str) {
+        let mut relays = self.relays.write();
+        let capabilities = relays.entry(relay.clone()).or_default();
+        capabilities.encodings.retain(|e| !e.eq_ignore_ascii_case(encoding));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_capabilities() {
+        let capabilities = RelayCapabilities::default();
+        let relay = Url::parse("http://relay:3030").unwrap();
+
+        // Unknown relays get the default capabilities
+        assert_eq!(capabilities.get(&relay), Capabilities::default());
+
+        // Missing fields take their default value
+        let advertised: Capabilities = serde_json::from_str(
+            r#"{"topOfBlock":false,"maxConstraintsPerSlot":64,"encodings":["json","ssz"]}"#,
+        )
+        .unwrap();
+        assert!(advertised.supports_commitment(INCLUSION_COMMITMENT));
+        assert!(advertised.supports_encoding("SSZ"));
+        assert_eq!(advertised.max_constrained_gas_per_slot, None);
+
+        assert!(capabilities.record(&relay, advertised.clone()));
+        assert!(!capabilities.record(&relay, advertised));
+        assert!(!capabilities.get(&relay).top_of_block);
+        assert_eq!(capabilities.get(&relay).max_constraints_per_slot, Some(64));
+
+        capabilities.disable_encoding(&relay, SSZ_ENCODING);
+        assert!(!capabilities.get(&relay).supports_encoding(SSZ_ENCODING));
+        assert!(capabilities.get(&relay).supports_encoding(JSON_ENCODING));
+    }
+}
@@ -2,69 +2,520 @@
 //! The Bolt sidecar's main purpose is to sit between the beacon node and Constraints client,
 //! so most requests are simply proxied to its API.
 
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use axum::http::StatusCode;
-use beacon_api_client::VersionedValue;
+use alloy::primitives::Address;
+use axum::http::{header, StatusCode};
+use bytes::Bytes;
 use ethereum_consensus::{
     builder::SignedValidatorRegistration, crypto::PublicKey as BlsPublicKey,
-    deneb::mainnet::SignedBlindedBeaconBlock, Fork,
+    deneb::presets::mainnet::ExecutionPayloadHeader, Fork,
 };
-use reqwest::Url;
-use tracing::error;
+use parking_lot::RwLock;
+use reqwest::{RequestBuilder, Response, Url};
+use tokio::time::sleep;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 use crate::{
     api::{
         builder::GetHeaderParams,
         spec::{
-            BuilderApi, BuilderApiError, ConstraintsApi, ErrorResponse, DELEGATE_PATH,
-            GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH, REVOKE_PATH, STATUS_PATH,
+            BuilderApi, BuilderApiError, ConstraintsApi, ErrorResponse, CAPABILITIES_PATH,
+            CONSENSUS_VERSION_HEADER, DELEGATE_PATH, GET_PAYLOAD_PATH, JSON_CONTENT_TYPE,
+            REGISTER_VALIDATORS_PATH, REVOKE_PATH, SSZ_CONTENT_TYPE, STATUS_PATH,
             SUBMIT_CONSTRAINTS_PATH,
         },
     },
+    common::now_ms,
     primitives::{
-        BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
-        SignedRevocation,
+        ssz::encode_registrations, BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid,
+        SignedDelegation, SignedRevocation, SszEncoding, SszError, VersionedSignedBlindedBlock,
+        VersionedSignedBuilderBid,
     },
+    telemetry::ApiMetrics,
+};
+
+use super::{
+    auth::RequestSigner,
+    capabilities::{Capabilities, RelayCapabilities, SSZ_ENCODING},
+    delegation_manager::DelegationManager,
+    delegations::{DelegationState, DelegationTracker},
+    fee_recipients::{FeeRecipientGuard, FeeRecipientSource},
+    lenient::from_relay_json,
+    registrations::RegistrationStore,
+    submissions::SubmissionLog,
+    throttle::{parse_retry_after, RelayThrottle, MAX_RATE_LIMIT_RETRIES, MAX_RATE_LIMIT_WAIT},
+    timeouts::RelayTimeouts,
 };
 
 /// A client for interacting with the Constraints client API.
+///
+/// Clones share the same target URL, so that it can be changed at runtime.
 #[derive(Debug, Clone)]
 pub struct ConstraintsClient {
     /// The URL of the MEV-Boost target supporting the Constraints API.
-    pub url: Url,
+    url: Arc<RwLock<Url>>,
     client: reqwest::Client,
-    delegations: Vec<SignedDelegation>,
+    /// The delegations and revocations of the validators, shared by all clones.
+    delegations: DelegationManager,
+    /// The delegations acknowledged by each relay, shared by all clones.
+    delegation_tracker: DelegationTracker,
+    /// The rate limits of each relay, shared by all clones.
+    throttle: RelayThrottle,
+    /// The adaptive timeouts of each relay endpoint, shared by all clones.
+    timeouts: RelayTimeouts,
+    /// The capabilities advertised by each relay, shared by all clones.
+    capabilities: RelayCapabilities,
+    /// The checks of the fee recipients of registrations and bids, shared by all clones.
+    fee_recipients: FeeRecipientGuard,
+    /// The signer of the submissions, if the relay only accepts authenticated ones.
+    request_signer: Option<RequestSigner>,
+    /// The store of the validator registrations to replay on startup, if any.
+    registrations: Option<RegistrationStore>,
+    /// The constraints submitted to each relay, shared by all clones.
+    submissions: SubmissionLog,
 }
 
 impl ConstraintsClient {
     /// Creates a new constraint client with the given URL.
     pub fn new<U: Into<Url>>(url: U) -> Self {
         Self {
-            url: url.into(),
+            url: Arc::new(RwLock::new(url.into())),
             client: reqwest::ClientBuilder::new().user_agent("bolt-sidecar").build().unwrap(),
-            delegations: Vec::new(),
+            delegations: DelegationManager::default(),
+            delegation_tracker: DelegationTracker::default(),
+            throttle: RelayThrottle::default(),
+            timeouts: RelayTimeouts::default(),
+            capabilities: RelayCapabilities::default(),
+            fee_recipients: FeeRecipientGuard::default(),
+            request_signer: None,
+            registrations: None,
+            submissions: SubmissionLog::default(),
         }
     }
 
-    /// Adds a list of delegations to the client.
-    pub fn add_delegations(&mut self, delegations: Vec<SignedDelegation>) {
-        self.delegations.extend(delegations);
+    /// Signs the submissions of constraints, delegations and revocations with the given
+    /// key, for relays that only accept submissions from authorized sidecars.
+    pub fn with_request_signer(mut self, signer: RequestSigner) -> Self {
+        self.request_signer = Some(signer);
+        self
+    }
+
+    /// Adapts the timeouts of the calls to the relay to its latency, within the given bounds.
+    pub fn with_timeouts(mut self, timeouts: RelayTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Checks the fee recipients of the validator registrations forwarded to the relay and
+    /// of its bids with the given guard.
+    pub fn with_fee_recipient_guard(mut self, guard: FeeRecipientGuard) -> Self {
+        self.fee_recipients = guard;
+        self
+    }
+
+    /// Manages the delegations and revocations with the given manager, e.g. to save them
+    /// on disk.
+    pub fn with_delegation_manager(mut self, manager: DelegationManager) -> Self {
+        self.delegations = manager;
+        self
+    }
+
+    /// Saves the validator registrations forwarded to the relay in the given store, to
+    /// [replay](ConstraintsClient::replay_registrations) them on startup.
+    pub fn with_registration_store(mut self, store: RegistrationStore) -> Self {
+        self.registrations = Some(store);
+        self
+    }
+
+    /// Records the constraints submitted to each relay in the given log, e.g. to append them
+    /// to an event log on disk.
+    pub fn with_submission_log(mut self, log: SubmissionLog) -> Self {
+        self.submissions = log;
+        self
+    }
+
+    /// Returns a client of another relay, sharing the delegations, rate limits, timeouts,
+    /// capabilities, fee recipient checks, submission log and signer of this one.
+    pub fn for_relay(&self, url: Url) -> Self {
+        Self { url: Arc::new(RwLock::new(url)), ..self.clone() }
+    }
+
+    /// Returns the URL of the MEV-Boost target supporting the Constraints API.
+    pub fn url(&self) -> Url {
+        self.url.read().clone()
+    }
+
+    /// Replaces the URL of the target, for this client and all its clones.
+    pub fn set_url(&self, url: Url) {
+        *self.url.write() = url;
+    }
+
+    /// Returns the capabilities of the current relay, as of the last
+    /// [refresh](ConstraintsClient::refresh_capabilities).
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.get(&self.url())
+    }
+
+    /// Fetches the capabilities of the current relay and records them. Relays that don't
+    /// serve the endpoint are assumed to support the [default](Capabilities::default) ones.
+    pub async fn refresh_capabilities(&self) -> Result<Capabilities, BuilderApiError> {
+        let relay = self.url();
+        let response = self
+            .send_rate_limited("capabilities", false, || {
+                self.client.get(self.endpoint(CAPABILITIES_PATH))
+            })
+            .await?;
+
+        let capabilities = match response.status() {
+            StatusCode::OK => {
+                let body = response.bytes().await?;
+                from_relay_json(&body, relay.as_str(), "capabilities")?
+            }
+            StatusCode::NOT_FOUND => Capabilities::default(),
+            _ => {
+                let error = response.json::<ErrorResponse>().await?;
+                return Err(BuilderApiError::FailedGettingCapabilities(error));
+            }
+        };
+
+        if self.capabilities.record(&relay, capabilities.clone()) {
+            info!(%relay, ?capabilities, "Relay capabilities changed");
+        }
+
+        Ok(capabilities)
+    }
+
+    /// Adds the delegations provided by the operator to the client.
+    pub fn add_delegations(&self, delegations: Vec<SignedDelegation>) {
+        if let Err(err) = self.delegations.add_delegations(delegations) {
+            warn!(?err, "Failed to save the delegations");
+        }
     }
 
     /// Finds all delegations for the given validator public key.
     pub fn find_delegatees(&self, validator_pubkey: &BlsPublicKey) -> HashSet<BlsPublicKey> {
-        self.delegations
-            .iter()
-            .filter(|d| d.message.validator_pubkey == *validator_pubkey)
-            .map(|d| d.message.delegatee_pubkey.clone())
-            .collect::<HashSet<_>>()
+        self.delegations.delegatees(validator_pubkey).into_iter().collect()
+    }
+
+    /// Returns the delegatees of the given validator public key, in the order of the
+    /// delegations.
+    pub fn ordered_delegatees(&self, validator_pubkey: &BlsPublicKey) -> Vec<BlsPublicKey> {
+        self.delegations.delegatees(validator_pubkey)
+    }
+
+    /// Finds the delegation from the given validator public key to the given delegatee.
+    pub fn find_delegation(
+        &self,
+        validator_pubkey: &BlsPublicKey,
+        delegatee_pubkey: &BlsPublicKey,
+    ) -> Option<SignedDelegation> {
+        self.delegations.find(validator_pubkey, delegatee_pubkey)
+    }
+
+    /// Returns the manager of the delegations and revocations.
+    pub fn delegation_manager(&self) -> DelegationManager {
+        self.delegations.clone()
+    }
+
+    /// Returns the tracker of the delegations acknowledged by each relay.
+    pub fn delegation_tracker(&self) -> DelegationTracker {
+        self.delegation_tracker.clone()
+    }
+
+    /// Returns the log of the constraints submitted to each relay.
+    pub fn submission_log(&self) -> SubmissionLog {
+        self.submissions.clone()
+    }
+
+    /// Returns the tracker of the rate limits of each relay.
+    pub fn relay_throttle(&self) -> RelayThrottle {
+        self.throttle.clone()
+    }
+
+    /// Returns the checks of the fee recipients of the registrations and bids of each relay.
+    pub fn fee_recipient_guard(&self) -> FeeRecipientGuard {
+        self.fee_recipients.clone()
+    }
+
+    /// Checks the fee recipient of a bid of the current relay, discarding the bid if it
+    /// must be rejected.
+    fn check_bid(&self, header: &ExecutionPayloadHeader) -> Result<(), BuilderApiError> {
+        let fee_recipient = Address::from_slice(header.fee_recipient.as_ref());
+        if self.fee_recipients.check(&self.url(), FeeRecipientSource::Bid, fee_recipient) {
+            Ok(())
+        } else {
+            Err(BuilderApiError::FeeRecipientMismatch(fee_recipient))
+        }
+    }
+
+    /// Re-sends the delegations that the current relay has not acknowledged yet,
+    /// returning the ones it persistently refuses.
+    pub async fn reconcile_delegations(&self) -> Vec<DelegationState> {
+        let relay = self.url();
+
+        let missing = self.delegation_tracker.missing(&relay, &self.delegations.active());
+        if missing.is_empty() {
+            return Vec::new();
+        }
+
+        if let Err(err) = self.delegate(&missing).await {
+            warn!(?err, %relay, count = missing.len(), "Failed to re-send missing delegations");
+        }
+
+        self.delegation_tracker.persistently_refused(&relay)
+    }
+
+    /// Re-sends the saved validator registrations to the current relay, along with their
+    /// delegations, then the delegations it has not acknowledged yet, so that a restarted
+    /// sidecar is usable for the next proposal slot without waiting for the next
+    /// registration cycle of the beacon node.
+    pub async fn replay_registrations(&self) -> Result<(), BuilderApiError> {
+        let registrations = match self.registrations.as_ref().map(|store| store.load()) {
+            Some(Ok(registrations)) => registrations,
+            Some(Err(err)) => {
+                warn!(?err, "Failed to load the saved validator registrations");
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+
+        if !registrations.is_empty() {
+            debug!(count = registrations.len(), "Replaying saved validator registrations");
+            self.register_validators(registrations).await?;
+        }
+
+        let relay = self.url();
+        let missing = self.delegation_tracker.missing(&relay, &self.delegations.active());
+        if !missing.is_empty() {
+            debug!(count = missing.len(), "Replaying delegations");
+            self.delegate(&missing).await?;
+        }
+
+        let revocations = self.delegations.revocations();
+        if !revocations.is_empty() {
+            debug!(count = revocations.len(), "Replaying revocations");
+            self.revoke(&revocations).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-sends every active delegation and revocation to the current relay, e.g. once it is
+    /// reachable again after an outage, during which it may have lost them.
+    pub async fn rebroadcast_delegations(&self) {
+        let relay = self.url();
+        let (delegations, revocations) =
+            (self.delegations.active(), self.delegations.revocations());
+
+        if !delegations.is_empty() {
+            if let Err(err) = self.delegate(&delegations).await {
+                warn!(%relay, ?err, "Failed to re-broadcast the delegations");
+            }
+        }
+        if !revocations.is_empty() {
+            if let Err(err) = self.revoke(&revocations).await {
+                warn!(%relay, ?err, "Failed to re-broadcast the revocations");
+            }
+        }
+
+        info!(
+            %relay,
+            delegations = delegations.len(),
+            revocations = revocations.len(),
+            "Re-broadcast the delegations to the relay"
+        );
+    }
+
+    /// Posts the constraints to the current relay.
+    async fn post_constraints(
+        &self,
+        constraints: &BatchedSignedConstraints,
+    ) -> Result<(), BuilderApiError> {
+        let json = Bytes::from(serde_json::to_vec(&constraints)?);
+        let response = self
+            .send_negotiated(
+                "submit_constraints",
+                true,
+                || constraints.to_ssz_bytes(),
+                json,
+                |content_type, body| {
+                    let request = self
+                        .client
+                        .post(self.endpoint(SUBMIT_CONSTRAINTS_PATH))
+                        .header(header::CONTENT_TYPE, content_type);
+                    self.authenticated(request, SUBMIT_CONSTRAINTS_PATH, &body).body(body)
+                },
+            )
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            let error = response.json::<ErrorResponse>().await?;
+            return Err(BuilderApiError::FailedSubmittingConstraints(error));
+        }
+
+        Ok(())
+    }
+
+    /// Posts the delegations to the current relay.
+    async fn post_delegations(
+        &self,
+        signed_data: &[SignedDelegation],
+    ) -> Result<(), BuilderApiError> {
+        let body = serde_json::to_string(signed_data)?;
+        let response = self
+            .send_rate_limited("delegate", true, || {
+                let request = self
+                    .client
+                    .post(self.endpoint(DELEGATE_PATH))
+                    .header("content-type", "application/json");
+                self.authenticated(request, DELEGATE_PATH, body.as_bytes()).body(body.clone())
+            })
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            let error = response.json::<ErrorResponse>().await?;
+            return Err(BuilderApiError::FailedDelegating(error));
+        }
+
+        Ok(())
+    }
+
+    /// Sends the request built by `request` to the `endpoint` of the current relay, with
+    /// the adaptive timeout of the endpoint, recording its latency, and its rate limit if it
+    /// responds with `429 Too Many Requests`.
+    ///
+    /// If `reschedule` is set, the request is held back while the relay is rate-limited
+    /// and sent again once it accepts requests, up to [MAX_RATE_LIMIT_RETRIES] times and for
+    /// at most [MAX_RATE_LIMIT_WAIT]. Otherwise, it fails right away when rate-limited.
+    async fn send_rate_limited<F>(
+        &self,
+        endpoint: &'static str,
+        reschedule: bool,
+        request: F,
+    ) -> Result<Response, BuilderApiError>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let relay = self.url();
+        let (mut waited, mut retries) = (Duration::ZERO, 0);
+
+        loop {
+            if let Some(remaining) = self.throttle.remaining(&relay).filter(|_| reschedule) {
+                if waited + remaining > MAX_RATE_LIMIT_WAIT {
+                    return Err(BuilderApiError::RateLimited(remaining));
+                }
+
+                debug!(%relay, ?remaining, "Relay is rate-limited, holding back request");
+                sleep(remaining).await;
+                waited += remaining;
+            }
+
+            // Each call runs in its own span, whose trace is attached to its latency as exemplar
+            let span = info_span!("relay_call", %relay, endpoint);
+            let timeout = self.timeouts.timeout(&relay, endpoint);
+            let start = Instant::now();
+            let response = request().timeout(timeout).send().instrument(span.clone()).await;
+            let latency = start.elapsed();
+            let status = match &response {
+                Ok(response) => {
+                    self.timeouts.record(&relay, endpoint, latency);
+                    response.status().as_u16().to_string()
+                }
+                Err(err) if err.is_timeout() => {
+                    self.timeouts.record(&relay, endpoint, timeout);
+                    "timeout".to_string()
+                }
+                Err(_) => "error".to_string(),
+            };
+            span.in_scope(|| {
+                ApiMetrics::observe_relay_call(latency, relay.to_string(), endpoint, status)
+            });
+
+            let response = response?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                self.throttle.clear(&relay);
+                return Ok(response);
+            }
+
+            let retry_after = parse_retry_after(response.headers());
+            self.throttle.record(&relay, retry_after);
+
+            if !reschedule || retries >= MAX_RATE_LIMIT_RETRIES {
+                return Err(BuilderApiError::RateLimited(retry_after));
+            }
+
+            warn!(%relay, ?retry_after, "Relay rate-limited the request, rescheduling it");
+            retries += 1;
+        }
+    }
+
+    /// Sends a request to the `endpoint` of the current relay like
+    /// [send_rate_limited](ConstraintsClient::send_rate_limited), with the SSZ encoding of
+    /// the body if the relay advertises it and the body has one, and its JSON one otherwise.
+    ///
+    /// Relays rejecting the SSZ body with `415 Unsupported Media Type` or `406 Not Acceptable`
+    /// get the JSON one right away, and only JSON ones until their capabilities are fetched
+    /// again.
+    async fn send_negotiated<S, F>(
+        &self,
+        endpoint: &'static str,
+        reschedule: bool,
+        ssz: S,
+        json: Bytes,
+        request: F,
+    ) -> Result<Response, BuilderApiError>
+    where
+        S: FnOnce() -> Result<Vec<u8>, SszError>,
+        F: Fn(&'static str, Bytes) -> RequestBuilder,
+    {
+        let relay = self.url();
+
+        if self.capabilities.get(&relay).supports_encoding(SSZ_ENCODING) {
+            match ssz() {
+                Ok(body) => {
+                    let body = Bytes::from(body);
+                    let response = self
+                        .send_rate_limited(endpoint, reschedule, || {
+                            request(SSZ_CONTENT_TYPE, body.clone())
+                        })
+                        .await?;
+
+                    if !matches!(
+                        response.status(),
+                        StatusCode::UNSUPPORTED_MEDIA_TYPE | StatusCode::NOT_ACCEPTABLE
+                    ) {
+                        return Ok(response);
+                    }
+
+                    warn!(%relay, endpoint, "Relay rejected an SSZ request, falling back to JSON");
+                    self.capabilities.disable_encoding(&relay, SSZ_ENCODING);
+                }
+                Err(err) => debug!(?err, endpoint, "Request has no SSZ encoding, sending JSON"),
+            }
+        }
+
+        self.send_rate_limited(endpoint, reschedule, || request(JSON_CONTENT_TYPE, json.clone()))
+            .await
+    }
+
+    /// Adds the authentication headers of a submission to `path`, if submissions are signed.
+    fn authenticated(&self, request: RequestBuilder, path: &str, body: &[u8]) -> RequestBuilder {
+        match self.request_signer.as_ref() {
+            Some(signer) => signer.sign(request, path, body),
+            None => request,
+        }
     }
 
     fn endpoint(&self, path: &str) -> Url {
-        self.url.join(path).unwrap_or_else(|e| {
-            error!(err = ?e, "Failed to join path: {} with url: {}", path, self.url);
-            self.url.clone()
+        let url = self.url();
+        url.join(path).unwrap_or_else(|e| {
+            error!(err = ?e, "Failed to join path: {} with url: {}", path, url);
+            url.clone()
         })
     }
 }
@@ -87,12 +538,43 @@ impl BuilderApi for ConstraintsClient {
         &self,
         registrations: Vec<SignedValidatorRegistration>,
     ) -> Result<(), BuilderApiError> {
+        let relay = self.url();
+        let mut rejected = None;
+        let registrations = registrations
+            .into_iter()
+            .filter(|r| {
+                let fee_recipient = Address::from_slice(r.message.fee_recipient.as_ref());
+                let allowed = self.fee_recipients.check(
+                    &relay,
+                    FeeRecipientSource::Registration,
+                    fee_recipient,
+                );
+                if !allowed {
+                    rejected = Some(fee_recipient);
+                }
+                allowed
+            })
+            .collect::<Vec<_>>();
+
+        // Don't forward an empty batch if every registration was rejected
+        if let (true, Some(fee_recipient)) = (registrations.is_empty(), rejected) {
+            return Err(BuilderApiError::FeeRecipientMismatch(fee_recipient));
+        }
+
+        let json = Bytes::from(serde_json::to_vec(&registrations)?);
         let response = self
-            .client
-            .post(self.endpoint(REGISTER_VALIDATORS_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_vec(&registrations)?)
-            .send()
+            .send_negotiated(
+                "register_validators",
+                true,
+                || encode_registrations(&registrations),
+                json,
+                |content_type, body| {
+                    self.client
+                        .post(self.endpoint(REGISTER_VALIDATORS_PATH))
+                        .header(header::CONTENT_TYPE, content_type)
+                        .body(body)
+                },
+            )
             .await?;
 
         if response.status() != StatusCode::OK {
@@ -100,17 +582,23 @@ impl BuilderApi for ConstraintsClient {
             return Err(BuilderApiError::FailedRegisteringValidators(error));
         }
 
-        // If there are any delegations, propagate the one associated to the incoming registrations to the relay
-        if self.delegations.is_empty() {
+        if let Some(store) = self.registrations.as_ref() {
+            if let Err(err) = store.record(&registrations) {
+                warn!(?err, path = %store.path().display(), "Failed to save validator registrations");
+            }
+        }
+
+        // If there are any delegations, propagate the one associated to the incoming registrations
+        // to the relay
+        let delegations = self.delegations.active();
+        if delegations.is_empty() {
             return Ok(());
         } else {
             let validator_pubkeys =
                 registrations.iter().map(|r| r.message.public_key.clone()).collect::<HashSet<_>>();
-            let filtered_delegations = self
-                .delegations
-                .iter()
+            let filtered_delegations = delegations
+                .into_iter()
                 .filter(|d| validator_pubkeys.contains(&d.message.validator_pubkey))
-                .cloned()
                 .collect::<Vec<_>>();
 
             if let Err(err) = self.delegate(&filtered_delegations).await {
@@ -129,14 +617,11 @@ impl BuilderApi for ConstraintsClient {
         let parent_hash = format!("0x{}", hex::encode(params.parent_hash.as_ref()));
         let public_key = format!("0x{}", hex::encode(params.public_key.as_ref()));
 
+        let path = format!("/eth/v1/builder/header/{}/{}/{}", params.slot, parent_hash, public_key);
         let response = self
-            .client
-            .get(self.endpoint(&format!(
-                "/eth/v1/builder/header/{}/{}/{}",
-                params.slot, parent_hash, public_key
-            )))
-            .header("content-type", "application/json")
-            .send()
+            .send_rate_limited("get_header", false, || {
+                self.client.get(self.endpoint(&path)).header("content-type", "application/json")
+            })
             .await?;
 
         if response.status() != StatusCode::OK {
@@ -144,22 +629,40 @@ impl BuilderApi for ConstraintsClient {
             return Err(BuilderApiError::FailedGettingHeader(error));
         }
 
-        let header = response.json::<SignedBuilderBid>().await?;
+        let body = response.bytes().await?;
+        let bid: SignedBuilderBid = from_relay_json(&body, self.url().as_str(), "get_header")?;
+        self.check_bid(&bid.message.header)?;
 
-        Ok(header)
+        Ok(bid)
     }
 
     /// Implements: <https://ethereum.github.io/builder-specs/#/Builder/submitBlindedBlock>
     async fn get_payload(
         &self,
-        signed_block: SignedBlindedBeaconBlock,
+        signed_block: VersionedSignedBlindedBlock,
     ) -> Result<GetPayloadResponse, BuilderApiError> {
+        let fork = signed_block.fork().to_string();
         let response = self
-            .client
-            .post(self.endpoint(GET_PAYLOAD_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_vec(&signed_block)?)
-            .send()
+            .send_negotiated(
+                "get_payload",
+                false,
+                || signed_block.to_ssz_bytes(),
+                signed_block.as_json().clone(),
+                |content_type, body| {
+                    // Relays answer in the encoding of the request, falling back to JSON
+                    let accept = if content_type == SSZ_CONTENT_TYPE {
+                        "application/octet-stream;q=1.0,application/json;q=0.9"
+                    } else {
+                        JSON_CONTENT_TYPE
+                    };
+                    self.client
+                        .post(self.endpoint(GET_PAYLOAD_PATH))
+                        .header(header::CONTENT_TYPE, content_type)
+                        .header(header::ACCEPT, accept)
+                        .header(CONSENSUS_VERSION_HEADER, &fork)
+                        .body(body)
+                },
+            )
             .await?;
 
         if response.status() != StatusCode::OK {
@@ -167,7 +670,17 @@ impl BuilderApi for ConstraintsClient {
             return Err(BuilderApiError::FailedGettingPayload(error));
         }
 
-        let payload = response.json().await?;
+        let ssz = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with(SSZ_CONTENT_TYPE));
+        let body = response.bytes().await?;
+        if ssz {
+            return Ok(GetPayloadResponse::from_ssz_bytes(signed_block.fork(), &body)?);
+        }
+
+        let payload = from_relay_json(&body, self.url().as_str(), "get_payload")?;
 
         Ok(payload)
     }
@@ -179,37 +692,30 @@ impl ConstraintsApi for ConstraintsClient {
         &self,
         constraints: &BatchedSignedConstraints,
     ) -> Result<(), BuilderApiError> {
-        let response = self
-            .client
-            .post(self.endpoint(SUBMIT_CONSTRAINTS_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_vec(&constraints)?)
-            .send()
-            .await?;
+        let sent_at_ms = now_ms();
+        let result = self.post_constraints(constraints).await;
 
-        if response.status() != StatusCode::OK {
-            let error = response.json::<ErrorResponse>().await?;
-            return Err(BuilderApiError::FailedSubmittingConstraints(error));
-        }
+        let outcome = result.as_ref().map(|_| ()).map_err(|err| err.to_string());
+        self.submissions.record(&self.url(), constraints, sent_at_ms, outcome);
 
-        Ok(())
+        result
     }
 
     async fn get_header_with_proofs(
         &self,
         params: GetHeaderParams,
-    ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError> {
+    ) -> Result<VersionedSignedBuilderBid, BuilderApiError> {
         let parent_hash = format!("0x{}", hex::encode(params.parent_hash.as_ref()));
         let public_key = format!("0x{}", hex::encode(params.public_key.as_ref()));
 
+        let path = format!(
+            "/eth/v1/builder/header_with_proofs/{}/{}/{}",
+            params.slot, parent_hash, public_key,
+        );
         let response = self
-            .client
-            .get(self.endpoint(&format!(
-                "/eth/v1/builder/header_with_proofs/{}/{}/{}",
-                params.slot, parent_hash, public_key,
-            )))
-            .header("content-type", "application/json")
-            .send()
+            .send_rate_limited("get_header_with_proofs", false, || {
+                self.client.get(self.endpoint(&path)).header("content-type", "application/json")
+            })
             .await?;
 
         if response.status() != StatusCode::OK {
@@ -217,41 +723,47 @@ impl ConstraintsApi for ConstraintsClient {
             return Err(BuilderApiError::FailedGettingHeader(error));
         }
 
-        let header = response.json::<VersionedValue<SignedBuilderBid>>().await?;
+        let body = response.bytes().await?;
 
-        if !matches!(header.version, Fork::Deneb) {
-            return Err(BuilderApiError::InvalidFork(header.version.to_string()));
+        // Check the fork first, so that bids of unsupported forks aren't reported as
+        // malformed responses
+        let ForkVersion { version } = serde_json::from_slice(&body)?;
+        if !VersionedSignedBuilderBid::SUPPORTED_FORKS.contains(&version) {
+            return Err(BuilderApiError::InvalidFork(version.to_string()));
         };
 
+        let bid: VersionedSignedBuilderBid =
+            from_relay_json(&body, self.url().as_str(), "get_header_with_proofs")?;
+        self.check_bid(bid.header())?;
+
         // TODO: verify proofs here?
 
-        Ok(header)
+        Ok(bid)
     }
 
     async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
-        let response = self
-            .client
-            .post(self.endpoint(DELEGATE_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_string(signed_data)?)
-            .send()
-            .await?;
+        let relay = self.url();
+        let result = self.post_delegations(signed_data).await;
 
-        if response.status() != StatusCode::OK {
-            let error = response.json::<ErrorResponse>().await?;
-            return Err(BuilderApiError::FailedDelegating(error));
-        }
+        let outcome = match &result {
+            Ok(()) => Ok(()),
+            Err(err) => Err((matches!(err, BuilderApiError::FailedDelegating(_)), err.to_string())),
+        };
+        self.delegation_tracker.record(&relay, signed_data, outcome);
 
-        Ok(())
+        result
     }
 
     async fn revoke(&self, signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError> {
+        let body = serde_json::to_string(signed_data)?;
         let response = self
-            .client
-            .post(self.endpoint(REVOKE_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_string(signed_data)?)
-            .send()
+            .send_rate_limited("revoke", true, || {
+                let request = self
+                    .client
+                    .post(self.endpoint(REVOKE_PATH))
+                    .header("content-type", "application/json");
+                self.authenticated(request, REVOKE_PATH, body.as_bytes()).body(body.clone())
+            })
             .await?;
 
         if response.status() != StatusCode::OK {
@@ -263,11 +775,20 @@ impl ConstraintsApi for ConstraintsClient {
     }
 }
 
+/// The fork version of a versioned relay response.
+#[derive(serde::Deserialize)]
+struct ForkVersion {
+    version: Fork,
+}
+
 #[cfg(test)]
 mod tests {
+    use axum::{http::HeaderMap, routing::post, Router};
+    use parking_lot::Mutex;
     use reqwest::Url;
 
-    use super::ConstraintsClient;
+    use super::*;
+    use crate::client::capabilities::JSON_ENCODING;
 
     #[test]
     fn test_join_endpoints() {
@@ -282,4 +803,39 @@ mod tests {
             Url::parse("http://localhost:8080/eth/v1/builder/validators").unwrap()
         );
     }
+
+    #[tokio::test]
+    async fn test_ssz_fallback_to_json() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = received.clone();
+        let router = Router::new().route(
+            SUBMIT_CONSTRAINTS_PATH,
+            post(move |headers: HeaderMap| async move {
+                let content_type = headers[header::CONTENT_TYPE].to_str().unwrap().to_string();
+                recorded.lock().push(content_type.clone());
+                if content_type == SSZ_CONTENT_TYPE {
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE
+                } else {
+                    StatusCode::OK
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let client = ConstraintsClient::new(url.clone());
+        let encodings = vec![SSZ_ENCODING.to_string(), JSON_ENCODING.to_string()];
+        client.capabilities.record(&url, Capabilities { encodings, ..Default::default() });
+
+        // The relay advertises SSZ but rejects it, so the submission is sent again as JSON
+        client.submit_constraints(&Vec::new()).await.unwrap();
+        assert_eq!(*received.lock(), vec![SSZ_CONTENT_TYPE, JSON_CONTENT_TYPE]);
+        assert!(!client.capabilities().supports_encoding(SSZ_ENCODING));
+
+        // and the next ones go straight to JSON
+        client.submit_constraints(&Vec::new()).await.unwrap();
+        assert_eq!(received.lock().len(), 3);
+        assert_eq!(received.lock()[2], JSON_CONTENT_TYPE);
+    }
 }
@@ -0,0 +1,266 @@
+//! Management of the delegations and revocations of the validators, saved on disk so that a
+//! restarted sidecar resolves the same delegatees and sends the same state to the relays.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{SignedDelegation, SignedRevocation};
+
+/// The latest signed message of a validator about one of its delegatees.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DelegationRecord {
+    /// The validator delegated to the delegatee.
+    Active {
+        delegation: SignedDelegation,
+        /// Whether the delegation was signed by the sidecar for the slots assigned to the
+        /// delegatee, rather than provided by the operator.
+        #[serde(default)]
+        generated: bool,
+    },
+    /// The validator revoked its delegation to the delegatee.
+    Revoked { revocation: SignedRevocation },
+}
+
+impl DelegationRecord {
+    /// Returns the public keys of the validator and of the delegatee.
+    pub fn key(&self) -> (&BlsPublicKey, &BlsPublicKey) {
+        match self {
+            Self::Active { delegation, .. } => {
+                (&delegation.message.validator_pubkey, &delegation.message.delegatee_pubkey)
+            }
+            Self::Revoked { revocation } => {
+                (&revocation.message.validator_pubkey, &revocation.message.delegatee_pubkey)
+            }
+        }
+    }
+}
+
+/// The delegations and revocations of the validators, deduplicated to the latest message of
+/// each validator about each delegatee.
+///
+/// Clones share the same state. If a path is set, the state is saved to it on every change,
+/// and loaded back by [DelegationManager::open] on startup.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationManager {
+    records: Arc<RwLock<Vec<DelegationRecord>>>,
+    path: Option<PathBuf>,
+}
+
+impl DelegationManager {
+    /// Opens the state saved at the given path, or an empty one if the file doesn't exist
+    /// yet. The file is created on the first change.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let records = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self { records: Arc::new(RwLock::new(records)), path: Some(path) })
+    }
+
+    /// Returns the path of the file the state is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Adds the delegations provided by the operator, e.g. from the delegations file.
+    ///
+    /// They replace the earlier delegations of the same validators to the same delegatees,
+    /// but not their revocations, which are kept until a delegation is recorded again.
+    pub fn add_delegations(&self, delegations: Vec<SignedDelegation>) -> io::Result<()> {
+        self.update(|records| {
+            for delegation in delegations {
+                let record = DelegationRecord::Active { delegation, generated: false };
+                match position(records, &record) {
+                    Some(i) if matches!(records[i], DelegationRecord::Revoked { .. }) => {}
+                    Some(i) => records[i] = record,
+                    None => records.push(record),
+                }
+            }
+        })
+    }
+
+    /// Records a delegation signed by the sidecar for the slots assigned to the delegatee.
+    pub fn record_generated(&self, delegation: SignedDelegation) -> io::Result<()> {
+        self.upsert(DelegationRecord::Active { delegation, generated: true })
+    }
+
+    /// Records a revocation, which deactivates the delegation it revokes.
+    pub fn record_revocation(&self, revocation: SignedRevocation) -> io::Result<()> {
+        self.upsert(DelegationRecord::Revoked { revocation })
+    }
+
+    /// Returns the active delegations, provided or generated, to send to the relays.
+    pub fn active(&self) -> Vec<SignedDelegation> {
+        self.records
+            .read()
+            .iter()
+            .filter_map(|record| match record {
+                DelegationRecord::Active { delegation, .. } => Some(delegation.clone()),
+                DelegationRecord::Revoked { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns the revocations, to send to the relays.
+    pub fn revocations(&self) -> Vec<SignedRevocation> {
+        self.records
+            .read()
+            .iter()
+            .filter_map(|record| match record {
+                DelegationRecord::Revoked { revocation } => Some(revocation.clone()),
+                DelegationRecord::Active { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Returns the delegatees of the active delegations provided for the validator, in the
+    /// order they were first recorded.
+    pub fn delegatees(&self, validator_pubkey: &BlsPublicKey) -> Vec<BlsPublicKey> {
+        self.records
+            .read()
+            .iter()
+            .filter_map(|record| match record {
+                DelegationRecord::Active { delegation, generated: false }
+                    if delegation.message.validator_pubkey == *validator_pubkey =>
+                {
+                    Some(delegation.message.delegatee_pubkey.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the active delegation provided for the validator to the delegatee, if any.
+    pub fn find(
+        &self,
+        validator_pubkey: &BlsPublicKey,
+        delegatee_pubkey: &BlsPublicKey,
+    ) -> Option<SignedDelegation> {
+        self.records.read().iter().find_map(|record| match record {
+            DelegationRecord::Active { delegation, generated: false }
+                if record.key() == (validator_pubkey, delegatee_pubkey) =>
+            {
+                Some(delegation.clone())
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns true if the sidecar signed an active delegation of the validator to the
+    /// delegatee, which it must revoke once no upcoming slot is assigned to the delegatee.
+    pub fn is_generated(
+        &self,
+        validator_pubkey: &BlsPublicKey,
+        delegatee_pubkey: &BlsPublicKey,
+    ) -> bool {
+        self.records.read().iter().any(|record| {
+            matches!(record, DelegationRecord::Active { generated: true, .. }) &&
+                record.key() == (validator_pubkey, delegatee_pubkey)
+        })
+    }
+
+    /// Returns every record, in the order they were first recorded.
+    pub fn records(&self) -> Vec<DelegationRecord> {
+        self.records.read().clone()
+    }
+
+    fn upsert(&self, record: DelegationRecord) -> io::Result<()> {
+        self.update(|records| match position(records, &record) {
+            Some(i) => records[i] = record,
+            None => records.push(record),
+        })
+    }
+
+    /// Applies a change to the records, then saves them if a path is set. The file is
+    /// replaced atomically.
+    fn update(&self, change: impl FnOnce(&mut Vec<DelegationRecord>)) -> io::Result<()> {
+        let mut records = self.records.write();
+        change(&mut records);
+
+        let Some(path) = self.path.as_ref() else { return Ok(()) };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        let file = File::create(&tmp)?;
+        serde_json::to_writer(BufWriter::new(&file), &*records)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+}
+
+fn position(records: &[DelegationRecord], record: &DelegationRecord) -> Option<usize> {
+    records.iter().position(|r| r.key() == record.key())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        primitives::{DelegationMessage, RevocationMessage},
+        signer::local::LocalSigner,
+    };
+
+    use super::*;
+
+    fn delegation(validator: &LocalSigner, delegatee: &LocalSigner) -> SignedDelegation {
+        SignedDelegation {
+            message: DelegationMessage::new(validator.pubkey(), delegatee.pubkey()),
+            signature: Default::default(),
+        }
+    }
+
+    fn revocation(validator: &LocalSigner, delegatee: &LocalSigner) -> SignedRevocation {
+        SignedRevocation {
+            message: RevocationMessage::new(validator.pubkey(), delegatee.pubkey()),
+            signature: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_delegation_manager() {
+        let dir = std::env::temp_dir().join(format!("bolt-delegations-{}", std::process::id()));
+        let path = dir.join("delegations.json");
+        let manager = DelegationManager::open(&path).unwrap();
+
+        let validator = LocalSigner::random();
+        let (alice, bob, carol) =
+            (LocalSigner::random(), LocalSigner::random(), LocalSigner::random());
+
+        // Duplicated delegations are only kept once
+        let provided = vec![delegation(&validator, &alice), delegation(&validator, &bob)];
+        manager.add_delegations(provided.clone()).unwrap();
+        manager.add_delegations(provided.clone()).unwrap();
+        assert_eq!(manager.active(), provided);
+        assert_eq!(manager.delegatees(&validator.pubkey()), vec![alice.pubkey(), bob.pubkey()]);
+
+        // Generated delegations are sent to the relays, but are not delegatees of their own
+        manager.record_generated(delegation(&validator, &carol)).unwrap();
+        assert_eq!(manager.active().len(), 3);
+        assert!(manager.is_generated(&validator.pubkey(), &carol.pubkey()));
+        assert!(manager.find(&validator.pubkey(), &carol.pubkey()).is_none());
+
+        // Revocations deactivate the delegations, even if provided again
+        manager.record_revocation(revocation(&validator, &alice)).unwrap();
+        manager.add_delegations(provided.clone()).unwrap();
+        assert_eq!(manager.delegatees(&validator.pubkey()), vec![bob.pubkey()]);
+        assert_eq!(manager.revocations(), vec![revocation(&validator, &alice)]);
+
+        // The state is loaded back from disk
+        let reopened = DelegationManager::open(&path).unwrap();
+        assert_eq!(reopened.records(), manager.records());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
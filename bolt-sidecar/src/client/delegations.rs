@@ -0,0 +1,212 @@
+//! Tracking of the delegations acknowledged by each relay, to re-send the missing ones
+//! and to surface the relays that persistently refuse them.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use parking_lot::RwLock;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{primitives::SignedDelegation, telemetry::ApiMetrics};
+
+/// The interval at which the delegations missing on the relay are re-sent.
+pub const RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The number of consecutive refusals after which a relay is considered to
+/// persistently refuse a delegation.
+pub const PERSISTENT_REFUSALS: u32 = 3;
+
+/// The status of a delegation on a relay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelegationStatus {
+    /// The delegation was not acknowledged yet.
+    Pending,
+    /// The relay acknowledged the delegation.
+    Acknowledged,
+    /// The relay refused the delegation the last time it was sent.
+    Refused,
+}
+
+/// The state of a delegation on a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegationState {
+    /// The public key of the delegating validator.
+    pub validator_pubkey: BlsPublicKey,
+    /// The public key of the delegatee.
+    pub delegatee_pubkey: BlsPublicKey,
+    /// The status of the delegation.
+    pub status: DelegationStatus,
+    /// The number of consecutive times the relay refused the delegation.
+    pub refusals: u32,
+    /// The last error returned when sending the delegation, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl DelegationState {
+    /// Returns true if the relay persistently refuses the delegation.
+    pub fn is_persistently_refused(&self) -> bool {
+        self.status == DelegationStatus::Refused && self.refusals >= PERSISTENT_REFUSALS
+    }
+}
+
+/// The state of the delegations on a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayDelegations {
+    /// The URL of the relay.
+    pub relay: Url,
+    /// The state of every delegation sent to the relay.
+    pub delegations: Vec<DelegationState>,
+}
+
+type DelegationKey = (BlsPublicKey, BlsPublicKey);
+
+/// Tracks the delegations acknowledged by each relay.
+///
+/// Clones share the same state, so that the constraints client records the outcome
+/// of the delegations it sends while the admin API reads them.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationTracker {
+    relays: Arc<RwLock<HashMap<Url, HashMap<DelegationKey, DelegationState>>>>,
+}
+
+impl DelegationTracker {
+    /// Records the outcome of sending the delegations to the relay: `Ok` if the relay
+    /// acknowledged them, `Err` with whether it refused them and the error otherwise.
+    ///
+    /// Only refusals count towards [PERSISTENT_REFUSALS], failures to reach the relay don't.
+    pub fn record(
+        &self,
+        relay: &Url,
+        delegations: &[SignedDelegation],
+        outcome: Result<(), (bool, String)>,
+    ) {
+        let mut relays = self.relays.write();
+        let states = relays.entry(relay.clone()).or_default();
+
+        for delegation in delegations {
+            let (validator_pubkey, delegatee_pubkey) = key(delegation);
+            let state = states
+                .entry((validator_pubkey.clone(), delegatee_pubkey.clone()))
+                .or_insert_with(|| DelegationState {
+                    validator_pubkey,
+                    delegatee_pubkey,
+                    status: DelegationStatus::Pending,
+                    refusals: 0,
+                    last_error: None,
+                });
+
+            match &outcome {
+                Ok(()) => {
+                    state.status = DelegationStatus::Acknowledged;
+                    state.refusals = 0;
+                    state.last_error = None;
+                }
+                Err((refused, error)) => {
+                    if *refused {
+                        state.status = DelegationStatus::Refused;
+                        state.refusals += 1;
+                        ApiMetrics::increment_delegations_refused(relay.to_string());
+                    }
+                    state.last_error = Some(error.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns the delegations that the relay has not acknowledged yet.
+    pub fn missing(&self, relay: &Url, delegations: &[SignedDelegation]) -> Vec<SignedDelegation> {
+        let relays = self.relays.read();
+        let states = relays.get(relay);
+
+        delegations
+            .iter()
+            .filter(|delegation| {
+                let state = states.and_then(|states| states.get(&key(delegation)));
+                state.map_or(true, |state| state.status != DelegationStatus::Acknowledged)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the delegations that the relay persistently refuses.
+    pub fn persistently_refused(&self, relay: &Url) -> Vec<DelegationState> {
+        let relays = self.relays.read();
+        let Some(states) = relays.get(relay) else { return Vec::new() };
+
+        states.values().filter(|state| state.is_persistently_refused()).cloned().collect()
+    }
+
+    /// Returns the state of the delegations on every relay they were sent to.
+    pub fn snapshot(&self) -> Vec<RelayDelegations> {
+        let mut snapshot = self
+            .relays
+            .read()
+            .iter()
+            .map(|(relay, states)| RelayDelegations {
+                relay: relay.clone(),
+                delegations: states.values().cloned().collect(),
+            })
+            .collect::<Vec<_>>();
+
+        snapshot.sort_by(|a, b| a.relay.cmp(&b.relay));
+        snapshot
+    }
+}
+
+fn key(delegation: &SignedDelegation) -> DelegationKey {
+    (delegation.message.validator_pubkey.clone(), delegation.message.delegatee_pubkey.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::DelegationMessage;
+
+    use super::*;
+
+    fn delegation(byte: u8) -> SignedDelegation {
+        let validator = BlsPublicKey::try_from([byte; 48].as_slice()).unwrap();
+        SignedDelegation {
+            message: DelegationMessage::new(validator, BlsPublicKey::default()),
+            signature: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_delegation_acknowledgements() {
+        let tracker = DelegationTracker::default();
+        let (relay, other) = (
+            Url::parse("http://relay-a:3030").unwrap(),
+            Url::parse("http://relay-b:3030").unwrap(),
+        );
+        let delegations = vec![delegation(1), delegation(2)];
+
+        assert_eq!(tracker.missing(&relay, &delegations).len(), 2);
+
+        tracker.record(&relay, &delegations[..1], Ok(()));
+        assert_eq!(tracker.missing(&relay, &delegations), vec![delegations[1].clone()]);
+        assert_eq!(tracker.missing(&other, &delegations).len(), 2);
+
+        // Failures to reach the relay are not refusals
+        tracker.record(&relay, &delegations[1..], Err((false, "timeout".to_string())));
+        assert!(tracker.persistently_refused(&relay).is_empty());
+
+        for _ in 0..PERSISTENT_REFUSALS {
+            tracker.record(&relay, &delegations[1..], Err((true, "unknown validator".to_string())));
+        }
+        let refused = tracker.persistently_refused(&relay);
+        assert_eq!(refused.len(), 1);
+        assert_eq!(refused[0].refusals, PERSISTENT_REFUSALS);
+
+        // An acknowledgement resets the refusals
+        tracker.record(&relay, &delegations[1..], Ok(()));
+        assert!(tracker.missing(&relay, &delegations).is_empty());
+        assert!(tracker.persistently_refused(&relay).is_empty());
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].delegations.len(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+//! Checks of the fee recipients of the validator registrations forwarded to each relay and
+//! of the bids it returns, against the fee recipients expected by the operator.
+
+use std::{collections::HashMap, sync::Arc};
+
+use alloy::primitives::Address;
+use parking_lot::RwLock;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    config::{FeeRecipientMismatchAction, FeeRecipientOpts},
+    telemetry::ApiMetrics,
+};
+
+/// What a checked fee recipient comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRecipientSource {
+    /// A validator registration forwarded to the relay.
+    Registration,
+    /// A bid returned by the relay.
+    Bid,
+}
+
+impl FeeRecipientSource {
+    /// Returns the label of the source in the metrics.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Registration => "registration",
+            Self::Bid => "bid",
+        }
+    }
+}
+
+/// The results of the fee recipient checks of a source.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeRecipientChecks {
+    /// The number of fee recipients checked so far.
+    pub checked: u64,
+    /// The number of them that were not expected.
+    pub mismatched: u64,
+    /// The last unexpected fee recipient, if any.
+    pub last_mismatch: Option<Address>,
+}
+
+impl FeeRecipientChecks {
+    fn record(&mut self, fee_recipient: Address, expected: bool) {
+        self.checked += 1;
+        if !expected {
+            self.mismatched += 1;
+            self.last_mismatch = Some(fee_recipient);
+        }
+    }
+}
+
+/// The results of the fee recipient checks of a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayFeeRecipients {
+    /// The URL of the relay.
+    pub relay: Url,
+    /// The checks of the validator registrations forwarded to the relay.
+    pub registrations: FeeRecipientChecks,
+    /// The checks of the bids returned by the relay.
+    pub bids: FeeRecipientChecks,
+}
+
+/// Checks the fee recipients of the registrations and bids against the expected ones,
+/// keeping the results of each relay.
+///
+/// Clones share the same results, so that the constraints client records the checks while
+/// the admin API reads them.
+#[derive(Debug, Clone, Default)]
+pub struct FeeRecipientGuard {
+    opts: Arc<FeeRecipientOpts>,
+    relays: Arc<RwLock<HashMap<Url, RelayFeeRecipients>>>,
+}
+
+impl FeeRecipientGuard {
+    /// Creates a guard checking the fee recipients against the given options.
+    pub fn new(opts: FeeRecipientOpts) -> Self {
+        Self { opts: Arc::new(opts), relays: Default::default() }
+    }
+
+    /// Checks the fee recipient of a registration or bid of the relay, returning whether it
+    /// may go through: always if it is expected or if mismatches are only alerted on.
+    ///
+    /// Nothing is checked if no fee recipient is expected.
+    pub fn check(&self, relay: &Url, source: FeeRecipientSource, fee_recipient: Address) -> bool {
+        if self.opts.expected_fee_recipients.is_empty() {
+            return true;
+        }
+
+        let expected = self.opts.expected_fee_recipients.contains(&fee_recipient);
+        let mut relays = self.relays.write();
+        let state = relays.entry(relay.clone()).or_insert_with(|| RelayFeeRecipients {
+            relay: relay.clone(),
+            registrations: FeeRecipientChecks::default(),
+            bids: FeeRecipientChecks::default(),
+        });
+        match source {
+            FeeRecipientSource::Registration => state.registrations.record(fee_recipient, expected),
+            FeeRecipientSource::Bid => state.bids.record(fee_recipient, expected),
+        }
+
+        ApiMetrics::increment_fee_recipient_checks(relay.to_string(), source.as_str(), expected);
+        if expected {
+            return true;
+        }
+
+        let action = self.opts.fee_recipient_mismatch;
+        error!(
+            %relay,
+            source = source.as_str(),
+            %fee_recipient,
+            expected = ?self.opts.expected_fee_recipients,
+            %action,
+            "Unexpected fee recipient"
+        );
+        action == FeeRecipientMismatchAction::Alert
+    }
+
+    /// Returns the results of the checks of every relay.
+    pub fn snapshot(&self) -> Vec<RelayFeeRecipients> {
+        let mut snapshot = self.relays.read().values().cloned().collect::<Vec<_>>();
+        snapshot.sort_by(|a, b| a.relay.as_str().cmp(b.relay.as_str()));
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_recipient_checks() {
+        let relay = Url::parse("http://relay:3030").unwrap();
+        let (expected, other) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        // Nothing is checked without expected fee recipients
+        let disabled = FeeRecipientGuard::default();
+        assert!(disabled.check(&relay, FeeRecipientSource::Bid, other));
+        assert!(disabled.snapshot().is_empty());
+
+        let opts = FeeRecipientOpts {
+            expected_fee_recipients: vec![expected],
+            fee_recipient_mismatch: FeeRecipientMismatchAction::Alert,
+        };
+        let alerting = FeeRecipientGuard::new(opts.clone());
+        assert!(alerting.check(&relay, FeeRecipientSource::Registration, expected));
+        assert!(alerting.check(&relay, FeeRecipientSource::Registration, other));
+
+        let snapshot = alerting.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].registrations.checked, 2);
+        assert_eq!(snapshot[0].registrations.mismatched, 1);
+        assert_eq!(snapshot[0].registrations.last_mismatch, Some(other));
+        assert_eq!(snapshot[0].bids, FeeRecipientChecks::default());
+
+        let rejecting = FeeRecipientGuard::new(FeeRecipientOpts {
+            fee_recipient_mismatch: FeeRecipientMismatchAction::Reject,
+            ..opts
+        });
+        assert!(rejecting.check(&relay, FeeRecipientSource::Bid, expected));
+        assert!(!rejecting.check(&relay, FeeRecipientSource::Bid, other));
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::telemetry::ApiMetrics;
+
+/// A deviation from the builder-specs JSON that relay responses are tolerated for.
+///
+/// Unknown fields need no tolerance, as they are ignored by the response types already.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tolerance {
+    /// Field names in another casing than `snake_case`, e.g. `blockHash` or `block-hash`.
+    FieldCasing,
+    /// Numbers sent as JSON numbers instead of decimal strings.
+    UnquotedNumbers,
+    /// Numbers sent as decimal strings where JSON numbers are expected.
+    QuotedNumbers,
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FieldCasing => "field casing",
+            Self::UnquotedNumbers => "unquoted numbers",
+            Self::QuotedNumbers => "quoted numbers",
+        })
+    }
+}
+
+/// The combinations of tolerances tried in order, from the least to the most intrusive.
+const ATTEMPTS: &[&[Tolerance]] = &[
+    &[Tolerance::FieldCasing],
+    &[Tolerance::FieldCasing, Tolerance::UnquotedNumbers],
+    &[Tolerance::FieldCasing, Tolerance::QuotedNumbers],
+];
+
+/// Deserializes the JSON response of a relay to `endpoint`, tolerating the slight deviations
+/// from the builder-specs JSON of real relays.
+///
+/// The response is first parsed strictly. If that fails, the [Tolerance]s are applied to it
+/// before parsing it again, with a warning naming the ones the response needed. The error of
+/// the strict parsing is returned if no tolerance helps.
+pub fn from_relay_json<T: DeserializeOwned>(
+    body: &[u8],
+    relay: &str,
+    endpoint: &'static str,
+) -> Result<T, serde_json::Error> {
+    let strict_err = match serde_json::from_slice(body) {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+
+    // Bodies that aren't JSON at all can't be tolerated
+    let Ok(raw) = serde_json::from_slice::<Value>(body) else { return Err(strict_err) };
+
+    for tolerances in ATTEMPTS {
+        let mut value = raw.clone();
+        for tolerance in *tolerances {
+            apply(&mut value, *tolerance);
+        }
+
+        if let Ok(parsed) = serde_json::from_value(value) {
+            let tolerances = tolerances.iter().map(ToString::to_string).collect::<Vec<_>>();
+            warn!(
+                relay,
+                endpoint,
+                ?tolerances,
+                error = %strict_err,
+                "Relay response deviates from the builder-specs, tolerated"
+            );
+            ApiMetrics::increment_relay_responses_tolerated(relay.to_string(), endpoint);
+            return Ok(parsed);
+        }
+    }
+
+    Err(strict_err)
+}
+
+/// Applies a tolerance to every field of the given JSON value, recursively.
+fn apply(value: &mut Value, tolerance: Tolerance) {
+    match value {
+        Value::Object(map) => {
+            if tolerance == Tolerance::FieldCasing {
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .map(|(key, value)| (to_snake_case(&key), value))
+                    .collect::<Map<_, _>>();
+            }
+            map.values_mut().for_each(|value| apply(value, tolerance));
+        }
+        Value::Array(values) => values.iter_mut().for_each(|value| apply(value, tolerance)),
+        Value::Number(number) if tolerance == Tolerance::UnquotedNumbers => {
+            *value = Value::String(number.to_string());
+        }
+        Value::String(string) if tolerance == Tolerance::QuotedNumbers => {
+            if let Ok(number) = string.parse::<u64>() {
+                *value = Value::from(number);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Converts a field name in `camelCase`, `PascalCase` or `kebab-case` to `snake_case`.
+fn to_snake_case(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if c == '-' {
+            snake.push('_');
+        } else if c.is_ascii_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::GetPayloadResponse;
+
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("blockHash"), "block_hash");
+        assert_eq!(to_snake_case("BlobKzgCommitments"), "blob_kzg_commitments");
+        assert_eq!(to_snake_case("gas-limit"), "gas_limit");
+        assert_eq!(to_snake_case("fee_recipient"), "fee_recipient");
+    }
+
+    #[test]
+    fn test_tolerated_payload_response() {
+        let body = std::fs::read("./src/client/test_util/deneb_get_payload_response.json")
+            .expect("failed to read get payload response file");
+        let mut value: Value = serde_json::from_slice(&body).unwrap();
+
+        // Alternative casing, an unquoted number and an unknown field
+        let data = value["data"].as_object_mut().unwrap();
+        let payload = data.remove("execution_payload").unwrap();
+        data.insert("executionPayload".to_string(), payload);
+        data["executionPayload"]["gas_limit"] = Value::from(30_000_000);
+        data.insert("relay_version".to_string(), Value::from("1.0"));
+
+        let deviating = serde_json::to_vec(&value).unwrap();
+        assert!(serde_json::from_slice::<GetPayloadResponse>(&deviating).is_err());
+
+        let parsed: GetPayloadResponse =
+            from_relay_json(&deviating, "http://relay", "get_payload").unwrap();
+        let expected: GetPayloadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), serde_json::to_value(expected).unwrap());
+
+        // Bodies that no tolerance fixes keep the strict error
+        assert!(
+            from_relay_json::<GetPayloadResponse>(b"{}", "http://relay", "get_payload").is_err()
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! An in-memory implementation of the Constraints API, with scripted responses and
+//! failure injection, to develop the driver behavior around relay failures against
+//! deterministic scenarios.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use ethereum_consensus::builder::SignedValidatorRegistration;
+use parking_lot::Mutex;
+
+use crate::{
+    api::{
+        builder::GetHeaderParams,
+        spec::{BuilderApi, BuilderApiError, ConstraintsApi, ErrorResponse},
+    },
+    primitives::{
+        BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
+        SignedRevocation, VersionedSignedBlindedBlock, VersionedSignedBuilderBid,
+    },
+};
+
+/// The endpoints of the Constraints API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// `GET /eth/v1/builder/status`
+    Status,
+    /// `POST /eth/v1/builder/validators`
+    RegisterValidators,
+    /// `GET /eth/v1/builder/header`
+    GetHeader,
+    /// `POST /eth/v1/builder/blinded_blocks`
+    GetPayload,
+    /// `POST /constraints/v1/builder/constraints`
+    SubmitConstraints,
+    /// `GET /eth/v1/builder/header_with_proofs`
+    GetHeaderWithProofs,
+    /// `POST /constraints/v1/builder/delegate`
+    Delegate,
+    /// `POST /constraints/v1/builder/revoke`
+    Revoke,
+}
+
+/// The outcome of a call to the mock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockOutcome {
+    /// The call succeeds.
+    Ok,
+    /// The call fails with the given status code and message.
+    Fail(StatusCode, String),
+    /// The call times out, without ever returning.
+    Timeout,
+    /// The outcome is returned after the given delay.
+    Delayed(Duration, Box<MockOutcome>),
+}
+
+impl MockOutcome {
+    /// A call failing with a `500 Internal Server Error`.
+    pub fn internal_error() -> Self {
+        Self::Fail(StatusCode::INTERNAL_SERVER_ERROR, "internal error".to_string())
+    }
+
+    /// Delay this outcome by the given duration.
+    pub fn delayed(self, delay: Duration) -> Self {
+        Self::Delayed(delay, Box::new(self))
+    }
+}
+
+/// An in-memory implementation of the Constraints API.
+///
+/// Every call pops the next outcome scripted for its endpoint, or returns the default
+/// outcome of the endpoint once the script is exhausted. Successful calls are recorded,
+/// and the bids and payloads served are the ones set beforehand.
+///
+/// Clones share the same state, so that a clone can be handed to the driver while
+/// the scenario is scripted and inspected through another.
+#[derive(Debug, Clone, Default)]
+pub struct MockConstraintsApi {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    scripts: HashMap<Endpoint, VecDeque<MockOutcome>>,
+    defaults: HashMap<Endpoint, MockOutcome>,
+    calls: HashMap<Endpoint, usize>,
+    bid: Option<SignedBuilderBid>,
+    payload: Option<GetPayloadResponse>,
+    registrations: Vec<SignedValidatorRegistration>,
+    constraints: Vec<BatchedSignedConstraints>,
+    delegations: Vec<SignedDelegation>,
+    revocations: Vec<SignedRevocation>,
+}
+
+impl MockConstraintsApi {
+    /// Create a new mock where every call succeeds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Script the outcome of the next call to the endpoint that has no outcome scripted yet.
+    pub fn push_outcome(&self, endpoint: Endpoint, outcome: MockOutcome) -> &Self {
+        self.state.lock().scripts.entry(endpoint).or_default().push_back(outcome);
+        self
+    }
+
+    /// Script the next `count` calls to the endpoint to fail with the given outcome.
+    pub fn fail_next(&self, endpoint: Endpoint, count: usize, outcome: MockOutcome) -> &Self {
+        for _ in 0..count {
+            self.push_outcome(endpoint, outcome.clone());
+        }
+        self
+    }
+
+    /// Set the outcome of the calls to the endpoint once its script is exhausted.
+    pub fn set_default(&self, endpoint: Endpoint, outcome: MockOutcome) -> &Self {
+        self.state.lock().defaults.insert(endpoint, outcome);
+        self
+    }
+
+    /// Set the bid served by successful header requests.
+    pub fn set_bid(&self, bid: SignedBuilderBid) -> &Self {
+        self.state.lock().bid = Some(bid);
+        self
+    }
+
+    /// Set the payload served by the next successful payload request.
+    pub fn set_payload(&self, payload: GetPayloadResponse) -> &Self {
+        self.state.lock().payload = Some(payload);
+        self
+    }
+
+    /// Returns the number of calls made to the endpoint, successful or not.
+    pub fn calls(&self, endpoint: Endpoint) -> usize {
+        self.state.lock().calls.get(&endpoint).copied().unwrap_or_default()
+    }
+
+    /// Returns the validator registrations received successfully.
+    pub fn registrations(&self) -> Vec<SignedValidatorRegistration> {
+        self.state.lock().registrations.clone()
+    }
+
+    /// Returns the batches of constraints received successfully, in order.
+    pub fn constraints(&self) -> Vec<BatchedSignedConstraints> {
+        self.state.lock().constraints.clone()
+    }
+
+    /// Returns the delegations received successfully.
+    pub fn delegations(&self) -> Vec<SignedDelegation> {
+        self.state.lock().delegations.clone()
+    }
+
+    /// Returns the revocations received successfully.
+    pub fn revocations(&self) -> Vec<SignedRevocation> {
+        self.state.lock().revocations.clone()
+    }
+
+    /// Record a call to the endpoint and play its next outcome, returning the error
+    /// response of the call if it fails.
+    async fn call(&self, endpoint: Endpoint) -> Result<(), ErrorResponse> {
+        let mut outcome = {
+            let mut state = self.state.lock();
+            *state.calls.entry(endpoint).or_default() += 1;
+
+            let scripted = state.scripts.get_mut(&endpoint).and_then(VecDeque::pop_front);
+            scripted.or_else(|| state.defaults.get(&endpoint).cloned()).unwrap_or(MockOutcome::Ok)
+        };
+
+        loop {
+            match outcome {
+                MockOutcome::Ok => return Ok(()),
+                MockOutcome::Fail(code, message) => return Err(ErrorResponse::new(code, message)),
+                MockOutcome::Timeout => std::future::pending::<()>().await,
+                MockOutcome::Delayed(delay, next) => {
+                    tokio::time::sleep(delay).await;
+                    outcome = *next;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BuilderApi for MockConstraintsApi {
+    async fn status(&self) -> Result<StatusCode, BuilderApiError> {
+        match self.call(Endpoint::Status).await {
+            Ok(()) => Ok(StatusCode::OK),
+            Err(err) => Err(BuilderApiError::Generic(format!("{err:?}"))),
+        }
+    }
+
+    async fn register_validators(
+        &self,
+        registrations: Vec<SignedValidatorRegistration>,
+    ) -> Result<(), BuilderApiError> {
+        self.call(Endpoint::RegisterValidators)
+            .await
+            .map_err(BuilderApiError::FailedRegisteringValidators)?;
+
+        self.state.lock().registrations.extend(registrations);
+        Ok(())
+    }
+
+    async fn get_header(
+        &self,
+        _params: GetHeaderParams,
+    ) -> Result<SignedBuilderBid, BuilderApiError> {
+        self.call(Endpoint::GetHeader).await.map_err(BuilderApiError::FailedGettingHeader)?;
+
+        self.state.lock().bid.clone().ok_or_else(|| {
+            BuilderApiError::FailedGettingHeader(ErrorResponse::new(
+                StatusCode::NO_CONTENT,
+                "no bid available",
+            ))
+        })
+    }
+
+    async fn get_payload(
+        &self,
+        _signed_block: VersionedSignedBlindedBlock,
+    ) -> Result<GetPayloadResponse, BuilderApiError> {
+        self.call(Endpoint::GetPayload).await.map_err(BuilderApiError::FailedGettingPayload)?;
+
+        self.state.lock().payload.take().ok_or_else(|| {
+            BuilderApiError::FailedGettingPayload(ErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                "no payload available",
+            ))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ConstraintsApi for MockConstraintsApi {
+    async fn submit_constraints(
+        &self,
+        constraints: &BatchedSignedConstraints,
+    ) -> Result<(), BuilderApiError> {
+        self.call(Endpoint::SubmitConstraints)
+            .await
+            .map_err(BuilderApiError::FailedSubmittingConstraints)?;
+
+        self.state.lock().constraints.push(constraints.clone());
+        Ok(())
+    }
+
+    async fn get_header_with_proofs(
+        &self,
+        _params: GetHeaderParams,
+    ) -> Result<VersionedSignedBuilderBid, BuilderApiError> {
+        self.call(Endpoint::GetHeaderWithProofs)
+            .await
+            .map_err(BuilderApiError::FailedGettingHeader)?;
+
+        let bid = self.state.lock().bid.clone().ok_or_else(|| {
+            BuilderApiError::FailedGettingHeader(ErrorResponse::new(
+                StatusCode::NO_CONTENT,
+                "no bid available",
+            ))
+        })?;
+
+        Ok(bid.into())
+    }
+
+    async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
+        self.call(Endpoint::Delegate).await.map_err(BuilderApiError::FailedDelegating)?;
+
+        self.state.lock().delegations.extend_from_slice(signed_data);
+        Ok(())
+    }
+
+    async fn revoke(&self, signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError> {
+        self.call(Endpoint::Revoke).await.map_err(BuilderApiError::FailedRevoking)?;
+
+        self.state.lock().revocations.extend_from_slice(signed_data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scripted_outcomes() {
+        let mock = MockConstraintsApi::new();
+        mock.fail_next(Endpoint::SubmitConstraints, 2, MockOutcome::internal_error());
+
+        let batch = BatchedSignedConstraints::default();
+        assert!(mock.submit_constraints(&batch).await.is_err());
+        assert!(mock.submit_constraints(&batch).await.is_err());
+        assert!(mock.submit_constraints(&batch).await.is_ok());
+        assert_eq!(mock.calls(Endpoint::SubmitConstraints), 3);
+        assert_eq!(mock.constraints().len(), 1);
+
+        // Clones share the same state
+        let clone = mock.clone();
+        clone.set_default(Endpoint::Delegate, MockOutcome::internal_error());
+        assert!(matches!(mock.delegate(&[]).await, Err(BuilderApiError::FailedDelegating(_))));
+
+        // Timeouts never return
+        mock.push_outcome(Endpoint::Revoke, MockOutcome::Timeout);
+        let res = tokio::time::timeout(Duration::from_millis(10), mock.revoke(&[])).await;
+        assert!(res.is_err());
+
+        let delayed = MockOutcome::Ok.delayed(Duration::from_millis(5));
+        mock.push_outcome(Endpoint::Revoke, delayed);
+        assert!(mock.revoke(&[]).await.is_ok());
+        assert_eq!(mock.calls(Endpoint::Revoke), 2);
+    }
+}
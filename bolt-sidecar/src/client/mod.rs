@@ -1,6 +1,32 @@
+/// Signing of the submissions to the Constraints API by the sidecar.
+pub mod auth;
+/// Typed client of the commitments API, for third-party integrators.
+pub mod bolt_rpc;
+/// Discovery of the features supported by each relay.
+pub mod capabilities;
 pub mod constraints_client;
+/// Deduplicated, persisted delegations and revocations of the validators.
+pub mod delegation_manager;
+pub mod delegations;
+/// Checks of the fee recipients of the registrations and bids against the expected ones.
+pub mod fee_recipients;
+/// Lenient deserialization of relay responses deviating slightly from the builder-specs.
+pub mod lenient;
+/// Fan-out of the Constraints API calls to several relays.
+pub mod multiplexer;
+
+/// In-memory Constraints API with scripted responses, for deterministic relay failure scenarios.
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod pubsub;
+/// Persistence of the validator registrations, replayed on startup.
+pub mod registrations;
 pub mod rpc;
+/// Log of the constraints submitted to each relay, queried for past slots.
+pub mod submissions;
+pub mod throttle;
+/// Adaptive timeouts of the calls to each relay endpoint.
+pub mod timeouts;
 
 // Re-export the beacon_api_client
 pub use beacon_api_client::mainnet::Client as BeaconClient;
@@ -0,0 +1,510 @@
+//! Fan-out of the Constraints API calls to several relays, picking the best bid among their
+//! headers and skipping the relays that keep failing.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::{B256, U256};
+use axum::http::StatusCode;
+use ethereum_consensus::{
+    builder::SignedValidatorRegistration, deneb::presets::mainnet::ExecutionPayloadHeader,
+};
+use futures::future::{join_all, select_ok};
+use parking_lot::RwLock;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{
+    api::{
+        builder::GetHeaderParams,
+        codes::ErrorCode,
+        spec::{BuilderApi, BuilderApiError, ConstraintsApi},
+    },
+    primitives::{
+        BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
+        SignedRevocation, TransactionExt, VersionedSignedBlindedBlock, VersionedSignedBuilderBid,
+    },
+    telemetry::ApiMetrics,
+};
+
+use super::constraints_client::ConstraintsClient;
+
+/// The number of consecutive failures after which a relay is considered unhealthy.
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// The time after which an unhealthy relay is called again, to check if it recovered.
+pub const UNHEALTHY_RELAY_COOLDOWN: Duration = Duration::from_secs(12);
+
+/// The time the relays have to return a header, slightly lower than the `get_header` timeout
+/// of the builder proxy, so that the bids of the fast relays are not lost to a slow one.
+const HEADER_FANOUT_TIMEOUT: Duration = Duration::from_millis(450);
+
+/// The health of a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayHealthState {
+    /// The URL of the relay.
+    pub relay: Url,
+    /// Whether the relay is called.
+    pub healthy: bool,
+    /// The number of consecutive calls to the relay that failed.
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Health {
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl Health {
+    fn is_healthy(&self) -> bool {
+        self.consecutive_failures < MAX_CONSECUTIVE_FAILURES ||
+            self.last_failure.map_or(true, |at| at.elapsed() >= UNHEALTHY_RELAY_COOLDOWN)
+    }
+}
+
+/// Tracks the health of each relay from the outcome of the calls to it.
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct RelayHealth {
+    relays: Arc<RwLock<HashMap<Url, Health>>>,
+}
+
+impl RelayHealth {
+    /// Records the outcome of a call to the relay. Only failures to reach the relay, time outs
+    /// and server errors count as failures, not rejections of the request.
+    ///
+    /// Returns true if the relay was unhealthy and the call succeeded, i.e. it recovered.
+    pub fn record<T>(&self, relay: &Url, result: &Result<T, BuilderApiError>) -> bool {
+        let failed = result.as_ref().err().is_some_and(is_relay_failure);
+
+        let mut relays = self.relays.write();
+        let health = relays.entry(relay.clone()).or_default();
+        if failed {
+            health.consecutive_failures += 1;
+            health.last_failure = Some(Instant::now());
+            if health.consecutive_failures == MAX_CONSECUTIVE_FAILURES {
+                warn!(%relay, "Relay is unhealthy, skipping it until it recovers");
+            }
+            false
+        } else {
+            let recovered = health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES;
+            if recovered {
+                info!(%relay, "Relay recovered");
+            }
+            health.consecutive_failures = 0;
+            recovered
+        }
+    }
+
+    /// Returns true if the relay is called, i.e. it did not fail too many times in a row
+    /// recently.
+    pub fn is_healthy(&self, relay: &Url) -> bool {
+        self.relays.read().get(relay).map_or(true, Health::is_healthy)
+    }
+
+    /// Returns the health of the given relays.
+    pub fn states(&self, relays: impl IntoIterator<Item = Url>) -> Vec<RelayHealthState> {
+        let states = self.relays.read();
+        relays
+            .into_iter()
+            .map(|relay| {
+                let health = states.get(&relay).cloned().unwrap_or_default();
+                RelayHealthState {
+                    healthy: health.is_healthy(),
+                    consecutive_failures: health.consecutive_failures,
+                    relay,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Tracks the gas of the transactions constrained in each slot, from the constraints
+/// submitted to the relays, to adjust the value of the bids of the slot for it.
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ConstrainedGas {
+    /// The gas limit of the constrained transactions of each slot, by transaction hash so
+    /// that constraints submitted twice are only counted once.
+    slots: Arc<RwLock<BTreeMap<u64, HashMap<B256, u64>>>>,
+}
+
+impl ConstrainedGas {
+    /// Records the transactions of the submitted constraints.
+    pub fn record(&self, constraints: &BatchedSignedConstraints) {
+        let mut slots = self.slots.write();
+        for signed in constraints {
+            let transactions = slots.entry(signed.message.slot).or_default();
+            for tx in &signed.message.transactions {
+                transactions.insert(*tx.hash(), tx.gas_limit());
+            }
+        }
+    }
+
+    /// Returns the constrained gas of the slot, forgetting the earlier slots.
+    pub fn get(&self, slot: u64) -> u64 {
+        let mut slots = self.slots.write();
+        *slots = slots.split_off(&slot);
+        slots.get(&slot).map_or(0, |transactions| transactions.values().sum())
+    }
+}
+
+/// Returns true if the error means that the relay couldn't serve the request.
+fn is_relay_failure(err: &BuilderApiError) -> bool {
+    matches!(err.error_code(), ErrorCode::UpstreamUnavailable | ErrorCode::Timeout)
+}
+
+/// A Constraints API client fanning out the calls to several relays concurrently.
+///
+/// Submissions of constraints, delegations, revocations and registrations succeed if any
+/// relay accepts them, the best valid bid among the headers of the relays is returned, and
+/// payloads are requested from every relay. Relays that keep failing are skipped until
+/// [UNHEALTHY_RELAY_COOLDOWN] passes, unless all of them are unhealthy.
+///
+/// Bids are compared on their value adjusted for the constrained gas of their slot, like
+/// bolt-boost does, so that the bid of the self-builder of the proposer, if any, competes
+/// with the relay bids on the same basis.
+#[derive(Debug, Clone)]
+pub struct RelayMultiplexer {
+    relays: Vec<ConstraintsClient>,
+    health: RelayHealth,
+    constrained_gas: ConstrainedGas,
+    /// The URL of the local builder of the proposer, also in `relays`.
+    self_builder: Option<Url>,
+}
+
+impl RelayMultiplexer {
+    /// Creates a multiplexer of the primary relay and the additional ones, whose clients
+    /// share the delegations, rate limits and timeouts of the primary one.
+    pub fn new(primary: ConstraintsClient, additional: impl IntoIterator<Item = Url>) -> Self {
+        let mut relays = vec![primary.clone()];
+        relays.extend(additional.into_iter().map(|url| primary.for_relay(url)));
+        Self {
+            relays,
+            health: RelayHealth::default(),
+            constrained_gas: ConstrainedGas::default(),
+            self_builder: None,
+        }
+    }
+
+    /// Adds the local builder of a self-building proposer, supporting the Constraints API.
+    /// It is called like the relays: it receives the committed constraints directly, and its
+    /// bids compete with the relay bids.
+    pub fn with_self_builder(mut self, url: Url) -> Self {
+        let self_builder = self.relays[0].for_relay(url.clone());
+        self.relays.push(self_builder);
+        self.self_builder = Some(url);
+        self
+    }
+
+    /// Returns the clients of the relays, the primary one first.
+    pub fn relays(&self) -> &[ConstraintsClient] {
+        &self.relays
+    }
+
+    /// Returns the health of every relay.
+    pub fn health(&self) -> Vec<RelayHealthState> {
+        self.health.states(self.relays.iter().map(ConstraintsClient::url))
+    }
+
+    /// Records the outcome of a call to the relay, re-broadcasting the delegations to it in
+    /// the background if it recovered, as it may have lost them while it was down.
+    fn record_outcome<T>(&self, relay: &Url, result: &Result<T, BuilderApiError>) {
+        if !self.health.record(relay, result) {
+            return;
+        }
+
+        if let Some(client) = self.relays.iter().find(|client| client.url() == *relay).cloned() {
+            tokio::spawn(async move { client.rebroadcast_delegations().await });
+        }
+    }
+
+    /// Returns the relays to call: the healthy ones, or all of them if none is healthy.
+    fn available(&self) -> Vec<&ConstraintsClient> {
+        let healthy = self
+            .relays
+            .iter()
+            .filter(|relay| self.health.is_healthy(&relay.url()))
+            .collect::<Vec<_>>();
+
+        if healthy.is_empty() {
+            self.relays.iter().collect()
+        } else {
+            healthy
+        }
+    }
+
+    /// Records the outcome of a call to each relay, returning `Ok` if any relay succeeded
+    /// and the last error otherwise.
+    fn settle(
+        &self,
+        endpoint: &'static str,
+        results: Vec<(Url, Result<(), BuilderApiError>)>,
+    ) -> Result<(), BuilderApiError> {
+        let mut outcome = Err(BuilderApiError::Generic("no relay configured".to_string()));
+        for (relay, result) in results {
+            self.record_outcome(&relay, &result);
+            match result {
+                Ok(()) => outcome = Ok(()),
+                Err(err) => {
+                    warn!(%relay, endpoint, ?err, "Relay call failed");
+                    if outcome.is_err() {
+                        outcome = Err(err);
+                    }
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Returns the best bid among the results of the relays, recording their outcome.
+    /// Bids for another parent block than the requested one are discarded, and the others
+    /// are compared on their value adjusted for the constrained gas of the slot.
+    ///
+    /// The `bid` function returns the payload header and the value of a bid.
+    fn best_bid<B>(
+        &self,
+        params: &GetHeaderParams,
+        results: Vec<(Url, Result<B, BuilderApiError>)>,
+        bid: impl Fn(&B) -> (&ExecutionPayloadHeader, U256),
+    ) -> Result<B, BuilderApiError> {
+        let constrained_gas = self.constrained_gas.get(params.slot);
+        let mut best: Option<(Url, B, u128)> = None;
+        let mut last_error = None;
+
+        for (relay, result) in results {
+            self.record_outcome(&relay, &result);
+            match result {
+                Ok(header) if bid(&header).0.parent_hash != params.parent_hash => {
+                    warn!(%relay, slot = params.slot, "Discarding bid for another parent block");
+                }
+                Ok(header) => {
+                    let (payload_header, value) = bid(&header);
+                    let adjusted_value = bolt_primitives::adjusted_bid_value(
+                        value.saturating_to(),
+                        payload_header.gas_used,
+                        constrained_gas,
+                    );
+                    debug!(
+                        %relay,
+                        slot = params.slot,
+                        %value,
+                        adjusted_value,
+                        gas_used = payload_header.gas_used,
+                        constrained_gas,
+                        "Received bid"
+                    );
+                    if best.as_ref().map_or(true, |(_, _, best)| *best < adjusted_value) {
+                        best = Some((relay, header, adjusted_value));
+                    }
+                }
+                Err(err) => {
+                    debug!(%relay, slot = params.slot, ?err, "Relay returned no bid");
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        let Some((relay, header, adjusted_value)) = best else {
+            return Err(last_error.unwrap_or_else(|| {
+                BuilderApiError::Generic(format!("no valid bid for slot {}", params.slot))
+            }));
+        };
+
+        let self_built = self.self_builder.as_ref() == Some(&relay);
+        info!(%relay, slot = params.slot, adjusted_value, self_built, "Selected bid");
+        if self_built {
+            ApiMetrics::increment_self_built_bids_selected();
+        }
+
+        Ok(header)
+    }
+}
+
+/// Calls the relay with [HEADER_FANOUT_TIMEOUT].
+async fn with_header_timeout<T>(
+    call: impl std::future::Future<Output = Result<T, BuilderApiError>>,
+) -> Result<T, BuilderApiError> {
+    tokio::time::timeout(HEADER_FANOUT_TIMEOUT, call).await?
+}
+
+#[async_trait::async_trait]
+impl BuilderApi for RelayMultiplexer {
+    async fn status(&self) -> Result<StatusCode, BuilderApiError> {
+        let results = join_all(self.relays.iter().map(|relay| relay.status())).await;
+
+        let mut last_error = None;
+        for result in results {
+            match result {
+                Ok(status) if status.is_success() => return Ok(status),
+                Ok(status) => last_error = Some(Ok(status)),
+                Err(err) => last_error = Some(Err(err)),
+            }
+        }
+        last_error.unwrap_or(Ok(StatusCode::SERVICE_UNAVAILABLE))
+    }
+
+    async fn register_validators(
+        &self,
+        registrations: Vec<SignedValidatorRegistration>,
+    ) -> Result<(), BuilderApiError> {
+        let results = join_all(self.available().into_iter().map(|relay| {
+            let registrations = registrations.clone();
+            async move { (relay.url(), relay.register_validators(registrations).await) }
+        }))
+        .await;
+
+        self.settle("register_validators", results)
+    }
+
+    async fn get_header(
+        &self,
+        params: GetHeaderParams,
+    ) -> Result<SignedBuilderBid, BuilderApiError> {
+        let results = join_all(self.available().into_iter().map(|relay| {
+            let params = params.clone();
+            async move { (relay.url(), with_header_timeout(relay.get_header(params)).await) }
+        }))
+        .await;
+
+        self.best_bid(&params, results, |bid| (&bid.message.header, bid.message.value))
+    }
+
+    async fn get_payload(
+        &self,
+        signed_block: VersionedSignedBlindedBlock,
+    ) -> Result<GetPayloadResponse, BuilderApiError> {
+        // Only the relay of the signed bid can reveal its payload, the others fail
+        let calls = self.relays.iter().map(|relay| {
+            let signed_block = signed_block.clone();
+            Box::pin(async move { relay.get_payload(signed_block).await })
+        });
+
+        select_ok(calls).await.map(|(payload, _)| payload)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConstraintsApi for RelayMultiplexer {
+    async fn submit_constraints(
+        &self,
+        constraints: &BatchedSignedConstraints,
+    ) -> Result<(), BuilderApiError> {
+        self.constrained_gas.record(constraints);
+
+        let results = join_all(self.available().into_iter().map(|relay| async move {
+            (relay.url(), relay.submit_constraints(constraints).await)
+        }))
+        .await;
+
+        self.settle("submit_constraints", results)
+    }
+
+    async fn get_header_with_proofs(
+        &self,
+        params: GetHeaderParams,
+    ) -> Result<VersionedSignedBuilderBid, BuilderApiError> {
+        let results =
+            join_all(self.available().into_iter().map(|relay| {
+                let params = params.clone();
+                async move {
+                    (relay.url(), with_header_timeout(relay.get_header_with_proofs(params)).await)
+                }
+            }))
+            .await;
+
+        self.best_bid(&params, results, |bid| (bid.header(), bid.value()))
+    }
+
+    async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
+        let results = join_all(
+            self.available()
+                .into_iter()
+                .map(|relay| async move { (relay.url(), relay.delegate(signed_data).await) }),
+        )
+        .await;
+
+        self.settle("delegate", results)
+    }
+
+    async fn revoke(&self, signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError> {
+        let results = join_all(
+            self.available()
+                .into_iter()
+                .map(|relay| async move { (relay.url(), relay.revoke(signed_data).await) }),
+        )
+        .await;
+
+        self.settle("revoke", results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::spec::ErrorResponse,
+        primitives::{ConstraintsMessage, SignedConstraints},
+        signer::local::LocalSigner,
+        test_util::test_legacy_transaction,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_constrained_gas() {
+        let tx = test_legacy_transaction();
+        let constraints = |slot| SignedConstraints {
+            message: ConstraintsMessage::from_transaction(
+                LocalSigner::random().pubkey(),
+                slot,
+                tx.clone(),
+            ),
+            signature: Default::default(),
+        };
+
+        let constrained_gas = ConstrainedGas::default();
+        constrained_gas.record(&vec![constraints(10), constraints(11)]);
+
+        // Constraints submitted twice are counted once
+        constrained_gas.record(&vec![constraints(11)]);
+        assert_eq!(constrained_gas.get(11), 21_000);
+
+        // Earlier slots are forgotten
+        assert_eq!(constrained_gas.get(10), 0);
+        assert_eq!(constrained_gas.get(12), 0);
+    }
+
+    #[test]
+    fn test_relay_health() {
+        let health = RelayHealth::default();
+        let relay = Url::parse("http://relay:3030").unwrap();
+        let unavailable = || {
+            let error = ErrorResponse::new(StatusCode::BAD_GATEWAY, "relay unavailable");
+            Err::<(), _>(BuilderApiError::FailedSubmittingConstraints(error))
+        };
+
+        // Rejections of the request don't make the relay unhealthy
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            health.record(&relay, &Err::<(), _>(BuilderApiError::InvalidFork("capella".into())));
+        }
+        assert!(health.is_healthy(&relay));
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            health.record(&relay, &unavailable());
+        }
+        assert!(!health.is_healthy(&relay));
+        assert_eq!(health.states([relay.clone()])[0].consecutive_failures, 3);
+
+        // A successful call makes it healthy again
+        assert!(health.record(&relay, &Ok(())));
+        assert!(health.is_healthy(&relay));
+        assert!(!health.record(&relay, &Ok(())));
+    }
+}
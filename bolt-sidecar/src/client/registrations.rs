@@ -0,0 +1,118 @@
+//! Persistence of the validator registrations forwarded to the relay, to replay them when the
+//! sidecar restarts instead of waiting for the next registration cycle of the beacon node.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ethereum_consensus::builder::SignedValidatorRegistration;
+use parking_lot::Mutex;
+
+/// A file with the latest signed registration of each validator forwarded to the relay.
+///
+/// Clones share the same file.
+#[derive(Debug, Clone)]
+pub struct RegistrationStore {
+    path: PathBuf,
+    /// Serializes the read-modify-write cycles of [RegistrationStore::record].
+    lock: Arc<Mutex<()>>,
+}
+
+impl RegistrationStore {
+    /// Creates a store of the registrations at the given path. The file is created on the
+    /// first recorded registration.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Default::default() }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the saved registrations, or none if the file doesn't exist yet.
+    pub fn load(&self) -> io::Result<Vec<SignedValidatorRegistration>> {
+        match File::open(&self.path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Saves the given registrations, replacing the older registrations of the same
+    /// validators. The file is replaced atomically.
+    pub fn record(&self, registrations: &[SignedValidatorRegistration]) -> io::Result<()> {
+        let _guard = self.lock.lock();
+
+        let mut latest = HashMap::new();
+        for registration in self.load()?.into_iter().chain(registrations.iter().cloned()) {
+            let pubkey = registration.message.public_key.clone();
+            match latest.get(&pubkey) {
+                Some(saved) if is_newer(saved, &registration) => {}
+                _ => {
+                    latest.insert(pubkey, registration);
+                }
+            }
+        }
+
+        let mut registrations = latest.into_values().collect::<Vec<_>>();
+        registrations
+            .sort_by(|a, b| a.message.public_key.as_ref().cmp(b.message.public_key.as_ref()));
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        let file = File::create(&tmp)?;
+        serde_json::to_writer(BufWriter::new(&file), &registrations)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// Returns true if `a` was signed after `b`.
+fn is_newer(a: &SignedValidatorRegistration, b: &SignedValidatorRegistration) -> bool {
+    a.message.timestamp > b.message.timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signer::local::LocalSigner;
+
+    use super::*;
+
+    fn registration(signer: &LocalSigner, timestamp: u64) -> SignedValidatorRegistration {
+        let mut registration = SignedValidatorRegistration::default();
+        registration.message.public_key = signer.pubkey();
+        registration.message.timestamp = timestamp;
+        registration
+    }
+
+    #[test]
+    fn test_record_latest_registrations() {
+        let dir = std::env::temp_dir().join(format!("bolt-registrations-{}", std::process::id()));
+        let store = RegistrationStore::new(dir.join("registrations.json"));
+        assert!(store.load().unwrap().is_empty());
+
+        let (alice, bob) = (LocalSigner::random(), LocalSigner::random());
+        store.record(&[registration(&alice, 10), registration(&bob, 10)]).unwrap();
+
+        // Only the latest registration of each validator is kept
+        store.record(&[registration(&alice, 20)]).unwrap();
+        store.record(&[registration(&bob, 5)]).unwrap();
+
+        let mut saved = store.load().unwrap();
+        saved.sort_by_key(|r| r.message.timestamp);
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved[0].message.public_key, bob.pubkey());
+        assert_eq!(saved[0].message.timestamp, 10);
+        assert_eq!(saved[1].message.public_key, alice.pubkey());
+        assert_eq!(saved[1].message.timestamp, 20);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,14 +1,17 @@
 //! This module contains the `RpcClient` struct, which is a wrapper around the `alloy_rpc_client`.
 //! It provides a simple interface to interact with the Execution layer JSON-RPC API.
 
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
 
 use alloy::{
     eips::BlockNumberOrTag,
     primitives::{Address, Bytes, B256, U256, U64},
     rpc::{
         client::{self as alloyClient, ClientBuilder},
-        types::{Block, FeeHistory, TransactionReceipt},
+        types::{Block, FeeHistory, Transaction, TransactionReceipt},
     },
     transports::{http::Http, TransportErrorKind, TransportResult},
 };
@@ -118,6 +121,30 @@ impl RpcClient {
         self.0.request("eth_sendRawTransaction", [raw]).await
     }
 
+    /// Get the pending transactions of the given sender in the mempool of the node, using
+    /// the `txpool_contentFrom` method. Queued transactions are not returned.
+    pub async fn get_pending_transactions(
+        &self,
+        address: &Address,
+    ) -> TransportResult<Vec<Transaction>> {
+        let mut content: HashMap<String, HashMap<String, Transaction>> =
+            self.0.request("txpool_contentFrom", [address]).await?;
+
+        Ok(content.remove("pending").map(|txs| txs.into_values().collect()).unwrap_or_default())
+    }
+
+    /// Get all the pending transactions in the mempool of the node, using the `txpool_content`
+    /// method. Queued transactions are not returned.
+    pub async fn get_txpool_pending(&self) -> TransportResult<Vec<Transaction>> {
+        let mut content: HashMap<String, HashMap<Address, HashMap<String, Transaction>>> =
+            self.0.request("txpool_content", ()).await?;
+
+        Ok(content
+            .remove("pending")
+            .map(|senders| senders.into_values().flat_map(HashMap::into_values).collect())
+            .unwrap_or_default())
+    }
+
     /// Get the receipts for a list of transaction hashes.
     pub async fn get_receipts(
         &self,
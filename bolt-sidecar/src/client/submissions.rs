@@ -0,0 +1,306 @@
+//! Log of the constraints submitted to each relay, to answer for the submissions of past
+//! slots: which constraints were sent, which relays acknowledged them and when.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use parking_lot::{Mutex, RwLock};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{common::now_ms, primitives::SignedConstraints};
+
+/// The number of most recent slots whose submissions are kept in memory. The submissions of
+/// the earlier slots are read back from the event log, if any.
+pub const RECENT_SLOTS: usize = 7200;
+
+/// A submission of the constraints of a slot to a relay, as recorded in the event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmissionEvent {
+    /// The target slot of the constraints.
+    pub slot: u64,
+    /// The URL of the relay.
+    pub relay: Url,
+    /// The time the constraints were sent, in unix milliseconds.
+    pub sent_at_ms: u64,
+    /// The time the relay acknowledged the constraints, in unix milliseconds, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at_ms: Option<u64>,
+    /// The error returned by the relay, if it didn't acknowledge the constraints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The signed constraints that were sent.
+    pub constraints: Vec<SignedConstraints>,
+}
+
+/// A submission of constraints to a relay, without the constraints themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelaySubmission {
+    /// The URL of the relay.
+    pub relay: Url,
+    /// The time the constraints were sent, in unix milliseconds.
+    pub sent_at_ms: u64,
+    /// The time the relay acknowledged the constraints, in unix milliseconds, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at_ms: Option<u64>,
+    /// The error returned by the relay, if it didn't acknowledge the constraints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The indexes of the sent constraints in [SlotSubmissions::constraints].
+    pub constraints: Vec<usize>,
+}
+
+/// The submissions of the constraints of a slot to every relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotSubmissions {
+    /// The slot of the constraints.
+    pub slot: u64,
+    /// Every signed constraints submitted for the slot, in the order they were first sent.
+    pub constraints: Vec<SignedConstraints>,
+    /// The submissions to the relays, in the order they were sent.
+    pub submissions: Vec<RelaySubmission>,
+}
+
+impl SlotSubmissions {
+    /// Aggregates the submission events of a slot.
+    pub fn from_events(slot: u64, events: &[SubmissionEvent]) -> Self {
+        let mut constraints = Vec::<SignedConstraints>::new();
+        let mut submissions = Vec::with_capacity(events.len());
+
+        for event in events {
+            let indexes = event
+                .constraints
+                .iter()
+                .map(|signed| match constraints.iter().position(|c| c == signed) {
+                    Some(i) => i,
+                    None => {
+                        constraints.push(signed.clone());
+                        constraints.len() - 1
+                    }
+                })
+                .collect();
+
+            submissions.push(RelaySubmission {
+                relay: event.relay.clone(),
+                sent_at_ms: event.sent_at_ms,
+                acknowledged_at_ms: event.acknowledged_at_ms,
+                error: event.error.clone(),
+                constraints: indexes,
+            });
+        }
+
+        Self { slot, constraints, submissions }
+    }
+
+    /// Returns the relays that acknowledged constraints of the slot.
+    pub fn acknowledged_by(&self) -> Vec<&Url> {
+        let mut relays = Vec::new();
+        for submission in self.submissions.iter().filter(|s| s.acknowledged_at_ms.is_some()) {
+            if !relays.contains(&&submission.relay) {
+                relays.push(&submission.relay);
+            }
+        }
+        relays
+    }
+}
+
+/// Records the submissions of constraints to each relay.
+///
+/// The submissions of the [RECENT_SLOTS] most recent slots are kept in memory. If a path is
+/// set, every submission is also appended to an event log, a file of JSON
+/// [SubmissionEvent]s, one per line, from which the earlier slots are read back.
+///
+/// Clones share the same state, so that the constraints client records the submissions
+/// while the admin API reads them.
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionLog {
+    recent: Arc<RwLock<BTreeMap<u64, Vec<SubmissionEvent>>>>,
+    file: Option<Arc<Mutex<File>>>,
+    path: Option<PathBuf>,
+}
+
+impl SubmissionLog {
+    /// Opens the event log at the given path, creating it if it doesn't exist, and loads the
+    /// submissions of the most recent slots it records.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut recent = BTreeMap::<u64, Vec<SubmissionEvent>>::new();
+        match File::open(&path) {
+            Ok(file) => {
+                for event in read_events(BufReader::new(file))? {
+                    recent.entry(event.slot).or_default().push(event);
+                }
+                while recent.len() > RECENT_SLOTS {
+                    recent.pop_first();
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            recent: Arc::new(RwLock::new(recent)),
+            file: Some(Arc::new(Mutex::new(file))),
+            path: Some(path),
+        })
+    }
+
+    /// Returns the path of the event log, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Records the outcome of submitting constraints to the relay, sent at the given time in
+    /// unix milliseconds: `Ok` if the relay acknowledged them, `Err` with the error otherwise.
+    ///
+    /// Constraints for several slots are recorded as one submission per slot.
+    pub fn record(
+        &self,
+        relay: &Url,
+        constraints: &[SignedConstraints],
+        sent_at_ms: u64,
+        outcome: Result<(), String>,
+    ) {
+        let acknowledged_at_ms = outcome.is_ok().then(now_ms);
+
+        let mut by_slot = BTreeMap::<u64, Vec<SignedConstraints>>::new();
+        for signed in constraints {
+            by_slot.entry(signed.message.slot).or_default().push(signed.clone());
+        }
+
+        for (slot, constraints) in by_slot {
+            let event = SubmissionEvent {
+                slot,
+                relay: relay.clone(),
+                sent_at_ms,
+                acknowledged_at_ms,
+                error: outcome.clone().err(),
+                constraints,
+            };
+
+            if let Some(file) = &self.file {
+                if let Err(err) = write_event(&mut *file.lock(), &event) {
+                    warn!(?err, slot, "Failed to append the submission to the event log");
+                }
+            }
+
+            let mut recent = self.recent.write();
+            recent.entry(slot).or_default().push(event);
+            while recent.len() > RECENT_SLOTS {
+                recent.pop_first();
+            }
+        }
+    }
+
+    /// Returns the submissions of the constraints of the slot, or `None` if none were
+    /// recorded. Slots that are no longer kept in memory are read back from the event log.
+    pub fn slot(&self, slot: u64) -> io::Result<Option<SlotSubmissions>> {
+        if let Some(events) = self.recent.read().get(&slot) {
+            return Ok(Some(SlotSubmissions::from_events(slot, events)));
+        }
+
+        let Some(path) = self.path.as_ref() else { return Ok(None) };
+        let events = read_events(BufReader::new(File::open(path)?))?
+            .into_iter()
+            .filter(|event| event.slot == slot)
+            .collect::<Vec<_>>();
+
+        Ok((!events.is_empty()).then(|| SlotSubmissions::from_events(slot, &events)))
+    }
+}
+
+/// Reads the events of a log, skipping a truncated last line left by a crash during a write.
+fn read_events(reader: impl BufRead) -> io::Result<Vec<SubmissionEvent>> {
+    let lines = reader.lines().collect::<io::Result<Vec<_>>>()?;
+
+    let mut events = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(line) {
+            Ok(event) => events.push(event),
+            Err(err) if i + 1 == lines.len() && err.is_eof() => {
+                warn!(line = i + 1, "Skipping the truncated last event of the submission log");
+            }
+            Err(err) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed submission event at line {}: {err}", i + 1),
+                ));
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Writes an event as a line of JSON, and waits for it to be written to disk.
+fn write_event(file: &mut File, event: &SubmissionEvent) -> io::Result<()> {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{primitives::ConstraintsMessage, signer::local::LocalSigner};
+
+    use super::*;
+
+    fn constraints(slot: u64) -> SignedConstraints {
+        SignedConstraints {
+            message: ConstraintsMessage {
+                pubkey: LocalSigner::random().pubkey(),
+                slot,
+                top: false,
+                index: None,
+                transactions: Vec::new(),
+            },
+            signature: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_submission_log() {
+        let dir = std::env::temp_dir().join(format!("bolt-submissions-{}", std::process::id()));
+        let path = dir.join("submissions.jsonl");
+        let log = SubmissionLog::open(&path).unwrap();
+
+        let primary = Url::parse("http://relay-1.local").unwrap();
+        let secondary = Url::parse("http://relay-2.local").unwrap();
+        let (first, second) = (constraints(10), constraints(10));
+
+        log.record(&primary, &[first.clone()], 1, Ok(()));
+        log.record(&secondary, &[first.clone()], 1, Err("timeout".to_string()));
+        log.record(&secondary, &[first.clone(), second.clone(), constraints(11)], 2, Ok(()));
+
+        let slot = log.slot(10).unwrap().unwrap();
+        assert_eq!(slot.constraints, vec![first, second]);
+        assert_eq!(slot.submissions.len(), 3);
+        assert_eq!(slot.submissions[1].error.as_deref(), Some("timeout"));
+        assert_eq!(slot.submissions[2].constraints, vec![0, 1]);
+        assert_eq!(slot.acknowledged_by(), vec![&primary, &secondary]);
+        assert!(log.slot(12).unwrap().is_none());
+
+        // The submissions are read back from the event log
+        let reopened = SubmissionLog::open(&path).unwrap();
+        assert_eq!(reopened.slot(10).unwrap(), Some(slot));
+        assert_eq!(reopened.slot(11).unwrap().unwrap().submissions.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
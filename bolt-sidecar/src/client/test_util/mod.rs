@@ -1,12 +1,8 @@
 #![allow(unused)]
 
-use beacon_api_client::VersionedValue;
 use ethereum_consensus::{
     builder::SignedValidatorRegistration,
-    deneb::{
-        self,
-        mainnet::{BlobsBundle, SignedBlindedBeaconBlock},
-    },
+    deneb::{self, mainnet::BlobsBundle},
     types::mainnet::ExecutionPayload,
 };
 use reqwest::StatusCode;
@@ -17,7 +13,7 @@ use crate::{
     api::{builder::GetHeaderParams, spec::BuilderApiError},
     primitives::{
         BatchedSignedConstraints, GetPayloadResponse, PayloadAndBlobs, SignedBuilderBid,
-        SignedDelegation, SignedRevocation,
+        SignedDelegation, SignedRevocation, VersionedSignedBlindedBlock, VersionedSignedBuilderBid,
     },
     BuilderApi, ConstraintsApi,
 };
@@ -70,7 +66,7 @@ impl BuilderApi for MockConstraintsClient {
 
     async fn get_payload(
         &self,
-        _signed_block: SignedBlindedBeaconBlock,
+        _signed_block: VersionedSignedBlindedBlock,
     ) -> Result<GetPayloadResponse, BuilderApiError> {
         let response = self.response_rx.borrow().clone();
         let payload = serde_json::from_value(response)?;
@@ -92,7 +88,7 @@ impl ConstraintsApi for MockConstraintsClient {
     async fn get_header_with_proofs(
         &self,
         _params: GetHeaderParams,
-    ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError> {
+    ) -> Result<VersionedSignedBuilderBid, BuilderApiError> {
         let response = self.response_rx.borrow().clone();
         let bid = serde_json::from_value(response)?;
         Ok(bid)
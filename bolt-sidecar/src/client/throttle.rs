@@ -0,0 +1,170 @@
+//! Tracking of the rate limits of each relay, to hold back submissions to the relays
+//! that responded with `429 Too Many Requests` until they accept requests again.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::RwLock;
+use reqwest::{header::HeaderMap, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::ApiMetrics;
+
+/// The delay before retrying a request to a relay that rate-limited it without telling
+/// when to retry.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// The maximum time a submission is held back by the rate limit of a relay before
+/// failing, as it is of no use after the slot it targets.
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(12);
+
+/// The number of times a rate-limited submission is rescheduled before failing.
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// The headers telling when a rate-limited request can be retried, by precedence.
+const RETRY_AFTER_HEADERS: [&str; 3] = ["retry-after", "ratelimit-reset", "x-ratelimit-reset"];
+
+/// The throttling state of a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayThrottleState {
+    /// The URL of the relay.
+    pub relay: Url,
+    /// Whether requests to the relay are currently held back.
+    pub throttled: bool,
+    /// The time in milliseconds until the relay accepts requests again, if throttled.
+    pub retry_after_ms: u64,
+    /// The number of requests the relay rate-limited so far.
+    pub rate_limited: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Throttle {
+    until: Instant,
+    rate_limited: u64,
+}
+
+/// Tracks the rate limits of each relay.
+///
+/// Clones share the same state, so that the constraints client records the rate limits
+/// it hits while the admin API reads them.
+#[derive(Debug, Clone, Default)]
+pub struct RelayThrottle {
+    relays: Arc<RwLock<HashMap<Url, Throttle>>>,
+}
+
+impl RelayThrottle {
+    /// Records that the relay rate-limited a request, and must not be sent requests for
+    /// the given duration.
+    pub fn record(&self, relay: &Url, retry_after: Duration) {
+        let mut relays = self.relays.write();
+        let throttle = relays
+            .entry(relay.clone())
+            .or_insert_with(|| Throttle { until: Instant::now(), rate_limited: 0 });
+
+        throttle.until = throttle.until.max(Instant::now() + retry_after);
+        throttle.rate_limited += 1;
+
+        ApiMetrics::increment_relay_rate_limited(relay.to_string());
+        ApiMetrics::set_relay_throttled(relay.to_string(), true);
+    }
+
+    /// Returns the time left until the relay accepts requests again, if it is throttled.
+    pub fn remaining(&self, relay: &Url) -> Option<Duration> {
+        let relays = self.relays.read();
+        let remaining = relays.get(relay)?.until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Records that the relay accepted a request, lifting its throttling.
+    pub fn clear(&self, relay: &Url) {
+        if let Some(throttle) = self.relays.write().get_mut(relay) {
+            throttle.until = Instant::now();
+            ApiMetrics::set_relay_throttled(relay.to_string(), false);
+        }
+    }
+
+    /// Returns the throttling state of every relay that rate-limited requests so far.
+    pub fn snapshot(&self) -> Vec<RelayThrottleState> {
+        let now = Instant::now();
+        let mut snapshot = self
+            .relays
+            .read()
+            .iter()
+            .map(|(relay, throttle)| {
+                let remaining = throttle.until.saturating_duration_since(now);
+                RelayThrottleState {
+                    relay: relay.clone(),
+                    throttled: !remaining.is_zero(),
+                    retry_after_ms: remaining.as_millis() as u64,
+                    rate_limited: throttle.rate_limited,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        snapshot.sort_by(|a, b| a.relay.cmp(&b.relay));
+        snapshot
+    }
+}
+
+/// Parses the time to wait before retrying a rate-limited request from the headers of
+/// its response, defaulting to [DEFAULT_RETRY_AFTER].
+///
+/// `Retry-After` and `RateLimit-Reset` are read as a number of seconds. `X-RateLimit-Reset`
+/// is read as a UNIX timestamp if it is in the future, as a number of seconds otherwise.
+/// HTTP dates are not supported, so the next header is read instead.
+pub fn parse_retry_after(headers: &HeaderMap) -> Duration {
+    RETRY_AFTER_HEADERS
+        .iter()
+        .find_map(|name| {
+            let value = headers.get(*name)?.to_str().ok()?.trim().parse::<u64>().ok()?;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+            // Rate limit resets as UNIX timestamps are larger than any sensible delay
+            Some(Duration::from_secs(if value > now { value - now } else { value }))
+        })
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), DEFAULT_RETRY_AFTER);
+
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("5"));
+        assert_eq!(parse_retry_after(&headers), Duration::from_secs(5));
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        headers.insert("x-ratelimit-reset", (now + 3).to_string().parse().unwrap());
+        assert!(parse_retry_after(&headers) <= Duration::from_secs(3));
+
+        // Retry-After takes precedence, HTTP dates fall back to the default
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        assert_eq!(parse_retry_after(&headers), Duration::from_secs(2));
+        headers.insert("retry-after", HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert!(parse_retry_after(&headers) <= Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_relay_throttle() {
+        let throttle = RelayThrottle::default();
+        let relay = Url::parse("http://relay.test").unwrap();
+        assert!(throttle.remaining(&relay).is_none());
+
+        throttle.record(&relay, Duration::from_secs(10));
+        assert!(throttle.remaining(&relay).unwrap() > Duration::from_secs(9));
+        assert!(throttle.snapshot()[0].throttled);
+
+        throttle.clear(&relay);
+        assert!(throttle.remaining(&relay).is_none());
+        assert_eq!(throttle.snapshot()[0].rate_limited, 1);
+    }
+}
@@ -0,0 +1,142 @@
+//! Adaptive timeouts of the calls to each relay endpoint, tuned to the latency observed so
+//! far: consistently slow relays get looser timeouts, and fast relays tighter ones.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use parking_lot::RwLock;
+use reqwest::Url;
+
+use crate::{config::RelayTimeoutOpts, telemetry::ApiMetrics};
+
+/// The number of latest calls to an endpoint whose latency is tracked.
+pub const LATENCY_WINDOW: usize = 100;
+
+/// The number of calls to an endpoint before its timeout adapts to their latency. Until
+/// then, the ceiling is used.
+pub const MIN_LATENCY_SAMPLES: usize = 10;
+
+/// The percentile of the observed latency that the timeout is derived from.
+const TIMEOUT_PERCENTILE: f64 = 0.99;
+
+/// The timeout as a percentage of the latency percentile, leaving some headroom.
+const TIMEOUT_HEADROOM_PERCENT: u32 = 150;
+
+type EndpointKey = (Url, &'static str);
+
+/// Tracks the rolling latency of the calls to each relay endpoint, and derives their
+/// timeouts from it, within the configured floor and ceiling.
+///
+/// Clones share the same state, so that all clones of the constraints client adapt to
+/// the same observations.
+#[derive(Debug, Clone, Default)]
+pub struct RelayTimeouts {
+    opts: RelayTimeoutOpts,
+    latencies: Arc<RwLock<HashMap<EndpointKey, VecDeque<Duration>>>>,
+}
+
+impl RelayTimeouts {
+    /// Creates the timeouts with the given floor and ceiling.
+    pub fn new(opts: RelayTimeoutOpts) -> Self {
+        Self { opts, latencies: Default::default() }
+    }
+
+    /// Records the latency of a call to the endpoint of the relay. Calls that timed out
+    /// are recorded with the timeout, so that it grows if the relay slows down.
+    pub fn record(&self, relay: &Url, endpoint: &'static str, latency: Duration) {
+        let mut latencies = self.latencies.write();
+        let window = latencies.entry((relay.clone(), endpoint)).or_default();
+        if window.len() == LATENCY_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(latency);
+
+        let timeout = self.derive_timeout(window);
+        ApiMetrics::set_relay_timeout(relay.to_string(), endpoint, timeout);
+    }
+
+    /// Returns the timeout of the next call to the endpoint of the relay.
+    pub fn timeout(&self, relay: &Url, endpoint: &'static str) -> Duration {
+        match self.latencies.read().get(&(relay.clone(), endpoint)) {
+            Some(window) => self.derive_timeout(window),
+            None => self.opts.ceiling(),
+        }
+    }
+
+    /// Returns the observed latency percentile of the endpoint of the relay, if called yet.
+    pub fn latency_percentile(
+        &self,
+        relay: &Url,
+        endpoint: &'static str,
+        percentile: f64,
+    ) -> Option<Duration> {
+        let latencies = self.latencies.read();
+        latencies
+            .get(&(relay.clone(), endpoint))
+            .and_then(|window| percentile_of(window, percentile))
+    }
+
+    fn derive_timeout(&self, window: &VecDeque<Duration>) -> Duration {
+        if window.len() < MIN_LATENCY_SAMPLES {
+            return self.opts.ceiling();
+        }
+
+        let Some(latency) = percentile_of(window, TIMEOUT_PERCENTILE) else {
+            return self.opts.ceiling();
+        };
+        (latency * TIMEOUT_HEADROOM_PERCENT / 100).clamp(self.opts.floor(), self.opts.ceiling())
+    }
+}
+
+/// Returns the given percentile, in [0, 1], of the latencies, with the nearest-rank method.
+fn percentile_of(latencies: &VecDeque<Duration>, percentile: f64) -> Option<Duration> {
+    if latencies.is_empty() {
+        return None;
+    }
+
+    let mut sorted = latencies.iter().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    let rank = (percentile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_timeouts() {
+        let opts =
+            RelayTimeoutOpts { relay_timeout_floor_ms: 100, relay_timeout_ceiling_ms: 2_000 };
+        let timeouts = RelayTimeouts::new(opts);
+        let (fast, slow) =
+            (Url::parse("http://fast:3030").unwrap(), Url::parse("http://slow:3030").unwrap());
+
+        // The ceiling applies until enough calls are observed
+        assert_eq!(timeouts.timeout(&fast, "get_header"), Duration::from_secs(2));
+
+        for _ in 0..MIN_LATENCY_SAMPLES {
+            timeouts.record(&fast, "get_header", Duration::from_millis(20));
+            timeouts.record(&slow, "get_header", Duration::from_millis(800));
+        }
+
+        // Fast relays get tight budgets, down to the floor
+        assert_eq!(timeouts.timeout(&fast, "get_header"), Duration::from_millis(100));
+        // Consistently slow relays get looser ones, up to the ceiling
+        assert_eq!(timeouts.timeout(&slow, "get_header"), Duration::from_millis(1_200));
+        assert_eq!(timeouts.timeout(&slow, "get_payload"), Duration::from_secs(2));
+
+        // Old observations leave the window
+        for _ in 0..LATENCY_WINDOW {
+            timeouts.record(&slow, "get_header", Duration::from_millis(200));
+        }
+        assert_eq!(timeouts.timeout(&slow, "get_header"), Duration::from_millis(300));
+        assert_eq!(
+            timeouts.latency_percentile(&slow, "get_header", 0.5),
+            Some(Duration::from_millis(200))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use alloy::{hex, signers::local::PrivateKeySigner};
+use clap::Subcommand;
+use eyre::{bail, eyre, Result};
+
+use crate::{
+    crypto::bls::cl_public_key_to_arr,
+    primitives::{read_delegations_file, DelegationFile, SignedMessages},
+    service::{ServiceDefinition, ServicePlatform},
+    signer::keystore::KeystoreSigner,
+    state::JournalInterchange,
+    BuilderApi, ConstraintsClient, Opts,
+};
+
+/// Subcommands of the sidecar binary. Without one, the sidecar runs.
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the sidecar (default)
+    Run,
+    /// Validate the configuration and print it as JSON with secrets redacted, then exit
+    CheckConfig,
+    /// Probe the status and latency of the configured relays, then exit
+    TestRelays,
+    /// Print the signed delegations loaded from the delegations file, decrypted, then exit
+    ExportDelegations,
+    /// Print the public keys of the configured signing keys, then exit
+    ShowKeys,
+    /// Print the unresolved commitments of the journal in the interchange format, then exit
+    ExportInterchange,
+    /// Import an interchange file into the journal of the stopped sidecar, then exit
+    ImportInterchange {
+        /// Path to the interchange file exported by the previous sidecar
+        path: PathBuf,
+    },
+    /// Print the definition of a service running this binary for a service manager, then exit
+    ServiceDefinition {
+        /// The service manager to define the service for
+        #[clap(value_enum)]
+        platform: ServicePlatform,
+        /// Path to the file with the `BOLT_SIDECAR_*` environment variables of the service
+        #[clap(long)]
+        env_file: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Runs an operational subcommand to completion. [Command::Run] is handled by the
+    /// caller, since it needs to set up telemetry and the operator APIs.
+    pub async fn execute(&self, opts: &Opts) -> Result<()> {
+        match self {
+            Self::Run => bail!("the run command is handled by the sidecar binary"),
+            Self::CheckConfig => check_config(opts),
+            Self::TestRelays => test_relays(opts).await,
+            Self::ExportDelegations => export_delegations(opts),
+            Self::ShowKeys => show_keys(opts),
+            Self::ExportInterchange => export_interchange(opts),
+            Self::ImportInterchange { path } => import_interchange(opts, path),
+            Self::ServiceDefinition { platform, env_file } => {
+                let definition = ServiceDefinition::for_current_exe(env_file.clone())?;
+                print!("{}", definition.render(*platform)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Validate the configuration, reporting all of its problems at once.
+fn check_config(opts: &Opts) -> Result<()> {
+    println!("{}", opts.to_pretty_json()?);
+
+    if let Err(errors) = opts.validate() {
+        bail!("{errors}");
+    }
+
+    println!("Configuration is valid");
+    Ok(())
+}
+
+/// Probe the status endpoint of the configured relays, failing if any is unhealthy.
+async fn test_relays(opts: &Opts) -> Result<()> {
+    let relays = [opts.constraints_api_url.clone()];
+    let mut unhealthy = 0;
+
+    for url in relays {
+        let client = ConstraintsClient::new(url.clone());
+
+        let start = Instant::now();
+        let status = client.status().await;
+        let latency_ms = start.elapsed().as_millis();
+
+        match status {
+            Ok(status) if status.is_success() => {
+                println!("{url}: {status} in {latency_ms}ms");
+            }
+            Ok(status) => {
+                println!("{url}: unhealthy, {status} in {latency_ms}ms");
+                unhealthy += 1;
+            }
+            Err(err) => {
+                println!("{url}: unreachable after {latency_ms}ms: {err}");
+                unhealthy += 1;
+            }
+        }
+    }
+
+    if unhealthy > 0 {
+        bail!("{unhealthy} relay(s) failed the status check");
+    }
+
+    Ok(())
+}
+
+/// Print the signed delegations of the delegations file, as they are sent to relays,
+/// in a plaintext delegation file.
+fn export_delegations(opts: &Opts) -> Result<()> {
+    let signing = &opts.constraint_signing;
+    let Some(path) = signing.delegations_path.as_ref() else {
+        bail!("no delegations file configured, set --delegations-path");
+    };
+
+    let password = signing.delegations_password.as_ref().map(|p| p.as_ref());
+    let delegations = read_delegations_file(path, &opts.chain, password)?;
+
+    let file =
+        DelegationFile::new(&opts.chain, SignedMessages { delegations, ..Default::default() });
+    println!("{}", serde_json::to_string_pretty(&file)?);
+
+    Ok(())
+}
+
+/// Print the unresolved commitments of the journal in the interchange format.
+fn export_interchange(opts: &Opts) -> Result<()> {
+    let Some(journal_path) = opts.journal_path.as_ref() else {
+        bail!("no journal configured, set --journal-path");
+    };
+
+    let interchange = JournalInterchange::export(journal_path, opts.chain.chain_id())?;
+    println!("{}", serde_json::to_string_pretty(&interchange)?);
+
+    Ok(())
+}
+
+/// Import an interchange file into the journal, refusing it if its commitments can't be
+/// honored safely by the commitment key of the sidecar.
+fn import_interchange(opts: &Opts, path: &Path) -> Result<()> {
+    let Some(journal_path) = opts.journal_path.as_ref() else {
+        bail!("no journal configured, set --journal-path");
+    };
+
+    let interchange: JournalInterchange = serde_json::from_slice(&fs::read(path)?)?;
+    let commitment_signer =
+        PrivateKeySigner::from_signing_key(opts.commitment_private_key.0.clone());
+
+    let imported =
+        interchange.import(journal_path, opts.chain.chain_id(), commitment_signer.address())?;
+    println!("Imported {imported} constraints into {}", journal_path.display());
+
+    Ok(())
+}
+
+/// Print the public keys of the configured signing keys, never the keys themselves.
+fn show_keys(opts: &Opts) -> Result<()> {
+    let signing = &opts.constraint_signing;
+
+    let builder_pubkey = opts.builder_private_key.sk_to_pk().to_bytes();
+    println!("builder: {}", hex::encode_prefixed(builder_pubkey));
+
+    let commitment_signer =
+        PrivateKeySigner::from_signing_key(opts.commitment_private_key.0.clone());
+    println!("commitment signer: {}", commitment_signer.address());
+
+    if let Some(key) = signing.constraint_private_key.as_ref() {
+        println!("constraint signer: {}", hex::encode_prefixed(key.sk_to_pk().to_bytes()));
+    } else if let Some(url) = signing.commit_boost_signer_url.as_ref() {
+        println!("constraint signer: keys held by the commit-boost signer at {url}");
+    } else if let Some(url) = signing.web3signer_url.as_ref() {
+        println!("constraint signer: keys held by the Web3Signer at {url}");
+    } else {
+        let keys_path =
+            signing.keystore_path.as_ref().ok_or(eyre!("no keystore path configured"))?;
+        let keystore = if let Some(password) = signing.keystore_password.as_ref() {
+            KeystoreSigner::from_password(keys_path, password.as_ref(), opts.chain)?
+        } else if let Some(path) = signing.keystore_password_path.as_ref() {
+            KeystoreSigner::from_password_file(keys_path, path, opts.chain)?
+        } else {
+            let secrets_path = signing
+                .keystore_secrets_path
+                .as_ref()
+                .ok_or(eyre!("no keystore secrets configured"))?;
+            KeystoreSigner::from_secrets_directory(keys_path, secrets_path, opts.chain)?
+        };
+
+        for pubkey in keystore.pubkeys() {
+            println!("constraint signer: {}", hex::encode_prefixed(cl_public_key_to_arr(pubkey)));
+        }
+    }
+
+    Ok(())
+}
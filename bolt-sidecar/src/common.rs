@@ -3,13 +3,15 @@ use std::{
     fs::read_to_string,
     ops::Deref,
     path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use alloy::{primitives::U256, signers::k256::ecdsa::SigningKey};
 use blst::min_pk::SecretKey;
 use rand::{Rng, RngCore};
 use reth_primitives::PooledTransactionsElement;
-use serde::{Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
     primitives::{AccountState, TransactionExt},
@@ -19,6 +21,11 @@ use crate::{
 /// The version of the Bolt sidecar binary.
 pub const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Returns the current time in unix milliseconds.
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
 /// Calculates the max_basefee `slot_diff` blocks in the future given a current basefee (in wei).
 /// Returns None if an overflow would occur.
 /// Cfr. https://github.com/flashbots/ethers-provider-flashbots-bundle/blob/7ddaf2c9d7662bef400151e0bfc89f5b13e72b4c/src/index.ts#L308
@@ -117,15 +124,19 @@ impl<'de> Deserialize<'de> for BlsSecretKeyWrapper {
         D: Deserializer<'de>,
     {
         let sk = String::deserialize(deserializer)?;
-        Ok(BlsSecretKeyWrapper::from(sk.as_str()))
+        sk.parse().map_err(de::Error::custom)
     }
 }
 
-impl From<&str> for BlsSecretKeyWrapper {
-    fn from(sk: &str) -> Self {
+impl FromStr for BlsSecretKeyWrapper {
+    type Err = String;
+
+    fn from_str(sk: &str) -> Result<Self, Self::Err> {
         let hex_sk = sk.strip_prefix("0x").unwrap_or(sk);
-        let sk = SecretKey::from_bytes(&hex::decode(hex_sk).expect("valid hex")).expect("valid sk");
-        BlsSecretKeyWrapper(sk)
+        let bytes = hex::decode(hex_sk).map_err(|e| format!("BLS secret key is not hex: {e}"))?;
+        let sk = SecretKey::from_bytes(&bytes)
+            .map_err(|e| format!("invalid BLS secret key, expected 32 bytes: {e:?}"))?;
+        Ok(BlsSecretKeyWrapper(sk))
     }
 }
 
@@ -136,6 +147,12 @@ impl Deref for BlsSecretKeyWrapper {
     }
 }
 
+impl Serialize for BlsSecretKeyWrapper {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_redacted(self, serializer)
+    }
+}
+
 impl fmt::Display for BlsSecretKeyWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "0x{}", hex::encode(self.0.to_bytes()))
@@ -147,7 +164,6 @@ pub struct EcdsaSecretKeyWrapper(pub SigningKey);
 
 impl EcdsaSecretKeyWrapper {
     /// Generate a new random ECDSA secret key.
-    #[allow(dead_code)]
     pub fn random() -> Self {
         Self(SigningKey::random(&mut rand::thread_rng()))
     }
@@ -159,16 +175,25 @@ impl<'de> Deserialize<'de> for EcdsaSecretKeyWrapper {
         D: Deserializer<'de>,
     {
         let sk = String::deserialize(deserializer)?;
-        Ok(EcdsaSecretKeyWrapper::from(sk.as_str()))
+        sk.parse().map_err(de::Error::custom)
     }
 }
 
-impl From<&str> for EcdsaSecretKeyWrapper {
-    fn from(sk: &str) -> Self {
+impl FromStr for EcdsaSecretKeyWrapper {
+    type Err = String;
+
+    fn from_str(sk: &str) -> Result<Self, Self::Err> {
         let hex_sk = sk.strip_prefix("0x").unwrap_or(sk);
-        let bytes = hex::decode(hex_sk).expect("valid hex");
-        let sk = SigningKey::from_slice(&bytes).expect("valid sk");
-        EcdsaSecretKeyWrapper(sk)
+        let bytes = hex::decode(hex_sk).map_err(|e| format!("ECDSA secret key is not hex: {e}"))?;
+        let sk = SigningKey::from_slice(&bytes)
+            .map_err(|_| "invalid ECDSA secret key, expected 32 bytes".to_string())?;
+        Ok(EcdsaSecretKeyWrapper(sk))
+    }
+}
+
+impl Serialize for EcdsaSecretKeyWrapper {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_redacted(self, serializer)
     }
 }
 
@@ -197,22 +222,29 @@ impl Default for JwtSecretConfig {
     }
 }
 
-impl From<&str> for JwtSecretConfig {
-    fn from(jwt: &str) -> Self {
+impl FromStr for JwtSecretConfig {
+    type Err = String;
+
+    fn from_str(jwt: &str) -> Result<Self, Self::Err> {
         let jwt = if jwt.starts_with("0x") {
             jwt.trim_start_matches("0x").to_string()
         } else if Path::new(&jwt).exists() {
             read_to_string(jwt)
-                .unwrap_or_else(|_| panic!("Failed reading JWT secret file: {:?}", jwt))
+                .map_err(|e| format!("failed reading JWT secret file {jwt}: {e}"))?
+                .trim()
                 .trim_start_matches("0x")
                 .to_string()
         } else {
             jwt.to_string()
         };
 
-        assert!(jwt.len() == 64, "Engine JWT secret must be a 32 byte hex string");
+        if jwt.len() != 64 || hex::decode(&jwt).is_err() {
+            return Err(
+                "JWT secret must be a 32 byte hex string or a file containing one".to_string()
+            );
+        }
 
-        Self(jwt)
+        Ok(Self(jwt))
     }
 }
 
@@ -222,7 +254,13 @@ impl<'de> Deserialize<'de> for JwtSecretConfig {
         D: serde::Deserializer<'de>,
     {
         let jwt = String::deserialize(deserializer)?;
-        Ok(Self::from(jwt.as_str()))
+        jwt.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for JwtSecretConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_redacted(self, serializer)
     }
 }
 
@@ -239,6 +277,14 @@ impl Display for JwtSecretConfig {
     }
 }
 
+/// The placeholder of secrets in serialized configurations.
+pub const REDACTED: &str = "********";
+
+/// Serialize a secret as a placeholder, so that it never ends up in printed configurations.
+pub fn serialize_redacted<T, S: Serializer>(_: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,8 +6,8 @@ use std::{
 
 use alloy::primitives::{address, Address};
 use clap::{Args, ValueEnum};
-use ethereum_consensus::deneb::{compute_fork_data_root, Root};
-use serde::Deserialize;
+use ethereum_consensus::{phase0::mainnet::SLOTS_PER_EPOCH, Fork};
+use serde::{Deserialize, Serialize};
 
 /// Default commitment deadline duration.
 ///
@@ -19,26 +19,29 @@ pub const DEFAULT_COMMITMENT_DEADLINE_IN_MILLIS: u64 = 8_000;
 /// Default slot time duration in seconds.
 pub const DEFAULT_SLOT_TIME_IN_SECONDS: u64 = 12;
 
-/// The domain mask for signing application-builder messages.
-pub const APPLICATION_BUILDER_DOMAIN_MASK: [u8; 4] = [0, 0, 0, 1];
-
-/// The domain mask for signing commit-boost messages.
-pub const COMMIT_BOOST_DOMAIN_MASK: [u8; 4] = [109, 109, 111, 67];
+pub use bolt_primitives::signing::{APPLICATION_BUILDER_DOMAIN_MASK, COMMIT_BOOST_DOMAIN_MASK};
 
 pub const DEFAULT_CHAIN_CONFIG: ChainConfig = ChainConfig {
     chain: Chain::Mainnet,
     commitment_deadline: DEFAULT_COMMITMENT_DEADLINE_IN_MILLIS,
     slot_time: DEFAULT_SLOT_TIME_IN_SECONDS,
     enable_unsafe_lookahead: false,
+    electra_fork_epoch: None,
 };
 
+/// The epoch of the Electra fork on Mainnet.
+pub const ELECTRA_FORK_EPOCH_MAINNET: u64 = 364_032;
+
+/// The epoch of the Electra fork on Holesky.
+pub const ELECTRA_FORK_EPOCH_HOLESKY: u64 = 115_968;
+
 /// The address of the canonical BoltManager contract for the Holesky chain.
 ///
 /// https://holesky.etherscan.io/address/0x440202829b493F9FF43E730EB5e8379EEa3678CF
 pub const MANAGER_ADDRESS_HOLESKY: Address = address!("440202829b493F9FF43E730EB5e8379EEa3678CF");
 
 /// Configuration for the chain the sidecar is running on.
-#[derive(Debug, Clone, Copy, Args, Deserialize)]
+#[derive(Debug, Clone, Copy, Args, Serialize, Deserialize)]
 pub struct ChainConfig {
     /// Chain on which the sidecar is running
     #[clap(
@@ -71,6 +74,10 @@ pub struct ChainConfig {
         default_value_t = DEFAULT_CHAIN_CONFIG.enable_unsafe_lookahead
     )]
     pub(crate) enable_unsafe_lookahead: bool,
+    /// The epoch of the Electra fork. If provided, it overrides the default
+    /// for the selected [Chain], e.g. for devnets.
+    #[clap(long, env = "BOLT_SIDECAR_ELECTRA_FORK_EPOCH")]
+    pub(crate) electra_fork_epoch: Option<u64>,
 }
 
 impl Default for ChainConfig {
@@ -80,7 +87,7 @@ impl Default for ChainConfig {
 }
 
 /// Supported chains for the sidecar
-#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize)]
 #[clap(rename_all = "kebab_case")]
 pub enum Chain {
     Mainnet,
@@ -109,6 +116,15 @@ impl Chain {
         }
     }
 
+    /// Returns the epoch of the Electra fork on the given chain, if it is scheduled.
+    pub const fn electra_fork_epoch(&self) -> Option<u64> {
+        match self {
+            Chain::Mainnet => Some(ELECTRA_FORK_EPOCH_MAINNET),
+            Chain::Holesky => Some(ELECTRA_FORK_EPOCH_HOLESKY),
+            _ => None,
+        }
+    }
+
     /// Returns the address of the canonical BoltManager contract for a given chain, if present
     pub const fn manager_address(&self) -> Option<Address> {
         match self {
@@ -160,21 +176,22 @@ impl ChainConfig {
         Duration::from_millis(self.commitment_deadline)
     }
 
-    /// Compute the domain for signing messages on the given chain.
-    fn compute_domain_from_mask(&self, mask: [u8; 4]) -> [u8; 32] {
-        let mut domain = [0; 32];
-
-        let fork_version = self.chain.fork_version();
+    /// Get the epoch of the Electra fork, either configured or known for the given chain.
+    pub fn electra_fork_epoch(&self) -> Option<u64> {
+        self.electra_fork_epoch.or(self.chain.electra_fork_epoch())
+    }
 
-        // Note: the application builder domain specs require the genesis_validators_root
-        // to be 0x00 for any out-of-protocol message. The commit-boost domain follows the
-        // same rule.
-        let root = Root::default();
-        let fork_data_root = compute_fork_data_root(fork_version, root).expect("valid fork data");
+    /// Get the fork active at the given slot, among the forks supported by the sidecar.
+    pub fn fork_at_slot(&self, slot: u64) -> Fork {
+        match self.electra_fork_epoch() {
+            Some(epoch) if slot / SLOTS_PER_EPOCH >= epoch => Fork::Electra,
+            _ => Fork::Deneb,
+        }
+    }
 
-        domain[..4].copy_from_slice(&mask);
-        domain[4..].copy_from_slice(&fork_data_root[..28]);
-        domain
+    /// Compute the domain for signing messages on the given chain.
+    fn compute_domain_from_mask(&self, mask: [u8; 4]) -> [u8; 32] {
+        bolt_primitives::compute_domain(mask, self.chain.fork_version())
     }
 }
 
@@ -234,4 +251,23 @@ mod tests {
         let kurtosis = ChainConfig::kurtosis(0, 0);
         assert_eq!(kurtosis.application_builder_domain(), BUILDER_DOMAIN_KURTOSIS);
     }
+
+    #[test]
+    fn test_fork_at_slot() {
+        use ethereum_consensus::Fork;
+
+        use super::{ChainConfig, ELECTRA_FORK_EPOCH_MAINNET};
+
+        let mainnet = ChainConfig::mainnet();
+        let electra_slot = ELECTRA_FORK_EPOCH_MAINNET * 32;
+        assert_eq!(mainnet.fork_at_slot(electra_slot - 1), Fork::Deneb);
+        assert_eq!(mainnet.fork_at_slot(electra_slot), Fork::Electra);
+
+        // Devnets have no Electra fork unless configured
+        let mut kurtosis = ChainConfig::kurtosis(12, 8000);
+        assert_eq!(kurtosis.fork_at_slot(1_000_000), Fork::Deneb);
+        kurtosis.electra_fork_epoch = Some(2);
+        assert_eq!(kurtosis.fork_at_slot(63), Fork::Deneb);
+        assert_eq!(kurtosis.fork_at_slot(64), Fork::Electra);
+    }
 }
@@ -3,15 +3,18 @@ use std::{fmt, path::PathBuf};
 use clap::{ArgGroup, Args};
 use lighthouse_account_utils::ZeroizeString;
 use reqwest::Url;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::common::{BlsSecretKeyWrapper, JwtSecretConfig};
+use crate::{
+    common::{serialize_redacted, BlsSecretKeyWrapper, JwtSecretConfig},
+    signer::web3signer::Web3SignerTlsCredentials,
+};
 
 /// Command-line options for signing constraint messages
-#[derive(Args, Deserialize)]
+#[derive(Args, Serialize, Deserialize)]
 #[clap(
     group = ArgGroup::new("signing-opts").required(true)
-        .args(&["constraint_private_key", "commit_boost_signer_url", "keystore_password", "keystore_secrets_path"])
+        .args(&["constraint_private_key", "commit_boost_signer_url", "web3signer_url", "keystore_password", "keystore_password_path", "keystore_secrets_path"])
 )]
 pub struct ConstraintSigningOpts {
     /// Private key to use for signing constraint messages
@@ -23,10 +26,41 @@ pub struct ConstraintSigningOpts {
     /// JWT in hexadecimal format for authenticating with the commit-boost service
     #[clap(long, env = "BOLT_SIDECAR_CB_JWT_HEX", requires("commit_boost_signer_url"))]
     pub commit_boost_jwt_hex: Option<JwtSecretConfig>,
+    /// URL of a Web3Signer holding the constraint signing keys, which are then never loaded
+    /// by the sidecar
+    #[clap(long, env = "BOLT_SIDECAR_WEB3SIGNER_URL")]
+    pub web3signer_url: Option<Url>,
+    /// Path to the PEM-encoded client certificate authenticating the sidecar to the Web3Signer
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_WEB3SIGNER_CLIENT_CERT_PATH",
+        requires_all(["web3signer_url", "web3signer_client_key_path"])
+    )]
+    pub web3signer_client_cert_path: Option<PathBuf>,
+    /// Path to the PEM-encoded PKCS#8 private key of the Web3Signer client certificate
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_WEB3SIGNER_CLIENT_KEY_PATH",
+        requires("web3signer_client_cert_path")
+    )]
+    pub web3signer_client_key_path: Option<PathBuf>,
+    /// Path to the PEM-encoded CA certificate of the Web3Signer, if it is not trusted by the
+    /// system
+    #[clap(long, env = "BOLT_SIDECAR_WEB3SIGNER_CA_CERT_PATH", requires("web3signer_url"))]
+    pub web3signer_ca_cert_path: Option<PathBuf>,
     /// The password for the ERC-2335 keystore.
     /// Reference: https://eips.ethereum.org/EIPS/eip-2335
     #[clap(long, env = "BOLT_SIDECAR_KEYSTORE_PASSWORD")]
+    #[serde(serialize_with = "serialize_redacted")]
     pub keystore_password: Option<ZeroizeString>,
+    /// The path to a file with the password for the ERC-2335 keystore, to avoid passing it
+    /// through the environment. Trailing newlines are ignored.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_KEYSTORE_PASSWORD_PATH",
+        conflicts_with_all(["keystore_password", "keystore_secrets_path"])
+    )]
+    pub keystore_password_path: Option<PathBuf>,
     /// The path to the ERC-2335 keystore secret passwords
     /// Reference: https://eips.ethereum.org/EIPS/eip-2335
     #[clap(long, env = "BOLT_SIDECAR_KEYSTORE_SECRETS_PATH", conflicts_with("keystore_password"))]
@@ -37,6 +71,23 @@ pub struct ConstraintSigningOpts {
     /// Path to the delegations file. If not provided, the default path is used.
     #[clap(long, env = "BOLT_SIDECAR_DELEGATIONS_PATH")]
     pub delegations_path: Option<PathBuf>,
+    /// The password of the delegations file, if it is encrypted.
+    #[clap(long, env = "BOLT_SIDECAR_DELEGATIONS_PASSWORD", requires("delegations_path"))]
+    #[serde(serialize_with = "serialize_redacted")]
+    pub delegations_password: Option<ZeroizeString>,
+}
+
+impl ConstraintSigningOpts {
+    /// Returns the TLS credentials for the Web3Signer, if a client certificate is configured.
+    pub fn web3signer_tls_credentials(&self) -> Option<Web3SignerTlsCredentials> {
+        let client_cert_path = self.web3signer_client_cert_path.clone()?;
+        let client_key_path = self.web3signer_client_key_path.clone()?;
+        Some(Web3SignerTlsCredentials {
+            client_cert_path,
+            client_key_path,
+            ca_cert_path: self.web3signer_ca_cert_path.clone(),
+        })
+    }
 }
 
 // Implement Debug manually to hide the keystore_password field
@@ -46,9 +97,16 @@ impl fmt::Debug for ConstraintSigningOpts {
             .field("constraint_private_key", &"********") // Hides the actual private key
             .field("commit_boost_signer_url", &self.commit_boost_signer_url)
             .field("commit_boost_jwt_hex", &self.commit_boost_jwt_hex)
+            .field("web3signer_url", &self.web3signer_url)
+            .field("web3signer_client_cert_path", &self.web3signer_client_cert_path)
+            .field("web3signer_client_key_path", &self.web3signer_client_key_path)
+            .field("web3signer_ca_cert_path", &self.web3signer_ca_cert_path)
             .field("keystore_password", &"********") // Hides the actual password
+            .field("keystore_password_path", &self.keystore_password_path)
             .field("keystore_path", &self.keystore_path)
             .field("keystore_secrets_path", &self.keystore_secrets_path)
+            .field("delegations_path", &self.delegations_path)
+            .field("delegations_password", &"********") // Hides the actual password
             .finish()
     }
 }
@@ -0,0 +1,88 @@
+use std::{fmt, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use serde::{Deserialize, Serialize};
+
+/// The policy deciding which delegatee (gateway) receives the authority over each
+/// proposal slot of a validator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[clap(rename_all = "kebab_case")]
+#[serde(rename_all = "kebab-case")]
+pub enum DelegationPolicy {
+    /// Any delegatee whose key is available signs the constraints of any slot.
+    #[default]
+    Any,
+    /// The delegatees take turns, by slot number.
+    RoundRobin,
+    /// The first delegatee gets every slot, unless the relay persistently refuses its
+    /// delegation, in which case the next one does.
+    PrimaryBackup,
+    /// The slots are assigned to delegatees by the assignments file, the others are assigned
+    /// as with `primary-backup`.
+    PerSlot,
+}
+
+impl DelegationPolicy {
+    /// Returns the name of the policy, as passed on the command line.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::RoundRobin => "round-robin",
+            Self::PrimaryBackup => "primary-backup",
+            Self::PerSlot => "per-slot",
+        }
+    }
+}
+
+impl fmt::Display for DelegationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options for targeting the delegation of each upcoming proposal slot to a single delegatee.
+///
+/// The delegatees of a validator are those of `--delegatees` if set, or else those of its
+/// delegations in the delegations file, in order of preference.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct DelegationTargetingOpts {
+    /// Policy deciding which delegatee receives the authority over each proposal slot
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_DELEGATION_POLICY",
+        value_enum,
+        default_value_t = DelegationPolicy::Any
+    )]
+    pub delegation_policy: DelegationPolicy,
+    /// Comma-separated public keys of the delegatees to assign the proposal slots to, in order
+    /// of preference. Delegations to them are signed for each slot with the validator keys
+    /// available to the sidecar, unless found in the delegations file
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_DELEGATEES",
+        value_delimiter = ',',
+        value_parser = parse_bls_public_key
+    )]
+    #[serde(default)]
+    pub delegatees: Vec<BlsPublicKey>,
+    /// Path to a JSON file mapping proposal slots to the public key of their delegatee,
+    /// for the `per-slot` policy
+    #[clap(long, env = "BOLT_SIDECAR_DELEGATION_ASSIGNMENTS_PATH")]
+    pub delegation_assignments_path: Option<PathBuf>,
+}
+
+impl DelegationTargetingOpts {
+    /// Returns true if the proposal slots are assigned to a single delegatee each.
+    pub fn enabled(&self) -> bool {
+        self.delegation_policy != DelegationPolicy::Any
+    }
+}
+
+/// Parse a hex-encoded BLS public key.
+fn parse_bls_public_key(s: &str) -> Result<BlsPublicKey, String> {
+    let hex_pk = s.strip_prefix("0x").unwrap_or(s);
+    let bytes = hex::decode(hex_pk).map_err(|e| format!("BLS public key is not hex: {e}"))?;
+    BlsPublicKey::try_from(bytes.as_slice())
+        .map_err(|e| format!("invalid BLS public key, expected 48 bytes: {e:?}"))
+}
@@ -0,0 +1,90 @@
+use std::{fmt, time::Duration};
+
+use clap::{Parser, ValueEnum};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+// Default failover values
+pub const DEFAULT_FAILOVER_PORT: u16 = 8019;
+pub const DEFAULT_FAILOVER_HEARTBEAT_INTERVAL_MS: u64 = 500;
+pub const DEFAULT_FAILOVER_HEARTBEAT_TIMEOUT_MS: u64 = 2_000;
+
+/// The role of a sidecar in an active/standby pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[clap(rename_all = "kebab_case")]
+#[serde(rename_all = "kebab-case")]
+pub enum FailoverRole {
+    /// Serve the commitments, mirroring their constraints and sending heartbeats to the standby.
+    Primary,
+    /// Take over the submission of the mirrored constraints if the primary misses its
+    /// heartbeats before their commitment deadline.
+    Standby,
+}
+
+impl fmt::Display for FailoverRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Primary => "primary",
+            Self::Standby => "standby",
+        })
+    }
+}
+
+/// Options for running the sidecar in an active/standby pair, coordinated over a gRPC
+/// channel from the primary to the standby.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+pub struct FailoverOpts {
+    /// Role of the sidecar in an active/standby pair. If not provided, the sidecar runs alone
+    #[clap(long, env = "BOLT_SIDECAR_FAILOVER_ROLE", value_enum)]
+    pub failover_role: Option<FailoverRole>,
+    /// URL of the failover server of the standby, that the primary mirrors its constraints
+    /// and sends its heartbeats to
+    #[clap(long, env = "BOLT_SIDECAR_FAILOVER_STANDBY_URL")]
+    pub failover_standby_url: Option<Url>,
+    /// Port on which the standby listens for the primary
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_FAILOVER_PORT",
+        default_value_t = FailoverOpts::default().failover_port
+    )]
+    pub failover_port: u16,
+    /// Interval in milliseconds at which the primary sends heartbeats to the standby
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_FAILOVER_HEARTBEAT_INTERVAL_MS",
+        default_value_t = FailoverOpts::default().failover_heartbeat_interval_ms
+    )]
+    pub failover_heartbeat_interval_ms: u64,
+    /// Time in milliseconds without heartbeats after which the standby considers the primary
+    /// down
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_FAILOVER_HEARTBEAT_TIMEOUT_MS",
+        default_value_t = FailoverOpts::default().failover_heartbeat_timeout_ms
+    )]
+    pub failover_heartbeat_timeout_ms: u64,
+}
+
+impl FailoverOpts {
+    /// Returns the interval of the heartbeats of the primary.
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_millis(self.failover_heartbeat_interval_ms)
+    }
+
+    /// Returns the time without heartbeats after which the primary is considered down.
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_millis(self.failover_heartbeat_timeout_ms)
+    }
+}
+
+impl Default for FailoverOpts {
+    fn default() -> Self {
+        Self {
+            failover_role: None,
+            failover_standby_url: None,
+            failover_port: DEFAULT_FAILOVER_PORT,
+            failover_heartbeat_interval_ms: DEFAULT_FAILOVER_HEARTBEAT_INTERVAL_MS,
+            failover_heartbeat_timeout_ms: DEFAULT_FAILOVER_HEARTBEAT_TIMEOUT_MS,
+        }
+    }
+}
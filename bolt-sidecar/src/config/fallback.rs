@@ -0,0 +1,32 @@
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum number of mempool transactions to fill a local block with
+pub const DEFAULT_FALLBACK_MAX_MEMPOOL_TXS: usize = 256;
+
+/// Local building of the fallback block, proposed instead of the relay bid when the relays
+/// fail to return a header.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct FallbackOpts {
+    /// Fill the remaining gas of locally built blocks with the pending transactions of the
+    /// public mempool, after the committed ones. Requires the `txpool` namespace
+    #[clap(long, env = "BOLT_SIDECAR_FALLBACK_MEMPOOL_FILL", default_value_t = false)]
+    #[serde(default)]
+    pub fallback_mempool_fill: bool,
+    /// Maximum number of mempool transactions to fill a locally built block with
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_FALLBACK_MAX_MEMPOOL_TXS",
+        default_value_t = FallbackOpts::default().fallback_max_mempool_txs
+    )]
+    pub fallback_max_mempool_txs: usize,
+}
+
+impl Default for FallbackOpts {
+    fn default() -> Self {
+        Self {
+            fallback_mempool_fill: false,
+            fallback_max_mempool_txs: DEFAULT_FALLBACK_MAX_MEMPOOL_TXS,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+use alloy::primitives::Address;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+/// What to do with a validator registration or a relay bid that doesn't use one of the
+/// expected fee recipients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[clap(rename_all = "kebab_case")]
+#[serde(rename_all = "kebab-case")]
+pub enum FeeRecipientMismatchAction {
+    /// Log an error and count the mismatch, but let the registration or bid through.
+    #[default]
+    Alert,
+    /// Drop the registration before it is forwarded to the relay, or discard the bid in
+    /// favor of the locally built block.
+    Reject,
+}
+
+impl fmt::Display for FeeRecipientMismatchAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Alert => "alert",
+            Self::Reject => "reject",
+        })
+    }
+}
+
+/// Options for checking that the validator registrations and the relay bids use the fee
+/// recipients of the operator, so that a misconfigured registration can't silently
+/// redirect the rewards of the validators.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct FeeRecipientOpts {
+    /// The fee recipients that validator registrations and relay bids are expected to use.
+    /// Multiple addresses must be separated by commas. If empty, they are not checked.
+    #[clap(long, env = "BOLT_SIDECAR_EXPECTED_FEE_RECIPIENTS", value_delimiter = ',')]
+    #[serde(default)]
+    pub expected_fee_recipients: Vec<Address>,
+    /// Whether to only alert on (`alert`) or to reject (`reject`) the registrations and bids
+    /// using another fee recipient
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_FEE_RECIPIENT_MISMATCH",
+        value_enum,
+        default_value_t = FeeRecipientMismatchAction::Alert
+    )]
+    #[serde(default)]
+    pub fee_recipient_mismatch: FeeRecipientMismatchAction,
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::ProposalStage;
+
+// Default latency budgets, in milliseconds
+pub const DEFAULT_COMMITMENT_DEADLINE_BUDGET_MS: u64 = 500;
+pub const DEFAULT_CONSTRAINT_SIGNING_BUDGET_MS: u64 = 50;
+pub const DEFAULT_CONSTRAINTS_SUBMISSION_BUDGET_MS: u64 = 250;
+pub const DEFAULT_GET_HEADER_BUDGET_MS: u64 = 400;
+pub const DEFAULT_GET_PAYLOAD_BUDGET_MS: u64 = 1_000;
+
+/// Latency budgets for each stage of the proposal-critical path.
+///
+/// Exceeding a budget does not abort the stage, it only emits a warning and increments
+/// a metric so that operators can tune their timeouts to their network position.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct LatencyBudgetOpts {
+    /// Budget in milliseconds for building the local payload once the commitment deadline is hit
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_DEADLINE_BUDGET_MS",
+        default_value_t = LatencyBudgetOpts::default().commitment_deadline_budget_ms
+    )]
+    pub commitment_deadline_budget_ms: u64,
+    /// Budget in milliseconds for signing the constraints of a single commitment request
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_CONSTRAINT_SIGNING_BUDGET_MS",
+        default_value_t = LatencyBudgetOpts::default().constraint_signing_budget_ms
+    )]
+    pub constraint_signing_budget_ms: u64,
+    /// Budget in milliseconds for the relays to acknowledge submitted constraints
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_CONSTRAINTS_SUBMISSION_BUDGET_MS",
+        default_value_t = LatencyBudgetOpts::default().constraints_submission_budget_ms
+    )]
+    pub constraints_submission_budget_ms: u64,
+    /// Budget in milliseconds for serving a `get_header` request from the beacon node
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_GET_HEADER_BUDGET_MS",
+        default_value_t = LatencyBudgetOpts::default().get_header_budget_ms
+    )]
+    pub get_header_budget_ms: u64,
+    /// Budget in milliseconds for serving a `get_payload` request from the beacon node
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_GET_PAYLOAD_BUDGET_MS",
+        default_value_t = LatencyBudgetOpts::default().get_payload_budget_ms
+    )]
+    pub get_payload_budget_ms: u64,
+}
+
+impl LatencyBudgetOpts {
+    /// Returns the configured budget for the given stage.
+    pub fn budget(&self, stage: ProposalStage) -> Duration {
+        let ms = match stage {
+            ProposalStage::CommitmentDeadline => self.commitment_deadline_budget_ms,
+            ProposalStage::ConstraintSigning => self.constraint_signing_budget_ms,
+            ProposalStage::ConstraintsSubmission => self.constraints_submission_budget_ms,
+            ProposalStage::GetHeader => self.get_header_budget_ms,
+            ProposalStage::GetPayload => self.get_payload_budget_ms,
+        };
+
+        Duration::from_millis(ms)
+    }
+}
+
+impl Default for LatencyBudgetOpts {
+    fn default() -> Self {
+        Self {
+            commitment_deadline_budget_ms: DEFAULT_COMMITMENT_DEADLINE_BUDGET_MS,
+            constraint_signing_budget_ms: DEFAULT_CONSTRAINT_SIGNING_BUDGET_MS,
+            constraints_submission_budget_ms: DEFAULT_CONSTRAINTS_SUBMISSION_BUDGET_MS,
+            get_header_budget_ms: DEFAULT_GET_HEADER_BUDGET_MS,
+            get_payload_budget_ms: DEFAULT_GET_PAYLOAD_BUDGET_MS,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::num::NonZero;
 
 use clap::Parser;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 // Default limit values
 pub const DEFAULT_MAX_COMMITMENTS: usize = 128;
@@ -9,7 +9,7 @@ pub const DEFAULT_MAX_COMMITTED_GAS: u64 = 10_000_000;
 pub const DEFAULT_MIN_PRIORITY_FEE: u128 = 1_000_000_000; // 1 Gwei
 
 /// Limits for the sidecar.
-#[derive(Debug, Parser, Clone, Copy, Deserialize)]
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
 pub struct LimitsOpts {
     /// Max number of commitments to accept per block
     #[clap(
@@ -32,6 +32,24 @@ pub struct LimitsOpts {
         default_value_t = LimitsOpts::default().min_priority_fee
     )]
     pub min_priority_fee: NonZero<u128>,
+    /// Gas of each block that is never allocated to commitments, e.g. to leave room for the
+    /// payout transaction of the builder or transactions mandated by the operator
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RESERVED_TEMPLATE_GAS",
+        default_value_t = LimitsOpts::default().reserved_template_gas
+    )]
+    #[serde(default)]
+    pub reserved_template_gas: u64,
+}
+
+impl LimitsOpts {
+    /// Returns the gas that commitments can use in a block with the given gas limit: the max
+    /// committed gas per slot, lowered if needed to leave the reserved template gas free.
+    pub fn committable_gas(&self, block_gas_limit: u64) -> u64 {
+        let unreserved = block_gas_limit.saturating_sub(self.reserved_template_gas);
+        self.max_committed_gas_per_slot.get().min(unreserved)
+    }
 }
 
 impl Default for LimitsOpts {
@@ -42,6 +60,7 @@ impl Default for LimitsOpts {
             max_committed_gas_per_slot: NonZero::new(DEFAULT_MAX_COMMITTED_GAS)
                 .expect("Valid non-zero"),
             min_priority_fee: NonZero::new(DEFAULT_MIN_PRIORITY_FEE).expect("Valid non-zero"),
+            reserved_template_gas: 0,
         }
     }
 }
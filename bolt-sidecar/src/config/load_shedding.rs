@@ -0,0 +1,64 @@
+use std::{num::NonZero, time::Duration};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+// Default load shedding values
+pub const DEFAULT_LOAD_SHEDDING_WINDOW_MS: u64 = 1_000;
+pub const DEFAULT_LOAD_SHEDDING_QUEUE_THRESHOLD: usize = 64;
+pub const DEFAULT_LOAD_SHEDDING_FEE_MULTIPLIER: u128 = 2;
+
+/// Options for shedding low-value work under heavy load ahead of a proposal, to protect the
+/// proposal-critical path.
+///
+/// Load is shed when the next slot is proposed by one of the validators of the sidecar, its
+/// commitment deadline is within the window, and at least the threshold of requests are queued.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct LoadSheddingOpts {
+    /// Window in milliseconds before the commitment deadline of a proposal slot in which load
+    /// can be shed. Set to 0 to disable load shedding
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_LOAD_SHEDDING_WINDOW_MS",
+        default_value_t = LoadSheddingOpts::default().load_shedding_window_ms
+    )]
+    pub load_shedding_window_ms: u64,
+    /// Number of queued commitment and simulation requests from which load is shed
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_LOAD_SHEDDING_QUEUE_THRESHOLD",
+        default_value_t = LoadSheddingOpts::default().load_shedding_queue_threshold
+    )]
+    pub load_shedding_queue_threshold: NonZero<usize>,
+    /// Multiplier of the min priority fee that commitment requests must pay while load is shed
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_LOAD_SHEDDING_FEE_MULTIPLIER",
+        default_value_t = LoadSheddingOpts::default().load_shedding_fee_multiplier
+    )]
+    pub load_shedding_fee_multiplier: NonZero<u128>,
+}
+
+impl LoadSheddingOpts {
+    /// Returns true if load can be shed.
+    pub fn enabled(&self) -> bool {
+        self.load_shedding_window_ms > 0
+    }
+
+    /// Returns the window before the commitment deadline in which load can be shed.
+    pub fn window(&self) -> Duration {
+        Duration::from_millis(self.load_shedding_window_ms)
+    }
+}
+
+impl Default for LoadSheddingOpts {
+    fn default() -> Self {
+        Self {
+            load_shedding_window_ms: DEFAULT_LOAD_SHEDDING_WINDOW_MS,
+            load_shedding_queue_threshold: NonZero::new(DEFAULT_LOAD_SHEDDING_QUEUE_THRESHOLD)
+                .expect("Valid non-zero"),
+            load_shedding_fee_multiplier: NonZero::new(DEFAULT_LOAD_SHEDDING_FEE_MULTIPLIER)
+                .expect("Valid non-zero"),
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use std::{num::NonZero, path::PathBuf};
+
 use alloy::primitives::Address;
 use clap::Parser;
 use reqwest::Url;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub mod validator_indexes;
 pub use validator_indexes::ValidatorIndexes;
@@ -16,9 +18,69 @@ pub mod telemetry;
 use telemetry::TelemetryOpts;
 
 pub mod limits;
-use limits::LimitsOpts;
+pub use limits::LimitsOpts;
+
+pub mod latency;
+pub use latency::LatencyBudgetOpts;
+
+pub mod fallback;
+pub use fallback::FallbackOpts;
+
+pub mod failover;
+pub use failover::{FailoverOpts, FailoverRole};
+
+pub mod relay_timeouts;
+pub use relay_timeouts::RelayTimeoutOpts;
+
+pub mod reservations;
+pub use reservations::ReservationOpts;
+
+pub mod vouchers;
+pub use vouchers::VoucherOpts;
+
+pub mod pricing;
+pub use pricing::{MarginCurve, PricingOpts};
+
+pub mod load_shedding;
+pub use load_shedding::LoadSheddingOpts;
+
+pub mod rate_limits;
+pub use rate_limits::RateLimitOpts;
 
-use crate::common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig};
+pub mod whitelist;
+pub use whitelist::WhitelistOpts;
+
+pub mod delegation_targeting;
+pub use delegation_targeting::{DelegationPolicy, DelegationTargetingOpts};
+
+pub mod tx_policy;
+pub use tx_policy::TxPolicyOpts;
+
+pub mod screening;
+pub use screening::{ScreeningFailMode, ScreeningOpts};
+
+pub mod fee_recipients;
+pub use fee_recipients::{FeeRecipientMismatchAction, FeeRecipientOpts};
+
+pub mod validation;
+pub use validation::{ConfigError, ConfigErrors};
+
+pub mod networks;
+pub use networks::read_networks_file;
+
+pub mod reload;
+#[cfg(unix)]
+pub use reload::spawn_sighup_reloader;
+pub use reload::{
+    spawn_whitelist_watcher, ConfigChange, ConfigReloader, ReloadError, ReloadSource,
+    ReloadableConfig,
+};
+
+use crate::{
+    commands::Command,
+    commitments::networks::NetworkRouter,
+    common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
+};
 
 /// Default port for the JSON-RPC server exposed by the sidecar.
 pub const DEFAULT_RPC_PORT: u16 = 8000;
@@ -26,10 +88,27 @@ pub const DEFAULT_RPC_PORT: u16 = 8000;
 /// Default port for the Constraints proxy server.
 pub const DEFAULT_CONSTRAINTS_PROXY_PORT: u16 = 18551;
 
+/// Default number of slots suggested to retarget the requests rejected for their target slot.
+pub const DEFAULT_RETARGET_SUGGESTIONS: usize = 3;
+
+/// Default number of slots ahead of a proposal slot at which its availability is attested.
+pub const DEFAULT_AVAILABILITY_LEAD_SLOTS: u64 = 2;
+
+/// Default number of decryption keys a signer can withhold before its encrypted requests are
+/// rejected.
+pub const DEFAULT_MAX_WITHHELD_DECRYPTION_KEYS: u64 = 3;
+
+/// Default max number of sequential squarings of the time-lock puzzles of encrypted requests.
+pub const DEFAULT_MAX_TIMELOCK_SQUARINGS: u64 = 1 << 20;
+
 /// Command-line options for the Bolt sidecar
-#[derive(Debug, Parser, Deserialize)]
+#[derive(Debug, Parser, Serialize, Deserialize)]
 #[clap(trailing_var_arg = true)]
 pub struct Opts {
+    /// The subcommand to execute. Runs the sidecar if not provided.
+    #[clap(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
     /// Port to listen on for incoming JSON-RPC requests
     #[clap(long, env = "BOLT_SIDECAR_PORT", default_value_t = DEFAULT_RPC_PORT)]
     pub port: u16,
@@ -50,6 +129,17 @@ pub struct Opts {
         default_value = "http://localhost:3030"
     )]
     pub constraints_api_url: Url,
+    /// URLs of additional servers supporting the Constraints API, comma-separated. The calls
+    /// are then fanned out to all of them concurrently, picking the best bid among their
+    /// headers and skipping the ones that keep failing.
+    #[clap(long, env = "BOLT_SIDECAR_ADDITIONAL_CONSTRAINTS_API_URLS", value_delimiter = ',')]
+    #[serde(default)]
+    pub additional_constraints_api_urls: Vec<Url>,
+    /// URL of the local builder of a self-building proposer, supporting the Constraints API.
+    /// It receives the committed constraints directly, and its bids compete with the relay
+    /// bids on their value adjusted for the constrained gas, like in bolt-boost.
+    #[clap(long, env = "BOLT_SIDECAR_SELF_BUILDER_URL")]
+    pub self_builder_url: Option<Url>,
     /// The port from which the Bolt sidecar will receive Builder-API requests from the
     /// Beacon client
     #[clap(
@@ -58,6 +148,10 @@ pub struct Opts {
         default_value_t = DEFAULT_CONSTRAINTS_PROXY_PORT
     )]
     pub constraints_proxy_port: u16,
+    /// Port on which to expose the admin API, bound to localhost only.
+    /// If not provided, the admin API is disabled.
+    #[clap(long, env = "BOLT_SIDECAR_ADMIN_PORT")]
+    pub admin_port: Option<u16>,
     /// Validator indexes of connected validators that the sidecar
     /// should accept commitments on behalf of. Accepted values:
     /// - a comma-separated list of indexes (e.g. "1,2,3,4")
@@ -93,6 +187,185 @@ pub struct Opts {
     /// Telemetry options
     #[clap(flatten)]
     pub telemetry: TelemetryOpts,
+    /// Latency budgets for the stages of the proposal-critical path
+    #[clap(flatten)]
+    #[serde(default)]
+    pub latency_budgets: LatencyBudgetOpts,
+    /// Local building of the fallback block
+    #[clap(flatten)]
+    #[serde(default)]
+    pub fallback: FallbackOpts,
+    /// Active/standby failover with another sidecar instance
+    #[clap(flatten)]
+    #[serde(default)]
+    pub failover: FailoverOpts,
+    /// Bounds of the adaptive timeouts of the calls to the relays
+    #[clap(flatten)]
+    #[serde(default)]
+    pub relay_timeouts: RelayTimeoutOpts,
+    /// Blockspace reservations that clients can subscribe to
+    #[clap(flatten)]
+    #[serde(default)]
+    pub reservations: ReservationOpts,
+    /// Payment vouchers that inclusion requests must carry
+    #[clap(flatten)]
+    #[serde(default)]
+    pub vouchers: VoucherOpts,
+    /// Pricing of inclusion commitments by the committed gas of their slot
+    #[clap(flatten)]
+    #[serde(default)]
+    pub pricing: PricingOpts,
+    /// Shedding of low-value work under load ahead of a proposal
+    #[clap(flatten)]
+    #[serde(default)]
+    pub load_shedding: LoadSheddingOpts,
+    /// Rate limits and quotas of the senders of commitment requests
+    #[clap(flatten)]
+    #[serde(default)]
+    pub rate_limits: RateLimitOpts,
+    /// Whitelist of the signers allowed to request commitments
+    #[clap(flatten)]
+    #[serde(default)]
+    pub whitelist: WhitelistOpts,
+    /// Targeting of the delegation of each upcoming proposal slot to a single delegatee
+    #[clap(flatten)]
+    #[serde(default)]
+    pub delegation_targeting: DelegationTargetingOpts,
+    /// Operator policies on the transactions committed to
+    #[clap(flatten)]
+    #[serde(default)]
+    pub tx_policy: TxPolicyOpts,
+    /// Compliance screening of the addresses of commitment requests
+    #[clap(flatten)]
+    #[serde(default)]
+    pub screening: ScreeningOpts,
+    /// Checks of the fee recipients of the validator registrations and relay bids
+    #[clap(flatten)]
+    #[serde(default)]
+    pub fee_recipients: FeeRecipientOpts,
+    /// Path to a JSON file with the settings to reload on SIGHUP, among: max_commitments_per_slot,
+    /// max_committed_gas_per_slot, min_priority_fee, whitelist, constraints_api_url, log_filter
+    #[clap(long, env = "BOLT_SIDECAR_RELOAD_CONFIG_PATH")]
+    pub reload_config_path: Option<PathBuf>,
+    /// Interval in milliseconds at which to scan the mempool of the execution client for
+    /// transactions competing with committed ones. Requires the `txpool` namespace.
+    /// If not provided, the mempool is not scanned.
+    #[clap(long, env = "BOLT_SIDECAR_MEMPOOL_SCAN_INTERVAL_MS")]
+    pub mempool_scan_interval_ms: Option<NonZero<u64>>,
+    /// Time in milliseconds into the slot before a proposal slot at which the auction of its
+    /// top-of-block bundle closes, committing to the highest-paying top-of-block request.
+    /// If not provided, top-of-block requests are rejected.
+    #[clap(long, env = "BOLT_SIDECAR_TOB_AUCTION_CUTOFF_MS")]
+    pub tob_auction_cutoff_ms: Option<NonZero<u64>>,
+    /// Position in the block of the first transaction of the top-of-block bundle, leaving the
+    /// positions before it to the builder. If not provided, the bundle starts at the top of the
+    /// block
+    #[clap(long, env = "BOLT_SIDECAR_TOB_BUNDLE_INDEX")]
+    pub tob_bundle_index: Option<u64>,
+    /// Accept inclusion requests of encrypted transactions with client-revealed encryption:
+    /// they are committed to by the hash of their ciphertext, and decrypted at the commitment
+    /// deadline of their slot with a key the client reveals before it, so that observers of
+    /// the commitments API can't frontrun them
+    #[clap(long, env = "BOLT_SIDECAR_CLIENT_REVEALED_ENCRYPTION", default_value_t = false)]
+    #[serde(default)]
+    pub client_revealed_encryption: bool,
+    /// Number of decryption keys a signer can withhold past the commitment deadline, leaving
+    /// the gas held for its encrypted requests unused, before its encrypted requests are
+    /// rejected. Set to 0 to never reject them
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_WITHHELD_DECRYPTION_KEYS",
+        default_value_t = DEFAULT_MAX_WITHHELD_DECRYPTION_KEYS
+    )]
+    #[serde(default = "default_max_withheld_decryption_keys")]
+    pub max_withheld_decryption_keys: u64,
+    /// Accept inclusion requests of encrypted transactions whose decryption key is locked in a
+    /// time-lock puzzle, which the sidecar solves by the commitment deadline if the client
+    /// doesn't reveal the key, so that clients can't withhold it
+    #[clap(long, env = "BOLT_SIDECAR_TIMELOCK_ENCRYPTION", default_value_t = false)]
+    #[serde(default)]
+    pub timelock_encryption: bool,
+    /// Max number of sequential squarings of the time-lock puzzles of encrypted requests, which
+    /// the sidecar must be able to compute before the commitment deadline
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_TIMELOCK_SQUARINGS",
+        default_value_t = DEFAULT_MAX_TIMELOCK_SQUARINGS
+    )]
+    #[serde(default = "default_max_timelock_squarings")]
+    pub max_timelock_squarings: u64,
+    /// Sign the submissions of constraints, delegations and revocations to the Constraints
+    /// API with the commitment key, for servers that only accept authorized sidecars
+    #[clap(long, env = "BOLT_SIDECAR_SIGN_CONSTRAINTS_REQUESTS", default_value_t = false)]
+    #[serde(default)]
+    pub sign_constraints_requests: bool,
+    /// Number of upcoming proposal slots to suggest in the rejections of requests for
+    /// slot-related reasons (full slot, passed deadline, no proposer), so that clients can
+    /// retarget them. Set to 0 to disable the suggestions.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RETARGET_SUGGESTIONS",
+        default_value_t = DEFAULT_RETARGET_SUGGESTIONS
+    )]
+    #[serde(default = "default_retarget_suggestions")]
+    pub retarget_suggestions: usize,
+    /// Number of slots ahead of each of its proposal slots at which the sidecar publishes a
+    /// signed attestation that it accepts commitments for the slot, with its limits and min
+    /// priority fee. Set to 0 to disable the attestations.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_AVAILABILITY_LEAD_SLOTS",
+        default_value_t = DEFAULT_AVAILABILITY_LEAD_SLOTS
+    )]
+    #[serde(default = "default_availability_lead_slots")]
+    pub availability_lead_slots: u64,
+    /// URL to POST each availability attestation to as it is published, in addition to
+    /// serving it from the commitments API.
+    #[clap(long, env = "BOLT_SIDECAR_AVAILABILITY_WEBHOOK_URL")]
+    pub availability_webhook_url: Option<Url>,
+    /// URL to POST each change of status of the commitments to, e.g. when they are
+    /// constrained, expire at the end of their slot, or are found included or missed.
+    #[clap(long, env = "BOLT_SIDECAR_COMMITMENT_WEBHOOK_URL")]
+    pub commitment_webhook_url: Option<Url>,
+    /// Path to the journal of the commitments made, reconciled against the chain on startup.
+    /// If not provided, commitments are not journaled and are lost on restart.
+    #[clap(long, env = "BOLT_SIDECAR_JOURNAL_PATH")]
+    pub journal_path: Option<PathBuf>,
+    /// Path to the file where the validator registrations forwarded to the relay are saved, to
+    /// replay them with their delegations on startup. If not provided, the registrations are
+    /// only sent when the beacon node sends them, and the delegations along with them.
+    #[clap(long, env = "BOLT_SIDECAR_REGISTRATIONS_PATH")]
+    pub registrations_path: Option<PathBuf>,
+    /// Path to the file where the delegations and revocations are saved, to resolve the same
+    /// delegatees and send the same ones to the relays after a restart. If not provided, the
+    /// delegations signed by the sidecar and the revocations are lost on restart.
+    #[clap(long, env = "BOLT_SIDECAR_DELEGATIONS_STATE_PATH")]
+    pub delegations_state_path: Option<PathBuf>,
+    /// Path to the event log of the constraints submitted to each relay, to query which
+    /// constraints were sent for a past slot and which relays acknowledged them on the admin
+    /// API. If not provided, only the submissions of the recent slots are kept, in memory.
+    #[clap(long, env = "BOLT_SIDECAR_SUBMISSION_LOG_PATH")]
+    pub submission_log_path: Option<PathBuf>,
+    /// Path to the history of the commitment keys of the sidecar, recording the slot at which
+    /// each key was rotated, so that the verifiers of past commitments can resolve the key that
+    /// signed them. If not provided, only the current key is served.
+    #[clap(long, env = "BOLT_SIDECAR_COMMITMENT_KEYS_PATH")]
+    pub commitment_keys_path: Option<PathBuf>,
+    /// Path to a JSON file with the options of additional networks to serve from this process,
+    /// keyed by the network identifier that selects them in the JSON-RPC path
+    /// (e.g. `/devnet/rpc/v1`). The network configured by the other flags is the primary one.
+    #[clap(long, env = "BOLT_SIDECAR_NETWORKS_CONFIG_PATH")]
+    pub networks_config_path: Option<PathBuf>,
+    /// The multi-network router that the commitments API is mounted on, set when serving
+    /// several networks from this process.
+    #[clap(skip)]
+    #[serde(skip)]
+    pub network_router: Option<NetworkRouter>,
+    /// Print the resolved configuration as JSON, with secrets redacted, along with
+    /// any configuration problems, then exit without starting the sidecar
+    #[clap(long, env = "BOLT_SIDECAR_PRINT_CONFIG", default_value_t = false)]
+    #[serde(skip)]
+    pub print_config: bool,
 
     /// Additional unrecognized arguments. Useful for CI and testing
     /// to avoid issues on potential extra flags provided (e.g. "--exact" from cargo nextest).
@@ -102,6 +375,22 @@ pub struct Opts {
     pub extra_args: Vec<String>,
 }
 
+const fn default_retarget_suggestions() -> usize {
+    DEFAULT_RETARGET_SUGGESTIONS
+}
+
+const fn default_availability_lead_slots() -> u64 {
+    DEFAULT_AVAILABILITY_LEAD_SLOTS
+}
+
+const fn default_max_withheld_decryption_keys() -> u64 {
+    DEFAULT_MAX_WITHHELD_DECRYPTION_KEYS
+}
+
+const fn default_max_timelock_squarings() -> u64 {
+    DEFAULT_MAX_TIMELOCK_SQUARINGS
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,76 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use eyre::{bail, eyre};
+
+use super::Opts;
+
+/// Read the options of the additional networks to serve next to the `primary` one, from a
+/// JSON file keyed by the network identifier that selects them in the JSON-RPC path.
+///
+/// Each entry holds the complete options of a network, in the format printed by
+/// `--print-config` with the secrets filled in. Networks are isolated: they get their own
+/// chain, keys, clients, relays and slot clock, and only share the JSON-RPC port, the
+/// metrics and the admin API of the primary network.
+pub fn read_networks_file(path: &Path, primary: &Opts) -> eyre::Result<BTreeMap<String, Opts>> {
+    let contents = fs::read_to_string(path)?;
+    let networks: BTreeMap<String, Opts> = serde_json::from_str(&contents)?;
+
+    let mut proxy_ports = BTreeMap::from([(primary.constraints_proxy_port, primary.chain.name())]);
+    let mut journals = BTreeMap::from_iter(
+        primary.journal_path.as_deref().map(|path| (path, primary.chain.name())),
+    );
+    for (network, opts) in &networks {
+        if !is_valid_network_id(network) {
+            bail!("Invalid network identifier '{network}', use lowercase letters, digits and '-'");
+        }
+
+        if network == primary.chain.name() {
+            bail!("Network '{network}' is already served as the primary network");
+        }
+
+        if opts.networks_config_path.is_some() {
+            bail!("Network '{network}' sets networks_config_path, which only the primary can");
+        }
+
+        opts.validate().map_err(|errors| eyre!("Network '{network}': {errors}"))?;
+
+        // Each beacon node connects to the builder proxy of its own network
+        if let Some(other) = proxy_ports.insert(opts.constraints_proxy_port, network.as_str()) {
+            bail!(
+                "Networks '{other}' and '{network}' both use constraints proxy port {}",
+                opts.constraints_proxy_port
+            );
+        }
+
+        // A journal records the commitments of a single network
+        if let Some(path) = opts.journal_path.as_deref() {
+            if let Some(other) = journals.insert(path, network.as_str()) {
+                bail!("Networks '{other}' and '{network}' both use journal {}", path.display());
+            }
+        }
+    }
+
+    Ok(networks)
+}
+
+/// Returns true if the network identifier can be used as a JSON-RPC path segment.
+fn is_valid_network_id(network: &str) -> bool {
+    !network.is_empty() &&
+        network.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_ids() {
+        for network in ["holesky", "devnet-2", "kurtosis1"] {
+            assert!(is_valid_network_id(network), "{network}");
+        }
+
+        for network in ["", "Holesky", "dev/net", "rpc v1", "devnet_2"] {
+            assert!(!is_valid_network_id(network), "{network}");
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+// Default pricing values
+pub const DEFAULT_PRICING_MAX_PREMIUM: u128 = 0;
+pub const DEFAULT_PRICING_BASEFEE_MARGIN_BPS: u64 = 0;
+
+/// The shape of the congestion premium of inclusion commitments over the share of the
+/// committable gas of a slot that is already committed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginCurve {
+    /// No premium, whatever the committed gas
+    #[default]
+    Flat,
+    /// A premium proportional to the committed share of the slot
+    Linear,
+    /// A premium growing with the square of the committed share of the slot
+    Quadratic,
+    /// A premium growing exponentially with the committed share of the slot, negligible
+    /// until the slot is about half full
+    Exponential,
+}
+
+/// Options for the pricing of inclusion commitments, on top of the min priority fee.
+///
+/// The min priority fee of a request is the configured min priority fee, plus a margin over
+/// the max basefee of its target slot, plus a congestion premium that grows with the gas
+/// already committed in the slot along the margin curve.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct PricingOpts {
+    /// Curve of the congestion premium over the share of the committable gas of the target
+    /// slot already committed
+    #[clap(long, env = "BOLT_SIDECAR_PRICING_CURVE", value_enum, default_value_t)]
+    #[serde(default)]
+    pub pricing_curve: MarginCurve,
+    /// Congestion premium in wei per gas added to the min priority fee when the target slot
+    /// is fully committed, scaled down along the pricing curve for less committed slots
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_PRICING_MAX_PREMIUM",
+        default_value_t = PricingOpts::default().pricing_max_premium
+    )]
+    #[serde(default)]
+    pub pricing_max_premium: u128,
+    /// Margin over the max basefee of the target slot added to the min priority fee, in basis
+    /// points of the basefee
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_PRICING_BASEFEE_MARGIN_BPS",
+        default_value_t = PricingOpts::default().pricing_basefee_margin_bps
+    )]
+    #[serde(default)]
+    pub pricing_basefee_margin_bps: u64,
+}
+
+impl PricingOpts {
+    /// Returns true if requests pay more than the configured min priority fee in some slots.
+    pub fn enabled(&self) -> bool {
+        (self.pricing_curve != MarginCurve::Flat && self.pricing_max_premium > 0) ||
+            self.pricing_basefee_margin_bps > 0
+    }
+}
+
+impl Default for PricingOpts {
+    fn default() -> Self {
+        Self {
+            pricing_curve: MarginCurve::Flat,
+            pricing_max_premium: DEFAULT_PRICING_MAX_PREMIUM,
+            pricing_basefee_margin_bps: DEFAULT_PRICING_BASEFEE_MARGIN_BPS,
+        }
+    }
+}
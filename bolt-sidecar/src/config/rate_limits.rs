@@ -0,0 +1,46 @@
+use std::num::NonZero;
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+// Default rate limit values
+pub const DEFAULT_RATE_LIMIT_BURST: u32 = 20;
+
+/// Options for the rate limits and quotas of the senders of requests to the commitments API.
+///
+/// Rate limits are token buckets refilled at the given rate per second, holding up to the burst
+/// of requests. Each limit is disabled if not provided.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimitOpts {
+    /// Max number of requests per second to the commitments API from a single IP address.
+    /// If not provided, requests are not limited per IP address
+    #[clap(long, env = "BOLT_SIDECAR_RATE_LIMIT_PER_IP")]
+    pub rate_limit_per_ip: Option<NonZero<u32>>,
+    /// Max number of requests per second to the commitments API signed by a single signer.
+    /// If not provided, requests are not limited per signer
+    #[clap(long, env = "BOLT_SIDECAR_RATE_LIMIT_PER_SIGNER")]
+    pub rate_limit_per_signer: Option<NonZero<u32>>,
+    /// Max number of requests accepted at once from a single IP address or signer, on top of
+    /// their rate limit
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RATE_LIMIT_BURST",
+        default_value_t = RateLimitOpts::default().rate_limit_burst
+    )]
+    pub rate_limit_burst: NonZero<u32>,
+    /// Max number of commitments a single signer can hold in each upcoming slot.
+    /// If not provided, signers can fill slots up to the limits of the sidecar
+    #[clap(long, env = "BOLT_SIDECAR_MAX_PENDING_COMMITMENTS_PER_SIGNER")]
+    pub max_pending_commitments_per_signer: Option<NonZero<usize>>,
+}
+
+impl Default for RateLimitOpts {
+    fn default() -> Self {
+        Self {
+            rate_limit_per_ip: None,
+            rate_limit_per_signer: None,
+            rate_limit_burst: NonZero::new(DEFAULT_RATE_LIMIT_BURST).expect("Valid non-zero"),
+            max_pending_commitments_per_signer: None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+// Default bounds of the relay call timeouts, in milliseconds
+pub const DEFAULT_RELAY_TIMEOUT_FLOOR_MS: u64 = 100;
+pub const DEFAULT_RELAY_TIMEOUT_CEILING_MS: u64 = 2_000;
+
+/// Bounds of the timeouts of the calls to the relays.
+///
+/// Within them, the timeout of each relay endpoint adapts to the latency observed so far,
+/// so that consistently slow relays don't time out needlessly while fast relays get tight
+/// budgets.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct RelayTimeoutOpts {
+    /// Lowest timeout in milliseconds of the calls to a relay, however fast it is
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RELAY_TIMEOUT_FLOOR_MS",
+        default_value_t = RelayTimeoutOpts::default().relay_timeout_floor_ms
+    )]
+    pub relay_timeout_floor_ms: u64,
+    /// Highest timeout in milliseconds of the calls to a relay, also used until its latency
+    /// is known
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RELAY_TIMEOUT_CEILING_MS",
+        default_value_t = RelayTimeoutOpts::default().relay_timeout_ceiling_ms
+    )]
+    pub relay_timeout_ceiling_ms: u64,
+}
+
+impl RelayTimeoutOpts {
+    /// Returns the lowest timeout of the calls to a relay.
+    pub fn floor(&self) -> Duration {
+        Duration::from_millis(self.relay_timeout_floor_ms.min(self.relay_timeout_ceiling_ms))
+    }
+
+    /// Returns the highest timeout of the calls to a relay.
+    pub fn ceiling(&self) -> Duration {
+        Duration::from_millis(self.relay_timeout_ceiling_ms)
+    }
+}
+
+impl Default for RelayTimeoutOpts {
+    fn default() -> Self {
+        Self {
+            relay_timeout_floor_ms: DEFAULT_RELAY_TIMEOUT_FLOOR_MS,
+            relay_timeout_ceiling_ms: DEFAULT_RELAY_TIMEOUT_CEILING_MS,
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    fmt,
+    num::NonZero,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use alloy::primitives::Address;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info, warn};
+
+use crate::telemetry::{parse_filter, LogFilterError, LogFilterHandle};
+
+use super::{ConfigError, ConfigErrors};
+
+/// The tracing target of the audit log entries emitted for each configuration change.
+pub const AUDIT_LOG_TARGET: &str = "bolt_sidecar::audit";
+
+/// The interval at which the whitelist file is checked for changes.
+const WHITELIST_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The subset of the sidecar configuration that can be changed at runtime, without
+/// restarting the sidecar and losing its in-memory slot state.
+///
+/// Every field is optional: only the provided ones are changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReloadableConfig {
+    /// Max number of commitments to accept per block
+    pub max_commitments_per_slot: Option<NonZero<usize>>,
+    /// Max committed gas per slot
+    pub max_committed_gas_per_slot: Option<NonZero<u64>>,
+    /// Min priority fee to accept for a commitment
+    pub min_priority_fee: Option<NonZero<u128>>,
+    /// Signers allowed to request commitments. An empty list accepts any signer.
+    pub whitelist: Option<Vec<Address>>,
+    /// URL of the relay (or Constraints API server) to send constraints to
+    pub constraints_api_url: Option<Url>,
+    /// Tracing filter directives, in the same format as `RUST_LOG`
+    pub log_filter: Option<String>,
+}
+
+impl ReloadableConfig {
+    /// Read the configuration from a JSON file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ReloadError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Check the configuration before it is applied, returning all the problems found.
+    pub fn validate(&self) -> Result<(), ReloadError> {
+        let mut errors = Vec::new();
+
+        if let Some(url) = &self.constraints_api_url {
+            if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+                errors.push(ConfigError::InvalidUrl {
+                    flag: "constraints-api-url",
+                    url: url.clone(),
+                });
+            }
+        }
+
+        if let Some(Err(err)) = self.log_filter.as_deref().map(parse_filter) {
+            errors.push(ConfigError::InvalidLogFilter(err.to_string()));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ReloadError::Invalid(ConfigErrors(errors)))
+        }
+    }
+}
+
+/// Where a configuration reload was requested from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadSource {
+    /// A SIGHUP signal, reloading the configuration file.
+    Sighup,
+    /// The admin API.
+    AdminApi,
+    /// A change of the whitelist file.
+    WhitelistFile,
+}
+
+impl fmt::Display for ReloadSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadSource::Sighup => write!(f, "sighup"),
+            ReloadSource::AdminApi => write!(f, "admin_api"),
+            ReloadSource::WhitelistFile => write!(f, "whitelist_file"),
+        }
+    }
+}
+
+/// A single setting changed by a configuration reload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    /// The name of the setting.
+    pub field: String,
+    /// The value before the reload.
+    pub old: String,
+    /// The value after the reload.
+    pub new: String,
+}
+
+impl ConfigChange {
+    /// Returns a change of the given setting, or `None` if the value did not change.
+    pub fn new(field: &str, old: impl fmt::Display, new: impl fmt::Display) -> Option<Self> {
+        let (old, new) = (old.to_string(), new.to_string());
+        (old != new).then(|| Self { field: field.to_string(), old, new })
+    }
+}
+
+/// Errors that can occur while reloading the configuration.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ReloadError {
+    #[error("Failed to read the configuration file: {0}")]
+    Read(#[from] std::io::Error),
+    #[error("Failed to parse the configuration file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("{0}")]
+    Invalid(ConfigErrors),
+    #[error(transparent)]
+    LogFilter(#[from] LogFilterError),
+    #[error("The sidecar driver is not running")]
+    DriverUnavailable,
+}
+
+/// A request to apply a [ReloadableConfig], handled by the sidecar driver.
+#[derive(Debug)]
+pub(crate) struct ReloadRequest {
+    /// The configuration to apply, already validated.
+    pub config: ReloadableConfig,
+    /// The channel to send the applied changes back on, or the problems found if the
+    /// configuration is invalid given the settings it doesn't change.
+    pub response: oneshot::Sender<Result<Vec<ConfigChange>, ConfigErrors>>,
+}
+
+/// A handle to reload the configuration of a running sidecar.
+///
+/// Configurations are validated as a whole before anything is applied, and
+/// every applied change is recorded in the audit log.
+#[derive(Debug, Clone)]
+pub struct ConfigReloader {
+    requests: mpsc::Sender<ReloadRequest>,
+    log_filter: Option<LogFilterHandle>,
+}
+
+impl ConfigReloader {
+    /// Creates a reloader sending requests to the sidecar driver.
+    pub(crate) fn new(requests: mpsc::Sender<ReloadRequest>) -> Self {
+        Self { requests, log_filter: None }
+    }
+
+    /// Set the handle to the tracing filter, so that the log filter can be reloaded.
+    pub fn with_log_filter(mut self, log_filter: LogFilterHandle) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    /// Validate and apply the given configuration, returning the settings that changed.
+    pub async fn reload(
+        &self,
+        config: ReloadableConfig,
+        source: ReloadSource,
+    ) -> Result<Vec<ConfigChange>, ReloadError> {
+        if let Err(err) = config.validate() {
+            warn!(%source, %err, "Rejected configuration reload");
+            return Err(err);
+        }
+
+        let log_filter = config.log_filter.clone();
+
+        let (response, changes) = oneshot::channel();
+        let request = ReloadRequest { config, response };
+        self.requests.send(request).await.map_err(|_| ReloadError::DriverUnavailable)?;
+        let mut changes = match changes.await.map_err(|_| ReloadError::DriverUnavailable)? {
+            Ok(changes) => changes,
+            Err(errors) => {
+                let err = ReloadError::Invalid(errors);
+                warn!(%source, %err, "Rejected configuration reload");
+                return Err(err);
+            }
+        };
+
+        if let (Some(directives), Some(handle)) = (log_filter, &self.log_filter) {
+            let old = handle.current()?;
+            handle.set(&directives)?;
+            changes.extend(ConfigChange::new("log_filter", old, handle.current()?));
+        }
+
+        for change in &changes {
+            info!(
+                target: AUDIT_LOG_TARGET,
+                %source,
+                field = %change.field,
+                old = %change.old,
+                new = %change.new,
+                "Configuration changed"
+            );
+        }
+
+        info!(%source, changes = changes.len(), "Configuration reloaded");
+        Ok(changes)
+    }
+}
+
+/// Reload the configuration file at the given path every time the process receives SIGHUP.
+#[cfg(unix)]
+pub fn spawn_sighup_reloader(
+    reloader: ConfigReloader,
+    path: std::path::PathBuf,
+) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+
+    Ok(tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!(path = %path.display(), "Received SIGHUP, reloading configuration");
+
+            let result = match ReloadableConfig::from_file(&path) {
+                Ok(config) => reloader.reload(config, ReloadSource::Sighup).await,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                error!(%err, "Failed to reload configuration");
+            }
+        }
+    }))
+}
+
+/// Read the JSON array of the addresses of the whitelisted signers from a file.
+pub fn read_whitelist_file(path: impl AsRef<Path>) -> Result<Vec<Address>, ReloadError> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Reload the whitelist from the file at the given path every time the file is modified.
+pub fn spawn_whitelist_watcher(
+    reloader: ConfigReloader,
+    path: PathBuf,
+) -> tokio::task::JoinHandle<()> {
+    let modified_at = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    tokio::spawn(async move {
+        let mut last_modified: Option<SystemTime> = modified_at(&path);
+        let mut interval = tokio::time::interval(WHITELIST_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let modified = modified_at(&path);
+            if modified.is_none() || modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            info!(path = %path.display(), "Whitelist file changed, reloading the whitelist");
+            let result = match read_whitelist_file(&path) {
+                Ok(whitelist) => {
+                    let config =
+                        ReloadableConfig { whitelist: Some(whitelist), ..Default::default() };
+                    reloader.reload(config, ReloadSource::WhitelistFile).await
+                }
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                error!(%err, "Failed to reload the whitelist");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reloadable_config() {
+        let config: ReloadableConfig = serde_json::from_str(
+            r#"{
+                "max_commitments_per_slot": 64,
+                "min_priority_fee": 2000000000,
+                "whitelist": ["0x0000000000000000000000000000000000000001"],
+                "constraints_api_url": "http://relay.local:3030",
+                "log_filter": "info,bolt_sidecar::driver=debug"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.max_commitments_per_slot, NonZero::new(64));
+        assert_eq!(config.max_committed_gas_per_slot, None);
+        assert_eq!(config.whitelist, Some(vec![Address::with_last_byte(1)]));
+        assert!(config.validate().is_ok());
+
+        let parse = serde_json::from_str::<ReloadableConfig>;
+        assert!(parse(r#"{"max_commitments_per_slot": 0}"#).is_err());
+        assert!(parse(r#"{"unknown_field": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_validate_reloadable_config() {
+        let config = ReloadableConfig {
+            constraints_api_url: Some("unix:/tmp/relay.sock".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(matches!(config.validate(), Err(ReloadError::Invalid(_))));
+
+        let config = ReloadableConfig {
+            log_filter: Some("bolt_sidecar=loud".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(config.validate(), Err(ReloadError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reload_records_changes() -> eyre::Result<()> {
+        let (tx, mut rx) = mpsc::channel::<ReloadRequest>(1);
+        let reloader = ConfigReloader::new(tx);
+
+        // A stand-in for the driver, changing the quota from 128
+        tokio::spawn(async move {
+            while let Some(request) = rx.recv().await {
+                let new = request.config.max_commitments_per_slot.unwrap();
+                let changes = ConfigChange::new("max_commitments_per_slot", 128, new);
+                let _ = request.response.send(Ok(changes.into_iter().collect()));
+            }
+        });
+
+        let config =
+            ReloadableConfig { max_commitments_per_slot: NonZero::new(64), ..Default::default() };
+        let changes = reloader.reload(config, ReloadSource::AdminApi).await?;
+        assert_eq!(
+            changes,
+            vec![ConfigChange {
+                field: "max_commitments_per_slot".to_string(),
+                old: "128".to_string(),
+                new: "64".to_string()
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reload_rejected_by_driver() {
+        let (tx, mut rx) = mpsc::channel::<ReloadRequest>(1);
+        let reloader = ConfigReloader::new(tx);
+
+        // A stand-in for the driver, with reservations exceeding the new committed gas
+        tokio::spawn(async move {
+            while let Some(request) = rx.recv().await {
+                let committed = request.config.max_committed_gas_per_slot.unwrap().get();
+                let error =
+                    ConfigError::ReservedGasExceedsCommitted { reserved: 2_000_000, committed };
+                let _ = request.response.send(Err(ConfigErrors(vec![error])));
+            }
+        });
+
+        let config = ReloadableConfig {
+            max_committed_gas_per_slot: NonZero::new(1_000_000),
+            ..Default::default()
+        };
+        let err = reloader.reload(config, ReloadSource::AdminApi).await.unwrap_err();
+        assert!(matches!(err, ReloadError::Invalid(ConfigErrors(errors)) if errors.len() == 1));
+    }
+
+    #[test]
+    fn test_read_whitelist_file() {
+        let path = std::env::temp_dir().join(format!("bolt-whitelist-{}.json", std::process::id()));
+        std::fs::write(&path, r#"["0x0000000000000000000000000000000000000001"]"#).unwrap();
+        assert_eq!(read_whitelist_file(&path).unwrap(), vec![Address::with_last_byte(1)]);
+
+        std::fs::write(&path, "0x0000000000000000000000000000000000000001").unwrap();
+        assert!(matches!(read_whitelist_file(&path), Err(ReloadError::Parse(_))));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
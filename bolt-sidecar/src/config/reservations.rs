@@ -0,0 +1,51 @@
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+// Default reservation values
+pub const DEFAULT_MAX_RESERVED_GAS: u64 = 0;
+pub const DEFAULT_RESERVATION_PRICE_PER_GAS: u128 = 1_000_000_000; // 1 Gwei
+pub const DEFAULT_MAX_RESERVATION_SLOTS: u64 = 7200; // 1 day
+
+/// Options for the blockspace reservations that clients can subscribe to.
+#[derive(Debug, Parser, Clone, Copy, Serialize, Deserialize)]
+pub struct ReservationOpts {
+    /// Max gas that can be reserved by subscriptions in every proposal slot, out of the
+    /// max committed gas. Set to 0 to disable reservations
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_RESERVED_GAS",
+        default_value_t = ReservationOpts::default().max_reserved_gas_per_slot
+    )]
+    pub max_reserved_gas_per_slot: u64,
+    /// Price in wei per unit of reserved gas, charged for every proposal slot of a subscription
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_RESERVATION_PRICE_PER_GAS",
+        default_value_t = ReservationOpts::default().reservation_price_per_gas
+    )]
+    pub reservation_price_per_gas: u128,
+    /// Max number of slots that a single subscription can span
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_RESERVATION_SLOTS",
+        default_value_t = ReservationOpts::default().max_reservation_slots
+    )]
+    pub max_reservation_slots: u64,
+}
+
+impl ReservationOpts {
+    /// Returns true if clients can reserve blockspace.
+    pub fn enabled(&self) -> bool {
+        self.max_reserved_gas_per_slot > 0
+    }
+}
+
+impl Default for ReservationOpts {
+    fn default() -> Self {
+        Self {
+            max_reserved_gas_per_slot: DEFAULT_MAX_RESERVED_GAS,
+            reservation_price_per_gas: DEFAULT_RESERVATION_PRICE_PER_GAS,
+            max_reservation_slots: DEFAULT_MAX_RESERVATION_SLOTS,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{fmt, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+// Default screening values
+pub const DEFAULT_SCREENING_CACHE_TTL_SECS: u64 = 3600; // 1 hour
+pub const DEFAULT_SCREENING_TIMEOUT_MS: u64 = 500;
+
+/// What to do with a request when an address can't be screened, e.g. because the
+/// screening service is unavailable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[clap(rename_all = "kebab_case")]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreeningFailMode {
+    /// Accept the request, as if the address was not flagged.
+    Open,
+    /// Reject the request.
+    #[default]
+    Closed,
+}
+
+impl fmt::Display for ScreeningFailMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+        })
+    }
+}
+
+/// Options for screening the addresses of commitment requests against a compliance list
+/// or service before committing to them, if enabled.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+pub struct ScreeningOpts {
+    /// Path to a file of flagged addresses, one per line, that commitment requests must not
+    /// involve as signer, sender or recipient
+    #[clap(long, env = "BOLT_SIDECAR_SCREENING_LIST_PATH", conflicts_with = "screening_url")]
+    pub screening_list_path: Option<PathBuf>,
+    /// URL of a screening service queried with `GET <url>/<address>` for the addresses of
+    /// commitment requests, answering `{ "flagged": bool, "label": string }`
+    #[clap(long, env = "BOLT_SIDECAR_SCREENING_URL")]
+    pub screening_url: Option<Url>,
+    /// Whether to accept (`open`) or reject (`closed`) the requests whose addresses can't be
+    /// screened
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_SCREENING_FAIL_MODE",
+        value_enum,
+        default_value_t = ScreeningFailMode::Closed
+    )]
+    pub screening_fail_mode: ScreeningFailMode,
+    /// Time in seconds for which the screening result of an address is cached
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_SCREENING_CACHE_TTL_SECS",
+        default_value_t = DEFAULT_SCREENING_CACHE_TTL_SECS
+    )]
+    pub screening_cache_ttl_secs: u64,
+    /// Timeout in milliseconds of the screening of an address
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_SCREENING_TIMEOUT_MS",
+        default_value_t = DEFAULT_SCREENING_TIMEOUT_MS
+    )]
+    pub screening_timeout_ms: u64,
+}
+
+impl Default for ScreeningOpts {
+    fn default() -> Self {
+        Self {
+            screening_list_path: None,
+            screening_url: None,
+            screening_fail_mode: ScreeningFailMode::default(),
+            screening_cache_ttl_secs: DEFAULT_SCREENING_CACHE_TTL_SECS,
+            screening_timeout_ms: DEFAULT_SCREENING_TIMEOUT_MS,
+        }
+    }
+}
+
+impl ScreeningOpts {
+    /// Returns true if the addresses of commitment requests are screened.
+    pub fn enabled(&self) -> bool {
+        self.screening_list_path.is_some() || self.screening_url.is_some()
+    }
+}
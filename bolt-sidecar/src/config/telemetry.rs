@@ -1,13 +1,18 @@
 use clap::Parser;
-use serde::Deserialize;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Clone, Deserialize)]
+#[derive(Parser, Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryOpts {
     /// The port on which to expose Prometheus metrics
     #[clap(short, long, env = "METRICS_PORT", default_value_t = 3300)]
     metrics_port: u16,
     #[clap(short, long, env = "DISABLE_METRICS", default_value_t = false)]
     disable_metrics: bool,
+    /// The OTLP gRPC endpoint to export traces to, e.g. `http://localhost:4317`.
+    /// Traces are not exported if unset.
+    #[clap(long, env = "BOLT_SIDECAR_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<Url>,
 }
 
 impl TelemetryOpts {
@@ -19,4 +24,9 @@ impl TelemetryOpts {
             Some(self.metrics_port)
         }
     }
+
+    /// Get the OTLP endpoint to export traces to, if any.
+    pub fn otlp_endpoint(&self) -> Option<&Url> {
+        self.otlp_endpoint.as_ref()
+    }
 }
@@ -0,0 +1,83 @@
+use std::{fmt, num::NonZero};
+
+use alloy::primitives::Address;
+use clap::{Parser, ValueEnum};
+use reth_primitives::TxType;
+use serde::{Deserialize, Serialize};
+
+/// The transaction types that can be committed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[clap(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CommitmentTxType {
+    /// Legacy transactions.
+    Legacy,
+    /// EIP-2930 access list transactions.
+    Eip2930,
+    /// EIP-1559 dynamic fee transactions.
+    Eip1559,
+    /// EIP-4844 blob transactions.
+    Eip4844,
+}
+
+impl CommitmentTxType {
+    /// Returns the commitment type of a transaction type, if it can be committed to.
+    pub const fn from_tx_type(tx_type: TxType) -> Option<Self> {
+        match tx_type {
+            TxType::Legacy => Some(Self::Legacy),
+            TxType::Eip2930 => Some(Self::Eip2930),
+            TxType::Eip1559 => Some(Self::Eip1559),
+            TxType::Eip4844 => Some(Self::Eip4844),
+            TxType::Eip7702 => None,
+        }
+    }
+
+    /// Returns the name of the type, as passed on the command line.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Legacy => "legacy",
+            Self::Eip2930 => "eip2930",
+            Self::Eip1559 => "eip1559",
+            Self::Eip4844 => "eip4844",
+        }
+    }
+}
+
+impl fmt::Display for CommitmentTxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options restricting the transactions that the operator commits to, on top of the
+/// validity checks of the sidecar. All transactions are accepted by default.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct TxPolicyOpts {
+    /// Comma-separated transaction types that are not committed to, e.g. `eip4844` to refuse
+    /// blob transactions
+    #[clap(long, env = "BOLT_SIDECAR_DISALLOWED_TX_TYPES", value_delimiter = ',', value_enum)]
+    #[serde(default)]
+    pub disallowed_tx_types: Vec<CommitmentTxType>,
+    /// Refuse to commit to contract creation transactions
+    #[clap(long, env = "BOLT_SIDECAR_DISALLOW_CONTRACT_CREATION", default_value_t = false)]
+    #[serde(default)]
+    pub disallow_contract_creation: bool,
+    /// Max size in bytes of the calldata of a transaction to commit to. If not provided, only the
+    /// transaction size limit applies
+    #[clap(long, env = "BOLT_SIDECAR_MAX_CALLDATA_BYTES")]
+    pub max_calldata_bytes: Option<NonZero<usize>>,
+    /// Comma-separated addresses that transactions to commit to must not be sent to
+    #[clap(long, env = "BOLT_SIDECAR_DENIED_TO_ADDRESSES", value_delimiter = ',')]
+    #[serde(default)]
+    pub denied_to_addresses: Vec<Address>,
+}
+
+impl TxPolicyOpts {
+    /// Returns true if any transaction is restricted by the policy.
+    pub fn enabled(&self) -> bool {
+        !self.disallowed_tx_types.is_empty() ||
+            self.disallow_contract_creation ||
+            self.max_calldata_bytes.is_some() ||
+            !self.denied_to_addresses.is_empty()
+    }
+}
@@ -0,0 +1,457 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use reqwest::Url;
+
+use super::{DelegationPolicy, FailoverRole, LimitsOpts, Opts, ReservationOpts};
+
+/// A single problem found while validating the sidecar [Opts].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("--slot-time must be greater than 0")]
+    ZeroSlotTime,
+    #[error(
+        "--commitment-deadline ({deadline}ms) must be lower than the slot time ({slot_time}ms), \
+         otherwise commitments can never be accepted"
+    )]
+    DeadlineAfterSlotEnd { deadline: u64, slot_time: u64 },
+    #[error(
+        "--{flag} ({budget}ms) exceeds the slot time ({slot_time}ms), \
+         lower it so that the warning can actually fire within the slot"
+    )]
+    BudgetExceedsSlot { flag: &'static str, budget: u64, slot_time: u64 },
+    #[error("--{flag} must be an http(s) URL with a host, got {url}")]
+    InvalidUrl { flag: &'static str, url: Url },
+    #[error(
+        "--{first} and --{second} are both set to port {port}, each listener needs its own port"
+    )]
+    PortConflict { first: &'static str, second: &'static str, port: u16 },
+    #[error("--validator-indexes is empty, the sidecar would not accept any commitment")]
+    NoValidatorIndexes,
+    #[error(
+        "--keystore-path is required when signing constraints with a keystore \
+         (--keystore-password, --keystore-password-path or --keystore-secrets-path)"
+    )]
+    MissingKeystorePath,
+    #[error("--{flag} points to {path}, which does not exist")]
+    MissingPath { flag: &'static str, path: String },
+    #[error("--{flag} points to {path}, which is not a directory")]
+    NotADirectory { flag: &'static str, path: String },
+    #[error("Invalid log filter directives: {0}")]
+    InvalidLogFilter(String),
+    #[error(
+        "--max-reserved-gas ({reserved}) exceeds --max-committed-gas ({committed}), \
+         reservations must fit in the committed gas of a slot"
+    )]
+    ReservedGasExceedsCommitted { reserved: u64, committed: u64 },
+    #[error("--voucher-settlement-interval-secs must be greater than 0 when vouchers are priced")]
+    ZeroVoucherSettlementInterval,
+    #[error(
+        "--tob-auction-cutoff-ms ({cutoff}ms) must be lower than the commitment deadline \
+         ({deadline}ms), otherwise the winning bids can't be committed to"
+    )]
+    TobCutoffAfterDeadline { cutoff: u64, deadline: u64 },
+    #[error("--delegation-policy per-slot requires --delegation-assignments-path")]
+    MissingDelegationAssignments,
+    #[error(
+        "--delegation-policy {0} requires --delegatees or --delegations-path, \
+         there are no delegatees to assign the proposal slots to"
+    )]
+    NoDelegatees(DelegationPolicy),
+    #[error(
+        "--relay-timeout-floor-ms ({floor}ms) exceeds --relay-timeout-ceiling-ms ({ceiling}ms)"
+    )]
+    RelayTimeoutFloorAboveCeiling { floor: u64, ceiling: u64 },
+    #[error("--failover-role primary requires --failover-standby-url")]
+    MissingFailoverStandbyUrl,
+    #[error(
+        "--failover-heartbeat-timeout-ms ({timeout}ms) must be greater than \
+         --failover-heartbeat-interval-ms ({interval}ms), otherwise the primary is always down"
+    )]
+    FailoverTimeoutBelowInterval { timeout: u64, interval: u64 },
+}
+
+/// All the problems found while validating the sidecar [Opts], so that
+/// they can be fixed at once instead of one restart at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration, found {} problem(s):", self.0.len())?;
+        for (i, error) in self.0.iter().enumerate() {
+            writeln!(f, "  {}. {error}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl Opts {
+    /// Cross-check the related settings of the configuration, returning all the
+    /// problems found. Individual values are already validated while parsing.
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut errors = Vec::new();
+
+        self.validate_timings(&mut errors);
+        self.validate_urls(&mut errors);
+        self.validate_ports(&mut errors);
+        self.validate_paths(&mut errors);
+
+        if self.validator_indexes.is_empty() {
+            errors.push(ConfigError::NoValidatorIndexes);
+        }
+
+        validate_limits(&self.limits, &self.reservations, &mut errors);
+
+        let floor = self.relay_timeouts.relay_timeout_floor_ms;
+        let ceiling = self.relay_timeouts.relay_timeout_ceiling_ms;
+        if floor > ceiling {
+            errors.push(ConfigError::RelayTimeoutFloorAboveCeiling { floor, ceiling });
+        }
+
+        let failover = &self.failover;
+        if failover.failover_role == Some(FailoverRole::Primary) &&
+            failover.failover_standby_url.is_none()
+        {
+            errors.push(ConfigError::MissingFailoverStandbyUrl);
+        }
+        let timeout = failover.failover_heartbeat_timeout_ms;
+        let interval = failover.failover_heartbeat_interval_ms;
+        if failover.failover_role.is_some() && timeout <= interval {
+            errors.push(ConfigError::FailoverTimeoutBelowInterval { timeout, interval });
+        }
+
+        if self.vouchers.enabled() && self.vouchers.voucher_settlement_interval_secs == 0 {
+            errors.push(ConfigError::ZeroVoucherSettlementInterval);
+        }
+
+        let targeting = &self.delegation_targeting;
+        if targeting.delegation_policy == DelegationPolicy::PerSlot &&
+            targeting.delegation_assignments_path.is_none()
+        {
+            errors.push(ConfigError::MissingDelegationAssignments);
+        }
+        if targeting.enabled() &&
+            targeting.delegatees.is_empty() &&
+            self.constraint_signing.delegations_path.is_none()
+        {
+            errors.push(ConfigError::NoDelegatees(targeting.delegation_policy));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors(errors))
+        }
+    }
+
+    /// Returns the resolved configuration as pretty-printed JSON, with secrets redacted.
+    pub fn to_pretty_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    fn validate_timings(&self, errors: &mut Vec<ConfigError>) {
+        if self.chain.slot_time == 0 {
+            errors.push(ConfigError::ZeroSlotTime);
+            return;
+        }
+
+        let slot_time = self.chain.slot_time * 1000;
+        let deadline = self.chain.commitment_deadline;
+        if deadline >= slot_time {
+            errors.push(ConfigError::DeadlineAfterSlotEnd { deadline, slot_time });
+        }
+
+        if let Some(cutoff) = self.tob_auction_cutoff_ms.map(|cutoff| cutoff.get()) {
+            if cutoff >= deadline {
+                errors.push(ConfigError::TobCutoffAfterDeadline { cutoff, deadline });
+            }
+        }
+
+        let budgets = &self.latency_budgets;
+        for (flag, budget) in [
+            ("commitment-deadline-budget-ms", budgets.commitment_deadline_budget_ms),
+            ("constraint-signing-budget-ms", budgets.constraint_signing_budget_ms),
+            ("constraints-submission-budget-ms", budgets.constraints_submission_budget_ms),
+            ("get-header-budget-ms", budgets.get_header_budget_ms),
+            ("get-payload-budget-ms", budgets.get_payload_budget_ms),
+        ] {
+            if budget > slot_time {
+                errors.push(ConfigError::BudgetExceedsSlot { flag, budget, slot_time });
+            }
+        }
+    }
+
+    fn validate_urls(&self, errors: &mut Vec<ConfigError>) {
+        let urls = [
+            ("execution-api-url", Some(&self.execution_api_url)),
+            ("beacon-api-url", Some(&self.beacon_api_url)),
+            ("engine-api-url", Some(&self.engine_api_url)),
+            ("constraints-api-url", Some(&self.constraints_api_url)),
+            ("self-builder-url", self.self_builder_url.as_ref()),
+            ("commit-boost-signer-url", self.constraint_signing.commit_boost_signer_url.as_ref()),
+            ("web3signer-url", self.constraint_signing.web3signer_url.as_ref()),
+            ("screening-url", self.screening.screening_url.as_ref()),
+            ("availability-webhook-url", self.availability_webhook_url.as_ref()),
+            ("commitment-webhook-url", self.commitment_webhook_url.as_ref()),
+            ("failover-standby-url", self.failover.failover_standby_url.as_ref()),
+        ];
+
+        let additional = self.additional_constraints_api_urls.iter();
+        let urls = urls
+            .into_iter()
+            .chain(additional.map(|url| ("additional-constraints-api-urls", Some(url))));
+        for (flag, url) in urls {
+            let Some(url) = url else { continue };
+            if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+                errors.push(ConfigError::InvalidUrl { flag, url: url.clone() });
+            }
+        }
+    }
+
+    fn validate_ports(&self, errors: &mut Vec<ConfigError>) {
+        let ports = [
+            ("port", Some(self.port)),
+            ("constraints-proxy-port", Some(self.constraints_proxy_port)),
+            ("admin-port", self.admin_port),
+            ("metrics-port", self.telemetry.metrics_port()),
+            (
+                "failover-port",
+                (self.failover.failover_role == Some(FailoverRole::Standby))
+                    .then_some(self.failover.failover_port),
+            ),
+        ];
+
+        let mut seen = HashMap::new();
+        for (flag, port) in ports {
+            // Port 0 lets the OS pick a free port, so it can't conflict
+            let Some(port) = port.filter(|port| *port != 0) else { continue };
+            if let Some(first) = seen.insert(port, flag) {
+                errors.push(ConfigError::PortConflict { first, second: flag, port });
+            }
+        }
+    }
+
+    fn validate_paths(&self, errors: &mut Vec<ConfigError>) {
+        let signing = &self.constraint_signing;
+
+        let uses_keystore = signing.keystore_password.is_some() ||
+            signing.keystore_password_path.is_some() ||
+            signing.keystore_secrets_path.is_some();
+        if uses_keystore && signing.keystore_path.is_none() {
+            errors.push(ConfigError::MissingKeystorePath);
+        }
+
+        let dirs = [
+            ("keystore-path", signing.keystore_path.as_deref()),
+            ("keystore-secrets-path", signing.keystore_secrets_path.as_deref()),
+        ];
+        for (flag, path) in dirs {
+            let Some(path) = path else { continue };
+            if let Some(error) = check_path(flag, path, true) {
+                errors.push(error);
+            }
+        }
+
+        let files = [
+            ("keystore-password-path", signing.keystore_password_path.as_deref()),
+            ("web3signer-client-cert-path", signing.web3signer_client_cert_path.as_deref()),
+            ("web3signer-client-key-path", signing.web3signer_client_key_path.as_deref()),
+            ("web3signer-ca-cert-path", signing.web3signer_ca_cert_path.as_deref()),
+            ("delegations-path", signing.delegations_path.as_deref()),
+            ("reload-config-path", self.reload_config_path.as_deref()),
+            (
+                "delegation-assignments-path",
+                self.delegation_targeting.delegation_assignments_path.as_deref(),
+            ),
+            ("screening-list-path", self.screening.screening_list_path.as_deref()),
+        ];
+        for (flag, path) in files {
+            let Some(path) = path else { continue };
+            if let Some(error) = check_path(flag, path, false) {
+                errors.push(error);
+            }
+        }
+    }
+}
+
+/// Check the limits against the other settings they depend on. These checks are shared by the
+/// startup validation and the configuration reloads, which can change the limits at runtime.
+pub(crate) fn validate_limits(
+    limits: &LimitsOpts,
+    reservations: &ReservationOpts,
+    errors: &mut Vec<ConfigError>,
+) {
+    let reserved = reservations.max_reserved_gas_per_slot;
+    let committed = limits.max_committed_gas_per_slot.get();
+    if reserved > committed {
+        errors.push(ConfigError::ReservedGasExceedsCommitted { reserved, committed });
+    }
+}
+
+/// Check that the path exists, and that it is a directory if required.
+fn check_path(flag: &'static str, path: &Path, dir: bool) -> Option<ConfigError> {
+    let display = path.display().to_string();
+    if !path.exists() {
+        Some(ConfigError::MissingPath { flag, path: display })
+    } else if dir && !path.is_dir() {
+        Some(ConfigError::NotADirectory { flag, path: display })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use clap::Parser;
+
+    use crate::{
+        common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
+        Command,
+    };
+
+    use super::*;
+
+    fn parse_opts(extra: &[&str]) -> Opts {
+        let args = [
+            "bolt-sidecar".to_string(),
+            "--validator-indexes=1..4".to_string(),
+            format!("--engine-jwt-hex={}", JwtSecretConfig::default()),
+            format!("--fee-recipient={}", alloy::primitives::Address::ZERO),
+            format!("--builder-private-key={}", BlsSecretKeyWrapper::random()),
+            format!("--commitment-private-key={}", EcdsaSecretKeyWrapper::random()),
+            format!("--constraint-private-key={}", BlsSecretKeyWrapper::random()),
+        ];
+
+        let extra = extra.iter().map(|arg| arg.to_string());
+        Opts::try_parse_from(args.into_iter().chain(extra)).unwrap()
+    }
+
+    #[test]
+    fn test_validate_default_opts() {
+        assert_eq!(parse_opts(&[]).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let opts = parse_opts(&[
+            "--slot-time=2",
+            "--commitment-deadline=3000",
+            "--constraints-proxy-port=8000",
+            "--beacon-api-url=unix:/tmp/beacon.sock",
+            "--delegations-path=/does/not/exist.json",
+        ]);
+
+        let errors = opts.validate().unwrap_err().0;
+        assert_eq!(
+            errors,
+            vec![
+                ConfigError::DeadlineAfterSlotEnd { deadline: 3000, slot_time: 2000 },
+                ConfigError::InvalidUrl {
+                    flag: "beacon-api-url",
+                    url: "unix:/tmp/beacon.sock".parse().unwrap()
+                },
+                ConfigError::PortConflict {
+                    first: "port",
+                    second: "constraints-proxy-port",
+                    port: 8000
+                },
+                ConfigError::MissingPath {
+                    flag: "delegations-path",
+                    path: "/does/not/exist.json".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_delegation_targeting() {
+        let opts = parse_opts(&["--delegation-policy=per-slot"]);
+        assert_eq!(
+            opts.validate().unwrap_err().0,
+            vec![
+                ConfigError::MissingDelegationAssignments,
+                ConfigError::NoDelegatees(DelegationPolicy::PerSlot),
+            ]
+        );
+
+        let delegatee = BlsSecretKeyWrapper::random().sk_to_pk().to_bytes();
+        let delegatee = format!("--delegatees=0x{}", hex::encode(delegatee));
+        let opts = parse_opts(&["--delegation-policy=round-robin", &delegatee]);
+        assert_eq!(opts.delegation_targeting.delegatees.len(), 1);
+        assert_eq!(opts.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_failover() {
+        let opts = parse_opts(&["--failover-role=primary", "--failover-heartbeat-timeout-ms=500"]);
+        assert_eq!(
+            opts.validate().unwrap_err().0,
+            vec![
+                ConfigError::MissingFailoverStandbyUrl,
+                ConfigError::FailoverTimeoutBelowInterval { timeout: 500, interval: 500 },
+            ]
+        );
+
+        let opts = parse_opts(&["--failover-role=standby", "--failover-port=8000"]);
+        assert_eq!(
+            opts.validate().unwrap_err().0,
+            vec![ConfigError::PortConflict { first: "port", second: "failover-port", port: 8000 }]
+        );
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let opts = parse_opts(&[
+            "--max-reserved-gas-per-slot=2000000",
+            "--max-committed-gas-per-slot=1000000",
+        ]);
+        assert_eq!(
+            opts.validate().unwrap_err().0,
+            vec![ConfigError::ReservedGasExceedsCommitted {
+                reserved: 2_000_000,
+                committed: 1_000_000
+            }]
+        );
+
+        // The same checks apply to the limits of a reload
+        let mut errors = Vec::new();
+        let limits = LimitsOpts {
+            max_committed_gas_per_slot: NonZero::new(3_000_000).unwrap(),
+            ..opts.limits
+        };
+        validate_limits(&limits, &opts.reservations, &mut errors);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_parse_subcommand() {
+        assert_eq!(parse_opts(&[]).command, None);
+        assert_eq!(parse_opts(&["check-config"]).command, Some(Command::CheckConfig));
+        assert_eq!(parse_opts(&["show-keys"]).command, Some(Command::ShowKeys));
+        assert_eq!(
+            parse_opts(&["import-interchange", "interchange.json"]).command,
+            Some(Command::ImportInterchange { path: "interchange.json".into() })
+        );
+        assert_eq!(
+            parse_opts(&["service-definition", "systemd", "--env-file", "sidecar.env"]).command,
+            Some(Command::ServiceDefinition {
+                platform: crate::service::ServicePlatform::Systemd,
+                env_file: Some("sidecar.env".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_print_config_redacts_secrets() {
+        let opts = parse_opts(&[]);
+        let json = opts.to_pretty_json().unwrap();
+
+        assert!(json.contains(crate::common::REDACTED));
+        assert!(!json.contains(&opts.builder_private_key.to_string()));
+        assert!(!json.contains(&opts.engine_jwt_hex.0));
+    }
+}
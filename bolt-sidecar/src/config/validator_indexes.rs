@@ -3,7 +3,7 @@ use std::{
     str::FromStr,
 };
 
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Clone, Default)]
 pub struct ValidatorIndexes(Vec<u64>);
@@ -12,6 +12,10 @@ impl ValidatorIndexes {
     pub fn contains(&self, index: u64) -> bool {
         self.0.contains(&index)
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl FromStr for ValidatorIndexes {
@@ -64,6 +68,12 @@ impl<'de> Deserialize<'de> for ValidatorIndexes {
     }
 }
 
+impl Serialize for ValidatorIndexes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl From<Vec<u64>> for ValidatorIndexes {
     fn from(vec: Vec<u64>) -> Self {
         Self(vec)
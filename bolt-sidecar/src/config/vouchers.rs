@@ -0,0 +1,82 @@
+use std::{num::NonZero, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::state::RetentionPolicy;
+
+// Default payment voucher values
+pub const DEFAULT_VOUCHER_PRICE_PER_GAS: u128 = 0;
+pub const DEFAULT_MAX_UNSETTLED_VOUCHER_AMOUNT: u128 = 100_000_000_000_000_000; // 0.1 ETH
+pub const DEFAULT_VOUCHER_SETTLEMENT_INTERVAL_SECS: u64 = 3600; // 1 hour
+pub const DEFAULT_VOUCHER_SETTLEMENT_DIR: &str = "vouchers";
+
+/// The prefix of the names of the voucher settlement batches, `vouchers-<unix timestamp>.json`.
+pub const VOUCHER_SETTLEMENT_PREFIX: &str = "vouchers";
+
+/// Options for the payment vouchers that inclusion requests must carry, if enabled.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+pub struct VoucherOpts {
+    /// Price in wei per unit of gas of the inclusion requests, paid with a payment voucher
+    /// signed by the request signer. Set to 0 to accept requests without vouchers
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_VOUCHER_PRICE_PER_GAS",
+        default_value_t = VoucherOpts::default().voucher_price_per_gas
+    )]
+    pub voucher_price_per_gas: u128,
+    /// Max amount in wei that a single payer can owe through vouchers not settled yet
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_UNSETTLED_VOUCHER_AMOUNT",
+        default_value_t = VoucherOpts::default().max_unsettled_voucher_amount
+    )]
+    pub max_unsettled_voucher_amount: u128,
+    /// Interval in seconds at which the latest vouchers of each payer are collected for
+    /// settlement
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_VOUCHER_SETTLEMENT_INTERVAL_SECS",
+        default_value_t = VoucherOpts::default().voucher_settlement_interval_secs
+    )]
+    pub voucher_settlement_interval_secs: u64,
+    /// Directory in which the batches of vouchers to settle on-chain are written
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_VOUCHER_SETTLEMENT_DIR",
+        default_value = DEFAULT_VOUCHER_SETTLEMENT_DIR
+    )]
+    pub voucher_settlement_dir: PathBuf,
+    /// Number of days after which the voucher settlement batches are compacted into an
+    /// archive in the `archive` subdirectory. If not provided, batches are kept as is
+    #[clap(long, env = "BOLT_SIDECAR_VOUCHER_SETTLEMENT_RETENTION_DAYS")]
+    pub voucher_settlement_retention_days: Option<NonZero<u64>>,
+}
+
+impl VoucherOpts {
+    /// Returns true if inclusion requests must be paid with vouchers.
+    pub fn enabled(&self) -> bool {
+        self.voucher_price_per_gas > 0
+    }
+
+    /// Returns the retention policy of the voucher settlement batches, if any.
+    pub fn retention_policy(&self) -> Option<RetentionPolicy> {
+        self.voucher_settlement_retention_days.map(|days| RetentionPolicy {
+            dir: self.voucher_settlement_dir.clone(),
+            prefix: VOUCHER_SETTLEMENT_PREFIX,
+            retain: Duration::from_secs(days.get() * 24 * 60 * 60),
+        })
+    }
+}
+
+impl Default for VoucherOpts {
+    fn default() -> Self {
+        Self {
+            voucher_price_per_gas: DEFAULT_VOUCHER_PRICE_PER_GAS,
+            max_unsettled_voucher_amount: DEFAULT_MAX_UNSETTLED_VOUCHER_AMOUNT,
+            voucher_settlement_interval_secs: DEFAULT_VOUCHER_SETTLEMENT_INTERVAL_SECS,
+            voucher_settlement_dir: PathBuf::from(DEFAULT_VOUCHER_SETTLEMENT_DIR),
+            voucher_settlement_retention_days: None,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use alloy::primitives::Address;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use super::reload::{read_whitelist_file, ReloadError};
+
+/// Options for the whitelist of the signers allowed to request commitments.
+/// Requests from any signer are accepted by default.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct WhitelistOpts {
+    /// Comma-separated addresses of the signers allowed to request commitments. If not provided,
+    /// requests from any signer are accepted
+    #[clap(long, env = "BOLT_SIDECAR_WHITELIST", value_delimiter = ',')]
+    #[serde(default)]
+    pub whitelist: Vec<Address>,
+    /// Path to a JSON file with the array of the addresses of the signers allowed to request
+    /// commitments. The file is watched, and the whitelist reloaded whenever it changes
+    #[clap(long, env = "BOLT_SIDECAR_WHITELIST_PATH", conflicts_with = "whitelist")]
+    pub whitelist_path: Option<PathBuf>,
+}
+
+impl WhitelistOpts {
+    /// Returns the signers of the whitelist at startup, reading the whitelist file if any.
+    pub fn signers(&self) -> Result<Vec<Address>, ReloadError> {
+        match &self.whitelist_path {
+            Some(path) => read_whitelist_file(path),
+            None => Ok(self.whitelist.clone()),
+        }
+    }
+}
@@ -17,6 +17,25 @@ pub trait SignableBLS {
     fn digest(&self) -> [u8; 32];
 }
 
+/// Verify the BLS signature of a signing root by the given public key.
+///
+/// Returns `false` on invalid public key or signature encodings instead of panicking,
+/// so that it can be used on untrusted inputs.
+pub fn verify_bls_signature(
+    pubkey: &BlsPublicKey,
+    signing_root: [u8; 32],
+    signature: &[u8],
+) -> bool {
+    let (Ok(pubkey), Ok(signature)) =
+        (PublicKey::from_bytes(pubkey.as_ref()), blst::min_pk::Signature::from_bytes(signature))
+    else {
+        return false;
+    };
+
+    let res = signature.verify(true, &signing_root, BLS_DST_PREFIX, &[], &pubkey, true);
+    res == blst::BLST_ERROR::BLST_SUCCESS
+}
+
 /// Convert a BLS public key from Consensus Types to a byte array.
 pub fn cl_public_key_to_arr(pubkey: BlsPublicKey) -> [u8; BLS_PUBLIC_KEY_BYTES_LEN] {
     pubkey.as_ref().try_into().expect("BLS keys are 48 bytes")
@@ -0,0 +1,293 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::B256;
+use axum::{
+    extract::{Path, State},
+    response::{
+        sse::{Event, KeepAlive},
+        Sse,
+    },
+    routing::get,
+    Json, Router,
+};
+use ethereum_consensus::phase0::mainnet::SLOTS_PER_EPOCH;
+use futures::{stream, Stream};
+use reqwest::Url;
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::broadcast, task::AbortHandle};
+use tracing::{debug, info, warn};
+
+use crate::primitives::BlsPublicKey;
+
+/// The path to the beacon API genesis endpoint.
+const GENESIS_PATH: &str = "/eth/v1/beacon/genesis";
+/// The path to the beacon API proposer duties endpoint.
+const PROPOSER_DUTIES_PATH: &str = "/eth/v1/validator/duties/proposer/:epoch";
+/// The path to the beacon API head header endpoint.
+const HEAD_HEADER_PATH: &str = "/eth/v1/beacon/headers/head";
+/// The path to the beacon API head block root endpoint.
+const HEAD_BLOCK_ROOT_PATH: &str = "/eth/v1/beacon/blocks/head/root";
+/// The path to the beacon API head RANDAO endpoint.
+const HEAD_RANDAO_PATH: &str = "/eth/v1/beacon/states/head/randao";
+/// The path to the builder API expected withdrawals endpoint.
+const EXPECTED_WITHDRAWALS_PATH: &str = "/eth/v1/builder/states/head/expected_withdrawals";
+/// The path to the beacon API events endpoint.
+const EVENTS_PATH: &str = "/eth/v1/events";
+
+/// The shared state of the [MockBeacon].
+#[derive(Debug)]
+struct BeaconState {
+    genesis_time: u64,
+    slot_time: u64,
+    proposer: BlsPublicKey,
+    validator_index: u64,
+    heads: broadcast::Sender<Value>,
+}
+
+impl BeaconState {
+    fn current_slot(&self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        now.saturating_sub(self.genesis_time) / self.slot_time
+    }
+}
+
+/// An in-process beacon node exposing the subset of the beacon API used by the sidecar.
+///
+/// Genesis is set to the time the beacon is started, and slots advance with the system
+/// clock. The configured validator is the proposer of every slot, and a head event is
+/// emitted at the start of each slot.
+#[derive(Debug)]
+pub struct MockBeacon {
+    addr: SocketAddr,
+    state: Arc<BeaconState>,
+    tasks: Vec<AbortHandle>,
+}
+
+impl MockBeacon {
+    /// Start the beacon on the given port of the loopback interface, with the given
+    /// validator proposing every slot. Use port 0 to let the OS pick a free port.
+    pub async fn start(
+        port: u16,
+        slot_time: u64,
+        proposer: BlsPublicKey,
+        validator_index: u64,
+    ) -> eyre::Result<Self> {
+        let genesis_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let (heads, _) = broadcast::channel(16);
+        let state =
+            Arc::new(BeaconState { genesis_time, slot_time, proposer, validator_index, heads });
+
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let addr = listener.local_addr()?;
+
+        let router = make_router(state.clone());
+        let server = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, router).await {
+                warn!(?err, "Mock beacon server stopped");
+            }
+        });
+
+        let ticker = tokio::spawn(emit_head_events(state.clone()));
+
+        info!(%addr, genesis_time, "Started mock beacon node");
+        Ok(Self { addr, state, tasks: vec![server.abort_handle(), ticker.abort_handle()] })
+    }
+
+    /// Returns the URL of the beacon node.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("valid URL")
+    }
+
+    /// Returns the genesis time, in seconds since the UNIX epoch.
+    pub fn genesis_time(&self) -> u64 {
+        self.state.genesis_time
+    }
+
+    /// Returns the current slot according to the system clock.
+    pub fn current_slot(&self) -> u64 {
+        self.state.current_slot()
+    }
+
+    /// Returns the start time of the given slot, in seconds since the UNIX epoch.
+    pub fn slot_start(&self, slot: u64) -> u64 {
+        self.state.genesis_time + slot * self.state.slot_time
+    }
+}
+
+impl Drop for MockBeacon {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(AbortHandle::abort);
+    }
+}
+
+fn make_router(state: Arc<BeaconState>) -> Router {
+    Router::new()
+        .route(GENESIS_PATH, get(genesis))
+        .route(PROPOSER_DUTIES_PATH, get(proposer_duties))
+        .route(HEAD_HEADER_PATH, get(head_header))
+        .route(HEAD_BLOCK_ROOT_PATH, get(head_block_root))
+        .route(HEAD_RANDAO_PATH, get(head_randao))
+        .route(EXPECTED_WITHDRAWALS_PATH, get(expected_withdrawals))
+        .route(EVENTS_PATH, get(events))
+        .with_state(state)
+}
+
+/// Broadcasts a head event at the start of every slot.
+async fn emit_head_events(state: Arc<BeaconState>) {
+    loop {
+        let next_slot = state.current_slot() + 1;
+        let next_slot_start = state.genesis_time + next_slot * state.slot_time;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        tokio::time::sleep(Duration::from_secs(next_slot_start).saturating_sub(now)).await;
+
+        debug!(slot = next_slot, "Mock beacon: emitting head event");
+        // An error means that there are no subscribers yet
+        let _ = state.heads.send(head_event(next_slot));
+    }
+}
+
+fn head_event(slot: u64) -> Value {
+    json!({
+        "slot": slot.to_string(),
+        "block": B256::random(),
+        "state": B256::random(),
+        "epoch_transition": slot % SLOTS_PER_EPOCH == 0,
+        "previous_duty_dependent_root": B256::ZERO,
+        "current_duty_dependent_root": B256::ZERO,
+        "execution_optimistic": false,
+    })
+}
+
+async fn genesis(State(state): State<Arc<BeaconState>>) -> Json<Value> {
+    Json(json!({
+        "data": {
+            "genesis_time": state.genesis_time.to_string(),
+            "genesis_validators_root": B256::ZERO,
+            "genesis_fork_version": "0x00000000",
+        }
+    }))
+}
+
+async fn proposer_duties(
+    State(state): State<Arc<BeaconState>>,
+    Path(epoch): Path<u64>,
+) -> Json<Value> {
+    let start_slot = epoch * SLOTS_PER_EPOCH;
+    let duties = (start_slot..start_slot + SLOTS_PER_EPOCH)
+        .map(|slot| {
+            json!({
+                "pubkey": state.proposer,
+                "validator_index": state.validator_index.to_string(),
+                "slot": slot.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Json(json!({
+        "dependent_root": B256::ZERO,
+        "execution_optimistic": false,
+        "data": duties,
+    }))
+}
+
+async fn head_header(State(state): State<Arc<BeaconState>>) -> Json<Value> {
+    Json(json!({
+        "execution_optimistic": false,
+        "finalized": false,
+        "data": {
+            "root": B256::random(),
+            "canonical": true,
+            "header": {
+                "message": {
+                    "slot": state.current_slot().to_string(),
+                    "proposer_index": state.validator_index.to_string(),
+                    "parent_root": B256::ZERO,
+                    "state_root": B256::ZERO,
+                    "body_root": B256::ZERO,
+                },
+                "signature": format!("0x{}", "00".repeat(96)),
+            }
+        }
+    }))
+}
+
+async fn head_block_root() -> Json<Value> {
+    Json(json!({
+        "execution_optimistic": false,
+        "finalized": false,
+        "data": { "root": B256::random() }
+    }))
+}
+
+async fn head_randao() -> Json<Value> {
+    Json(json!({
+        "execution_optimistic": false,
+        "finalized": false,
+        "data": { "randao": B256::random() }
+    }))
+}
+
+/// The mock beacon has no validators to withdraw from.
+async fn expected_withdrawals() -> Json<Value> {
+    Json(json!({ "execution_optimistic": false, "finalized": false, "data": [] }))
+}
+
+/// Streams head events as server-sent events. Other topics are ignored.
+async fn events(
+    State(state): State<Arc<BeaconState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let heads = state.heads.subscribe();
+
+    let stream = stream::unfold(heads, |mut heads| async move {
+        loop {
+            match heads.recv().await {
+                Ok(head) => {
+                    let event = Event::default().event("head").data(head.to_string());
+                    return Some((Ok(event), heads));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use beacon_api_client::{BlockId, StateId};
+
+    use super::*;
+    use crate::{
+        common::BlsSecretKeyWrapper, signer::local::LocalSigner, BeaconClient, ChainConfig,
+    };
+
+    #[tokio::test]
+    async fn test_mock_beacon_endpoints() -> eyre::Result<()> {
+        let proposer =
+            LocalSigner::new(BlsSecretKeyWrapper::random().0, ChainConfig::default()).pubkey();
+        let beacon = MockBeacon::start(0, 1, proposer.clone(), 0).await?;
+        let client = BeaconClient::new(beacon.url());
+
+        let genesis = client.get_genesis_details().await?;
+        assert_eq!(genesis.genesis_time, beacon.genesis_time());
+
+        let (_, duties) = client.get_proposer_duties(1).await?;
+        assert_eq!(duties.len() as u64, SLOTS_PER_EPOCH);
+        assert!(duties.iter().all(|duty| duty.public_key == proposer));
+
+        let header = client.get_beacon_header(BlockId::Head).await?;
+        assert!(header.header.message.slot <= beacon.current_slot());
+
+        let withdrawals = client.get_expected_withdrawals(StateId::Head, None).await?;
+        assert!(withdrawals.is_empty());
+
+        Ok(())
+    }
+}
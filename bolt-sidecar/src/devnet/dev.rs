@@ -0,0 +1,235 @@
+use std::{process::Stdio, time::Duration};
+
+use alloy::{
+    primitives::{utils::format_ether, U256},
+    providers::{Provider, ProviderBuilder},
+    signers::local::PrivateKeySigner,
+};
+use clap::Parser;
+use eyre::{bail, Context};
+use reqwest::Url;
+use tokio::process::{Child, Command};
+use tracing::{info, warn};
+
+use crate::{
+    config::{DEFAULT_CONSTRAINTS_PROXY_PORT, DEFAULT_RPC_PORT},
+    ChainConfig,
+};
+
+use super::{Devnet, DevnetConfig};
+
+/// The default slot time of the development mode, in seconds.
+pub const DEFAULT_DEV_SLOT_TIME: u64 = 2;
+
+/// The default port of the execution client spawned by the development mode.
+pub const DEFAULT_DEV_ANVIL_PORT: u16 = 8545;
+
+/// The balance of each of the pre-funded test signers, in ether.
+const FUNDED_SIGNER_BALANCE_ETH: u64 = 10_000;
+
+/// The time to wait for the spawned execution client to serve its JSON-RPC API.
+const ANVIL_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Options of the localhost development mode, run with `bolt-sidecar --dev`.
+///
+/// The development mode runs the sidecar against an in-process [Devnet] with accelerated
+/// slots, an anvil execution client and pre-funded test signers, so that preconfirmation
+/// flows can be tested locally without any devnet infrastructure.
+#[derive(Debug, Clone, Parser)]
+pub struct DevOpts {
+    /// Run the sidecar in localhost development mode
+    #[clap(long, env = "BOLT_SIDECAR_DEV", default_value_t = false)]
+    pub dev: bool,
+    /// Port to listen on for incoming JSON-RPC requests
+    #[clap(long, env = "BOLT_SIDECAR_PORT", default_value_t = DEFAULT_RPC_PORT)]
+    pub port: u16,
+    /// The port of the builder proxy, requested by the in-process proposer
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_CONSTRAINTS_PROXY_PORT",
+        default_value_t = DEFAULT_CONSTRAINTS_PROXY_PORT
+    )]
+    pub constraints_proxy_port: u16,
+    /// The URL of an anvil-compatible execution client to use, with the chain ID of the
+    /// Kurtosis chain (3151908). If not provided, `anvil` is spawned from the `PATH`.
+    #[clap(long, env = "BOLT_SIDECAR_EXECUTION_API_URL")]
+    pub execution_api_url: Option<Url>,
+    /// The port of the spawned `anvil`, if no execution client URL is provided
+    #[clap(long, env = "BOLT_SIDECAR_DEV_ANVIL_PORT", default_value_t = DEFAULT_DEV_ANVIL_PORT)]
+    pub anvil_port: u16,
+    /// The slot time of the development chain, in seconds
+    #[clap(long, env = "BOLT_SIDECAR_DEV_SLOT_TIME", default_value_t = DEFAULT_DEV_SLOT_TIME)]
+    pub slot_time: u64,
+    /// The number of test signers to fund on the execution client
+    #[clap(long, env = "BOLT_SIDECAR_DEV_FUNDED_SIGNERS", default_value_t = 5)]
+    pub funded_signers: usize,
+}
+
+impl DevOpts {
+    /// Returns true if the development mode is requested, with the `--dev` flag or the
+    /// `BOLT_SIDECAR_DEV` environment variable.
+    ///
+    /// This is checked before parsing the sidecar [Opts](crate::Opts), whose required
+    /// options are not needed in development mode.
+    pub fn requested() -> bool {
+        std::env::args().skip(1).any(|arg| arg == "--dev" || arg == "--dev=true") ||
+            std::env::var("BOLT_SIDECAR_DEV").is_ok_and(|value| value == "true")
+    }
+}
+
+/// Returns the balance of each of the pre-funded test signers, in wei.
+fn funded_signer_balance() -> U256 {
+    U256::from(FUNDED_SIGNER_BALANCE_ETH) * U256::from(10u64.pow(18))
+}
+
+/// A running localhost development environment. Dropping it kills the spawned `anvil`.
+#[derive(Debug)]
+pub struct DevMode {
+    devnet: Devnet,
+    execution_api_url: Url,
+    signers: Vec<PrivateKeySigner>,
+    slot_time: u64,
+    _anvil: Option<Child>,
+}
+
+impl DevMode {
+    /// Spawn `anvil` if needed, fund the test signers on it, then start the devnet.
+    pub async fn start(opts: DevOpts) -> eyre::Result<Self> {
+        if opts.slot_time == 0 {
+            bail!("The slot time must be greater than 0");
+        }
+
+        let chain_id = ChainConfig::kurtosis(opts.slot_time, 0).chain_id();
+        let (execution_api_url, anvil) = match opts.execution_api_url {
+            Some(url) => (url, None),
+            None => {
+                let anvil = Command::new("anvil")
+                    .args(["--port", &opts.anvil_port.to_string()])
+                    .args(["--chain-id", &chain_id.to_string()])
+                    .args(["--block-time", &opts.slot_time.to_string()])
+                    .stdout(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()
+                    .wrap_err("Failed to spawn anvil, is it installed and in the PATH?")?;
+
+                let url = Url::parse(&format!("http://127.0.0.1:{}", opts.anvil_port))?;
+                (url, Some(anvil))
+            }
+        };
+
+        let provider = ProviderBuilder::new().on_http(execution_api_url.clone());
+        let el_chain_id = tokio::time::timeout(ANVIL_STARTUP_TIMEOUT, async {
+            loop {
+                match provider.get_chain_id().await {
+                    Ok(chain_id) => break chain_id,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(200)).await,
+                }
+            }
+        })
+        .await
+        .wrap_err_with(|| format!("Execution client at {execution_api_url} is unreachable"))?;
+
+        if el_chain_id != chain_id {
+            bail!("Execution client has chain ID {el_chain_id}, expected {chain_id}");
+        }
+
+        let signers: Vec<_> =
+            (0..opts.funded_signers).map(|_| PrivateKeySigner::random()).collect();
+        for signer in &signers {
+            provider
+                .raw_request::<_, ()>(
+                    "anvil_setBalance".into(),
+                    (signer.address(), funded_signer_balance()),
+                )
+                .await
+                .wrap_err("Failed to fund test signer, is the execution client anvil?")?;
+        }
+
+        let devnet = Devnet::start(DevnetConfig {
+            execution_api_url: execution_api_url.clone(),
+            rpc_port: opts.port,
+            constraints_proxy_port: opts.constraints_proxy_port,
+            slot_time: opts.slot_time,
+            ..Default::default()
+        })
+        .await?;
+
+        Ok(Self { devnet, execution_api_url, signers, slot_time: opts.slot_time, _anvil: anvil })
+    }
+
+    /// Returns the pre-funded test signers.
+    pub fn signers(&self) -> &[PrivateKeySigner] {
+        &self.signers
+    }
+
+    /// Print the endpoints and the pre-funded test signers of the development environment.
+    pub fn print_summary(&self) {
+        let balance = format_ether(funded_signer_balance());
+
+        println!();
+        println!("Bolt sidecar running in development mode");
+        println!();
+        println!("Commitments API:  {}", self.devnet.rpc_url());
+        println!("Execution client: {}", self.execution_api_url);
+        println!("Chain ID:         {}", ChainConfig::kurtosis(self.slot_time, 0).chain_id());
+        println!("Slot time:        {}s", self.slot_time);
+        println!();
+        println!("Pre-funded test signers ({balance} ETH each):");
+        for (i, signer) in self.signers.iter().enumerate() {
+            println!("({i}) {} 0x{}", signer.address(), hex::encode(signer.to_bytes()));
+        }
+        println!();
+    }
+
+    /// Propose every slot of the devnet until CTRL+C, then stop it.
+    ///
+    /// The devnet proposer requests the block of each slot from the sidecar as soon as it
+    /// starts, and the committed transactions of the block are then sent to the execution
+    /// client, so that they land on chain like they would on a real network.
+    pub async fn run(self) -> eyre::Result<()> {
+        let provider = ProviderBuilder::new().on_http(self.execution_api_url.clone());
+        let mut slot = self.devnet.current_slot() + 1;
+
+        loop {
+            tokio::select! {
+                _ = self.devnet.wait_for_slot(slot) => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+
+            match self.devnet.propose(slot).await {
+                Ok(payload) => {
+                    let transactions = payload.execution_payload().transactions();
+                    info!(slot, txs = transactions.len(), "Dev mode: proposed block");
+
+                    for tx in transactions.iter() {
+                        if let Err(err) = provider.send_raw_transaction(tx.as_ref()).await {
+                            warn!(slot, ?err, "Dev mode: failed to send committed transaction");
+                        }
+                    }
+                }
+                Err(err) => warn!(slot, ?err, "Dev mode: failed to propose block"),
+            }
+
+            slot = slot.max(self.devnet.current_slot()) + 1;
+        }
+
+        self.devnet.shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use super::*;
+
+    #[test]
+    fn test_dev_opts() {
+        DevOpts::command().debug_assert();
+
+        let opts = DevOpts::parse_from(["bolt-sidecar", "--dev", "--slot-time=1"]);
+        assert!(opts.dev);
+        assert_eq!(opts.slot_time, 1);
+        assert_eq!(opts.port, DEFAULT_RPC_PORT);
+    }
+}
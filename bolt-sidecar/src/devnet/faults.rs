@@ -0,0 +1,175 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use axum::http::StatusCode;
+use clap::ValueEnum;
+use rand::Rng;
+
+/// The endpoints of the [MockRelay](super::MockRelay) in which faults can be injected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab_case")]
+pub enum RelayEndpoint {
+    /// The builder API status endpoint.
+    Status,
+    /// The builder API register validators endpoint.
+    RegisterValidators,
+    /// The constraints API submit constraints endpoint.
+    SubmitConstraints,
+    /// The constraints API delegate endpoint.
+    Delegate,
+    /// The constraints API revoke endpoint.
+    Revoke,
+    /// Both the builder API get header and the constraints API get header with proofs endpoints.
+    GetHeader,
+    /// The builder API get payload endpoint.
+    GetPayload,
+}
+
+impl FromStr for RelayEndpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, true)
+    }
+}
+
+/// The faults injected in a single endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EndpointFaults {
+    /// Delay before the request is handled.
+    pub latency: Duration,
+    /// Probability in [0, 1] that the request fails.
+    pub failure_rate: f64,
+    /// Number of upcoming requests that fail regardless of the failure rate.
+    pub fail_next: u64,
+}
+
+/// The outcome of a request to a faulty endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FaultOutcome {
+    /// The request should be handled normally, after the given delay.
+    Proceed(Duration),
+    /// The request should fail with the given status, after the given delay.
+    Fail(Duration, StatusCode),
+}
+
+/// Latencies and failures injected in the endpoints of the [MockRelay](super::MockRelay),
+/// to rehearse how the sidecar behaves with a slow or unreliable relay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultInjection {
+    endpoints: HashMap<RelayEndpoint, EndpointFaults>,
+    failure_status: StatusCode,
+}
+
+impl Default for FaultInjection {
+    fn default() -> Self {
+        Self { endpoints: HashMap::new(), failure_status: StatusCode::INTERNAL_SERVER_ERROR }
+    }
+}
+
+impl FaultInjection {
+    /// Set the latency of the given endpoint.
+    pub fn with_latency(mut self, endpoint: RelayEndpoint, latency: Duration) -> Self {
+        self.endpoints.entry(endpoint).or_default().latency = latency;
+        self
+    }
+
+    /// Set the probability in [0, 1] that a request to the given endpoint fails.
+    pub fn with_failure_rate(mut self, endpoint: RelayEndpoint, rate: f64) -> Self {
+        self.endpoints.entry(endpoint).or_default().failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Make the next `count` requests to the given endpoint fail.
+    pub fn with_fail_next(mut self, endpoint: RelayEndpoint, count: u64) -> Self {
+        self.endpoints.entry(endpoint).or_default().fail_next = count;
+        self
+    }
+
+    /// Set the status code returned by failing requests. Defaults to 500.
+    pub fn with_failure_status(mut self, status: StatusCode) -> Self {
+        self.failure_status = status;
+        self
+    }
+
+    /// Returns the faults injected in the given endpoint.
+    pub fn endpoint(&self, endpoint: RelayEndpoint) -> EndpointFaults {
+        self.endpoints.get(&endpoint).copied().unwrap_or_default()
+    }
+
+    /// Decide the outcome of a new request to the given endpoint.
+    pub(crate) fn next_outcome(&mut self, endpoint: RelayEndpoint) -> FaultOutcome {
+        let Some(faults) = self.endpoints.get_mut(&endpoint) else {
+            return FaultOutcome::Proceed(Duration::ZERO);
+        };
+
+        let fail = if faults.fail_next > 0 {
+            faults.fail_next -= 1;
+            true
+        } else {
+            faults.failure_rate > 0.0 && rand::thread_rng().gen_bool(faults.failure_rate)
+        };
+
+        if fail {
+            FaultOutcome::Fail(faults.latency, self.failure_status)
+        } else {
+            FaultOutcome::Proceed(faults.latency)
+        }
+    }
+}
+
+/// Parse a `<endpoint>=<value>` pair, e.g. `get-header=500`.
+pub fn parse_endpoint_value<T>(s: &str) -> Result<(RelayEndpoint, T), String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let (endpoint, value) =
+        s.split_once('=').ok_or_else(|| format!("expected <endpoint>=<value>, got {s}"))?;
+    let value = value.trim().parse::<T>().map_err(|e| format!("invalid value {value}: {e}"))?;
+
+    Ok((endpoint.trim().parse()?, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_injection_outcomes() {
+        let mut faults = FaultInjection::default()
+            .with_latency(RelayEndpoint::GetHeader, Duration::from_millis(200))
+            .with_fail_next(RelayEndpoint::GetHeader, 1)
+            .with_failure_rate(RelayEndpoint::GetPayload, 1.0)
+            .with_failure_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let latency = Duration::from_millis(200);
+        let status = StatusCode::SERVICE_UNAVAILABLE;
+        assert_eq!(
+            faults.next_outcome(RelayEndpoint::GetHeader),
+            FaultOutcome::Fail(latency, status)
+        );
+        assert_eq!(faults.next_outcome(RelayEndpoint::GetHeader), FaultOutcome::Proceed(latency));
+        assert_eq!(
+            faults.next_outcome(RelayEndpoint::GetPayload),
+            FaultOutcome::Fail(Duration::ZERO, status)
+        );
+        assert_eq!(
+            faults.next_outcome(RelayEndpoint::Status),
+            FaultOutcome::Proceed(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_endpoint_value() {
+        assert_eq!(
+            parse_endpoint_value::<u64>("get-header=500"),
+            Ok((RelayEndpoint::GetHeader, 500))
+        );
+        assert_eq!(
+            parse_endpoint_value::<f64>("submit-constraints=0.5"),
+            Ok((RelayEndpoint::SubmitConstraints, 0.5))
+        );
+        assert!(parse_endpoint_value::<u64>("get-header").is_err());
+        assert!(parse_endpoint_value::<u64>("unknown=1").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fs, path::Path};
+
+use alloy::{
+    eips::eip2718::Encodable2718,
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    rpc::types::TransactionRequest,
+    signers::{k256::ecdsa::SigningKey, local::PrivateKeySigner},
+};
+use blst::min_pk::SecretKey;
+use ethereum_consensus::{bellatrix::mainnet::Transaction, ssz::prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::SignableBLS,
+    primitives::{
+        proofs::{compute_multiproof, transaction_leaf, transactions_root},
+        ssz::SszEncoding,
+        BlsPublicKey, ConstraintsMessage, FullTransaction, MerkleMultiProof, SignedConstraints,
+    },
+    signer::local::LocalSigner,
+    ChainConfig,
+};
+
+/// The maximum number of payload transactions in a fixture.
+pub const MAX_FIXTURE_TRANSACTIONS: usize = 1024;
+
+/// The parameters of a [ProofFixture]. The same parameters always produce the same fixture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureParams {
+    /// The seed from which keys and transactions are derived.
+    pub seed: u64,
+    /// The slot of the constraints.
+    pub slot: u64,
+    /// The number of signed constraints messages.
+    pub constraints_count: usize,
+    /// The number of transactions in each constraints message.
+    pub txs_per_constraint: usize,
+    /// The number of unconstrained transactions in the payload, interleaved
+    /// with the constrained ones.
+    pub unconstrained_txs: usize,
+}
+
+impl Default for FixtureParams {
+    fn default() -> Self {
+        Self { seed: 0, slot: 1, constraints_count: 2, txs_per_constraint: 2, unconstrained_txs: 4 }
+    }
+}
+
+/// A deterministic set of signed constraints, the payload transactions that satisfy them,
+/// and the Merkle multiproof of their inclusion, to test other implementations of the
+/// Constraints API against bolt's proof format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofFixture {
+    /// The parameters the fixture was generated from.
+    pub params: FixtureParams,
+    /// The public key of the signer of the constraints.
+    pub pubkey: BlsPublicKey,
+    /// The signed constraints.
+    pub constraints: Vec<SignedConstraints>,
+    /// The EIP-2718 encoded transactions of the payload, in order.
+    pub transactions: Vec<Bytes>,
+    /// The leaves of the transactions tree, i.e. the hash tree roots of the transactions.
+    pub leaves: Vec<B256>,
+    /// The transactions root of the payload.
+    pub transactions_root: B256,
+    /// The positions of the constrained transactions in the payload.
+    pub constrained_indices: Vec<usize>,
+    /// The multiproof of inclusion of the constrained transactions.
+    pub proofs: MerkleMultiProof,
+}
+
+impl ProofFixture {
+    /// Generate the fixture for the given parameters on the given chain.
+    pub async fn generate(params: FixtureParams, chain: ChainConfig) -> eyre::Result<Self> {
+        let constrained_count = params.constraints_count * params.txs_per_constraint;
+        let total = constrained_count + params.unconstrained_txs;
+        if total > MAX_FIXTURE_TRANSACTIONS {
+            eyre::bail!("Too many transactions: {total} (max {MAX_FIXTURE_TRANSACTIONS})");
+        }
+
+        let sender = PrivateKeySigner::from_signing_key(SigningKey::from_slice(
+            seeded(params.seed, b"sender").as_slice(),
+        )?);
+        let wallet = EthereumWallet::from(sender.clone());
+
+        let mut transactions = Vec::with_capacity(total);
+        for nonce in 0..total as u64 {
+            let to = Address::from_slice(&seeded(params.seed, &nonce.to_le_bytes())[12..]);
+            let tx = TransactionRequest::default()
+                .with_from(sender.address())
+                .with_to(to)
+                .with_chain_id(chain.chain_id())
+                .with_nonce(nonce)
+                .with_value(U256::from(nonce + 1))
+                .with_gas_limit(21_000)
+                .with_max_priority_fee_per_gas(1_000_000_000)
+                .with_max_fee_per_gas(20_000_000_000);
+
+            let raw = tx.build(&wallet).await?.encoded_2718();
+            transactions.push(FullTransaction::decode_enveloped(raw)?);
+        }
+
+        // Spread the constrained transactions evenly in the payload, starting at the top
+        let constrained_indices = (0..constrained_count)
+            .map(|i| i * total / constrained_count.max(1))
+            .collect::<Vec<_>>();
+
+        let signer = LocalSigner::new(seeded_bls_key(params.seed, b"bls")?, chain);
+        let pubkey = signer.pubkey();
+
+        let mut constraints = Vec::with_capacity(params.constraints_count);
+        for chunk in constrained_indices.chunks(params.txs_per_constraint.max(1)) {
+            let message = ConstraintsMessage {
+                pubkey: pubkey.clone(),
+                slot: params.slot,
+                top: false,
+                index: None,
+                transactions: chunk.iter().map(|i| transactions[*i].clone()).collect(),
+            };
+            let signature = signer.sign_commit_boost_root(message.digest())?;
+            constraints.push(SignedConstraints { message, signature });
+        }
+
+        let leaves = transactions.iter().map(transaction_leaf).collect::<Result<Vec<_>, _>>()?;
+        let tx_hashes =
+            constrained_indices.iter().map(|i| *transactions[*i].hash()).collect::<Vec<_>>();
+        let proofs = compute_multiproof(&leaves, &tx_hashes, &constrained_indices)?;
+
+        Ok(Self {
+            params,
+            pubkey,
+            constraints,
+            transactions: transactions.iter().map(|tx| tx.envelope_encoded()).collect(),
+            transactions_root: transactions_root(&leaves),
+            leaves,
+            constrained_indices,
+            proofs,
+        })
+    }
+
+    /// Write the fixture to the given directory, as `fixture.json` and SSZ files:
+    /// - `constraints_<i>.ssz`: each signed constraints message
+    /// - `transactions.ssz`: the payload transactions, as an SSZ list of byte lists
+    /// - `proofs.ssz`: the multiproof of inclusion of the constrained transactions
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> eyre::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        fs::write(dir.join("fixture.json"), serde_json::to_string_pretty(self)?)?;
+
+        for (i, constraints) in self.constraints.iter().enumerate() {
+            fs::write(dir.join(format!("constraints_{i}.ssz")), constraints.to_ssz_bytes()?)?;
+        }
+
+        let mut transactions = List::<Transaction, MAX_FIXTURE_TRANSACTIONS>::default();
+        for tx in &self.transactions {
+            transactions.push(Transaction::try_from(tx.as_ref())?);
+        }
+        fs::write(dir.join("transactions.ssz"), ssz_rs::serialize(&transactions)?)?;
+        fs::write(dir.join("proofs.ssz"), ssz_rs::serialize(&self.proofs)?)?;
+
+        Ok(())
+    }
+}
+
+/// Derive 32 bytes from the seed and a domain, so that keys and transactions are
+/// deterministic but distinct.
+fn seeded(seed: u64, domain: &[u8]) -> B256 {
+    keccak256([seed.to_le_bytes().as_slice(), domain].concat())
+}
+
+/// Derive a BLS secret key from the seed and a domain.
+pub(crate) fn seeded_bls_key(seed: u64, domain: &[u8]) -> eyre::Result<SecretKey> {
+    let ikm = seeded(seed, domain);
+    let Ok(key) = SecretKey::key_gen(ikm.as_slice(), &[]) else {
+        eyre::bail!("Failed to derive the BLS key from seed {seed}");
+    };
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::proofs::{generalized_index, verify_multiproof};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_proof_fixture_is_deterministic() -> eyre::Result<()> {
+        let params = FixtureParams::default();
+        let fixture = ProofFixture::generate(params, ChainConfig::default()).await?;
+        let again = ProofFixture::generate(params, ChainConfig::default()).await?;
+
+        assert_eq!(serde_json::to_string(&fixture)?, serde_json::to_string(&again)?);
+        assert_eq!(fixture.constraints.len(), params.constraints_count);
+        assert_eq!(fixture.transactions.len(), 8);
+
+        let proven =
+            fixture.constrained_indices.iter().map(|i| fixture.leaves[*i]).collect::<Vec<_>>();
+        let indices =
+            fixture.proofs.generalized_indexes.iter().map(|i| *i as usize).collect::<Vec<_>>();
+        let hashes = fixture
+            .proofs
+            .merkle_hashes
+            .iter()
+            .map(|h| B256::from_slice(h.as_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(indices[0], generalized_index(fixture.constrained_indices[0]));
+        assert!(verify_multiproof(&proven, &hashes, &indices, fixture.transactions_root)?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! An in-process devnet to exercise the full commitment flow locally, without a
+//! beacon node, relay or validator client.
+//!
+//! The devnet is made of a [MockBeacon] driving the slot clock, a [MockRelay] implementing
+//! the Constraints API, and a sidecar started with the [SidecarBuilder]. The devnet itself
+//! plays the role of the proposer, requesting headers and payloads from the sidecar's
+//! builder proxy with [Devnet::propose]. Only the execution client is external.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use ethereum_consensus::{deneb::mainnet::SignedBlindedBeaconBlock, primitives::Hash32};
+use reqwest::{StatusCode, Url};
+use tracing::info;
+
+use crate::{
+    api::spec::{ErrorResponse, GET_PAYLOAD_PATH},
+    common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
+    config::{DEFAULT_CONSTRAINTS_PROXY_PORT, DEFAULT_RPC_PORT},
+    primitives::{BlsPublicKey, GetPayloadResponse, SignedBuilderBid},
+    signer::local::LocalSigner,
+    ChainConfig, Opts, SidecarBuilder, SidecarHandle,
+};
+
+/// The mock beacon node of the devnet.
+pub mod beacon;
+pub use beacon::MockBeacon;
+
+/// The mock relay of the devnet, implementing the Constraints API.
+pub mod relay;
+pub use relay::MockRelay;
+
+/// Latencies and failures that can be injected in the mock relay.
+pub mod faults;
+pub use faults::{FaultInjection, RelayEndpoint};
+
+/// The localhost development mode of the sidecar, run with `--dev`.
+pub mod dev;
+pub use dev::{DevMode, DevOpts};
+
+/// Deterministic fixtures of constraints and their inclusion proofs, to test
+/// other implementations against bolt's proof format.
+pub mod fixtures;
+pub use fixtures::{FixtureParams, ProofFixture};
+
+/// Canonical JSON and SSZ test vectors of the Constraints API messages, to check
+/// interoperability with other implementations such as the Go relay.
+pub mod vectors;
+pub use vectors::{InteropVectors, MessageVector};
+
+/// The default slot time of the devnet, in seconds.
+pub const DEFAULT_DEVNET_SLOT_TIME: u64 = 4;
+
+/// The validator index of the single proposer of the devnet.
+const DEVNET_VALIDATOR_INDEX: u64 = 0;
+
+/// Configuration of the in-process [Devnet].
+#[derive(Debug, Clone)]
+pub struct DevnetConfig {
+    /// The URL of the execution client to validate commitment requests against.
+    pub execution_api_url: Url,
+    /// The URL of the Engine API of the execution client, used for local block building.
+    pub engine_api_url: Url,
+    /// The JWT secret to authenticate to the Engine API. If not provided, a random one
+    /// is used and local block building will fail.
+    pub engine_jwt_hex: Option<String>,
+    /// The port of the sidecar commitments JSON-RPC listener.
+    pub rpc_port: u16,
+    /// The port of the sidecar builder proxy listener.
+    pub constraints_proxy_port: u16,
+    /// The slot time of the devnet, in seconds.
+    pub slot_time: u64,
+}
+
+impl Default for DevnetConfig {
+    fn default() -> Self {
+        Self {
+            execution_api_url: Url::parse("http://localhost:8545").expect("valid URL"),
+            engine_api_url: Url::parse("http://localhost:8551").expect("valid URL"),
+            engine_jwt_hex: None,
+            rpc_port: DEFAULT_RPC_PORT,
+            constraints_proxy_port: DEFAULT_CONSTRAINTS_PROXY_PORT,
+            slot_time: DEFAULT_DEVNET_SLOT_TIME,
+        }
+    }
+}
+
+/// A running in-process devnet. Dropping it stops the mock beacon and relay,
+/// use [Devnet::shutdown] to also stop the sidecar gracefully.
+#[derive(Debug)]
+pub struct Devnet {
+    config: DevnetConfig,
+    beacon: MockBeacon,
+    relay: MockRelay,
+    sidecar: SidecarHandle,
+    proposer: BlsPublicKey,
+    http: reqwest::Client,
+}
+
+impl Devnet {
+    /// Start the mock beacon and relay, then the sidecar connected to them.
+    pub async fn start(config: DevnetConfig) -> eyre::Result<Self> {
+        // Commitments for the next slot are accepted for the first 2/3 of the slot,
+        // which leaves time to submit the constraints to the relay before it ends.
+        let commitment_deadline = config.slot_time * 1000 * 2 / 3;
+        let chain = ChainConfig::kurtosis(config.slot_time, commitment_deadline);
+
+        let relay = MockRelay::start(0, chain).await?;
+
+        // The proposer of every slot is the validator whose key signs the constraints,
+        // so that no delegation is needed.
+        let constraint_key = BlsSecretKeyWrapper::random();
+        let proposer = LocalSigner::new(constraint_key.0.clone(), chain).pubkey();
+
+        let beacon =
+            MockBeacon::start(0, config.slot_time, proposer.clone(), DEVNET_VALIDATOR_INDEX)
+                .await?;
+
+        let engine_jwt_hex =
+            config.engine_jwt_hex.clone().unwrap_or_else(|| JwtSecretConfig::default().0);
+
+        let opts = Opts::try_parse_from([
+            "bolt-sidecar".to_string(),
+            format!("--port={}", config.rpc_port),
+            format!("--execution-api-url={}", config.execution_api_url),
+            format!("--beacon-api-url={}", beacon.url()),
+            format!("--engine-api-url={}", config.engine_api_url),
+            format!("--constraints-api-url={}", relay.url()),
+            format!("--constraints-proxy-port={}", config.constraints_proxy_port),
+            format!("--validator-indexes={DEVNET_VALIDATOR_INDEX}"),
+            format!("--engine-jwt-hex={engine_jwt_hex}"),
+            format!("--fee-recipient={}", alloy::primitives::Address::ZERO),
+            format!("--builder-private-key={}", BlsSecretKeyWrapper::random()),
+            format!("--commitment-private-key={}", EcdsaSecretKeyWrapper::random()),
+            format!("--constraint-private-key={constraint_key}"),
+            "--chain=kurtosis".to_string(),
+            format!("--slot-time={}", config.slot_time),
+            format!("--commitment-deadline={commitment_deadline}"),
+        ])?;
+
+        let sidecar = SidecarBuilder::new(opts).spawn().await?;
+
+        info!(
+            rpc_port = config.rpc_port,
+            constraints_proxy_port = config.constraints_proxy_port,
+            beacon = %beacon.url(),
+            relay = %relay.url(),
+            "Devnet started"
+        );
+
+        Ok(Self { config, beacon, relay, sidecar, proposer, http: reqwest::Client::new() })
+    }
+
+    /// Returns the URL of the sidecar commitments JSON-RPC API.
+    pub fn rpc_url(&self) -> Url {
+        Url::parse(&format!("http://127.0.0.1:{}", self.config.rpc_port)).expect("valid URL")
+    }
+
+    /// Returns the URL of the sidecar builder proxy.
+    pub fn constraints_proxy_url(&self) -> Url {
+        Url::parse(&format!("http://127.0.0.1:{}", self.config.constraints_proxy_port))
+            .expect("valid URL")
+    }
+
+    /// Returns the mock beacon node of the devnet.
+    pub fn beacon(&self) -> &MockBeacon {
+        &self.beacon
+    }
+
+    /// Returns the mock relay of the devnet.
+    pub fn relay(&self) -> &MockRelay {
+        &self.relay
+    }
+
+    /// Returns the public key of the validator proposing every slot.
+    pub fn proposer(&self) -> &BlsPublicKey {
+        &self.proposer
+    }
+
+    /// Returns the current slot of the devnet.
+    pub fn current_slot(&self) -> u64 {
+        self.beacon.current_slot()
+    }
+
+    /// Wait until the start of the given slot. Returns immediately if it has already started.
+    pub async fn wait_for_slot(&self, slot: u64) {
+        let slot_start = Duration::from_secs(self.beacon.slot_start(slot));
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        tokio::time::sleep(slot_start.saturating_sub(now)).await;
+    }
+
+    /// Act as the proposer of the given slot: fetch the header from the sidecar builder
+    /// proxy, then submit the blinded block for it and return the revealed payload.
+    pub async fn propose(&self, slot: u64) -> eyre::Result<GetPayloadResponse> {
+        let parent_hash = Hash32::default();
+        let header_url = self.constraints_proxy_url().join(&format!(
+            "/eth/v1/builder/header/{slot}/0x{}/0x{}",
+            hex::encode(parent_hash.as_ref()),
+            hex::encode(self.proposer.as_ref())
+        ))?;
+
+        let response = self.http.get(header_url).send().await?;
+        if response.status() != StatusCode::OK {
+            let error = response.json::<ErrorResponse>().await?;
+            eyre::bail!("Failed to get header for slot {slot}: {error:?}");
+        }
+
+        let bid = response.json::<beacon_api_client::VersionedValue<SignedBuilderBid>>().await?;
+        info!(slot, block_hash = %bid.data.message.header.block_hash, "Devnet: received header");
+
+        let mut signed_block = SignedBlindedBeaconBlock::default();
+        signed_block.message.slot = slot;
+        signed_block.message.proposer_index = DEVNET_VALIDATOR_INDEX as usize;
+        signed_block.message.body.execution_payload_header = bid.data.message.header;
+
+        let response = self
+            .http
+            .post(self.constraints_proxy_url().join(GET_PAYLOAD_PATH)?)
+            .json(&signed_block)
+            .send()
+            .await?;
+        if response.status() != StatusCode::OK {
+            let error = response.json::<ErrorResponse>().await?;
+            eyre::bail!("Failed to get payload for slot {slot}: {error:?}");
+        }
+
+        Ok(response.json::<GetPayloadResponse>().await?)
+    }
+
+    /// Stop the sidecar, then the mock beacon and relay.
+    pub async fn shutdown(self) -> eyre::Result<()> {
+        self.sidecar.shutdown().await?;
+        info!("Devnet stopped");
+        Ok(())
+    }
+}
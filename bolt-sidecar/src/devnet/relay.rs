@@ -0,0 +1,428 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::{B256, U256};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use beacon_api_client::VersionedValue;
+use blst::min_pk::SecretKey;
+use ethereum_consensus::{
+    bellatrix::mainnet::Transaction,
+    builder::SignedValidatorRegistration,
+    deneb::mainnet::{
+        ExecutionPayload as DenebExecutionPayload, ExecutionPayloadHeader,
+        SignedBlindedBeaconBlock, MAX_TRANSACTIONS_PER_PAYLOAD,
+    },
+    primitives::Hash32,
+    ssz::prelude::{HashTreeRoot, List},
+    types::mainnet::ExecutionPayload,
+    Fork,
+};
+use parking_lot::Mutex;
+use reqwest::Url;
+use serde::Serialize;
+use tokio::{net::TcpListener, task::AbortHandle};
+use tracing::{debug, info, warn};
+
+use crate::{
+    api::{
+        builder::GetHeaderParams,
+        spec::{
+            ErrorResponse, DELEGATE_PATH, GET_HEADER_PATH, GET_HEADER_WITH_PROOFS_PATH,
+            GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH, REVOKE_PATH, STATUS_PATH,
+            SUBMIT_CONSTRAINTS_PATH,
+        },
+    },
+    builder::signature::sign_builder_message,
+    common::BlsSecretKeyWrapper,
+    primitives::{
+        BatchedSignedConstraints, BlsPublicKey, BuilderBid, GetPayloadResponse, PayloadAndBlobs,
+        SignedBuilderBid, SignedConstraints, SignedDelegation, SignedRevocation,
+    },
+    ChainConfig,
+};
+
+use super::faults::{FaultInjection, FaultOutcome, RelayEndpoint};
+
+/// The value of the bids returned by the mock relay, in wei.
+const MOCK_BID_VALUE: u64 = 1_000_000_000;
+
+/// A signed builder bid, along with the hashes of the constrained transactions it includes.
+///
+/// NOTE: the mock relay does not compute merkle proofs, as the sidecar does not verify them
+/// yet. It only lists the transaction hashes so that callers can check for inclusion.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedBuilderBidWithTxHashes {
+    #[serde(flatten)]
+    bid: SignedBuilderBid,
+    proofs: ConstrainedTxHashes,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ConstrainedTxHashes {
+    transaction_hashes: Vec<B256>,
+}
+
+/// The in-memory state of the [MockRelay].
+#[derive(Debug)]
+struct RelayState {
+    chain: ChainConfig,
+    builder_key: SecretKey,
+    constraints: Mutex<HashMap<u64, Vec<SignedConstraints>>>,
+    delegations: Mutex<Vec<SignedDelegation>>,
+    revocations: Mutex<Vec<SignedRevocation>>,
+    registrations: Mutex<Vec<SignedValidatorRegistration>>,
+    /// Payloads of the bids returned so far, by block hash.
+    payloads: Mutex<HashMap<B256, PayloadAndBlobs>>,
+    faults: Mutex<FaultInjection>,
+}
+
+impl RelayState {
+    /// Apply the faults injected in the given endpoint, waiting for its latency
+    /// and returning an error if the request should fail.
+    async fn inject_faults(&self, endpoint: RelayEndpoint) -> Result<(), MockRelayError> {
+        let outcome = self.faults.lock().next_outcome(endpoint);
+
+        match outcome {
+            FaultOutcome::Proceed(latency) => {
+                tokio::time::sleep(latency).await;
+                Ok(())
+            }
+            FaultOutcome::Fail(latency, code) => {
+                tokio::time::sleep(latency).await;
+                debug!(?endpoint, %code, "Mock relay: injecting failure");
+                Err(MockRelayError { code, message: format!("injected failure on {endpoint:?}") })
+            }
+        }
+    }
+}
+
+/// An in-process relay implementing the Constraints API, for local testing.
+///
+/// The relay keeps every message it receives in memory, and builds a block containing
+/// the constrained transactions of a slot when asked for a header. It does not check
+/// signatures nor simulate the transactions.
+#[derive(Debug)]
+pub struct MockRelay {
+    addr: SocketAddr,
+    state: Arc<RelayState>,
+    task: AbortHandle,
+}
+
+impl MockRelay {
+    /// Start the relay on the given port of the loopback interface.
+    /// Use port 0 to let the OS pick a free port.
+    pub async fn start(port: u16, chain: ChainConfig) -> eyre::Result<Self> {
+        Self::start_on(SocketAddr::from(([127, 0, 0, 1], port)), chain).await
+    }
+
+    /// Start the relay on the given address.
+    pub async fn start_on(addr: SocketAddr, chain: ChainConfig) -> eyre::Result<Self> {
+        let state = Arc::new(RelayState {
+            chain,
+            builder_key: BlsSecretKeyWrapper::random().0,
+            constraints: Default::default(),
+            delegations: Default::default(),
+            revocations: Default::default(),
+            registrations: Default::default(),
+            payloads: Default::default(),
+            faults: Default::default(),
+        });
+
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+
+        let router = make_router(state.clone());
+        let task = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, router).await {
+                warn!(?err, "Mock relay server stopped");
+            }
+        })
+        .abort_handle();
+
+        info!(%addr, "Started mock relay");
+        Ok(Self { addr, state, task })
+    }
+
+    /// Returns the URL of the relay.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("valid URL")
+    }
+
+    /// Returns the constraints received for the given slot.
+    pub fn constraints(&self, slot: u64) -> Vec<SignedConstraints> {
+        self.state.constraints.lock().get(&slot).cloned().unwrap_or_default()
+    }
+
+    /// Returns all the delegations received so far.
+    pub fn delegations(&self) -> Vec<SignedDelegation> {
+        self.state.delegations.lock().clone()
+    }
+
+    /// Returns all the revocations received so far.
+    pub fn revocations(&self) -> Vec<SignedRevocation> {
+        self.state.revocations.lock().clone()
+    }
+
+    /// Returns the number of validator registrations received so far.
+    pub fn registrations_count(&self) -> usize {
+        self.state.registrations.lock().len()
+    }
+
+    /// Returns the faults currently injected in the relay endpoints.
+    pub fn faults(&self) -> FaultInjection {
+        self.state.faults.lock().clone()
+    }
+
+    /// Replace the faults injected in the relay endpoints. Requests already
+    /// in flight are not affected.
+    pub fn set_faults(&self, faults: FaultInjection) {
+        *self.state.faults.lock() = faults;
+    }
+}
+
+impl Drop for MockRelay {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn make_router(state: Arc<RelayState>) -> Router {
+    Router::new()
+        .route(STATUS_PATH, get(status))
+        .route(REGISTER_VALIDATORS_PATH, post(register_validators))
+        .route(SUBMIT_CONSTRAINTS_PATH, post(submit_constraints))
+        .route(DELEGATE_PATH, post(delegate))
+        .route(REVOKE_PATH, post(revoke))
+        .route(GET_HEADER_PATH, get(get_header))
+        .route(GET_HEADER_WITH_PROOFS_PATH, get(get_header))
+        .route(GET_PAYLOAD_PATH, post(get_payload))
+        .with_state(state)
+}
+
+async fn status(State(state): State<Arc<RelayState>>) -> Result<StatusCode, MockRelayError> {
+    state.inject_faults(RelayEndpoint::Status).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn register_validators(
+    State(state): State<Arc<RelayState>>,
+    Json(registrations): Json<Vec<SignedValidatorRegistration>>,
+) -> Result<StatusCode, MockRelayError> {
+    state.inject_faults(RelayEndpoint::RegisterValidators).await?;
+    debug!(count = registrations.len(), "Mock relay: received validator registrations");
+    state.registrations.lock().extend(registrations);
+    Ok(StatusCode::OK)
+}
+
+async fn submit_constraints(
+    State(state): State<Arc<RelayState>>,
+    Json(constraints): Json<BatchedSignedConstraints>,
+) -> Result<StatusCode, MockRelayError> {
+    state.inject_faults(RelayEndpoint::SubmitConstraints).await?;
+
+    let mut stored = state.constraints.lock();
+    for signed_constraints in constraints {
+        let slot = signed_constraints.message.slot;
+        debug!(slot, "Mock relay: received constraints");
+        stored.entry(slot).or_default().push(signed_constraints);
+    }
+
+    Ok(StatusCode::OK)
+}
+
+async fn delegate(
+    State(state): State<Arc<RelayState>>,
+    Json(delegations): Json<Vec<SignedDelegation>>,
+) -> Result<StatusCode, MockRelayError> {
+    state.inject_faults(RelayEndpoint::Delegate).await?;
+    debug!(count = delegations.len(), "Mock relay: received delegations");
+    state.delegations.lock().extend(delegations);
+    Ok(StatusCode::OK)
+}
+
+async fn revoke(
+    State(state): State<Arc<RelayState>>,
+    Json(revocations): Json<Vec<SignedRevocation>>,
+) -> Result<StatusCode, MockRelayError> {
+    state.inject_faults(RelayEndpoint::Revoke).await?;
+    debug!(count = revocations.len(), "Mock relay: received revocations");
+    state.revocations.lock().extend(revocations);
+    Ok(StatusCode::OK)
+}
+
+/// Builds a block with the constrained transactions of the slot and returns a signed bid for it.
+async fn get_header(
+    State(state): State<Arc<RelayState>>,
+    Path(params): Path<GetHeaderParams>,
+) -> Result<Json<VersionedValue<SignedBuilderBidWithTxHashes>>, MockRelayError> {
+    state.inject_faults(RelayEndpoint::GetHeader).await?;
+
+    let constraints = state.constraints.lock().get(&params.slot).cloned().unwrap_or_default();
+
+    let mut transactions = List::<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>::default();
+    let mut transaction_hashes = Vec::new();
+    for tx in constraints.iter().flat_map(|c| c.message.transactions.iter()) {
+        let encoded = tx.envelope_encoded();
+        transactions.push(Transaction::try_from(encoded.as_ref()).map_err(MockRelayError::new)?);
+        transaction_hashes.push(*tx.hash());
+    }
+
+    let block_hash = B256::random();
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+    let payload = DenebExecutionPayload {
+        parent_hash: params.parent_hash.clone(),
+        block_number: params.slot,
+        timestamp,
+        block_hash: Hash32::try_from(block_hash.as_slice()).map_err(MockRelayError::new)?,
+        transactions,
+        ..Default::default()
+    };
+
+    let header = ExecutionPayloadHeader {
+        parent_hash: payload.parent_hash.clone(),
+        block_number: payload.block_number,
+        timestamp: payload.timestamp,
+        block_hash: payload.block_hash.clone(),
+        transactions_root: payload.transactions.hash_tree_root().map_err(MockRelayError::new)?,
+        withdrawals_root: payload.withdrawals.hash_tree_root().map_err(MockRelayError::new)?,
+        ..Default::default()
+    };
+
+    let public_key = BlsPublicKey::try_from(state.builder_key.sk_to_pk().to_bytes().as_slice())
+        .map_err(MockRelayError::new)?;
+    let message = BuilderBid {
+        header,
+        blob_kzg_commitments: Default::default(),
+        value: U256::from(MOCK_BID_VALUE),
+        public_key,
+    };
+    let signature = sign_builder_message(&state.chain, &state.builder_key, &message)
+        .map_err(MockRelayError::new)?;
+
+    state.payloads.lock().insert(
+        block_hash,
+        PayloadAndBlobs {
+            execution_payload: ExecutionPayload::Deneb(payload),
+            blobs_bundle: Default::default(),
+        },
+    );
+
+    info!(slot = params.slot, %block_hash, txs = transaction_hashes.len(), "Mock relay: returning header");
+
+    Ok(Json(VersionedValue {
+        version: Fork::Deneb,
+        data: SignedBuilderBidWithTxHashes {
+            bid: SignedBuilderBid { message, signature },
+            proofs: ConstrainedTxHashes { transaction_hashes },
+        },
+        meta: Default::default(),
+    }))
+}
+
+/// Returns the payload of a previously returned bid, given its signed blinded block.
+async fn get_payload(
+    State(state): State<Arc<RelayState>>,
+    Json(signed_block): Json<SignedBlindedBeaconBlock>,
+) -> Result<Json<GetPayloadResponse>, MockRelayError> {
+    state.inject_faults(RelayEndpoint::GetPayload).await?;
+
+    let header = &signed_block.message.body.execution_payload_header;
+    let block_hash = B256::from_slice(header.block_hash.as_ref());
+
+    let Some(payload) = state.payloads.lock().remove(&block_hash) else {
+        return Err(MockRelayError {
+            code: StatusCode::BAD_REQUEST,
+            message: format!("unknown block hash {block_hash}"),
+        });
+    };
+
+    info!(slot = signed_block.message.slot, %block_hash, "Mock relay: returning payload");
+    Ok(Json(GetPayloadResponse::Deneb(payload)))
+}
+
+/// An error returned by the [MockRelay], serialized as a builder API [ErrorResponse].
+#[derive(Debug)]
+struct MockRelayError {
+    code: StatusCode,
+    message: String,
+}
+
+impl MockRelayError {
+    fn new<E: std::fmt::Debug>(err: E) -> Self {
+        Self { code: StatusCode::INTERNAL_SERVER_ERROR, message: format!("{err:?}") }
+    }
+}
+
+impl IntoResponse for MockRelayError {
+    fn into_response(self) -> Response {
+        (self.code, Json(ErrorResponse::new(self.code, self.message))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::spec::{BuilderApi, BuilderApiError, ConstraintsApi},
+        signer::local::LocalSigner,
+        ConstraintsClient,
+    };
+
+    #[tokio::test]
+    async fn test_mock_relay_header_roundtrip() -> eyre::Result<()> {
+        let relay = MockRelay::start(0, ChainConfig::default()).await?;
+        let client = ConstraintsClient::new(relay.url());
+
+        assert_eq!(client.status().await?, StatusCode::OK);
+
+        let proposer = LocalSigner::new(BlsSecretKeyWrapper::random().0, ChainConfig::default());
+        let params = GetHeaderParams {
+            slot: 10,
+            parent_hash: Hash32::default(),
+            public_key: proposer.pubkey(),
+        };
+        let bid = client.get_header_with_proofs(params).await?;
+        assert_eq!(bid.header().block_number, 10);
+
+        let mut signed_block = SignedBlindedBeaconBlock::default();
+        signed_block.message.slot = 10;
+        signed_block.message.body.execution_payload_header = bid.header().clone();
+
+        let payload = client.get_payload(signed_block.into()).await?;
+        assert_eq!(payload.block_hash(), &bid.header().block_hash);
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_mock_relay_fault_injection() -> eyre::Result<()> {
+        let relay = MockRelay::start(0, ChainConfig::default()).await?;
+        let client = ConstraintsClient::new(relay.url());
+
+        relay.set_faults(
+            FaultInjection::default()
+                .with_fail_next(RelayEndpoint::SubmitConstraints, 1)
+                .with_failure_status(StatusCode::SERVICE_UNAVAILABLE),
+        );
+
+        let constraints = vec![SignedConstraints::default()];
+        let err = client.submit_constraints(&constraints).await.unwrap_err();
+        assert!(matches!(err, BuilderApiError::FailedSubmittingConstraints(_)));
+        assert!(relay.constraints(0).is_empty());
+
+        client.submit_constraints(&constraints).await?;
+        assert_eq!(relay.constraints(0).len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use std::{fmt::Debug, fs, path::Path};
+
+use alloy::primitives::{Bytes, B256};
+use blst::min_pk::SecretKey;
+use eyre::{ensure, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builder::signature::compute_signing_root,
+    crypto::{bls::verify_bls_signature, SignableBLS},
+    primitives::{
+        ssz::SszEncoding, BatchedSignedConstraints, BlsPublicKey, BlsSignature, DelegationMessage,
+        RevocationMessage, SignedConstraints, SignedDelegation, SignedRevocation,
+    },
+    signer::local::LocalSigner,
+    ChainConfig,
+};
+
+use super::fixtures::{seeded_bls_key, FixtureParams, ProofFixture};
+
+/// A signed message of the Constraints API, along with everything needed to
+/// reproduce its encodings and signature in another implementation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageVector<T> {
+    /// The BLS secret key of the signer.
+    pub secret_key: B256,
+    /// The signed message, in its JSON encoding.
+    pub signed: T,
+    /// The SSZ encoding of the signed message.
+    pub ssz: Bytes,
+    /// The digest of the message, i.e. the object root that is signed.
+    pub digest: B256,
+    /// The signing root, i.e. the digest mixed in with the Commit-Boost signing domain.
+    pub signing_root: B256,
+}
+
+/// Canonical test vectors of the messages exchanged with relays through the Constraints
+/// API, to check that other implementations (e.g. the Go relay) encode, hash and sign
+/// them exactly like the sidecar.
+///
+/// The validator key signs the delegation and the revocation to the delegatee key,
+/// which in turn signs the batch of constraints. The same seed always produces the
+/// same vectors, as BLS signatures are deterministic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteropVectors {
+    /// The seed from which the keys and transactions are derived.
+    pub seed: u64,
+    /// The name of the chain of the signing domain.
+    pub chain: String,
+    /// The Commit-Boost signing domain of the chain.
+    pub domain: B256,
+    /// A delegation from the validator key to the delegatee key.
+    pub delegation: MessageVector<SignedDelegation>,
+    /// A revocation of the delegation.
+    pub revocation: MessageVector<SignedRevocation>,
+    /// A batch of constraints signed by the delegatee key, as submitted to relays.
+    pub constraints: Vec<MessageVector<SignedConstraints>>,
+}
+
+impl InteropVectors {
+    /// Generate the vectors for the given seed on the given chain.
+    pub async fn generate(seed: u64, chain: ChainConfig) -> eyre::Result<Self> {
+        let validator_sk = seeded_bls_key(seed, b"validator")?;
+        let validator_key = B256::from(validator_sk.to_bytes());
+        let validator = LocalSigner::new(validator_sk, chain);
+
+        // The proof fixture constraints are signed by the key derived from "bls"
+        let params = FixtureParams { seed, ..Default::default() };
+        let fixture = ProofFixture::generate(params, chain).await?;
+        let delegatee_key = B256::from(seeded_bls_key(seed, b"bls")?.to_bytes());
+
+        let message = DelegationMessage::new(validator.pubkey(), fixture.pubkey.clone());
+        let signature = sign(&validator, message.digest())?;
+        let digest = message.digest();
+        let delegation = MessageVector::new(
+            validator_key,
+            SignedDelegation { message, signature },
+            digest,
+            &chain,
+        )?;
+
+        let message = RevocationMessage::new(validator.pubkey(), fixture.pubkey.clone());
+        let signature = sign(&validator, message.digest())?;
+        let digest = message.digest();
+        let revocation = MessageVector::new(
+            validator_key,
+            SignedRevocation { message, signature },
+            digest,
+            &chain,
+        )?;
+
+        let constraints = fixture
+            .constraints
+            .into_iter()
+            .map(|signed| {
+                let digest = signed.message.digest();
+                MessageVector::new(delegatee_key, signed, digest, &chain)
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            seed,
+            chain: chain.name().to_string(),
+            domain: B256::from(chain.commit_boost_domain()),
+            delegation,
+            revocation,
+            constraints,
+        })
+    }
+
+    /// Returns the batch of signed constraints of the vectors.
+    pub fn batch(&self) -> BatchedSignedConstraints {
+        self.constraints.iter().map(|c| c.signed.clone()).collect()
+    }
+
+    /// Check the vectors against the sidecar implementation on the given chain:
+    /// the SSZ encodings, digests, signing roots and signatures must all match.
+    pub fn verify(&self, chain: &ChainConfig) -> eyre::Result<()> {
+        let domain = B256::from(chain.commit_boost_domain());
+        ensure!(self.domain == domain, "domain mismatch: expected {domain}, got {}", self.domain);
+
+        let delegation = &self.delegation.signed;
+        self.delegation
+            .verify(
+                &delegation.message.validator_pubkey,
+                delegation.message.digest(),
+                delegation.signature.as_ref(),
+                domain,
+            )
+            .map_err(|e| eyre!("delegation: {e}"))?;
+
+        let revocation = &self.revocation.signed;
+        self.revocation
+            .verify(
+                &revocation.message.validator_pubkey,
+                revocation.message.digest(),
+                revocation.signature.as_ref(),
+                domain,
+            )
+            .map_err(|e| eyre!("revocation: {e}"))?;
+
+        for (i, vector) in self.constraints.iter().enumerate() {
+            let constraints = &vector.signed;
+            vector
+                .verify(
+                    &constraints.message.pubkey,
+                    constraints.message.digest(),
+                    constraints.signature.as_ref(),
+                    domain,
+                )
+                .map_err(|e| eyre!("constraints {i}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the vectors from a `vectors.json` file.
+    pub fn from_file(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Write the vectors to the given directory, as `vectors.json` and SSZ files:
+    /// - `delegation.ssz` and `revocation.ssz`: the signed delegation and revocation
+    /// - `constraints_<i>.ssz`: each signed constraints message of the batch
+    /// - `batch.json`: the batch of signed constraints, as submitted to relays
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> eyre::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        fs::write(dir.join("vectors.json"), serde_json::to_string_pretty(self)?)?;
+        fs::write(dir.join("batch.json"), serde_json::to_string_pretty(&self.batch())?)?;
+        fs::write(dir.join("delegation.ssz"), &self.delegation.ssz)?;
+        fs::write(dir.join("revocation.ssz"), &self.revocation.ssz)?;
+        for (i, constraints) in self.constraints.iter().enumerate() {
+            fs::write(dir.join(format!("constraints_{i}.ssz")), &constraints.ssz)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: SszEncoding + PartialEq + Debug> MessageVector<T> {
+    fn new(
+        secret_key: B256,
+        signed: T,
+        digest: [u8; 32],
+        chain: &ChainConfig,
+    ) -> eyre::Result<Self> {
+        Ok(Self {
+            secret_key,
+            ssz: signed.to_ssz_bytes()?.into(),
+            digest: B256::from(digest),
+            signing_root: B256::from(compute_signing_root(digest, chain.commit_boost_domain())),
+            signed,
+        })
+    }
+
+    /// Check the vector against the given signer, message digest and signature.
+    fn verify(
+        &self,
+        signer: &BlsPublicKey,
+        digest: [u8; 32],
+        signature: &[u8],
+        domain: B256,
+    ) -> eyre::Result<()> {
+        let Ok(secret_key) = SecretKey::from_bytes(self.secret_key.as_slice()) else {
+            eyre::bail!("invalid secret key");
+        };
+        ensure!(
+            secret_key.sk_to_pk().to_bytes().as_slice() == signer.as_ref(),
+            "the secret key does not match the signer {signer:?}"
+        );
+
+        let decoded = T::from_ssz_bytes(&self.ssz)?;
+        ensure!(decoded == self.signed, "SSZ decodes to {decoded:?}");
+        ensure!(self.signed.to_ssz_bytes()? == self.ssz.as_ref(), "SSZ encoding mismatch");
+
+        ensure!(self.digest == digest, "digest mismatch: expected {}", B256::from(digest));
+        let signing_root = B256::from(compute_signing_root(digest, domain.0));
+        ensure!(
+            self.signing_root == signing_root,
+            "signing root mismatch: expected {signing_root}"
+        );
+
+        ensure!(verify_bls_signature(signer, signing_root.0, signature), "invalid signature");
+        Ok(())
+    }
+}
+
+/// Sign the digest with the Commit-Boost domain, as a consensus signature.
+fn sign(signer: &LocalSigner, digest: [u8; 32]) -> eyre::Result<BlsSignature> {
+    let signature = signer.sign_commit_boost_root(digest)?;
+    Ok(BlsSignature::try_from(signature.as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_interop_vectors_roundtrip() -> eyre::Result<()> {
+        let chain = ChainConfig::holesky();
+        let vectors = InteropVectors::generate(0, chain).await?;
+        assert_eq!(vectors, InteropVectors::generate(0, chain).await?);
+        vectors.verify(&chain)?;
+
+        // The vectors are consumed from their JSON encoding
+        let json = serde_json::to_string(&vectors)?;
+        let parsed: InteropVectors = serde_json::from_str(&json)?;
+        parsed.verify(&chain)?;
+
+        // Signatures are bound to the signing domain of the chain
+        assert!(vectors.verify(&ChainConfig::mainnet()).is_err());
+
+        let mut tampered = vectors.clone();
+        tampered.constraints[0].signed.message.slot += 1;
+        assert!(tampered.verify(&chain).is_err());
+
+        Ok(())
+    }
+}
@@ -1,10 +1,18 @@
 use std::{
-    collections::HashSet,
-    fmt,
-    time::{Duration, Instant},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    future::Future,
+    num::NonZero,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use alloy::{rpc::types::beacon::events::HeadEvent, signers::local::PrivateKeySigner};
+use alloy::{
+    primitives::{Address, Bytes, B256},
+    rpc::types::beacon::events::HeadEvent,
+    signers::local::PrivateKeySigner,
+};
 use beacon_api_client::mainnet::Client as BeaconClient;
 use ethereum_consensus::{
     clock::{self, SlotStream, SystemTimeProvider},
@@ -12,29 +20,72 @@ use ethereum_consensus::{
     phase0::mainnet::SLOTS_PER_EPOCH,
 };
 use futures::StreamExt;
-use tokio::sync::mpsc;
+use reqwest::Url;
+use tokio::{
+    sync::{broadcast, mpsc, oneshot, watch},
+    task::AbortHandle,
+    time::{Interval, MissedTickBehavior},
+};
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    builder::payload_fetcher::LocalPayloadFetcher,
+    builder::{
+        payload_fetcher::LocalPayloadFetcher,
+        template::{TemplateReader, TemplateSnapshotRequest},
+    },
     chain_io::manager::BoltManager,
+    client::{
+        auth::RequestSigner, capabilities::CAPABILITIES_REFRESH_INTERVAL,
+        delegation_manager::DelegationManager, delegations::RECONCILE_INTERVAL,
+        fee_recipients::FeeRecipientGuard, multiplexer::RelayMultiplexer,
+        registrations::RegistrationStore, submissions::SubmissionLog, timeouts::RelayTimeouts,
+    },
     commitments::{
-        server::{CommitmentsApiServer, Event as CommitmentEvent},
-        spec::Error as CommitmentError,
+        server::{
+            CommitmentsApiServer, EncryptedEvent, Event as CommitmentEvent, ReservationEvent,
+            SimulationEvent,
+        },
+        spec::{Error as CommitmentError, InclusionSimulation},
+        whitelist::Whitelist,
     },
-    crypto::{bls::cl_public_key_to_arr, SignableBLS, SignerECDSA},
+    config::{
+        reload::{ConfigChange, ConfigReloader, ReloadRequest},
+        validation::validate_limits,
+        vouchers::VOUCHER_SETTLEMENT_PREFIX,
+        ConfigErrors, LatencyBudgetOpts, LimitsOpts,
+    },
+    crypto::{SignableBLS, SignerECDSA},
+    failover::{start_failover_server, Failover},
     primitives::{
-        read_signed_delegations_from_file, CommitmentRequest, ConstraintsMessage,
-        FetchPayloadRequest, SignedConstraints, TransactionExt,
+        attestation::CommitmentKeyMessage, read_delegations_file, Availability, BlsSignature,
+        CommitmentRequest, ConstraintsMessage, DecryptionKeyReveal, DelegationMessage,
+        EncryptedCommitment, EncryptedInclusionRequest, FetchPayloadRequest, InclusionRequest,
+        RevocationMessage, SidecarIdentity, SignedAvailability, SignedCommitment,
+        SignedCommitmentKey, SignedConstraints, SignedDelegation, SignedEncryptedCommitment,
+        SignedReservation, SignedRevocation, TimeLockPuzzle, TransactionExt, TxOutcome,
     },
-    signer::{keystore::KeystoreSigner, local::LocalSigner},
+    signer::{keystore::KeystoreSigner, local::LocalSigner, web3signer::Web3Signer},
     start_builder_proxy_server,
-    state::{fetcher::StateFetcher, ConsensusState, ExecutionState, HeadTracker, StateClient},
-    telemetry::ApiMetrics,
-    BuilderProxyConfig, CommitBoostSigner, ConstraintsApi, ConstraintsClient, LocalBuilder, Opts,
-    SignerBLS,
+    state::{
+        fetcher::StateFetcher,
+        journal::check_conflicts,
+        validation::{MempoolValidator, TxPolicyValidator, VoucherValidator},
+        AvailabilityBoard, BeaconEvent, CommitmentJournal, CommitmentKeyHistory, CommitmentStatus,
+        CommitmentStatuses, CompactionReport, ConsensusState, DeadlineEncrypted, DeadlineSchedule,
+        DelegationSchedule, DelegationTargeting, EncryptedPool, EncryptedRequestError,
+        ExecutionState, HeadTracker, JournalRecord, LoadSheddingPolicy, PendingEncrypted,
+        PendingSlot, RetentionPolicy, RevealedEncrypted, Screening, ShedReason, SlotAssignment,
+        SlotScoped, StateClient, TobAuction, TobBid, TxPolicy, ValidationError, ValidationPipeline,
+        VoucherSettlement,
+    },
+    telemetry::{ApiMetrics, ProposalStage, StageTimer},
+    BuilderProxyConfig, CommitBoostSigner, ConstraintsApi, ConstraintsClient, DelegationTracker,
+    LocalBuilder, Opts, RelayThrottle, SidecarHealth, SignerBLS,
 };
 
+/// The channel on which the outcome of a commitment request is sent.
+type CommitmentResponder = oneshot::Sender<Result<SignedCommitment, CommitmentError>>;
+
 /// The driver for the sidecar, responsible for managing the main event loop.
 ///
 /// The reponsibilities of the driver include:
@@ -47,6 +98,8 @@ use crate::{
 pub struct SidecarDriver<C, ECDSA> {
     /// Head tracker for monitoring the beacon chain clock
     head_tracker: HeadTracker,
+    /// Channel for receiving the typed events of the beacon node
+    beacon_events_rx: broadcast::Receiver<BeaconEvent>,
     /// Execution state for tracking the current head and block templates
     execution: ExecutionState<C>,
     /// Consensus state for tracking the current slot and validator indexes
@@ -59,12 +112,111 @@ pub struct SidecarDriver<C, ECDSA> {
     local_builder: LocalBuilder,
     /// Client for interacting with the constraints service
     constraints_client: ConstraintsClient,
+    /// The Constraints API that the constraints are submitted to, which is the
+    /// constraints client unless replaced with [SidecarDriver::with_constraints_api]
+    constraints_api: Arc<dyn ConstraintsApi + Send + Sync>,
     /// Channel for receiving incoming API events
     api_events_rx: mpsc::Receiver<CommitmentEvent>,
     /// Channel for receiving requests to fetch a local payload
     payload_requests_rx: mpsc::Receiver<FetchPayloadRequest>,
+    /// Channel for receiving blockspace reservation requests
+    reservations_rx: mpsc::Receiver<ReservationEvent>,
+    /// Channel for receiving encrypted inclusion requests and their decryption keys
+    encrypted_rx: mpsc::Receiver<EncryptedEvent>,
+    /// Encrypted requests committed to, waiting to be decrypted at their commitment deadline
+    encrypted_pool: EncryptedPool,
+    /// The max squarings of the time-lock puzzles of encrypted requests, if they are required
+    max_timelock_squarings: Option<u64>,
+    /// Channel for sending the keys of the time-lock puzzles solved in the background
+    timelock_solutions_tx: mpsc::Sender<DecryptionKeyReveal>,
+    /// Channel for receiving the keys of the time-lock puzzles solved in the background
+    timelock_solutions_rx: mpsc::Receiver<DecryptionKeyReveal>,
+    /// Channel for receiving inclusion requests to simulate
+    simulations_rx: mpsc::Receiver<SimulationEvent>,
     /// Stream of slots made from the consensus clock
     slot_stream: SlotStream<SystemTimeProvider>,
+    /// Latency budgets for the stages of the proposal-critical path
+    latency_budgets: LatencyBudgetOpts,
+    /// Shutdown signal shared with the servers started by the driver
+    shutdown: Arc<watch::Sender<bool>>,
+    /// Handle to the builder proxy server task, aborted on shutdown
+    builder_proxy_task: AbortHandle,
+    /// Handle to the delegation reconciliation task, if there are delegations to reconcile
+    reconciler_task: Option<AbortHandle>,
+    /// Handle to the task refreshing the capabilities of the relays, aborted on shutdown
+    capabilities_task: AbortHandle,
+    /// Channel for receiving configuration reload requests
+    reload_rx: mpsc::Receiver<ReloadRequest>,
+    /// Sender of configuration reload requests, handed out to [ConfigReloader]s
+    reload_tx: mpsc::Sender<ReloadRequest>,
+    /// Signers allowed to request commitments, shared with the commitments API that enforces it
+    whitelist: Whitelist,
+    /// Channel for receiving requests for block template snapshots
+    snapshot_rx: mpsc::Receiver<TemplateSnapshotRequest>,
+    /// Sender of template snapshot requests, handed out to [TemplateReader]s
+    snapshot_tx: mpsc::Sender<TemplateSnapshotRequest>,
+    /// Interval of the scans of the mempool for competing transactions, if enabled
+    mempool_scan: Option<Interval>,
+    /// Interval of the settlements of the payment vouchers, if vouchers are required
+    voucher_settlement: Option<Interval>,
+    /// Directory where the batches of vouchers to settle are written
+    voucher_settlement_dir: PathBuf,
+    /// Retention policy of the voucher settlement batches, if enabled
+    voucher_retention: Option<RetentionPolicy>,
+    /// Journal of the commitments made, if enabled
+    journal: Option<CommitmentJournal>,
+    /// Policy for shedding low-value work under load ahead of a proposal
+    load_shedding: LoadSheddingPolicy,
+    /// Auctions of the top-of-block bundle of the upcoming slots, if enabled
+    tob_auction: Option<TobAuction<CommitmentResponder>>,
+    /// Position in the block of the top-of-block bundle, if not at its top
+    tob_bundle_index: Option<u64>,
+    /// Policy deciding which delegatee receives the authority over each proposal slot
+    delegation_targeting: DelegationTargeting,
+    /// Assignments of the upcoming proposal slots to their delegatees, shared with the API
+    delegation_schedule: DelegationSchedule,
+    /// Number of slots suggested to retarget the requests rejected for their target slot
+    retarget_suggestions: usize,
+    /// Availability attestations of the upcoming proposal slots, shared with the API
+    availability_board: AvailabilityBoard,
+    /// Statuses of the commitments made, shared with the API
+    commitment_statuses: CommitmentStatuses,
+    /// Readiness and liveness of the driver, shared with the API and the service manager
+    health: SidecarHealth,
+    /// Number of commitments of each signer in the upcoming slots
+    pending_commitments: SlotScoped<HashMap<Address, usize>>,
+    /// Max number of commitments of a signer per slot, if limited
+    max_pending_commitments_per_signer: Option<NonZero<usize>>,
+    /// Number of slots ahead of a proposal slot at which its availability is attested
+    availability_lead_slots: u64,
+    /// URL to POST the availability attestations to, if any
+    availability_webhook_url: Option<Url>,
+    /// The side of the sidecar in an active/standby pair, if paired
+    failover: Option<Failover>,
+    /// Handle to the task sending heartbeats to the standby or serving the primary, if paired
+    failover_task: Option<AbortHandle>,
+    /// Handle to the task POSTing the changes of status of the commitments, if enabled
+    commitment_webhook_task: Option<AbortHandle>,
+}
+
+/// The handles of a [SidecarDriver] served by the operator APIs, such as the admin API,
+/// the configuration reloaders and the service manager notifications.
+#[derive(Debug, Clone)]
+pub struct OperatorHandles {
+    /// Handle to reload the operational parameters of the driver.
+    pub reloader: ConfigReloader,
+    /// Handle to read snapshots of the pending block templates.
+    pub templates: TemplateReader,
+    /// Tracker of the delegations acknowledged by each relay.
+    pub delegations: DelegationTracker,
+    /// Tracker of the rate limits of each relay.
+    pub relays: RelayThrottle,
+    /// Log of the constraints submitted to each relay.
+    pub submissions: SubmissionLog,
+    /// Checks of the fee recipients of the registrations and bids of each relay.
+    pub fee_recipients: FeeRecipientGuard,
+    /// Health of the driver, to signal its readiness to the service manager.
+    pub health: SidecarHealth,
 }
 
 impl fmt::Debug for SidecarDriver<StateClient, PrivateKeySigner> {
@@ -79,6 +231,8 @@ impl fmt::Debug for SidecarDriver<StateClient, PrivateKeySigner> {
             .field("constraints_client", &self.constraints_client)
             .field("api_events_rx", &self.api_events_rx)
             .field("payload_requests_rx", &self.payload_requests_rx)
+            .field("latency_budgets", &self.latency_budgets)
+            .field("whitelist", &self.whitelist.signers())
             .finish()
     }
 }
@@ -90,14 +244,8 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
         let state_client = StateClient::new(opts.execution_api_url.clone());
 
         // Constraints are signed with a BLS private key
-        let constraint_signer = SignerBLS::Local(LocalSigner::new(
-            opts.constraint_signing
-                .constraint_private_key
-                .clone()
-                .expect("local constraint signing key")
-                .0,
-            opts.chain,
-        ));
+        let constraint_signer =
+            SignerBLS::Local(LocalSigner::from_opts(&opts.constraint_signing, opts.chain)?);
 
         // Commitment responses are signed with a regular Ethereum wallet private key.
         let commitment_key = opts.commitment_private_key.0.clone();
@@ -113,20 +261,7 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
         // The default state client simply uses the execution API URL to fetch state updates.
         let state_client = StateClient::new(opts.execution_api_url.clone());
 
-        let keystore = if let Some(psw) = opts.constraint_signing.keystore_password.as_ref() {
-            KeystoreSigner::from_password(
-                opts.constraint_signing.keystore_path.as_ref().expect("keystore path"),
-                psw.as_ref(),
-                opts.chain,
-            )?
-        } else {
-            KeystoreSigner::from_secrets_directory(
-                opts.constraint_signing.keystore_path.as_ref().expect("keystore path"),
-                opts.constraint_signing.keystore_secrets_path.as_ref().expect("keystore secrets"),
-                opts.chain,
-            )?
-        };
-
+        let keystore = KeystoreSigner::from_opts(&opts.constraint_signing, opts.chain)?;
         let keystore_signer = SignerBLS::Keystore(keystore);
 
         // Commitment responses are signed with a regular Ethereum wallet private key.
@@ -137,16 +272,30 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
     }
 }
 
+impl SidecarDriver<StateClient, PrivateKeySigner> {
+    /// Create a new sidecar driver with the given [Opts] and Web3Signer remote signer.
+    pub async fn with_web3signer(opts: &Opts) -> eyre::Result<Self> {
+        // The default state client simply uses the execution API URL to fetch state updates.
+        let state_client = StateClient::new(opts.execution_api_url.clone());
+
+        let web3signer = Web3Signer::from_opts(&opts.constraint_signing, opts.chain).await?;
+        let web3signer = SignerBLS::Web3Signer(web3signer);
+
+        // Commitment responses are signed with a regular Ethereum wallet private key.
+        let commitment_key = opts.commitment_private_key.0.clone();
+        let commitment_signer = PrivateKeySigner::from_signing_key(commitment_key);
+
+        Self::from_components(opts, web3signer, commitment_signer, state_client).await
+    }
+}
+
 impl SidecarDriver<StateClient, CommitBoostSigner> {
     /// Create a new sidecar driver with the given [Opts] and commit-boost signer.
     pub async fn with_commit_boost_signer(opts: &Opts) -> eyre::Result<Self> {
         // The default state client simply uses the execution API URL to fetch state updates.
         let state_client = StateClient::new(opts.execution_api_url.clone());
 
-        let commit_boost_signer = CommitBoostSigner::new(
-            opts.constraint_signing.commit_boost_signer_url.clone().expect("CommitBoost URL"),
-            &opts.constraint_signing.commit_boost_jwt_hex.clone().expect("CommitBoost JWT"),
-        )?;
+        let commit_boost_signer = CommitBoostSigner::from_opts(&opts.constraint_signing)?;
 
         let cb_bls_signer = SignerBLS::CommitBoost(commit_boost_signer.clone());
 
@@ -162,13 +311,40 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         commitment_signer: ECDSA,
         fetcher: C,
     ) -> eyre::Result<Self> {
-        let mut constraints_client = ConstraintsClient::new(opts.constraints_api_url.clone());
+        let mut constraints_client = ConstraintsClient::new(opts.constraints_api_url.clone())
+            .with_timeouts(RelayTimeouts::new(opts.relay_timeouts))
+            .with_fee_recipient_guard(FeeRecipientGuard::new(opts.fee_recipients.clone()));
+        if opts.sign_constraints_requests {
+            let signer = PrivateKeySigner::from_signing_key(opts.commitment_private_key.0.clone());
+            constraints_client = constraints_client.with_request_signer(RequestSigner::new(signer));
+        }
+        if let Some(path) = opts.registrations_path.as_ref() {
+            constraints_client =
+                constraints_client.with_registration_store(RegistrationStore::new(path));
+        }
+        if let Some(path) = opts.delegations_state_path.as_ref() {
+            let manager = DelegationManager::open(path).map_err(|err| {
+                eyre::eyre!("Failed to open the delegations state {}: {err}", path.display())
+            })?;
+            constraints_client = constraints_client.with_delegation_manager(manager);
+        }
+        if let Some(path) = opts.submission_log_path.as_ref() {
+            let log = SubmissionLog::open(path).map_err(|err| {
+                eyre::eyre!("Failed to open the submission log {}: {err}", path.display())
+            })?;
+            constraints_client = constraints_client.with_submission_log(log);
+        }
 
         // read the delegations from disk if they exist and add them to the constraints client.
         let validator_public_keys = if let Some(delegations_file_path) =
             opts.constraint_signing.delegations_path.as_ref()
         {
-            let delegations = read_signed_delegations_from_file(delegations_file_path)?;
+            let password = opts.constraint_signing.delegations_password.as_ref();
+            let delegations = read_delegations_file(
+                delegations_file_path,
+                &opts.chain,
+                password.map(|p| p.as_ref()),
+            )?;
             let validator_public_keys =
                 delegations.iter().map(|d| d.message.validator_pubkey.clone()).collect::<Vec<_>>();
             constraints_client.add_delegations(delegations);
@@ -189,7 +365,7 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
                 "Verifying validators and operator keys with Bolt Manager, this may take a while..."
             );
             bolt_manager
-                .verify_validator_pubkeys(validator_public_keys, commitment_signer_pubkey)
+                .verify_validator_pubkeys(validator_public_keys.clone(), commitment_signer_pubkey)
                 .await?;
             info!(
                 validator_public_keys_len,
@@ -204,54 +380,437 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         }
 
         let beacon_client = BeaconClient::new(opts.beacon_api_url.clone());
-        let execution = ExecutionState::new(fetcher, opts.limits).await?;
+        let mut execution = ExecutionState::new(fetcher, opts.limits)
+            .await?
+            .with_reservations(opts.reservations)
+            .with_vouchers(opts.vouchers.clone(), commitment_signer.public_key())
+            .with_pricing(opts.pricing);
+
+        // refuse the transactions that the operator doesn't commit to, if restricted
+        if opts.tx_policy.enabled() {
+            let pipeline = execution.validation_pipeline().clone().with_validator_before_state(
+                TxPolicyValidator::new(TxPolicy::new(&opts.tx_policy)),
+            );
+            execution.set_validation_pipeline(pipeline);
+        }
+
+        // screen the addresses of the requests for compliance, if configured
+        execution.set_screening(Screening::from_opts(&opts.screening)?);
+
+        // reject the commitments building on nonces contested in the mempool, if scanned
+        let mempool_scan = opts.mempool_scan_interval_ms.map(|interval_ms| {
+            let pipeline = execution
+                .validation_pipeline()
+                .clone()
+                .with_validator_before_state(MempoolValidator);
+            execution.set_validation_pipeline(pipeline);
+
+            let mut interval = tokio::time::interval(Duration::from_millis(interval_ms.get()));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+
+        // require the commitment requests to be paid with vouchers, if priced
+        let voucher_settlement = opts.vouchers.enabled().then(|| {
+            let pipeline = execution
+                .validation_pipeline()
+                .clone()
+                .with_validator_before_state(VoucherValidator);
+            execution.set_validation_pipeline(pipeline);
+
+            let period = Duration::from_secs(opts.vouchers.voucher_settlement_interval_secs);
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
 
         let genesis_time = beacon_client.get_genesis_details().await?.genesis_time;
-        let slot_stream =
-            clock::from_system_time(genesis_time, opts.chain.slot_time(), SLOTS_PER_EPOCH)
-                .into_stream();
+        let slot_clock =
+            clock::from_system_time(genesis_time, opts.chain.slot_time(), SLOTS_PER_EPOCH);
+        let current_slot = slot_clock.current_slot().unwrap_or_default();
+        let slot_stream = slot_clock.into_stream();
+
+        // read the journal of the commitments, refusing to start if honoring them is unsafe
+        let (journal, journal_pending) = match opts.journal_path.as_ref() {
+            Some(path) => {
+                let (journal, mut pending) = CommitmentJournal::open(path)?;
+                check_conflicts(&mut pending, current_slot, commitment_signer.public_key())
+                    .map_err(|err| {
+                        eyre::eyre!("Refusing to start with journal {}: {err}", path.display())
+                    })?;
+                (Some(journal), pending)
+            }
+            None => (None, BTreeMap::new()),
+        };
+
+        // record the commitment key, so that the verifiers of the commitments signed with the
+        // previous keys can still resolve them after a rotation
+        let mut commitment_keys = match opts.commitment_keys_path.as_ref() {
+            Some(path) => CommitmentKeyHistory::open(path).map_err(|err| {
+                eyre::eyre!("Failed to open the commitment keys {}: {err}", path.display())
+            })?,
+            None => CommitmentKeyHistory::default(),
+        };
+        let public_key = opts.commitment_private_key.0.verifying_key().to_sec1_bytes();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if commitment_keys.activate(
+            commitment_signer.public_key(),
+            Bytes::from(public_key.to_vec()),
+            current_slot,
+            now,
+        )? {
+            info!(
+                address = %commitment_signer.public_key(),
+                slot = current_slot,
+                "Rotated the commitment key"
+            );
+        }
 
         let local_builder = LocalBuilder::new(opts, beacon_client.clone(), genesis_time);
         let head_tracker = HeadTracker::start(beacon_client.clone());
+        let beacon_events_rx = head_tracker.subscribe_events();
 
+        let deadline_schedule = DeadlineSchedule::new(
+            genesis_time,
+            Duration::from_secs(opts.chain.slot_time()),
+            opts.chain.commitment_deadline(),
+        );
         let consensus = ConsensusState::new(
             beacon_client,
             opts.validator_indexes.clone(),
             opts.chain.commitment_deadline(),
             opts.chain.enable_unsafe_lookahead,
-        );
+        )
+        .with_deadline_schedule(deadline_schedule);
 
         let (payload_requests_tx, payload_requests_rx) = mpsc::channel(16);
+        let mut relays = RelayMultiplexer::new(
+            constraints_client.clone(),
+            opts.additional_constraints_api_urls.iter().cloned(),
+        );
+        if let Some(url) = opts.self_builder_url.clone() {
+            relays = relays.with_self_builder(url);
+        }
         let builder_proxy_cfg = BuilderProxyConfig {
-            constraints_client: constraints_client.clone(),
+            relays: relays.clone(),
             server_port: opts.constraints_proxy_port,
+            latency_budgets: opts.latency_budgets,
         };
 
         // start the builder api proxy server
-        tokio::spawn(async move {
+        let builder_proxy_task = tokio::spawn(async move {
             let payload_fetcher = LocalPayloadFetcher::new(payload_requests_tx);
             if let Err(err) = start_builder_proxy_server(payload_fetcher, builder_proxy_cfg).await {
                 error!(?err, "Builder API proxy server failed");
             }
-        });
+        })
+        .abort_handle();
+
+        // replay the saved registrations and the delegations right away, as the relay may have
+        // lost them while the sidecar was down
+        for relay in relays.relays() {
+            tokio::spawn(replay_registrations(relay.clone()));
+        }
+
+        // re-send the delegations that the relay has not acknowledged yet, periodically
+        let reconciler_task =
+            opts.constraint_signing.delegations_path.as_ref().map(|_| {
+                tokio::spawn(reconcile_delegations(relays.relays().to_vec())).abort_handle()
+            });
 
-        // start the commitments api server
+        // fetch the capabilities of the relays, which the commitments offered depend on
+        let capabilities_task =
+            tokio::spawn(refresh_capabilities(relays.relays().to_vec())).abort_handle();
+
+        // start the commitments api server, which stops either on CTRL+C or on driver shutdown
+        let shutdown = Arc::new(watch::channel(false).0);
+        let api_shutdown = shutdown_signal(&shutdown);
+
+        // mirror the constraints to the standby, or stand by to take over from the primary
+        let failover = Failover::from_opts(&opts.failover)?;
+        let failover_task = failover.clone().map(|failover| match failover {
+            Failover::Primary(primary) => {
+                let interval = opts.failover.heartbeat_interval();
+                tokio::spawn(primary.send_heartbeats(interval)).abort_handle()
+            }
+            Failover::Standby(standby) => {
+                let port = opts.failover.failover_port;
+                let failover_shutdown = shutdown_signal(&shutdown);
+                tokio::spawn(async move {
+                    if let Err(err) = start_failover_server(standby, port, failover_shutdown).await
+                    {
+                        error!(?err, "Failover server failed");
+                    }
+                })
+                .abort_handle()
+            }
+        });
         let api_addr = format!("0.0.0.0:{}", opts.port);
         let (api_events_tx, api_events_rx) = mpsc::channel(1024);
-        CommitmentsApiServer::new(api_addr).run(api_events_tx).await;
+        let (reservations_tx, reservations_rx) = mpsc::channel(64);
+        let (encrypted_tx, encrypted_rx) = mpsc::channel(64);
+        let (timelock_solutions_tx, timelock_solutions_rx) = mpsc::channel(64);
+        let (simulations_tx, simulations_rx) = mpsc::channel(64);
+        let identity = attest_commitment_key(
+            &validator_public_keys,
+            &constraint_signer,
+            &constraints_client,
+            commitment_signer.public_key(),
+            opts.chain.chain_id(),
+        )
+        .await?;
+        let delegation_targeting = DelegationTargeting::from_opts(&opts.delegation_targeting)?;
+        let delegation_schedule = DelegationSchedule::default();
+        let availability_board = AvailabilityBoard::default();
+        let commitment_statuses = CommitmentStatuses::new(genesis_time, opts.chain.slot_time());
+        let commitment_webhook_task = opts.commitment_webhook_url.clone().map(|url| {
+            let updates = commitment_statuses.subscribe();
+            tokio::spawn(forward_commitment_statuses(url, updates)).abort_handle()
+        });
+        let health = SidecarHealth::default();
+        let whitelist = Whitelist::new(opts.whitelist.signers()?);
+        let mut api_server = CommitmentsApiServer::new(&api_addr)
+            .with_chain_id(opts.chain.chain_id())
+            .with_identity(identity)
+            .with_simulations(simulations_tx)
+            .with_delegation_schedule(delegation_schedule.clone())
+            .with_availability_board(availability_board.clone())
+            .with_commitment_statuses(commitment_statuses.clone())
+            .with_rate_limits(opts.rate_limits)
+            .with_whitelist(whitelist.clone())
+            .with_health(health.clone())
+            .with_commitment_keys(commitment_keys.keys())
+            .with_response_signer(PrivateKeySigner::from_signing_key(
+                opts.commitment_private_key.0.clone(),
+            ));
+        if opts.reservations.enabled() {
+            api_server = api_server.with_reservations(reservations_tx);
+        }
+        if opts.client_revealed_encryption || opts.timelock_encryption {
+            api_server = api_server.with_encrypted_requests(encrypted_tx);
+        }
+        if let Some(network_router) = opts.network_router.clone() {
+            api_server = api_server.with_network_router(network_router);
+        }
+        api_server
+            .with_shutdown(&api_addr, async move {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = api_shutdown => {}
+                }
+            })
+            .run(api_events_tx)
+            .await;
+
+        let (reload_tx, reload_rx) = mpsc::channel(8);
+        let (snapshot_tx, snapshot_rx) = mpsc::channel(8);
 
-        Ok(SidecarDriver {
+        let mut driver = SidecarDriver {
             head_tracker,
+            beacon_events_rx,
             execution,
             consensus,
             constraint_signer,
             commitment_signer,
             local_builder,
+            constraints_api: Arc::new(relays),
             constraints_client,
             api_events_rx,
             payload_requests_rx,
+            reservations_rx,
+            encrypted_rx,
+            encrypted_pool: EncryptedPool::new(opts.max_withheld_decryption_keys),
+            max_timelock_squarings: opts.timelock_encryption.then_some(opts.max_timelock_squarings),
+            timelock_solutions_tx,
+            timelock_solutions_rx,
+            simulations_rx,
             slot_stream,
-        })
+            latency_budgets: opts.latency_budgets,
+            shutdown,
+            builder_proxy_task,
+            reconciler_task,
+            capabilities_task,
+            reload_rx,
+            reload_tx,
+            whitelist,
+            snapshot_rx,
+            snapshot_tx,
+            mempool_scan,
+            voucher_settlement,
+            voucher_settlement_dir: opts.vouchers.voucher_settlement_dir.clone(),
+            voucher_retention: opts.vouchers.retention_policy(),
+            journal,
+            load_shedding: LoadSheddingPolicy::new(opts.load_shedding),
+            tob_auction: opts
+                .tob_auction_cutoff_ms
+                .map(|cutoff| TobAuction::new(Duration::from_millis(cutoff.get()))),
+            tob_bundle_index: opts.tob_bundle_index,
+            delegation_targeting,
+            delegation_schedule,
+            retarget_suggestions: opts.retarget_suggestions,
+            availability_board,
+            commitment_statuses,
+            health,
+            pending_commitments: SlotScoped::new(),
+            max_pending_commitments_per_signer: opts.rate_limits.max_pending_commitments_per_signer,
+            availability_lead_slots: opts.availability_lead_slots,
+            availability_webhook_url: opts.availability_webhook_url.clone(),
+            failover,
+            failover_task,
+            commitment_webhook_task,
+        };
+        driver.reconcile_journal(journal_pending, current_slot).await?;
+
+        Ok(driver)
+    }
+
+    /// Reconcile the pending slots of the journal against the chain.
+    ///
+    /// The commitments of the slots that passed are verified against the receipts of the
+    /// execution client and resolved. The constraints of the upcoming slots are added back
+    /// to their block templates, and the ones of the current and next slot that the relay
+    /// did not acknowledge are re-submitted right away, as their commitment deadline may
+    /// have passed during the restart.
+    async fn reconcile_journal(
+        &mut self,
+        pending: BTreeMap<u64, PendingSlot>,
+        current_slot: u64,
+    ) -> eyre::Result<()> {
+        let Some(journal) = self.journal.clone() else {
+            return Ok(());
+        };
+
+        for (slot, entry) in pending {
+            if slot < current_slot {
+                let hashes = entry.transaction_hashes();
+                let found = self.execution.included_transactions(&hashes).await?;
+                let (included, missed): (Vec<_>, Vec<_>) =
+                    hashes.into_iter().partition(|hash| found.contains(hash));
+
+                if missed.is_empty() {
+                    info!(slot, included = included.len(), "Journaled commitments were included");
+                } else {
+                    warn!(slot, ?missed, "Journaled commitments were not included");
+                }
+                self.commitment_statuses.record_resolved(slot, &included, &missed);
+                journal.append(&JournalRecord::Resolved { slot, included, missed })?;
+                continue;
+            }
+
+            for hash in entry.transaction_hashes() {
+                self.commitment_statuses.record_pending(slot, hash);
+            }
+            if entry.acknowledged {
+                self.commitment_statuses.mark_constrained(slot);
+            }
+            for constraints in entry.constraints.iter().cloned() {
+                self.execution.add_constraint(slot, constraints);
+            }
+            info!(slot, constraints = entry.constraints.len(), "Restored journaled commitments");
+
+            if !entry.acknowledged && slot <= current_slot + 1 {
+                match self.constraints_api.submit_constraints(&entry.constraints).await {
+                    Ok(()) => {
+                        self.commitment_statuses.mark_constrained(slot);
+                        journal.append(&JournalRecord::Acknowledged { slot })?
+                    }
+                    Err(err) => error!(?err, slot, "Failed to re-submit journaled constraints"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the pipeline of validators that incoming commitment requests must pass,
+    /// e.g. to register custom validators at startup. To keep the validators enabled by the
+    /// options, extend the current [Self::validation_pipeline] instead of a new one.
+    pub fn with_validation_pipeline(mut self, pipeline: ValidationPipeline) -> Self {
+        self.execution.set_validation_pipeline(pipeline);
+        self
+    }
+
+    /// Returns the pipeline of validators that incoming commitment requests must pass.
+    pub fn validation_pipeline(&self) -> &ValidationPipeline {
+        self.execution.validation_pipeline()
+    }
+
+    /// Returns the execution state, to validate requests against in tests.
+    #[cfg(test)]
+    pub(crate) fn execution_mut(&mut self) -> &mut ExecutionState<C> {
+        &mut self.execution
+    }
+
+    /// Replace the compliance screening that the addresses of commitment requests must pass,
+    /// e.g. to plug a custom [AddressScreener](crate::state::AddressScreener) at startup.
+    pub fn with_screening(mut self, screening: Screening) -> Self {
+        self.execution.set_screening(Some(screening));
+        self
+    }
+
+    /// Replace the Constraints API that the constraints are submitted to, e.g. with the
+    /// `MockConstraintsApi` of the `mock` feature to script relay failures.
+    pub fn with_constraints_api(
+        mut self,
+        api: impl ConstraintsApi + Send + Sync + 'static,
+    ) -> Self {
+        self.constraints_api = Arc::new(api);
+        self
+    }
+
+    /// Returns a handle to reload the operational parameters of the driver at runtime.
+    pub fn config_reloader(&self) -> ConfigReloader {
+        ConfigReloader::new(self.reload_tx.clone())
+    }
+
+    /// Returns the tracker of the delegations acknowledged by each relay.
+    pub fn delegation_tracker(&self) -> DelegationTracker {
+        self.constraints_client.delegation_tracker()
+    }
+
+    /// Returns the tracker of the rate limits of each relay.
+    pub fn relay_throttle(&self) -> RelayThrottle {
+        self.constraints_client.relay_throttle()
+    }
+
+    /// Returns the log of the constraints submitted to each relay.
+    pub fn submission_log(&self) -> SubmissionLog {
+        self.constraints_client.submission_log()
+    }
+
+    /// Returns the health of the driver, to signal its readiness to the service manager.
+    pub fn health(&self) -> SidecarHealth {
+        self.health.clone()
+    }
+
+    /// Returns the checks of the fee recipients of the registrations and bids of each relay.
+    pub fn fee_recipient_guard(&self) -> FeeRecipientGuard {
+        self.constraints_client.fee_recipient_guard()
+    }
+
+    /// Returns a handle to read snapshots of the pending block templates of the driver.
+    pub fn template_reader(&self) -> TemplateReader {
+        TemplateReader::new(self.snapshot_tx.clone())
+    }
+
+    /// Returns the handles that the operator APIs serve over the driver.
+    pub fn operator_handles(&self) -> OperatorHandles {
+        OperatorHandles {
+            reloader: self.config_reloader(),
+            templates: self.template_reader(),
+            delegations: self.delegation_tracker(),
+            relays: self.relay_throttle(),
+            submissions: self.submission_log(),
+            fee_recipients: self.fee_recipient_guard(),
+            health: self.health(),
+        }
+    }
+
+    /// Returns the sender used to signal the driver and its servers to shut down.
+    /// Sending `true` on it stops a driver started with [SidecarDriver::run_until_shutdown].
+    pub fn shutdown_sender(&self) -> Arc<watch::Sender<bool>> {
+        Arc::clone(&self.shutdown)
     }
 
     /// Run the main event loop endlessly for the sidecar driver.
@@ -268,172 +827,1529 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             ╚═════╝  ╚═════╝ ╚══════╝╚═╝   "#;
         println!("{BOLT}");
 
+        loop {
+            self.handle_next_event().await;
+        }
+    }
+
+    /// Run the main event loop until a shutdown is signaled through the
+    /// [shutdown sender](SidecarDriver::shutdown_sender), then stop the servers
+    /// started by the driver.
+    pub async fn run_until_shutdown(mut self) {
+        let shutdown = shutdown_signal(&self.shutdown);
+        tokio::pin!(shutdown);
+
         loop {
             tokio::select! {
-                Some(api_event) = self.api_events_rx.recv() => {
-                    self.handle_incoming_api_event(api_event).await;
-                }
-                Ok(head_event) = self.head_tracker.next_head() => {
-                    self.handle_new_head_event(head_event).await;
-                }
-                Some(slot) = self.consensus.commitment_deadline.wait() => {
-                    self.handle_commitment_deadline(slot).await;
-                }
-                Some(payload_request) = self.payload_requests_rx.recv() => {
-                    self.handle_fetch_payload_request(payload_request);
-                }
-                Some(slot) = self.slot_stream.next() => {
-                    if let Err(e) = self.consensus.update_slot(slot).await {
-                        error!(err = ?e, "Failed to update consensus state slot");
-                    }
+                _ = &mut shutdown => break,
+                _ = self.handle_next_event() => {}
+            }
+        }
+
+        info!("Shutting down the sidecar driver");
+        self.builder_proxy_task.abort();
+        self.capabilities_task.abort();
+        if let Some(task) = &self.reconciler_task {
+            task.abort();
+        }
+        if let Some(task) = &self.failover_task {
+            task.abort();
+        }
+        if let Some(task) = &self.commitment_webhook_task {
+            task.abort();
+        }
+    }
+
+    /// Wait for the next event of the main event loop and handle it.
+    async fn handle_next_event(&mut self) {
+        self.health.heartbeat();
+        tokio::select! {
+            Some(api_event) = self.api_events_rx.recv() => {
+                self.handle_incoming_api_event(api_event).await;
+            }
+            Ok(head_event) = self.head_tracker.next_head() => {
+                self.handle_new_head_event(head_event).await;
+            }
+            Ok(beacon_event) = self.beacon_events_rx.recv() => {
+                self.handle_beacon_event(beacon_event).await;
+            }
+            Some(slot) = self.consensus.next_deadline() => {
+                self.handle_commitment_deadline(slot).await;
+            }
+            Some(payload_request) = self.payload_requests_rx.recv() => {
+                self.handle_fetch_payload_request(payload_request);
+            }
+            Some(reservation_event) = self.reservations_rx.recv() => {
+                self.handle_reservation_event(reservation_event).await;
+            }
+            Some(encrypted_event) = self.encrypted_rx.recv() => {
+                self.handle_encrypted_event(encrypted_event).await;
+            }
+            Some(solution) = self.timelock_solutions_rx.recv() => {
+                self.handle_timelock_solution(solution);
+            }
+            Some(simulation_event) = self.simulations_rx.recv() => {
+                self.handle_simulation_event(simulation_event).await;
+            }
+            Some(reload_request) = self.reload_rx.recv() => {
+                self.handle_reload_request(reload_request);
+            }
+            Some(snapshot_request) = self.snapshot_rx.recv() => {
+                let TemplateSnapshotRequest { slot, response } = snapshot_request;
+                let _ = response.send(self.execution.template_snapshot(slot));
+            }
+            _ = tick(&mut self.mempool_scan) => {
+                self.handle_mempool_scan().await;
+            }
+            _ = tick(&mut self.voucher_settlement) => {
+                self.handle_voucher_settlement();
+            }
+            Some(slot) = tob_cutoff(&mut self.tob_auction) => {
+                self.handle_tob_cutoff(slot).await;
+            }
+            Some(slot) = self.slot_stream.next() => {
+                if let Err(e) = self.consensus.update_slot(slot).await {
+                    error!(err = ?e, "Failed to update consensus state slot");
                 }
+                self.start_tob_auction(slot);
+                self.expire_commitments(slot);
+                self.update_delegation_schedule(slot).await;
+                self.attest_availability(slot).await;
             }
         }
     }
 
     /// Handle an incoming API event, validating the request and responding with a commitment.
     async fn handle_incoming_api_event(&mut self, event: CommitmentEvent) {
-        let CommitmentEvent { mut request, response } = event;
+        let CommitmentEvent { request, response } = event;
         info!("Received new commitment request: {:?}", request);
         ApiMetrics::increment_inclusion_commitments_received();
 
         let start = Instant::now();
 
-        let validator_pubkey = match self.consensus.validate_request(&request) {
-            Ok(pubkey) => pubkey,
-            Err(err) => {
-                error!(?err, "Consensus: failed to validate request");
-                let _ = response.send(Err(CommitmentError::Consensus(err)));
-                return;
-            }
-        };
+        // TODO: match when we have more request types
+        let CommitmentRequest::Inclusion(inclusion_request) = &request;
 
-        if let Err(err) = self.execution.validate_request(&mut request).await {
-            error!(?err, "Execution: failed to commit request");
-            ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
-            let _ = response.send(Err(CommitmentError::Validation(err)));
+        if let Err(err) = self.check_tob_request(inclusion_request) {
+            let _ = response.send(Err(self.suggest_slots(inclusion_request, err)));
             return;
         }
 
-        // TODO: match when we have more request types
-        let CommitmentRequest::Inclusion(inclusion_request) = request.clone();
-        let target_slot = inclusion_request.slot;
+        // Under load ahead of a proposal, only the requests paying a raised fee are accepted
+        let shedding = self.should_shed_load();
+        let mut limits = self.limits();
+        if shedding {
+            limits = self.load_shedding.shed_limits(limits);
+        }
+
+        let (validator_pubkey, validated) = match self
+            .validate_commitment_request(inclusion_request, &limits)
+            .await
+        {
+            Ok(validated) => validated,
+            Err(err) => {
+                if shedding &&
+                    matches!(
+                        err,
+                        CommitmentError::Validation(ValidationError::MaxPriorityFeePerGasTooLow(_))
+                    )
+                {
+                    ApiMetrics::increment_shed_requests(ShedReason::LowPriorityFee.as_str());
+                }
+
+                let result = match err {
+                    CommitmentError::Validation(err) if inclusion_request.accepts_partial() => {
+                        self.commit_valid_prefix(inclusion_request, &limits, err, start).await
+                    }
+                    err => Err(err),
+                };
+                let result = result.map_err(|err| self.suggest_slots(inclusion_request, err));
+                let _ = response.send(result);
+                return;
+            }
+        };
 
         info!(
-            target_slot,
+            target_slot = validated.slot,
             elapsed = ?start.elapsed(),
             "Validation against execution state passed"
         );
 
-        let delegatees = self.constraints_client.find_delegatees(&validator_pubkey);
-        let available_pubkeys = self.constraint_signer.available_pubkeys();
-
-        let Some(pubkey) = pick_public_key(validator_pubkey, available_pubkeys, delegatees) else {
-            error!(%target_slot, "No available public key to sign constraints with");
-            let _ = response.send(Err(CommitmentError::Internal));
+        // Top-of-block requests are only committed to if they win the auction of their slot
+        if validated.top {
+            self.place_tob_bid(validated, response);
             return;
-        };
-
-        // NOTE: we iterate over the transactions in the request and generate a signed constraint
-        // for each one. This is because the transactions in the commitment request are not
-        // supposed to be treated as a relative-ordering bundle, but a batch
-        // with no ordering guarantees.
-        for tx in inclusion_request.txs {
-            let tx_type = tx.tx_type();
-            let message = ConstraintsMessage::from_transaction(pubkey.clone(), target_slot, tx);
-            let digest = message.digest();
+        }
 
-            let signature = match self.constraint_signer {
-                SignerBLS::Local(ref signer) => signer.sign_commit_boost_root(digest),
-                SignerBLS::CommitBoost(ref signer) => signer.sign_commit_boost_root(digest).await,
-                SignerBLS::Keystore(ref signer) => {
-                    signer.sign_commit_boost_root(digest, cl_public_key_to_arr(pubkey.clone()))
-                }
-            };
+        let result = self.commit_validated(request, validator_pubkey, validated, start).await;
+        let _ = response.send(result);
+    }
 
-            let signed_constraints = match signature {
-                Ok(signature) => SignedConstraints { message, signature },
-                Err(e) => {
-                    error!(?e, "Failed to sign constraints");
-                    let _ = response.send(Err(CommitmentError::Internal));
-                    return;
-                }
-            };
+    /// Sign the constraints of a validated request and add them to the block template of its
+    /// target slot, then commit to the request by signing it.
+    async fn commit_validated(
+        &mut self,
+        request: CommitmentRequest,
+        validator_pubkey: BlsPublicKey,
+        validated: InclusionRequest,
+        start: Instant,
+    ) -> Result<SignedCommitment, CommitmentError> {
+        let target_slot = validated.slot;
+        self.check_pending_quota(&validated)?;
 
-            ApiMetrics::increment_transactions_preconfirmed(tx_type);
-            self.execution.add_constraint(target_slot, signed_constraints);
-        }
+        // For requests with a slot range, the signed commitment encodes the whole range rather
+        // than the target slot, except for rollup batches whose commitment is to the slot
+        // chosen by the sidecar.
+        let request =
+            if validated.batch { CommitmentRequest::Inclusion(validated.clone()) } else { request };
 
-        // Create a commitment by signing the request
-        match request.commit_and_sign(&self.commitment_signer).await {
-            Ok(commitment) => {
-                debug!(target_slot, elapsed = ?start.elapsed(), "Commitment signed and sent");
-                response.send(Ok(commitment)).ok()
-            }
+        // Create a commitment by signing the request. It is signed before the constraints are
+        // added and the voucher debited, so that a failure leaves neither behind.
+        let commitment = match request.commit_and_sign(&self.commitment_signer).await {
+            Ok(commitment) => commitment,
             Err(err) => {
                 error!(?err, "Failed to sign commitment");
-                response.send(Err(CommitmentError::Internal)).ok()
+                return Err(CommitmentError::Internal);
             }
         };
 
+        self.sign_and_add_constraints(validator_pubkey, &validated).await?;
+        self.execution.debit_voucher(&validated);
+
+        if validated.max_slot.is_some_and(|max_slot| max_slot > target_slot) {
+            self.execution.track_ranged_commitment(validated);
+        }
+
+        debug!(target_slot, elapsed = ?start.elapsed(), "Commitment signed and sent");
         ApiMetrics::increment_inclusion_commitments_accepted();
+        Ok(commitment)
     }
 
-    /// Handle a new head event, updating the execution state.
-    async fn handle_new_head_event(&mut self, head_event: HeadEvent) {
-        let slot = head_event.slot;
-        info!(slot, "Received new head event");
+    /// Commit to the longest valid prefix of a request accepting partial commitment, some of
+    /// whose transactions are invalid, reporting the outcome of each of its transactions.
+    ///
+    /// Prefixes are validated from the longest to the shortest, so that the transactions
+    /// after the first invalid one are never committed to, e.g. nonces following a gap.
+    /// If no prefix is valid, the error of the first transaction is returned.
+    async fn commit_valid_prefix(
+        &mut self,
+        request: &InclusionRequest,
+        limits: &LimitsOpts,
+        err: ValidationError,
+        start: Instant,
+    ) -> Result<SignedCommitment, CommitmentError> {
+        // The error of the shortest invalid prefix, which rejects its last transaction
+        let mut rejection = err;
 
-        // We use None to signal that we want to fetch the latest EL head
-        if let Err(e) = self.execution.update_head(None, slot).await {
-            error!(err = ?e, "Failed to update execution state head");
+        for accepted in (1..request.txs.len()).rev() {
+            let prefix =
+                InclusionRequest { txs: request.txs[..accepted].to_vec(), ..request.clone() };
+
+            let (validator_pubkey, validated) =
+                match self.validate_commitment_request(&prefix, limits).await {
+                    Ok(validated) => validated,
+                    Err(CommitmentError::Validation(err)) => {
+                        rejection = err;
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                };
+
+            info!(accepted, total = request.txs.len(), reason = %rejection, "Committing to the valid prefix of a partial request");
+            let outcomes = TxOutcome::for_prefix(
+                &request.txs,
+                accepted,
+                rejection.error_code(),
+                rejection.to_string(),
+            );
+
+            let committed = CommitmentRequest::Inclusion(prefix);
+            let SignedCommitment::Inclusion(commitment) =
+                self.commit_validated(committed, validator_pubkey, validated, start).await?;
+            return Ok(SignedCommitment::Inclusion(commitment.with_outcomes(outcomes)));
         }
+
+        Err(CommitmentError::Validation(rejection))
     }
 
-    /// Handle a commitment deadline event, submitting constraints to the Constraints client service
-    /// and starting to build a local payload for the given target slot.
-    async fn handle_commitment_deadline(&mut self, slot: u64) {
-        debug!(slot, "Commitment deadline reached, building local block");
+    /// Attach the next viable slots of the sidecar to the rejection of a request for a
+    /// slot-related reason, so that the client can retarget it. Other rejections, and those
+    /// without any viable slot to suggest, are returned as is.
+    fn suggest_slots(&self, request: &InclusionRequest, err: CommitmentError) -> CommitmentError {
+        if self.retarget_suggestions == 0 || !err.error_code().is_slot_related() {
+            return err;
+        }
 
-        let Some(template) = self.execution.get_block_template(slot) else {
-            warn!("No block template found for slot {slot} when requested");
-            return;
-        };
+        let suggested_slots = self.viable_slots(request);
+        if suggested_slots.is_empty() {
+            return err;
+        }
 
-        if let Err(e) = self.local_builder.build_new_local_payload(slot, template).await {
-            error!(err = ?e, "Error while building local payload at deadline for slot {slot}");
-        };
+        debug!(slot = request.slot, ?suggested_slots, "Suggesting slots to retarget request");
+        CommitmentError::Retarget { err: Box::new(err), suggested_slots }
+    }
 
-        // TODO: fix retry logic, and move this to separate task in the constraints client itself
-        let constraints = template.signed_constraints_list.clone();
-        let constraints_client = self.constraints_client.clone();
-        tokio::spawn(async move {
-            let max_retries = 5;
-            let mut i = 0;
-            while let Err(e) = constraints_client.submit_constraints(&constraints).await {
-                error!(err = ?e, "Error submitting constraints to constraints client, retrying...");
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                i += 1;
-                if i >= max_retries {
-                    error!("Max retries reached while submitting to Constraints client");
-                    break;
-                }
-            }
-        });
+    /// Returns the next upcoming proposal slots, other than the target slot of the request,
+    /// that have enough room left in their block template for the request and whose
+    /// constraints the sidecar can sign, up to the configured number of suggestions.
+    ///
+    /// The transactions are not validated again, so a suggested slot may still reject them.
+    fn viable_slots(&self, request: &InclusionRequest) -> Vec<u64> {
+        let limits = self.limits();
+        let gas_limit = request.gas_limit();
+        let has_blobs = request.txs.iter().any(|tx| tx.as_eip4844().is_some());
+
+        let proposal_slots = self.consensus.proposal_slots(0, u64::MAX).unwrap_or_default();
+        proposal_slots
+            .into_iter()
+            .filter(|(slot, _)| *slot != request.slot)
+            .filter(|(slot, validator_pubkey)| {
+                let template = self.execution.template_snapshot(*slot);
+                template.transactions.len() < limits.max_commitments_per_slot.get() &&
+                    gas_limit < template.remaining_gas &&
+                    (!has_blobs || template.remaining_blobs > 0) &&
+                    self.signing_pubkey(*slot, validator_pubkey.clone()).is_ok()
+            })
+            .map(|(slot, _)| slot)
+            .take(self.retarget_suggestions)
+            .collect()
     }
 
-    /// Handle a fetch payload request, responding with the local payload if available.
-    fn handle_fetch_payload_request(&mut self, request: FetchPayloadRequest) {
-        info!(slot = request.slot, "Received local payload request");
+    /// Returns the limits of the commitments, capped by the ones advertised by the relay so
+    /// that the constraints of every commitment are accepted.
+    fn limits(&self) -> LimitsOpts {
+        let mut limits = self.execution.limits();
+        let capabilities = self.constraints_client.capabilities();
 
-        let Some(payload_and_bid) = self.local_builder.get_cached_payload() else {
-            warn!(slot = request.slot, "No local payload found");
-            let _ = request.response_tx.send(None);
-            return;
+        if let Some(max) = capabilities.max_constraints_per_slot.and_then(NonZero::new) {
+            limits.max_commitments_per_slot = limits.max_commitments_per_slot.min(max);
+        }
+        if let Some(max) = capabilities.max_constrained_gas_per_slot.and_then(NonZero::new) {
+            limits.max_committed_gas_per_slot = limits.max_committed_gas_per_slot.min(max);
+        }
+
+        limits
+    }
+
+    /// Check that a top-of-block request can take part in the auction of its target slot.
+    /// Other requests always pass.
+    fn check_tob_request(&self, request: &InclusionRequest) -> Result<(), CommitmentError> {
+        if !request.top {
+            return Ok(());
+        }
+
+        let err = match &self.tob_auction {
+            None => ValidationError::TopOfBlockDisabled,
+            Some(_) if !self.constraints_client.capabilities().top_of_block => {
+                ValidationError::TopOfBlockDisabled
+            }
+            Some(_) if request.max_slot.is_some() => ValidationError::TopOfBlockSlotRange,
+            Some(auction) if auction.is_closed(request.slot) => {
+                ValidationError::TopOfBlockAuctionClosed(request.slot)
+            }
+            Some(_) => return Ok(()),
         };
 
-        if let Err(e) = request.response_tx.send(Some(payload_and_bid)) {
-            error!(err = ?e, "Failed to send payload and bid in response channel");
+        ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
+        Err(CommitmentError::Validation(err))
+    }
+
+    /// Place a validated top-of-block request in the auction of its target slot, priced at
+    /// the total tip of its transactions. It is answered when the auction closes.
+    fn place_tob_bid(&mut self, request: InclusionRequest, response: CommitmentResponder) {
+        let Some(auction) = self.tob_auction.as_mut() else {
+            let _ = response.send(Err(ValidationError::TopOfBlockDisabled.into()));
+            return;
+        };
+
+        let price = request.total_tip(self.execution.basefee());
+        debug!(slot = request.slot, price, "Placed top-of-block bid");
+        auction.place(TobBid { request, price, responder: response });
+    }
+
+    /// Close the top-of-block auction of a slot at its cutoff, committing to the highest-paying
+    /// bid that is still valid, and answering the bids below it with the winning price.
+    async fn handle_tob_cutoff(&mut self, slot: u64) {
+        let Some(auction) = self.tob_auction.as_mut() else { return };
+        let bids = auction.close(slot);
+        if bids.is_empty() {
+            return;
+        }
+
+        let limits = self.limits();
+        let mut winning_price = None;
+        for TobBid { request, price, responder } in bids {
+            if let Some(winning_price) = winning_price {
+                let _ = responder.send(Err(CommitmentError::Outbid { slot, winning_price }));
+                continue;
+            }
+
+            // The state may have changed since the bid was placed, so it is validated again
+            let start = Instant::now();
+            let result = match self.validate_commitment_request(&request, &limits).await {
+                Ok((validator_pubkey, validated)) => {
+                    let committed = CommitmentRequest::Inclusion(request);
+                    self.commit_validated(committed, validator_pubkey, validated, start).await
+                }
+                Err(err) => Err(err),
+            };
+
+            if result.is_ok() {
+                info!(slot, price, "Top-of-block auction won");
+                winning_price = Some(price);
+            }
+            let _ = responder.send(result);
+        }
+    }
+
+    /// Start counting down to the top-of-block auction cutoff of the slot after the given one,
+    /// rejecting the bids of the auctions that missed their cutoff.
+    fn start_tob_auction(&mut self, slot: u64) {
+        let Some(auction) = self.tob_auction.as_mut() else { return };
+
+        for TobBid { request, responder, .. } in auction.start_slot(slot) {
+            let err = ValidationError::TopOfBlockAuctionClosed(request.slot);
+            let _ = responder.send(Err(err.into()));
+        }
+    }
+
+    /// Validate an inclusion request against the consensus and execution state, returning the
+    /// public key of the proposer of the target slot along with the validated request.
+    ///
+    /// Requests with a slot range target the earliest proposal slot of the range they are valid
+    /// for, which is set as the slot of the validated request.
+    async fn validate_commitment_request(
+        &mut self,
+        request: &InclusionRequest,
+        limits: &LimitsOpts,
+    ) -> Result<(BlsPublicKey, InclusionRequest), CommitmentError> {
+        if request.max_slot.is_some() {
+            return self.find_viable_slot(request, request.slot, limits).await;
+        }
+
+        let mut request = CommitmentRequest::Inclusion(request.clone());
+
+        let validator_pubkey = self.consensus.validate_request(&request).map_err(|err| {
+            error!(?err, "Consensus: failed to validate request");
+            err
+        })?;
+
+        if let Err(err) = self.execution.validate_request_with_limits(&mut request, limits).await {
+            error!(?err, "Execution: failed to commit request");
+            ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
+            return Err(CommitmentError::Validation(err));
+        }
+
+        let CommitmentRequest::Inclusion(request) = request;
+        Ok((validator_pubkey, request))
+    }
+
+    /// Find the earliest proposal slot from `from` up to the last slot of the range of the
+    /// request that the request is valid for, validating it against each of them in order.
+    /// Rollup batches are instead validated against the cheapest slots first, by their
+    /// [minimum inclusion cost](ExecutionState::min_inclusion_cost).
+    ///
+    /// If the request is valid for none of them, the error of the last one is returned.
+    async fn find_viable_slot(
+        &mut self,
+        request: &InclusionRequest,
+        from: u64,
+        limits: &LimitsOpts,
+    ) -> Result<(BlsPublicKey, InclusionRequest), CommitmentError> {
+        let max_slot = request.max_slot.unwrap_or(request.slot);
+        let mut slots = self.consensus.proposal_slots(from, max_slot).map_err(|err| {
+            error!(?err, "Consensus: failed to validate request");
+            err
+        })?;
+
+        if request.batch {
+            // The sort is stable, so that the earliest of the cheapest slots comes first
+            slots.sort_by_cached_key(|(slot, _)| {
+                self.execution.min_inclusion_cost(request, *slot).unwrap_or(u128::MAX)
+            });
+        }
+
+        let mut last_err = None;
+        for (slot, validator_pubkey) in slots {
+            let mut candidate =
+                CommitmentRequest::Inclusion(InclusionRequest { slot, ..request.clone() });
+
+            match self.execution.validate_request_with_limits(&mut candidate, limits).await {
+                Ok(()) => {
+                    let CommitmentRequest::Inclusion(candidate) = candidate;
+                    return Ok((validator_pubkey, candidate));
+                }
+                Err(err) => {
+                    debug!(?err, slot, "Request not valid for candidate slot");
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        let err = last_err.expect("At least one candidate slot");
+        error!(?err, from, max_slot, "Execution: failed to commit request in slot range");
+        ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
+        Err(CommitmentError::Validation(err))
+    }
+
+    /// Returns the public key to sign the constraints of the given slot with: the delegatee
+    /// assigned the authority over the slot if any, or else any key available for the validator.
+    fn signing_pubkey(
+        &self,
+        slot: u64,
+        validator_pubkey: BlsPublicKey,
+    ) -> Result<BlsPublicKey, CommitmentError> {
+        let available_pubkeys = self.constraint_signer.available_pubkeys();
+
+        let assigned = self.delegation_schedule.get(slot).and_then(|a| a.delegatee_pubkey);
+        if let Some(delegatee) = assigned {
+            return if available_pubkeys.contains(&delegatee) {
+                Ok(delegatee)
+            } else {
+                Err(CommitmentError::SlotAssigned(slot))
+            };
+        }
+
+        // Delegatees can't sign constraints for the slots after the expiry of their delegation
+        let delegatees = self
+            .constraints_client
+            .find_delegatees(&validator_pubkey)
+            .into_iter()
+            .filter(|delegatee| {
+                self.constraints_client
+                    .find_delegation(&validator_pubkey, delegatee)
+                    .is_some_and(|delegation| !delegation.message.scope.is_expired(slot))
+            })
+            .collect();
+        pick_public_key(validator_pubkey, available_pubkeys, delegatees)
+            .ok_or(CommitmentError::Internal)
+    }
+
+    /// Assign the upcoming proposal slots of the sidecar to their delegatees according to the
+    /// delegation policy, then revoke the delegations signed for the slots that passed.
+    async fn update_delegation_schedule(&mut self, slot: u64) {
+        let proposal_slots = self.consensus.proposal_slots(slot + 1, u64::MAX).unwrap_or_default();
+        let refused = self
+            .constraints_client
+            .delegation_tracker()
+            .persistently_refused(&self.constraints_client.url());
+
+        for (proposal_slot, validator_pubkey) in proposal_slots {
+            if self.delegation_schedule.get(proposal_slot).is_some() {
+                continue;
+            }
+
+            let delegated = self.constraints_client.ordered_delegatees(&validator_pubkey);
+            let candidates = self.delegation_targeting.candidates(delegated);
+            let refused = refused
+                .iter()
+                .filter(|state| state.validator_pubkey == validator_pubkey)
+                .map(|state| state.delegatee_pubkey.clone())
+                .collect::<HashSet<_>>();
+
+            let delegatee = self.delegation_targeting.assign(proposal_slot, &candidates, &refused);
+            let generated = match &delegatee {
+                Some(delegatee) => self.ensure_delegation(&validator_pubkey, delegatee).await,
+                None => false,
+            };
+
+            if delegatee.is_some() {
+                info!(slot = proposal_slot, ?delegatee, generated, "Assigned proposal slot");
+            }
+            self.delegation_schedule.assign(SlotAssignment {
+                slot: proposal_slot,
+                validator_pubkey,
+                delegatee_pubkey: delegatee,
+                generated,
+            });
+        }
+
+        for past in self.delegation_schedule.advance(slot) {
+            let Some(delegatee) = past.delegatee_pubkey.filter(|_| past.generated) else {
+                continue;
+            };
+            if !self.delegation_schedule.is_generated(&past.validator_pubkey, &delegatee) {
+                self.revoke_delegation(past.validator_pubkey, delegatee).await;
+            }
+        }
+    }
+
+    /// Publish a signed availability attestation for each upcoming proposal slot of the sidecar
+    /// within the lead slots, then drop the attestations of the slots that passed.
+    ///
+    /// Only the slots whose constraints the sidecar can sign are attested, so that gateways
+    /// can route the requests for the other ones elsewhere.
+    async fn attest_availability(&mut self, slot: u64) {
+        self.availability_board.advance(slot);
+        if self.availability_lead_slots == 0 {
+            return;
+        }
+
+        let to = slot.saturating_add(self.availability_lead_slots);
+        let proposal_slots = self.consensus.proposal_slots(slot + 1, to).unwrap_or_default();
+        let limits = self.limits();
+
+        for (proposal_slot, validator_pubkey) in proposal_slots {
+            if self.availability_board.is_attested(proposal_slot) {
+                continue;
+            }
+            if let Err(err) = self.signing_pubkey(proposal_slot, validator_pubkey) {
+                warn!(?err, slot = proposal_slot, "Not attesting the availability of the slot");
+                continue;
+            }
+
+            let availability = Availability {
+                chain_id: self.execution.chain_id(),
+                slot: proposal_slot,
+                max_commitments: limits.max_commitments_per_slot.get() as u64,
+                remaining_gas: self.execution.template_snapshot(proposal_slot).remaining_gas,
+                min_priority_fee: limits.min_priority_fee.get(),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            };
+            let signature = match self.commitment_signer.sign_hash(&availability.digest()).await {
+                Ok(signature) => signature,
+                Err(err) => {
+                    error!(?err, slot = proposal_slot, "Failed to sign availability attestation");
+                    continue;
+                }
+            };
+
+            let attestation = SignedAvailability { availability, signature };
+            if !self.availability_board.publish(attestation.clone()) {
+                continue;
+            }
+            info!(slot = proposal_slot, "Published availability attestation");
+
+            if let Some(url) = self.availability_webhook_url.clone() {
+                tokio::spawn(async move {
+                    let res = reqwest::Client::new().post(url).json(&attestation).send().await;
+                    if let Err(err) = res.and_then(|res| res.error_for_status()) {
+                        warn!(?err, slot = proposal_slot, "Failed to send availability webhook");
+                    }
+                });
+            }
+        }
+    }
+
+    /// Make sure the relay can accept the constraints signed by the delegatee for the validator,
+    /// signing and sending a delegation for the assigned slots if none was provided.
+    ///
+    /// Returns true if the delegation was signed by the sidecar, to be revoked after the slots.
+    async fn ensure_delegation(
+        &self,
+        validator_pubkey: &BlsPublicKey,
+        delegatee: &BlsPublicKey,
+    ) -> bool {
+        if self.constraints_client.find_delegation(validator_pubkey, delegatee).is_some() {
+            return false;
+        }
+        // The delegation may also have been signed before a restart
+        let manager = self.constraints_client.delegation_manager();
+        if self.delegation_schedule.is_generated(validator_pubkey, delegatee) ||
+            manager.is_generated(validator_pubkey, delegatee)
+        {
+            return true;
+        }
+
+        let message = DelegationMessage::new(validator_pubkey.clone(), delegatee.clone());
+        let Some(signature) =
+            self.sign_with_validator_key(message.digest(), validator_pubkey).await
+        else {
+            warn!(?validator_pubkey, ?delegatee, "No delegation to the assigned delegatee");
+            return false;
+        };
+
+        let constraints_api = Arc::clone(&self.constraints_api);
+        let delegation = SignedDelegation { message, signature };
+        if let Err(err) = manager.record_generated(delegation.clone()) {
+            warn!(?err, "Failed to save the delegation to the assigned delegatee");
+        }
+        tokio::spawn(async move {
+            if let Err(err) = constraints_api.delegate(&[delegation]).await {
+                error!(?err, "Failed to send the delegation to the assigned delegatee");
+            }
+        });
+        true
+    }
+
+    /// Revoke a delegation signed by the sidecar, once no upcoming slot is assigned to it.
+    async fn revoke_delegation(&self, validator_pubkey: BlsPublicKey, delegatee: BlsPublicKey) {
+        let message = RevocationMessage::new(validator_pubkey.clone(), delegatee.clone());
+        let Some(signature) =
+            self.sign_with_validator_key(message.digest(), &validator_pubkey).await
+        else {
+            return;
+        };
+
+        info!(?validator_pubkey, ?delegatee, "Revoking the delegation of the passed slots");
+        let constraints_api = Arc::clone(&self.constraints_api);
+        let revocation = SignedRevocation { message, signature };
+        if let Err(err) =
+            self.constraints_client.delegation_manager().record_revocation(revocation.clone())
+        {
+            warn!(?err, "Failed to save the revocation of the passed slots");
+        }
+        tokio::spawn(async move {
+            if let Err(err) = constraints_api.revoke(&[revocation]).await {
+                error!(?err, "Failed to revoke the delegation of the passed slots");
+            }
+        });
+    }
+
+    /// Sign a delegation or revocation digest with the key of the validator, if available.
+    async fn sign_with_validator_key(
+        &self,
+        digest: [u8; 32],
+        validator_pubkey: &BlsPublicKey,
+    ) -> Option<BlsSignature> {
+        if !self.constraint_signer.available_pubkeys().contains(validator_pubkey) {
+            return None;
+        }
+
+        match self.constraint_signer.sign_commit_boost_root(digest, validator_pubkey).await {
+            Ok(signature) => BlsSignature::try_from(signature.as_slice()).ok(),
+            Err(err) => {
+                error!(?err, ?validator_pubkey, "Failed to sign with the validator key");
+                None
+            }
+        }
+    }
+
+    /// Check that the signer of a request doesn't already hold the max number of commitments
+    /// in its target slot.
+    fn check_pending_quota(&self, request: &InclusionRequest) -> Result<(), CommitmentError> {
+        let (Some(max), Some(signer)) = (self.max_pending_commitments_per_signer, request.signer)
+        else {
+            return Ok(());
+        };
+
+        let pending = self
+            .pending_commitments
+            .get(request.slot)
+            .and_then(|signers| signers.get(&signer).copied())
+            .unwrap_or(0);
+        if pending >= max.get() {
+            warn!(
+                ?signer,
+                slot = request.slot,
+                pending,
+                "Max pending commitments of the signer reached"
+            );
+            return Err(CommitmentError::PendingQuotaReached { slot: request.slot, max: max.get() });
+        }
+
+        Ok(())
+    }
+
+    /// Sign the constraints of a validated request with a key available for the validator,
+    /// and add them to the block template of its target slot.
+    async fn sign_and_add_constraints(
+        &mut self,
+        validator_pubkey: BlsPublicKey,
+        request: &InclusionRequest,
+    ) -> Result<(), CommitmentError> {
+        let target_slot = request.slot;
+
+        let pubkey = self.signing_pubkey(target_slot, validator_pubkey).inspect_err(|err| {
+            error!(?err, %target_slot, "No available public key to sign constraints with");
+        })?;
+
+        // NOTE: we iterate over the transactions in the request and generate a signed constraint
+        // for each one. This is because the transactions in the commitment request are not
+        // supposed to be treated as a relative-ordering bundle, but a batch
+        // with no ordering guarantees. Top-of-block requests are the exception: their
+        // transactions are an ordered bundle, constrained by a single message.
+        //
+        // Every constraint is signed before any is added, so that a failure leaves none behind.
+        let messages = if request.top {
+            vec![ConstraintsMessage::top_of_block(pubkey.clone(), request, self.tob_bundle_index)]
+        } else {
+            request
+                .txs
+                .iter()
+                .cloned()
+                .map(|tx| ConstraintsMessage::from_transaction(pubkey.clone(), target_slot, tx))
+                .collect()
+        };
+
+        let signing_timer =
+            StageTimer::start(ProposalStage::ConstraintSigning, target_slot, &self.latency_budgets);
+        let mut signed = Vec::with_capacity(messages.len());
+        for message in messages {
+            let digest = message.digest();
+
+            let signature = self.constraint_signer.sign_commit_boost_root(digest, &pubkey).await;
+
+            match signature {
+                Ok(signature) => signed.push(SignedConstraints { message, signature }),
+                Err(e) => {
+                    error!(?e, "Failed to sign constraints");
+                    return Err(CommitmentError::Internal);
+                }
+            }
+        }
+
+        // The commitment must not be returned unless it survives a restart
+        if let Some(journal) = &self.journal {
+            for signed_constraints in &signed {
+                let record = JournalRecord::Committed {
+                    slot: target_slot,
+                    commitment_signer: self.commitment_signer.public_key(),
+                    constraints: signed_constraints.clone(),
+                };
+                if let Err(err) = journal.append(&record) {
+                    error!(?err, "Failed to journal constraints");
+                    return Err(CommitmentError::Internal);
+                }
+            }
+        }
+
+        for signed_constraints in signed {
+            if let Some(Failover::Primary(primary)) = &self.failover {
+                primary.mirror(target_slot, &signed_constraints);
+            }
+
+            for tx in &signed_constraints.message.transactions {
+                ApiMetrics::increment_transactions_preconfirmed(tx.tx_type());
+                self.commitment_statuses.record_pending(target_slot, *tx.hash());
+            }
+            self.execution.add_constraint(target_slot, signed_constraints);
+        }
+        signing_timer.finish();
+
+        if let Some(signer) = request.signer {
+            self.execution.record_reserved_usage(target_slot, signer, request.gas_limit());
+            *self.pending_commitments.get_or_default(target_slot).entry(signer).or_default() += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Roll over a commitment with a slot range that missed its target slot to the
+    /// earliest later proposal slot of its range that it is still valid for.
+    async fn rollover_commitment(&mut self, request: InclusionRequest) {
+        let missed = request.slot;
+
+        let limits = self.limits();
+        let (validator_pubkey, validated) = match self
+            .find_viable_slot(&request, missed + 1, &limits)
+            .await
+        {
+            Ok(validated) => validated,
+            Err(err) => {
+                error!(?err, missed, max_slot = ?request.max_slot, "Failed to roll over commitment");
+                return;
+            }
+        };
+
+        let target_slot = validated.slot;
+        if let Err(err) = self.sign_and_add_constraints(validator_pubkey, &validated).await {
+            error!(?err, missed, target_slot, "Failed to roll over commitment");
+            return;
+        }
+
+        info!(missed, target_slot, txs = validated.txs.len(), "Rolled over commitment");
+        if validated.max_slot.is_some_and(|max_slot| max_slot > target_slot) {
+            self.execution.track_ranged_commitment(validated);
+        }
+    }
+
+    /// Handle the simulation of an inclusion request, responding with whether it would be
+    /// accepted by [SidecarDriver::handle_incoming_api_event] without committing to it.
+    ///
+    /// The request goes through the same validation, including the availability of a key
+    /// to sign its constraints with, but nothing is signed nor added to the templates.
+    async fn handle_simulation_event(&mut self, event: SimulationEvent) {
+        let SimulationEvent { request, response } = event;
+        ApiMetrics::increment_inclusion_simulations();
+
+        if let Err(err) = self.check_tob_request(&request) {
+            let _ = response.send(InclusionSimulation::rejected(&err));
+            return;
+        }
+
+        // Simulations are the first work shed under load ahead of a proposal
+        if self.should_shed_load() {
+            ApiMetrics::increment_shed_requests(ShedReason::Simulation.as_str());
+            let _ = response.send(InclusionSimulation::rejected(&CommitmentError::Overloaded));
+            return;
+        }
+
+        let limits = self.limits();
+        let simulation = match self.validate_commitment_request(&request, &limits).await {
+            Ok((validator_pubkey, validated)) => {
+                match self.signing_pubkey(validated.slot, validator_pubkey) {
+                    Ok(_) => InclusionSimulation::accepted(validated.slot),
+                    Err(err) => InclusionSimulation::rejected(&err),
+                }
+            }
+            Err(err) => InclusionSimulation::rejected(&err),
+        };
+
+        debug!(?simulation, "Simulated inclusion request");
+        let _ = response.send(simulation);
+    }
+
+    /// Returns true if low-value work must be shed to protect the commitment deadline of an
+    /// upcoming proposal, according to the load shedding policy.
+    fn should_shed_load(&self) -> bool {
+        let queued = self.api_events_rx.len() + self.simulations_rx.len();
+        self.load_shedding.should_shed(self.consensus.time_to_proposal_deadline(), queued)
+    }
+
+    /// Handle a blockspace reservation request, responding with the reservation
+    /// signed by the commitment signer if there is enough gas left to reserve.
+    async fn handle_reservation_event(&mut self, event: ReservationEvent) {
+        let ReservationEvent { request, response } = event;
+
+        let reservation = match self.execution.reserve_blockspace(&request) {
+            Ok(reservation) => reservation,
+            Err(err) => {
+                warn!(?err, ?request, "Rejected blockspace reservation");
+                let _ = response.send(Err(err.into()));
+                return;
+            }
+        };
+
+        match self.commitment_signer.sign_hash(&reservation.digest()).await {
+            Ok(signature) => {
+                info!(
+                    subscriber = %reservation.subscriber,
+                    gas_per_slot = reservation.gas_per_slot,
+                    start_slot = reservation.start_slot,
+                    end_slot = reservation.end_slot,
+                    "Blockspace reserved"
+                );
+                let _ = response.send(Ok(SignedReservation { reservation, signature }));
+            }
+            Err(err) => {
+                error!(?err, "Failed to sign reservation");
+                let _ = response.send(Err(CommitmentError::Internal));
+            }
+        }
+    }
+
+    /// Handle an encrypted inclusion request or the reveal of the decryption key of one.
+    async fn handle_encrypted_event(&mut self, event: EncryptedEvent) {
+        match event {
+            EncryptedEvent::Request { request, response } => {
+                let result = self.commit_encrypted_request(request).await;
+                let _ = response.send(result);
+            }
+            EncryptedEvent::Reveal { reveal, response } => {
+                let result = self.encrypted_pool.reveal(&reveal);
+                match &result {
+                    Ok(slot) => {
+                        debug!(slot, ciphertext_hash = %reveal.ciphertext_hash, "Decryption key revealed")
+                    }
+                    Err(err) => {
+                        warn!(?err, ciphertext_hash = %reveal.ciphertext_hash, "Rejected decryption key")
+                    }
+                }
+                let _ = response.send(result.map_err(CommitmentError::from));
+            }
+        }
+    }
+
+    /// Commit to an encrypted request by the hash of its ciphertext, holding its gas limit in
+    /// its target slot until its transactions are decrypted at the commitment deadline.
+    async fn commit_encrypted_request(
+        &mut self,
+        request: EncryptedInclusionRequest,
+    ) -> Result<SignedEncryptedCommitment, CommitmentError> {
+        let signer = request.signer.ok_or(EncryptedRequestError::MissingSigner)?;
+        self.encrypted_pool.check_signer(signer).inspect_err(|err| {
+            warn!(?err, %signer, "Rejected encrypted request of a signer withholding its keys");
+        })?;
+        request.check_kdf().map_err(EncryptedRequestError::from)?;
+        if let Some(max_squarings) = self.max_timelock_squarings {
+            let timelock =
+                request.timelock.as_ref().ok_or(EncryptedRequestError::MissingTimeLock)?;
+            timelock.check(max_squarings).map_err(EncryptedRequestError::from)?;
+        }
+
+        let ciphertext_hash = request.ciphertext_hash();
+        if self.encrypted_pool.contains(request.slot, &ciphertext_hash) {
+            return Err(EncryptedRequestError::Duplicate(ciphertext_hash).into());
+        }
+
+        // The target slot must be a proposal slot of the sidecar whose deadline hasn't passed
+        self.consensus.proposal_slots(request.slot, request.slot)?;
+
+        let commitment = EncryptedCommitment {
+            signer,
+            slot: request.slot,
+            gas_limit: request.gas_limit,
+            ciphertext_hash,
+        };
+        let signature =
+            self.commitment_signer.sign_hash(&commitment.digest()).await.map_err(|err| {
+                error!(?err, "Failed to sign encrypted commitment");
+                CommitmentError::Internal
+            })?;
+
+        let limits = self.limits();
+        self.execution.hold_encrypted_gas(&request, &limits).inspect_err(|err| {
+            warn!(?err, %signer, slot = request.slot, "Rejected encrypted request");
+        })?;
+
+        info!(
+            %signer,
+            slot = request.slot,
+            gas_limit = request.gas_limit,
+            %ciphertext_hash,
+            "Committed to encrypted request"
+        );
+        if let Some(timelock) = request.timelock.clone() {
+            self.solve_timelock(ciphertext_hash, timelock);
+        }
+        self.encrypted_pool.insert(request);
+        Ok(SignedEncryptedCommitment { commitment, signature })
+    }
+
+    /// Solve the time-lock puzzle of an encrypted request on a blocking thread, sending the key
+    /// it locks back to the driver once solved.
+    fn solve_timelock(&self, ciphertext_hash: B256, timelock: TimeLockPuzzle) {
+        let solutions = self.timelock_solutions_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let start = Instant::now();
+            let key = timelock.solve();
+            debug!(%ciphertext_hash, elapsed = ?start.elapsed(), "Solved time-lock puzzle");
+            let _ = solutions.blocking_send(DecryptionKeyReveal { ciphertext_hash, key });
+        });
+    }
+
+    /// Handle the key of a solved time-lock puzzle like a reveal by the client. It is rejected if
+    /// the deadline of the request passed, or if the client locked a wrong key in the puzzle.
+    fn handle_timelock_solution(&mut self, solution: DecryptionKeyReveal) {
+        let ciphertext_hash = solution.ciphertext_hash;
+        match self.encrypted_pool.reveal(&solution) {
+            Ok(slot) => debug!(slot, %ciphertext_hash, "Decryption key unlocked by time-lock"),
+            Err(err) => warn!(?err, %ciphertext_hash, "Time-lock puzzle didn't unlock the key"),
+        }
+    }
+
+    /// Decrypt the encrypted requests of the slot at its commitment deadline, and add the
+    /// constraints of their transactions to its block template. The requests whose key wasn't
+    /// revealed, whose transactions use more than their gas limit, or are invalid against the
+    /// state, are dropped. The keys not revealed are counted as withheld, including those of
+    /// the passed slots.
+    async fn commit_encrypted_requests(&mut self, slot: u64) {
+        let DeadlineEncrypted { revealed, withheld } = self.encrypted_pool.take(slot);
+        for PendingEncrypted { request, ciphertext_hash, .. } in withheld {
+            let signer = request.signer.unwrap_or_default();
+            warn!(
+                slot = request.slot,
+                %ciphertext_hash,
+                %signer,
+                withheld = self.encrypted_pool.withheld_keys(signer),
+                "Decryption key not revealed before the deadline"
+            );
+            ApiMetrics::increment_decryption_keys_withheld();
+        }
+        if revealed.is_empty() {
+            return;
+        }
+
+        // The deadline passed, so the proposer is looked up regardless of it
+        let validator_pubkey = match self.consensus.proposer_pubkey(slot) {
+            Ok(validator_pubkey) => validator_pubkey,
+            Err(err) => {
+                error!(?err, slot, "No proposer found for the encrypted requests of the slot");
+                return;
+            }
+        };
+
+        let limits = self.limits();
+        for RevealedEncrypted { request, ciphertext_hash, key } in revealed {
+            let txs = match request.decrypt(&key) {
+                Ok(txs) => txs,
+                Err(err) => {
+                    warn!(?err, slot, %ciphertext_hash, "Failed to decrypt encrypted request");
+                    continue;
+                }
+            };
+
+            let decrypted = InclusionRequest {
+                slot,
+                max_slot: None,
+                top: false,
+                partial: false,
+                batch: false,
+                txs,
+                signature: None,
+                signer: request.signer,
+                voucher: None,
+            };
+            if decrypted.gas_limit() > request.gas_limit {
+                warn!(
+                    slot,
+                    %ciphertext_hash,
+                    gas_limit = request.gas_limit,
+                    used = decrypted.gas_limit(),
+                    "Decrypted transactions exceed the gas limit committed to"
+                );
+                continue;
+            }
+
+            let mut decrypted = CommitmentRequest::Inclusion(decrypted);
+            if let Err(err) =
+                self.execution.validate_request_with_limits(&mut decrypted, &limits).await
+            {
+                warn!(?err, slot, %ciphertext_hash, "Decrypted transactions are invalid");
+                ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
+                continue;
+            }
+
+            let CommitmentRequest::Inclusion(decrypted) = decrypted;
+            match self.sign_and_add_constraints(validator_pubkey.clone(), &decrypted).await {
+                Ok(()) => info!(
+                    slot,
+                    %ciphertext_hash,
+                    txs = decrypted.txs.len(),
+                    "Committed the decrypted transactions of an encrypted request"
+                ),
+                Err(err) => error!(?err, slot, %ciphertext_hash, "Failed to sign constraints"),
+            }
+        }
+    }
+
+    /// Handle a new head event, updating the execution state.
+    async fn handle_new_head_event(&mut self, head_event: HeadEvent) {
+        let slot = head_event.slot;
+        info!(slot, "Received new head event");
+
+        // We use None to signal that we want to fetch the latest EL head
+        if let Err(e) = self.execution.update_head(None, slot).await {
+            error!(err = ?e, "Failed to update execution state head");
+        }
+        self.resolve_commitment_statuses(slot).await;
+        self.pending_commitments.advance(slot);
+        self.health.record_head(slot);
+
+        // Commitments with a slot range whose target slot was missed move on to the next one
+        for request in self.execution.take_rollovers() {
+            self.rollover_commitment(request).await;
+        }
+    }
+
+    /// Expire the commitments of the slots that ended before their head was received, and
+    /// reclaim the state kept for them. The state of the previous slot is kept for one more
+    /// slot, as its head may still arrive late.
+    fn expire_commitments(&mut self, slot: u64) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let expired = self.commitment_statuses.expire(now);
+        if !expired.is_empty() {
+            warn!(slot, count = expired.len(), "Commitments expired before the head of their slot");
+            ApiMetrics::increment_commitments_expired(expired.len() as u64);
+        }
+
+        let passed = slot.saturating_sub(2);
+        if passed > self.pending_commitments.head() {
+            self.pending_commitments.advance(passed);
+        }
+        let templates = self.execution.expire_templates(passed);
+        if templates > 0 {
+            debug!(slot = passed, templates, "Dropped the block templates of missed slots");
+        }
+    }
+
+    /// Resolve the statuses of the commitments of the slots up to the new head, against the
+    /// receipts of the execution client. Failed lookups are retried at the next head.
+    async fn resolve_commitment_statuses(&mut self, head: u64) {
+        for (slot, hashes) in self.commitment_statuses.unresolved_until(head) {
+            match self.execution.included_transactions(&hashes).await {
+                Ok(found) => self.commitment_statuses.resolve(slot, |hash| found.contains(hash)),
+                Err(err) => warn!(?err, slot, "Failed to resolve the statuses of commitments"),
+            }
+        }
+        self.commitment_statuses.advance(head);
+    }
+
+    /// Handle a typed event of the beacon node. Heads are handled by
+    /// [SidecarDriver::handle_new_head_event], which also catches up after reorgs and gaps.
+    async fn handle_beacon_event(&mut self, event: BeaconEvent) {
+        match event {
+            BeaconEvent::PayloadAttributes(attributes) => {
+                if let Err(e) = self.execution.apply_payload_attributes(attributes).await {
+                    error!(err = ?e, "Failed to apply payload attributes");
+                }
+            }
+            BeaconEvent::ChainReorg(reorg) => {
+                warn!(
+                    slot = reorg.slot,
+                    depth = reorg.depth,
+                    old_head = %reorg.old_head_block,
+                    new_head = %reorg.new_head_block,
+                    "Beacon chain reorg"
+                );
+            }
+            BeaconEvent::Gap { last_slot, slot } => {
+                debug!(last_slot, slot, "Gap in the beacon heads, catching up at the next head");
+            }
+            BeaconEvent::Head(_) => {}
+        }
+    }
+
+    /// Handle a commitment deadline event, submitting constraints to the Constraints client service
+    /// and starting to build a local payload for the given target slot.
+    async fn handle_commitment_deadline(&mut self, slot: u64) {
+        debug!(slot, "Commitment deadline reached, building local block");
+
+        // The gas held for the encrypted requests is used before the reservations are released
+        self.commit_encrypted_requests(slot).await;
+
+        // No more commitments can be made for the slot, so the reservations are released
+        // and charged to their subscribers
+        for usage in self.execution.release_reservations(slot) {
+            info!(
+                slot,
+                subscriber = %usage.subscriber,
+                reserved = usage.reserved,
+                used = usage.used,
+                price = %usage.price,
+                "Released blockspace reservation"
+            );
+        }
+
+        self.take_over_from_primary(slot);
+
+        let Some(template) = self.execution.get_block_template(slot) else {
+            warn!("No block template found for slot {slot} when requested");
+            return;
+        };
+
+        let deadline_timer =
+            StageTimer::start(ProposalStage::CommitmentDeadline, slot, &self.latency_budgets);
+        if let Err(e) = self.local_builder.build_new_local_payload(slot, template).await {
+            error!(err = ?e, "Error while building local payload at deadline for slot {slot}");
+        };
+        deadline_timer.finish();
+
+        // TODO: fix retry logic, and move this to separate task in the constraints client itself
+        let constraints = template.signed_constraints_list.clone();
+        let constraints_api = Arc::clone(&self.constraints_api);
+        let latency_budgets = self.latency_budgets;
+        let journal = self.journal.clone();
+        let commitment_statuses = self.commitment_statuses.clone();
+        let failover = self.failover.clone();
+        tokio::spawn(async move {
+            let submission_timer =
+                StageTimer::start(ProposalStage::ConstraintsSubmission, slot, &latency_budgets);
+            let max_retries = 5;
+            let mut i = 0;
+            while let Err(e) = constraints_api.submit_constraints(&constraints).await {
+                error!(err = ?e, "Error submitting constraints to constraints client, retrying...");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                i += 1;
+                if i >= max_retries {
+                    error!("Max retries reached while submitting to Constraints client");
+                    return;
+                }
+            }
+            submission_timer.finish();
+            commitment_statuses.mark_constrained(slot);
+            if let Some(Failover::Primary(primary)) = failover {
+                primary.mark_submitted(slot);
+            }
+
+            if let Some(journal) = journal {
+                if let Err(err) = journal.append(&JournalRecord::Acknowledged { slot }) {
+                    error!(?err, slot, "Failed to journal the acknowledgment of constraints");
+                }
+            }
+        });
+    }
+
+    /// Take over the constraints mirrored by the primary for the slot, if this sidecar is the
+    /// standby of a pair and the primary missed its heartbeats. They are added to the block
+    /// template of the slot, to be submitted to the relays and built into the local payload.
+    fn take_over_from_primary(&mut self, slot: u64) {
+        let Some(Failover::Standby(standby)) = &self.failover else { return };
+        let Some(constraints) = standby.take_over(slot) else { return };
+
+        warn!(
+            slot,
+            constraints = constraints.len(),
+            "Primary sidecar is down, taking over its constraints"
+        );
+        ApiMetrics::increment_failover_takeovers();
+        for signed_constraints in constraints {
+            for tx in signed_constraints.message.transactions.iter() {
+                self.commitment_statuses.record_pending(slot, *tx.hash());
+            }
+            self.execution.add_constraint(slot, signed_constraints);
+        }
+    }
+
+    /// Handle a fetch payload request, responding with the local payload if available.
+    fn handle_fetch_payload_request(&mut self, request: FetchPayloadRequest) {
+        info!(slot = request.slot, "Received local payload request");
+
+        let Some(payload_and_bid) = self.local_builder.get_cached_payload() else {
+            warn!(slot = request.slot, "No local payload found");
+            let _ = request.response_tx.send(None);
+            return;
+        };
+
+        if let Err(e) = request.response_tx.send(Some(payload_and_bid)) {
+            error!(err = ?e, "Failed to send payload and bid in response channel");
+        }
+    }
+
+    /// Scan the mempool for transactions competing with the committed ones, so that new
+    /// commitments building on contested nonces are rejected until the next scan.
+    async fn handle_mempool_scan(&mut self) {
+        match self.execution.scan_mempool().await {
+            Ok(conflicts) => debug!(conflicts = conflicts.len(), "Scanned the mempool"),
+            Err(err) => warn!(?err, "Failed to scan the mempool for competing transactions"),
+        }
+    }
+
+    /// Write the latest unsettled voucher of each payer to a new batch in the settlement
+    /// directory, for the operator to redeem them on-chain. Vouchers that could not be
+    /// written are kept for the next settlement. Batches past their retention period are
+    /// archived first.
+    fn handle_voucher_settlement(&mut self) {
+        if let Some(retention) = &self.voucher_retention {
+            match retention.compact(SystemTime::now()) {
+                Ok(CompactionReport { compacted, archive: Some(archive) }) => {
+                    info!(compacted, archive = %archive.display(), "Archived voucher settlement batches");
+                }
+                Ok(_) => {}
+                Err(err) => error!(?err, "Failed to archive voucher settlement batches"),
+            }
+        }
+
+        let settlements = self.execution.pending_voucher_settlements();
+        if settlements.is_empty() {
+            return;
+        }
+
+        match write_voucher_settlements(&self.voucher_settlement_dir, &settlements) {
+            Ok(path) => {
+                for settlement in &settlements {
+                    info!(
+                        payer = ?settlement.payer,
+                        amount = %settlement.voucher.amount,
+                        previously_settled = %settlement.previously_settled,
+                        "Voucher ready for settlement"
+                    );
+                }
+                info!(path = %path.display(), payers = settlements.len(), "Wrote voucher settlement batch");
+                self.execution.mark_vouchers_settled(&settlements);
+            }
+            Err(err) => error!(?err, "Failed to write voucher settlement batch"),
+        }
+    }
+
+    /// Handle a configuration reload request, applying the provided settings and
+    /// responding with the ones that changed. The configuration is already validated.
+    fn handle_reload_request(&mut self, request: ReloadRequest) {
+        let ReloadRequest { config, response } = request;
+        let mut changes = Vec::new();
+
+        let old = self.execution.limits();
+        let new = LimitsOpts {
+            max_commitments_per_slot: config
+                .max_commitments_per_slot
+                .unwrap_or(old.max_commitments_per_slot),
+            max_committed_gas_per_slot: config
+                .max_committed_gas_per_slot
+                .unwrap_or(old.max_committed_gas_per_slot),
+            min_priority_fee: config.min_priority_fee.unwrap_or(old.min_priority_fee),
+            reserved_template_gas: old.reserved_template_gas,
+        };
+
+        // Nothing is applied if the new limits fail the checks made at startup
+        let mut errors = Vec::new();
+        validate_limits(&new, &self.execution.reservation_opts(), &mut errors);
+        if !errors.is_empty() {
+            let _ = response.send(Err(ConfigErrors(errors)));
+            return;
+        }
+
+        changes.extend([
+            ConfigChange::new(
+                "max_commitments_per_slot",
+                old.max_commitments_per_slot,
+                new.max_commitments_per_slot,
+            ),
+            ConfigChange::new(
+                "max_committed_gas_per_slot",
+                old.max_committed_gas_per_slot,
+                new.max_committed_gas_per_slot,
+            ),
+            ConfigChange::new("min_priority_fee", old.min_priority_fee, new.min_priority_fee),
+        ]);
+        self.execution.set_limits(new);
+
+        if let Some(whitelist) = config.whitelist {
+            let old = format!("{:?}", self.whitelist.signers());
+            self.whitelist.set(whitelist);
+            let new = format!("{:?}", self.whitelist.signers());
+            changes.push(ConfigChange::new("whitelist", old, new));
+        }
+
+        if let Some(url) = config.constraints_api_url {
+            changes.push(ConfigChange::new(
+                "constraints_api_url",
+                self.constraints_client.url(),
+                &url,
+            ));
+            self.constraints_client.set_url(url);
+        }
+
+        let _ = response.send(Ok(changes.into_iter().flatten().collect()));
+    }
+}
+
+/// Returns a future that resolves once `true` is sent on the given shutdown channel.
+fn shutdown_signal(shutdown: &watch::Sender<bool>) -> impl Future<Output = ()> + Send + 'static {
+    let mut rx = shutdown.subscribe();
+    async move {
+        let _ = rx.wait_for(|stop| *stop).await;
+    }
+}
+
+/// Sign the attestations binding the commitment key to each of the validators, with
+/// the validator key itself or one of its delegatees, depending on the available keys.
+async fn attest_commitment_key(
+    validators: &[BlsPublicKey],
+    constraint_signer: &SignerBLS,
+    constraints_client: &ConstraintsClient,
+    commitment_signer: Address,
+    chain_id: u64,
+) -> eyre::Result<SidecarIdentity> {
+    let available_pubkeys = constraint_signer.available_pubkeys();
+
+    let mut attestations = Vec::with_capacity(validators.len());
+    for validator in validators {
+        let delegatees = constraints_client.find_delegatees(validator);
+        let Some(signer) =
+            pick_public_key(validator.clone(), available_pubkeys.clone(), delegatees)
+        else {
+            warn!(?validator, "No available public key to attest the commitment key with");
+            continue;
+        };
+
+        let message =
+            CommitmentKeyMessage { validator_pubkey: validator.clone(), commitment_signer };
+        let signature = constraint_signer.sign_commit_boost_root(message.digest(), &signer).await?;
+        let delegation = (signer != *validator)
+            .then(|| constraints_client.find_delegation(validator, &signer))
+            .flatten();
+
+        attestations.push(SignedCommitmentKey { message, signer, signature, delegation });
+    }
+
+    info!(attestations = attestations.len(), ?commitment_signer, "Attested the commitment key");
+    Ok(SidecarIdentity { commitment_signer, chain_id, attestations })
+}
+
+/// Write a batch of vouchers to settle to a new timestamped file in the given directory,
+/// returning its path.
+fn write_voucher_settlements(
+    dir: &Path,
+    settlements: &[VoucherSettlement],
+) -> eyre::Result<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let path = dir.join(format!("{VOUCHER_SETTLEMENT_PREFIX}-{now}.json"));
+
+    fs::create_dir_all(dir)?;
+    fs::write(&path, serde_json::to_string_pretty(settlements)?)?;
+    Ok(path)
+}
+
+/// Wait for the next tick of an optional interval, forever if it is not set.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the cutoff of the next top-of-block auction, forever if auctions are disabled.
+async fn tob_cutoff<R>(auction: &mut Option<TobAuction<R>>) -> Option<u64> {
+    match auction {
+        Some(auction) => auction.wait_cutoff().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Replay the saved validator registrations and the delegations to the relay on startup.
+async fn replay_registrations(constraints_client: ConstraintsClient) {
+    let relay = constraints_client.url();
+    match constraints_client.replay_registrations().await {
+        Ok(()) => info!(%relay, "Replayed validator registrations and delegations"),
+        Err(err) => warn!(%relay, ?err, "Failed to replay validator registrations"),
+    }
+}
+
+/// Periodically fetch the capabilities of the relays, as they can change when a relay upgrades.
+async fn refresh_capabilities(relays: Vec<ConstraintsClient>) {
+    let mut interval = tokio::time::interval(CAPABILITIES_REFRESH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        for constraints_client in &relays {
+            if let Err(err) = constraints_client.refresh_capabilities().await {
+                let relay = constraints_client.url();
+                warn!(%relay, ?err, "Failed to fetch relay capabilities");
+            }
+        }
+    }
+}
+
+/// POST the changes of status of the commitments to the webhook, until the task is aborted.
+async fn forward_commitment_statuses(url: Url, mut updates: broadcast::Receiver<CommitmentStatus>) {
+    let client = reqwest::Client::new();
+
+    loop {
+        let status = match updates.recv().await {
+            Ok(status) => status,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!(missed, "Commitment webhook lagged behind the status updates");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+
+        let res = client.post(url.clone()).json(&status).send().await;
+        if let Err(err) = res.and_then(|res| res.error_for_status()) {
+            warn!(?err, slot = status.slot, tx_hash = %status.tx_hash, "Failed to send commitment webhook");
+        }
+    }
+}
+
+/// Periodically re-send the delegations that the relay has not acknowledged yet,
+/// alerting on the ones it persistently refuses.
+async fn reconcile_delegations(relays: Vec<ConstraintsClient>) {
+    let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+    // The first tick completes immediately, while the delegations are replayed on startup
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        for constraints_client in &relays {
+            let relay = constraints_client.url();
+            for state in constraints_client.reconcile_delegations().await {
+                error!(
+                    %relay,
+                    validator = ?state.validator_pubkey,
+                    delegatee = ?state.delegatee_pubkey,
+                    refusals = state.refusals,
+                    last_error = ?state.last_error,
+                    "Relay persistently refuses delegation"
+                );
+            }
         }
     }
 }
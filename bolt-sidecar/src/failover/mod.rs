@@ -0,0 +1,245 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use reqwest::Url;
+use tokio::time::MissedTickBehavior;
+use tonic::transport::{Endpoint, Server};
+use tracing::{debug, info, warn};
+
+pub use crate::config::FailoverRole;
+use crate::{common::now_ms, config::FailoverOpts, primitives::SignedConstraints};
+
+/// The protobuf messages and gRPC service of the failover channel.
+pub mod proto;
+use proto::{FailoverClient, FailoverServer, Heartbeat, MirroredConstraints};
+
+/// Number of attempts to mirror constraints to the standby before giving up.
+const MIRROR_ATTEMPTS: usize = 3;
+
+/// The primary side of an active/standby pair of sidecars. It mirrors the constraints it
+/// signs to the standby, and sends it heartbeats with the last slot it submitted.
+#[derive(Debug, Clone)]
+pub struct FailoverPrimary {
+    client: FailoverClient,
+    last_submitted_slot: Arc<AtomicU64>,
+}
+
+impl FailoverPrimary {
+    /// Create a new primary connecting to the standby at the given URL. The connection
+    /// is established lazily, so the standby doesn't need to be up first.
+    pub fn new(standby_url: &Url) -> eyre::Result<Self> {
+        let channel = Endpoint::from_shared(standby_url.to_string())?.connect_lazy();
+
+        Ok(Self {
+            client: FailoverClient::new(channel),
+            last_submitted_slot: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Mirror the constraints signed for a slot to the standby, in the background.
+    pub fn mirror(&self, slot: u64, constraints: &SignedConstraints) {
+        let signed_constraints = serde_json::to_vec(constraints).expect("serializable constraints");
+        let request = MirroredConstraints { slot, signed_constraints };
+        let mut client = self.client.clone();
+
+        tokio::spawn(async move {
+            for attempt in 1..=MIRROR_ATTEMPTS {
+                match client.mirror_constraints(request.clone()).await {
+                    Ok(_) => return,
+                    Err(err) => debug!(?err, slot, attempt, "Failed to mirror constraints"),
+                }
+            }
+            warn!(slot, "Failed to mirror constraints to the standby sidecar");
+        });
+    }
+
+    /// Record that the constraints of the slot were submitted to the relays, so that the
+    /// standby doesn't submit them again.
+    pub fn mark_submitted(&self, slot: u64) {
+        self.last_submitted_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// Send heartbeats to the standby at the given interval, until the task is aborted.
+    pub async fn send_heartbeats(self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut client = self.client.clone();
+        let mut connected = false;
+        loop {
+            interval.tick().await;
+
+            let heartbeat = Heartbeat {
+                timestamp_ms: now_ms(),
+                last_submitted_slot: self.last_submitted_slot.load(Ordering::Relaxed),
+            };
+            match client.heartbeat(heartbeat).await {
+                Ok(_) if !connected => {
+                    info!("Connected to the standby sidecar");
+                    connected = true;
+                }
+                Err(err) if connected => {
+                    warn!(?err, "Failed to send heartbeat to the standby sidecar");
+                    connected = false;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The standby side of an active/standby pair of sidecars. It keeps the constraints
+/// mirrored by the primary, and takes over their submission at the commitment deadline
+/// of their slot if the primary missed its heartbeats.
+#[derive(Debug, Clone)]
+pub struct StandbyState {
+    inner: Arc<Mutex<StandbyInner>>,
+    heartbeat_timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+struct StandbyInner {
+    /// The time the last heartbeat of the primary was received, if any
+    last_heartbeat: Option<Instant>,
+    /// The last slot whose constraints the primary submitted to the relays
+    last_submitted_slot: u64,
+    /// The constraints mirrored by the primary, by target slot
+    constraints: BTreeMap<u64, Vec<SignedConstraints>>,
+}
+
+impl StandbyState {
+    /// Create a new standby state, considering the primary down once it hasn't sent a
+    /// heartbeat for the given timeout.
+    pub fn new(heartbeat_timeout: Duration) -> Self {
+        Self { inner: Default::default(), heartbeat_timeout }
+    }
+
+    /// Record a heartbeat of the primary.
+    pub fn record_heartbeat(&self, heartbeat: &Heartbeat) {
+        let mut inner = self.inner.lock();
+        if inner.last_heartbeat.is_none() {
+            info!("Received the first heartbeat of the primary sidecar");
+        }
+        inner.last_heartbeat = Some(Instant::now());
+        inner.last_submitted_slot = inner.last_submitted_slot.max(heartbeat.last_submitted_slot);
+    }
+
+    /// Record constraints mirrored by the primary for a slot. Constraints mirrored twice,
+    /// e.g. on a retry, are only kept once.
+    pub fn record_constraints(&self, slot: u64, constraints: SignedConstraints) {
+        let mut inner = self.inner.lock();
+        let mirrored = inner.constraints.entry(slot).or_default();
+        if !mirrored.contains(&constraints) {
+            mirrored.push(constraints);
+        }
+    }
+
+    /// Returns true if the primary sent a heartbeat within the timeout.
+    pub fn primary_alive(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.last_heartbeat.is_some_and(|last| last.elapsed() < self.heartbeat_timeout)
+    }
+
+    /// Take the constraints mirrored for the slot, if the standby must take over their
+    /// submission: the primary missed its heartbeats and didn't submit them already.
+    ///
+    /// The constraints of the slot and the earlier ones are dropped either way.
+    pub fn take_over(&self, slot: u64) -> Option<Vec<SignedConstraints>> {
+        let alive = self.primary_alive();
+
+        let mut inner = self.inner.lock();
+        let later = inner.constraints.split_off(&(slot + 1));
+        let mut passed = std::mem::replace(&mut inner.constraints, later);
+
+        if alive || inner.last_submitted_slot >= slot {
+            return None;
+        }
+
+        passed.remove(&slot).filter(|constraints| !constraints.is_empty())
+    }
+}
+
+/// Start the failover server of the standby, which receives the heartbeats and the
+/// constraints of the primary until the shutdown future resolves.
+pub async fn start_failover_server(
+    state: StandbyState,
+    port: u16,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> eyre::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    info!(port, "Starting failover server...");
+
+    Server::builder()
+        .add_service(FailoverServer::new(state))
+        .serve_with_shutdown(addr, shutdown)
+        .await?;
+
+    Ok(())
+}
+
+/// The role of the sidecar in an active/standby pair, with its side of the failover channel.
+#[derive(Debug, Clone)]
+pub enum Failover {
+    /// The sidecar serves the commitments, and mirrors them to the standby.
+    Primary(FailoverPrimary),
+    /// The sidecar takes over the submission of the constraints if the primary goes down.
+    Standby(StandbyState),
+}
+
+impl Failover {
+    /// Returns the failover side of the sidecar for the given options, if any.
+    pub fn from_opts(opts: &FailoverOpts) -> eyre::Result<Option<Self>> {
+        match (opts.failover_role, opts.failover_standby_url.as_ref()) {
+            (None, _) => Ok(None),
+            (Some(FailoverRole::Primary), Some(url)) => {
+                Ok(Some(Self::Primary(FailoverPrimary::new(url)?)))
+            }
+            (Some(FailoverRole::Primary), None) => {
+                eyre::bail!("--failover-standby-url is required for the primary sidecar")
+            }
+            (Some(FailoverRole::Standby), _) => {
+                Ok(Some(Self::Standby(StandbyState::new(opts.heartbeat_timeout()))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standby_take_over() {
+        let standby = StandbyState::new(Duration::from_millis(100));
+        let constraints = SignedConstraints::default();
+
+        standby.record_constraints(10, constraints.clone());
+        standby.record_constraints(10, constraints.clone());
+        standby.record_constraints(11, constraints.clone());
+
+        // The primary is alive, so its constraints are left to it
+        standby.record_heartbeat(&Heartbeat { timestamp_ms: 0, last_submitted_slot: 9 });
+        assert!(standby.primary_alive());
+        assert_eq!(standby.take_over(10), None);
+
+        // The primary missed its heartbeats before the deadline of slot 11
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(!standby.primary_alive());
+        assert_eq!(standby.take_over(11), Some(vec![constraints]));
+
+        // Slots already submitted by the primary are never taken over
+        standby.record_constraints(12, SignedConstraints::default());
+        standby.record_heartbeat(&Heartbeat { timestamp_ms: 0, last_submitted_slot: 12 });
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(standby.take_over(12), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! The protobuf messages and gRPC service of the failover channel, as defined in
+//! `proto/bolt/failover/v1/failover.proto`.
+//!
+//! Like the lifecycle events, the types are written by hand rather than generated, so that
+//! building the sidecar doesn't require `protoc`. They must be kept in sync with the schema.
+
+use std::{convert::Infallible, future};
+
+use prost::Message;
+use tonic::{
+    body::{empty_body, BoxBody},
+    codec::ProstCodec,
+    codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError},
+    metadata::GRPC_CONTENT_TYPE,
+    server::{Grpc, NamedService, UnaryService},
+    transport::Channel,
+    Code, Request, Response, Status,
+};
+
+use super::StandbyState;
+
+/// The fully qualified name of the failover service.
+const SERVICE_NAME: &str = "bolt.failover.v1.Failover";
+/// The path of the `Heartbeat` method.
+const HEARTBEAT_PATH: &str = "/bolt.failover.v1.Failover/Heartbeat";
+/// The path of the `MirrorConstraints` method.
+const MIRROR_CONSTRAINTS_PATH: &str = "/bolt.failover.v1.Failover/MirrorConstraints";
+
+/// A heartbeat of the primary.
+#[derive(Clone, PartialEq, Message)]
+pub struct Heartbeat {
+    /// The time the heartbeat was sent, in unix milliseconds.
+    #[prost(uint64, tag = "1")]
+    pub timestamp_ms: u64,
+    /// The last slot whose constraints the primary submitted to the relays.
+    #[prost(uint64, tag = "2")]
+    pub last_submitted_slot: u64,
+}
+
+/// The acknowledgment of a heartbeat.
+#[derive(Clone, PartialEq, Message)]
+pub struct HeartbeatAck {}
+
+/// Constraints signed by the primary for a slot.
+#[derive(Clone, PartialEq, Message)]
+pub struct MirroredConstraints {
+    /// The target slot of the constraints.
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// The signed constraints, in the JSON encoding of the Constraints API.
+    #[prost(bytes = "vec", tag = "2")]
+    pub signed_constraints: Vec<u8>,
+}
+
+/// The acknowledgment of mirrored constraints.
+#[derive(Clone, PartialEq, Message)]
+pub struct MirrorAck {}
+
+/// The gRPC client of the failover service, used by the primary.
+#[derive(Debug, Clone)]
+pub struct FailoverClient {
+    inner: tonic::client::Grpc<Channel>,
+}
+
+impl FailoverClient {
+    /// Create a new client over the given channel to the standby.
+    pub fn new(channel: Channel) -> Self {
+        Self { inner: tonic::client::Grpc::new(channel) }
+    }
+
+    /// Signals that the primary is alive.
+    pub async fn heartbeat(&mut self, request: Heartbeat) -> Result<HeartbeatAck, Status> {
+        self.unary(request, HEARTBEAT_PATH).await
+    }
+
+    /// Mirrors constraints signed by the primary.
+    pub async fn mirror_constraints(
+        &mut self,
+        request: MirroredConstraints,
+    ) -> Result<MirrorAck, Status> {
+        self.unary(request, MIRROR_CONSTRAINTS_PATH).await
+    }
+
+    async fn unary<Req, Res>(&mut self, request: Req, path: &'static str) -> Result<Res, Status>
+    where
+        Req: Message + Send + Sync + 'static,
+        Res: Message + Default + Send + Sync + 'static,
+    {
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(format!("Standby is not ready: {e}")))?;
+
+        let path = http::uri::PathAndQuery::from_static(path);
+        let codec = ProstCodec::<Req, Res>::default();
+        let response = self.inner.unary(Request::new(request), path, codec).await?;
+
+        Ok(response.into_inner())
+    }
+}
+
+/// The gRPC server of the failover service, run by the standby.
+#[derive(Debug, Clone)]
+pub struct FailoverServer {
+    state: StandbyState,
+}
+
+impl FailoverServer {
+    /// Create a new server recording the heartbeats and constraints of the primary
+    /// in the given standby state.
+    pub fn new(state: StandbyState) -> Self {
+        Self { state }
+    }
+}
+
+impl NamedService for FailoverServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+impl<B> Service<http::Request<B>> for FailoverServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let state = self.state.clone();
+
+        match req.uri().path() {
+            HEARTBEAT_PATH => Box::pin(async move {
+                let method = Unary(move |heartbeat: Heartbeat| {
+                    state.record_heartbeat(&heartbeat);
+                    Ok(HeartbeatAck {})
+                });
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(method, req).await)
+            }),
+            MIRROR_CONSTRAINTS_PATH => Box::pin(async move {
+                let method = Unary(move |mirrored: MirroredConstraints| {
+                    let constraints = serde_json::from_slice(&mirrored.signed_constraints)
+                        .map_err(|e| Status::invalid_argument(e.to_string()))?;
+                    state.record_constraints(mirrored.slot, constraints);
+                    Ok(MirrorAck {})
+                });
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(method, req).await)
+            }),
+            _ => Box::pin(async move {
+                let mut response = http::Response::new(empty_body());
+                let headers = response.headers_mut();
+                headers.insert(Status::GRPC_STATUS, (Code::Unimplemented as i32).into());
+                headers.insert(http::header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+                Ok(response)
+            }),
+        }
+    }
+}
+
+/// Adapter of a synchronous handler to a unary gRPC method.
+struct Unary<F>(F);
+
+impl<F, Req, Res> UnaryService<Req> for Unary<F>
+where
+    F: FnMut(Req) -> Result<Res, Status>,
+{
+    type Response = Res;
+    type Future = future::Ready<Result<Response<Res>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        future::ready((self.0)(request.into_inner()).map(Response::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_wire_format() {
+        let heartbeat = Heartbeat { timestamp_ms: 1, last_submitted_slot: 2 };
+
+        // Field 1 varint, then field 2 varint
+        let bytes = heartbeat.encode_to_vec();
+        assert_eq!(bytes, vec![0x08, 0x01, 0x10, 0x02]);
+        assert_eq!(Heartbeat::decode(bytes.as_slice()).unwrap(), heartbeat);
+    }
+}
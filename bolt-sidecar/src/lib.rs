@@ -6,13 +6,27 @@
 /// All APIs in use by the sidecar.
 mod api;
 pub use api::{
+    admin::{start_admin_server, AdminServerConfig},
     builder::{start_builder_proxy_server, BuilderProxyConfig},
-    commitments,
+    codes::{ErrorCode, ERROR_CODE_HEADER},
+    commitments::{self, networks::NetworkRouter},
     spec::{BuilderApi, ConstraintsApi},
 };
 
 mod client;
-pub use client::{constraints_client::ConstraintsClient, rpc::RpcClient, BeaconClient};
+#[cfg(any(test, feature = "mock"))]
+pub use client::mock;
+pub use client::{
+    bolt_rpc::{BoltRpcClient, BoltRpcError, RetryPolicy},
+    constraints_client::ConstraintsClient,
+    delegations::{DelegationState, DelegationStatus, DelegationTracker, RelayDelegations},
+    fee_recipients::{FeeRecipientGuard, RelayFeeRecipients},
+    multiplexer::{RelayHealthState, RelayMultiplexer},
+    rpc::RpcClient,
+    submissions::{RelaySubmission, SlotSubmissions, SubmissionLog},
+    throttle::{RelayThrottle, RelayThrottleState},
+    BeaconClient,
+};
 
 /// Telemetry and metrics utilities
 pub mod telemetry;
@@ -21,19 +35,40 @@ pub mod telemetry;
 /// (To be refactored)
 mod common;
 
+/// Operational subcommands of the sidecar binary
+pub mod commands;
+pub use commands::Command;
+
 /// Driver for the sidecar, which manages the main event loop
 pub mod driver;
-pub use driver::SidecarDriver;
+pub use driver::{OperatorHandles, SidecarDriver};
+
+/// Programmatic entrypoint to configure and run the sidecar in-process
+pub mod sidecar;
+pub use sidecar::{SidecarBuilder, SidecarHandle};
+
+/// Active/standby failover between two sidecar instances
+pub mod failover;
+
+/// Integration with the service managers of the operating systems
+pub mod service;
+pub use service::SidecarHealth;
 
 /// Functionality for building local block templates that can
 /// be used as a fallback for proposers. It's also used to keep
 /// any intermediary state that is needed to simulate EVM execution
 pub mod builder;
-pub use builder::LocalBuilder;
+pub use builder::{LocalBuilder, TemplateReader, TemplateSnapshot};
 
 /// Configuration and command-line argument parsing
 mod config;
-pub use config::{ChainConfig, Opts};
+#[cfg(unix)]
+pub use config::spawn_sighup_reloader;
+pub use config::{
+    read_networks_file, spawn_whitelist_watcher, ChainConfig, ConfigChange, ConfigReloader,
+    LatencyBudgetOpts, LimitsOpts, MarginCurve, Opts, PricingOpts, RateLimitOpts, ReloadError,
+    ReloadSource, ReloadableConfig, ReservationOpts, WhitelistOpts,
+};
 
 /// Crypto utilities, including BLS and ECDSA
 pub mod crypto;
@@ -46,11 +81,23 @@ pub mod state;
 
 /// The signers available to the sidecar
 mod signer;
-pub use signer::{commit_boost::CommitBoostSigner, SignerBLS};
+pub use signer::{
+    commit_boost::CommitBoostSigner,
+    web3signer::{Web3Signer, Web3SignerTlsCredentials},
+    SignerBLS,
+};
 
 /// Utilities and contracts wrappers for interacting with the Bolt registry
 pub mod chain_io;
 
+/// In-process devnet with a mock beacon node and relay, to run the full commitment
+/// flow locally
+pub mod devnet;
+
+/// Synthetic workloads and time-sliced timings of the hot paths, to measure
+/// performance regressions
+pub mod bench;
+
 /// Utilities for testing
 #[cfg(test)]
 mod test_util;
@@ -0,0 +1,213 @@
+use alloy::{
+    primitives::Address,
+    signers::k256::sha2::{Digest, Sha256},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builder::signature::compute_signing_root,
+    crypto::{
+        bls::{verify_bls_signature, BLSSig},
+        SignableBLS,
+    },
+    ChainConfig,
+};
+
+use super::{delegation::SignedMessageAction, BlsPublicKey, SignedDelegation};
+
+/// Errors that can occur while verifying a commitment key attestation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AttestationError {
+    #[error("Invalid attestation signature by {0:?}")]
+    InvalidSignature(BlsPublicKey),
+    #[error("The attestation is signed by {0:?}, but no delegation to it is provided")]
+    MissingDelegation(BlsPublicKey),
+    #[error("The delegation does not delegate from the attested validator to the signer")]
+    DelegationMismatch,
+    #[error("Invalid delegation signature by {0:?}")]
+    InvalidDelegationSignature(BlsPublicKey),
+    #[error("The attestation binds {got}, expected {expected}")]
+    CommitmentSignerMismatch { expected: Address, got: Address },
+}
+
+/// A message binding the ECDSA key that signs the commitments of a sidecar
+/// to a validator it makes commitments on behalf of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentKeyMessage {
+    /// The public key of the validator.
+    pub validator_pubkey: BlsPublicKey,
+    /// The address of the ECDSA key signing the commitments.
+    pub commitment_signer: Address,
+}
+
+impl SignableBLS for CommitmentKeyMessage {
+    fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([SignedMessageAction::CommitmentKey as u8]);
+        hasher.update(self.validator_pubkey.to_vec());
+        hasher.update(self.commitment_signer);
+
+        hasher.finalize().into()
+    }
+}
+
+/// A [CommitmentKeyMessage] signed with the Commit-Boost domain, either by the validator
+/// key itself or by a delegatee of the validator, in which case the delegation is included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCommitmentKey {
+    /// The attested message.
+    pub message: CommitmentKeyMessage,
+    /// The BLS public key that signed the message.
+    pub signer: BlsPublicKey,
+    /// The signature of the message.
+    pub signature: BLSSig,
+    /// The delegation from the validator to the signer, if they differ.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<SignedDelegation>,
+}
+
+impl SignedCommitmentKey {
+    /// Verify the signature of the attestation on the given chain, along with the
+    /// delegation from the attested validator to the signer if they differ.
+    pub fn verify(&self, chain: &ChainConfig) -> Result<(), AttestationError> {
+        let domain = chain.commit_boost_domain();
+
+        let signing_root = compute_signing_root(self.message.digest(), domain);
+        if !verify_bls_signature(&self.signer, signing_root, self.signature.as_ref()) {
+            return Err(AttestationError::InvalidSignature(self.signer.clone()));
+        }
+
+        if self.signer == self.message.validator_pubkey {
+            return Ok(());
+        }
+
+        let Some(delegation) = &self.delegation else {
+            return Err(AttestationError::MissingDelegation(self.signer.clone()));
+        };
+
+        if delegation.message.validator_pubkey != self.message.validator_pubkey ||
+            delegation.message.delegatee_pubkey != self.signer
+        {
+            return Err(AttestationError::DelegationMismatch);
+        }
+
+        let signing_root = compute_signing_root(delegation.message.digest(), domain);
+        if !verify_bls_signature(
+            &delegation.message.validator_pubkey,
+            signing_root,
+            delegation.signature.as_ref(),
+        ) {
+            return Err(AttestationError::InvalidDelegationSignature(
+                delegation.message.validator_pubkey.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The identity of a sidecar: the ECDSA key signing its commitments, along with
+/// the attestations binding it to the validators it makes commitments on behalf of.
+///
+/// Users can check that a commitment signed by [SidecarIdentity::commitment_signer]
+/// actually binds the proposer of the target slot before relying on it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SidecarIdentity {
+    /// The address of the ECDSA key signing the commitments, identifying the sidecar.
+    pub commitment_signer: Address,
+    /// The chain ID the sidecar is running on.
+    pub chain_id: u64,
+    /// The attestations of the commitment key, one per validator.
+    pub attestations: Vec<SignedCommitmentKey>,
+}
+
+impl SidecarIdentity {
+    /// Verify all the attestations on the given chain, returning the public keys
+    /// of the validators bound to the commitment signer.
+    pub fn verify(&self, chain: &ChainConfig) -> Result<Vec<BlsPublicKey>, AttestationError> {
+        self.attestations
+            .iter()
+            .map(|attestation| {
+                let got = attestation.message.commitment_signer;
+                if got != self.commitment_signer {
+                    let expected = self.commitment_signer;
+                    return Err(AttestationError::CommitmentSignerMismatch { expected, got });
+                }
+
+                attestation.verify(chain)?;
+                Ok(attestation.message.validator_pubkey.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::BlsSecretKeyWrapper,
+        primitives::{BlsSignature, DelegationMessage},
+        signer::local::LocalSigner,
+    };
+
+    use super::*;
+
+    fn random_signer(chain: ChainConfig) -> LocalSigner {
+        LocalSigner::new(BlsSecretKeyWrapper::random().0, chain)
+    }
+
+    #[test]
+    fn test_verify_commitment_key_attestation() {
+        let chain = ChainConfig::holesky();
+        let commitment_signer = Address::random();
+        let validator = random_signer(chain);
+        let delegatee = random_signer(chain);
+
+        let message =
+            CommitmentKeyMessage { validator_pubkey: validator.pubkey(), commitment_signer };
+
+        // Signed by the validator key itself
+        let signature = validator.sign_commit_boost_root(message.digest()).unwrap();
+        let direct = SignedCommitmentKey {
+            message: message.clone(),
+            signer: validator.pubkey(),
+            signature,
+            delegation: None,
+        };
+        assert_eq!(direct.verify(&chain), Ok(()));
+        assert!(direct.verify(&ChainConfig::mainnet()).is_err());
+
+        // Signed by a delegatee of the validator
+        let signature = delegatee.sign_commit_boost_root(message.digest()).unwrap();
+        let mut delegated = SignedCommitmentKey {
+            message,
+            signer: delegatee.pubkey(),
+            signature,
+            delegation: None,
+        };
+        assert_eq!(
+            delegated.verify(&chain),
+            Err(AttestationError::MissingDelegation(delegatee.pubkey()))
+        );
+
+        let delegation = DelegationMessage::new(validator.pubkey(), delegatee.pubkey());
+        let signature = validator.sign_commit_boost_root(delegation.digest()).unwrap();
+        delegated.delegation = Some(SignedDelegation {
+            message: delegation,
+            signature: BlsSignature::try_from(signature.as_slice()).unwrap(),
+        });
+        assert_eq!(delegated.verify(&chain), Ok(()));
+
+        let identity = SidecarIdentity {
+            commitment_signer,
+            chain_id: chain.chain_id(),
+            attestations: vec![direct, delegated],
+        };
+        assert_eq!(identity.verify(&chain), Ok(vec![validator.pubkey(), validator.pubkey()]));
+
+        let other = SidecarIdentity { commitment_signer: Address::random(), ..identity };
+        assert!(matches!(
+            other.verify(&chain),
+            Err(AttestationError::CommitmentSignerMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use alloy::primitives::{keccak256, Address, Signature, B256};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    commitment::{deserialize_sig, serialize_sig, SignatureError},
+    Slot,
+};
+
+/// The domain tag of availability attestation digests, so that they can't be mistaken for
+/// the digest of a commitment or reservation signed by the same key.
+const AVAILABILITY_DOMAIN: &[u8] = b"bolt-availability";
+
+/// An attestation of the sidecar that it is live and accepting commitments for an upcoming
+/// proposal slot, with the limits and pricing that apply to them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Availability {
+    /// The chain ID the sidecar is running on.
+    pub chain_id: u64,
+    /// The proposal slot the sidecar accepts commitments for.
+    pub slot: Slot,
+    /// The max number of commitments accepted in the slot.
+    pub max_commitments: u64,
+    /// The gas of the slot still available to commitments when attesting.
+    pub remaining_gas: u64,
+    /// The min priority fee of the commitments, in wei.
+    pub min_priority_fee: u128,
+    /// The UNIX timestamp of the attestation, in seconds.
+    pub timestamp: u64,
+}
+
+impl Availability {
+    /// Returns the digest of the attestation, signed by the commitment key of the sidecar:
+    ///
+    /// digest = keccak256(
+    ///     bytes("bolt-availability") | be_bytes(chain_id) | be_bytes(slot) |
+    ///     be_bytes(max_commitments) | be_bytes(remaining_gas) | be_bytes(min_priority_fee) |
+    ///     be_bytes(timestamp)
+    /// )
+    pub fn digest(&self) -> B256 {
+        let mut data = Vec::with_capacity(AVAILABILITY_DOMAIN.len() + 56);
+        data.extend_from_slice(AVAILABILITY_DOMAIN);
+        data.extend_from_slice(&self.chain_id.to_be_bytes());
+        data.extend_from_slice(&self.slot.to_be_bytes());
+        data.extend_from_slice(&self.max_commitments.to_be_bytes());
+        data.extend_from_slice(&self.remaining_gas.to_be_bytes());
+        data.extend_from_slice(&self.min_priority_fee.to_be_bytes());
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+
+        keccak256(&data)
+    }
+}
+
+/// An [Availability] signed by the commitment key of the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedAvailability {
+    #[serde(flatten)]
+    pub availability: Availability,
+    #[serde(deserialize_with = "deserialize_sig", serialize_with = "serialize_sig")]
+    pub signature: Signature,
+}
+
+impl SignedAvailability {
+    /// Verifies that the attestation was signed by the given commitment signer.
+    pub fn verify(&self, commitment_signer: Address) -> Result<(), SignatureError> {
+        match self.signature.recover_address_from_prehash(&self.availability.digest()) {
+            Ok(recovered) if recovered == commitment_signer => Ok(()),
+            _ => Err(SignatureError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+
+    #[test]
+    fn test_availability_signature() {
+        let signer = PrivateKeySigner::random();
+        let availability = Availability {
+            chain_id: 17000,
+            slot: 100,
+            max_commitments: 128,
+            remaining_gas: 10_000_000,
+            min_priority_fee: 1_000_000_000,
+            timestamp: 1_700_000_000,
+        };
+        let signature = signer.sign_hash_sync(&availability.digest()).unwrap();
+        let signed = SignedAvailability { availability: availability.clone(), signature };
+        assert!(signed.verify(signer.address()).is_ok());
+        assert!(signed.verify(Address::ZERO).is_err());
+
+        // Attestations are bound to their slot
+        let forged = SignedAvailability {
+            availability: Availability { slot: 101, ..availability },
+            signature,
+        };
+        assert!(forged.verify(signer.address()).is_err());
+
+        let json = serde_json::to_value(&signed).unwrap();
+        assert_eq!(json["slot"], 100);
+        assert_eq!(serde_json::from_value::<SignedAvailability>(json).unwrap(), signed);
+    }
+}
@@ -1,16 +1,32 @@
 use serde::{de, Deserialize, Deserializer, Serialize};
-use std::str::FromStr;
+use std::{cmp::Ordering, str::FromStr};
 
-use alloy::primitives::{keccak256, Address, Signature, B256};
+use alloy::primitives::{Address, Signature, TxHash, B256};
+use bolt_primitives::InclusionDigest;
 
-use crate::crypto::SignerECDSA;
+use crate::{api::codes::ErrorCode, crypto::SignerECDSA};
 
-use super::{deserialize_txs, serialize_txs, FullTransaction, TransactionExt};
+use super::{
+    deserialize_tx, deserialize_txs, serialize_tx, serialize_txs, FullTransaction, PaymentVoucher,
+    TransactionExt,
+};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Invalid signature")]
 pub struct SignatureError;
 
+pub use bolt_primitives::commitment::{CommitmentType, COMMITMENT_DIGEST_VERSION};
+
+/// The encoding of a commitment request that a user signature was made over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestVersion {
+    /// `keccak256(tx_hash1 | ... | tx_hashN | le_bytes(slot))`, see [InclusionRequest::digest].
+    /// Deprecated for user signatures, which are still accepted for compatibility.
+    Legacy,
+    /// The canonical versioned encoding, see [InclusionRequest::signing_digest].
+    V1,
+}
+
 /// Commitment requests sent by users or RPC proxies to the sidecar.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
@@ -30,9 +46,119 @@ pub enum SignedCommitment {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct InclusionCommitment {
     #[serde(flatten)]
-    request: InclusionRequest,
+    pub(crate) request: InclusionRequest,
     #[serde(deserialize_with = "deserialize_sig", serialize_with = "serialize_sig")]
-    signature: Signature,
+    pub(crate) signature: Signature,
+    /// The outcome of each transaction of the original request, for partial commitments
+    /// to the valid prefix of a request. Empty if the whole request was committed to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) outcomes: Vec<TxOutcome>,
+}
+
+impl InclusionCommitment {
+    /// Returns the committed request.
+    pub fn request(&self) -> &InclusionRequest {
+        &self.request
+    }
+
+    /// Returns the outcome of each transaction of the original request, if partially
+    /// committed to.
+    pub fn outcomes(&self) -> &[TxOutcome] {
+        &self.outcomes
+    }
+
+    /// Returns the signature of the commitment by the sidecar.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Recovers the address of the commitment signer of the sidecar from the signature.
+    pub fn recover_signer(&self) -> Result<Address, SignatureError> {
+        self.signature
+            .recover_address_from_prehash(&self.request.digest())
+            .map_err(|_| SignatureError)
+    }
+
+    /// Returns the hashes of the committed transactions: all of them, or the accepted
+    /// ones if partially committed to.
+    pub fn committed_tx_hashes(&self) -> Vec<TxHash> {
+        if self.outcomes.is_empty() {
+            return self.request.txs.iter().map(|tx| *tx.hash()).collect();
+        }
+
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.status == TxStatus::Accepted)
+            .map(|outcome| outcome.hash)
+            .collect()
+    }
+
+    /// Sets the outcomes of the transactions of the original request.
+    pub fn with_outcomes(mut self, outcomes: Vec<TxOutcome>) -> Self {
+        self.outcomes = outcomes;
+        self
+    }
+}
+
+/// The status of a transaction of a request accepting partial commitment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    /// The transaction is committed to.
+    Accepted,
+    /// The transaction is invalid, and is not committed to.
+    Rejected,
+    /// The transaction follows a rejected one, and is not committed to so as to respect the
+    /// ordering of the request, e.g. of the nonces of a sender.
+    Skipped,
+}
+
+/// The outcome of a transaction of a request accepting partial commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutcome {
+    /// The hash of the transaction.
+    pub hash: TxHash,
+    /// Whether the transaction is committed to.
+    pub status: TxStatus,
+    /// The code of the error that rejected the transaction, if rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    /// The reason why the transaction was rejected, if rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl TxOutcome {
+    /// Returns the outcomes of the transactions of a request of which the first `accepted`
+    /// ones are committed to, the next one was rejected with the given error, and the
+    /// following ones are skipped.
+    pub fn for_prefix(
+        txs: &[FullTransaction],
+        accepted: usize,
+        code: ErrorCode,
+        reason: String,
+    ) -> Vec<Self> {
+        txs.iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                let hash = *tx.hash();
+                match i.cmp(&accepted) {
+                    Ordering::Less => {
+                        Self { hash, status: TxStatus::Accepted, code: None, reason: None }
+                    }
+                    Ordering::Equal => Self {
+                        hash,
+                        status: TxStatus::Rejected,
+                        code: Some(code),
+                        reason: Some(reason.clone()),
+                    },
+                    Ordering::Greater => {
+                        Self { hash, status: TxStatus::Skipped, code: None, reason: None }
+                    }
+                }
+            })
+            .collect()
+    }
 }
 
 impl From<SignedCommitment> for InclusionCommitment {
@@ -63,7 +189,11 @@ impl CommitmentRequest {
             CommitmentRequest::Inclusion(req) => {
                 let digest = req.digest();
                 let signature = signer.sign_hash(&digest).await?;
-                Ok(SignedCommitment::Inclusion(InclusionCommitment { request: req, signature }))
+                Ok(SignedCommitment::Inclusion(InclusionCommitment {
+                    request: req,
+                    signature,
+                    outcomes: Vec::new(),
+                }))
             }
         }
     }
@@ -80,7 +210,25 @@ impl CommitmentRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct InclusionRequest {
     /// The consensus slot number at which the transaction should be included.
+    /// For requests with a slot range, the first slot of the range.
     pub slot: u64,
+    /// The last slot of the range, for requests to include the transactions in any
+    /// proposal slot of the sidecar from `slot` up to this slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slot: Option<u64>,
+    /// Whether the transactions must be included at the top of the block, as the single
+    /// top-of-block bundle of the slot, which is auctioned among the competing requests.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub top: bool,
+    /// Whether the sidecar may commit to the longest valid prefix of the transactions if some
+    /// of them are invalid, instead of rejecting the whole request. Ignored for top-of-block
+    /// requests, whose bundle is atomic.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// Whether the request is a rollup batch, to be committed to in the cheapest proposal
+    /// slot of its range rather than the earliest. Only set from a [BatchInclusionRequest].
+    #[serde(skip)]
+    pub batch: bool,
     /// The transaction to be included.
     #[serde(deserialize_with = "deserialize_txs", serialize_with = "serialize_txs")]
     pub txs: Vec<FullTransaction>,
@@ -91,6 +239,82 @@ pub struct InclusionRequest {
     pub signature: Option<Signature>,
     #[serde(skip)]
     pub signer: Option<Address>,
+    /// The payment voucher of the request, if the sidecar requires one. It is signed
+    /// separately by the request signer, so it is not part of the request digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voucher: Option<PaymentVoucher>,
+}
+
+/// Request of a rollup sequencer to include a batch transaction, carrying its blobs or
+/// calldata, in the cheapest proposal slot of the sidecar within a maximum delay.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchInclusionRequest {
+    /// The earliest slot at which the batch can be included.
+    pub slot: u64,
+    /// The maximum number of slots after `slot` that the inclusion can be delayed by.
+    pub max_delay: u64,
+    /// The batch transaction.
+    #[serde(deserialize_with = "deserialize_tx", serialize_with = "serialize_tx")]
+    pub tx: FullTransaction,
+}
+
+impl From<BatchInclusionRequest> for InclusionRequest {
+    /// Returns the inclusion request of the batch over the slot range of its delay.
+    fn from(request: BatchInclusionRequest) -> Self {
+        Self {
+            slot: request.slot,
+            max_slot: Some(request.slot.saturating_add(request.max_delay)),
+            top: false,
+            partial: false,
+            batch: true,
+            txs: vec![request.tx],
+            signature: None,
+            signer: None,
+            voucher: None,
+        }
+    }
+}
+
+/// Request to include transactions previously uploaded to the sidecar, referenced by hash
+/// rather than carried in full. Once resolved, it is signed and committed to exactly like
+/// the equivalent [InclusionRequest], whose digests only cover the transaction hashes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InclusionRequestByHash {
+    /// The consensus slot number at which the transactions should be included.
+    /// For requests with a slot range, the first slot of the range.
+    pub slot: u64,
+    /// The last slot of the range, for requests with a slot range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slot: Option<u64>,
+    /// Whether the transactions must be included at the top of the block.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub top: bool,
+    /// Whether the sidecar may commit to the longest valid prefix of the transactions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// The hashes of the uploaded transactions to be included, in order.
+    pub tx_hashes: Vec<TxHash>,
+    /// The payment voucher of the request, if the sidecar requires one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voucher: Option<PaymentVoucher>,
+}
+
+impl InclusionRequestByHash {
+    /// Returns the inclusion request of the given transactions, resolved from the hashes of
+    /// the request in the same order.
+    pub fn into_request(self, txs: Vec<FullTransaction>) -> InclusionRequest {
+        InclusionRequest {
+            slot: self.slot,
+            max_slot: self.max_slot,
+            top: self.top,
+            partial: self.partial,
+            batch: false,
+            txs,
+            signature: None,
+            signer: None,
+            voucher: self.voucher,
+        }
+    }
 }
 
 impl InclusionRequest {
@@ -166,11 +390,34 @@ impl InclusionRequest {
         })
     }
 
+    /// Returns the total tip paid by the transactions of this request at the given basefee,
+    /// as their effective tip per gas times their gas limit.
+    pub fn total_tip(&self, base_fee: u128) -> u128 {
+        self.txs
+            .iter()
+            .map(|tx| {
+                let tip = tx.effective_tip_per_gas(base_fee).unwrap_or_default();
+                tip.saturating_mul(tx.gas_limit() as u128)
+            })
+            .fold(0, u128::saturating_add)
+    }
+
     /// Returns the total gas limit of all transactions in this request.
     pub fn gas_limit(&self) -> u64 {
         self.txs.iter().map(|tx| tx.gas_limit()).sum()
     }
 
+    /// Returns true if the longest valid prefix of the transactions can be committed to when
+    /// some of them are invalid.
+    pub fn accepts_partial(&self) -> bool {
+        self.partial && !self.top && self.txs.len() > 1
+    }
+
+    /// Returns the slots at which the transactions can be included.
+    pub fn candidate_slots(&self) -> std::ops::RangeInclusive<u64> {
+        self.slot..=self.max_slot.unwrap_or(self.slot)
+    }
+
     /// Returns the transaction signer.
     pub fn signer(&self) -> Option<Address> {
         self.signer
@@ -196,7 +443,7 @@ impl InclusionRequest {
     }
 }
 
-fn deserialize_sig<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+pub(crate) fn deserialize_sig<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
     T: FromStr,
@@ -206,7 +453,10 @@ where
     T::from_str(s.trim_start_matches("0x")).map_err(de::Error::custom)
 }
 
-fn serialize_sig<S: serde::Serializer>(sig: &Signature, serializer: S) -> Result<S::Ok, S::Error> {
+pub(crate) fn serialize_sig<S: serde::Serializer>(
+    sig: &Signature,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
     let parity = sig.v();
     // As bytes encodes the parity as 27/28, need to change that.
     let mut bytes = sig.as_bytes();
@@ -215,19 +465,72 @@ fn serialize_sig<S: serde::Serializer>(sig: &Signature, serializer: S) -> Result
 }
 
 impl InclusionRequest {
-    /// Returns the digest of the request.
+    /// Returns the legacy digest of the request.
     /// digest = keccak256(bytes(tx_hash1) | bytes(tx_hash2) | ... | le_bytes(target_slot))
+    ///
+    /// For requests with a slot range, `le_bytes(max_slot)` is appended so that the signed
+    /// commitment encodes the whole range.
+    ///
+    /// NOTE: this is also the commitment ID verified by the `BoltChallenger` contract, so
+    /// the commitments signed by the sidecar are still made over it.
     pub fn digest(&self) -> B256 {
-        let mut data = Vec::new();
-        // First field is the concatenation of all the transaction hashes
-        data.extend_from_slice(
-            &self.txs.iter().map(|tx| tx.hash().as_slice()).collect::<Vec<_>>().concat(),
-        );
+        let tx_hashes = self.txs.iter().map(|tx| tx.hash());
+        bolt_primitives::legacy_inclusion_digest(tx_hashes, self.slot, self.max_slot).into()
+    }
 
-        // Second field is the little endian encoding of the target slot
-        data.extend_from_slice(&self.slot.to_le_bytes());
+    /// Returns the canonical digest of the request on the given chain, signed by users:
+    ///
+    /// digest = keccak256(
+    ///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | be_bytes(target_slot) |
+    ///     bytes(tx_hash1) | bytes(tx_hash2) | ...
+    /// )
+    ///
+    /// which is equivalent to Solidity's
+    /// `keccak256(abi.encodePacked(uint8, uint8, uint64, uint64, bytes32[]))`.
+    ///
+    /// For requests with a slot range, the commitment type is [CommitmentType::InclusionBySlot]
+    /// and `be_bytes(max_slot)` follows the target slot. For top-of-block requests, it is
+    /// [CommitmentType::InclusionTopOfBlock]. For rollup batches, it is
+    /// [CommitmentType::InclusionBatch] and `be_bytes(max_delay)` follows the target slot.
+    /// For requests accepting partial commitment, `u8(1)` follows the transaction hashes.
+    pub fn signing_digest(&self, chain_id: u64) -> B256 {
+        let digest = InclusionDigest {
+            chain_id,
+            slot: self.slot,
+            max_slot: self.max_slot,
+            top: self.top,
+            batch: self.batch,
+            partial: self.partial,
+        };
+
+        digest.signing_digest(self.txs.iter().map(|tx| tx.hash())).into()
+    }
 
-        keccak256(&data)
+    /// Verifies that the signature over the request was made by the given signer, over
+    /// the canonical digest on the given chain or else over the legacy digest.
+    /// Returns the encoding that was signed.
+    ///
+    /// The legacy digest doesn't encode the top-of-block, partial and batch flags, so such
+    /// requests must be signed over the canonical digest.
+    pub fn verify_signature(
+        &self,
+        signature: &Signature,
+        signer: Address,
+        chain_id: u64,
+    ) -> Result<DigestVersion, SignatureError> {
+        let recover = |digest: B256| signature.recover_address_from_prehash(&digest).ok();
+
+        if recover(self.signing_digest(chain_id)) == Some(signer) {
+            Ok(DigestVersion::V1)
+        } else if !self.top &&
+            !self.partial &&
+            !self.batch &&
+            recover(self.digest()) == Some(signer)
+        {
+            Ok(DigestVersion::Legacy)
+        } else {
+            Err(SignatureError)
+        }
     }
 }
 
@@ -266,9 +569,19 @@ mod tests {
     use alloy::{
         hex,
         primitives::{Address, Signature},
+        signers::{local::PrivateKeySigner, SignerSync},
     };
 
-    use super::{CommitmentRequest, InclusionRequest};
+    use crate::api::codes::ErrorCode;
+
+    use super::{
+        BatchInclusionRequest, CommitmentRequest, DigestVersion, InclusionRequest,
+        InclusionRequestByHash, TxOutcome, TxStatus,
+    };
+
+    /// Shared with the `bolt` CLI, which must sign the same digest.
+    const SIGNING_DIGEST_VECTOR: &str =
+        "b2804102e8bba61f18692361dbf738ac078ca871a8680ee33fb473f633e27e87";
 
     #[test]
     fn test_create_digest() {
@@ -293,6 +606,170 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_signing_digest() {
+        let json_req = r#"{
+            "slot": 633067,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        let digest = req.signing_digest(17000);
+        assert_eq!(hex::encode(digest.as_slice()), SIGNING_DIGEST_VECTOR);
+
+        // The digest is bound to the chain and to the legacy encoding
+        assert_ne!(req.signing_digest(1), digest);
+        assert_ne!(req.digest(), digest);
+    }
+
+    #[test]
+    fn test_verify_signature_versions() {
+        let json_req = r#"{
+            "slot": 633067,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        let signer = PrivateKeySigner::random();
+
+        let sig = signer.sign_hash_sync(&req.signing_digest(17000)).unwrap();
+        assert_eq!(req.verify_signature(&sig, signer.address(), 17000).unwrap(), DigestVersion::V1);
+        // Signed for another chain
+        assert!(req.verify_signature(&sig, signer.address(), 1).is_err());
+
+        let sig = signer.sign_hash_sync(&req.digest()).unwrap();
+        assert_eq!(
+            req.verify_signature(&sig, signer.address(), 17000).unwrap(),
+            DigestVersion::Legacy
+        );
+        assert!(req.verify_signature(&sig, Address::ZERO, 17000).is_err());
+    }
+
+    #[test]
+    fn test_slot_range_digests() {
+        let json_req = r#"{
+            "slot": 633067,
+            "max_slot": 633099,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        assert_eq!(req.candidate_slots(), 633067..=633099);
+
+        // Both digests encode the range, so they differ from the single slot request
+        let single = InclusionRequest { max_slot: None, ..req.clone() };
+        assert_ne!(req.signing_digest(17000), single.signing_digest(17000));
+        assert_ne!(req.digest(), single.digest());
+
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash_sync(&req.signing_digest(17000)).unwrap();
+        assert_eq!(req.verify_signature(&sig, signer.address(), 17000).unwrap(), DigestVersion::V1);
+        assert!(single.verify_signature(&sig, signer.address(), 17000).is_err());
+
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["max_slot"], 633099);
+        assert!(serde_json::to_value(&single).unwrap().get("max_slot").is_none());
+    }
+
+    #[test]
+    fn test_top_of_block_digests() {
+        let json_req = r#"{
+            "slot": 633067,
+            "top": true,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        assert!(req.top);
+
+        let regular = InclusionRequest { top: false, ..req.clone() };
+        assert_ne!(req.signing_digest(17000), regular.signing_digest(17000));
+        assert!(serde_json::to_value(&regular).unwrap().get("top").is_none());
+
+        // A signature over the legacy digest could be replayed as a top-of-block request
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash_sync(&req.digest()).unwrap();
+        assert!(req.verify_signature(&sig, signer.address(), 17000).is_err());
+        assert!(regular.verify_signature(&sig, signer.address(), 17000).is_ok());
+    }
+
+    #[test]
+    fn test_partial_digests() {
+        let json_req = r#"{
+            "slot": 633067,
+            "partial": true,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        assert!(req.partial);
+
+        let atomic = InclusionRequest { partial: false, ..req.clone() };
+        assert_ne!(req.signing_digest(17000), atomic.signing_digest(17000));
+        assert!(serde_json::to_value(&atomic).unwrap().get("partial").is_none());
+
+        // A signature over the legacy digest could be replayed as a partial request
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash_sync(&req.digest()).unwrap();
+        assert!(req.verify_signature(&sig, signer.address(), 17000).is_err());
+        assert!(atomic.verify_signature(&sig, signer.address(), 17000).is_ok());
+
+        let outcomes = TxOutcome::for_prefix(
+            &[req.txs[0].clone(), req.txs[0].clone(), req.txs[0].clone()],
+            1,
+            ErrorCode::NonceConflict,
+            "nonce".to_string(),
+        );
+        let statuses = outcomes.iter().map(|o| o.status).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![TxStatus::Accepted, TxStatus::Rejected, TxStatus::Skipped]);
+        assert_eq!(outcomes[1].code, Some(ErrorCode::NonceConflict));
+    }
+
+    #[test]
+    fn test_batch_digests() {
+        let json_req = r#"{
+            "slot": 633067,
+            "max_delay": 8,
+            "tx": "0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"
+        }"#;
+        let batch: BatchInclusionRequest = serde_json::from_str(json_req).unwrap();
+        let req = InclusionRequest::from(batch);
+        assert!(req.batch);
+        assert_eq!(req.candidate_slots(), 633067..=633075);
+
+        // The batch is signed over its delay, and differs from the slot range request
+        let ranged = InclusionRequest { batch: false, ..req.clone() };
+        assert_ne!(req.signing_digest(17000), ranged.signing_digest(17000));
+
+        let signer = PrivateKeySigner::random();
+        let sig = signer.sign_hash_sync(&req.digest()).unwrap();
+        assert!(req.verify_signature(&sig, signer.address(), 17000).is_err());
+        let sig = signer.sign_hash_sync(&req.signing_digest(17000)).unwrap();
+        assert_eq!(req.verify_signature(&sig, signer.address(), 17000).unwrap(), DigestVersion::V1);
+    }
+
+    #[test]
+    fn test_by_hash_digests() {
+        let json_req = r#"{
+            "slot": 633067,
+            "max_slot": 633070,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+
+        let by_hash = InclusionRequestByHash {
+            slot: req.slot,
+            max_slot: req.max_slot,
+            top: false,
+            partial: false,
+            tx_hashes: req.txs.iter().map(|tx| *tx.hash()).collect(),
+            voucher: None,
+        };
+        let json = serde_json::to_value(&by_hash).unwrap();
+        assert_eq!(serde_json::from_value::<InclusionRequestByHash>(json).unwrap(), by_hash);
+
+        // A request resolved from the hashes is signed over the same digest
+        let resolved = by_hash.into_request(req.txs.clone());
+        assert_eq!(resolved, req);
+        assert_eq!(resolved.signing_digest(17000), req.signing_digest(17000));
+    }
+
     #[test]
     fn test_deserialize_inclusion_request() {
         let json_req = r#"{
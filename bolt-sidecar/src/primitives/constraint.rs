@@ -1,4 +1,3 @@
-use alloy::signers::k256::sha2::{Digest, Sha256};
 use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
 use serde::{Deserialize, Serialize};
 
@@ -13,7 +12,7 @@ pub type BatchedSignedConstraints = Vec<SignedConstraints>;
 /// A container for a list of constraints and the signature of the proposer sidecar.
 ///
 /// Reference: https://chainbound.github.io/bolt-docs/api/builder#constraints
-#[derive(Serialize, Default, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
 pub struct SignedConstraints {
     /// The constraints that need to be signed.
     pub message: ConstraintsMessage,
@@ -33,6 +32,11 @@ pub struct ConstraintsMessage {
     /// Indicates whether these constraints are only valid on the top of the block.
     /// NOTE: Per slot, only 1 top-of-block bundle is valid.
     pub top: bool,
+    /// The position in the block of the first transaction of a top-of-block bundle, the
+    /// others taking the next positions in order. Bundles without an index start at the top
+    /// of the block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
     /// The constraints that need to be signed.
     #[serde(deserialize_with = "deserialize_txs", serialize_with = "serialize_txs")]
     pub transactions: Vec<FullTransaction>,
@@ -43,36 +47,42 @@ impl ConstraintsMessage {
     pub fn build(pubkey: BlsPublicKey, request: InclusionRequest) -> Self {
         let transactions = request.txs;
 
-        Self { pubkey, slot: request.slot, top: false, transactions }
+        Self { pubkey, slot: request.slot, top: request.top, index: None, transactions }
+    }
+
+    /// Builds the constraints message of the top-of-block bundle of a request, starting at the
+    /// given position in the block or at its top if None.
+    pub fn top_of_block(
+        pubkey: BlsPublicKey,
+        request: &InclusionRequest,
+        index: Option<u64>,
+    ) -> Self {
+        Self { pubkey, slot: request.slot, top: true, index, transactions: request.txs.clone() }
     }
 
     /// Builds a constraints message from a single transaction.
     pub fn from_transaction(pubkey: BlsPublicKey, slot: u64, transaction: FullTransaction) -> Self {
-        Self { pubkey, slot, top: false, transactions: vec![transaction] }
+        Self { pubkey, slot, top: false, index: None, transactions: vec![transaction] }
     }
 }
 
 impl SignableBLS for ConstraintsMessage {
     fn digest(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(self.pubkey.to_vec());
-        hasher.update(self.slot.to_le_bytes());
-        hasher.update((self.top as u8).to_le_bytes());
-
-        for tx in &self.transactions {
-            hasher.update(tx.hash());
-        }
-
-        hasher.finalize().into()
+        bolt_primitives::constraints_digest(
+            &self.pubkey,
+            self.slot,
+            self.top,
+            self.index,
+            self.transactions.iter().map(|tx| tx.hash()),
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::signer::local::LocalSigner;
+    use crate::{signer::local::LocalSigner, test_util::test_legacy_transaction};
 
     use super::*;
-    use alloy::primitives::bytes;
     use blst::min_pk::Signature as BlsSignature;
     use rand::{rngs::ThreadRng, Rng};
 
@@ -101,7 +111,7 @@ mod tests {
         let transactions = random_constraints(1); // Generate 'n' random constraints
 
         // Create a random `ConstraintsMessage`
-        let message = ConstraintsMessage { pubkey, slot, top, transactions };
+        let message = ConstraintsMessage { pubkey, slot, top, index: None, transactions };
 
         // Compute tree hash root
         let digest = SignableBLS::digest(&message);
@@ -121,7 +131,7 @@ mod tests {
         let transactions = random_constraints(2); // Generate 'n' random constraints
 
         // Create a random `ConstraintsMessage`
-        let message = ConstraintsMessage { pubkey, slot, top, transactions };
+        let message = ConstraintsMessage { pubkey, slot, top, index: None, transactions };
 
         // Serialize the `ConstraintsMessage` to JSON
         let json = serde_json::to_string(&message).unwrap();
@@ -137,8 +147,7 @@ mod tests {
     fn test_constraints_signature_roundtrip() {
         let signer = LocalSigner::random();
 
-        let tx_bytes = bytes!("f8678085019dc6838082520894deaddeaddeaddeaddeaddeaddeaddeaddeaddead38808360306ca06664c078fa60bd3ece050903dd295949908dd9686ec8871fa558f868e031cd39a00ed4f0b122b32b73f19230fabe6a726e2d07f84eda5beaa42a1ae1271bdee39f").to_vec();
-        let tx = FullTransaction::decode_enveloped(tx_bytes.as_slice()).unwrap();
+        let tx = test_legacy_transaction();
 
         let constraint = ConstraintsMessage::from_transaction(signer.pubkey(), 165, tx);
 
@@ -150,4 +159,30 @@ mod tests {
         let blst_sig = BlsSignature::from_bytes(signed_constraints.signature.as_ref()).unwrap();
         assert!(signer.verify_commit_boost_root(digest, &blst_sig).is_ok());
     }
+
+    #[test]
+    fn test_indexed_bundle() {
+        let request = InclusionRequest {
+            slot: 165,
+            max_slot: None,
+            top: true,
+            partial: false,
+            batch: false,
+            txs: random_constraints(2),
+            signature: None,
+            signer: None,
+            voucher: None,
+        };
+        let unindexed = ConstraintsMessage::top_of_block(BlsPublicKey::default(), &request, None);
+        let indexed = ConstraintsMessage::top_of_block(BlsPublicKey::default(), &request, Some(2));
+
+        // The index is only serialized when set, and is part of the signed digest
+        let json = serde_json::to_value(&unindexed).unwrap();
+        assert!(json.get("index").is_none());
+        assert_eq!(serde_json::to_value(&indexed).unwrap()["index"], 2);
+        assert_ne!(unindexed.digest(), indexed.digest());
+
+        let json = serde_json::to_string(&indexed).unwrap();
+        assert_eq!(serde_json::from_str::<ConstraintsMessage>(&json).unwrap(), indexed);
+    }
 }
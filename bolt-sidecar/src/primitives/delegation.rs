@@ -1,49 +1,112 @@
 use std::{fs, path::PathBuf};
 
-use alloy::signers::k256::sha2::{Digest, Sha256};
-use ethereum_consensus::crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature};
+use ethereum_consensus::{
+    crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature},
+    ssz::prelude::{ssz_rs, SimpleSerialize},
+};
 
 use crate::crypto::SignableBLS;
 
-/// Event types that can be emitted by the validator pubkey to
-/// signal some action on the Bolt protocol.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
-pub enum SignedMessageAction {
-    /// Signal delegation of a validator pubkey to a delegatee pubkey.
-    Delegation,
-    /// Signal revocation of a previously delegated pubkey.
-    Revocation,
-}
+pub use bolt_primitives::delegation::{SignedMessageAction, INCLUSION_SCOPE, TOP_OF_BLOCK_SCOPE};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, SimpleSerialize, serde::Serialize, serde::Deserialize, PartialEq, Eq,
+)]
 pub struct SignedDelegation {
     pub message: DelegationMessage,
     pub signature: BlsSignature,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, SimpleSerialize, serde::Serialize, serde::Deserialize, PartialEq, Eq,
+)]
 pub struct DelegationMessage {
     action: u8,
     pub validator_pubkey: BlsPublicKey,
     pub delegatee_pubkey: BlsPublicKey,
+    /// The limits of the constraints the delegatee can sign. Unscoped by default.
+    #[serde(default, skip_serializing_if = "DelegationScope::is_unscoped")]
+    pub scope: DelegationScope,
 }
 
 impl DelegationMessage {
     /// Create a new delegation message.
     pub fn new(validator_pubkey: BlsPublicKey, delegatee_pubkey: BlsPublicKey) -> Self {
-        Self { action: SignedMessageAction::Delegation as u8, validator_pubkey, delegatee_pubkey }
+        Self {
+            action: SignedMessageAction::Delegation as u8,
+            validator_pubkey,
+            delegatee_pubkey,
+            scope: DelegationScope::default(),
+        }
+    }
+
+    /// Limits the constraints the delegatee can sign to the given scope.
+    pub fn with_scope(mut self, scope: DelegationScope) -> Self {
+        self.scope = scope;
+        self
     }
 }
 
 impl SignableBLS for DelegationMessage {
     fn digest(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update([self.action]);
-        hasher.update(self.validator_pubkey.to_vec());
-        hasher.update(self.delegatee_pubkey.to_vec());
+        bolt_primitives::delegation_digest(
+            self.action,
+            &self.validator_pubkey,
+            &self.delegatee_pubkey,
+            &self.scope.into(),
+        )
+    }
+}
 
-        hasher.finalize().into()
+/// The limits of the constraints a delegatee can sign on behalf of a validator, enforced by
+/// bolt-boost on the constraints submitted by the delegatee.
+///
+/// Each limit is disabled when zero, so that the default scope is unscoped. This mirrors
+/// [bolt_primitives::DelegationScope] with its SSZ encoding.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    SimpleSerialize,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+)]
+#[serde(default)]
+pub struct DelegationScope {
+    /// The max gas the delegatee can constrain per slot.
+    pub max_gas_per_slot: u64,
+    /// The bitmask of the commitment types the delegatee can sign constraints for, see
+    /// [INCLUSION_SCOPE] and [TOP_OF_BLOCK_SCOPE].
+    pub commitment_types: u8,
+    /// The last slot the delegatee can sign constraints for.
+    pub expiry_slot: u64,
+}
+
+impl DelegationScope {
+    /// Returns true if none of the limits is set.
+    pub fn is_unscoped(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if the delegation expired before the given slot.
+    pub fn is_expired(&self, slot: u64) -> bool {
+        bolt_primitives::DelegationScope::from(*self).is_expired(slot)
+    }
+
+    /// Returns true if the delegatee can sign constraints for the given slot, of the
+    /// top-of-block type or not.
+    pub fn allows(&self, slot: u64, top: bool) -> bool {
+        bolt_primitives::DelegationScope::from(*self).allows(slot, top)
+    }
+}
+
+impl From<DelegationScope> for bolt_primitives::DelegationScope {
+    fn from(scope: DelegationScope) -> Self {
+        let DelegationScope { max_gas_per_slot, commitment_types, expiry_slot } = scope;
+        Self { max_gas_per_slot, commitment_types, expiry_slot }
     }
 }
 
@@ -60,13 +123,17 @@ pub fn read_signed_delegations_from_file(
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, SimpleSerialize, serde::Serialize, serde::Deserialize, PartialEq, Eq,
+)]
 pub struct SignedRevocation {
     pub message: RevocationMessage,
     pub signature: BlsSignature,
 }
 
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, SimpleSerialize, serde::Serialize, serde::Deserialize, PartialEq, Eq,
+)]
 pub struct RevocationMessage {
     action: u8,
     pub validator_pubkey: BlsPublicKey,
@@ -82,12 +149,11 @@ impl RevocationMessage {
 
 impl SignableBLS for RevocationMessage {
     fn digest(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update([self.action]);
-        hasher.update(self.validator_pubkey.to_vec());
-        hasher.update(self.delegatee_pubkey.to_vec());
-
-        hasher.finalize().into()
+        bolt_primitives::revocation_digest(
+            self.action,
+            &self.validator_pubkey,
+            &self.delegatee_pubkey,
+        )
     }
 }
 
@@ -95,13 +161,39 @@ impl SignableBLS for RevocationMessage {
 mod tests {
     use std::path::PathBuf;
 
+    use super::*;
+
+    #[test]
+    fn test_delegation_scope() {
+        let message = DelegationMessage::new(BlsPublicKey::default(), BlsPublicKey::default());
+        let unscoped = message.digest();
+
+        // The scope is part of the digest only when set
+        let scoped = message.clone().with_scope(DelegationScope {
+            commitment_types: INCLUSION_SCOPE,
+            expiry_slot: 100,
+            ..Default::default()
+        });
+        assert_eq!(message.clone().with_scope(DelegationScope::default()).digest(), unscoped);
+        assert_ne!(scoped.digest(), unscoped);
+
+        assert!(scoped.scope.allows(100, false));
+        assert!(!scoped.scope.allows(100, true));
+        assert!(!scoped.scope.allows(101, false));
+        assert!(message.scope.allows(u64::MAX, true));
+
+        // Unscoped delegations are serialized as before scopes were introduced
+        let json = serde_json::to_value(&message).unwrap();
+        assert!(json.get("scope").is_none());
+    }
+
     #[test]
     fn test_read_signed_delegations_from_file() {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("test_data/delegations.json");
 
-        let delegations = super::read_signed_delegations_from_file(&path)
-            .expect("Failed to read delegations from file");
+        let delegations =
+            read_signed_delegations_from_file(&path).expect("Failed to read delegations from file");
 
         assert_eq!(delegations.len(), 1);
         assert_eq!(
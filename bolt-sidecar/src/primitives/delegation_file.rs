@@ -0,0 +1,232 @@
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lighthouse_eth2_keystore::{
+    decrypt, default_kdf, encrypt,
+    json_keystore::{
+        Aes128Ctr, ChecksumModule, Cipher, CipherModule, Crypto, EmptyMap, EmptyString, KdfModule,
+        Sha256Checksum,
+    },
+    IV_SIZE, SALT_SIZE,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::ChainConfig;
+
+use super::{read_signed_delegations_from_file, SignedDelegation, SignedRevocation};
+
+/// The current version of the delegation file format.
+pub const DELEGATION_FILE_VERSION: u64 = 1;
+
+/// Error reading or writing a delegation file.
+#[derive(Debug, thiserror::Error)]
+pub enum DelegationFileError {
+    #[error("failed to access the delegation file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse the delegation file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported delegation file version {0}, expected {DELEGATION_FILE_VERSION}")]
+    UnsupportedVersion(u64),
+    #[error("delegation file is signed for {found}, but the configured chain is {expected}")]
+    WrongChain { expected: String, found: String },
+    #[error("delegation file is encrypted, but no password was provided")]
+    MissingPassword,
+    #[error("failed to encrypt or decrypt the delegation file: {0:?}")]
+    Crypto(lighthouse_eth2_keystore::Error),
+}
+
+/// The signed messages stored in a delegation file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedMessages {
+    /// The signed delegations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<SignedDelegation>,
+    /// The signed revocations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revocations: Vec<SignedRevocation>,
+}
+
+/// A file of signed delegations and revocations, bound to the chain they are signed for.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "chain": "holesky",
+///   "created_at": 1731000000,
+///   "delegations": [{ "message": { "action": 0, ... }, "signature": "0x..." }],
+///   "revocations": []
+/// }
+/// ```
+///
+/// When encrypted, the messages are replaced by an `encrypted` field holding the
+/// EIP-2335 `crypto` module of their JSON serialization, which can only be read with the
+/// password of the file. The metadata is always kept in plaintext.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DelegationFile {
+    /// The version of the file format.
+    pub version: u64,
+    /// The name of the chain the messages are signed for, e.g. "mainnet".
+    pub chain: String,
+    /// The UNIX timestamp in seconds at which the file was created.
+    pub created_at: u64,
+    #[serde(flatten)]
+    messages: SignedMessages,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted: Option<Crypto>,
+}
+
+impl DelegationFile {
+    /// Create a new plaintext file with the given messages, signed for the given chain.
+    pub fn new(chain: &ChainConfig, messages: SignedMessages) -> Self {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        Self {
+            version: DELEGATION_FILE_VERSION,
+            chain: chain.name().to_string(),
+            created_at,
+            messages,
+            encrypted: None,
+        }
+    }
+
+    /// Returns true if the messages of the file are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted.is_some()
+    }
+
+    /// Encrypt the messages of the file with the given password, using the default
+    /// EIP-2335 key derivation function and cipher.
+    pub fn encrypt(mut self, password: &[u8]) -> Result<Self, DelegationFileError> {
+        let plain_text = serde_json::to_vec(&std::mem::take(&mut self.messages))?;
+
+        let salt = rand::thread_rng().gen::<[u8; SALT_SIZE]>();
+        let iv = rand::thread_rng().gen::<[u8; IV_SIZE]>().to_vec().into();
+        let (kdf, cipher) = (default_kdf(salt.to_vec()), Cipher::Aes128Ctr(Aes128Ctr { iv }));
+
+        let (cipher_text, checksum) =
+            encrypt(&plain_text, password, &kdf, &cipher).map_err(DelegationFileError::Crypto)?;
+
+        self.encrypted = Some(Crypto {
+            kdf: KdfModule { function: kdf.function(), params: kdf, message: EmptyString },
+            checksum: ChecksumModule {
+                function: Sha256Checksum::function(),
+                params: EmptyMap,
+                message: checksum.to_vec().into(),
+            },
+            cipher: CipherModule {
+                function: cipher.function(),
+                params: cipher,
+                message: cipher_text.into(),
+            },
+        });
+
+        Ok(self)
+    }
+
+    /// Returns the messages of the file, decrypting them with the password if needed.
+    pub fn into_messages(
+        self,
+        password: Option<&[u8]>,
+    ) -> Result<SignedMessages, DelegationFileError> {
+        let Some(crypto) = self.encrypted else { return Ok(self.messages) };
+        let password = password.ok_or(DelegationFileError::MissingPassword)?;
+
+        let plain_text = decrypt(password, &crypto).map_err(DelegationFileError::Crypto)?;
+        Ok(serde_json::from_slice(plain_text.as_bytes())?)
+    }
+
+    /// Read a file, checking that it is signed for the given chain.
+    pub fn read(path: &Path, chain: &ChainConfig) -> Result<Self, DelegationFileError> {
+        let file: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if file.version != DELEGATION_FILE_VERSION {
+            return Err(DelegationFileError::UnsupportedVersion(file.version));
+        }
+
+        if file.chain != chain.name() {
+            return Err(DelegationFileError::WrongChain {
+                expected: chain.name().to_string(),
+                found: file.chain,
+            });
+        }
+
+        Ok(file)
+    }
+
+    /// Write the file to the given path as pretty-printed JSON.
+    pub fn write(&self, path: &Path) -> Result<(), DelegationFileError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Read the signed delegations of a delegation file signed for the given chain, decrypting
+/// them with the password if needed.
+///
+/// Files in the legacy format, a plain JSON array of signed delegations, are still
+/// accepted, but their chain can't be checked.
+pub fn read_delegations_file(
+    path: &Path,
+    chain: &ChainConfig,
+    password: Option<&[u8]>,
+) -> eyre::Result<Vec<SignedDelegation>> {
+    let contents = fs::read_to_string(path)?;
+    if contents.trim_start().starts_with('[') {
+        warn!(path = %path.display(), "Reading delegations in the legacy format, their chain can't be checked");
+        return read_signed_delegations_from_file(&path.to_path_buf());
+    }
+
+    let file = DelegationFile::read(path, chain)?;
+    Ok(file.into_messages(password)?.delegations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_delegation_file_roundtrip() {
+        let legacy = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/delegations.json");
+        let delegations = read_signed_delegations_from_file(&legacy).unwrap();
+        let messages = SignedMessages { delegations, revocations: Vec::new() };
+
+        let dir = std::env::temp_dir().join(format!("bolt-delegations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (plain, encrypted) = (dir.join("plain.json"), dir.join("encrypted.json"));
+
+        let chain = ChainConfig::holesky();
+        DelegationFile::new(&chain, messages.clone()).write(&plain).unwrap();
+        DelegationFile::new(&chain, messages.clone())
+            .encrypt(b"password")
+            .unwrap()
+            .write(&encrypted)
+            .unwrap();
+
+        assert_eq!(read_delegations_file(&plain, &chain, None).unwrap(), messages.delegations);
+        assert_eq!(
+            read_delegations_file(&encrypted, &chain, Some(b"password")).unwrap(),
+            messages.delegations
+        );
+        assert!(read_delegations_file(&encrypted, &chain, None).is_err());
+        assert!(read_delegations_file(&encrypted, &chain, Some(b"wrong")).is_err());
+
+        // Files signed for another chain are rejected
+        let err = DelegationFile::read(&plain, &ChainConfig::mainnet()).unwrap_err();
+        assert!(matches!(err, DelegationFileError::WrongChain { .. }));
+
+        // Legacy files are still accepted
+        assert_eq!(
+            read_delegations_file(&legacy, &ChainConfig::mainnet(), None).unwrap(),
+            messages.delegations
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
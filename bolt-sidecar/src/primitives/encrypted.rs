@@ -0,0 +1,303 @@
+use alloy::primitives::{keccak256, Address, Signature, B256};
+use lighthouse_eth2_keystore::{
+    decrypt,
+    json_keystore::{Aes128Ctr, Cipher, Crypto, Kdf},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    commitment::{
+        deserialize_sig, serialize_sig, CommitmentType, DigestVersion, SignatureError,
+        COMMITMENT_DIGEST_VERSION,
+    },
+    transaction::deserialize_txs,
+    FullTransaction, Slot, TimeLockPuzzle,
+};
+
+/// The max number of iterations of the PBKDF2 key derivation of an encrypted request. The
+/// decryption key is random rather than a password, so a costly derivation would only slow
+/// down the sidecar at the commitment deadline.
+pub const MAX_PBKDF2_ITERATIONS: u32 = 1 << 14;
+
+/// Error decrypting the transactions of an encrypted request.
+#[derive(Debug, Error)]
+pub enum DecryptionError {
+    /// The key derivation function is not PBKDF2, or is too costly.
+    #[error("unsupported key derivation, only PBKDF2 with at most {MAX_PBKDF2_ITERATIONS} iterations is accepted")]
+    UnsupportedKdf,
+    /// The key doesn't match the checksum of the ciphertext.
+    #[error("wrong decryption key")]
+    WrongKey,
+    /// The ciphertext couldn't be decrypted.
+    #[error("failed to decrypt the ciphertext: {0}")]
+    Crypto(String),
+    /// The plaintext is not a list of signed raw transactions.
+    #[error("malformed plaintext: {0}")]
+    MalformedPlaintext(#[from] serde_json::Error),
+}
+
+/// Request to include transactions that stay encrypted until their decryption key is revealed,
+/// before the commitment deadline of the target slot. The sidecar commits to the hash of the
+/// ciphertext and to the gas it may use, so that observers of the commitments API can't
+/// frontrun the transactions.
+///
+/// The ciphertext is an EIP-2335 `crypto` module, whose plaintext is the JSON list of the
+/// signed raw transactions, e.g. `["0x02f8...", "0x02f8..."]`, encrypted with the 32-byte
+/// decryption key as the password. The key can also be locked in a time-lock puzzle, which the
+/// sidecar solves before the deadline if the client doesn't reveal it.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedInclusionRequest {
+    /// The consensus slot number at which the transactions should be included.
+    pub slot: Slot,
+    /// The max gas of the transactions, held for them until they are decrypted.
+    pub gas_limit: u64,
+    /// The encrypted transactions.
+    pub ciphertext: Crypto,
+    /// The time-lock puzzle locking the decryption key, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timelock: Option<TimeLockPuzzle>,
+    /// The signer of the request.
+    #[serde(skip)]
+    pub signer: Option<Address>,
+}
+
+impl EncryptedInclusionRequest {
+    /// Returns the hash of the ciphertext, which identifies the request:
+    ///
+    /// ciphertext_hash = keccak256(
+    ///     kdf_params | bytes(kdf_salt) | bytes(cipher_iv) | bytes(cipher_message) |
+    ///     bytes(checksum_message)
+    /// )
+    ///
+    /// where each `bytes(..)` is preceded by its length as `be_bytes(u64)`, and `kdf_params` is
+    /// `u8(0) | be_bytes(c) | be_bytes(dklen)` for PBKDF2, or
+    /// `u8(1) | be_bytes(dklen) | be_bytes(n) | be_bytes(r) | be_bytes(p)` for scrypt, with the
+    /// parameters as `u32`.
+    pub fn ciphertext_hash(&self) -> B256 {
+        let mut data = Vec::new();
+        let salt = match &self.ciphertext.kdf.params {
+            Kdf::Pbkdf2(pbkdf2) => {
+                data.push(0);
+                data.extend_from_slice(&pbkdf2.c.to_be_bytes());
+                data.extend_from_slice(&pbkdf2.dklen.to_be_bytes());
+                pbkdf2.salt.as_bytes()
+            }
+            Kdf::Scrypt(scrypt) => {
+                data.push(1);
+                for param in [scrypt.dklen, scrypt.n, scrypt.r, scrypt.p] {
+                    data.extend_from_slice(&param.to_be_bytes());
+                }
+                scrypt.salt.as_bytes()
+            }
+        };
+        let Cipher::Aes128Ctr(Aes128Ctr { iv }) = &self.ciphertext.cipher.params;
+
+        for bytes in [
+            salt,
+            iv.as_bytes(),
+            self.ciphertext.cipher.message.as_bytes(),
+            self.ciphertext.checksum.message.as_bytes(),
+        ] {
+            data.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+            data.extend_from_slice(bytes);
+        }
+
+        keccak256(&data)
+    }
+
+    /// Returns the canonical digest of the request on the given chain, signed by users:
+    ///
+    /// digest = keccak256(
+    ///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | be_bytes(slot) |
+    ///     be_bytes(gas_limit) | bytes(ciphertext_hash)
+    /// )
+    ///
+    /// For requests with a time-lock puzzle, `bytes(puzzle_hash)` follows the ciphertext hash.
+    pub fn signing_digest(&self, chain_id: u64) -> B256 {
+        let mut data = Vec::with_capacity(90);
+        data.push(COMMITMENT_DIGEST_VERSION);
+        data.push(CommitmentType::EncryptedInclusion as u8);
+        data.extend_from_slice(&chain_id.to_be_bytes());
+        data.extend_from_slice(&self.slot.to_be_bytes());
+        data.extend_from_slice(&self.gas_limit.to_be_bytes());
+        data.extend_from_slice(self.ciphertext_hash().as_slice());
+        if let Some(timelock) = &self.timelock {
+            data.extend_from_slice(timelock.hash().as_slice());
+        }
+
+        keccak256(&data)
+    }
+
+    /// Verifies that the signature over the request was made by the given signer. Returns
+    /// the encoding that was signed, which is always the canonical one.
+    pub fn verify_signature(
+        &self,
+        signature: &Signature,
+        signer: Address,
+        chain_id: u64,
+    ) -> Result<DigestVersion, SignatureError> {
+        let digest = self.signing_digest(chain_id);
+        match signature.recover_address_from_prehash(&digest) {
+            Ok(recovered) if recovered == signer => Ok(DigestVersion::V1),
+            _ => Err(SignatureError),
+        }
+    }
+
+    /// Checks that the ciphertext can be decrypted cheaply, i.e. that its key derivation is
+    /// PBKDF2 with a bounded number of iterations.
+    pub fn check_kdf(&self) -> Result<(), DecryptionError> {
+        match &self.ciphertext.kdf.params {
+            Kdf::Pbkdf2(pbkdf2) if pbkdf2.c <= MAX_PBKDF2_ITERATIONS => Ok(()),
+            _ => Err(DecryptionError::UnsupportedKdf),
+        }
+    }
+
+    /// Decrypts the transactions with the given key.
+    pub fn decrypt(&self, key: &B256) -> Result<Vec<FullTransaction>, DecryptionError> {
+        self.check_kdf()?;
+
+        let plain_text = decrypt(key.as_slice(), &self.ciphertext).map_err(|err| match err {
+            lighthouse_eth2_keystore::Error::InvalidPassword => DecryptionError::WrongKey,
+            err => DecryptionError::Crypto(format!("{err:?}")),
+        })?;
+
+        let mut deserializer = serde_json::Deserializer::from_slice(plain_text.as_bytes());
+        Ok(deserialize_txs(&mut deserializer)?)
+    }
+}
+
+/// The decryption key of an encrypted request, revealed before the commitment deadline of its
+/// slot by the client, whoever it shared it with, or the sidecar once it solves the time-lock
+/// puzzle of the request. Anyone holding the key can reveal it, as it is checked against the
+/// ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptionKeyReveal {
+    /// The hash of the ciphertext of the request.
+    pub ciphertext_hash: B256,
+    /// The decryption key.
+    pub key: B256,
+}
+
+/// A commitment to include the transactions of an encrypted request once decrypted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedCommitment {
+    /// The signer of the request.
+    pub signer: Address,
+    /// The target slot of the request.
+    pub slot: Slot,
+    /// The max gas of the transactions.
+    pub gas_limit: u64,
+    /// The hash of the ciphertext of the request.
+    pub ciphertext_hash: B256,
+}
+
+impl EncryptedCommitment {
+    /// Returns the digest of the commitment:
+    ///
+    /// digest = keccak256(
+    ///     bytes(signer) | be_bytes(slot) | be_bytes(gas_limit) | bytes(ciphertext_hash)
+    /// )
+    pub fn digest(&self) -> B256 {
+        let mut data = Vec::with_capacity(68);
+        data.extend_from_slice(self.signer.as_slice());
+        data.extend_from_slice(&self.slot.to_be_bytes());
+        data.extend_from_slice(&self.gas_limit.to_be_bytes());
+        data.extend_from_slice(self.ciphertext_hash.as_slice());
+
+        keccak256(&data)
+    }
+}
+
+/// An [EncryptedCommitment] signed by the commitment key of the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedEncryptedCommitment {
+    #[serde(flatten)]
+    pub commitment: EncryptedCommitment,
+    #[serde(deserialize_with = "deserialize_sig", serialize_with = "serialize_sig")]
+    pub signature: Signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use lighthouse_eth2_keystore::{
+        default_kdf,
+        json_keystore::{EmptyString, KdfModule},
+        SALT_SIZE,
+    };
+    use rand::Rng;
+
+    use crate::test_util::{encrypt_txs, lock_key, test_legacy_transaction};
+
+    use super::*;
+
+    #[test]
+    fn test_encrypted_request_decryption() {
+        let txs = vec![test_legacy_transaction()];
+        let key = B256::random();
+        let request = EncryptedInclusionRequest {
+            slot: 10,
+            gas_limit: 21_000,
+            ciphertext: encrypt_txs(&txs, &key),
+            timelock: None,
+            signer: None,
+        };
+
+        assert!(request.check_kdf().is_ok());
+        assert_eq!(request.decrypt(&key).unwrap(), txs);
+        assert!(matches!(request.decrypt(&B256::random()), Err(DecryptionError::WrongKey)));
+
+        // The derivation of the default EIP-2335 keystores is too costly
+        let salt = rand::thread_rng().gen::<[u8; SALT_SIZE]>().to_vec();
+        let kdf = default_kdf(salt);
+        let costly = EncryptedInclusionRequest {
+            ciphertext: Crypto {
+                kdf: KdfModule { function: kdf.function(), params: kdf, message: EmptyString },
+                ..encrypt_txs(&txs, &key)
+            },
+            ..request
+        };
+        assert!(matches!(costly.check_kdf(), Err(DecryptionError::UnsupportedKdf)));
+    }
+
+    #[test]
+    fn test_ciphertext_hash() {
+        let key = B256::random();
+        let request = EncryptedInclusionRequest {
+            slot: 10,
+            gas_limit: 21_000,
+            ciphertext: encrypt_txs(&[test_legacy_transaction()], &key),
+            timelock: None,
+            signer: None,
+        };
+        let hash = request.ciphertext_hash();
+        let json = serde_json::to_value(&request).unwrap();
+        let edited = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut json = json.clone();
+            edit(&mut json["ciphertext"]);
+            serde_json::from_value::<EncryptedInclusionRequest>(json).unwrap().ciphertext_hash()
+        };
+
+        // The parameters of the key derivation are part of the hash
+        assert_ne!(edited(&|crypto| crypto["kdf"]["params"]["c"] = 2048.into()), hash);
+        assert_ne!(edited(&|crypto| crypto["kdf"]["params"]["dklen"] = 16.into()), hash);
+
+        // Moving bytes from a field to the next changes the hash
+        let moved = edited(&|crypto| {
+            let iv = crypto["cipher"]["params"]["iv"].as_str().unwrap().to_owned();
+            let message = crypto["cipher"]["message"].as_str().unwrap().to_owned();
+            crypto["cipher"]["params"]["iv"] = format!("{iv}{}", &message[..2]).into();
+            crypto["cipher"]["message"] = message[2..].into();
+        });
+        assert_ne!(moved, hash);
+
+        // The time-lock puzzle is signed along with the ciphertext
+        let locked = EncryptedInclusionRequest { timelock: Some(lock_key(&key, 10)), ..request };
+        assert_eq!(locked.ciphertext_hash(), hash);
+        let unlocked = serde_json::from_value::<EncryptedInclusionRequest>(json).unwrap();
+        assert_ne!(locked.signing_digest(1), unlocked.signing_digest(1));
+    }
+}
@@ -0,0 +1,125 @@
+//! The protobuf encoding of the lifecycle events of commitments, as defined in
+//! `proto/bolt/events/v1/events.proto`.
+//!
+//! The types mirror the schema field by field and tag by tag, and must be kept in sync with
+//! it. They are written by hand rather than generated, so that building the sidecar doesn't
+//! require `protoc`.
+
+use prost::Message;
+
+/// A lifecycle event of the commitments of a slot.
+#[derive(Clone, PartialEq, Message)]
+pub struct LifecycleEvent {
+    /// The time the event happened, in unix milliseconds.
+    #[prost(uint64, tag = "1")]
+    pub timestamp_ms: u64,
+    /// The event.
+    #[prost(oneof = "Event", tags = "2, 3, 4")]
+    pub event: Option<Event>,
+}
+
+/// The events of a [LifecycleEvent].
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum Event {
+    /// Constraints were signed for a slot, and the commitment is about to be returned.
+    #[prost(message, tag = "2")]
+    CommitmentAccepted(CommitmentAccepted),
+    /// The relay acknowledged the constraints of a slot.
+    #[prost(message, tag = "3")]
+    ConstraintsSubmitted(ConstraintsSubmitted),
+    /// The outcome of the commitments of a slot that passed was verified on chain.
+    #[prost(message, tag = "4")]
+    InclusionOutcome(InclusionOutcome),
+}
+
+/// Constraints were signed for a slot, and the commitment is about to be returned.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommitmentAccepted {
+    /// The target slot of the commitment.
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// The address of the commitment key that signed the commitment, 20 bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub commitment_signer: Vec<u8>,
+    /// The BLS public key that signed the constraints, 48 bytes.
+    #[prost(bytes = "vec", tag = "3")]
+    pub constraints_pubkey: Vec<u8>,
+    /// The hashes of the committed transactions, 32 bytes each, in order.
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub tx_hashes: Vec<Vec<u8>>,
+    /// Whether the transactions are committed to the top of the block.
+    #[prost(bool, tag = "5")]
+    pub top: bool,
+}
+
+/// The relay acknowledged the constraints of a slot.
+#[derive(Clone, PartialEq, Message)]
+pub struct ConstraintsSubmitted {
+    /// The slot of the constraints.
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+}
+
+/// The outcome of the commitments of a slot that passed was verified on chain.
+#[derive(Clone, PartialEq, Message)]
+pub struct InclusionOutcome {
+    /// The slot of the commitments.
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// The hashes of the committed transactions found on chain, 32 bytes each.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub included: Vec<Vec<u8>>,
+    /// The hashes of the committed transactions not found on chain, 32 bytes each.
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub missed: Vec<Vec<u8>>,
+}
+
+impl LifecycleEvent {
+    /// Returns the slot of the event, if any.
+    pub fn slot(&self) -> Option<u64> {
+        match self.event.as_ref()? {
+            Event::CommitmentAccepted(event) => Some(event.slot),
+            Event::ConstraintsSubmitted(event) => Some(event.slot),
+            Event::InclusionOutcome(event) => Some(event.slot),
+        }
+    }
+
+    /// Encodes the event in the protobuf wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Decodes an event from the protobuf wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, prost::DecodeError> {
+        Self::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle_event_wire_format() {
+        let event = LifecycleEvent {
+            timestamp_ms: 1,
+            event: Some(Event::ConstraintsSubmitted(ConstraintsSubmitted { slot: 2 })),
+        };
+
+        // Field 1 varint, then field 3 length-delimited holding field 1 varint
+        let bytes = event.to_bytes();
+        assert_eq!(bytes, vec![0x08, 0x01, 0x1a, 0x02, 0x08, 0x02]);
+        assert_eq!(LifecycleEvent::from_bytes(&bytes).unwrap(), event);
+        assert_eq!(event.slot(), Some(2));
+
+        let outcome = LifecycleEvent {
+            timestamp_ms: 1_700_000_000_000,
+            event: Some(Event::InclusionOutcome(InclusionOutcome {
+                slot: 10,
+                included: vec![vec![1; 32]],
+                missed: vec![vec![2; 32], vec![3; 32]],
+            })),
+        };
+        assert_eq!(LifecycleEvent::from_bytes(&outcome.to_bytes()).unwrap(), outcome);
+    }
+}
@@ -22,25 +22,83 @@ use tokio::sync::oneshot;
 
 pub use ethereum_consensus::crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature};
 
+/// Attestations binding the commitment key of a sidecar to its validators.
+pub mod attestation;
+pub use attestation::{SidecarIdentity, SignedCommitmentKey};
+
+/// Attestations of the availability of the sidecar in its upcoming proposal slots.
+pub mod availability;
+pub use availability::{Availability, SignedAvailability};
+
 /// Commitment types, received by users wishing to receive preconfirmations.
 pub mod commitment;
-pub use commitment::{CommitmentRequest, InclusionRequest};
+pub use commitment::{
+    BatchInclusionRequest, CommitmentRequest, InclusionRequest, InclusionRequestByHash, TxOutcome,
+    TxStatus,
+};
 
 /// Constraint types, signed by proposers and sent along the PBS pipeline
 /// for validation.
 pub mod constraint;
 pub use constraint::{BatchedSignedConstraints, ConstraintsMessage, SignedConstraints};
 
+/// The protobuf wire schema of the lifecycle events of commitments.
+pub mod events;
+pub use events::LifecycleEvent;
+
 /// Delegation and revocation signed message types and utilities.
 pub mod delegation;
 pub use delegation::{
-    read_signed_delegations_from_file, DelegationMessage, RevocationMessage, SignedDelegation,
-    SignedRevocation,
+    read_signed_delegations_from_file, DelegationMessage, DelegationScope, RevocationMessage,
+    SignedDelegation, SignedRevocation,
+};
+
+/// The file format of signed delegations and revocations, with chain metadata and
+/// optional encryption.
+pub mod delegation_file;
+pub use delegation_file::{read_delegations_file, DelegationFile, SignedMessages};
+
+/// Merkle multiproofs of transaction inclusion in execution payloads.
+pub mod proofs;
+pub use proofs::ProofError;
+
+/// Blockspace reservations that clients subscribe to for a period of slots.
+pub mod reservation;
+pub use reservation::{Reservation, ReservationRequest, SignedReservation};
+
+/// Inclusion requests of encrypted transactions, decrypted with a key revealed before the
+/// commitment deadline.
+pub mod encrypted;
+pub use encrypted::{
+    DecryptionError, DecryptionKeyReveal, EncryptedCommitment, EncryptedInclusionRequest,
+    SignedEncryptedCommitment,
+};
+
+/// Time-lock puzzles locking the decryption keys of encrypted requests until the sidecar
+/// solves them.
+pub mod timelock;
+pub use timelock::{TimeLockError, TimeLockPuzzle};
+
+/// Fork-versioned builder bids and blinded blocks, across the Deneb and Electra forks.
+pub mod versioned;
+pub use versioned::{
+    ElectraBuilderBid, ExecutionRequests, SignedElectraBuilderBid, VersionedSignedBlindedBlock,
+    VersionedSignedBuilderBid,
 };
 
+/// Micro-payment vouchers that pay for commitment requests over off-chain channels.
+pub mod voucher;
+pub use voucher::PaymentVoucher;
+
+/// SSZ encoding, decoding and merkleization of the bolt primitives.
+pub mod ssz;
+pub use ssz::{SszEncoding, SszError};
+
 /// Transaction types and extension utilities.
 pub mod transaction;
-pub use transaction::{deserialize_txs, serialize_txs, FullTransaction, TransactionExt};
+pub use transaction::{
+    deserialize_tx, deserialize_txs, serialize_tx, serialize_txs, FullTransaction, TransactionExt,
+};
 
 /// An alias for a Beacon Chain slot number
 pub type Slot = u64;
@@ -93,12 +151,15 @@ pub struct MerkleProof {
     hashes: List<Hash32, 1000>,
 }
 
+/// A multiproof of inclusion of transactions in the transactions tree of a payload,
+/// as returned by relays implementing the Constraints API.
 #[derive(Debug, Default, Clone, SimpleSerialize, serde::Serialize, serde::Deserialize)]
 pub struct MerkleMultiProof {
     // We use List here for SSZ, TODO: choose max
-    transaction_hashes: List<Hash32, 300>,
-    generalized_indexes: List<u64, 300>,
-    merkle_hashes: List<Hash32, 1000>,
+    pub transaction_hashes: List<Hash32, 300>,
+    #[serde(rename = "generalized_indeces", alias = "generalized_indexes")]
+    pub generalized_indexes: List<u64, 300>,
+    pub merkle_hashes: List<Hash32, 1000>,
 }
 
 #[derive(Debug)]
@@ -109,7 +170,7 @@ pub struct FetchPayloadRequest {
 
 #[derive(Debug)]
 pub struct PayloadAndBid {
-    pub bid: SignedBuilderBid,
+    pub bid: VersionedSignedBuilderBid,
     pub payload: GetPayloadResponse,
 }
 
@@ -0,0 +1,252 @@
+use std::collections::{BTreeSet, HashMap};
+
+use alloy::{
+    primitives::B256,
+    signers::k256::sha2::{Digest, Sha256},
+};
+use ethereum_consensus::{
+    bellatrix::mainnet::Transaction,
+    deneb::Hash32,
+    ssz::prelude::{HashTreeRoot, List},
+};
+
+use super::{FullTransaction, MerkleMultiProof};
+
+/// The depth of the transactions tree of an execution payload, without the length mix-in.
+/// It holds up to `MAX_TRANSACTIONS_PER_PAYLOAD = 2^20` transactions.
+pub const TRANSACTIONS_TREE_DEPTH: u32 = 20;
+
+/// Errors that can occur while computing or verifying transaction inclusion proofs.
+#[derive(Debug, thiserror::Error)]
+pub enum ProofError {
+    #[error("Transaction index {0} out of bounds ({1} transactions)")]
+    IndexOutOfBounds(usize, usize),
+    #[error("Leaves and indices length mismatch")]
+    LengthMismatch,
+    #[error("Missing proof hash for generalized index {0}")]
+    MissingHash(usize),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Proof too large: {0}")]
+    TooLarge(String),
+}
+
+/// Returns the leaf of a transaction in the transactions tree, i.e. the hash tree root of its
+/// EIP-2718 envelope as an SSZ byte list. Blob transactions are taken without their sidecar.
+pub fn transaction_leaf(tx: &FullTransaction) -> Result<B256, ProofError> {
+    raw_transaction_leaf(tx.payload_encoded())
+}
+
+/// Returns the leaf of an EIP-2718 encoded transaction in the transactions tree.
+pub fn raw_transaction_leaf(raw: &[u8]) -> Result<B256, ProofError> {
+    let tx =
+        Transaction::try_from(raw).map_err(|e| ProofError::InvalidTransaction(e.to_string()))?;
+    let root = tx.hash_tree_root().map_err(|e| ProofError::InvalidTransaction(e.to_string()))?;
+    Ok(B256::from_slice(root.as_ref()))
+}
+
+/// Returns the generalized index of the transaction at the given position in the
+/// transactions tree, accounting for the length mix-in of the SSZ list.
+pub const fn generalized_index(tx_index: usize) -> usize {
+    (2 << TRANSACTIONS_TREE_DEPTH) + tx_index
+}
+
+/// Returns the root of the transactions tree with the given leaves, which is
+/// the `transactions_root` of an execution payload header.
+pub fn transactions_root(leaves: &[B256]) -> B256 {
+    let data_root = node_at(leaves, 2);
+    hash_pair(&data_root, &length_chunk(leaves.len()))
+}
+
+/// Computes a multiproof for the inclusion of the transactions at the given positions,
+/// in the format expected by the Constraints API.
+pub fn compute_multiproof(
+    leaves: &[B256],
+    tx_hashes: &[B256],
+    tx_indices: &[usize],
+) -> Result<MerkleMultiProof, ProofError> {
+    if tx_hashes.len() != tx_indices.len() {
+        return Err(ProofError::LengthMismatch);
+    }
+
+    if let Some(index) = tx_indices.iter().find(|i| **i >= leaves.len()) {
+        return Err(ProofError::IndexOutOfBounds(*index, leaves.len()));
+    }
+
+    let indices = tx_indices.iter().map(|i| generalized_index(*i)).collect::<Vec<_>>();
+    let merkle_hashes = helper_indices(&indices)
+        .into_iter()
+        .map(|index| if index == 3 { length_chunk(leaves.len()) } else { node_at(leaves, index) })
+        .map(|hash| to_hash32(&hash))
+        .collect::<Vec<_>>();
+
+    Ok(MerkleMultiProof {
+        transaction_hashes: to_list(tx_hashes.iter().map(to_hash32).collect())?,
+        generalized_indexes: to_list(indices.into_iter().map(|i| i as u64).collect())?,
+        merkle_hashes: to_list(merkle_hashes)?,
+    })
+}
+
+/// Verifies a multiproof of the given leaves against the transactions root.
+pub fn verify_multiproof(
+    leaves: &[B256],
+    proof: &[B256],
+    indices: &[usize],
+    root: B256,
+) -> Result<bool, ProofError> {
+    if leaves.len() != indices.len() {
+        return Err(ProofError::LengthMismatch);
+    }
+
+    let helpers = helper_indices(indices);
+    if proof.len() != helpers.len() {
+        return Err(ProofError::LengthMismatch);
+    }
+
+    let mut nodes = indices.iter().copied().zip(leaves.iter().copied()).collect::<HashMap<_, _>>();
+    nodes.extend(helpers.into_iter().zip(proof.iter().copied()));
+
+    // Hash the known nodes up to the root, from the deepest ones
+    let mut keys = nodes.keys().copied().collect::<BTreeSet<_>>();
+    while let Some(index) = keys.pop_last() {
+        if index <= 1 {
+            break;
+        }
+
+        let parent = index / 2;
+        if nodes.contains_key(&parent) {
+            continue;
+        }
+
+        let (left, right) = (parent * 2, parent * 2 + 1);
+        let left = *nodes.get(&left).ok_or(ProofError::MissingHash(left))?;
+        let right = *nodes.get(&right).ok_or(ProofError::MissingHash(right))?;
+        nodes.insert(parent, hash_pair(&left, &right));
+        keys.insert(parent);
+    }
+
+    Ok(nodes.get(&1) == Some(&root))
+}
+
+/// Returns the indices of the sibling nodes needed to prove the given generalized indices,
+/// in decreasing order, as defined in the consensus specs.
+fn helper_indices(indices: &[usize]) -> Vec<usize> {
+    let mut helpers = BTreeSet::new();
+    let mut paths = BTreeSet::new();
+
+    for &index in indices {
+        let mut current = index;
+        while current > 1 {
+            helpers.insert(current ^ 1);
+            paths.insert(current);
+            current /= 2;
+        }
+    }
+
+    helpers.difference(&paths).rev().copied().collect()
+}
+
+/// Returns the node at the given generalized index of the transactions data tree.
+fn node_at(leaves: &[B256], index: usize) -> B256 {
+    let depth = index.ilog2();
+    let height = TRANSACTIONS_TREE_DEPTH + 1 - depth;
+    let first_leaf = (index - (1 << depth)) << height;
+
+    if first_leaf >= leaves.len() {
+        return zero_hash(height);
+    }
+
+    if height == 0 {
+        return leaves[first_leaf];
+    }
+
+    hash_pair(&node_at(leaves, index * 2), &node_at(leaves, index * 2 + 1))
+}
+
+/// Returns the root of an empty tree of the given height.
+fn zero_hash(height: u32) -> B256 {
+    (0..height).fold(B256::ZERO, |hash, _| hash_pair(&hash, &hash))
+}
+
+/// Returns the SSZ length mix-in chunk for a list of the given length.
+fn length_chunk(len: usize) -> B256 {
+    let mut chunk = B256::ZERO;
+    chunk[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    chunk
+}
+
+fn to_hash32(hash: &B256) -> Hash32 {
+    Hash32::try_from(hash.as_slice()).expect("hashes are 32 bytes")
+}
+
+fn to_list<T, const N: usize>(items: Vec<T>) -> Result<List<T, N>, ProofError>
+where
+    List<T, N>: TryFrom<Vec<T>>,
+{
+    let len = items.len();
+    List::try_from(items).map_err(|_| ProofError::TooLarge(format!("{len} items (max {N})")))
+}
+
+fn hash_pair(left: &B256, right: &B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use ethereum_consensus::bellatrix::mainnet::MAX_TRANSACTIONS_PER_PAYLOAD;
+
+    use super::*;
+
+    fn test_transactions(count: usize) -> List<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD> {
+        let mut list = List::default();
+        for i in 0..count {
+            list.push(Transaction::try_from(vec![i as u8 + 1; 50 + i].as_ref()).unwrap());
+        }
+        list
+    }
+
+    #[test]
+    fn test_transactions_root() {
+        let transactions = test_transactions(5);
+        let leaves = transactions
+            .iter()
+            .map(|tx| raw_transaction_leaf(tx.as_ref()).unwrap())
+            .collect::<Vec<_>>();
+
+        let expected = transactions.hash_tree_root().unwrap();
+        assert_eq!(transactions_root(&leaves).as_slice(), expected.as_ref());
+        assert_eq!(
+            transactions_root(&[]).as_slice(),
+            test_transactions(0).hash_tree_root().unwrap().as_ref()
+        );
+    }
+
+    #[test]
+    fn test_multiproof_roundtrip() {
+        let transactions = test_transactions(7);
+        let leaves = transactions
+            .iter()
+            .map(|tx| raw_transaction_leaf(tx.as_ref()).unwrap())
+            .collect::<Vec<_>>();
+        let root = transactions_root(&leaves);
+
+        let tx_indices = [1, 4, 5];
+        let hashes = tx_indices.iter().map(|i| B256::with_last_byte(*i as u8)).collect::<Vec<_>>();
+        let proof = compute_multiproof(&leaves, &hashes, &tx_indices).unwrap();
+
+        let proven = tx_indices.iter().map(|i| leaves[*i]).collect::<Vec<_>>();
+        let indices = proof.generalized_indexes.iter().map(|i| *i as usize).collect::<Vec<_>>();
+        let hashes =
+            proof.merkle_hashes.iter().map(|h| B256::from_slice(h.as_ref())).collect::<Vec<_>>();
+        assert_eq!(indices[0], generalized_index(1));
+        assert!(verify_multiproof(&proven, &hashes, &indices, root).unwrap());
+
+        // A different leaf must not verify
+        let mut tampered = proven.clone();
+        tampered[0] = leaves[0];
+        assert!(!verify_multiproof(&tampered, &hashes, &indices, root).unwrap());
+    }
+}
@@ -0,0 +1,140 @@
+use alloy::primitives::{keccak256, Address, Signature, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    commitment::{
+        deserialize_sig, serialize_sig, CommitmentType, DigestVersion, SignatureError,
+        COMMITMENT_DIGEST_VERSION,
+    },
+    Slot,
+};
+
+/// Request to reserve a fixed amount of gas in every proposal slot of the operator
+/// between `start_slot` and `end_slot`, both inclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationRequest {
+    /// The gas to reserve in every proposal slot of the period.
+    pub gas_per_slot: u64,
+    /// The first slot of the period.
+    pub start_slot: Slot,
+    /// The last slot of the period.
+    pub end_slot: Slot,
+    /// The subscriber, i.e. the signer of the request.
+    #[serde(skip)]
+    pub signer: Option<Address>,
+}
+
+impl ReservationRequest {
+    /// Returns the canonical digest of the request on the given chain, signed by subscribers:
+    ///
+    /// digest = keccak256(
+    ///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | be_bytes(gas_per_slot) |
+    ///     be_bytes(start_slot) | be_bytes(end_slot)
+    /// )
+    pub fn signing_digest(&self, chain_id: u64) -> B256 {
+        let mut data = Vec::with_capacity(34);
+        data.push(COMMITMENT_DIGEST_VERSION);
+        data.push(CommitmentType::Reservation as u8);
+        data.extend_from_slice(&chain_id.to_be_bytes());
+        data.extend_from_slice(&self.gas_per_slot.to_be_bytes());
+        data.extend_from_slice(&self.start_slot.to_be_bytes());
+        data.extend_from_slice(&self.end_slot.to_be_bytes());
+
+        keccak256(&data)
+    }
+
+    /// Verifies that the signature over the request was made by the given signer. Returns
+    /// the encoding that was signed, which is always the canonical one.
+    pub fn verify_signature(
+        &self,
+        signature: &Signature,
+        signer: Address,
+        chain_id: u64,
+    ) -> Result<DigestVersion, SignatureError> {
+        let digest = self.signing_digest(chain_id);
+        match signature.recover_address_from_prehash(&digest) {
+            Ok(recovered) if recovered == signer => Ok(DigestVersion::V1),
+            _ => Err(SignatureError),
+        }
+    }
+
+    /// Returns the number of slots in the period.
+    pub fn period(&self) -> u64 {
+        self.end_slot.saturating_sub(self.start_slot) + 1
+    }
+}
+
+/// A blockspace reservation accepted by the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    /// The subscriber the gas is reserved for.
+    pub subscriber: Address,
+    /// The gas reserved in every proposal slot of the period.
+    pub gas_per_slot: u64,
+    /// The first slot of the period.
+    pub start_slot: Slot,
+    /// The last slot of the period.
+    pub end_slot: Slot,
+    /// The price in wei charged for every proposal slot of the period.
+    pub price_per_slot: U256,
+}
+
+impl Reservation {
+    /// Returns the digest of the reservation, which also identifies it:
+    ///
+    /// digest = keccak256(
+    ///     bytes(subscriber) | be_bytes(gas_per_slot) | be_bytes(start_slot) |
+    ///     be_bytes(end_slot) | be_bytes(price_per_slot)
+    /// )
+    pub fn digest(&self) -> B256 {
+        let mut data = Vec::with_capacity(76);
+        data.extend_from_slice(self.subscriber.as_slice());
+        data.extend_from_slice(&self.gas_per_slot.to_be_bytes());
+        data.extend_from_slice(&self.start_slot.to_be_bytes());
+        data.extend_from_slice(&self.end_slot.to_be_bytes());
+        data.extend_from_slice(&self.price_per_slot.to_be_bytes::<32>());
+
+        keccak256(&data)
+    }
+
+    /// Returns true if the reservation covers the given slot.
+    pub fn is_active(&self, slot: Slot) -> bool {
+        (self.start_slot..=self.end_slot).contains(&slot)
+    }
+}
+
+/// A [Reservation] signed by the commitment key of the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedReservation {
+    #[serde(flatten)]
+    pub reservation: Reservation,
+    #[serde(deserialize_with = "deserialize_sig", serialize_with = "serialize_sig")]
+    pub signature: Signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+
+    #[test]
+    fn test_reservation_request_signature() {
+        let signer = PrivateKeySigner::random();
+        let request = ReservationRequest {
+            gas_per_slot: 100_000,
+            start_slot: 10,
+            end_slot: 19,
+            signer: None,
+        };
+        assert_eq!(request.period(), 10);
+
+        let signature = signer.sign_hash_sync(&request.signing_digest(1)).unwrap();
+        assert!(request.verify_signature(&signature, signer.address(), 1).is_ok());
+
+        // Signatures are bound to the chain and to the requested period
+        assert!(request.verify_signature(&signature, signer.address(), 17000).is_err());
+        let longer = ReservationRequest { end_slot: 20, ..request };
+        assert!(longer.verify_signature(&signature, signer.address(), 1).is_err());
+    }
+}
@@ -0,0 +1,555 @@
+use alloy::primitives::{Address, Signature, B256};
+use ethereum_consensus::{
+    bellatrix::mainnet::Transaction,
+    builder::SignedValidatorRegistration,
+    crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature},
+    deneb::mainnet::{BlobsBundle, ExecutionPayload as DenebExecutionPayload},
+    ssz::prelude::*,
+    types::mainnet::ExecutionPayload,
+    Fork,
+};
+
+use crate::crypto::bls::BLSSig;
+
+use super::{
+    commitment::{ECDSASignatureExt, InclusionCommitment},
+    BatchedSignedConstraints, ConstraintsMessage, DelegationMessage, FullTransaction,
+    GetPayloadResponse, InclusionRequest, PayloadAndBlobs, RevocationMessage, SignedConstraints,
+    SignedDelegation, SignedRevocation,
+};
+
+/// The maximum number of transactions that can be encoded in a single constraints
+/// message or inclusion commitment.
+pub const MAX_TRANSACTIONS_PER_MESSAGE: usize = 128;
+
+/// The maximum number of signed constraints that can be encoded in a single submission.
+pub const MAX_CONSTRAINTS_PER_SUBMISSION: usize = 1024;
+
+/// The length of an ECDSA signature with its parity byte.
+pub const ECDSA_SIGNATURE_LENGTH: usize = 65;
+
+/// The length of an execution layer address.
+pub const ADDRESS_LENGTH: usize = 20;
+
+/// A list of opaque, EIP-2718 encoded transactions.
+pub type Transactions = List<Transaction, MAX_TRANSACTIONS_PER_MESSAGE>;
+
+/// Errors that can occur while converting primitives to and from SSZ.
+#[derive(Debug, thiserror::Error)]
+pub enum SszError {
+    #[error("Failed to serialize: {0}")]
+    Serialize(#[from] SerializeError),
+    #[error("Failed to deserialize: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("Failed to compute hash tree root: {0}")]
+    Merkleization(#[from] MerkleizationError),
+    #[error("Too many transactions: {0} (max {MAX_TRANSACTIONS_PER_MESSAGE})")]
+    TooManyTransactions(usize),
+    #[error("Too many constraints: {0} (max {MAX_CONSTRAINTS_PER_SUBMISSION})")]
+    TooManyConstraints(usize),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Invalid ECDSA signature")]
+    InvalidSignature,
+    #[error("Invalid signer address")]
+    InvalidSigner,
+    #[error("Commitments with a slot range have no SSZ encoding")]
+    SlotRange,
+    #[error("Top-of-block commitments have no SSZ encoding")]
+    TopOfBlock,
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+    #[error("The {0} fork has no SSZ encoding")]
+    UnsupportedFork(Fork),
+    #[error("Invalid execution requests: {0}")]
+    InvalidExecutionRequests(String),
+}
+
+/// Encoding, decoding and merkleization of bolt primitives as SSZ, so that they can
+/// be exchanged with non-Rust implementations and verified on-chain.
+pub trait SszEncoding: Sized {
+    /// Serializes the value into SSZ bytes.
+    fn to_ssz_bytes(&self) -> Result<Vec<u8>, SszError>;
+
+    /// Deserializes the value from SSZ bytes.
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError>;
+
+    /// Returns the SSZ hash tree root of the value.
+    fn ssz_hash_tree_root(&self) -> Result<B256, SszError>;
+}
+
+/// Implements [SszEncoding] for a type that is natively [SimpleSerialize].
+macro_rules! impl_ssz_encoding {
+    ($($ty:ty),*) => {
+        $(
+            impl SszEncoding for $ty {
+                fn to_ssz_bytes(&self) -> Result<Vec<u8>, SszError> {
+                    Ok(ssz_rs::serialize(self)?)
+                }
+
+                fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+                    Ok(ssz_rs::deserialize(bytes)?)
+                }
+
+                fn ssz_hash_tree_root(&self) -> Result<B256, SszError> {
+                    Ok(B256::from(self.hash_tree_root()?.0))
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [SszEncoding] for a type through its SSZ container representation.
+macro_rules! impl_ssz_encoding_via {
+    ($($ty:ty => $container:ty),*) => {
+        $(
+            impl SszEncoding for $ty {
+                fn to_ssz_bytes(&self) -> Result<Vec<u8>, SszError> {
+                    <$container>::try_from(self)?.to_ssz_bytes()
+                }
+
+                fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+                    Self::try_from(<$container>::from_ssz_bytes(bytes)?)
+                }
+
+                fn ssz_hash_tree_root(&self) -> Result<B256, SszError> {
+                    <$container>::try_from(self)?.ssz_hash_tree_root()
+                }
+            }
+        )*
+    };
+}
+
+impl_ssz_encoding!(
+    DelegationMessage,
+    SignedDelegation,
+    RevocationMessage,
+    SignedRevocation,
+    ConstraintsMessageContainer,
+    SignedConstraintsContainer,
+    InclusionCommitmentContainer,
+    DenebPayloadAndBlobsContainer
+);
+
+impl_ssz_encoding_via!(
+    ConstraintsMessage => ConstraintsMessageContainer,
+    SignedConstraints => SignedConstraintsContainer,
+    InclusionCommitment => InclusionCommitmentContainer
+);
+
+/// SSZ container of a [ConstraintsMessage], with transactions as opaque byte lists.
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+pub struct ConstraintsMessageContainer {
+    pub pubkey: BlsPublicKey,
+    pub slot: u64,
+    pub top: bool,
+    /// The index of the top-of-block bundle, empty if the bundle has none.
+    pub index: List<u64, 1>,
+    pub transactions: Transactions,
+}
+
+/// SSZ container of a [SignedConstraints].
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+pub struct SignedConstraintsContainer {
+    pub message: ConstraintsMessageContainer,
+    pub signature: BlsSignature,
+}
+
+/// SSZ container of a batch of [SignedConstraints], as submitted to the relays.
+pub type BatchedSignedConstraintsContainer =
+    List<SignedConstraintsContainer, MAX_CONSTRAINTS_PER_SUBMISSION>;
+
+impl SszEncoding for BatchedSignedConstraints {
+    fn to_ssz_bytes(&self) -> Result<Vec<u8>, SszError> {
+        Ok(ssz_rs::serialize(&batch_container(self)?)?)
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, SszError> {
+        let container: BatchedSignedConstraintsContainer = ssz_rs::deserialize(bytes)?;
+        container.iter().cloned().map(SignedConstraints::try_from).collect()
+    }
+
+    fn ssz_hash_tree_root(&self) -> Result<B256, SszError> {
+        Ok(B256::from(batch_container(self)?.hash_tree_root()?.0))
+    }
+}
+
+/// SSZ container of a Deneb [PayloadAndBlobs], as returned by the relays to SSZ requests.
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+pub struct DenebPayloadAndBlobsContainer {
+    pub execution_payload: DenebExecutionPayload,
+    pub blobs_bundle: BlobsBundle,
+}
+
+impl From<DenebPayloadAndBlobsContainer> for PayloadAndBlobs {
+    fn from(container: DenebPayloadAndBlobsContainer) -> Self {
+        Self {
+            execution_payload: ExecutionPayload::Deneb(container.execution_payload),
+            blobs_bundle: container.blobs_bundle,
+        }
+    }
+}
+
+impl GetPayloadResponse {
+    /// Decodes the SSZ encoding of the payload returned by a relay for a block of the
+    /// given fork. Only Deneb payloads are supported.
+    pub fn from_ssz_bytes(fork: Fork, bytes: &[u8]) -> Result<Self, SszError> {
+        match fork {
+            Fork::Deneb => {
+                let container = DenebPayloadAndBlobsContainer::from_ssz_bytes(bytes)?;
+                Ok(Self::Deneb(container.into()))
+            }
+            fork => Err(SszError::UnsupportedFork(fork)),
+        }
+    }
+}
+
+/// Encodes a batch of validator registrations as an SSZ list. The registrations have a
+/// fixed size, so the list is their concatenation.
+pub fn encode_registrations(
+    registrations: &[SignedValidatorRegistration],
+) -> Result<Vec<u8>, SszError> {
+    let mut bytes = Vec::new();
+    for registration in registrations {
+        bytes.extend(ssz_rs::serialize(registration)?);
+    }
+    Ok(bytes)
+}
+
+/// SSZ container of an [InclusionCommitment]. ECDSA signatures are encoded as 65 bytes,
+/// with the last one being the parity (0 or 1).
+///
+/// The signer of the request and its signature are empty lists if unknown.
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+pub struct InclusionCommitmentContainer {
+    pub slot: u64,
+    pub transactions: Transactions,
+    /// The address of the signer of the request.
+    pub signer: List<u8, ADDRESS_LENGTH>,
+    /// The signature of the request by its signer.
+    pub request_signature: List<u8, ECDSA_SIGNATURE_LENGTH>,
+    /// The signature of the commitment by the sidecar.
+    pub signature: Vector<u8, ECDSA_SIGNATURE_LENGTH>,
+}
+
+impl TryFrom<&ConstraintsMessage> for ConstraintsMessageContainer {
+    type Error = SszError;
+
+    fn try_from(message: &ConstraintsMessage) -> Result<Self, Self::Error> {
+        let mut index = List::default();
+        if let Some(position) = message.index {
+            index.push(position);
+        }
+
+        Ok(Self {
+            pubkey: message.pubkey.clone(),
+            slot: message.slot,
+            top: message.top,
+            index,
+            transactions: encode_transactions(&message.transactions)?,
+        })
+    }
+}
+
+impl TryFrom<ConstraintsMessageContainer> for ConstraintsMessage {
+    type Error = SszError;
+
+    fn try_from(container: ConstraintsMessageContainer) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pubkey: container.pubkey,
+            slot: container.slot,
+            top: container.top,
+            index: container.index.first().copied(),
+            transactions: decode_transactions(&container.transactions)?,
+        })
+    }
+}
+
+impl TryFrom<&SignedConstraints> for SignedConstraintsContainer {
+    type Error = SszError;
+
+    fn try_from(signed: &SignedConstraints) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message: ConstraintsMessageContainer::try_from(&signed.message)?,
+            signature: BlsSignature::try_from(signed.signature.as_slice())
+                .map_err(|_| SszError::InvalidSignature)?,
+        })
+    }
+}
+
+impl TryFrom<SignedConstraintsContainer> for SignedConstraints {
+    type Error = SszError;
+
+    fn try_from(container: SignedConstraintsContainer) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message: ConstraintsMessage::try_from(container.message)?,
+            signature: BLSSig::from_slice(container.signature.as_ref()),
+        })
+    }
+}
+
+impl TryFrom<&InclusionCommitment> for InclusionCommitmentContainer {
+    type Error = SszError;
+
+    fn try_from(commitment: &InclusionCommitment) -> Result<Self, Self::Error> {
+        if commitment.request.max_slot.is_some() {
+            return Err(SszError::SlotRange);
+        }
+        if commitment.request.top {
+            return Err(SszError::TopOfBlock);
+        }
+
+        let signer = commitment.request.signer.map(|signer| signer.to_vec()).unwrap_or_default();
+        let request_signature = commitment
+            .request
+            .signature
+            .map(|signature| signature.as_bytes_with_parity().to_vec())
+            .unwrap_or_default();
+        let signature = commitment.signature.as_bytes_with_parity();
+
+        Ok(Self {
+            slot: commitment.request.slot,
+            transactions: encode_transactions(&commitment.request.txs)?,
+            signer: List::try_from(signer).map_err(|_| SszError::InvalidSigner)?,
+            request_signature: List::try_from(request_signature)
+                .map_err(|_| SszError::InvalidSignature)?,
+            signature: Vector::try_from(signature.to_vec())
+                .map_err(|_| SszError::InvalidSignature)?,
+        })
+    }
+}
+
+impl TryFrom<InclusionCommitmentContainer> for InclusionCommitment {
+    type Error = SszError;
+
+    fn try_from(container: InclusionCommitmentContainer) -> Result<Self, Self::Error> {
+        let request = InclusionRequest {
+            slot: container.slot,
+            max_slot: None,
+            top: false,
+            partial: false,
+            batch: false,
+            txs: decode_transactions(&container.transactions)?,
+            signature: decode_optional_signature(container.request_signature.as_ref())?,
+            signer: decode_optional_signer(container.signer.as_ref())?,
+            voucher: None,
+        };
+
+        let signature = Signature::try_from(container.signature.as_ref())
+            .map_err(|_| SszError::InvalidSignature)?;
+
+        Ok(Self { request, signature, outcomes: Vec::new() })
+    }
+}
+
+/// Encodes the transactions in their EIP-2718 envelope, as done in execution payloads.
+fn encode_transactions(txs: &[FullTransaction]) -> Result<Transactions, SszError> {
+    if txs.len() > MAX_TRANSACTIONS_PER_MESSAGE {
+        return Err(SszError::TooManyTransactions(txs.len()));
+    }
+
+    let mut transactions = Transactions::default();
+    for tx in txs {
+        let encoded = tx.envelope_encoded();
+        let tx = Transaction::try_from(encoded.as_ref())
+            .map_err(|e| SszError::InvalidTransaction(e.to_string()))?;
+        transactions.push(tx);
+    }
+
+    Ok(transactions)
+}
+
+fn batch_container(
+    batch: &BatchedSignedConstraints,
+) -> Result<BatchedSignedConstraintsContainer, SszError> {
+    let containers =
+        batch.iter().map(SignedConstraintsContainer::try_from).collect::<Result<Vec<_>, _>>()?;
+    BatchedSignedConstraintsContainer::try_from(containers)
+        .map_err(|_| SszError::TooManyConstraints(batch.len()))
+}
+
+/// Decodes an optional ECDSA signature, encoded as an empty list if missing.
+fn decode_optional_signature(bytes: &[u8]) -> Result<Option<Signature>, SszError> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    Signature::try_from(bytes).map(Some).map_err(|_| SszError::InvalidSignature)
+}
+
+/// Decodes an optional address, encoded as an empty list if missing.
+fn decode_optional_signer(bytes: &[u8]) -> Result<Option<Address>, SszError> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    Address::try_from(bytes).map(Some).map_err(|_| SszError::InvalidSigner)
+}
+
+fn decode_transactions(transactions: &Transactions) -> Result<Vec<FullTransaction>, SszError> {
+    transactions
+        .iter()
+        .map(|tx| {
+            FullTransaction::decode_enveloped(tx.as_ref())
+                .map_err(|e| SszError::InvalidTransaction(e.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{b256, hex},
+        signers::{local::PrivateKeySigner, SignerSync},
+    };
+
+    use super::*;
+    use crate::{
+        crypto::SignableBLS, signer::local::LocalSigner, test_util::test_legacy_transaction,
+    };
+
+    #[test]
+    fn test_constraints_ssz_roundtrip() {
+        let signer = LocalSigner::random();
+        let message =
+            ConstraintsMessage::from_transaction(signer.pubkey(), 165, test_legacy_transaction());
+        let signature = signer.sign_commit_boost_root(message.digest()).unwrap();
+        let signed = SignedConstraints { message, signature };
+
+        let bytes = signed.to_ssz_bytes().unwrap();
+        let decoded = SignedConstraints::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded, signed);
+
+        assert_eq!(
+            decoded.message.ssz_hash_tree_root().unwrap(),
+            signed.message.ssz_hash_tree_root().unwrap()
+        );
+
+        // Top-of-block bundles keep their index
+        let indexed = ConstraintsMessage { top: true, index: Some(2), ..signed.message };
+        let bytes = indexed.to_ssz_bytes().unwrap();
+        assert_eq!(ConstraintsMessage::from_ssz_bytes(&bytes).unwrap(), indexed);
+    }
+
+    #[test]
+    fn test_batched_constraints_ssz_roundtrip() {
+        let signer = LocalSigner::random();
+        let batch = (165..168)
+            .map(|slot| {
+                let message = ConstraintsMessage::from_transaction(
+                    signer.pubkey(),
+                    slot,
+                    test_legacy_transaction(),
+                );
+                let signature = signer.sign_commit_boost_root(message.digest()).unwrap();
+                SignedConstraints { message, signature }
+            })
+            .collect::<BatchedSignedConstraints>();
+
+        let bytes = batch.to_ssz_bytes().unwrap();
+        assert_eq!(BatchedSignedConstraints::from_ssz_bytes(&bytes).unwrap(), batch);
+
+        let registrations = vec![SignedValidatorRegistration::default(); 2];
+        // fee recipient (20) + gas limit (8) + timestamp (8) + pubkey (48) + signature (96)
+        assert_eq!(encode_registrations(&registrations).unwrap().len(), 2 * 180);
+    }
+
+    #[test]
+    fn test_delegation_ssz_roundtrip() {
+        let signer = LocalSigner::random();
+        let message = DelegationMessage::new(signer.pubkey(), BlsPublicKey::default());
+        let signature = signer.sign_commit_boost_root(message.digest()).unwrap();
+        let signature = BlsSignature::try_from(signature.as_slice()).unwrap();
+        let signed = SignedDelegation { message, signature };
+
+        let bytes = signed.to_ssz_bytes().unwrap();
+        // action (1) + 2 pubkeys (48) + scope (8 + 1 + 8) + signature (96)
+        assert_eq!(bytes.len(), 1 + 48 * 2 + 17 + 96);
+        assert_eq!(SignedDelegation::from_ssz_bytes(&bytes).unwrap(), signed);
+
+        let message = RevocationMessage::new(signer.pubkey(), BlsPublicKey::default());
+        let signature = signer.sign_commit_boost_root(message.digest()).unwrap();
+        let signature = BlsSignature::try_from(signature.as_slice()).unwrap();
+        let signed = SignedRevocation { message, signature };
+
+        let bytes = signed.to_ssz_bytes().unwrap();
+        assert_eq!(SignedRevocation::from_ssz_bytes(&bytes).unwrap(), signed);
+    }
+
+    #[tokio::test]
+    async fn test_inclusion_commitment_ssz_roundtrip() {
+        let user = PrivateKeySigner::random();
+        let mut request = InclusionRequest {
+            slot: 42,
+            max_slot: None,
+            top: false,
+            partial: false,
+            batch: false,
+            txs: vec![test_legacy_transaction()],
+            signature: None,
+            signer: Some(user.address()),
+            voucher: None,
+        };
+        request.signature = Some(user.sign_hash_sync(&request.digest()).unwrap());
+
+        let signer = PrivateKeySigner::random();
+
+        let commitment = InclusionCommitment::from(
+            crate::primitives::CommitmentRequest::Inclusion(request)
+                .commit_and_sign(&signer)
+                .await
+                .unwrap(),
+        );
+
+        let bytes = commitment.to_ssz_bytes().unwrap();
+        let decoded = InclusionCommitment::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded, commitment);
+        assert_eq!(decoded.request().signer, Some(user.address()));
+        assert_eq!(decoded.recover_signer().unwrap(), signer.address());
+    }
+
+    /// The vectors are generated by `test_data/ssz/generate_vectors.py`, a standalone
+    /// implementation of the SSZ spec (consensus-specs v1.4.0) on the Python standard library.
+    #[test]
+    fn test_ssz_reference_vectors() {
+        let validator_pubkey = BlsPublicKey::try_from(hex!("8a37d5942b2919e4e77f7784805146da013bd4cd0c77eee5f689873980a23c70570dfd08abc3b267003b32d2e1c015eb").as_ref()).unwrap();
+        let delegatee_pubkey = BlsPublicKey::try_from(hex!("8a5985a8000d845913dad7651ea42f30b71b561cf759189f3390ddfa726d1112b182af8547a8393af24116173832442f").as_ref()).unwrap();
+        // The point at infinity
+        let mut signature = [0; 96];
+        signature[0] = 0xc0;
+        let signature = BlsSignature::try_from(signature.as_ref()).unwrap();
+
+        let revocation = SignedRevocation {
+            message: RevocationMessage::new(validator_pubkey.clone(), delegatee_pubkey),
+            signature,
+        };
+        let bytes = hex!("018a37d5942b2919e4e77f7784805146da013bd4cd0c77eee5f689873980a23c70570dfd08abc3b267003b32d2e1c015eb8a5985a8000d845913dad7651ea42f30b71b561cf759189f3390ddfa726d1112b182af8547a8393af24116173832442fc00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(revocation.to_ssz_bytes().unwrap(), bytes);
+        assert_eq!(SignedRevocation::from_ssz_bytes(&bytes).unwrap(), revocation);
+        assert_eq!(
+            revocation.message.ssz_hash_tree_root().unwrap(),
+            b256!("cf0ee4c7355dc7875b77aac9e94c25154cff88af2d5423d78105b304193d87c9")
+        );
+        assert_eq!(
+            revocation.ssz_hash_tree_root().unwrap(),
+            b256!("804232f9783e4c31a08dad245b4f8e88307a902b03926ce3b0a085c32bb4ad1d")
+        );
+
+        let constraints =
+            ConstraintsMessage::from_transaction(validator_pubkey, 165, test_legacy_transaction());
+        let bytes = hex!("8a37d5942b2919e4e77f7784805146da013bd4cd0c77eee5f689873980a23c70570dfd08abc3b267003b32d2e1c015eba50000000000000000410000004100000004000000f8678085019dc6838082520894deaddeaddeaddeaddeaddeaddeaddeaddeaddead38808360306ca06664c078fa60bd3ece050903dd295949908dd9686ec8871fa558f868e031cd39a00ed4f0b122b32b73f19230fabe6a726e2d07f84eda5beaa42a1ae1271bdee39f");
+        assert_eq!(constraints.to_ssz_bytes().unwrap(), bytes);
+        assert_eq!(ConstraintsMessage::from_ssz_bytes(&bytes).unwrap(), constraints);
+        assert_eq!(
+            constraints.ssz_hash_tree_root().unwrap(),
+            b256!("9b90737f5a2f55d537269267abbadfff51aee7ac9eefb454a49dc1c6a64b98ce")
+        );
+
+        let indexed = ConstraintsMessage { top: true, index: Some(2), ..constraints };
+        let bytes = hex!("8a37d5942b2919e4e77f7784805146da013bd4cd0c77eee5f689873980a23c70570dfd08abc3b267003b32d2e1c015eba500000000000000014100000049000000020000000000000004000000f8678085019dc6838082520894deaddeaddeaddeaddeaddeaddeaddeaddeaddead38808360306ca06664c078fa60bd3ece050903dd295949908dd9686ec8871fa558f868e031cd39a00ed4f0b122b32b73f19230fabe6a726e2d07f84eda5beaa42a1ae1271bdee39f");
+        assert_eq!(indexed.to_ssz_bytes().unwrap(), bytes);
+        assert_eq!(ConstraintsMessage::from_ssz_bytes(&bytes).unwrap(), indexed);
+        assert_eq!(
+            indexed.ssz_hash_tree_root().unwrap(),
+            b256!("afa49ac746c593a5e6dc64a02f70f4a56b7f0f21c747d97f407c3654db5cb168")
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use alloy::primitives::{keccak256, Uint, B256};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Unsigned integer of the size of the largest modulus of a time-lock puzzle.
+pub type U2048 = Uint<2048, 32>;
+
+/// The min size in bits of the modulus of a time-lock puzzle. Factoring it, which would unlock
+/// the puzzle without the squarings, takes far longer than the slot the puzzle hides a key for.
+pub const MIN_TIMELOCK_MODULUS_BITS: usize = 1024;
+
+/// Error checking a time-lock puzzle.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TimeLockError {
+    /// The modulus is too small to hide the key until the puzzle is solved.
+    #[error("the modulus must have at least {MIN_TIMELOCK_MODULUS_BITS} bits")]
+    ModulusTooSmall,
+    /// The base is 0, 1 or -1 modulo the modulus, whose squares are trivial.
+    #[error("the base must be greater than 1 and lower than the modulus minus 1")]
+    InvalidBase,
+    /// The puzzle takes more squarings to solve than the sidecar accepts.
+    #[error("too many squarings: {0} (max {1})")]
+    TooManySquarings(u64, u64),
+}
+
+/// A time-lock puzzle of Rivest, Shamir and Wagner, locking the decryption key of an encrypted
+/// request until it is solved:
+///
+/// key = locked_key ^ keccak256(be_bytes(base ^ (2 ^ squarings) mod modulus))
+///
+/// with the solution encoded on 256 bytes. Solving it takes `squarings` sequential modular
+/// squarings, which can't be parallelized. The client, which knows the factorization of the
+/// modulus, computes the solution right away with the exponent `2 ^ squarings mod phi(modulus)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLockPuzzle {
+    /// The RSA modulus, whose factorization is only known to the client.
+    pub modulus: U2048,
+    /// The base squared to solve the puzzle.
+    pub base: U2048,
+    /// The number of sequential squarings to solve the puzzle.
+    pub squarings: u64,
+    /// The decryption key, masked with the hash of the solution.
+    pub locked_key: B256,
+}
+
+impl TimeLockPuzzle {
+    /// Checks that the puzzle hides its key until solved, and takes at most the given number
+    /// of squarings to solve.
+    pub fn check(&self, max_squarings: u64) -> Result<(), TimeLockError> {
+        if self.modulus.bit_len() < MIN_TIMELOCK_MODULUS_BITS {
+            return Err(TimeLockError::ModulusTooSmall);
+        }
+        if self.base <= U2048::from(1) || self.base >= self.modulus - U2048::from(1) {
+            return Err(TimeLockError::InvalidBase);
+        }
+        if self.squarings > max_squarings {
+            return Err(TimeLockError::TooManySquarings(self.squarings, max_squarings));
+        }
+        Ok(())
+    }
+
+    /// Solves the puzzle with sequential squarings, returning the key it locks. This takes time
+    /// proportional to the number of squarings, so it must run on a blocking thread.
+    pub fn solve(&self) -> B256 {
+        let mut solution = self.base;
+        for _ in 0..self.squarings {
+            solution = solution.mul_mod(solution, self.modulus);
+        }
+        self.unlock(&solution)
+    }
+
+    /// Returns the key locked by the puzzle, given its solution.
+    pub fn unlock(&self, solution: &U2048) -> B256 {
+        self.locked_key ^ keccak256(solution.to_be_bytes::<256>())
+    }
+
+    /// Returns the hash of the puzzle, signed along with the ciphertext of its request:
+    ///
+    /// puzzle_hash = keccak256(
+    ///     be_bytes(modulus) | be_bytes(base) | be_bytes(squarings) | bytes(locked_key)
+    /// )
+    ///
+    /// with the modulus and the base encoded on 256 bytes.
+    pub fn hash(&self) -> B256 {
+        let mut data = Vec::with_capacity(552);
+        data.extend_from_slice(&self.modulus.to_be_bytes::<256>());
+        data.extend_from_slice(&self.base.to_be_bytes::<256>());
+        data.extend_from_slice(&self.squarings.to_be_bytes());
+        data.extend_from_slice(self.locked_key.as_slice());
+
+        keccak256(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{lock_key, test_timelock_primes};
+
+    use super::*;
+
+    #[test]
+    fn test_timelock_puzzle() {
+        let key = B256::random();
+        let puzzle = lock_key(&key, 1000);
+
+        assert!(puzzle.check(1000).is_ok());
+        assert_eq!(puzzle.solve(), key);
+
+        // The key is only unlocked by the solution after all the squarings
+        let fewer = TimeLockPuzzle { squarings: 999, ..puzzle.clone() };
+        assert_ne!(fewer.solve(), key);
+        assert_eq!(puzzle.check(999), Err(TimeLockError::TooManySquarings(1000, 999)));
+
+        let (p, _) = test_timelock_primes();
+        let small = TimeLockPuzzle { modulus: p, ..puzzle.clone() };
+        assert_eq!(small.check(1000), Err(TimeLockError::ModulusTooSmall));
+
+        let trivial = TimeLockPuzzle { base: puzzle.modulus - U2048::from(1), ..puzzle.clone() };
+        assert_eq!(trivial.check(1000), Err(TimeLockError::InvalidBase));
+
+        assert_ne!(fewer.hash(), puzzle.hash());
+    }
+}
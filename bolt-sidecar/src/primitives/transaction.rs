@@ -1,8 +1,15 @@
-use std::fmt::Debug;
-use std::{borrow::Cow, fmt};
+use std::{
+    borrow::Cow,
+    fmt,
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
 
 use alloy::primitives::{Address, U256};
-use reth_primitives::{BlobTransactionSidecar, Bytes, PooledTransactionsElement, TxKind, TxType};
+use reth_primitives::{
+    BlobTransactionSidecar, Bytes, PooledTransactionsElement, Transaction, TransactionSigned,
+    TxKind, TxType,
+};
 use serde::{de, ser::SerializeSeq};
 
 /// Trait that exposes additional information on transaction types that don't already do it
@@ -112,23 +119,41 @@ pub const fn tx_type_str(tx_type: TxType) -> &'static str {
 }
 
 /// A wrapper type for a full, complete transaction (i.e. with blob sidecars attached).
-#[derive(Clone, PartialEq, Eq)]
+///
+/// The transaction is decoded once from its raw EIP-2718 bytes, which are kept alongside
+/// the decoded view so that they never need to be re-encoded. Both are reference-counted,
+/// so clones are cheap and share the same data, including the blob sidecar.
+#[derive(Clone)]
 pub struct FullTransaction {
-    pub tx: PooledTransactionsElement,
+    tx: Arc<PooledTransactionsElement>,
+    /// The EIP-2718 network encoding, including the blob sidecar if any.
+    raw: bytes::Bytes,
+    /// The EIP-2718 encoding as included in a payload, computed on first use.
+    payload_raw: OnceLock<bytes::Bytes>,
     pub sender: Option<Address>,
 }
 
 impl From<PooledTransactionsElement> for FullTransaction {
     fn from(tx: PooledTransactionsElement) -> Self {
-        Self { tx, sender: None }
+        let raw = tx.envelope_encoded().0;
+        Self { tx: Arc::new(tx), raw, payload_raw: OnceLock::new(), sender: None }
     }
 }
 
+impl PartialEq for FullTransaction {
+    fn eq(&self, other: &Self) -> bool {
+        // The encoding is canonical, so the raw bytes identify the transaction
+        self.raw == other.raw && self.sender == other.sender
+    }
+}
+
+impl Eq for FullTransaction {}
+
 impl fmt::Debug for FullTransaction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut debug_struct = f.debug_struct("FullTransaction");
 
-        match &self.tx {
+        match self.tx.as_ref() {
             PooledTransactionsElement::BlobTransaction(blob_tx) => {
                 let shortened_blobs: Vec<String> =
                     // Use alternative `Display` to print trimmed blob
@@ -161,21 +186,57 @@ impl std::ops::Deref for FullTransaction {
     }
 }
 
-impl std::ops::DerefMut for FullTransaction {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.tx
-    }
-}
-
 impl FullTransaction {
     /// Convenience method to parse a raw transaction into a `FullTransaction`.
     pub fn decode_enveloped(data: impl AsRef<[u8]>) -> eyre::Result<Self> {
-        let tx = PooledTransactionsElement::decode_enveloped(&mut data.as_ref())?;
-        Ok(Self { tx, sender: None })
+        Self::from_raw(bytes::Bytes::copy_from_slice(data.as_ref()))
+    }
+
+    /// Parse a raw EIP-2718 transaction, keeping the given buffer without copying it.
+    pub fn from_raw(raw: bytes::Bytes) -> eyre::Result<Self> {
+        let tx = PooledTransactionsElement::decode_enveloped(&mut raw.as_ref())?;
+        Ok(Self { tx: Arc::new(tx), raw, payload_raw: OnceLock::new(), sender: None })
     }
 
+    /// Returns the inner transaction, cloning it if it is shared with other clones.
     pub fn into_inner(self) -> PooledTransactionsElement {
-        self.tx
+        Arc::unwrap_or_clone(self.tx)
+    }
+
+    /// Returns the raw EIP-2718 network encoding of the transaction, including the blob
+    /// sidecar if any, as it was decoded.
+    pub fn raw(&self) -> &bytes::Bytes {
+        &self.raw
+    }
+
+    /// Returns the EIP-2718 network encoding of the transaction, without re-encoding it.
+    pub fn envelope_encoded(&self) -> Bytes {
+        Bytes(self.raw.clone())
+    }
+
+    /// Returns the EIP-2718 encoding of the transaction as included in an execution payload,
+    /// i.e. without the blob sidecar. It is computed once, and only differs from [Self::raw]
+    /// for blob transactions.
+    pub fn payload_encoded(&self) -> &bytes::Bytes {
+        self.payload_raw.get_or_init(|| match self.tx.as_ref() {
+            PooledTransactionsElement::BlobTransaction(_) => {
+                self.to_signed_transaction().envelope_encoded().0
+            }
+            _ => self.raw.clone(),
+        })
+    }
+
+    /// Returns the signed transaction as included in an execution payload, without
+    /// cloning the blob sidecar.
+    pub fn to_signed_transaction(&self) -> TransactionSigned {
+        match self.tx.as_ref() {
+            PooledTransactionsElement::BlobTransaction(blob_tx) => TransactionSigned {
+                hash: blob_tx.hash,
+                signature: blob_tx.signature,
+                transaction: Transaction::Eip4844(blob_tx.transaction.clone()),
+            },
+            other => other.clone().into_transaction(),
+        }
     }
 
     /// Returns the sender of the transaction, if recovered.
@@ -214,8 +275,7 @@ pub fn serialize_txs<S: serde::Serializer>(
 ) -> Result<S::Ok, S::Error> {
     let mut seq = serializer.serialize_seq(Some(txs.len()))?;
     for tx in txs {
-        let encoded = tx.tx.envelope_encoded();
-        seq.serialize_element(&format!("0x{}", hex::encode(encoded)))?;
+        seq.serialize_element(&format!("0x{}", hex::encode(tx.raw())))?;
     }
     seq.end()
 }
@@ -230,11 +290,54 @@ where
 
     for s in hex_strings {
         let data = hex::decode(s.trim_start_matches("0x")).map_err(de::Error::custom)?;
-        let tx = PooledTransactionsElement::decode_enveloped(&mut data.as_slice())
-            .map_err(de::Error::custom)
-            .map(|tx| FullTransaction { tx, sender: None })?;
+        // The decoded buffer is moved into the transaction, without copying it
+        let tx = FullTransaction::from_raw(data.into()).map_err(de::Error::custom)?;
         txs.push(tx);
     }
 
     Ok(txs)
 }
+
+/// Serialize a transaction as a hex-encoded string.
+pub fn serialize_tx<S: serde::Serializer>(
+    tx: &FullTransaction,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(tx.raw())))
+}
+
+/// Deserialize a transaction from a hex-encoded string.
+pub fn deserialize_tx<'de, D>(deserializer: D) -> Result<FullTransaction, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = <Cow<'_, str> as de::Deserialize>::deserialize(deserializer)?;
+    let data = hex::decode(s.trim_start_matches("0x")).map_err(de::Error::custom)?;
+    FullTransaction::from_raw(data.into()).map_err(de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TEST_LEGACY_TX;
+
+    #[test]
+    fn test_full_transaction_keeps_raw_bytes() {
+        let raw = TEST_LEGACY_TX;
+        let tx = FullTransaction::from_raw(raw.0.clone()).unwrap();
+
+        // The decoded view is encoded the same way as the raw bytes it was decoded from
+        assert_eq!(tx.raw(), &raw.0);
+        assert_eq!(tx.tx.envelope_encoded(), raw);
+        assert_eq!(tx.payload_encoded(), &raw.0);
+
+        // Clones share the same buffer instead of copying it
+        let clone = tx.clone();
+        assert_eq!(clone.raw().as_ptr(), tx.raw().as_ptr());
+        assert_eq!(clone, tx);
+
+        let json = format!(r#"["0x{}"]"#, hex::encode(&raw));
+        let txs = deserialize_txs(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(txs, vec![tx]);
+    }
+}
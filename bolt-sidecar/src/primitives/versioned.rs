@@ -0,0 +1,391 @@
+use alloy::{
+    primitives::{B256, U256},
+    signers::k256::sha2::{Digest, Sha256},
+};
+use bytes::Bytes;
+use ethereum_consensus::{
+    crypto::KzgCommitment,
+    deneb::{mainnet::MAX_BLOB_COMMITMENTS_PER_BLOCK, presets::mainnet::ExecutionPayloadHeader},
+    primitives::{Bytes32, ExecutionAddress},
+    serde::as_str,
+    ssz::prelude::*,
+    Fork,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{BlsPublicKey, BlsSignature, SignedBuilderBid, SszError};
+
+/// The maximum number of deposit requests in an Electra execution payload.
+pub const MAX_DEPOSIT_REQUESTS_PER_PAYLOAD: usize = 8192;
+/// The maximum number of withdrawal requests in an Electra execution payload.
+pub const MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD: usize = 16;
+/// The maximum number of consolidation requests in an Electra execution payload.
+pub const MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD: usize = 2;
+
+/// The EIP-7685 type of the deposit requests.
+pub const DEPOSIT_REQUEST_TYPE: u8 = 0x00;
+/// The EIP-7685 type of the withdrawal requests.
+pub const WITHDRAWAL_REQUEST_TYPE: u8 = 0x01;
+/// The EIP-7685 type of the consolidation requests.
+pub const CONSOLIDATION_REQUEST_TYPE: u8 = 0x02;
+
+/// A deposit request of an execution payload (EIP-6110).
+#[derive(Debug, Default, Clone, PartialEq, SimpleSerialize, Serialize, Deserialize)]
+pub struct DepositRequest {
+    pub pubkey: BlsPublicKey,
+    pub withdrawal_credentials: Bytes32,
+    #[serde(with = "as_str")]
+    pub amount: u64,
+    pub signature: BlsSignature,
+    #[serde(with = "as_str")]
+    pub index: u64,
+}
+
+/// A withdrawal request of an execution payload, triggered from the execution layer (EIP-7002).
+#[derive(Debug, Default, Clone, PartialEq, SimpleSerialize, Serialize, Deserialize)]
+pub struct WithdrawalRequest {
+    pub source_address: ExecutionAddress,
+    pub validator_pubkey: BlsPublicKey,
+    #[serde(with = "as_str")]
+    pub amount: u64,
+}
+
+/// A consolidation request of an execution payload (EIP-7251).
+#[derive(Debug, Default, Clone, PartialEq, SimpleSerialize, Serialize, Deserialize)]
+pub struct ConsolidationRequest {
+    pub source_address: ExecutionAddress,
+    pub source_pubkey: BlsPublicKey,
+    pub target_pubkey: BlsPublicKey,
+}
+
+/// The execution layer requests of an Electra execution payload, which builder bids
+/// commit to next to the payload header.
+#[derive(Debug, Default, Clone, PartialEq, SimpleSerialize, Serialize, Deserialize)]
+pub struct ExecutionRequests {
+    pub deposits: List<DepositRequest, MAX_DEPOSIT_REQUESTS_PER_PAYLOAD>,
+    pub withdrawals: List<WithdrawalRequest, MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD>,
+    pub consolidations: List<ConsolidationRequest, MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD>,
+}
+
+impl ExecutionRequests {
+    /// Decodes the `executionRequests` of the engine API (EIP-7685): the non-empty lists of
+    /// requests in increasing order of type, each encoded as its request type followed by the
+    /// SSZ encoding of the list.
+    pub fn from_engine<T: AsRef<[u8]>>(requests: &[T]) -> Result<Self, SszError> {
+        let mut decoded = Self::default();
+        let mut last_type = None;
+        for request in requests {
+            let Some((&request_type, data)) = request.as_ref().split_first() else {
+                return Err(SszError::InvalidExecutionRequests("empty request".to_string()));
+            };
+            if data.is_empty() || last_type >= Some(request_type) {
+                return Err(SszError::InvalidExecutionRequests(format!(
+                    "requests of type {request_type} are empty or out of order"
+                )));
+            }
+            last_type = Some(request_type);
+
+            match request_type {
+                DEPOSIT_REQUEST_TYPE => decoded.deposits = ssz_rs::deserialize(data)?,
+                WITHDRAWAL_REQUEST_TYPE => decoded.withdrawals = ssz_rs::deserialize(data)?,
+                CONSOLIDATION_REQUEST_TYPE => decoded.consolidations = ssz_rs::deserialize(data)?,
+                other => {
+                    return Err(SszError::InvalidExecutionRequests(format!(
+                        "unknown request type {other}"
+                    )))
+                }
+            }
+        }
+
+        Ok(decoded)
+    }
+
+    /// Encodes the requests as the `executionRequests` of the engine API, see
+    /// [Self::from_engine].
+    pub fn to_engine(&self) -> Result<Vec<Vec<u8>>, SszError> {
+        let lists = [
+            (DEPOSIT_REQUEST_TYPE, ssz_rs::serialize(&self.deposits)?),
+            (WITHDRAWAL_REQUEST_TYPE, ssz_rs::serialize(&self.withdrawals)?),
+            (CONSOLIDATION_REQUEST_TYPE, ssz_rs::serialize(&self.consolidations)?),
+        ];
+
+        Ok(lists
+            .into_iter()
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(request_type, data)| std::iter::once(request_type).chain(data).collect())
+            .collect())
+    }
+
+    /// Returns the hash of the requests committed to by the execution block header
+    /// (EIP-7685), over their encoding for the engine API:
+    ///
+    /// requests_hash = sha256(sha256(requests_0) | sha256(requests_1) | ...)
+    pub fn requests_hash(&self) -> Result<B256, SszError> {
+        let mut hasher = Sha256::new();
+        for requests in self.to_engine()? {
+            hasher.update(Sha256::digest(&requests));
+        }
+
+        Ok(B256::from_slice(&hasher.finalize()))
+    }
+
+    /// Returns the requests without the deposits. Deposits are created by the transactions
+    /// of a block, while the withdrawal and consolidation requests are dequeued from their
+    /// system contracts at the end of the block.
+    pub fn without_deposits(self) -> Self {
+        Self { deposits: List::default(), ..self }
+    }
+}
+
+/// A builder bid of the Electra fork. The payload header is unchanged from Deneb.
+#[derive(Debug, Default, Clone, SimpleSerialize, Serialize, Deserialize)]
+pub struct ElectraBuilderBid {
+    pub header: ExecutionPayloadHeader,
+    pub blob_kzg_commitments: List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+    pub execution_requests: ExecutionRequests,
+    #[serde(with = "as_str")]
+    pub value: U256,
+    #[serde(rename = "pubkey")]
+    pub public_key: BlsPublicKey,
+}
+
+#[derive(Debug, Default, Clone, SimpleSerialize, Serialize, Deserialize)]
+pub struct SignedElectraBuilderBid {
+    pub message: ElectraBuilderBid,
+    pub signature: BlsSignature,
+}
+
+/// A signed builder bid of any of the supported forks, tagged with its fork version
+/// like the `getHeader` responses of the builder-specs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "version", content = "data")]
+pub enum VersionedSignedBuilderBid {
+    #[serde(rename = "deneb")]
+    Deneb(SignedBuilderBid),
+    #[serde(rename = "electra")]
+    Electra(SignedElectraBuilderBid),
+}
+
+impl VersionedSignedBuilderBid {
+    /// The forks whose bids are supported.
+    pub const SUPPORTED_FORKS: [Fork; 2] = [Fork::Deneb, Fork::Electra];
+
+    /// Returns the fork of the bid.
+    pub fn fork(&self) -> Fork {
+        match self {
+            Self::Deneb(_) => Fork::Deneb,
+            Self::Electra(_) => Fork::Electra,
+        }
+    }
+
+    /// Returns the header of the payload the bid is for.
+    pub fn header(&self) -> &ExecutionPayloadHeader {
+        match self {
+            Self::Deneb(bid) => &bid.message.header,
+            Self::Electra(bid) => &bid.message.header,
+        }
+    }
+
+    /// Returns the value of the bid, in wei.
+    pub fn value(&self) -> U256 {
+        match self {
+            Self::Deneb(bid) => bid.message.value,
+            Self::Electra(bid) => bid.message.value,
+        }
+    }
+}
+
+impl From<SignedBuilderBid> for VersionedSignedBuilderBid {
+    fn from(bid: SignedBuilderBid) -> Self {
+        Self::Deneb(bid)
+    }
+}
+
+/// A signed blinded beacon block of any of the supported forks.
+///
+/// The sidecar only needs the slot and the execution payload header of the block, so the
+/// block is decoded partially and forwarded to the relays as it was received. This keeps
+/// the sidecar from re-encoding the fork-specific parts of the block, e.g. the Electra
+/// attestations and execution requests.
+#[derive(Debug, Clone)]
+pub struct VersionedSignedBlindedBlock {
+    fork: Fork,
+    slot: u64,
+    execution_payload_header: ExecutionPayloadHeader,
+    raw: Bytes,
+}
+
+/// The fields of a signed blinded beacon block that are common to the supported forks.
+#[derive(Deserialize)]
+struct PartialSignedBlindedBlock {
+    message: PartialBlindedBlock,
+}
+
+#[derive(Deserialize)]
+struct PartialBlindedBlock {
+    #[serde(with = "as_str")]
+    slot: u64,
+    body: PartialBlindedBlockBody,
+}
+
+#[derive(Deserialize)]
+struct PartialBlindedBlockBody {
+    execution_payload_header: ExecutionPayloadHeader,
+}
+
+impl VersionedSignedBlindedBlock {
+    /// Decodes a JSON signed blinded beacon block of the given fork.
+    pub fn from_json(fork: Fork, raw: Bytes) -> Result<Self, serde_json::Error> {
+        let block = serde_json::from_slice::<PartialSignedBlindedBlock>(&raw)?;
+
+        Ok(Self {
+            fork,
+            slot: block.message.slot,
+            execution_payload_header: block.message.body.execution_payload_header,
+            raw,
+        })
+    }
+
+    /// Returns the fork of the block.
+    pub fn fork(&self) -> Fork {
+        self.fork
+    }
+
+    /// Returns the slot of the block.
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// Returns the execution payload header of the block.
+    pub fn execution_payload_header(&self) -> &ExecutionPayloadHeader {
+        &self.execution_payload_header
+    }
+
+    /// Returns the JSON encoding of the block, as it was received.
+    pub fn as_json(&self) -> &Bytes {
+        &self.raw
+    }
+
+    /// Returns the SSZ encoding of the block. Only Deneb blocks are fully decoded by the
+    /// sidecar, so the blocks of the later forks are only forwarded as JSON.
+    pub fn to_ssz_bytes(&self) -> Result<Vec<u8>, SszError> {
+        if self.fork != Fork::Deneb {
+            return Err(SszError::UnsupportedFork(self.fork));
+        }
+
+        let block = serde_json::from_slice::<
+            ethereum_consensus::deneb::mainnet::SignedBlindedBeaconBlock,
+        >(&self.raw)
+        .map_err(|err| SszError::InvalidBlock(err.to_string()))?;
+        Ok(ssz_rs::serialize(&block)?)
+    }
+}
+
+impl From<ethereum_consensus::deneb::mainnet::SignedBlindedBeaconBlock>
+    for VersionedSignedBlindedBlock
+{
+    fn from(block: ethereum_consensus::deneb::mainnet::SignedBlindedBeaconBlock) -> Self {
+        let raw = serde_json::to_vec(&block).expect("serializable blinded block");
+
+        Self {
+            fork: Fork::Deneb,
+            slot: block.message.slot,
+            execution_payload_header: block.message.body.execution_payload_header,
+            raw: raw.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+    use ethereum_consensus::deneb::mainnet::SignedBlindedBeaconBlock;
+
+    use super::*;
+
+    #[test]
+    fn test_versioned_bid_decoding() {
+        let deneb = serde_json::json!({
+            "version": "deneb",
+            "data": SignedBuilderBid::default(),
+        });
+        let bid = serde_json::from_value::<VersionedSignedBuilderBid>(deneb).unwrap();
+        assert_eq!(bid.fork(), Fork::Deneb);
+
+        let mut electra_bid = SignedElectraBuilderBid::default();
+        electra_bid.message.value = U256::from(42);
+        electra_bid.message.execution_requests.withdrawals.push(WithdrawalRequest::default());
+        let electra = serde_json::json!({ "version": "electra", "data": electra_bid });
+
+        let bid = serde_json::from_value::<VersionedSignedBuilderBid>(electra.clone()).unwrap();
+        assert_eq!(bid.fork(), Fork::Electra);
+        assert_eq!(bid.value(), U256::from(42));
+        assert_eq!(serde_json::to_value(&bid).unwrap(), electra);
+
+        let capella = serde_json::json!({ "version": "capella", "data": {} });
+        assert!(serde_json::from_value::<VersionedSignedBuilderBid>(capella).is_err());
+    }
+
+    #[test]
+    fn test_execution_requests_engine_encoding() {
+        let empty = ExecutionRequests::default();
+        assert!(empty.to_engine().unwrap().is_empty());
+        assert_eq!(
+            empty.requests_hash().unwrap(),
+            b256!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+
+        let mut requests = ExecutionRequests::default();
+        requests.deposits.push(DepositRequest { amount: 32, ..Default::default() });
+        requests.withdrawals.push(WithdrawalRequest {
+            source_address: ExecutionAddress::try_from([0x11; 20].as_ref()).unwrap(),
+            amount: 5,
+            ..Default::default()
+        });
+
+        let encoded = requests.to_engine().unwrap();
+        assert_eq!(encoded.len(), 2);
+        assert_eq!(encoded[1].len(), 77);
+        assert_eq!(ExecutionRequests::from_engine(&encoded).unwrap(), requests);
+
+        // The reference hash of the withdrawal request alone, computed from its encoding
+        let withdrawals = requests.clone().without_deposits();
+        assert!(withdrawals.deposits.is_empty());
+        assert_eq!(
+            withdrawals.requests_hash().unwrap(),
+            b256!("51fbff89612a29d54aabaa1fde678b96003b93ece37dd87cb564a122e4cabcb6")
+        );
+
+        // Empty, unsorted and unknown requests are rejected
+        let reversed = encoded.iter().rev().collect::<Vec<_>>();
+        assert!(ExecutionRequests::from_engine(&reversed).is_err());
+        assert!(ExecutionRequests::from_engine(&[vec![WITHDRAWAL_REQUEST_TYPE]]).is_err());
+        assert!(ExecutionRequests::from_engine(&[vec![0x03, 0x00]]).is_err());
+    }
+
+    #[test]
+    fn test_blinded_block_partial_decoding() {
+        let mut block = SignedBlindedBeaconBlock::default();
+        block.message.slot = 42;
+        block.message.body.execution_payload_header.block_number = 7;
+
+        // The Electra block has fields unknown to the Deneb types
+        let mut json = serde_json::to_value(&block).unwrap();
+        json["message"]["body"]["execution_requests"] =
+            serde_json::to_value(ExecutionRequests::default()).unwrap();
+        let raw = Bytes::from(serde_json::to_vec(&json).unwrap());
+
+        let versioned = VersionedSignedBlindedBlock::from_json(Fork::Electra, raw.clone()).unwrap();
+        assert_eq!(versioned.fork(), Fork::Electra);
+        assert_eq!(versioned.slot(), 42);
+        assert_eq!(versioned.execution_payload_header().block_number, 7);
+        assert_eq!(versioned.as_json(), &raw);
+
+        assert!(versioned.to_ssz_bytes().is_err());
+
+        let deneb = VersionedSignedBlindedBlock::from(block.clone());
+        assert_eq!(deneb.fork(), Fork::Deneb);
+        assert_eq!(deneb.slot(), 42);
+        assert_eq!(deneb.to_ssz_bytes().unwrap(), ssz_rs::serialize(&block).unwrap());
+    }
+}
@@ -0,0 +1,46 @@
+use alloy::primitives::{keccak256, Address, Signature, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use super::commitment::{
+    deserialize_sig, serialize_sig, CommitmentType, SignatureError, COMMITMENT_DIGEST_VERSION,
+};
+
+/// A micro-payment voucher signed by the payer of an inclusion request, authorizing the
+/// payee to claim a cumulative amount from the off-chain payment channel of the payer.
+///
+/// Every voucher supersedes the previous ones of the same payer, so that only the latest
+/// one needs to be settled on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentVoucher {
+    /// The payee of the channel, i.e. the commitment signer of the sidecar.
+    pub payee: Address,
+    /// The cumulative amount in wei paid over the channel, including this voucher.
+    pub amount: U256,
+    /// The signature of the payer over the digest of the voucher.
+    #[serde(deserialize_with = "deserialize_sig", serialize_with = "serialize_sig")]
+    pub signature: Signature,
+}
+
+impl PaymentVoucher {
+    /// Returns the canonical digest of the voucher on the given chain, signed by payers:
+    ///
+    /// digest = keccak256(
+    ///     u8(version) | u8(commitment_type) | be_bytes(chain_id) | payee | be_bytes32(amount)
+    /// )
+    pub fn signing_digest(payee: Address, amount: U256, chain_id: u64) -> B256 {
+        let mut data = Vec::with_capacity(62);
+        data.push(COMMITMENT_DIGEST_VERSION);
+        data.push(CommitmentType::PaymentVoucher as u8);
+        data.extend_from_slice(&chain_id.to_be_bytes());
+        data.extend_from_slice(payee.as_slice());
+        data.extend_from_slice(&amount.to_be_bytes::<32>());
+
+        keccak256(&data)
+    }
+
+    /// Recovers the payer of the voucher on the given chain.
+    pub fn recover_payer(&self, chain_id: u64) -> Result<Address, SignatureError> {
+        let digest = Self::signing_digest(self.payee, self.amount, chain_id);
+        self.signature.recover_address_from_prehash(&digest).map_err(|_| SignatureError)
+    }
+}
@@ -0,0 +1,230 @@
+use std::{fmt::Write, fs, path::PathBuf};
+
+use clap::ValueEnum;
+use eyre::{Context, Result};
+
+/// The name of the service installed by the definitions.
+pub const SERVICE_NAME: &str = "bolt-sidecar";
+
+/// The label of the launchd job.
+const LAUNCHD_LABEL: &str = "xyz.boltprotocol.sidecar";
+
+/// The watchdog interval of the systemd unit, in seconds. The driver beats at least once per
+/// slot, so a minute without beats means it is stalled.
+const SYSTEMD_WATCHDOG_SECS: u64 = 60;
+
+/// The service managers the sidecar can be installed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ServicePlatform {
+    /// A systemd unit on Linux, notifying readiness and pinging the watchdog
+    Systemd,
+    /// A launchd property list on macOS
+    Launchd,
+    /// A WinSW service wrapper configuration on Windows
+    Windows,
+}
+
+/// The definition of a service running the sidecar, rendered for a service manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceDefinition {
+    /// The path of the sidecar binary.
+    pub executable: PathBuf,
+    /// The path of the file with the `BOLT_SIDECAR_*` environment variables, if any.
+    pub env_file: Option<PathBuf>,
+}
+
+impl ServiceDefinition {
+    /// Creates the definition of a service running the current binary with the environment
+    /// of the given file.
+    pub fn for_current_exe(env_file: Option<PathBuf>) -> Result<Self> {
+        let executable = std::env::current_exe().wrap_err("failed to locate the sidecar binary")?;
+        Ok(Self { executable, env_file })
+    }
+
+    /// Renders the definition for the given service manager.
+    pub fn render(&self, platform: ServicePlatform) -> Result<String> {
+        match platform {
+            ServicePlatform::Systemd => Ok(self.systemd_unit()),
+            ServicePlatform::Launchd => Ok(self.launchd_plist()),
+            ServicePlatform::Windows => self.winsw_config(),
+        }
+    }
+
+    /// A unit of `Type=notify`, which is only started once the sidecar is ready and is
+    /// restarted if its driver stalls.
+    fn systemd_unit(&self) -> String {
+        let mut unit = String::new();
+        let _ = writeln!(unit, "[Unit]");
+        let _ = writeln!(unit, "Description=Bolt sidecar");
+        let _ = writeln!(unit, "Wants=network-online.target");
+        let _ = writeln!(unit, "After=network-online.target");
+        let _ = writeln!(unit);
+        let _ = writeln!(unit, "[Service]");
+        let _ = writeln!(unit, "Type=notify");
+        let _ = writeln!(unit, "NotifyAccess=main");
+        let _ = writeln!(unit, "ExecStart={}", self.executable.display());
+        if let Some(env_file) = &self.env_file {
+            let _ = writeln!(unit, "EnvironmentFile={}", env_file.display());
+        }
+        let _ = writeln!(unit, "WatchdogSec={SYSTEMD_WATCHDOG_SECS}");
+        let _ = writeln!(unit, "TimeoutStartSec=300");
+        let _ = writeln!(unit, "Restart=on-failure");
+        let _ = writeln!(unit, "RestartSec=5");
+        let _ = writeln!(unit);
+        let _ = writeln!(unit, "[Install]");
+        let _ = writeln!(unit, "WantedBy=multi-user.target");
+        unit
+    }
+
+    /// A job kept alive by launchd. launchd can't load environment files, so the sidecar is
+    /// started through a shell exporting the variables of the file.
+    fn launchd_plist(&self) -> String {
+        let executable = self.executable.display().to_string();
+        let arguments = match &self.env_file {
+            Some(env_file) => vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                format!("set -a; . '{}'; set +a; exec '{executable}'", env_file.display()),
+            ],
+            None => vec![executable],
+        };
+
+        let mut plist = String::new();
+        let _ = writeln!(plist, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            plist,
+            r#"<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">"#
+        );
+        let _ = writeln!(plist, r#"<plist version="1.0">"#);
+        let _ = writeln!(plist, "<dict>");
+        let _ = writeln!(plist, "  <key>Label</key>");
+        let _ = writeln!(plist, "  <string>{LAUNCHD_LABEL}</string>");
+        let _ = writeln!(plist, "  <key>ProgramArguments</key>");
+        let _ = writeln!(plist, "  <array>");
+        for argument in arguments {
+            let _ = writeln!(plist, "    <string>{}</string>", escape_xml(&argument));
+        }
+        let _ = writeln!(plist, "  </array>");
+        let _ = writeln!(plist, "  <key>RunAtLoad</key>");
+        let _ = writeln!(plist, "  <true/>");
+        let _ = writeln!(plist, "  <key>KeepAlive</key>");
+        let _ = writeln!(plist, "  <dict>");
+        let _ = writeln!(plist, "    <key>SuccessfulExit</key>");
+        let _ = writeln!(plist, "    <false/>");
+        let _ = writeln!(plist, "  </dict>");
+        let _ = writeln!(plist, "  <key>StandardOutPath</key>");
+        let _ = writeln!(plist, "  <string>/usr/local/var/log/{SERVICE_NAME}.log</string>");
+        let _ = writeln!(plist, "  <key>StandardErrorPath</key>");
+        let _ = writeln!(plist, "  <string>/usr/local/var/log/{SERVICE_NAME}.log</string>");
+        let _ = writeln!(plist, "</dict>");
+        let _ = writeln!(plist, "</plist>");
+        plist
+    }
+
+    /// A configuration of the WinSW wrapper, which runs the sidecar as a Windows service.
+    /// WinSW can't load environment files, so their variables are inlined.
+    fn winsw_config(&self) -> Result<String> {
+        let env = match &self.env_file {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+                parse_env_file(&content)
+            }
+            None => Vec::new(),
+        };
+
+        let mut config = String::new();
+        let _ = writeln!(config, "<service>");
+        let _ = writeln!(config, "  <id>{SERVICE_NAME}</id>");
+        let _ = writeln!(config, "  <name>Bolt sidecar</name>");
+        let _ = writeln!(config, "  <description>Bolt sidecar</description>");
+        let _ = writeln!(
+            config,
+            "  <executable>{}</executable>",
+            escape_xml(&self.executable.display().to_string())
+        );
+        for (key, value) in env {
+            let _ = writeln!(
+                config,
+                r#"  <env name="{}" value="{}"/>"#,
+                escape_xml(&key),
+                escape_xml(&value)
+            );
+        }
+        let _ = writeln!(config, "  <startmode>Automatic</startmode>");
+        let _ = writeln!(config, r#"  <onfailure action="restart" delay="5 sec"/>"#);
+        let _ = writeln!(config, r#"  <log mode="roll"/>"#);
+        let _ = writeln!(config, "</service>");
+        Ok(config)
+    }
+}
+
+/// Parses the `KEY=VALUE` lines of an environment file, skipping comments and blank lines
+/// and unquoting the values.
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_definitions() {
+        let definition = ServiceDefinition {
+            executable: PathBuf::from("/usr/local/bin/bolt-sidecar"),
+            env_file: Some(PathBuf::from("/etc/bolt/sidecar.env")),
+        };
+
+        let unit = definition.render(ServicePlatform::Systemd).unwrap();
+        assert!(unit.contains("Type=notify"));
+        assert!(unit.contains("ExecStart=/usr/local/bin/bolt-sidecar"));
+        assert!(unit.contains("EnvironmentFile=/etc/bolt/sidecar.env"));
+
+        let plist = definition.render(ServicePlatform::Launchd).unwrap();
+        assert!(plist.contains(
+            "<string>set -a; . &apos;/etc/bolt/sidecar.env&apos;; set +a; exec &apos;/usr/local/bin/bolt-sidecar&apos;</string>"
+        ));
+
+        // The environment file must exist to be inlined in the WinSW configuration
+        assert!(definition.render(ServicePlatform::Windows).is_err());
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let env = parse_env_file(
+            "# Sidecar\nBOLT_SIDECAR_PORT=8017\nexport BOLT_SIDECAR_CHAIN=\"holesky\"\n\n",
+        );
+        assert_eq!(
+            env,
+            vec![
+                ("BOLT_SIDECAR_PORT".to_string(), "8017".to_string()),
+                ("BOLT_SIDECAR_CHAIN".to_string(), "holesky".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::Slot;
+
+/// The readiness of the sidecar, as served to orchestrators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readiness {
+    /// Whether the sidecar can serve commitments for the upcoming slots.
+    pub ready: bool,
+    /// The last head slot processed by the driver, if any.
+    pub head_slot: Option<Slot>,
+}
+
+#[derive(Debug)]
+struct HealthInner {
+    ready: AtomicBool,
+    head_slot: AtomicU64,
+    heartbeat: Mutex<Instant>,
+}
+
+/// The health of the sidecar driver, shared with the commitments API and the service
+/// manager integration.
+///
+/// The sidecar is ready once the driver processed its first head, at which point its
+/// consensus and execution states are synced and it can commit to the next slot. It is
+/// alive as long as the event loop of the driver keeps beating.
+#[derive(Debug, Clone)]
+pub struct SidecarHealth {
+    inner: Arc<HealthInner>,
+}
+
+impl Default for SidecarHealth {
+    fn default() -> Self {
+        let inner = HealthInner {
+            ready: AtomicBool::new(false),
+            head_slot: AtomicU64::new(0),
+            heartbeat: Mutex::new(Instant::now()),
+        };
+
+        Self { inner: Arc::new(inner) }
+    }
+}
+
+impl SidecarHealth {
+    /// Records a head processed by the driver, making the sidecar ready.
+    pub fn record_head(&self, slot: Slot) {
+        self.inner.head_slot.store(slot, Ordering::Relaxed);
+        self.inner.ready.store(true, Ordering::Release);
+        self.heartbeat();
+    }
+
+    /// Records a turn of the event loop of the driver.
+    pub fn heartbeat(&self) {
+        *self.inner.heartbeat.lock() = Instant::now();
+    }
+
+    /// Returns true if the sidecar can serve commitments.
+    pub fn is_ready(&self) -> bool {
+        self.inner.ready.load(Ordering::Acquire)
+    }
+
+    /// Returns true if the event loop of the driver beat within the given duration.
+    pub fn is_alive(&self, max_stall: Duration) -> bool {
+        self.inner.heartbeat.lock().elapsed() <= max_stall
+    }
+
+    /// Returns the readiness of the sidecar.
+    pub fn readiness(&self) -> Readiness {
+        let ready = self.is_ready();
+        let head_slot = ready.then(|| self.inner.head_slot.load(Ordering::Relaxed));
+        Readiness { ready, head_slot }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_health() {
+        let health = SidecarHealth::default();
+        assert_eq!(health.readiness(), Readiness { ready: false, head_slot: None });
+        assert!(health.is_alive(Duration::from_secs(1)));
+
+        health.clone().record_head(12);
+        assert_eq!(health.readiness(), Readiness { ready: true, head_slot: Some(12) });
+    }
+}
@@ -0,0 +1,15 @@
+//! Integration of the sidecar with the service managers of the operating systems.
+
+/// The health of the sidecar driver, tracking its readiness and liveness.
+pub mod health;
+pub use health::{Readiness, SidecarHealth};
+
+/// The definitions of the services running the sidecar, per service manager.
+pub mod definitions;
+pub use definitions::{ServiceDefinition, ServicePlatform};
+
+/// The `sd_notify` protocol of systemd, for readiness and watchdog notifications.
+#[cfg(unix)]
+pub mod systemd;
+#[cfg(unix)]
+pub use systemd::SystemdNotifier;
@@ -0,0 +1,130 @@
+use std::{env, io, os::unix::net::UnixDatagram, path::PathBuf, time::Duration};
+
+use tracing::{debug, info, warn};
+
+use super::SidecarHealth;
+
+/// The environment variable with the path of the notification socket of systemd.
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+
+/// The environment variable with the watchdog interval of systemd, in microseconds.
+const WATCHDOG_USEC_ENV: &str = "WATCHDOG_USEC";
+
+/// The environment variable with the PID the watchdog applies to, if restricted.
+const WATCHDOG_PID_ENV: &str = "WATCHDOG_PID";
+
+/// The interval at which readiness is checked until the sidecar is ready.
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A client of the `sd_notify` protocol of systemd, for units with `Type=notify`.
+///
+/// Notifications are datagrams of newline-separated `KEY=VALUE` assignments sent to the
+/// socket in the [NOTIFY_SOCKET_ENV] environment variable, which is set by systemd.
+#[derive(Debug)]
+pub struct SystemdNotifier {
+    socket: UnixDatagram,
+    path: PathBuf,
+    watchdog: Option<Duration>,
+}
+
+impl SystemdNotifier {
+    /// Creates a notifier from the environment set by systemd, or returns `None` if the
+    /// sidecar is not run by systemd with notifications enabled.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        let Some(path) = env::var_os(NOTIFY_SOCKET_ENV).map(PathBuf::from) else {
+            return Ok(None);
+        };
+
+        let watchdog = env::var(WATCHDOG_USEC_ENV)
+            .ok()
+            .and_then(|usec| usec.parse().ok())
+            .filter(|_| {
+                env::var(WATCHDOG_PID_ENV).map_or(true, |pid| pid.parse() == Ok(std::process::id()))
+            })
+            .map(Duration::from_micros);
+
+        Ok(Some(Self { socket: UnixDatagram::unbound()?, path, watchdog }))
+    }
+
+    /// Returns the interval within which systemd expects watchdog notifications, if enabled.
+    pub fn watchdog(&self) -> Option<Duration> {
+        self.watchdog
+    }
+
+    /// Sends the given state assignments to systemd.
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        let path = self.path.as_os_str().as_encoded_bytes();
+        if let Some(name) = path.strip_prefix(b"@") {
+            // Abstract sockets are only supported on Linux
+            #[cfg(target_os = "linux")]
+            {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                return self.socket.send_to_addr(state.as_bytes(), &addr).map(|_| ());
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                let _ = name;
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "abstract socket"));
+            }
+        }
+
+        self.socket.send_to(state.as_bytes(), &self.path).map(|_| ())
+    }
+
+    /// Signals readiness once the sidecar can serve its first slot, then pings the watchdog
+    /// of systemd as long as the driver is alive, so that systemd restarts a stalled sidecar.
+    pub async fn run(self, health: SidecarHealth) {
+        while !health.is_ready() {
+            tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+        }
+
+        let status = match health.readiness().head_slot {
+            Some(slot) => format!("READY=1\nSTATUS=Serving commitments from slot {slot}"),
+            None => "READY=1".to_string(),
+        };
+        match self.notify(&status) {
+            Ok(()) => info!("Notified systemd of the readiness of the sidecar"),
+            Err(err) => warn!(?err, "Failed to notify systemd of the readiness of the sidecar"),
+        }
+
+        let Some(watchdog) = self.watchdog else { return };
+
+        // Pinging at half the interval leaves room for a late ping
+        let mut interval = tokio::time::interval(watchdog / 2);
+        loop {
+            interval.tick().await;
+            if !health.is_alive(watchdog) {
+                warn!(?watchdog, "The driver is stalled, skipping the watchdog notification");
+                continue;
+            }
+
+            if let Err(err) = self.notify("WATCHDOG=1") {
+                debug!(?err, "Failed to ping the watchdog of systemd");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_notify() {
+        let dir = env::temp_dir().join(format!("bolt-notify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notify.sock");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        let notifier =
+            SystemdNotifier { socket: UnixDatagram::unbound().unwrap(), path, watchdog: None };
+        notifier.notify("READY=1").unwrap();
+
+        let mut buf = [0; 64];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use alloy::signers::local::PrivateKeySigner;
+use reqwest::Url;
+use tokio::{
+    sync::watch,
+    task::{JoinError, JoinHandle},
+};
+use tracing::info;
+
+use crate::{
+    config::{
+        DelegationTargetingOpts, LatencyBudgetOpts, LimitsOpts, LoadSheddingOpts, PricingOpts,
+        RateLimitOpts, WhitelistOpts,
+    },
+    crypto::ecdsa::SignerECDSA,
+    state::{AddressScreener, CommitmentValidator, Screening, StateClient, ValidationPipeline},
+    Opts, SidecarDriver, SignerBLS,
+};
+
+/// Builder to configure and start the sidecar from another Rust program, without
+/// going through the command-line entrypoint.
+///
+/// The builder starts from a complete set of [Opts] and allows to override the
+/// relays, signers, validation policies and listeners before starting the driver.
+///
+/// ```no_run
+/// # async fn run(opts: bolt_sidecar::Opts) -> eyre::Result<()> {
+/// use bolt_sidecar::SidecarBuilder;
+///
+/// let handle = SidecarBuilder::new(opts).with_rpc_port(8017).spawn().await?;
+///
+/// // ... later on
+/// handle.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SidecarBuilder<ECDSA = PrivateKeySigner> {
+    opts: Opts,
+    constraint_signer: Option<SignerBLS>,
+    commitment_signer: ECDSA,
+    validators: Vec<Arc<dyn CommitmentValidator>>,
+    validation_pipeline: Option<ValidationPipeline>,
+    screener: Option<Arc<dyn AddressScreener>>,
+}
+
+impl SidecarBuilder<PrivateKeySigner> {
+    /// Create a new builder from the given options. Commitments are signed with the
+    /// commitment private key in the options unless another signer is provided.
+    pub fn new(opts: Opts) -> Self {
+        let commitment_signer =
+            PrivateKeySigner::from_signing_key(opts.commitment_private_key.0.clone());
+
+        Self {
+            opts,
+            constraint_signer: None,
+            commitment_signer,
+            validators: Vec::new(),
+            validation_pipeline: None,
+            screener: None,
+        }
+    }
+}
+
+impl<ECDSA: SignerECDSA + Sync + 'static> SidecarBuilder<ECDSA> {
+    /// Set the URL of the relay (or Constraints API server) to send constraints to.
+    pub fn with_constraints_api_url(mut self, url: Url) -> Self {
+        self.opts.constraints_api_url = url;
+        self
+    }
+
+    /// Set the operating limits used to validate incoming commitment requests.
+    pub fn with_limits(mut self, limits: LimitsOpts) -> Self {
+        self.opts.limits = limits;
+        self
+    }
+
+    /// Register a custom validator that incoming commitment requests must pass.
+    /// It runs after the built-in checks and the ones enabled by the options, but before
+    /// any account state is fetched.
+    pub fn with_validator<V: CommitmentValidator + 'static>(mut self, validator: V) -> Self {
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Replace the whole pipeline of validators that commitment requests must pass.
+    ///
+    /// This drops the validators enabled by the options, such as the transaction policies,
+    /// the mempool conflicts and the payment vouchers, which must then be part of the given
+    /// pipeline to be enforced. Validators registered with [Self::with_validator] are still
+    /// added to it.
+    pub fn with_validation_pipeline(mut self, pipeline: ValidationPipeline) -> Self {
+        self.validation_pipeline = Some(pipeline);
+        self
+    }
+
+    /// Set the screener that the addresses of commitment requests are screened with for
+    /// compliance, instead of the list or service of the screening options. The screening
+    /// options still configure its cache, timeout and fail mode.
+    pub fn with_screener<S: AddressScreener + 'static>(mut self, screener: S) -> Self {
+        self.screener = Some(Arc::new(screener));
+        self
+    }
+
+    /// Set the latency budgets for the stages of the proposal-critical path.
+    pub fn with_latency_budgets(mut self, latency_budgets: LatencyBudgetOpts) -> Self {
+        self.opts.latency_budgets = latency_budgets;
+        self
+    }
+
+    /// Set the pricing of inclusion commitments by the committed gas of their slot.
+    pub fn with_pricing(mut self, pricing: PricingOpts) -> Self {
+        self.opts.pricing = pricing;
+        self
+    }
+
+    /// Set the policy for shedding low-value work under load ahead of a proposal.
+    pub fn with_load_shedding(mut self, load_shedding: LoadSheddingOpts) -> Self {
+        self.opts.load_shedding = load_shedding;
+        self
+    }
+
+    /// Set the rate limits and quotas of the senders of commitment requests.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimitOpts) -> Self {
+        self.opts.rate_limits = rate_limits;
+        self
+    }
+
+    /// Set the whitelist of the signers allowed to request commitments.
+    pub fn with_whitelist(mut self, whitelist: WhitelistOpts) -> Self {
+        self.opts.whitelist = whitelist;
+        self
+    }
+
+    /// Set the policy deciding which delegatee receives the authority over each proposal slot.
+    pub fn with_delegation_targeting(mut self, targeting: DelegationTargetingOpts) -> Self {
+        self.opts.delegation_targeting = targeting;
+        self
+    }
+
+    /// Set the port of the commitments JSON-RPC listener.
+    pub fn with_rpc_port(mut self, port: u16) -> Self {
+        self.opts.port = port;
+        self
+    }
+
+    /// Set the port of the Builder-API proxy listener, used by the beacon client.
+    pub fn with_constraints_proxy_port(mut self, port: u16) -> Self {
+        self.opts.constraints_proxy_port = port;
+        self
+    }
+
+    /// Set the signer used to sign constraints. If not provided, the signer is
+    /// derived from the constraint signing options.
+    pub fn with_constraint_signer(mut self, signer: SignerBLS) -> Self {
+        self.constraint_signer = Some(signer);
+        self
+    }
+
+    /// Set the signer used to sign commitment responses.
+    pub fn with_commitment_signer<S: SignerECDSA>(self, signer: S) -> SidecarBuilder<S> {
+        SidecarBuilder {
+            opts: self.opts,
+            constraint_signer: self.constraint_signer,
+            commitment_signer: signer,
+            validators: self.validators,
+            validation_pipeline: self.validation_pipeline,
+            screener: self.screener,
+        }
+    }
+
+    /// Build the sidecar driver, starting its API servers without running the main event loop.
+    /// Fails with all the configuration problems found, if any.
+    pub async fn build(self) -> eyre::Result<SidecarDriver<StateClient, ECDSA>> {
+        self.opts.validate()?;
+
+        let constraint_signer = match self.constraint_signer {
+            Some(signer) => signer,
+            None => SignerBLS::from_opts(&self.opts.constraint_signing, self.opts.chain).await?,
+        };
+
+        let state_client = StateClient::new(self.opts.execution_api_url.clone());
+
+        let driver = SidecarDriver::from_components(
+            &self.opts,
+            constraint_signer,
+            self.commitment_signer,
+            state_client,
+        )
+        .await?;
+
+        // The custom validators are added to the pipeline derived from the options
+        let pipeline =
+            self.validation_pipeline.unwrap_or_else(|| driver.validation_pipeline().clone());
+        let pipeline = self
+            .validators
+            .into_iter()
+            .fold(pipeline, |pipeline, v| pipeline.with_shared_validator_before_state(v));
+        let driver = driver.with_validation_pipeline(pipeline);
+        Ok(match self.screener {
+            Some(screener) => driver.with_screening(Screening::new(screener, &self.opts.screening)),
+            None => driver,
+        })
+    }
+
+    /// Build the sidecar driver and run its main event loop in the background,
+    /// returning a [SidecarHandle] to control it.
+    pub async fn spawn(self) -> eyre::Result<SidecarHandle> {
+        let driver = self.build().await?;
+        let shutdown = driver.shutdown_sender();
+        let task = tokio::spawn(driver.run_until_shutdown());
+
+        info!("Started embedded Bolt sidecar");
+        Ok(SidecarHandle { shutdown, task })
+    }
+}
+
+/// A handle to a sidecar running in the background, started with [SidecarBuilder::spawn].
+///
+/// Dropping the handle does not stop the sidecar: use [SidecarHandle::shutdown] instead.
+#[derive(Debug)]
+pub struct SidecarHandle {
+    shutdown: Arc<watch::Sender<bool>>,
+    task: JoinHandle<()>,
+}
+
+impl SidecarHandle {
+    /// Returns true if the sidecar event loop has stopped.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Signal the sidecar to shut down and wait for its event loop and servers to stop.
+    pub async fn shutdown(self) -> Result<(), JoinError> {
+        self.shutdown.send_replace(true);
+        self.task.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::{
+        common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
+        devnet::MockBeacon,
+        signer::local::LocalSigner,
+        state::{validation::WhitelistValidator, ValidationError},
+        test_util::{create_signed_commitment_request, default_test_transaction, launch_anvil},
+        ChainConfig,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builder_keeps_the_validators_of_the_options() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        let constraint_key = BlsSecretKeyWrapper::random();
+        let chain = ChainConfig::kurtosis(2, 1000);
+        let proposer = LocalSigner::new(constraint_key.0.clone(), chain).pubkey();
+        let beacon = MockBeacon::start(0, 2, proposer, 0).await?;
+
+        let opts = Opts::try_parse_from([
+            "bolt-sidecar".to_string(),
+            "--port=0".to_string(),
+            "--constraints-proxy-port=0".to_string(),
+            format!("--execution-api-url={}", anvil.endpoint_url()),
+            format!("--beacon-api-url={}", beacon.url()),
+            "--validator-indexes=0".to_string(),
+            format!("--engine-jwt-hex={}", JwtSecretConfig::default().0),
+            format!("--fee-recipient={}", alloy::primitives::Address::ZERO),
+            format!("--builder-private-key={}", BlsSecretKeyWrapper::random()),
+            format!("--commitment-private-key={}", EcdsaSecretKeyWrapper::random()),
+            format!("--constraint-private-key={constraint_key}"),
+            "--chain=kurtosis".to_string(),
+            "--slot-time=2".to_string(),
+            "--commitment-deadline=1000".to_string(),
+            "--voucher-price-per-gas=1".to_string(),
+            "--disallow-contract-creation".to_string(),
+            "--mempool-scan-interval-ms=1000".to_string(),
+        ])?;
+
+        let mut driver = SidecarBuilder::new(opts)
+            .with_validator(WhitelistValidator::new([*sender]))
+            .build()
+            .await?;
+
+        // The custom validator is added to the ones enabled by the options
+        let names = driver.validation_pipeline().names();
+        assert!(names.contains(&"voucher"));
+        assert!(names.contains(&"tx_policy"));
+        assert!(names.contains(&"mempool"));
+        assert!(names.contains(&"whitelist"));
+
+        // Requests of whitelisted signers must still be paid
+        let tx = default_test_transaction(*sender, None);
+        let mut request = create_signed_commitment_request(&[tx], sender_pk, 10).await?;
+        assert!(matches!(
+            driver.execution_mut().validate_request(&mut request).await,
+            Err(ValidationError::Voucher(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@ use std::{
 use alloy::rpc::types::beacon::constants::BLS_PUBLIC_KEY_BYTES_LEN;
 
 use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use lighthouse_account_utils::ZeroizeString;
 use lighthouse_bls::Keypair;
 use lighthouse_eth2_keystore::Keystore;
 use ssz::Encode;
@@ -65,6 +66,21 @@ impl KeystoreSigner {
         Ok(Self { keypairs, chain })
     }
 
+    /// Creates a new `KeystoreSigner` from the keystore files in the `keys_path` directory,
+    /// decrypted with the password in the file at `password_path`, so that it doesn't have to
+    /// be passed through the environment.
+    pub fn from_password_file(
+        keys_path: &PathBuf,
+        password_path: &Path,
+        chain: ChainConfig,
+    ) -> SignerResult<Self> {
+        let password = read_password(password_path)?;
+        Self::from_password(keys_path, password.as_ref(), chain)
+    }
+
+    /// Creates a new `KeystoreSigner` from the keystore files in the `keys_path` directory,
+    /// each decrypted with the password in the file of `secrets_path` named after its public
+    /// key.
     pub fn from_secrets_directory(
         keys_path: &PathBuf,
         secrets_path: &Path,
@@ -83,11 +99,10 @@ impl KeystoreSigner {
             let mut secret_path = secrets_path.to_path_buf();
             secret_path.push(pubkey);
 
-            let password = fs::read_to_string(secret_path)
-                .map_err(|e| KeystoreError::ReadFromSecretFile(format!("{e:?}")))?;
+            let password = read_password(&secret_path)?;
 
             let keypair = keystore
-                .decrypt_keypair(password.as_bytes())
+                .decrypt_keypair(password.as_ref())
                 .map_err(|e| KeystoreError::KeypairDecryption(path.clone(), format!("{e:?}")))?;
             keypairs.push(keypair);
         }
@@ -176,6 +191,14 @@ fn find_json_keystores(keys_path: &PathBuf) -> SignerResult<Vec<PathBuf>> {
     Ok(keystores_paths)
 }
 
+/// Reads a keystore password from a file, without the trailing newlines that editors and
+/// `echo` append to it.
+fn read_password(path: &Path) -> SignerResult<ZeroizeString> {
+    let password = fs::read_to_string(path)
+        .map_err(|e| KeystoreError::ReadFromSecretFile(format!("{}: {e:?}", path.display())))?;
+    Ok(ZeroizeString::from(password.trim_end_matches(['\r', '\n']).to_string()))
+}
+
 fn read_dir(path: &PathBuf) -> SignerResult<ReadDir> {
     Ok(fs::read_dir(path).map_err(KeystoreError::ReadFromDirectory)?)
 }
@@ -344,6 +367,14 @@ mod tests {
             .expect("to create keystore signer from secrets dir");
 
             assert_eq!(keystore_signer_from_directory.keypairs.len(), 3);
+
+            // The trailing newline of a password file is not part of the password
+            let password_path = std::env::temp_dir().join("bolt-keystore-password");
+            std::fs::write(&password_path, format!("{password}\n")).expect("to write password");
+            let keystore_signer_from_file =
+                KeystoreSigner::from_password_file(&keys_path, &password_path, chain_config)
+                    .expect("to create keystore signer from password file");
+            assert_eq!(keystore_signer_from_file.keypairs.len(), 3);
             assert_eq!(
                 keystore_signer_from_directory
                     .keypairs
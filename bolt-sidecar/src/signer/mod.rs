@@ -2,6 +2,12 @@ use std::collections::HashSet;
 
 use ethereum_consensus::crypto::bls::PublicKey as BlsPublicKey;
 
+use crate::{
+    config::ConstraintSigningOpts,
+    crypto::bls::{cl_public_key_to_arr, BLSSig},
+    ChainConfig,
+};
+
 pub mod commit_boost;
 use commit_boost::CommitBoostSigner;
 
@@ -11,6 +17,9 @@ use keystore::KeystoreSigner;
 pub mod local;
 use local::LocalSigner;
 
+pub mod web3signer;
+use web3signer::Web3Signer;
+
 /// Error in the signer.
 #[derive(Debug, thiserror::Error)]
 pub enum SignerError {
@@ -20,6 +29,10 @@ pub enum SignerError {
     CommitBoost(#[from] commit_boost::CommitBoostError),
     #[error("keystore signer error: {0}")]
     Keystore(#[from] keystore::KeystoreError),
+    #[error("web3signer error: {0}")]
+    Web3Signer(#[from] web3signer::Web3SignerError),
+    #[error("missing signing option: {0}")]
+    MissingOption(&'static str),
 }
 
 pub type SignerResult<T> = std::result::Result<T, SignerError>;
@@ -33,15 +46,104 @@ pub enum SignerBLS {
     CommitBoost(CommitBoostSigner),
     /// Signer consisting of multiple keypairs loaded from ERC-2335 keystores files.
     Keystore(KeystoreSigner),
+    /// Remote signer delegating to a Web3Signer instance.
+    Web3Signer(Web3Signer),
 }
 
 impl SignerBLS {
+    /// Derives the constraint signer from the signing options, in order of precedence:
+    /// local key, Commit-Boost, Web3Signer, keystore.
+    pub async fn from_opts(opts: &ConstraintSigningOpts, chain: ChainConfig) -> SignerResult<Self> {
+        if opts.constraint_private_key.is_some() {
+            LocalSigner::from_opts(opts, chain).map(Self::Local)
+        } else if opts.commit_boost_signer_url.is_some() {
+            CommitBoostSigner::from_opts(opts).map(Self::CommitBoost)
+        } else if opts.web3signer_url.is_some() {
+            Web3Signer::from_opts(opts, chain).await.map(Self::Web3Signer)
+        } else {
+            KeystoreSigner::from_opts(opts, chain).map(Self::Keystore)
+        }
+    }
+
     /// Returns all the public keys available for signing.
     pub fn available_pubkeys(&self) -> HashSet<BlsPublicKey> {
         match self {
             SignerBLS::Local(signer) => [signer.pubkey()].into(),
             SignerBLS::CommitBoost(signer) => [signer.pubkey()].into(),
             SignerBLS::Keystore(signer) => signer.pubkeys(),
+            SignerBLS::Web3Signer(signer) => signer.pubkeys(),
+        }
+    }
+
+    /// Signs an object root with the Commit-Boost domain, using the given public key.
+    pub async fn sign_commit_boost_root(
+        &self,
+        root: [u8; 32],
+        pubkey: &BlsPublicKey,
+    ) -> SignerResult<BLSSig> {
+        match self {
+            SignerBLS::Local(signer) => signer.sign_commit_boost_root(root),
+            SignerBLS::CommitBoost(signer) => signer.sign_commit_boost_root(root).await,
+            SignerBLS::Keystore(signer) => {
+                signer.sign_commit_boost_root(root, cl_public_key_to_arr(pubkey.clone()))
+            }
+            SignerBLS::Web3Signer(signer) => signer.sign_commit_boost_root(root, pubkey).await,
+        }
+    }
+}
+
+impl LocalSigner {
+    /// Creates the signer of the constraint private key of the signing options.
+    pub fn from_opts(opts: &ConstraintSigningOpts, chain: ChainConfig) -> SignerResult<Self> {
+        let key = opts
+            .constraint_private_key
+            .as_ref()
+            .ok_or(SignerError::MissingOption("constraint private key"))?;
+        Ok(Self::new(key.0.clone(), chain))
+    }
+}
+
+impl CommitBoostSigner {
+    /// Connects to the Commit-Boost signer of the signing options.
+    pub fn from_opts(opts: &ConstraintSigningOpts) -> SignerResult<Self> {
+        let url = opts
+            .commit_boost_signer_url
+            .clone()
+            .ok_or(SignerError::MissingOption("Commit-Boost signer URL"))?;
+        let jwt = opts
+            .commit_boost_jwt_hex
+            .as_ref()
+            .ok_or(SignerError::MissingOption("Commit-Boost JWT"))?;
+        Self::new(url, jwt)
+    }
+}
+
+impl Web3Signer {
+    /// Connects to the Web3Signer of the signing options.
+    pub async fn from_opts(opts: &ConstraintSigningOpts, chain: ChainConfig) -> SignerResult<Self> {
+        let url =
+            opts.web3signer_url.clone().ok_or(SignerError::MissingOption("Web3Signer URL"))?;
+        Self::connect(url, opts.web3signer_tls_credentials().as_ref(), chain).await
+    }
+}
+
+impl KeystoreSigner {
+    /// Loads the keystores of the signing options, decrypted with the password, the password
+    /// file or the secrets directory, in this order of precedence.
+    pub fn from_opts(opts: &ConstraintSigningOpts, chain: ChainConfig) -> SignerResult<Self> {
+        let keystore_path =
+            opts.keystore_path.as_ref().ok_or(SignerError::MissingOption("keystore path"))?;
+
+        if let Some(password) = opts.keystore_password.as_ref() {
+            Self::from_password(keystore_path, password.as_ref(), chain)
+        } else if let Some(path) = opts.keystore_password_path.as_ref() {
+            Self::from_password_file(keystore_path, path, chain)
+        } else {
+            let secrets_path = opts
+                .keystore_secrets_path
+                .as_ref()
+                .ok_or(SignerError::MissingOption("keystore secrets path"))?;
+            Self::from_secrets_directory(keystore_path, secrets_path, chain)
         }
     }
 }
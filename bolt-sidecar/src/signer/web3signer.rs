@@ -0,0 +1,256 @@
+//! A remote signer delegating BLS signatures to a Web3Signer instance.
+
+use std::{collections::HashSet, fmt::Debug, fs, path::PathBuf};
+
+use alloy::hex;
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use reqwest::{Certificate, Client, Identity, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info};
+
+use crate::{
+    builder::signature::compute_signing_root,
+    crypto::bls::{verify_bls_signature, BLSSig},
+    ChainConfig,
+};
+
+use super::SignerResult;
+
+/// The JSON-RPC method listing the BLS public keys available on the signer.
+const LIST_ACCOUNTS_METHOD: &str = "commit_boost_listAccounts";
+
+/// The JSON-RPC method requesting the signature of a signing root by one of the keys.
+const REQUEST_SIGNATURE_METHOD: &str = "commit_boost_requestSignature";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Web3SignerError {
+    #[error("failed to read TLS credentials from {0}: {1}")]
+    ReadCredentials(PathBuf, std::io::Error),
+    #[error("invalid TLS credentials: {0}")]
+    InvalidCredentials(reqwest::Error),
+    #[error("failed to reach the Web3Signer: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Web3Signer error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("invalid Web3Signer response: {0}")]
+    InvalidResponse(String),
+    #[error("public key {0} is not available on the Web3Signer")]
+    UnknownPublicKey(String),
+    #[error("invalid signature of public key {0} returned by the Web3Signer")]
+    InvalidSignature(String),
+}
+
+/// The TLS credentials authenticating the sidecar to the Web3Signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Web3SignerTlsCredentials {
+    /// The PEM-encoded client certificate.
+    pub client_cert_path: PathBuf,
+    /// The PEM-encoded PKCS#8 private key of the client certificate.
+    pub client_key_path: PathBuf,
+    /// The PEM-encoded CA certificate of the Web3Signer, if it is not trusted by the system.
+    pub ca_cert_path: Option<PathBuf>,
+}
+
+/// A BLS signer delegating signatures to a remote Web3Signer over its JSON-RPC API, so that
+/// the validator keys are never exposed to the sidecar.
+///
+/// The signing roots are computed by the sidecar with the Commit Boost domain, and each
+/// returned signature is verified before use. The public keys are listed once on connection.
+#[derive(Clone)]
+pub struct Web3Signer {
+    client: Client,
+    url: Url,
+    pubkeys: HashSet<BlsPublicKey>,
+    chain: ChainConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcRequest<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl Web3Signer {
+    /// Connects to the Web3Signer at the given URL, authenticating with the client certificate
+    /// if any, and lists its public keys.
+    pub async fn connect(
+        url: Url,
+        credentials: Option<&Web3SignerTlsCredentials>,
+        chain: ChainConfig,
+    ) -> SignerResult<Self> {
+        let mut builder = Client::builder();
+        if let Some(credentials) = credentials {
+            let cert = read(&credentials.client_cert_path)?;
+            let key = read(&credentials.client_key_path)?;
+            let identity = Identity::from_pkcs8_pem(&cert, &key)
+                .map_err(Web3SignerError::InvalidCredentials)?;
+            builder = builder.identity(identity);
+
+            if let Some(path) = credentials.ca_cert_path.as_ref() {
+                let ca = Certificate::from_pem(&read(path)?)
+                    .map_err(Web3SignerError::InvalidCredentials)?;
+                builder = builder.add_root_certificate(ca);
+            }
+        }
+        let client = builder.build().map_err(Web3SignerError::InvalidCredentials)?;
+
+        let mut signer = Self { client, url, pubkeys: HashSet::new(), chain };
+        let accounts: Vec<String> = signer.call(LIST_ACCOUNTS_METHOD, Vec::new()).await?;
+        signer.pubkeys = accounts
+            .iter()
+            .map(|account| {
+                hex::decode(account)
+                    .ok()
+                    .and_then(|bytes| BlsPublicKey::try_from(bytes.as_slice()).ok())
+                    .ok_or_else(|| {
+                        Web3SignerError::InvalidResponse(format!("invalid public key {account}"))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        info!(url = %signer.url, pubkeys = signer.pubkeys.len(), "Connected to the Web3Signer");
+        Ok(signer)
+    }
+
+    /// Returns the public keys available on the Web3Signer.
+    pub fn pubkeys(&self) -> HashSet<BlsPublicKey> {
+        self.pubkeys.clone()
+    }
+
+    /// Requests the signature of an object root with the Commit Boost domain by the given key.
+    pub async fn sign_commit_boost_root(
+        &self,
+        root: [u8; 32],
+        pubkey: &BlsPublicKey,
+    ) -> SignerResult<BLSSig> {
+        if !self.pubkeys.contains(pubkey) {
+            let pubkey = hex::encode_prefixed(pubkey.as_ref());
+            return Err(Web3SignerError::UnknownPublicKey(pubkey).into());
+        }
+
+        let signing_root = compute_signing_root(root, self.chain.commit_boost_domain());
+        let account = hex::encode_prefixed(pubkey.as_ref());
+        let params =
+            vec![Value::from(account.clone()), Value::from(hex::encode_prefixed(signing_root))];
+        let signature: String = self.call(REQUEST_SIGNATURE_METHOD, params).await?;
+
+        let signature = hex::decode(&signature)
+            .ok()
+            .filter(|sig| verify_bls_signature(pubkey, signing_root, sig))
+            .ok_or(Web3SignerError::InvalidSignature(account))?;
+
+        debug!(?pubkey, "Received signature from the Web3Signer");
+        Ok(BLSSig::from_slice(&signature))
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<T, Web3SignerError> {
+        let request = RpcRequest { jsonrpc: "2.0", id: 1, method, params };
+        let response = self
+            .client
+            .post(self.url.join("/rpc/v1").expect("valid path"))
+            .json(&request)
+            .send()
+            .await?
+            .json::<RpcResponse<T>>()
+            .await?;
+
+        match (response.result, response.error) {
+            (_, Some(RpcError { code, message })) => Err(Web3SignerError::Rpc { code, message }),
+            (Some(result), None) => Ok(result),
+            (None, None) => {
+                Err(Web3SignerError::InvalidResponse(format!("no result for {method}")))
+            }
+        }
+    }
+}
+
+impl Debug for Web3Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Web3Signer")
+            .field("url", &self.url.as_str())
+            .field("pubkeys", &self.pubkeys.len())
+            .field("chain", &self.chain.name())
+            .finish()
+    }
+}
+
+fn read(path: &PathBuf) -> Result<Vec<u8>, Web3SignerError> {
+    fs::read(path).map_err(|err| Web3SignerError::ReadCredentials(path.clone(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+    use serde_json::json;
+
+    use crate::{
+        common::BlsSecretKeyWrapper,
+        crypto::bls::{BlsSecretKey, SignableBLS, BLS_DST_PREFIX},
+        signer::{local::LocalSigner, SignerError},
+        test_util::TestSignableData,
+    };
+
+    use super::*;
+
+    /// Serves the JSON-RPC API of a Web3Signer holding the given key, returning its URL.
+    async fn mock_web3signer(key: BlsSecretKey) -> Url {
+        let rpc = move |Json(request): Json<Value>| async move {
+            let result = match request["method"].as_str() {
+                Some(LIST_ACCOUNTS_METHOD) => {
+                    json!([hex::encode_prefixed(key.sk_to_pk().to_bytes())])
+                }
+                Some(REQUEST_SIGNATURE_METHOD) => {
+                    let root = hex::decode(request["params"][1].as_str().unwrap()).unwrap();
+                    let sig = key.sign(&root, BLS_DST_PREFIX, &[]);
+                    json!(hex::encode_prefixed(sig.to_bytes()))
+                }
+                _ => return Json(json!({ "id": 1, "error": { "code": -32601, "message": "?" } })),
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/rpc/v1", post(rpc));
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        format!("http://{addr}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_web3signer() {
+        let sk = BlsSecretKeyWrapper::random().0;
+        let key = LocalSigner::new(sk.clone(), ChainConfig::mainnet());
+        let url = mock_web3signer(sk).await;
+
+        let signer = Web3Signer::connect(url, None, ChainConfig::mainnet()).await.unwrap();
+        assert_eq!(signer.pubkeys(), [key.pubkey()].into());
+
+        let msg = TestSignableData { data: [1; 32] };
+        let signature = signer.sign_commit_boost_root(msg.digest(), &key.pubkey()).await.unwrap();
+        assert_eq!(signature, key.sign_commit_boost_root(msg.digest()).unwrap());
+
+        let unknown = LocalSigner::random().pubkey();
+        let err = signer.sign_commit_boost_root(msg.digest(), &unknown).await.unwrap_err();
+        assert!(matches!(err, SignerError::Web3Signer(Web3SignerError::UnknownPublicKey(_))));
+    }
+}
@@ -0,0 +1,49 @@
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::{beacon::events::PayloadAttributesData, Withdrawal},
+};
+
+use crate::primitives::Slot;
+
+/// The exact execution attributes of an upcoming proposal slot, known from the
+/// `payload_attributes` events of the beacon node rather than extrapolated from
+/// the latest block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalAttributes {
+    /// The proposal slot.
+    pub slot: Slot,
+    /// The hash of the parent block of the proposal.
+    pub parent_block_hash: B256,
+    /// The basefee of the proposed block.
+    pub basefee: u128,
+    /// The blob basefee of the proposed block.
+    pub blob_basefee: u128,
+    /// The gas limit of the parent block, which the proposed block can only
+    /// deviate from by 1/1024.
+    pub gas_limit: u64,
+    /// The fee recipient suggested by the proposer.
+    pub fee_recipient: Address,
+    /// The withdrawals processed at the end of the proposed block.
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+impl ProposalAttributes {
+    /// Creates the attributes of the proposal announced by the given event, with the fees
+    /// and gas limit derived from its parent block.
+    pub fn new(
+        data: PayloadAttributesData,
+        basefee: u128,
+        blob_basefee: u128,
+        gas_limit: u64,
+    ) -> Self {
+        Self {
+            slot: data.proposal_slot,
+            parent_block_hash: data.parent_block_hash,
+            basefee,
+            blob_basefee,
+            gas_limit,
+            fee_recipient: data.payload_attributes.suggested_fee_recipient,
+            withdrawals: data.payload_attributes.withdrawals.unwrap_or_default(),
+        }
+    }
+}
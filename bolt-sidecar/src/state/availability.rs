@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::primitives::{availability::SignedAvailability, Slot};
+
+use super::SlotScoped;
+
+/// The availability attestations of the upcoming proposal slots of the sidecar.
+///
+/// Clones share the same state, so that the driver publishes the attestations while the
+/// commitments API serves them to gateways.
+#[derive(Debug, Clone, Default)]
+pub struct AvailabilityBoard {
+    attestations: Arc<RwLock<SlotScoped<SignedAvailability>>>,
+}
+
+impl AvailabilityBoard {
+    /// Returns true if the given slot was attested.
+    pub fn is_attested(&self, slot: Slot) -> bool {
+        self.attestations.read().get(slot).is_some()
+    }
+
+    /// Publishes the attestation of a slot, unless the slot passed or is already attested.
+    /// Returns true if the attestation was published.
+    pub fn publish(&self, attestation: SignedAvailability) -> bool {
+        let slot = attestation.availability.slot;
+        let mut attestations = self.attestations.write();
+        if slot <= attestations.head() || attestations.get(slot).is_some() {
+            return false;
+        }
+
+        attestations.insert(slot, attestation);
+        true
+    }
+
+    /// Returns the attestations of the upcoming slots, by ascending slot.
+    pub fn upcoming(&self) -> Vec<SignedAvailability> {
+        self.attestations.read().iter().map(|(_, attestation)| attestation.clone()).collect()
+    }
+
+    /// Drops the attestations of every slot up to and including the given head slot.
+    pub fn advance(&self, head: Slot) {
+        self.attestations.write().advance(head);
+    }
+}
@@ -0,0 +1,200 @@
+use alloy::rpc::types::beacon::events::{ChainReorgEvent, HeadEvent, PayloadAttributesEvent};
+use beacon_api_client::Topic;
+use futures::{
+    future,
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use std::time::Duration;
+use tokio::{sync::broadcast, task::AbortHandle, time::sleep};
+use tracing::{debug, warn};
+
+use crate::{primitives::Slot, BeaconClient};
+
+/// The delay between retries when attempting to reconnect to the beacon client
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The number of events buffered for each subscriber of the event stream
+const EVENTS_CHANNEL_CAPACITY: usize = 64;
+
+/// The merged stream of events of all topics, where `None` marks the end of a topic stream
+type EventStream = BoxStream<'static, Option<Result<BeaconEvent, beacon_api_client::Error>>>;
+
+/// A topic for subscribing to new head events
+#[derive(Debug)]
+pub struct NewHeadsTopic;
+
+impl Topic for NewHeadsTopic {
+    const NAME: &'static str = "head";
+
+    type Data = HeadEvent;
+}
+
+/// A topic for subscribing to payload attributes events
+#[derive(Debug)]
+pub struct PayloadAttributesTopic;
+
+impl Topic for PayloadAttributesTopic {
+    const NAME: &'static str = "payload_attributes";
+
+    type Data = PayloadAttributesEvent;
+}
+
+/// A topic for subscribing to chain reorg events
+#[derive(Debug)]
+pub struct ChainReorgTopic;
+
+impl Topic for ChainReorgTopic {
+    const NAME: &'static str = "chain_reorg";
+
+    type Data = ChainReorgEvent;
+}
+
+/// A typed event of the beacon node event stream.
+#[derive(Debug, Clone)]
+pub enum BeaconEvent {
+    /// A new head of the beacon chain.
+    Head(HeadEvent),
+    /// The payload attributes of the next block to build.
+    PayloadAttributes(PayloadAttributesEvent),
+    /// A reorg of the beacon chain.
+    ChainReorg(ChainReorgEvent),
+    /// The new head is more than one slot after the last one, so heads in between were
+    /// either missed, e.g. while reconnecting, or never proposed.
+    Gap {
+        /// The slot of the last head received before the gap.
+        last_slot: Slot,
+        /// The slot of the head received after the gap.
+        slot: Slot,
+    },
+}
+
+/// Actor subscribed to the event stream of the beacon node, broadcasting its typed
+/// events to all of its subscribers.
+///
+/// Durability: the subscription is re-established whenever the stream of any topic
+/// fails or ends, and heads missed in the meantime are reported as a [BeaconEvent::Gap].
+#[derive(Debug)]
+pub struct BeaconEvents {
+    /// Channel to broadcast the events on
+    events_tx: broadcast::Sender<BeaconEvent>,
+    /// Handle to the background task that listens for events.
+    /// Kept to allow for graceful shutdown.
+    quit: AbortHandle,
+}
+
+impl BeaconEvents {
+    /// Subscribe to the event stream of the given beacon client in the background.
+    pub fn start(beacon_client: BeaconClient) -> Self {
+        let (events_tx, _) = broadcast::channel(EVENTS_CHANNEL_CAPACITY);
+
+        let task = tokio::spawn(run(beacon_client, events_tx.clone()));
+
+        Self { events_tx, quit: task.abort_handle() }
+    }
+
+    /// Subscribe to the events broadcast after the call to this method.
+    pub fn subscribe(&self) -> broadcast::Receiver<BeaconEvent> {
+        self.events_tx.subscribe()
+    }
+
+    /// Stop the subscription and cleanup resources
+    pub fn stop(&self) {
+        self.quit.abort();
+    }
+}
+
+/// Listen for events until aborted, reconnecting whenever the stream fails or ends.
+async fn run(beacon_client: BeaconClient, events_tx: broadcast::Sender<BeaconEvent>) {
+    let mut last_slot = None;
+
+    loop {
+        let mut events = match subscribe(&beacon_client).await {
+            Ok(events) => events,
+            Err(err) => {
+                warn!(?err, "failed to subscribe to beacon events, retrying...");
+                sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Some(Ok(event)) => event,
+                Some(Err(err)) => {
+                    warn!(?err, "error reading beacon event stream, reconnecting...");
+                    break;
+                }
+                None => {
+                    warn!("beacon event stream ended, reconnecting...");
+                    break;
+                }
+            };
+
+            if let BeaconEvent::Head(ref head) = event {
+                if let Some(gap) = detect_gap(&mut last_slot, head.slot) {
+                    warn!(?gap, "gap in the beacon heads");
+                    let _ = events_tx.send(gap);
+                }
+            }
+
+            // Sending only fails when there are no subscribers, which is fine
+            if events_tx.send(event).is_err() {
+                debug!("no subscribers to beacon events");
+            }
+        }
+
+        sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Subscribe to every topic, merging their events in a single stream. The end of the
+/// stream of any topic is reported with a `None` item.
+async fn subscribe(beacon_client: &BeaconClient) -> Result<EventStream, beacon_api_client::Error> {
+    let heads = beacon_client.get_events::<NewHeadsTopic>().await?;
+    let attributes = beacon_client.get_events::<PayloadAttributesTopic>().await?;
+    let reorgs = beacon_client.get_events::<ChainReorgTopic>().await?;
+
+    let ended = || stream::once(future::ready(None));
+    Ok(stream::select_all([
+        heads.map(|e| Some(e.map(BeaconEvent::Head))).chain(ended()).boxed(),
+        attributes.map(|e| Some(e.map(BeaconEvent::PayloadAttributes))).chain(ended()).boxed(),
+        reorgs.map(|e| Some(e.map(BeaconEvent::ChainReorg))).chain(ended()).boxed(),
+    ])
+    .boxed())
+}
+
+/// Track the slot of the last head, returning a [BeaconEvent::Gap] if the new head
+/// is more than one slot after it.
+fn detect_gap(last_slot: &mut Option<Slot>, slot: Slot) -> Option<BeaconEvent> {
+    let gap = match *last_slot {
+        Some(last_slot) if slot > last_slot + 1 => Some(BeaconEvent::Gap { last_slot, slot }),
+        _ => None,
+    };
+
+    // Heads can go back on reorgs, only the gaps after the highest head are reported
+    *last_slot = Some(last_slot.map_or(slot, |last_slot| last_slot.max(slot)));
+    gap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_gap() {
+        let mut last_slot = None;
+
+        assert!(detect_gap(&mut last_slot, 10).is_none());
+        assert!(detect_gap(&mut last_slot, 11).is_none());
+
+        // Reorged heads are not gaps
+        assert!(detect_gap(&mut last_slot, 11).is_none());
+        assert!(detect_gap(&mut last_slot, 9).is_none());
+        assert_eq!(last_slot, Some(11));
+
+        let gap = detect_gap(&mut last_slot, 14);
+        assert!(matches!(gap, Some(BeaconEvent::Gap { last_slot: 11, slot: 14 })));
+        assert!(detect_gap(&mut last_slot, 15).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! History of the commitment keys of the sidecar, saved on disk so that the verifiers of past
+//! commitments can resolve which key was authoritative at their slot after the key is rotated.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+
+/// A commitment key of the sidecar, and the window it signed the commitments in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitmentKeyRecord {
+    /// The address of the key.
+    pub address: Address,
+    /// The compressed SEC1 public key.
+    pub public_key: Bytes,
+    /// The first slot the key signed commitments for.
+    pub from_slot: u64,
+    /// The time the key was first used, in unix seconds.
+    pub activated_at: u64,
+    /// The slot from which the next key signed the commitments, if the key was rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until_slot: Option<u64>,
+    /// The time the key was rotated, in unix seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<u64>,
+}
+
+impl CommitmentKeyRecord {
+    /// Returns true if the key signed the commitments of the given slot.
+    pub fn is_valid_at(&self, slot: u64) -> bool {
+        self.from_slot <= slot && self.until_slot.map_or(true, |until| slot < until)
+    }
+}
+
+/// The commitment keys of the sidecar, as served to the verifiers of its commitments.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitmentKeys {
+    /// The key currently signing the commitments, if known.
+    pub current: Option<CommitmentKeyRecord>,
+    /// The keys it replaced, from the most recent one.
+    pub previous: Vec<CommitmentKeyRecord>,
+}
+
+impl CommitmentKeys {
+    /// Returns the key that signed the commitments of the given slot, if known.
+    pub fn key_at(&self, slot: u64) -> Option<&CommitmentKeyRecord> {
+        self.current.iter().chain(&self.previous).find(|key| key.is_valid_at(slot))
+    }
+}
+
+/// The commitment keys used by the sidecar, in the order they were activated.
+///
+/// If a path is set, the history is saved to it on every rotation, and loaded back by
+/// [CommitmentKeyHistory::open] on startup. Otherwise, only the current key is known.
+#[derive(Debug, Clone, Default)]
+pub struct CommitmentKeyHistory {
+    records: Vec<CommitmentKeyRecord>,
+    path: Option<PathBuf>,
+}
+
+impl CommitmentKeyHistory {
+    /// Opens the history saved at the given path, or an empty one if the file doesn't exist
+    /// yet. The file is created on the first activation.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let records = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self { records, path: Some(path) })
+    }
+
+    /// Returns the path of the file the history is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Records the key the sidecar signs the commitments with from the given slot, at the
+    /// given time in unix seconds. If it differs from the current key, the current key is
+    /// rotated out at that slot. Returns true if the key was rotated.
+    pub fn activate(
+        &mut self,
+        address: Address,
+        public_key: Bytes,
+        slot: u64,
+        now: u64,
+    ) -> io::Result<bool> {
+        let rotated = match self.records.last_mut() {
+            Some(current) if current.address == address => return Ok(false),
+            Some(current) => {
+                current.until_slot = Some(slot);
+                current.rotated_at = Some(now);
+                true
+            }
+            None => false,
+        };
+
+        self.records.push(CommitmentKeyRecord {
+            address,
+            public_key,
+            from_slot: slot,
+            activated_at: now,
+            until_slot: None,
+            rotated_at: None,
+        });
+        self.save()?;
+        Ok(rotated)
+    }
+
+    /// Returns the current and previous keys.
+    pub fn keys(&self) -> CommitmentKeys {
+        let mut previous = self.records.clone();
+        let current = previous.pop();
+        previous.reverse();
+
+        CommitmentKeys { current, previous }
+    }
+
+    /// Saves the history if a path is set. The file is replaced atomically.
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = self.path.as_ref() else { return Ok(()) };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        let file = File::create(&tmp)?;
+        serde_json::to_writer(BufWriter::new(&file), &self.records)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_key_rotation() {
+        let dir = std::env::temp_dir().join(format!("bolt-commitment-keys-{}", std::process::id()));
+        let path = dir.join("commitment_keys.json");
+        let (old, new) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let mut history = CommitmentKeyHistory::open(&path).unwrap();
+        assert!(!history.activate(old, Bytes::from_static(&[2; 33]), 10, 1_000).unwrap());
+
+        // Restarting with the same key doesn't rotate it
+        let mut history = CommitmentKeyHistory::open(&path).unwrap();
+        assert!(!history.activate(old, Bytes::from_static(&[2; 33]), 20, 2_000).unwrap());
+        assert!(history.keys().previous.is_empty());
+
+        let mut history = CommitmentKeyHistory::open(&path).unwrap();
+        assert!(history.activate(new, Bytes::from_static(&[3; 33]), 30, 3_000).unwrap());
+
+        let keys = CommitmentKeyHistory::open(&path).unwrap().keys();
+        assert_eq!(keys.current.as_ref().map(|key| key.address), Some(new));
+        assert_eq!(keys.previous[0].until_slot, Some(30));
+        assert_eq!(keys.previous[0].rotated_at, Some(3_000));
+
+        // The key of each slot is resolved from its validity window
+        assert_eq!(keys.key_at(29).map(|key| key.address), Some(old));
+        assert_eq!(keys.key_at(30).map(|key| key.address), Some(new));
+        assert!(keys.key_at(9).is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+use std::{collections::HashMap, sync::Arc};
+
+use alloy::primitives::TxHash;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{config::chain::DEFAULT_SLOT_TIME_IN_SECONDS, primitives::Slot};
+
+use super::SlotScoped;
+
+/// The number of slots the statuses of the commitments are kept for after their slot, for
+/// users to find out whether they were honored.
+pub const STATUS_RETENTION_SLOTS: u64 = 64;
+
+/// The number of status updates buffered for each subscriber. Slower subscribers miss the
+/// oldest updates.
+pub const STATUS_UPDATES_CAPACITY: usize = 1024;
+
+/// The state of a commitment to include a transaction in a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitmentState {
+    /// The transaction was committed to, and its constraints are not submitted yet.
+    Pending,
+    /// The constraints of the transaction were acknowledged by the relay.
+    Constrained,
+    /// The target slot ended before its head was received, so the inclusion of the
+    /// transaction is not known yet.
+    Expired,
+    /// The slot passed, and the transaction was included on chain.
+    Included,
+    /// The slot passed, and the transaction was not included on chain.
+    Missed,
+    /// No commitment to the transaction in the slot is known, or its status was dropped.
+    Unknown,
+}
+
+impl CommitmentState {
+    /// Returns true if the slot of the commitment passed.
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, Self::Included | Self::Missed)
+    }
+}
+
+/// The status of a commitment, keyed by the hash of its transaction and its target slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitmentStatus {
+    /// The target slot of the commitment.
+    pub slot: Slot,
+    /// The hash of the committed transaction.
+    pub tx_hash: TxHash,
+    /// The state of the commitment.
+    pub status: CommitmentState,
+    /// The end of the target slot, in unix seconds, after which the commitment expires.
+    pub expires_at: u64,
+}
+
+/// A query of the status of the commitment to a transaction in a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentStatusQuery {
+    /// The target slot of the commitment.
+    pub slot: Slot,
+    /// The hash of the committed transaction.
+    pub tx_hash: TxHash,
+}
+
+/// The statuses of the commitments of the recent and upcoming slots.
+///
+/// Clones share the same state, so that the driver records the statuses while the
+/// commitments API serves them to users. Every change of status is broadcast to the
+/// [subscribers](CommitmentStatuses::subscribe).
+#[derive(Debug, Clone)]
+pub struct CommitmentStatuses {
+    slots: Arc<RwLock<SlotScoped<HashMap<TxHash, CommitmentState>>>>,
+    updates: broadcast::Sender<CommitmentStatus>,
+    genesis_time: u64,
+    slot_time: u64,
+}
+
+impl Default for CommitmentStatuses {
+    fn default() -> Self {
+        Self::new(0, DEFAULT_SLOT_TIME_IN_SECONDS)
+    }
+}
+
+impl CommitmentStatuses {
+    /// Create the statuses of a chain with the given genesis time and slot time, in seconds,
+    /// which set the expiry of the commitments.
+    pub fn new(genesis_time: u64, slot_time: u64) -> Self {
+        let (updates, _) = broadcast::channel(STATUS_UPDATES_CAPACITY);
+        Self { slots: Default::default(), updates, genesis_time, slot_time }
+    }
+
+    /// Returns a receiver of the changes of status of the commitments made from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<CommitmentStatus> {
+        self.updates.subscribe()
+    }
+
+    /// Returns the end of the given slot in unix seconds, when its commitments expire.
+    pub fn expires_at(&self, slot: Slot) -> u64 {
+        self.genesis_time + (slot + 1) * self.slot_time
+    }
+
+    /// Returns the status of the commitment to the transaction in the given slot.
+    pub fn get(&self, slot: Slot, tx_hash: TxHash) -> CommitmentStatus {
+        let status = self
+            .slots
+            .read()
+            .get(slot)
+            .and_then(|statuses| statuses.get(&tx_hash).copied())
+            .unwrap_or(CommitmentState::Unknown);
+
+        self.status(slot, tx_hash, status)
+    }
+
+    /// Records the commitment to the transaction in the given slot.
+    pub fn record_pending(&self, slot: Slot, tx_hash: TxHash) {
+        let mut slots = self.slots.write();
+        let statuses = slots.get_or_default(slot);
+        if !statuses.contains_key(&tx_hash) {
+            statuses.insert(tx_hash, CommitmentState::Pending);
+            self.notify(slot, tx_hash, CommitmentState::Pending);
+        }
+    }
+
+    /// Marks the pending commitments of the slot as constrained, once the relay acknowledged
+    /// their constraints.
+    pub fn mark_constrained(&self, slot: Slot) {
+        if let Some(statuses) = self.slots.write().get_mut(slot) {
+            for (hash, status) in statuses.iter_mut() {
+                if *status == CommitmentState::Pending {
+                    *status = CommitmentState::Constrained;
+                    self.notify(slot, *hash, *status);
+                }
+            }
+        }
+    }
+
+    /// Marks the pending and constrained commitments of the slots that ended by the given
+    /// unix time as expired, and returns their new statuses. They are still resolved once
+    /// the head of their slot is received.
+    pub fn expire(&self, now: u64) -> Vec<CommitmentStatus> {
+        let mut expired = Vec::new();
+
+        let mut slots = self.slots.write();
+        for (slot, statuses) in slots.iter_mut() {
+            if self.expires_at(*slot) > now {
+                break;
+            }
+
+            for (hash, status) in statuses.iter_mut() {
+                if matches!(status, CommitmentState::Pending | CommitmentState::Constrained) {
+                    *status = CommitmentState::Expired;
+                    expired.push(self.status(*slot, *hash, *status));
+                }
+            }
+        }
+        drop(slots);
+
+        for status in &expired {
+            let _ = self.updates.send(status.clone());
+        }
+        expired
+    }
+
+    /// Returns the hashes of the transactions of the unresolved commitments of the slots up to
+    /// and including the given one, by slot.
+    pub fn unresolved_until(&self, slot: Slot) -> Vec<(Slot, Vec<TxHash>)> {
+        self.slots
+            .read()
+            .iter()
+            .take_while(|(s, _)| **s <= slot)
+            .filter_map(|(s, statuses)| {
+                let hashes = statuses
+                    .iter()
+                    .filter(|(_, status)| !status.is_resolved())
+                    .map(|(hash, _)| *hash)
+                    .collect::<Vec<_>>();
+                (!hashes.is_empty()).then_some((*s, hashes))
+            })
+            .collect()
+    }
+
+    /// Resolves the commitments of a slot that passed, given the transactions found on chain.
+    pub fn resolve(&self, slot: Slot, included: impl Fn(&TxHash) -> bool) {
+        if let Some(statuses) = self.slots.write().get_mut(slot) {
+            for (hash, status) in statuses.iter_mut() {
+                *status = if included(hash) {
+                    CommitmentState::Included
+                } else {
+                    CommitmentState::Missed
+                };
+                self.notify(slot, *hash, *status);
+            }
+        }
+    }
+
+    /// Records the resolution of commitments of a slot that passed, e.g. from the journal.
+    pub fn record_resolved(&self, slot: Slot, included: &[TxHash], missed: &[TxHash]) {
+        let mut slots = self.slots.write();
+        let statuses = slots.get_or_default(slot);
+        statuses.extend(included.iter().map(|hash| (*hash, CommitmentState::Included)));
+        statuses.extend(missed.iter().map(|hash| (*hash, CommitmentState::Missed)));
+    }
+
+    /// Drops the statuses of the slots that passed more than [STATUS_RETENTION_SLOTS] before
+    /// the given head slot.
+    pub fn advance(&self, head: Slot) {
+        if let Some(oldest) = head.checked_sub(STATUS_RETENTION_SLOTS) {
+            self.slots.write().advance(oldest);
+        }
+    }
+
+    fn status(&self, slot: Slot, tx_hash: TxHash, status: CommitmentState) -> CommitmentStatus {
+        CommitmentStatus { slot, tx_hash, status, expires_at: self.expires_at(slot) }
+    }
+
+    /// Broadcasts a change of status. Updates are dropped if there are no subscribers.
+    fn notify(&self, slot: Slot, tx_hash: TxHash, status: CommitmentState) {
+        let _ = self.updates.send(self.status(slot, tx_hash, status));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_statuses() {
+        let statuses = CommitmentStatuses::default();
+        let (tx, other) = (TxHash::repeat_byte(1), TxHash::repeat_byte(2));
+
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Unknown);
+
+        statuses.record_pending(10, tx);
+        statuses.record_pending(10, other);
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Pending);
+        assert_eq!(statuses.get(11, tx).status, CommitmentState::Unknown);
+
+        statuses.mark_constrained(10);
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Constrained);
+
+        let unresolved = statuses.unresolved_until(10);
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].1.len(), 2);
+
+        statuses.resolve(10, |hash| *hash == tx);
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Included);
+        assert_eq!(statuses.get(10, other).status, CommitmentState::Missed);
+        assert!(statuses.unresolved_until(10).is_empty());
+
+        statuses.advance(10 + STATUS_RETENTION_SLOTS);
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Unknown);
+    }
+
+    #[test]
+    fn test_commitment_expiry() {
+        let statuses = CommitmentStatuses::new(1_000, 12);
+        let mut updates = statuses.subscribe();
+        let (tx, other) = (TxHash::repeat_byte(1), TxHash::repeat_byte(2));
+
+        statuses.record_pending(10, tx);
+        statuses.record_pending(11, other);
+        statuses.record_pending(11, other);
+        assert_eq!(statuses.get(10, tx).expires_at, 1_132);
+
+        // Slot 10 ends at 1132, and slot 11 at 1144
+        assert!(statuses.expire(1_131).is_empty());
+        let expired = statuses.expire(1_140);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].tx_hash, tx);
+        assert_eq!(statuses.get(10, tx).status, CommitmentState::Expired);
+        assert_eq!(statuses.get(11, other).status, CommitmentState::Pending);
+        assert!(statuses.expire(1_140).is_empty());
+
+        // Expired commitments are still resolved at the head of their slot
+        assert_eq!(statuses.unresolved_until(10), vec![(10, vec![tx])]);
+        statuses.resolve(10, |_| true);
+
+        let received = std::iter::from_fn(|| updates.try_recv().ok())
+            .map(|update| (update.slot, update.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            received,
+            vec![
+                (10, CommitmentState::Pending),
+                (11, CommitmentState::Pending),
+                (10, CommitmentState::Expired),
+                (10, CommitmentState::Included),
+            ]
+        );
+    }
+}
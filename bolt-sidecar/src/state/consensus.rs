@@ -8,8 +8,9 @@ use ethereum_consensus::{crypto::PublicKey as BlsPublicKey, phase0::mainnet::SLO
 use tokio::join;
 use tracing::debug;
 
-use super::CommitmentDeadline;
+use super::{CommitmentDeadline, DeadlineSchedule};
 use crate::{
+    api::codes::ErrorCode,
     config::ValidatorIndexes,
     primitives::{CommitmentRequest, Slot},
     telemetry::ApiMetrics,
@@ -29,6 +30,22 @@ pub enum ConsensusError {
     DeadlineExceeded,
     #[error("Validator not found in the slot")]
     ValidatorNotFound,
+    #[error("No proposal slot of the sidecar from slot {0} to slot {1}")]
+    NoProposalSlotInRange(Slot, Slot),
+}
+
+impl ConsensusError {
+    /// Returns the stable error code of the error, shared with the other bolt components.
+    pub const fn error_code(&self) -> ErrorCode {
+        match self {
+            ConsensusError::BeaconApiError(_) => ErrorCode::UpstreamUnavailable,
+            ConsensusError::InvalidSlot(_) => ErrorCode::ValidationFailed,
+            ConsensusError::DeadlineExceeded => ErrorCode::DeadlinePassed,
+            ConsensusError::ValidatorNotFound | ConsensusError::NoProposalSlotInRange(_, _) => {
+                ErrorCode::NoProposer
+            }
+        }
+    }
 }
 
 /// Represents an epoch in the beacon chain.
@@ -64,6 +81,9 @@ pub struct ConsensusState {
     pub commitment_deadline: CommitmentDeadline,
     /// The duration of the commitment deadline.
     commitment_deadline_duration: Duration,
+    /// The commitment deadlines of the slots on the slot clock, if scheduled. Otherwise, the
+    /// deadline of the next slot is counted from when the latest slot was received.
+    deadline_schedule: Option<DeadlineSchedule>,
     /// If commitment requests should be validated also against the unsafe lookahead
     pub unsafe_lookahead_enabled: bool,
 }
@@ -95,10 +115,26 @@ impl ConsensusState {
             latest_slot_timestamp: Instant::now(),
             commitment_deadline: CommitmentDeadline::new(0, commitment_deadline_duration),
             commitment_deadline_duration,
+            deadline_schedule: None,
             unsafe_lookahead_enabled,
         }
     }
 
+    /// Schedules the commitment deadlines of the slots on the slot clock, rather than counting
+    /// them from when each slot is received.
+    pub fn with_deadline_schedule(mut self, schedule: DeadlineSchedule) -> Self {
+        self.deadline_schedule = Some(schedule);
+        self
+    }
+
+    /// Waits for the next commitment deadline, returning its slot.
+    pub async fn next_deadline(&mut self) -> Option<Slot> {
+        match self.deadline_schedule.as_mut() {
+            Some(schedule) => Some(schedule.next().await),
+            None => self.commitment_deadline.wait().await,
+        }
+    }
+
     /// This function validates the state of the chain against a block. It checks 2 things:
     /// 1. The target slot is one of our proposer slots. (TODO)
     /// 2. The request hasn't passed the slot deadline.
@@ -117,9 +153,7 @@ impl ConsensusState {
         }
 
         // If the request is for the next slot, check if it's within the commitment deadline
-        if req.slot == self.latest_slot + 1 &&
-            self.latest_slot_timestamp + self.commitment_deadline_duration < Instant::now()
-        {
+        if self.deadline_exceeded(req.slot) {
             return Err(ConsensusError::DeadlineExceeded);
         }
 
@@ -129,14 +163,73 @@ impl ConsensusState {
         Ok(validator_pubkey)
     }
 
+    /// Returns the proposal slots of the sidecar from `from` up to `to` (inclusive) that
+    /// commitments can still be made for, along with the validator public key of each,
+    /// in ascending order.
+    ///
+    /// Slots outside of the known lookahead are not considered, as their proposers are unknown.
+    pub fn proposal_slots(
+        &self,
+        from: Slot,
+        to: Slot,
+    ) -> Result<Vec<(Slot, BlsPublicKey)>, ConsensusError> {
+        if to < from {
+            return Err(ConsensusError::InvalidSlot(to));
+        }
+
+        let mut slots = self
+            .epoch
+            .proposer_duties
+            .iter()
+            .filter(|duty| {
+                duty.slot >= from.max(self.epoch.start_slot) &&
+                    duty.slot <= to &&
+                    duty.slot > self.latest_slot &&
+                    duty.slot < self.furthest_slot() &&
+                    !self.deadline_exceeded(duty.slot) &&
+                    self.validator_indexes.contains(duty.validator_index as u64)
+            })
+            .map(|duty| (duty.slot, duty.public_key.clone()))
+            .collect::<Vec<_>>();
+
+        if slots.is_empty() {
+            return Err(ConsensusError::NoProposalSlotInRange(from, to));
+        }
+
+        slots.sort_by_key(|(slot, _)| *slot);
+        Ok(slots)
+    }
+
+    /// Returns the public key of the validator of the sidecar proposing the given slot, even if
+    /// its commitment deadline passed, e.g. to sign the constraints of the requests decrypted
+    /// at the deadline.
+    pub fn proposer_pubkey(&self, slot: Slot) -> Result<BlsPublicKey, ConsensusError> {
+        self.find_validator_pubkey_for_slot(slot)
+    }
+
+    /// Returns the time left until the commitment deadline of the next slot, if it is proposed
+    /// by one of the validators of the sidecar and its deadline has not passed yet.
+    pub fn time_to_proposal_deadline(&self) -> Option<Duration> {
+        self.find_validator_pubkey_for_slot(self.latest_slot + 1).ok()?;
+        if let Some(schedule) = self.deadline_schedule.as_ref() {
+            return schedule.time_to_deadline(self.latest_slot + 1);
+        }
+
+        let deadline = self.latest_slot_timestamp + self.commitment_deadline_duration;
+        deadline.checked_duration_since(Instant::now())
+    }
+
     /// Update the latest head and fetch the relevant data from the beacon chain.
     pub async fn update_slot(&mut self, slot: u64) -> Result<(), ConsensusError> {
         debug!("Updating slot to {slot}");
         ApiMetrics::set_latest_head(slot as u32);
 
-        // Reset the commitment deadline to start counting for the next slot.
-        self.commitment_deadline =
-            CommitmentDeadline::new(slot + 1, self.commitment_deadline_duration);
+        // Reset the commitment deadline to start counting for the next slot, unless the
+        // deadlines are scheduled on the slot clock.
+        if self.deadline_schedule.is_none() {
+            self.commitment_deadline =
+                CommitmentDeadline::new(slot + 1, self.commitment_deadline_duration);
+        }
 
         // Update the timestamp with current time
         self.latest_slot_timestamp = Instant::now();
@@ -198,6 +291,17 @@ impl ConsensusState {
             .ok_or(ConsensusError::ValidatorNotFound)
     }
 
+    /// Returns true if the commitment deadline of the slot has passed. Without a schedule, only
+    /// the deadline of the next slot is known.
+    fn deadline_exceeded(&self, slot: Slot) -> bool {
+        if let Some(schedule) = self.deadline_schedule.as_ref() {
+            return schedule.is_passed(slot);
+        }
+
+        slot == self.latest_slot + 1 &&
+            self.latest_slot_timestamp + self.commitment_deadline_duration < Instant::now()
+    }
+
     /// Returns the furthest slot for which a commitment request is considered valid, whether in
     /// the current epoch or next epoch (if unsafe lookahead is enabled)
     fn furthest_slot(&self) -> u64 {
@@ -236,6 +340,7 @@ mod tests {
             commitment_deadline: CommitmentDeadline::new(0, Duration::from_secs(1)),
             validator_indexes,
             commitment_deadline_duration: Duration::from_secs(1),
+            deadline_schedule: None,
             latest_slot: 0,
             unsafe_lookahead_enabled: false,
         };
@@ -251,6 +356,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_proposal_slots() {
+        let duty = |slot, validator_index| ProposerDuty {
+            public_key: Default::default(),
+            slot,
+            validator_index,
+        };
+        let proposer_duties = vec![duty(3, 100), duty(1, 100), duty(2, 101), duty(5, 102)];
+
+        let state = ConsensusState {
+            beacon_api_client: Client::new(Url::parse("http://localhost").unwrap()),
+            epoch: Epoch { value: 0, start_slot: 0, proposer_duties },
+            latest_slot_timestamp: Instant::now(),
+            commitment_deadline: CommitmentDeadline::new(0, Duration::from_secs(1)),
+            validator_indexes: ValidatorIndexes::from(vec![100, 102]),
+            commitment_deadline_duration: Duration::from_secs(1),
+            deadline_schedule: None,
+            latest_slot: 1,
+            unsafe_lookahead_enabled: false,
+        };
+
+        // Past slots and slots of other validators are skipped
+        let slots = state.proposal_slots(1, 10).unwrap();
+        assert_eq!(slots.iter().map(|(slot, _)| *slot).collect::<Vec<_>>(), vec![3, 5]);
+
+        assert!(matches!(
+            state.proposal_slots(2, 2),
+            Err(ConsensusError::NoProposalSlotInRange(2, 2))
+        ));
+        assert!(matches!(state.proposal_slots(5, 4), Err(ConsensusError::InvalidSlot(4))));
+    }
+
     #[tokio::test]
     async fn test_update_slot() -> eyre::Result<()> {
         let _ = tracing_subscriber::fmt::try_init();
@@ -274,6 +411,7 @@ mod tests {
             validator_indexes,
             commitment_deadline: CommitmentDeadline::new(0, commitment_deadline_duration),
             commitment_deadline_duration,
+            deadline_schedule: None,
             unsafe_lookahead_enabled: false,
         };
 
@@ -320,6 +458,7 @@ mod tests {
             validator_indexes: Default::default(),
             commitment_deadline: CommitmentDeadline::new(0, commitment_deadline_duration),
             commitment_deadline_duration,
+            deadline_schedule: None,
             // We test for both epochs
             unsafe_lookahead_enabled: true,
         };
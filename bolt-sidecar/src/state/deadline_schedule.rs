@@ -0,0 +1,115 @@
+use std::{pin::Pin, time::Duration};
+
+use tokio::time::{sleep_until, Instant, Sleep};
+
+use crate::{common::now_ms, primitives::Slot};
+
+/// The commitment deadlines of the slots, each at a fixed cutoff into the slot before it.
+///
+/// Deadlines are computed from the genesis time of the chain, rather than from when the start
+/// of the previous slot is observed, so that a busy event loop or a late slot tick doesn't
+/// push them back. Past its deadline, no commitment can be made for a slot anymore, and its
+/// constraints are signed and submitted to the relays.
+#[derive(Debug)]
+pub struct DeadlineSchedule {
+    /// The genesis time of the chain, in unix milliseconds.
+    genesis_time_ms: u64,
+    /// The duration of a slot, in milliseconds.
+    slot_time_ms: u64,
+    /// The time into the previous slot of the deadline of a slot, in milliseconds.
+    cutoff_ms: u64,
+    /// The slot of the next deadline.
+    next_slot: Slot,
+    /// The timer of the next deadline.
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl DeadlineSchedule {
+    /// Creates the schedule of the chain with the given genesis time in unix seconds and slot
+    /// time, with the deadline of each slot at the given cutoff into the previous one. The
+    /// first deadline is the next one that hasn't passed yet.
+    pub fn new(genesis_time: u64, slot_time: Duration, cutoff: Duration) -> Self {
+        let mut schedule = Self {
+            genesis_time_ms: genesis_time.saturating_mul(1000),
+            slot_time_ms: (slot_time.as_millis() as u64).max(1),
+            cutoff_ms: cutoff.as_millis() as u64,
+            next_slot: 0,
+            sleep: Box::pin(sleep_until(Instant::now())),
+        };
+        schedule.next_slot = schedule.first_open_slot(now_ms());
+        schedule.sleep.as_mut().reset(schedule.instant(schedule.next_slot));
+        schedule
+    }
+
+    /// Returns the commitment deadline of the slot, in unix milliseconds.
+    pub fn deadline_ms(&self, slot: Slot) -> u64 {
+        self.genesis_time_ms
+            .saturating_add(slot.saturating_sub(1).saturating_mul(self.slot_time_ms))
+            .saturating_add(self.cutoff_ms)
+    }
+
+    /// Returns true if the commitment deadline of the slot passed.
+    pub fn is_passed(&self, slot: Slot) -> bool {
+        self.deadline_ms(slot) <= now_ms()
+    }
+
+    /// Returns the time left until the commitment deadline of the slot, if it hasn't passed.
+    pub fn time_to_deadline(&self, slot: Slot) -> Option<Duration> {
+        let left = self.deadline_ms(slot).checked_sub(now_ms())?;
+        (left > 0).then(|| Duration::from_millis(left))
+    }
+
+    /// Waits for the next commitment deadline, returning its slot. Every slot is returned in
+    /// order, even if its deadline passed while not waiting.
+    ///
+    /// This is cancel-safe: the deadline is only consumed once it is returned.
+    pub async fn next(&mut self) -> Slot {
+        self.sleep.as_mut().await;
+
+        let slot = self.next_slot;
+        self.next_slot += 1;
+        let next = self.instant(self.next_slot);
+        self.sleep.as_mut().reset(next);
+        slot
+    }
+
+    /// Returns the first slot whose deadline is after the given time in unix milliseconds.
+    fn first_open_slot(&self, now_ms: u64) -> Slot {
+        match now_ms.checked_sub(self.genesis_time_ms + self.cutoff_ms) {
+            Some(elapsed) => elapsed / self.slot_time_ms + 2,
+            None => 1,
+        }
+    }
+
+    /// Returns the instant of the deadline of the slot on the tokio clock.
+    fn instant(&self, slot: Slot) -> Instant {
+        Instant::now() + Duration::from_millis(self.deadline_ms(slot).saturating_sub(now_ms()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline_schedule() {
+        let genesis_time = now_ms() / 1000 - 10;
+        let mut schedule =
+            DeadlineSchedule::new(genesis_time, Duration::from_secs(1), Duration::from_millis(500));
+
+        // The deadline of a slot is at the cutoff into the previous one
+        assert_eq!(schedule.deadline_ms(11), genesis_time * 1000 + 10_500);
+        assert_eq!(schedule.first_open_slot(genesis_time * 1000 + 10_500), 12);
+        assert_eq!(schedule.first_open_slot(genesis_time * 1000 + 10_499), 11);
+        assert_eq!(schedule.first_open_slot(genesis_time * 1000), 1);
+
+        let first = schedule.next_slot;
+        assert!(schedule.is_passed(first - 1));
+        assert!(schedule.time_to_deadline(first).is_some());
+
+        // Deadlines are reached in order, once each
+        assert_eq!(schedule.next().await, first);
+        assert!(schedule.is_passed(first));
+        assert_eq!(schedule.next().await, first + 1);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    sync::Arc,
+};
+
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{DelegationPolicy, DelegationTargetingOpts},
+    primitives::Slot,
+};
+
+use super::SlotScoped;
+
+/// The delegatee assigned the authority over an upcoming proposal slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotAssignment {
+    /// The proposal slot.
+    pub slot: Slot,
+    /// The public key of the validator proposing the slot.
+    pub validator_pubkey: BlsPublicKey,
+    /// The public key of the delegatee signing the constraints of the slot. If not set,
+    /// any delegatee whose key is available to the sidecar signs them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegatee_pubkey: Option<BlsPublicKey>,
+    /// Whether the delegation to the delegatee was signed by the sidecar for the slot,
+    /// and must be revoked after it.
+    #[serde(skip)]
+    pub generated: bool,
+}
+
+/// Decides which delegatee receives the authority over each proposal slot, according to
+/// the configured [DelegationPolicy].
+#[derive(Debug, Clone, Default)]
+pub struct DelegationTargeting {
+    policy: DelegationPolicy,
+    delegatees: Vec<BlsPublicKey>,
+    assignments: BTreeMap<Slot, BlsPublicKey>,
+}
+
+impl DelegationTargeting {
+    /// Creates the targeting of the given policy, with the configured delegatees in order of
+    /// preference and the assignments of the `per-slot` policy.
+    pub fn new(
+        policy: DelegationPolicy,
+        delegatees: Vec<BlsPublicKey>,
+        assignments: BTreeMap<Slot, BlsPublicKey>,
+    ) -> Self {
+        Self { policy, delegatees, assignments }
+    }
+
+    /// Creates the targeting from the options, reading the assignments file if any.
+    pub fn from_opts(opts: &DelegationTargetingOpts) -> eyre::Result<Self> {
+        let assignments = match opts.delegation_assignments_path.as_ref() {
+            Some(path) => serde_json::from_str(&fs::read_to_string(path)?).map_err(|err| {
+                eyre::eyre!("Failed to parse delegation assignments {}: {err}", path.display())
+            })?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self::new(opts.delegation_policy, opts.delegatees.clone(), assignments))
+    }
+
+    /// Returns the policy of the targeting.
+    pub fn policy(&self) -> DelegationPolicy {
+        self.policy
+    }
+
+    /// Returns the candidate delegatees of a validator in order of preference: the configured
+    /// ones if any, or else the given delegatees of its delegations.
+    pub fn candidates(&self, delegated: Vec<BlsPublicKey>) -> Vec<BlsPublicKey> {
+        if self.delegatees.is_empty() {
+            delegated
+        } else {
+            self.delegatees.clone()
+        }
+    }
+
+    /// Returns the delegatee among the candidates, in order of preference, that receives the
+    /// authority over the given slot, skipping the ones whose delegation the relay
+    /// persistently refuses when falling back to backups.
+    ///
+    /// Returns `None` with the `any` policy, or if there is no candidate.
+    pub fn assign(
+        &self,
+        slot: Slot,
+        candidates: &[BlsPublicKey],
+        refused: &HashSet<BlsPublicKey>,
+    ) -> Option<BlsPublicKey> {
+        let primary_backup = || {
+            candidates
+                .iter()
+                .find(|delegatee| !refused.contains(delegatee))
+                .or_else(|| candidates.first())
+                .cloned()
+        };
+
+        match self.policy {
+            DelegationPolicy::Any => None,
+            DelegationPolicy::RoundRobin if candidates.is_empty() => None,
+            DelegationPolicy::RoundRobin => {
+                Some(candidates[(slot % candidates.len() as u64) as usize].clone())
+            }
+            DelegationPolicy::PrimaryBackup => primary_backup(),
+            DelegationPolicy::PerSlot => {
+                self.assignments.get(&slot).cloned().or_else(primary_backup)
+            }
+        }
+    }
+}
+
+/// The assignments of the upcoming proposal slots to their delegatees.
+///
+/// Clones share the same state, so that the driver records the assignments while the
+/// commitments API reports them to gateways.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationSchedule {
+    assignments: Arc<RwLock<SlotScoped<SlotAssignment>>>,
+}
+
+impl DelegationSchedule {
+    /// Returns the assignment of the given slot, if any.
+    pub fn get(&self, slot: Slot) -> Option<SlotAssignment> {
+        self.assignments.read().get(slot).cloned()
+    }
+
+    /// Records the assignment of a slot, unless the slot is already assigned.
+    /// Returns true if the assignment was recorded.
+    pub fn assign(&self, assignment: SlotAssignment) -> bool {
+        let mut assignments = self.assignments.write();
+        if assignment.slot <= assignments.head() || assignments.get(assignment.slot).is_some() {
+            return false;
+        }
+
+        assignments.insert(assignment.slot, assignment);
+        true
+    }
+
+    /// Returns the assignments of the upcoming slots, by ascending slot.
+    pub fn upcoming(&self) -> Vec<SlotAssignment> {
+        self.assignments.read().iter().map(|(_, assignment)| assignment.clone()).collect()
+    }
+
+    /// Drops the assignments of every slot up to and including the given head slot,
+    /// returning them by ascending slot.
+    pub fn advance(&self, head: Slot) -> Vec<SlotAssignment> {
+        self.assignments.write().advance(head).into_values().collect()
+    }
+
+    /// Returns true if a delegation from the validator to the delegatee was signed for
+    /// an upcoming slot.
+    pub fn is_generated(&self, validator_pubkey: &BlsPublicKey, delegatee: &BlsPublicKey) -> bool {
+        self.assignments.read().iter().any(|(_, assignment)| {
+            assignment.generated &&
+                assignment.validator_pubkey == *validator_pubkey &&
+                assignment.delegatee_pubkey.as_ref() == Some(delegatee)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> BlsPublicKey {
+        BlsPublicKey::try_from([byte; 48].as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_delegation_targeting() {
+        let candidates = [key(1), key(2), key(3)];
+        let none = HashSet::new();
+        let refused = HashSet::from([key(1)]);
+
+        let any = DelegationTargeting::default();
+        assert_eq!(any.assign(10, &candidates, &none), None);
+
+        let round_robin =
+            DelegationTargeting::new(DelegationPolicy::RoundRobin, Vec::new(), BTreeMap::new());
+        assert_eq!(round_robin.assign(10, &candidates, &none), Some(key(2)));
+        assert_eq!(round_robin.assign(11, &candidates, &none), Some(key(3)));
+        assert_eq!(round_robin.assign(10, &[], &none), None);
+
+        // The backup takes over once the relay persistently refuses the primary
+        let primary_backup =
+            DelegationTargeting::new(DelegationPolicy::PrimaryBackup, Vec::new(), BTreeMap::new());
+        assert_eq!(primary_backup.assign(10, &candidates, &none), Some(key(1)));
+        assert_eq!(primary_backup.assign(10, &candidates, &refused), Some(key(2)));
+
+        // Unassigned slots fall back to primary-backup
+        let assignments = BTreeMap::from([(12, key(3))]);
+        let per_slot = DelegationTargeting::new(DelegationPolicy::PerSlot, Vec::new(), assignments);
+        assert_eq!(per_slot.assign(12, &candidates, &none), Some(key(3)));
+        assert_eq!(per_slot.assign(13, &candidates, &none), Some(key(1)));
+
+        // The configured delegatees take precedence over those of the delegations
+        let configured =
+            DelegationTargeting::new(DelegationPolicy::RoundRobin, vec![key(4)], BTreeMap::new());
+        assert_eq!(configured.candidates(candidates.to_vec()), vec![key(4)]);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, B256};
+use thiserror::Error;
+
+use crate::primitives::{
+    DecryptionError, DecryptionKeyReveal, EncryptedInclusionRequest, Slot, TimeLockError,
+};
+
+use super::SlotScoped;
+
+/// Possible errors when requesting the inclusion of encrypted transactions.
+#[derive(Debug, Error)]
+pub enum EncryptedRequestError {
+    /// Encrypted requests are disabled on this sidecar.
+    #[error("Encrypted requests are disabled")]
+    Disabled,
+    /// The request has no signer.
+    #[error("Missing encrypted request signer")]
+    MissingSigner,
+    /// The request has no time-lock puzzle, which the sidecar requires.
+    #[error("Missing time-lock puzzle")]
+    MissingTimeLock,
+    /// The time-lock puzzle of the request is rejected.
+    #[error("Invalid time-lock puzzle: {0}")]
+    TimeLock(#[from] TimeLockError),
+    /// The gas limit is zero.
+    #[error("The gas limit must be greater than zero")]
+    ZeroGas,
+    /// A request with the same ciphertext is already pending.
+    #[error("Duplicate encrypted request {0}")]
+    Duplicate(B256),
+    /// Not enough gas is left in the target slot to hold the gas limit of the request.
+    #[error("Not enough gas left in the slot, only {0} available")]
+    InsufficientCapacity(u64),
+    /// No request with the ciphertext is pending, or the deadline of its slot passed.
+    #[error("No pending encrypted request {0}")]
+    UnknownCiphertext(B256),
+    /// The ciphertext can't be decrypted with the key.
+    #[error(transparent)]
+    Decryption(#[from] DecryptionError),
+    /// The signer withheld too many decryption keys past the commitment deadline.
+    #[error("Signer {0} withheld {1} decryption keys")]
+    TooManyWithheldKeys(Address, u64),
+}
+
+/// An encrypted request committed to, waiting for its decryption key.
+#[derive(Debug)]
+pub struct PendingEncrypted {
+    /// The encrypted request.
+    pub request: EncryptedInclusionRequest,
+    /// The hash of the ciphertext of the request.
+    pub ciphertext_hash: B256,
+    /// The decryption key, once revealed.
+    pub key: Option<B256>,
+}
+
+/// An encrypted request whose decryption key was revealed before the deadline.
+#[derive(Debug)]
+pub struct RevealedEncrypted {
+    /// The encrypted request.
+    pub request: EncryptedInclusionRequest,
+    /// The hash of the ciphertext of the request.
+    pub ciphertext_hash: B256,
+    /// The decryption key.
+    pub key: B256,
+}
+
+/// The encrypted requests taken from the pool at the commitment deadline of a slot.
+#[derive(Debug, Default)]
+pub struct DeadlineEncrypted {
+    /// The requests of the slot whose key was revealed, in the order they were received.
+    pub revealed: Vec<RevealedEncrypted>,
+    /// The requests of the slot and of the passed ones whose key wasn't revealed, each
+    /// counted as a key withheld by its signer.
+    pub withheld: Vec<PendingEncrypted>,
+}
+
+/// The encrypted requests committed to in the upcoming slots, until their transactions are
+/// decrypted at the commitment deadline of their slot.
+///
+/// The decryption keys are revealed by the clients, or by the sidecar once it solves the
+/// time-lock puzzles of the requests. A client that doesn't lock its key in a puzzle, or locks
+/// a wrong one, can withhold it and leave the gas held for its request unused. The keys
+/// withheld are counted per signer, whose requests are rejected past a maximum.
+#[derive(Debug, Default)]
+pub struct EncryptedPool {
+    /// The pending requests, by target slot, in the order they were received.
+    pending: SlotScoped<Vec<PendingEncrypted>>,
+    /// The number of decryption keys withheld past the deadline, by signer.
+    withheld: HashMap<Address, u64>,
+    /// The number of keys a signer can withhold before its requests are rejected, or 0 to
+    /// never reject them.
+    max_withheld: u64,
+}
+
+impl EncryptedPool {
+    /// Creates an empty pool, rejecting the requests of signers that withheld the given
+    /// number of keys, or never if 0.
+    pub fn new(max_withheld: u64) -> Self {
+        Self { max_withheld, ..Default::default() }
+    }
+
+    /// Returns the number of decryption keys withheld by the signer.
+    pub fn withheld_keys(&self, signer: Address) -> u64 {
+        self.withheld.get(&signer).copied().unwrap_or_default()
+    }
+
+    /// Checks that the signer didn't withhold too many decryption keys.
+    pub fn check_signer(&self, signer: Address) -> Result<(), EncryptedRequestError> {
+        let withheld = self.withheld_keys(signer);
+        if self.max_withheld > 0 && withheld >= self.max_withheld {
+            return Err(EncryptedRequestError::TooManyWithheldKeys(signer, withheld));
+        }
+        Ok(())
+    }
+
+    /// Returns true if a request with the given ciphertext hash is pending in the slot.
+    pub fn contains(&self, slot: Slot, ciphertext_hash: &B256) -> bool {
+        self.pending
+            .get(slot)
+            .is_some_and(|pending| pending.iter().any(|p| &p.ciphertext_hash == ciphertext_hash))
+    }
+
+    /// Adds a request committed to, returning the hash of its ciphertext.
+    pub fn insert(&mut self, request: EncryptedInclusionRequest) -> B256 {
+        let ciphertext_hash = request.ciphertext_hash();
+        self.pending.get_or_default(request.slot).push(PendingEncrypted {
+            request,
+            ciphertext_hash,
+            key: None,
+        });
+        ciphertext_hash
+    }
+
+    /// Records the decryption key of a pending request, after checking it against the
+    /// checksum of the ciphertext. Returns the target slot of the request.
+    pub fn reveal(&mut self, reveal: &DecryptionKeyReveal) -> Result<Slot, EncryptedRequestError> {
+        let (slot, pending) = self
+            .pending
+            .iter_mut()
+            .find_map(|(slot, pending)| {
+                pending
+                    .iter_mut()
+                    .find(|p| p.ciphertext_hash == reveal.ciphertext_hash)
+                    .map(|pending| (*slot, pending))
+            })
+            .ok_or(EncryptedRequestError::UnknownCiphertext(reveal.ciphertext_hash))?;
+
+        pending.request.decrypt(&reveal.key)?;
+        pending.key = Some(reveal.key);
+        Ok(slot)
+    }
+
+    /// Takes the pending requests of the slot at its commitment deadline, dropping those of
+    /// the earlier slots. No key can be revealed for them afterwards, so the keys that weren't
+    /// are counted as withheld by their signers.
+    pub fn take(&mut self, slot: Slot) -> DeadlineEncrypted {
+        let passed = self.pending.advance(slot);
+
+        let mut taken = DeadlineEncrypted::default();
+        for (pending_slot, pending) in passed {
+            for pending in pending {
+                let PendingEncrypted { request, ciphertext_hash, key } = pending;
+                match key {
+                    Some(key) if pending_slot == slot => {
+                        taken.revealed.push(RevealedEncrypted { request, ciphertext_hash, key })
+                    }
+                    Some(_) => {}
+                    None => {
+                        if let Some(signer) = request.signer {
+                            *self.withheld.entry(signer).or_default() += 1;
+                        }
+                        taken.withheld.push(PendingEncrypted { request, ciphertext_hash, key });
+                    }
+                }
+            }
+        }
+
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{encrypt_txs, test_legacy_transaction};
+
+    use super::*;
+
+    #[test]
+    fn test_encrypted_pool_reveal() {
+        let txs = vec![test_legacy_transaction()];
+        let (revealed_key, hidden_key) = (B256::random(), B256::random());
+
+        let mut pool = EncryptedPool::default();
+        let request = |slot, key| EncryptedInclusionRequest {
+            slot,
+            gas_limit: 21_000,
+            ciphertext: encrypt_txs(&txs, key),
+            timelock: None,
+            signer: None,
+        };
+        let revealed = pool.insert(request(10, &revealed_key));
+        let hidden = pool.insert(request(10, &hidden_key));
+        let missed = pool.insert(request(9, &revealed_key));
+        assert!(pool.contains(10, &revealed));
+
+        // Keys are checked against the ciphertext
+        let wrong = DecryptionKeyReveal { ciphertext_hash: revealed, key: hidden_key };
+        assert!(matches!(
+            pool.reveal(&wrong),
+            Err(EncryptedRequestError::Decryption(DecryptionError::WrongKey))
+        ));
+        let reveal = DecryptionKeyReveal { ciphertext_hash: revealed, key: revealed_key };
+        assert_eq!(pool.reveal(&reveal).unwrap(), 10);
+
+        // The unrevealed requests of the slot are withheld along with those of the passed slots
+        let taken = pool.take(10);
+        assert_eq!(taken.revealed.len(), 1);
+        assert_eq!(taken.revealed[0].key, revealed_key);
+        let withheld = taken.withheld.iter().map(|p| p.ciphertext_hash).collect::<Vec<_>>();
+        assert_eq!(withheld, vec![missed, hidden]);
+
+        // The requests of the passed slots can't be revealed anymore
+        let late = DecryptionKeyReveal { ciphertext_hash: missed, key: revealed_key };
+        assert!(matches!(pool.reveal(&late), Err(EncryptedRequestError::UnknownCiphertext(_))));
+    }
+
+    #[test]
+    fn test_encrypted_pool_withheld_keys() {
+        let txs = vec![test_legacy_transaction()];
+        let (signer, other) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let request = |slot, signer| EncryptedInclusionRequest {
+            slot,
+            gas_limit: 21_000,
+            ciphertext: encrypt_txs(&txs, &B256::random()),
+            timelock: None,
+            signer: Some(signer),
+        };
+
+        let mut pool = EncryptedPool::new(2);
+        pool.insert(request(9, signer));
+        pool.insert(request(10, signer));
+        pool.insert(request(10, other));
+        assert!(pool.check_signer(signer).is_ok());
+
+        // The keys of the passed slots count as withheld, with those of the slot
+        let taken = pool.take(10);
+        assert_eq!(taken.withheld.len(), 3);
+        assert_eq!(pool.withheld_keys(signer), 2);
+        assert_eq!(pool.withheld_keys(other), 1);
+        assert!(matches!(
+            pool.check_signer(signer),
+            Err(EncryptedRequestError::TooManyWithheldKeys(s, 2)) if s == signer
+        ));
+        assert!(pool.check_signer(other).is_ok());
+
+        // Signers are never rejected without a maximum
+        let mut pool = EncryptedPool::new(0);
+        pool.insert(request(10, signer));
+        pool.take(10);
+        assert!(pool.check_signer(signer).is_ok());
+    }
+}
@@ -1,27 +1,39 @@
 use alloy::{
-    eips::eip4844::MAX_BLOBS_PER_BLOCK,
-    primitives::{Address, B256, U256},
+    eips::eip4844::DATA_GAS_PER_BLOB,
+    primitives::{Address, B256},
+    rpc::types::beacon::events::PayloadAttributesEvent,
     transports::TransportError,
 };
-use reth_primitives::{
-    revm_primitives::EnvKzgSettings, BlobTransactionValidationError, PooledTransactionsElement,
-};
-use std::{
-    collections::{HashMap, HashSet},
-    ops::Deref,
-};
+use reth_primitives::{revm_primitives::EnvKzgSettings, BlobTransactionValidationError};
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 use tracing::{debug, trace, warn};
 
 use crate::{
-    builder::BlockTemplate,
-    common::{calculate_max_basefee, max_transaction_cost, validate_transaction},
-    config::limits::LimitsOpts,
-    primitives::{AccountState, CommitmentRequest, SignedConstraints, Slot},
+    api::codes::ErrorCode,
+    builder::{BlockTemplate, TemplateSnapshot},
+    common::calculate_max_basefee,
+    config::{limits::LimitsOpts, PricingOpts, ReservationOpts, VoucherOpts},
+    primitives::{
+        AccountState, CommitmentRequest, EncryptedInclusionRequest, FullTransaction,
+        InclusionRequest, Reservation, ReservationRequest, SignedConstraints,
+    },
     telemetry::ApiMetrics,
 };
 
-use super::fetcher::StateFetcher;
+use super::{
+    attributes::ProposalAttributes,
+    encrypted::EncryptedRequestError,
+    fetcher::StateFetcher,
+    mempool::{find_conflicts, MempoolConflict},
+    pricing::{PriceQuote, PricingEngine},
+    reservations::{ReservationBook, ReservationError, ReservationUsage},
+    screening::Screening,
+    slot_scoped::SlotScoped,
+    tx_policy::TxPolicyViolation,
+    validation::{ValidationContext, ValidationParams, ValidationPipeline},
+    vouchers::{VoucherError, VoucherLedger, VoucherSettlement},
+};
 
 /// Possible commitment validation errors.
 ///
@@ -58,9 +70,13 @@ pub enum ValidationError {
     /// Max priority fee per gas is greater than max fee per gas.
     #[error("Max priority fee per gas is greater than max fee per gas")]
     MaxPriorityFeePerGasTooHigh,
-    /// Max priority fee per gas is less than min priority fee.
-    #[error("Max priority fee per gas is less than min priority fee")]
-    MaxPriorityFeePerGasTooLow,
+    /// Max priority fee per gas is less than the min priority fee quoted for the slot.
+    #[error(
+        "Max priority fee per gas is less than the min priority fee of {} wei quoted for slot {}",
+        .0.min_priority_fee,
+        .0.slot
+    )]
+    MaxPriorityFeePerGasTooLow(PriceQuote),
     /// The sender does not have enough balance to pay for the transaction.
     #[error("Not enough balance to pay for value + maximum fee")]
     InsufficientBalance,
@@ -85,6 +101,38 @@ pub enum ValidationError {
     /// The transaction chain ID does not match the expected chain ID.
     #[error("Chain ID mismatch")]
     ChainIdMismatch,
+    /// The request signer is not in the whitelist of the sidecar.
+    #[error("Signer {0} is not whitelisted")]
+    SignerNotWhitelisted(Address),
+    /// A pending transaction in the mempool spends the same nonce of the sender as a
+    /// committed transaction, with a higher tip.
+    #[error("Nonce {1} of {0} is contested by a higher-tip transaction in the mempool")]
+    MempoolConflict(Address, u64),
+    /// The payment voucher of the request is invalid.
+    #[error(transparent)]
+    Voucher(#[from] VoucherError),
+    /// Top-of-block requests are not accepted, as no auction is configured or the relay
+    /// doesn't enforce top-of-block constraints.
+    #[error("Top-of-block requests are not accepted")]
+    TopOfBlockDisabled,
+    /// Top-of-block requests can't have a slot range.
+    #[error("Top-of-block requests can't have a slot range")]
+    TopOfBlockSlotRange,
+    /// The top-of-block auction of the slot is closed.
+    #[error("The top-of-block auction of slot {0} is closed")]
+    TopOfBlockAuctionClosed(u64),
+    /// A transaction is refused by the transaction policy of the operator.
+    #[error(transparent)]
+    TxPolicy(#[from] TxPolicyViolation),
+    /// An address of the request is flagged by the compliance screening of the operator.
+    #[error("Address {address} flagged by {screener} screening: {label}")]
+    Screened { address: Address, screener: &'static str, label: String },
+    /// An address of the request can't be screened, and the screening fails closed.
+    #[error("Compliance screening by {0} is unavailable, retry later")]
+    ScreeningUnavailable(&'static str),
+    /// The request was rejected by a custom validator.
+    #[error("Rejected by {0}: {1}")]
+    Rejected(&'static str, String),
     /// NOTE: this should not be exposed to the user.
     #[error("Internal error: {0}")]
     Internal(String),
@@ -109,7 +157,7 @@ impl ValidationError {
             ValidationError::GasLimitTooHigh => "gas_limit_too_high",
             ValidationError::TransactionSizeTooHigh => "transaction_size_too_high",
             ValidationError::MaxPriorityFeePerGasTooHigh => "max_priority_fee_per_gas_too_high",
-            ValidationError::MaxPriorityFeePerGasTooLow => "max_priority_fee_per_gas_too_low",
+            ValidationError::MaxPriorityFeePerGasTooLow(_) => "max_priority_fee_per_gas_too_low",
             ValidationError::InsufficientBalance => "insufficient_balance",
             ValidationError::Eip4844Limit => "eip4844_limit",
             ValidationError::SlotTooLow(_) => "slot_too_low",
@@ -122,9 +170,58 @@ impl ValidationError {
             ValidationError::Signature(_) => "signature",
             ValidationError::RecoverSigner => "recover_signer",
             ValidationError::ChainIdMismatch => "chain_id_mismatch",
+            ValidationError::SignerNotWhitelisted(_) => "signer_not_whitelisted",
+            ValidationError::MempoolConflict(_, _) => "mempool_conflict",
+            ValidationError::Voucher(_) => "voucher",
+            ValidationError::TopOfBlockDisabled => "top_of_block_disabled",
+            ValidationError::TopOfBlockSlotRange => "top_of_block_slot_range",
+            ValidationError::TopOfBlockAuctionClosed(_) => "top_of_block_auction_closed",
+            ValidationError::TxPolicy(_) => "tx_policy",
+            ValidationError::Screened { .. } => "screened",
+            ValidationError::ScreeningUnavailable(_) => "screening_unavailable",
+            ValidationError::Rejected(_, _) => "rejected",
             ValidationError::Internal(_) => "internal",
         }
     }
+
+    /// Returns the stable error code of the error, shared with the other bolt components.
+    pub const fn error_code(&self) -> ErrorCode {
+        match self {
+            ValidationError::BaseFeeTooLow(_) |
+            ValidationError::BlobBaseFeeTooLow(_) |
+            ValidationError::MaxPriorityFeePerGasTooLow(_) => ErrorCode::FeeTooLow,
+            ValidationError::NonceTooLow(_, _) |
+            ValidationError::NonceTooHigh(_, _) |
+            ValidationError::MempoolConflict(_, _) => ErrorCode::NonceConflict,
+            ValidationError::InsufficientBalance => ErrorCode::InsufficientBalance,
+            ValidationError::Eip4844Limit |
+            ValidationError::MaxCommitmentsReachedForSlot(_, _) |
+            ValidationError::MaxCommittedGasReachedForSlot(_, _) => ErrorCode::SlotFull,
+            ValidationError::SlotTooLow(_) | ValidationError::TopOfBlockAuctionClosed(_) => {
+                ErrorCode::DeadlinePassed
+            }
+            ValidationError::Signature(_) => ErrorCode::InvalidSignature,
+            ValidationError::RecoverSigner => ErrorCode::MalformedSignature,
+            ValidationError::ChainIdMismatch => ErrorCode::WrongChain,
+            ValidationError::SignerNotWhitelisted(_) => ErrorCode::Unauthorized,
+            ValidationError::Voucher(_) => ErrorCode::PaymentRequired,
+            ValidationError::TxPolicy(_) | ValidationError::Screened { .. } => {
+                ErrorCode::PolicyViolation
+            }
+            ValidationError::ScreeningUnavailable(_) => ErrorCode::UpstreamUnavailable,
+            ValidationError::Rejected(_, _) => ErrorCode::Rejected,
+            ValidationError::MaxBaseFeeCalcOverflow | ValidationError::Internal(_) => {
+                ErrorCode::Internal
+            }
+            ValidationError::BlobValidation(_) |
+            ValidationError::AccountHasCode |
+            ValidationError::GasLimitTooHigh |
+            ValidationError::TransactionSizeTooHigh |
+            ValidationError::MaxPriorityFeePerGasTooHigh |
+            ValidationError::TopOfBlockDisabled |
+            ValidationError::TopOfBlockSlotRange => ErrorCode::ValidationFailed,
+        }
+    }
 }
 
 /// The minimal state of the execution layer at some block number (`head`).
@@ -154,7 +251,7 @@ pub struct ExecutionState<C> {
     /// The block templates by target SLOT NUMBER.
     /// We have multiple block templates because in rare cases we might have multiple
     /// proposal duties for a single lookahead.
-    block_templates: HashMap<Slot, BlockTemplate>,
+    block_templates: SlotScoped<BlockTemplate>,
     /// The chain ID of the chain (constant).
     chain_id: u64,
     /// The limits set for the sidecar.
@@ -165,24 +262,26 @@ pub struct ExecutionState<C> {
     client: C,
     /// Other values used for validation
     validation_params: ValidationParams,
-}
-
-/// Other values used for validation.
-#[derive(Debug)]
-pub struct ValidationParams {
-    block_gas_limit: u64,
-    max_tx_input_bytes: usize,
-    max_init_code_byte_size: usize,
-}
-
-impl Default for ValidationParams {
-    fn default() -> Self {
-        Self {
-            block_gas_limit: 30_000_000,
-            max_tx_input_bytes: 4 * 32 * 1024,
-            max_init_code_byte_size: 2 * 24576,
-        }
-    }
+    /// The validators that commitment requests must pass, in order
+    validation_pipeline: ValidationPipeline,
+    /// The blockspace reservations of the upcoming slots
+    reservations: ReservationBook,
+    /// The commitments with a slot range, by their current target slot, that
+    /// can still be rolled over to a later slot of their range if missed.
+    ranged_commitments: Vec<InclusionRequest>,
+    /// The commitments with a slot range that missed their target slot and
+    /// must be rolled over by the driver.
+    rollovers: Vec<InclusionRequest>,
+    /// The execution attributes of the upcoming proposal slots, from the beacon node
+    proposal_attributes: SlotScoped<ProposalAttributes>,
+    /// The committed transactions contested in the mempool, by sender, as of the last scan
+    mempool_conflicts: HashMap<Address, MempoolConflict>,
+    /// The payment channels debited with the vouchers of the accepted requests
+    vouchers: VoucherLedger,
+    /// The compliance screening of the addresses of the requests, if enabled
+    screening: Option<Screening>,
+    /// The pricing of inclusion commitments by the committed gas of their slot
+    pricing: PricingEngine,
 }
 
 impl<C: StateFetcher> ExecutionState<C> {
@@ -205,20 +304,62 @@ impl<C: StateFetcher> ExecutionState<C> {
             client,
             slot: 0,
             account_states: HashMap::new(),
-            block_templates: HashMap::new(),
+            block_templates: SlotScoped::new(),
             // Load the default KZG settings
             kzg_settings: EnvKzgSettings::default(),
             // TODO: add a way to configure these values from CLI
             validation_params: ValidationParams::default(),
+            validation_pipeline: ValidationPipeline::default(),
+            reservations: ReservationBook::default(),
+            ranged_commitments: Vec::new(),
+            rollovers: Vec::new(),
+            proposal_attributes: SlotScoped::new(),
+            mempool_conflicts: HashMap::new(),
+            vouchers: VoucherLedger::default(),
+            screening: None,
+            pricing: PricingEngine::default(),
         })
     }
 
+    /// Returns the chain ID of the execution client.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
     /// Returns the current base fee in gwei
     pub fn basefee(&self) -> u128 {
         self.basefee
     }
 
-    /// Validates the commitment request against state (historical + intermediate).
+    /// Returns the minimum cost of including the transactions of the request at the target
+    /// slot, which is their gas at the maximum basefee of the slot plus the minimum priority
+    /// fee, and their blob gas at its maximum blob basefee. The fees are exact if the
+    /// attributes of the proposal are known.
+    ///
+    /// Returns `None` if the maximum basefee overflows.
+    pub fn min_inclusion_cost(&self, request: &InclusionRequest, target_slot: u64) -> Option<u128> {
+        let (basefee, blob_basefee) = match self.proposal_attributes.get(target_slot) {
+            Some(attributes) => (attributes.basefee, attributes.blob_basefee),
+            None => {
+                let slot_diff = target_slot.saturating_sub(self.slot);
+                (
+                    calculate_max_basefee(self.basefee, slot_diff)?,
+                    calculate_max_basefee(self.blob_basefee, slot_diff)?,
+                )
+            }
+        };
+
+        let gas_price = basefee.checked_add(self.limits.min_priority_fee.get())?;
+        request.txs.iter().try_fold(0u128, |cost, tx| {
+            let blobs = tx.blob_sidecar().map_or(0, |sidecar| sidecar.blobs.len() as u128);
+            let blob_gas = blobs * DATA_GAS_PER_BLOB as u128;
+            cost.checked_add((tx.gas_limit() as u128).checked_mul(gas_price)?)?
+                .checked_add(blob_gas.checked_mul(blob_basefee)?)
+        })
+    }
+
+    /// Validates the commitment request against state (historical + intermediate),
+    /// by running it through the [ValidationPipeline] of the sidecar.
     ///
     /// NOTE: This function only simulates against execution state, it does not consider
     /// timing or proposer slot targets.
@@ -232,219 +373,334 @@ impl<C: StateFetcher> ExecutionState<C> {
     pub async fn validate_request(
         &mut self,
         request: &mut CommitmentRequest,
+    ) -> Result<(), ValidationError> {
+        let limits = self.limits;
+        self.validate_request_with_limits(request, &limits).await
+    }
+
+    /// Validates the commitment request like [ExecutionState::validate_request], but against
+    /// the given limits instead of the configured ones, e.g. a raised min priority fee.
+    pub async fn validate_request_with_limits(
+        &mut self,
+        request: &mut CommitmentRequest,
+        limits: &LimitsOpts,
     ) -> Result<(), ValidationError> {
         let CommitmentRequest::Inclusion(req) = request;
 
-        let signer = req.signer().expect("Set signer");
         req.recover_signers()?;
 
-        let target_slot = req.slot;
+        // Clone the pipeline handle so that account states can be fetched
+        // while iterating over the validators.
+        let pipeline = self.validation_pipeline.clone();
+        let mut account_states_fetched = false;
+
+        for validator in pipeline.iter() {
+            if validator.requires_account_states() && !account_states_fetched {
+                self.fetch_missing_account_states(req).await?;
+                account_states_fetched = true;
+            }
+
+            let ctx = ValidationContext {
+                chain_id: self.chain_id,
+                slot: self.slot,
+                basefee: self.basefee,
+                blob_basefee: self.blob_basefee,
+                limits,
+                params: &self.validation_params,
+                block_templates: &self.block_templates,
+                account_states: &self.account_states,
+                kzg_settings: &self.kzg_settings,
+                reservations: &self.reservations,
+                proposal_attributes: &self.proposal_attributes,
+                mempool_conflicts: &self.mempool_conflicts,
+                vouchers: &self.vouchers,
+                pricing: &self.pricing,
+            };
 
-        // Validate the chain ID
-        if !req.validate_chain_id(self.chain_id) {
-            return Err(ValidationError::ChainIdMismatch);
+            if let Err(err) = validator.validate(req, &ctx) {
+                debug!(validator = validator.name(), ?err, "Commitment request rejected");
+                return Err(err);
+            }
         }
 
-        // Check if there is room for more commitments
-        if let Some(template) = self.get_block_template(target_slot) {
-            if template.transactions_len() >= self.limits.max_commitments_per_slot.get() {
-                return Err(ValidationError::MaxCommitmentsReachedForSlot(
-                    self.slot,
-                    self.limits.max_commitments_per_slot.get(),
-                ));
+        // Screen the addresses last, so that only otherwise valid requests incur
+        // the lookups of the screening
+        if let Some(screening) = &self.screening {
+            if let Err(err) = screening.check(req).await {
+                debug!(?err, "Commitment request rejected by screening");
+                return Err(err);
             }
         }
 
-        // Check if the committed gas exceeds the maximum
-        let template_committed_gas =
-            self.get_block_template(target_slot).map(|t| t.committed_gas()).unwrap_or(0);
+        Ok(())
+    }
 
-        if template_committed_gas + req.gas_limit() >= self.limits.max_committed_gas_per_slot.get()
-        {
-            return Err(ValidationError::MaxCommittedGasReachedForSlot(
-                self.slot,
-                self.limits.max_committed_gas_per_slot.get(),
-            ));
-        }
+    /// Fetches and caches the account states of the request senders that are not cached yet.
+    async fn fetch_missing_account_states(
+        &mut self,
+        req: &InclusionRequest,
+    ) -> Result<(), ValidationError> {
+        for tx in req.txs.iter() {
+            let sender = tx.sender().expect("Recovered sender");
+            if self.account_state(sender).is_some() {
+                continue;
+            }
 
-        // Check if the transaction size exceeds the maximum
-        if !req.validate_tx_size_limit(self.validation_params.max_tx_input_bytes) {
-            return Err(ValidationError::TransactionSizeTooHigh);
-        }
+            let account = match self.client.get_account_state(sender, None).await {
+                Ok(account) => account,
+                Err(err) => {
+                    return Err(ValidationError::Internal(format!(
+                        "Error fetching account state: {:?}",
+                        err
+                    )))
+                }
+            };
 
-        // Check if the transaction is a contract creation and the init code size exceeds the
-        // maximum
-        if !req.validate_init_code_limit(self.validation_params.max_init_code_byte_size) {
-            return Err(ValidationError::TransactionSizeTooHigh);
+            self.account_states.insert(*sender, account);
         }
 
-        // Check if the gas limit is higher than the maximum block gas limit
-        if req.gas_limit() > self.validation_params.block_gas_limit {
-            return Err(ValidationError::GasLimitTooHigh);
-        }
+        Ok(())
+    }
 
-        // Ensure max_priority_fee_per_gas is less than max_fee_per_gas
-        if !req.validate_max_priority_fee() {
-            return Err(ValidationError::MaxPriorityFeePerGasTooHigh);
-        }
+    /// Replaces the pipeline of validators that commitment requests must pass.
+    pub fn set_validation_pipeline(&mut self, pipeline: ValidationPipeline) {
+        self.validation_pipeline = pipeline;
+    }
 
-        // Check if the max_fee_per_gas would cover the maximum possible basefee.
-        let slot_diff = target_slot.saturating_sub(self.slot);
+    /// Returns the pipeline of validators that commitment requests must pass.
+    pub fn validation_pipeline(&self) -> &ValidationPipeline {
+        &self.validation_pipeline
+    }
 
-        // Calculate the max possible basefee given the slot diff
-        let max_basefee = calculate_max_basefee(self.basefee, slot_diff)
-            .ok_or(ValidationError::MaxBaseFeeCalcOverflow)?;
+    /// Sets the compliance screening that the addresses of commitment requests must pass,
+    /// after the validation pipeline.
+    pub fn set_screening(&mut self, screening: Option<Screening>) {
+        self.screening = screening;
+    }
 
-        debug!(%slot_diff, basefee = self.basefee, %max_basefee, "Validating basefee");
+    /// Returns the limits that commitment requests are validated against.
+    pub fn limits(&self) -> LimitsOpts {
+        self.limits
+    }
 
-        // Validate the base fee
-        if !req.validate_basefee(max_basefee) {
-            return Err(ValidationError::BaseFeeTooLow(max_basefee));
-        }
+    /// Replaces the limits that commitment requests are validated against.
+    /// Commitments already accepted are not affected.
+    pub fn set_limits(&mut self, limits: LimitsOpts) {
+        self.limits = limits;
+    }
 
-        // Ensure max_priority_fee_per_gas is greater than or equal to min_priority_fee
-        if !req.validate_min_priority_fee(max_basefee, self.limits.min_priority_fee.get()) {
-            return Err(ValidationError::MaxPriorityFeePerGasTooLow);
-        }
+    /// Returns the options of the blockspace reservations.
+    pub fn reservation_opts(&self) -> ReservationOpts {
+        self.reservations.opts()
+    }
+
+    /// Enables blockspace reservations with the given options.
+    pub fn with_reservations(mut self, opts: ReservationOpts) -> Self {
+        self.reservations = ReservationBook::new(opts);
+        self
+    }
 
-        if target_slot < self.slot {
-            debug!(%target_slot, %self.slot, "Target slot lower than current slot");
-            return Err(ValidationError::SlotTooLow(self.slot));
+    /// Requires inclusion requests to be paid with vouchers to `payee`, with the given options.
+    pub fn with_vouchers(mut self, opts: VoucherOpts, payee: Address) -> Self {
+        self.vouchers = VoucherLedger::new(opts, payee);
+        self
+    }
+
+    /// Prices inclusion commitments with the given options.
+    pub fn with_pricing(mut self, opts: PricingOpts) -> Self {
+        self.pricing = PricingEngine::new(opts);
+        self
+    }
+
+    /// Debits the payment channel of the signer of an accepted request with its voucher.
+    pub fn debit_voucher(&mut self, request: &InclusionRequest) {
+        if let (Some(signer), Some(voucher)) = (request.signer, request.voucher.as_ref()) {
+            self.vouchers.debit(signer, voucher);
         }
+    }
 
-        // Validate each transaction in the request against the account state,
-        // keeping track of the nonce and balance diffs, including:
-        // - any existing state in the account trie
-        // - any previously committed transactions
-        // - any previous transaction in the same request
-        //
-        // NOTE: it's also possible for a request to contain multiple transactions
-        // from different senders, in this case each sender will have its own nonce
-        // and balance diffs that will be applied to the account state.
-        let mut bundle_nonce_diff_map = HashMap::new();
-        let mut bundle_balance_diff_map = HashMap::new();
-        for tx in req.txs.iter() {
-            let sender = tx.sender().expect("Recovered sender");
+    /// Returns the latest unsettled voucher of each payer, to write to a settlement batch.
+    pub fn pending_voucher_settlements(&self) -> Vec<VoucherSettlement> {
+        self.vouchers.pending_settlements()
+    }
 
-            // From previous preconfirmations requests retrieve
-            // - the nonce difference from the account state.
-            // - the balance difference from the account state.
-            // - the highest slot number for which the user has requested a preconfirmation.
-            //
-            // If the templates do not exist, or this is the first request for this sender,
-            // its diffs will be zero.
-            let (nonce_diff, balance_diff, highest_slot_for_account) =
-                self.block_templates.iter().fold(
-                    (0, U256::ZERO, 0),
-                    |(nonce_diff_acc, balance_diff_acc, highest_slot), (slot, block_template)| {
-                        let (nonce_diff, balance_diff, slot) = block_template
-                            .get_diff(sender)
-                            .map(|(nonce, balance)| (nonce, balance, *slot))
-                            .unwrap_or((0, U256::ZERO, 0));
-
-                        (
-                            nonce_diff_acc + nonce_diff,
-                            balance_diff_acc.saturating_add(balance_diff),
-                            u64::max(highest_slot, slot),
-                        )
-                    },
-                );
+    /// Marks the given vouchers as settled, lifting the unsettled limit of their payers.
+    pub fn mark_vouchers_settled(&mut self, settlements: &[VoucherSettlement]) {
+        self.vouchers.mark_settled(settlements);
+    }
 
-            if target_slot < highest_slot_for_account {
-                debug!(%target_slot, %highest_slot_for_account, "There is a request for a higher slot");
-                return Err(ValidationError::SlotTooLow(highest_slot_for_account));
-            }
+    /// Reserves blockspace in the upcoming proposal slots of the request period.
+    pub fn reserve_blockspace(
+        &mut self,
+        request: &ReservationRequest,
+    ) -> Result<Reservation, ReservationError> {
+        let max_committed_gas = self.limits.max_committed_gas_per_slot.get();
+        self.reservations.reserve(request, self.slot, max_committed_gas)
+    }
 
-            trace!(?signer, nonce_diff, %balance_diff, "Applying diffs to account state");
-
-            let account_state = match self.account_state(sender).copied() {
-                Some(account) => account,
-                None => {
-                    // Fetch the account state from the client if it does not exist
-                    let account = match self.client.get_account_state(sender, None).await {
-                        Ok(account) => account,
-                        Err(err) => {
-                            return Err(ValidationError::Internal(format!(
-                                "Error fetching account state: {:?}",
-                                err
-                            )))
-                        }
-                    };
-
-                    self.account_states.insert(*sender, account);
-                    account
-                }
-            };
+    /// Holds the gas limit of an encrypted request in its target slot for its signer, until
+    /// its transactions are decrypted at the commitment deadline. The gas already committed
+    /// or reserved in the slot is not available to hold.
+    pub fn hold_encrypted_gas(
+        &mut self,
+        request: &EncryptedInclusionRequest,
+        limits: &LimitsOpts,
+    ) -> Result<(), EncryptedRequestError> {
+        let signer = request.signer.ok_or(EncryptedRequestError::MissingSigner)?;
+        if request.gas_limit == 0 {
+            return Err(EncryptedRequestError::ZeroGas);
+        }
+
+        let block_gas_limit = self
+            .proposal_attributes
+            .get(request.slot)
+            .map_or(self.validation_params.block_gas_limit, |attributes| attributes.gas_limit);
+        let committed_gas = self.block_templates.get(request.slot).map_or(0, |t| t.committed_gas());
+        let available = limits
+            .committable_gas(block_gas_limit)
+            .saturating_sub(committed_gas)
+            .saturating_sub(self.reservations.unused_reserved_gas(request.slot, None));
+        if request.gas_limit > available {
+            return Err(EncryptedRequestError::InsufficientCapacity(available));
+        }
 
-            debug!(?account_state, ?nonce_diff, ?balance_diff, "Validating transaction");
+        self.reservations.hold(request.slot, signer, request.gas_limit);
+        Ok(())
+    }
 
-            let sender_nonce_diff = bundle_nonce_diff_map.entry(sender).or_insert(0);
-            let sender_balance_diff = bundle_balance_diff_map.entry(sender).or_insert(U256::ZERO);
+    /// Records gas committed to the signer of a request in the given slot, drawn
+    /// from its reservation first.
+    pub fn record_reserved_usage(&mut self, slot: u64, signer: Address, gas: u64) {
+        self.reservations.record_usage(slot, signer, gas);
+    }
 
-            // Apply the diffs to this account according to the info fetched from the templates
-            // and the current bundle diffs for this sender.
-            let account_state_with_diffs = AccountState {
-                transaction_count: account_state
-                    .transaction_count
-                    .saturating_add(nonce_diff)
-                    .saturating_add(*sender_nonce_diff),
+    /// Releases the reservations of the given slot at its commitment deadline.
+    pub fn release_reservations(&mut self, slot: u64) -> Vec<ReservationUsage> {
+        self.reservations.release(slot)
+    }
 
-                balance: account_state
-                    .balance
-                    .saturating_sub(balance_diff)
-                    .saturating_sub(*sender_balance_diff),
+    /// Returns a snapshot of the pending template for the given slot, which is
+    /// empty if no commitments were made for it.
+    pub fn template_snapshot(&self, slot: u64) -> TemplateSnapshot {
+        let block_gas_limit = self
+            .proposal_attributes
+            .get(slot)
+            .map_or(self.validation_params.block_gas_limit, |attributes| attributes.gas_limit);
+        let max_committed_gas = self.limits.committable_gas(block_gas_limit);
+        match self.block_templates.get(slot) {
+            Some(template) => template.snapshot(slot, max_committed_gas),
+            None => BlockTemplate::default().snapshot(slot, max_committed_gas),
+        }
+    }
 
-                has_code: account_state.has_code,
-            };
+    /// Records the execution attributes of the upcoming proposal announced by a
+    /// `payload_attributes` event, so that requests for its slot are validated against
+    /// its exact basefee and gas limit. These are derived from its parent block.
+    pub async fn apply_payload_attributes(
+        &mut self,
+        event: PayloadAttributesEvent,
+    ) -> Result<(), TransportError> {
+        let data = event.data;
+        if data.proposal_slot <= self.slot {
+            return Ok(());
+        }
 
-            // Validate the transaction against the account state with existing diffs
-            validate_transaction(&account_state_with_diffs, tx)?;
+        let parent = Some(data.parent_block_number);
+        let (basefee, blob_basefee, gas_limit) = tokio::try_join!(
+            self.client.get_basefee(parent),
+            self.client.get_blob_basefee(parent),
+            self.client.get_gas_limit(parent)
+        )?;
 
-            // Check EIP-4844-specific limits
-            if let Some(transaction) = tx.as_eip4844() {
-                if let Some(template) = self.block_templates.get(&target_slot) {
-                    if template.blob_count() >= MAX_BLOBS_PER_BLOCK {
-                        return Err(ValidationError::Eip4844Limit);
-                    }
-                }
+        let attributes = ProposalAttributes::new(data, basefee, blob_basefee, gas_limit);
+        debug!(
+            slot = attributes.slot,
+            basefee,
+            blob_basefee,
+            gas_limit,
+            withdrawals = attributes.withdrawals.len(),
+            "Received proposal attributes"
+        );
 
-                let PooledTransactionsElement::BlobTransaction(ref blob_transaction) = tx.deref()
-                else {
-                    unreachable!("EIP-4844 transaction should be a blob transaction")
-                };
+        self.proposal_attributes.insert(attributes.slot, attributes);
+        Ok(())
+    }
 
-                // Calculate max possible increase in blob basefee
-                let max_blob_basefee = calculate_max_basefee(self.blob_basefee, slot_diff)
-                    .ok_or(ValidationError::MaxBaseFeeCalcOverflow)?;
+    /// Returns the execution attributes of the given proposal slot, if known.
+    pub fn proposal_attributes(&self, slot: u64) -> Option<&ProposalAttributes> {
+        self.proposal_attributes.get(slot)
+    }
 
-                debug!(%max_blob_basefee, blob_basefee = blob_transaction.transaction.max_fee_per_blob_gas, "Validating blob basefee");
-                if blob_transaction.transaction.max_fee_per_blob_gas < max_blob_basefee {
-                    return Err(ValidationError::BlobBaseFeeTooLow(max_blob_basefee));
+    /// Scans the mempool of the execution client for pending transactions competing with
+    /// the committed ones, i.e. spending the same sender nonce with a higher tip, which
+    /// would leave the committed transactions invalid if included first.
+    ///
+    /// The conflicts found replace the ones of the previous scan, and are used by the
+    /// [MempoolValidator](super::validation::MempoolValidator) to reject new commitments
+    /// building on a contested nonce.
+    pub async fn scan_mempool(&mut self) -> Result<Vec<MempoolConflict>, TransportError> {
+        let mut committed: HashMap<Address, Vec<&FullTransaction>> = HashMap::new();
+        for (_, template) in self.block_templates.iter() {
+            for constraints in template.signed_constraints_list.iter() {
+                for tx in constraints.message.transactions.iter() {
+                    if let Some(sender) = tx.sender() {
+                        committed.entry(*sender).or_default().push(tx);
+                    }
                 }
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (sender, txs) in committed {
+            let pending = self.client.get_pending_transactions(&sender).await?;
+            conflicts.extend(find_conflicts(&txs, &pending, self.basefee));
+        }
 
-                // Validate blob against KZG settings
-                transaction.validate_blob(&blob_transaction.sidecar, self.kzg_settings.get())?;
+        let previous = std::mem::take(&mut self.mempool_conflicts);
+        for conflict in conflicts.iter() {
+            if !previous.contains_key(&conflict.sender) {
+                warn!(?conflict, "Committed transaction contested by a mempool transaction");
+                ApiMetrics::increment_mempool_conflicts();
             }
 
-            // Increase the bundle nonce and balance diffs for this sender for the next iteration
-            *sender_nonce_diff += 1;
-            *sender_balance_diff += max_transaction_cost(tx);
+            // Keep the lowest contested nonce of each sender
+            let lowest =
+                self.mempool_conflicts.entry(conflict.sender).or_insert_with(|| conflict.clone());
+            if conflict.nonce < lowest.nonce {
+                *lowest = conflict.clone();
+            }
         }
 
-        Ok(())
+        Ok(conflicts)
+    }
+
+    /// Returns the hashes of the given transactions that were included on chain, according
+    /// to the receipts of the execution client.
+    pub async fn included_transactions(
+        &self,
+        hashes: &[B256],
+    ) -> Result<HashSet<B256>, TransportError> {
+        let receipts = self.client.get_receipts(hashes).await?;
+        Ok(receipts.into_iter().flatten().map(|receipt| receipt.transaction_hash).collect())
+    }
+
+    /// Tracks a commitment with a slot range, targeting the slot of the request,
+    /// so that it is rolled over to a later slot of its range if the target is missed.
+    pub fn track_ranged_commitment(&mut self, request: InclusionRequest) {
+        self.ranged_commitments.push(request);
+    }
+
+    /// Takes the commitments with a slot range that missed their target slot, with
+    /// only the transactions that were not included.
+    pub fn take_rollovers(&mut self) -> Vec<InclusionRequest> {
+        std::mem::take(&mut self.rollovers)
     }
 
     /// Commits the transaction to the target block. Initializes a new block template
     /// if one does not exist for said block number.
     /// TODO: remove `pub` modifier once `try_commit` is fully implemented.
     pub fn add_constraint(&mut self, target_slot: u64, signed_constraints: SignedConstraints) {
-        if let Some(template) = self.block_templates.get_mut(&target_slot) {
-            template.add_constraints(signed_constraints);
-        } else {
-            let mut template = BlockTemplate::default();
-            template.add_constraints(signed_constraints);
-            self.block_templates.insert(target_slot, template);
-        }
+        self.block_templates.get_or_default(target_slot).add_constraints(signed_constraints);
     }
 
     /// Updates the state corresponding to the provided block number and slot.
@@ -461,6 +717,7 @@ impl<C: StateFetcher> ExecutionState<C> {
         let update = self.client.get_state_update(accounts, block_number).await?;
         trace!(%slot, ?update, "Applying execution state update");
 
+        self.invalidate_included_elsewhere(slot).await?;
         self.apply_state_update(update);
 
         // Remove any block templates that are no longer valid
@@ -471,7 +728,8 @@ impl<C: StateFetcher> ExecutionState<C> {
 
             let mut receipts_len = 0;
             for receipt in receipts.iter().flatten() {
-                // Calculate the total tip revenue for this transaction: (effective_gas_price - basefee) * gas_used
+                // Calculate the total tip revenue for this transaction: (effective_gas_price -
+                // basefee) * gas_used
                 let tip_per_gas = receipt.effective_gas_price - self.basefee;
                 let total_tip = tip_per_gas * receipt.gas_used;
 
@@ -497,6 +755,99 @@ impl<C: StateFetcher> ExecutionState<C> {
             }
         }
 
+        // The templates of missed slots are no longer valid either
+        self.block_templates.advance(slot);
+        self.proposal_attributes.advance(slot);
+
+        self.check_ranged_commitments(slot).await
+    }
+
+    /// Removes the commitments of the templates of the slots after `slot` whose transactions
+    /// were already included in an earlier block, e.g. by another proposer, to free their
+    /// budget and avoid constraining the proposal on transactions that can't be included again.
+    ///
+    /// This must run before the templates are refreshed with the new account states, so that
+    /// the later commitments of the same senders are not invalidated by their advanced nonce.
+    async fn invalidate_included_elsewhere(&mut self, slot: u64) -> Result<(), TransportError> {
+        let hashes = self
+            .block_templates
+            .iter()
+            .filter(|(target_slot, _)| **target_slot > slot)
+            .flat_map(|(_, template)| template.transaction_hashes())
+            .collect::<Vec<_>>();
+
+        if hashes.is_empty() {
+            return Ok(());
+        }
+
+        let receipts = self.client.get_receipts(&hashes).await?;
+        let included =
+            receipts.iter().flatten().map(|r| r.transaction_hash).collect::<HashSet<_>>();
+
+        if included.is_empty() {
+            return Ok(());
+        }
+
+        for (target_slot, template) in self.block_templates.iter_mut() {
+            if *target_slot <= slot {
+                continue;
+            }
+
+            let removed = template.remove_included(&included);
+            if removed > 0 {
+                warn!(target_slot, removed, "Removed committed transactions included elsewhere");
+                ApiMetrics::increment_transactions_included_elsewhere(removed as u64);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the commitments with a slot range whose target slot is not after `slot`,
+    /// scheduling the transactions that were not included for a rollover if their range
+    /// goes beyond it.
+    async fn check_ranged_commitments(&mut self, slot: u64) -> Result<(), TransportError> {
+        let (due, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.ranged_commitments).into_iter().partition(|r| r.slot <= slot);
+        self.ranged_commitments = pending;
+
+        for (i, mut request) in due.iter().cloned().enumerate() {
+            let hashes = request.txs.iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+            let receipts = match self.client.get_receipts(&hashes).await {
+                Ok(receipts) => receipts,
+                Err(err) => {
+                    // Check the remaining commitments again at the next head
+                    self.ranged_commitments.extend(due.into_iter().skip(i));
+                    return Err(err);
+                }
+            };
+
+            let included =
+                receipts.iter().flatten().map(|r| r.transaction_hash).collect::<Vec<_>>();
+            request.txs.retain(|tx| !included.contains(tx.hash()));
+            if request.txs.is_empty() {
+                continue;
+            }
+
+            let max_slot = request.max_slot.unwrap_or(request.slot);
+            if max_slot > slot {
+                debug!(
+                    missed = request.slot,
+                    max_slot,
+                    txs = request.txs.len(),
+                    "Rolling over commitment"
+                );
+                self.rollovers.push(request);
+            } else {
+                warn!(
+                    target_slot = request.slot,
+                    max_slot,
+                    txs = request.txs.len(),
+                    "Slot range of commitment ended without inclusion"
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -541,14 +892,24 @@ impl<C: StateFetcher> ExecutionState<C> {
 
     /// Gets the block template for the given slot number.
     pub fn get_block_template(&mut self, slot: u64) -> Option<&BlockTemplate> {
-        self.block_templates.get(&slot)
+        self.block_templates.get(slot)
+    }
+
+    /// Drops the block templates of the slots up to and including the given one, whose head
+    /// was not received, e.g. because the slots were missed. Returns the number of templates
+    /// dropped.
+    pub fn expire_templates(&mut self, slot: u64) -> usize {
+        if slot <= self.block_templates.head() {
+            return 0;
+        }
+        self.block_templates.advance(slot).len()
     }
 
     /// Gets the block template for the given slot number and removes it from the cache.
     /// This should be called when we need to propose a block for the given slot,
     /// or when a new head comes in which makes an older block template useless.
     pub fn remove_block_template(&mut self, slot: u64) -> Option<BlockTemplate> {
-        self.block_templates.remove(&slot)
+        self.block_templates.remove(slot)
     }
 }
 
@@ -569,7 +930,7 @@ mod tests {
         consensus::constants::ETH_TO_WEI,
         eips::eip2718::Encodable2718,
         network::EthereumWallet,
-        primitives::{uint, Uint},
+        primitives::{uint, Uint, U256},
         providers::{network::TransactionBuilder, Provider, ProviderBuilder},
         signers::local::PrivateKeySigner,
     };
@@ -577,9 +938,10 @@ mod tests {
     use reth_primitives::constants::GWEI_TO_WEI;
 
     use crate::{
+        common::calculate_max_basefee,
         crypto::SignableBLS,
         primitives::{ConstraintsMessage, SignedConstraints},
-        state::fetcher,
+        state::{fetcher, validation::WhitelistValidator},
         test_util::{create_signed_commitment_request, default_test_transaction, launch_anvil},
     };
 
@@ -610,6 +972,43 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_inclusion_request_whitelist() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+
+        let mut state = ExecutionState::new(client.clone(), LimitsOpts::default()).await?;
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        let tx = default_test_transaction(*sender, None);
+        let mut request = create_signed_commitment_request(&[tx], sender_pk, 10).await?;
+        let signer = request.as_inclusion_request().unwrap().signer().unwrap();
+
+        state.set_validation_pipeline(
+            ValidationPipeline::default()
+                .with_validator_before_state(WhitelistValidator::new([Address::ZERO])),
+        );
+        assert!(matches!(
+            state.validate_request(&mut request).await,
+            Err(ValidationError::SignerNotWhitelisted(_))
+        ));
+
+        state.set_validation_pipeline(
+            ValidationPipeline::default()
+                .with_validator_before_state(WhitelistValidator::new([signer])),
+        );
+        assert!(state.validate_request(&mut request).await.is_ok());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_invalid_inclusion_slot() -> eyre::Result<()> {
         let _ = tracing_subscriber::fmt::try_init();
@@ -800,6 +1199,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(200000000).unwrap(), // 0.2 gwei
+            reserved_template_gas: 0,
         };
 
         let mut state = ExecutionState::new(client.clone(), limits).await?;
@@ -839,6 +1239,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(2000000000).unwrap(),
+            reserved_template_gas: 0,
         };
         let mut state = ExecutionState::new(client.clone(), limits).await?;
 
@@ -861,6 +1262,36 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_reserved_template_gas() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+
+        // Reserve all but 5M gas of the default 30M block gas limit
+        let limits = LimitsOpts { reserved_template_gas: 25_000_000, ..LimitsOpts::default() };
+        let mut state = ExecutionState::new(client.clone(), limits).await?;
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        assert_eq!(state.template_snapshot(10).remaining_gas, 5_000_000);
+
+        let tx = default_test_transaction(*sender, None).with_gas_limit(6_000_000);
+        let mut request = create_signed_commitment_request(&[tx], sender_pk, 10).await?;
+
+        assert!(matches!(
+            state.validate_request(&mut request).await,
+            Err(ValidationError::MaxCommittedGasReachedForSlot(_, 5_000_000))
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_invalid_inclusion_request_min_priority_fee() -> eyre::Result<()> {
         let anvil = launch_anvil();
@@ -870,6 +1301,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(2 * GWEI_TO_WEI as u128).unwrap(),
+            reserved_template_gas: 0,
         };
 
         let mut state = ExecutionState::new(client.clone(), limits).await?;
@@ -889,7 +1321,7 @@ mod tests {
 
         assert!(matches!(
             state.validate_request(&mut request).await,
-            Err(ValidationError::MaxPriorityFeePerGasTooLow)
+            Err(ValidationError::MaxPriorityFeePerGasTooLow(_))
         ));
 
         // Create a transaction with a max priority fee that is correct
@@ -912,6 +1344,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(2 * GWEI_TO_WEI as u128).unwrap(),
+            reserved_template_gas: 0,
         };
 
         let mut state = ExecutionState::new(client.clone(), limits).await?;
@@ -936,7 +1369,7 @@ mod tests {
 
         assert!(matches!(
             state.validate_request(&mut request).await,
-            Err(ValidationError::MaxPriorityFeePerGasTooLow)
+            Err(ValidationError::MaxPriorityFeePerGasTooLow(_))
         ));
 
         // Create a transaction with a gas price that is correct
@@ -959,6 +1392,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(2 * GWEI_TO_WEI as u128).unwrap(),
+            reserved_template_gas: 0,
         };
 
         let mut state = ExecutionState::new(client.clone(), limits).await?;
@@ -1044,6 +1478,106 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_invalidate_included_elsewhere() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let mut state = ExecutionState::new(client.clone(), LimitsOpts::default()).await?;
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        // initialize the state by updating the head once
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        let wallet: PrivateKeySigner = anvil.keys()[0].clone().into();
+        let signer: EthereumWallet = wallet.into();
+        let bls_signer = LocalSigner::random();
+        let target_slot = 10;
+
+        // commit two consecutive transactions of the same sender in separate constraints
+        let txs =
+            [default_test_transaction(*sender, None), default_test_transaction(*sender, Some(1))];
+        for tx in txs.iter().cloned() {
+            let mut request =
+                create_signed_commitment_request(&[tx], sender_pk, target_slot).await?;
+            assert!(state.validate_request(&mut request).await.is_ok());
+
+            let inclusion_request = request.as_inclusion_request().unwrap().clone();
+            let message = ConstraintsMessage::build(Default::default(), inclusion_request);
+            let signature = bls_signer.sign_commit_boost_root(message.digest()).unwrap();
+            state.add_constraint(target_slot, SignedConstraints { message, signature });
+        }
+
+        assert_eq!(state.get_block_template(target_slot).unwrap().transactions_len(), 2);
+
+        // the first transaction is included before the target slot
+        let signed = txs[0].clone().build(&signer).await?;
+        let notif = provider.send_raw_transaction(&signed.encoded_2718()).await?;
+        let receipt = notif.get_receipt().await?;
+
+        state.update_head(receipt.block_number, receipt.block_number.unwrap()).await?;
+
+        // only the included transaction is removed, the next nonce is still valid
+        let transactions = state.get_block_template(target_slot).unwrap().transactions();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].nonce(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_template_snapshot() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+
+        let limits = LimitsOpts::default();
+        let mut state = ExecutionState::new(client.clone(), limits).await?;
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        let target_slot = 10;
+        let empty = state.template_snapshot(target_slot);
+        assert!(empty.transactions.is_empty());
+        assert_eq!(empty.remaining_gas, limits.max_committed_gas_per_slot.get());
+
+        let tx = default_test_transaction(*sender, None);
+        let mut request = create_signed_commitment_request(&[tx], sender_pk, target_slot).await?;
+        let inclusion_request = request.as_inclusion_request().unwrap().clone();
+        assert!(state.validate_request(&mut request).await.is_ok());
+
+        let bls_signer = LocalSigner::random();
+        let mut message = ConstraintsMessage::build(Default::default(), inclusion_request);
+        message.top = true;
+        let signature = bls_signer.sign_commit_boost_root(message.digest()).unwrap();
+        let hash = *message.transactions[0].hash();
+        state.add_constraint(target_slot, SignedConstraints { message, signature });
+
+        let snapshot = state.template_snapshot(target_slot);
+        assert_eq!(snapshot.slot, target_slot);
+        assert_eq!(snapshot.transactions.len(), 1);
+        assert_eq!(snapshot.transactions[0].hash, hash);
+        assert_eq!(snapshot.transactions[0].sender, Some(*sender));
+        assert_eq!(snapshot.top_of_block, vec![hash]);
+        assert_eq!(
+            snapshot.committed_gas + snapshot.remaining_gas,
+            limits.max_committed_gas_per_slot.get()
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_invalidate_stale_template() -> eyre::Result<()> {
         let _ = tracing_subscriber::fmt::try_init();
@@ -1097,6 +1631,7 @@ mod tests {
             max_commitments_per_slot: NonZero::new(10).unwrap(),
             max_committed_gas_per_slot: NonZero::new(5_000_000).unwrap(),
             min_priority_fee: NonZero::new(1000000000).unwrap(),
+            reserved_template_gas: 0,
         };
         let mut state = ExecutionState::new(client.clone(), limits).await?;
 
@@ -1137,6 +1672,45 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_ranged_commitment_rollover() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+
+        let mut state = ExecutionState::new(client.clone(), LimitsOpts::default()).await?;
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        let target_slot = slot + 1;
+        let tx = default_test_transaction(*sender, None);
+        let mut request = create_signed_commitment_request(&[tx], sender_pk, target_slot).await?;
+        let mut ranged = request.as_inclusion_request().unwrap().clone();
+        ranged.max_slot = Some(target_slot + 2);
+
+        assert!(state.validate_request(&mut request).await.is_ok());
+        state.track_ranged_commitment(ranged.clone());
+
+        // The target slot is missed without the transaction, so it is rolled over
+        state.update_head(None, target_slot).await?;
+        let rollovers = state.take_rollovers();
+        assert_eq!(rollovers.len(), 1);
+        assert_eq!(rollovers[0].txs, ranged.txs);
+        assert!(state.take_rollovers().is_empty());
+
+        // Once the range is over, the commitment is dropped
+        state.track_ranged_commitment(InclusionRequest { slot: target_slot + 2, ..ranged });
+        state.update_head(None, target_slot + 2).await?;
+        assert!(state.take_rollovers().is_empty());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_valid_bundle_inclusion_request() -> eyre::Result<()> {
         let _ = tracing_subscriber::fmt::try_init();
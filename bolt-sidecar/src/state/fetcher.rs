@@ -7,7 +7,7 @@ use std::{collections::HashMap, time::Duration};
 use alloy::{
     eips::BlockNumberOrTag,
     primitives::{Address, Bytes, U256, U64},
-    rpc::types::TransactionReceipt,
+    rpc::types::{Transaction, TransactionReceipt},
     transports::TransportError,
 };
 use futures::{stream::FuturesOrdered, StreamExt};
@@ -40,6 +40,8 @@ pub trait StateFetcher {
 
     async fn get_blob_basefee(&self, block_number: Option<u64>) -> Result<u128, TransportError>;
 
+    async fn get_gas_limit(&self, block_number: Option<u64>) -> Result<u64, TransportError>;
+
     async fn get_account_state(
         &self,
         address: &Address,
@@ -48,11 +50,18 @@ pub trait StateFetcher {
 
     async fn get_chain_id(&self) -> Result<u64, TransportError>;
 
-    /// Gets the receipts for the said list of transaction hashes. IMPORTANT: order is not maintained!
+    /// Gets the receipts for the said list of transaction hashes. IMPORTANT: order is not
+    /// maintained!
     async fn get_receipts(
         &self,
         hashes: &[TxHash],
     ) -> Result<Vec<Option<TransactionReceipt>>, TransportError>;
+
+    /// Gets the pending transactions of the given sender in the mempool of the node.
+    async fn get_pending_transactions(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<Transaction>, TransportError>;
 }
 
 /// A basic state fetcher that uses an RPC client to fetch state updates.
@@ -190,6 +199,11 @@ impl StateFetcher for StateClient {
         self.client.get_blob_basefee(block_number).await
     }
 
+    async fn get_gas_limit(&self, block_number: Option<u64>) -> Result<u64, TransportError> {
+        let block = self.client.get_block(block_number, false).await?;
+        Ok(block.header.gas_limit as u64)
+    }
+
     async fn get_account_state(
         &self,
         address: &Address,
@@ -223,6 +237,13 @@ impl StateFetcher for StateClient {
     ) -> Result<Vec<Option<TransactionReceipt>>, TransportError> {
         self.client.get_receipts(hashes).await
     }
+
+    async fn get_pending_transactions(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<Transaction>, TransportError> {
+        self.client.get_pending_transactions(address).await
+    }
 }
 
 #[cfg(test)]
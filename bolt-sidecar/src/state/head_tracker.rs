@@ -1,82 +1,66 @@
 use alloy::rpc::types::beacon::events::HeadEvent;
-use beacon_api_client::Topic;
-use futures::StreamExt;
-use std::time::Duration;
-use tokio::{sync::broadcast, task::AbortHandle, time::sleep};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::AbortHandle,
+};
 use tracing::warn;
 
 use crate::BeaconClient;
 
-/// The delay between retries when attempting to reconnect to the beacon client
-const RETRY_DELAY: Duration = Duration::from_secs(1);
+use super::beacon_events::{BeaconEvent, BeaconEvents};
+
+pub use super::beacon_events::NewHeadsTopic;
 
 /// Simple actor to keep track of the most recent head of the beacon chain
 /// and broadcast updates to its subscribers.
 ///
-/// Durability: the tracker will always attempt to reconnect to the provided
-/// beacon client URL in case of disconnection or other errors.
+/// Durability: the tracker is fed by a [BeaconEvents] subscription, which will always
+/// attempt to reconnect to the provided beacon client URL in case of disconnection or
+/// other errors.
 #[derive(Debug)]
 pub struct HeadTracker {
+    /// The subscription to the beacon node events the heads are tracked from
+    events: BeaconEvents,
     /// Channel to receive updates of the "Head" beacon topic
     new_heads_rx: broadcast::Receiver<HeadEvent>,
-    /// Handle to the background task that listens for new head events.
+    /// Handle to the background task that forwards the head events.
     /// Kept to allow for graceful shutdown.
     quit: AbortHandle,
 }
 
-/// A topic for subscribing to new head events
-#[derive(Debug)]
-pub struct NewHeadsTopic;
-
-impl Topic for NewHeadsTopic {
-    const NAME: &'static str = "head";
-
-    type Data = HeadEvent;
-}
-
 impl HeadTracker {
     /// Create a new `HeadTracker` with the given beacon client HTTP URL and
     /// start listening for new head events in the background
     pub fn start(beacon_client: BeaconClient) -> Self {
         let (new_heads_tx, new_heads_rx) = broadcast::channel(32);
 
+        let events = BeaconEvents::start(beacon_client);
+        let mut events_rx = events.subscribe();
+
         let task = tokio::spawn(async move {
             loop {
-                let mut event_stream = match beacon_client.get_events::<NewHeadsTopic>().await {
-                    Ok(events) => events,
-                    Err(err) => {
-                        warn!(?err, "failed to subscribe to new heads topic, retrying...");
-                        sleep(RETRY_DELAY).await;
-                        continue;
-                    }
-                };
-
-                let event = match event_stream.next().await {
-                    Some(Ok(event)) => event,
-                    Some(Err(err)) => {
-                        warn!(?err, "error reading new head event stream, retrying...");
-                        sleep(RETRY_DELAY).await;
-                        continue;
+                match events_rx.recv().await {
+                    Ok(BeaconEvent::Head(event)) => {
+                        if let Err(err) = new_heads_tx.send(event) {
+                            warn!(?err, "failed to broadcast new head event to subscribers");
+                        }
                     }
-                    None => {
-                        warn!("new head event stream ended, retrying...");
-                        sleep(RETRY_DELAY).await;
-                        continue;
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "head tracker lagged behind the beacon events");
                     }
-                };
-
-                if let Err(err) = new_heads_tx.send(event) {
-                    warn!(?err, "failed to broadcast new head event to subscribers");
+                    Err(RecvError::Closed) => break,
                 }
             }
         });
 
-        Self { new_heads_rx, quit: task.abort_handle() }
+        Self { events, new_heads_rx, quit: task.abort_handle() }
     }
 
     /// Stop the tracker and cleanup resources
     pub fn stop(self) {
         self.quit.abort();
+        self.events.stop();
     }
 
     /// Get the next head event from the tracker
@@ -91,6 +75,12 @@ impl HeadTracker {
     pub fn subscribe_new_heads(&self) -> broadcast::Receiver<HeadEvent> {
         self.new_heads_rx.resubscribe()
     }
+
+    /// Subscribe to all the typed events of the beacon node, received after the call
+    /// to this method
+    pub fn subscribe_events(&self) -> broadcast::Receiver<BeaconEvent> {
+        self.events.subscribe()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,221 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::Path,
+};
+
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::SignedConstraints;
+
+use super::journal::{
+    check_conflicts, CommitmentJournal, JournalConflict, JournalRecord, PendingSlot,
+};
+
+/// The version of the interchange format written by the sidecar.
+pub const INTERCHANGE_FORMAT_VERSION: &str = "1";
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterchangeError {
+    #[error("failed to access the journal: {0}")]
+    Io(#[from] io::Error),
+    #[error("unsupported interchange format version {0}, expected {INTERCHANGE_FORMAT_VERSION}")]
+    UnsupportedVersion(String),
+    #[error("interchange file of chain ID {found}, expected {expected}")]
+    ChainMismatch { expected: u64, found: u64 },
+    #[error("refusing to import the interchange file: {0}")]
+    Conflict(#[from] JournalConflict),
+}
+
+/// The metadata of an interchange file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchangeMetadata {
+    /// The version of the format, see [INTERCHANGE_FORMAT_VERSION].
+    pub interchange_format_version: String,
+    /// The chain ID of the commitments.
+    pub chain_id: u64,
+}
+
+/// The commitments of a slot not resolved yet, as exchanged between sidecars.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchangeSlot {
+    /// The target slot of the commitments.
+    pub slot: u64,
+    /// The commitment keys that signed the commitments of the slot.
+    pub commitment_signers: BTreeSet<Address>,
+    /// Whether the relay acknowledged the constraints of the slot.
+    pub acknowledged: bool,
+    /// The constraints signed for the slot, in the order they were committed.
+    pub signed_constraints: Vec<SignedConstraints>,
+}
+
+/// An interchange file of the constraints signed by a sidecar and not resolved yet, to migrate
+/// them to another sidecar without signing conflicting constraints for the same slots.
+///
+/// Like the slashing protection interchange format of EIP-3076, the file is exported from the
+/// commitment journal of the old sidecar once it is stopped, and imported into the journal of
+/// the new one before it is started. The new sidecar then honors the imported commitments, and
+/// refuses the requests that would conflict with them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalInterchange {
+    /// The metadata of the file.
+    pub metadata: InterchangeMetadata,
+    /// The pending slots, by ascending slot.
+    pub data: Vec<InterchangeSlot>,
+}
+
+impl JournalInterchange {
+    /// Creates the interchange file of the pending slots of a journal.
+    pub fn new(chain_id: u64, pending: &BTreeMap<u64, PendingSlot>) -> Self {
+        let data = pending
+            .iter()
+            .map(|(slot, entry)| InterchangeSlot {
+                slot: *slot,
+                commitment_signers: entry.commitment_signers.clone(),
+                acknowledged: entry.acknowledged,
+                signed_constraints: entry.constraints.clone(),
+            })
+            .collect();
+
+        let metadata = InterchangeMetadata {
+            interchange_format_version: INTERCHANGE_FORMAT_VERSION.to_string(),
+            chain_id,
+        };
+
+        Self { metadata, data }
+    }
+
+    /// Exports the pending slots of the journal at the given path. The journal is not
+    /// compacted, so it can be exported from a running sidecar for backup.
+    pub fn export(path: &Path, chain_id: u64) -> Result<Self, InterchangeError> {
+        Ok(Self::new(chain_id, &CommitmentJournal::read_pending(path)?))
+    }
+
+    /// Imports the interchange file into the journal at the given path, creating it if it
+    /// doesn't exist, and returns the number of imported constraints. The sidecar using the
+    /// journal must be stopped.
+    ///
+    /// The import is refused as a whole if the file was exported for another chain, or if its
+    /// commitments were signed by another commitment key than the one of the sidecar or
+    /// conflict with the commitments of the journal. Constraints already in the journal are
+    /// skipped, so importing the same file twice is a no-op.
+    pub fn import(
+        self,
+        path: &Path,
+        chain_id: u64,
+        commitment_signer: Address,
+    ) -> Result<usize, InterchangeError> {
+        if self.metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+            return Err(InterchangeError::UnsupportedVersion(
+                self.metadata.interchange_format_version,
+            ));
+        }
+        if self.metadata.chain_id != chain_id {
+            return Err(InterchangeError::ChainMismatch {
+                expected: chain_id,
+                found: self.metadata.chain_id,
+            });
+        }
+
+        let (journal, mut pending) = CommitmentJournal::open(path)?;
+
+        let mut records = Vec::new();
+        for imported in self.data {
+            let slot = imported.slot;
+            if let Some(&signer) =
+                imported.commitment_signers.iter().find(|s| **s != commitment_signer)
+            {
+                return Err(JournalConflict::ForeignSigner { slot, signer }.into());
+            }
+
+            let entry = pending.entry(slot).or_default();
+            for constraints in imported.signed_constraints {
+                if entry.constraints.contains(&constraints) {
+                    continue;
+                }
+                entry.constraints.push(constraints.clone());
+                entry.commitment_signers.insert(commitment_signer);
+                records.push(JournalRecord::Committed { slot, commitment_signer, constraints });
+            }
+
+            if imported.acknowledged && !entry.acknowledged {
+                entry.acknowledged = true;
+                records.push(JournalRecord::Acknowledged { slot });
+            }
+        }
+
+        // Every pending slot is checked, as the import runs without the current slot
+        check_conflicts(&mut pending, 0, commitment_signer)?;
+
+        let imported =
+            records.iter().filter(|r| matches!(r, JournalRecord::Committed { .. })).count();
+        for record in &records {
+            journal.append(record)?;
+        }
+
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        primitives::ConstraintsMessage, signer::local::LocalSigner,
+        test_util::test_legacy_transaction,
+    };
+
+    use super::*;
+
+    fn committed(slot: u64, signer: Address) -> JournalRecord {
+        let tx = test_legacy_transaction();
+        let message =
+            ConstraintsMessage::from_transaction(LocalSigner::random().pubkey(), slot, tx);
+        JournalRecord::Committed {
+            slot,
+            commitment_signer: signer,
+            constraints: SignedConstraints { message, signature: Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_journal_interchange() {
+        let dir = std::env::temp_dir().join(format!("bolt-interchange-{}", std::process::id()));
+        let (old, new) = (dir.join("old.jsonl"), dir.join("new.jsonl"));
+        let signer = Address::repeat_byte(1);
+
+        let (journal, _) = CommitmentJournal::open(&old).unwrap();
+        for record in
+            [committed(10, signer), committed(11, signer), JournalRecord::Acknowledged { slot: 10 }]
+        {
+            journal.append(&record).unwrap();
+        }
+
+        let interchange = JournalInterchange::export(&old, 1).unwrap();
+        assert_eq!(interchange.data.len(), 2);
+
+        // The file round-trips through JSON
+        let json = serde_json::to_string(&interchange).unwrap();
+        let interchange: JournalInterchange = serde_json::from_str(&json).unwrap();
+
+        // Files of another chain, or signed by another commitment key, are refused
+        assert!(matches!(
+            interchange.clone().import(&new, 17000, signer),
+            Err(InterchangeError::ChainMismatch { expected: 17000, found: 1 })
+        ));
+        assert!(matches!(
+            interchange.clone().import(&new, 1, Address::repeat_byte(2)),
+            Err(InterchangeError::Conflict(JournalConflict::ForeignSigner { slot: 10, .. }))
+        ));
+
+        assert_eq!(interchange.clone().import(&new, 1, signer).unwrap(), 2);
+        assert_eq!(interchange.import(&new, 1, signer).unwrap(), 0);
+
+        let (_, pending) = CommitmentJournal::open(&new).unwrap();
+        assert_eq!(pending, CommitmentJournal::read_pending(&old).unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::{Address, TxHash};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::primitives::{
+    events::{CommitmentAccepted, ConstraintsSubmitted, Event, InclusionOutcome},
+    LifecycleEvent, SignedConstraints,
+};
+
+/// A record of the commitment journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalRecord {
+    /// Constraints were signed for a slot, and the commitment is about to be returned.
+    Committed {
+        /// The target slot of the constraints.
+        slot: u64,
+        /// The address of the commitment key that signed the commitment.
+        commitment_signer: Address,
+        /// The signed constraints.
+        constraints: SignedConstraints,
+    },
+    /// The relay acknowledged the constraints of a slot.
+    Acknowledged {
+        /// The slot of the constraints.
+        slot: u64,
+    },
+    /// The outcome of the commitments of a slot that passed was verified on chain.
+    Resolved {
+        /// The slot of the commitments.
+        slot: u64,
+        /// The committed transactions found on chain.
+        included: Vec<TxHash>,
+        /// The committed transactions not found on chain.
+        missed: Vec<TxHash>,
+    },
+}
+
+impl JournalRecord {
+    /// Returns the slot of the record.
+    pub fn slot(&self) -> u64 {
+        match self {
+            Self::Committed { slot, .. } |
+            Self::Acknowledged { slot } |
+            Self::Resolved { slot, .. } => *slot,
+        }
+    }
+
+    /// Returns the lifecycle event of the record, in the wire schema shared with the external
+    /// consumers of the events, with the given time in unix milliseconds.
+    pub fn to_lifecycle_event(&self, timestamp_ms: u64) -> LifecycleEvent {
+        let hashes =
+            |hashes: &[TxHash]| -> Vec<Vec<u8>> { hashes.iter().map(|h| h.to_vec()).collect() };
+
+        let event = match self {
+            Self::Committed { slot, commitment_signer, constraints } => {
+                Event::CommitmentAccepted(CommitmentAccepted {
+                    slot: *slot,
+                    commitment_signer: commitment_signer.to_vec(),
+                    constraints_pubkey: constraints.message.pubkey.to_vec(),
+                    tx_hashes: constraints
+                        .message
+                        .transactions
+                        .iter()
+                        .map(|tx| tx.hash().to_vec())
+                        .collect(),
+                    top: constraints.message.top,
+                })
+            }
+            Self::Acknowledged { slot } => {
+                Event::ConstraintsSubmitted(ConstraintsSubmitted { slot: *slot })
+            }
+            Self::Resolved { slot, included, missed } => {
+                Event::InclusionOutcome(InclusionOutcome {
+                    slot: *slot,
+                    included: hashes(included),
+                    missed: hashes(missed),
+                })
+            }
+        };
+
+        LifecycleEvent { timestamp_ms, event: Some(event) }
+    }
+}
+
+/// The commitments of a slot recorded in the journal and not resolved yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingSlot {
+    /// The signed constraints of the slot, in the order they were committed.
+    pub constraints: Vec<SignedConstraints>,
+    /// The commitment keys that signed the commitments of the slot.
+    pub commitment_signers: BTreeSet<Address>,
+    /// Whether the relay acknowledged the constraints of the slot.
+    pub acknowledged: bool,
+}
+
+impl PendingSlot {
+    /// Returns the hashes of the committed transactions of the slot.
+    pub fn transaction_hashes(&self) -> Vec<TxHash> {
+        self.constraints
+            .iter()
+            .flat_map(|c| c.message.transactions.iter().map(|tx| *tx.hash()))
+            .collect()
+    }
+}
+
+/// A journal entry that makes it unsafe to keep committing after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum JournalConflict {
+    /// Commitments were signed with another commitment key, which may sign conflicting ones.
+    #[error("slot {slot} has commitments signed by {signer}, not by the current commitment key")]
+    ForeignSigner {
+        /// The slot of the commitments.
+        slot: u64,
+        /// The commitment key that signed them.
+        signer: Address,
+    },
+    /// Two different transactions were committed with the same sender and nonce.
+    #[error("slot {slot} has conflicting commitments for nonce {nonce} of {sender}")]
+    ConflictingNonce {
+        /// The slot of the second commitment.
+        slot: u64,
+        /// The sender of the transactions.
+        sender: Address,
+        /// The nonce of the transactions.
+        nonce: u64,
+    },
+    /// The signer of a committed transaction can't be recovered.
+    #[error("slot {slot} has a committed transaction {hash} without a valid signature")]
+    UnrecoverableSender {
+        /// The slot of the commitment.
+        slot: u64,
+        /// The hash of the transaction.
+        hash: TxHash,
+    },
+}
+
+/// An append-only journal of the commitments made by the sidecar, written before each
+/// commitment is returned so that they survive a restart.
+///
+/// The journal is a file of JSON [JournalRecord]s, one per line. On open, the records are
+/// replayed into the [PendingSlot]s that are not resolved yet, and the file is compacted to
+/// the records of those slots only.
+#[derive(Debug, Clone)]
+pub struct CommitmentJournal {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl CommitmentJournal {
+    /// Opens the journal at the given path, creating it if it doesn't exist, and returns it
+    /// along with the pending slots it records.
+    ///
+    /// A truncated last line, left by a crash during a write, is skipped: the commitment it
+    /// recorded was not returned. Any other malformed line is an error.
+    pub fn open(path: &Path) -> io::Result<(Self, BTreeMap<u64, PendingSlot>)> {
+        let records = match File::open(path) {
+            Ok(file) => read_records(BufReader::new(file))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        let pending = replay(&records);
+
+        // Only keep the records of the pending slots, replacing the file atomically
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let compacted = path.with_extension("tmp");
+        let mut file = File::create(&compacted)?;
+        for record in records.iter().filter(|r| pending.contains_key(&r.slot())) {
+            write_record(&mut file, record)?;
+        }
+        file.sync_all()?;
+        fs::rename(&compacted, path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        let journal = Self { path: path.to_path_buf(), file: Arc::new(Mutex::new(file)) };
+        Ok((journal, pending))
+    }
+
+    /// Reads the pending slots of the journal at the given path without compacting it, so
+    /// that it can be read while the sidecar appends to it.
+    pub fn read_pending(path: &Path) -> io::Result<BTreeMap<u64, PendingSlot>> {
+        let records = read_records(BufReader::new(File::open(path)?))?;
+        Ok(replay(&records))
+    }
+
+    /// Returns the path of the journal.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a record to the journal, and waits for it to be written to disk.
+    pub fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        let mut file = self.file.lock().expect("journal lock");
+        write_record(&mut *file, record)?;
+        file.sync_data()
+    }
+}
+
+/// Checks the pending slots from `current_slot` on for commitments that can't be honored
+/// safely by the given commitment key after a restart, and recovers the senders of their
+/// transactions.
+pub fn check_conflicts(
+    pending: &mut BTreeMap<u64, PendingSlot>,
+    current_slot: u64,
+    commitment_signer: Address,
+) -> Result<(), JournalConflict> {
+    let mut nonces = HashMap::new();
+
+    for (&slot, entry) in pending.range_mut(current_slot..) {
+        if let Some(&signer) = entry.commitment_signers.iter().find(|s| **s != commitment_signer) {
+            return Err(JournalConflict::ForeignSigner { slot, signer });
+        }
+
+        for tx in entry.constraints.iter_mut().flat_map(|c| c.message.transactions.iter_mut()) {
+            let hash = *tx.hash();
+            let Some(sender) = tx.recover_signer() else {
+                return Err(JournalConflict::UnrecoverableSender { slot, hash });
+            };
+            tx.sender = Some(sender);
+
+            let nonce = tx.nonce();
+            match nonces.insert((sender, nonce), hash) {
+                Some(other) if other != hash => {
+                    return Err(JournalConflict::ConflictingNonce { slot, sender, nonce });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replays the records into the slots that are not resolved yet.
+fn replay(records: &[JournalRecord]) -> BTreeMap<u64, PendingSlot> {
+    let mut pending = BTreeMap::<u64, PendingSlot>::new();
+
+    for record in records {
+        match record {
+            JournalRecord::Committed { slot, commitment_signer, constraints } => {
+                let entry = pending.entry(*slot).or_default();
+                entry.constraints.push(constraints.clone());
+                entry.commitment_signers.insert(*commitment_signer);
+            }
+            JournalRecord::Acknowledged { slot } => {
+                if let Some(entry) = pending.get_mut(slot) {
+                    entry.acknowledged = true;
+                }
+            }
+            JournalRecord::Resolved { slot, .. } => {
+                pending.remove(slot);
+            }
+        }
+    }
+
+    pending
+}
+
+/// Reads the records of a journal, skipping a truncated last line.
+fn read_records(reader: impl BufRead) -> io::Result<Vec<JournalRecord>> {
+    let lines = reader.lines().collect::<io::Result<Vec<_>>>()?;
+
+    let mut records = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(err) if i + 1 == lines.len() && err.is_eof() => {
+                warn!(line = i + 1, "Skipping the truncated last record of the journal");
+            }
+            Err(err) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed journal record at line {}: {err}", i + 1),
+                ));
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Writes a record as a line of JSON.
+fn write_record(writer: &mut impl Write, record: &JournalRecord) -> io::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    writer.write_all(&line)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        primitives::ConstraintsMessage, signer::local::LocalSigner,
+        test_util::test_legacy_transaction,
+    };
+
+    use super::*;
+
+    fn committed(slot: u64, signer: Address) -> JournalRecord {
+        let tx = test_legacy_transaction();
+        let message =
+            ConstraintsMessage::from_transaction(LocalSigner::random().pubkey(), slot, tx);
+        JournalRecord::Committed {
+            slot,
+            commitment_signer: signer,
+            constraints: SignedConstraints { message, signature: Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_replay_and_compact_journal() {
+        let dir = std::env::temp_dir().join(format!("bolt-journal-{}", std::process::id()));
+        let path = dir.join("journal.jsonl");
+        let signer = Address::repeat_byte(1);
+
+        let (journal, pending) = CommitmentJournal::open(&path).unwrap();
+        assert!(pending.is_empty());
+
+        for record in [
+            committed(10, signer),
+            committed(11, signer),
+            committed(11, signer),
+            JournalRecord::Acknowledged { slot: 11 },
+            committed(12, Address::repeat_byte(2)),
+            JournalRecord::Resolved { slot: 10, included: vec![], missed: vec![] },
+        ] {
+            journal.append(&record).unwrap();
+        }
+
+        // A crash in the middle of a write leaves a truncated line
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"type":"committed","slot":13,"#).unwrap();
+        drop(file);
+
+        let (_, mut pending) = CommitmentJournal::open(&path).unwrap();
+        assert_eq!(pending.keys().copied().collect::<Vec<_>>(), [11, 12]);
+        assert_eq!(pending[&11].constraints.len(), 2);
+        assert!(pending[&11].acknowledged);
+        assert!(!pending[&12].acknowledged);
+
+        // The resolved slot and the truncated record were compacted away
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 4);
+
+        // The commitments of slot 12 were signed with another key
+        assert_eq!(
+            check_conflicts(&mut pending, 11, signer),
+            Err(JournalConflict::ForeignSigner { slot: 12, signer: Address::repeat_byte(2) })
+        );
+
+        // The same transaction can be committed twice, and its sender is recovered
+        assert_eq!(check_conflicts(&mut pending, 13, signer), Ok(()));
+        pending.remove(&12);
+        assert_eq!(check_conflicts(&mut pending, 11, signer), Ok(()));
+        assert!(pending[&11].constraints[0].message.transactions[0].sender().is_some());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
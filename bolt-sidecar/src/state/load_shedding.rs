@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use crate::config::{limits::LimitsOpts, load_shedding::LoadSheddingOpts};
+
+/// The low-value work shed under load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShedReason {
+    /// The simulation of an inclusion request, which nothing is committed to.
+    Simulation,
+    /// A commitment request paying less than the raised min priority fee.
+    LowPriorityFee,
+}
+
+impl ShedReason {
+    /// Returns the label of the reason, used in metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Simulation => "simulation",
+            Self::LowPriorityFee => "low_priority_fee",
+        }
+    }
+}
+
+/// The policy deciding when to shed load ahead of a proposal, and how much of it.
+///
+/// While load is shed, simulations are rejected right away and commitment requests must pay
+/// a multiple of the min priority fee, so that the requests worth the most are validated
+/// and signed in time for the commitment deadline.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadSheddingPolicy {
+    opts: LoadSheddingOpts,
+}
+
+impl LoadSheddingPolicy {
+    /// Creates a policy with the given options.
+    pub fn new(opts: LoadSheddingOpts) -> Self {
+        Self { opts }
+    }
+
+    /// Returns true if load must be shed, given the time left until the commitment deadline
+    /// of the next slot if the sidecar proposes it, and the number of queued requests.
+    pub fn should_shed(&self, time_to_deadline: Option<Duration>, queued: usize) -> bool {
+        self.opts.enabled() &&
+            queued >= self.opts.load_shedding_queue_threshold.get() &&
+            time_to_deadline.is_some_and(|left| left <= self.opts.window())
+    }
+
+    /// Returns the limits to validate commitment requests against while load is shed,
+    /// with the min priority fee raised by the multiplier of the policy.
+    pub fn shed_limits(&self, limits: LimitsOpts) -> LimitsOpts {
+        let min_priority_fee =
+            limits.min_priority_fee.saturating_mul(self.opts.load_shedding_fee_multiplier);
+        LimitsOpts { min_priority_fee, ..limits }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use super::*;
+
+    #[test]
+    fn test_load_shedding_policy() {
+        let opts = LoadSheddingOpts {
+            load_shedding_window_ms: 500,
+            load_shedding_queue_threshold: NonZero::new(10).unwrap(),
+            load_shedding_fee_multiplier: NonZero::new(3).unwrap(),
+        };
+        let policy = LoadSheddingPolicy::new(opts);
+        let close = Some(Duration::from_millis(200));
+
+        assert!(policy.should_shed(close, 10));
+
+        // Not enough requests are queued
+        assert!(!policy.should_shed(close, 9));
+        // The deadline is too far away, or the sidecar doesn't propose the next slot
+        assert!(!policy.should_shed(Some(Duration::from_millis(800)), 100));
+        assert!(!policy.should_shed(None, 100));
+
+        // Disabled policies never shed load
+        let disabled =
+            LoadSheddingPolicy::new(LoadSheddingOpts { load_shedding_window_ms: 0, ..opts });
+        assert!(!disabled.should_shed(close, 100));
+
+        let limits = LimitsOpts::default();
+        let shed = policy.shed_limits(limits);
+        assert_eq!(shed.min_priority_fee.get(), limits.min_priority_fee.get() * 3);
+        assert_eq!(shed.max_committed_gas_per_slot, limits.max_committed_gas_per_slot);
+    }
+}
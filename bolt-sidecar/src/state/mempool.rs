@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use alloy::{
+    consensus::TxType,
+    primitives::{Address, TxHash},
+    rpc::types::Transaction,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::FullTransaction;
+
+/// A pending transaction of the public mempool that spends the same sender nonce as a
+/// committed transaction, while paying a higher tip. Block builders will likely include
+/// it instead, leaving the committed transaction invalid at execution time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolConflict {
+    /// The sender of both transactions.
+    pub sender: Address,
+    /// The nonce spent by both transactions.
+    pub nonce: u64,
+    /// The hash of the committed transaction.
+    pub committed: TxHash,
+    /// The hash of the competing transaction.
+    pub competing: TxHash,
+}
+
+/// Returns the conflicts between the committed transactions of a sender and its pending
+/// transactions in the mempool, given the basefee to compare their tips at.
+///
+/// Pending transactions with the same nonce but a lower or equal tip are ignored, since
+/// the committed transaction wins the auction for the nonce.
+pub fn find_conflicts(
+    committed: &[&FullTransaction],
+    pending: &[Transaction],
+    basefee: u128,
+) -> Vec<MempoolConflict> {
+    let mut conflicts = Vec::new();
+
+    for tx in committed {
+        let Some(sender) = tx.sender().copied() else { continue };
+        let tip = tx.effective_tip_per_gas(basefee).unwrap_or_default();
+
+        let competing = pending.iter().find(|pending| {
+            pending.from == sender &&
+                pending.nonce == tx.nonce() &&
+                pending.hash != *tx.hash() &&
+                pending_tip_per_gas(pending, basefee) > tip
+        });
+
+        if let Some(competing) = competing {
+            conflicts.push(MempoolConflict {
+                sender,
+                nonce: tx.nonce(),
+                committed: *tx.hash(),
+                competing: competing.hash,
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// Returns the pending transactions of the public mempool to fill a locally built block with,
+/// after its committed transactions, highest tip first.
+///
+/// The transactions of each sender are taken in nonce order without gaps, and a sender is
+/// dropped at its first transaction that can't be included: one that doesn't pay the basefee,
+/// doesn't fit in the remaining gas, or carries blobs, since the mempool doesn't expose their
+/// sidecars. Senders of committed transactions are skipped so that their nonces stay valid.
+pub fn select_fill_transactions(
+    pending: Vec<Transaction>,
+    committed_senders: &HashSet<Address>,
+    basefee: u128,
+    mut gas_available: u64,
+    max_txs: usize,
+) -> Vec<Transaction> {
+    let mut by_sender: HashMap<Address, Vec<Transaction>> = HashMap::new();
+    for tx in pending {
+        if !committed_senders.contains(&tx.from) {
+            by_sender.entry(tx.from).or_default().push(tx);
+        }
+    }
+
+    let mut queues = by_sender
+        .into_values()
+        .map(|mut txs| {
+            txs.sort_by_key(|tx| tx.nonce);
+            txs.dedup_by_key(|tx| tx.nonce);
+            let first = txs[0].nonce;
+            txs.into_iter()
+                .enumerate()
+                .take_while(|(i, tx)| tx.nonce == first + *i as u64)
+                .map(|(_, tx)| tx)
+                .collect::<VecDeque<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut selected = Vec::new();
+    while selected.len() < max_txs {
+        let Some((i, _)) = queues
+            .iter()
+            .enumerate()
+            .filter_map(|(i, txs)| txs.front().map(|tx| (i, pending_tip_per_gas(tx, basefee))))
+            .max_by_key(|(_, tip)| *tip)
+        else {
+            break
+        };
+
+        let tx = queues[i].pop_front().expect("non-empty queue");
+        let max_fee = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+        let is_blob = tx.transaction_type == Some(TxType::Eip4844 as u8);
+
+        if max_fee < basefee || is_blob || tx.gas > gas_available as u128 {
+            queues.swap_remove(i);
+            continue;
+        }
+
+        gas_available -= tx.gas as u64;
+        selected.push(tx);
+    }
+
+    selected
+}
+
+/// Returns the tip per gas paid by a pending transaction at the given basefee.
+fn pending_tip_per_gas(tx: &Transaction, basefee: u128) -> u128 {
+    match (tx.max_fee_per_gas, tx.gas_price) {
+        (Some(max_fee), _) => {
+            let max_tip = max_fee.saturating_sub(basefee);
+            tx.max_priority_fee_per_gas.map_or(max_tip, |tip| tip.min(max_tip))
+        }
+        (None, Some(gas_price)) => gas_price.saturating_sub(basefee),
+        (None, None) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner};
+
+    use crate::test_util::{create_signed_commitment_request, default_test_transaction};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_find_conflicts() -> eyre::Result<()> {
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let sender = PrivateKeySigner::from(sk.clone()).address();
+
+        let tx = default_test_transaction(sender, None);
+        let request = create_signed_commitment_request(&[tx], &sk, 10).await?;
+        let committed = &request.as_inclusion_request().unwrap().txs[0];
+
+        let pending = |priority_fee: u128| Transaction {
+            hash: TxHash::repeat_byte(0xff),
+            from: sender,
+            nonce: 0,
+            max_fee_per_gas: Some(20_000_000_000),
+            max_priority_fee_per_gas: Some(priority_fee),
+            ..Default::default()
+        };
+
+        // The committed transaction tips 1 gwei
+        let basefee = 1_000_000_000;
+        assert!(find_conflicts(&[committed], &[pending(1_000_000_000)], basefee).is_empty());
+
+        let conflicts = find_conflicts(&[committed], &[pending(2_000_000_000)], basefee);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].nonce, 0);
+        assert_eq!(conflicts[0].committed, *committed.hash());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_fill_transactions() {
+        let (alice, bob, carol) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+
+        let pending = |from: Address, nonce: u64, priority_fee: u128| Transaction {
+            hash: TxHash::with_last_byte(nonce as u8),
+            from,
+            nonce,
+            gas: 21_000,
+            max_fee_per_gas: Some(20_000_000_000),
+            max_priority_fee_per_gas: Some(priority_fee),
+            ..Default::default()
+        };
+
+        let basefee = 1_000_000_000;
+        let pool = vec![
+            pending(alice, 1, 1_000_000_000),
+            pending(alice, 0, 2_000_000_000),
+            pending(alice, 3, 5_000_000_000),
+            pending(bob, 0, 3_000_000_000),
+            pending(carol, 0, 4_000_000_000),
+        ];
+
+        // Carol has committed transactions, and alice's nonce 3 follows a gap
+        let committed = HashSet::from([carol]);
+        let selected = select_fill_transactions(pool.clone(), &committed, basefee, 1_000_000, 10);
+        let order = selected.iter().map(|tx| (tx.from, tx.nonce)).collect::<Vec<_>>();
+        assert_eq!(order, vec![(bob, 0), (alice, 0), (alice, 1)]);
+
+        // Only two transactions fit in the remaining gas
+        let selected = select_fill_transactions(pool, &committed, basefee, 50_000, 10);
+        assert_eq!(selected.len(), 2);
+    }
+}
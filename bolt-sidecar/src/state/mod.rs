@@ -14,14 +14,101 @@ use tokio::time::Sleep;
 mod execution;
 pub use execution::{ExecutionState, ValidationError};
 
+/// Pluggable validation pipeline that commitment requests must pass.
+pub mod validation;
+pub use validation::{CommitmentValidator, ValidationContext, ValidationPipeline};
+
+/// The execution attributes of upcoming proposals, from the beacon node.
+pub mod attributes;
+pub use attributes::ProposalAttributes;
+
+/// In-memory state scoped to slots, torn down as the chain advances.
+pub mod slot_scoped;
+pub use slot_scoped::SlotScoped;
+
+/// Blockspace reservations enforced when allocating template space.
+pub mod reservations;
+pub use reservations::{ReservationBook, ReservationError};
+
+/// Detection of mempool transactions competing with committed ones, and selection of
+/// the mempool transactions to fill locally built blocks with.
+pub mod mempool;
+pub use mempool::MempoolConflict;
+
+/// Payment channels debited with the vouchers of inclusion requests.
+pub mod vouchers;
+pub use vouchers::{VoucherError, VoucherLedger, VoucherSettlement};
+
+/// Retention and compaction of the files written by the sidecar.
+pub mod retention;
+pub use retention::{CompactionReport, RetentionPolicy};
+
+/// Journal of the commitments made, reconciled against the chain on startup.
+pub mod journal;
+pub use journal::{CommitmentJournal, JournalConflict, JournalRecord, PendingSlot};
+
+/// Interchange format of the journal, to migrate commitments between sidecars.
+pub mod interchange;
+pub use interchange::{InterchangeError, JournalInterchange};
+
+/// Pricing of inclusion commitments by the committed gas of their slot.
+pub mod pricing;
+pub use pricing::{PriceQuote, PricingEngine};
+
+/// Shedding of low-value work under load ahead of a proposal.
+pub mod load_shedding;
+pub use load_shedding::{LoadSheddingPolicy, ShedReason};
+
+/// History of the commitment keys, served to the verifiers of past commitments.
+pub mod commitment_keys;
+pub use commitment_keys::{CommitmentKeyHistory, CommitmentKeyRecord, CommitmentKeys};
+
+/// Statuses of the commitments made, served to their requesters.
+pub mod commitment_status;
+pub use commitment_status::{CommitmentState, CommitmentStatus, CommitmentStatuses};
+
+/// Auctions of the top-of-block bundle of the upcoming proposal slots.
+pub mod tob_auction;
+pub use tob_auction::{TobAuction, TobBid};
+
+/// Encrypted requests committed to, waiting for their decryption keys.
+pub mod encrypted;
+pub use encrypted::{
+    DeadlineEncrypted, EncryptedPool, EncryptedRequestError, PendingEncrypted, RevealedEncrypted,
+};
+
+/// Operator policies on the transactions committed to.
+pub mod tx_policy;
+pub use tx_policy::{TxPolicy, TxPolicyViolation};
+
+/// Pluggable compliance screening of the addresses of commitment requests.
+pub mod screening;
+pub use screening::{AddressScreener, Screening, ScreeningVerdict};
+
+/// Targeting of the delegation of each upcoming proposal slot to a single delegatee.
+pub mod delegation_targeting;
+pub use delegation_targeting::{DelegationSchedule, DelegationTargeting, SlotAssignment};
+
+/// Signed attestations of the availability of the sidecar in its upcoming proposal slots.
+pub mod availability;
+pub use availability::AvailabilityBoard;
+
 /// Module to fetch state from the Execution layer.
 pub mod fetcher;
 pub use fetcher::StateClient;
 
+/// The commitment deadlines of the slots, scheduled on the slot clock.
+pub mod deadline_schedule;
+pub use deadline_schedule::DeadlineSchedule;
+
 /// Module to track the consensus state.
 pub mod consensus;
 pub use consensus::ConsensusState;
 
+/// Module to subscribe to the event stream of the beacon node.
+pub mod beacon_events;
+pub use beacon_events::{BeaconEvent, BeaconEvents};
+
 /// Module to track the head of the chain.
 pub mod head_tracker;
 pub use head_tracker::HeadTracker;
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::pricing::{MarginCurve, PricingOpts};
+
+/// The steepness of the [MarginCurve::Exponential] curve.
+const EXPONENTIAL_STEEPNESS: f64 = 4.0;
+
+/// The min priority fee quoted for an inclusion commitment in a slot, returned to the clients
+/// of underpriced requests so that they can retry with a higher tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceQuote {
+    /// The target slot of the request.
+    pub slot: u64,
+    /// The min priority fee per gas of the transactions of the request, in wei.
+    pub min_priority_fee: u128,
+    /// The max basefee of the target slot the quote is made at, in wei.
+    pub max_basefee: u128,
+    /// The gas already committed in the target slot.
+    pub committed_gas: u64,
+    /// The gas that can be committed in the target slot.
+    pub max_committed_gas: u64,
+}
+
+/// Computes the min priority fee of inclusion commitments from the committed gas of their
+/// target slot, its max basefee and the margins of the operator.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PricingEngine {
+    opts: PricingOpts,
+}
+
+impl PricingEngine {
+    /// Creates an engine with the given options.
+    pub fn new(opts: PricingOpts) -> Self {
+        Self { opts }
+    }
+
+    /// Returns the options of the engine.
+    pub fn opts(&self) -> &PricingOpts {
+        &self.opts
+    }
+
+    /// Quotes the min priority fee of a request in the given slot, where `base_priority_fee`
+    /// is the min priority fee of the sidecar, possibly raised under load.
+    pub fn quote(
+        &self,
+        slot: u64,
+        base_priority_fee: u128,
+        max_basefee: u128,
+        committed_gas: u64,
+        max_committed_gas: u64,
+    ) -> PriceQuote {
+        let basefee_margin =
+            max_basefee.saturating_mul(self.opts.pricing_basefee_margin_bps as u128) / 10_000;
+
+        let utilization = if max_committed_gas == 0 {
+            1.0
+        } else {
+            (committed_gas as f64 / max_committed_gas as f64).min(1.0)
+        };
+        let premium = (self.opts.pricing_max_premium as f64 * self.curve(utilization)) as u128;
+
+        PriceQuote {
+            slot,
+            min_priority_fee: base_priority_fee
+                .saturating_add(basefee_margin)
+                .saturating_add(premium),
+            max_basefee,
+            committed_gas,
+            max_committed_gas,
+        }
+    }
+
+    /// Returns the share of the max premium charged at the given utilization, between 0 and 1.
+    fn curve(&self, utilization: f64) -> f64 {
+        match self.opts.pricing_curve {
+            MarginCurve::Flat => 0.0,
+            MarginCurve::Linear => utilization,
+            MarginCurve::Quadratic => utilization * utilization,
+            MarginCurve::Exponential => {
+                (EXPONENTIAL_STEEPNESS * utilization).exp_m1() / EXPONENTIAL_STEEPNESS.exp_m1()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn engine(curve: MarginCurve) -> PricingEngine {
+        PricingEngine::new(PricingOpts {
+            pricing_curve: curve,
+            pricing_max_premium: 10 * GWEI,
+            pricing_basefee_margin_bps: 1_000,
+        })
+    }
+
+    #[test]
+    fn test_pricing_curves() {
+        // An empty slot only pays the basefee margin of 10% on top of the min priority fee
+        for curve in [MarginCurve::Flat, MarginCurve::Linear, MarginCurve::Exponential] {
+            let quote = engine(curve).quote(10, GWEI, 20 * GWEI, 0, 1_000);
+            assert_eq!(quote.min_priority_fee, 3 * GWEI);
+        }
+
+        // A half-full slot
+        let quote = |curve| engine(curve).quote(10, GWEI, 20 * GWEI, 500, 1_000).min_priority_fee;
+        assert_eq!(quote(MarginCurve::Flat), 3 * GWEI);
+        assert_eq!(quote(MarginCurve::Linear), 8 * GWEI);
+        assert_eq!(quote(MarginCurve::Quadratic), 5_500_000_000);
+        assert!(quote(MarginCurve::Exponential) < quote(MarginCurve::Quadratic));
+
+        // A full slot pays the whole premium on every curve but the flat one
+        for curve in [MarginCurve::Linear, MarginCurve::Quadratic, MarginCurve::Exponential] {
+            let quote = engine(curve).quote(10, GWEI, 20 * GWEI, 1_000, 1_000);
+            assert_eq!(quote.min_priority_fee, 13 * GWEI);
+        }
+    }
+
+    #[test]
+    fn test_pricing_disabled() {
+        let engine = PricingEngine::default();
+        assert!(!engine.opts().enabled());
+
+        let quote = engine.quote(10, GWEI, 20 * GWEI, 900, 1_000);
+        assert_eq!(quote.min_priority_fee, GWEI);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
+use thiserror::Error;
+
+use crate::{
+    config::ReservationOpts,
+    primitives::{Reservation, ReservationRequest, Slot},
+};
+
+use super::SlotScoped;
+
+/// Possible errors when reserving blockspace.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReservationError {
+    /// Reservations are disabled on this sidecar.
+    #[error("Blockspace reservations are disabled")]
+    Disabled,
+    /// The request has no subscriber.
+    #[error("Missing reservation subscriber")]
+    MissingSubscriber,
+    /// The reserved gas is zero.
+    #[error("The reserved gas must be greater than zero")]
+    ZeroGas,
+    /// The period ends before it starts, or starts in the past.
+    #[error("Invalid reservation period: slots {0} to {1}")]
+    InvalidPeriod(Slot, Slot),
+    /// The period is longer than allowed.
+    #[error("The reservation period of {0} slots exceeds the maximum of {1}")]
+    PeriodTooLong(u64, u64),
+    /// Not enough gas is left to reserve over the period.
+    #[error("Not enough gas left to reserve, only {0} available over the period")]
+    InsufficientCapacity(u64),
+}
+
+/// The usage of a reservation in a slot, reported when it is released.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservationUsage {
+    /// The subscriber of the reservation.
+    pub subscriber: Address,
+    /// The gas reserved in the slot.
+    pub reserved: u64,
+    /// The gas committed to the subscriber in the slot.
+    pub used: u64,
+    /// The price charged for the slot.
+    pub price: U256,
+}
+
+/// The blockspace reservations accepted by the sidecar, along with the gas
+/// committed to their subscribers in every upcoming slot.
+///
+/// The unused gas of a reservation is withheld from other requests until the
+/// commitment deadline of the slot, when it is released.
+#[derive(Debug, Default)]
+pub struct ReservationBook {
+    /// The reservation options.
+    opts: ReservationOpts,
+    /// The accepted reservations, which are pruned once their period ends.
+    reservations: Vec<Reservation>,
+    /// The gas committed to each subscriber, by slot.
+    used: SlotScoped<HashMap<Address, u64>>,
+}
+
+impl ReservationBook {
+    /// Creates an empty book with the given options.
+    pub fn new(opts: ReservationOpts) -> Self {
+        Self { opts, ..Default::default() }
+    }
+
+    /// Returns the reservation options.
+    pub fn opts(&self) -> ReservationOpts {
+        self.opts
+    }
+
+    /// Returns the reservations that are not released yet.
+    pub fn reservations(&self) -> &[Reservation] {
+        &self.reservations
+    }
+
+    /// Accepts the request if there is enough gas left to reserve in every slot of
+    /// its period, out of the given max committed gas per slot.
+    pub fn reserve(
+        &mut self,
+        request: &ReservationRequest,
+        current_slot: Slot,
+        max_committed_gas: u64,
+    ) -> Result<Reservation, ReservationError> {
+        if !self.opts.enabled() {
+            return Err(ReservationError::Disabled);
+        }
+
+        let subscriber = request.signer.ok_or(ReservationError::MissingSubscriber)?;
+        if request.gas_per_slot == 0 {
+            return Err(ReservationError::ZeroGas);
+        }
+
+        let (start, end) = (request.start_slot, request.end_slot);
+        if end < start || start <= current_slot {
+            return Err(ReservationError::InvalidPeriod(start, end));
+        }
+
+        if request.period() > self.opts.max_reservation_slots {
+            return Err(ReservationError::PeriodTooLong(
+                request.period(),
+                self.opts.max_reservation_slots,
+            ));
+        }
+
+        let capacity = self.opts.max_reserved_gas_per_slot.min(max_committed_gas);
+        let available = capacity.saturating_sub(self.peak_reserved_gas(start, end));
+        if request.gas_per_slot > available {
+            return Err(ReservationError::InsufficientCapacity(available));
+        }
+
+        let price_per_slot =
+            U256::from(request.gas_per_slot) * U256::from(self.opts.reservation_price_per_gas);
+        let reservation = Reservation {
+            subscriber,
+            gas_per_slot: request.gas_per_slot,
+            start_slot: start,
+            end_slot: end,
+            price_per_slot,
+        };
+
+        self.reservations.push(reservation.clone());
+        Ok(reservation)
+    }
+
+    /// Holds gas for the subscriber in a single slot, free of charge, e.g. for the transactions
+    /// of an encrypted request until they are decrypted. Unlike [ReservationBook::reserve], the
+    /// capacity is checked by the caller and holds are accepted even if reservations are
+    /// disabled.
+    pub fn hold(&mut self, slot: Slot, subscriber: Address, gas: u64) {
+        self.reservations.push(Reservation {
+            subscriber,
+            gas_per_slot: gas,
+            start_slot: slot,
+            end_slot: slot,
+            price_per_slot: U256::ZERO,
+        });
+    }
+
+    /// Returns the gas reserved for the subscriber in the given slot.
+    pub fn reserved_gas(&self, slot: Slot, subscriber: &Address) -> u64 {
+        self.active(slot).filter(|r| &r.subscriber == subscriber).map(|r| r.gas_per_slot).sum()
+    }
+
+    /// Returns the reserved gas that is not used yet in the given slot by subscribers
+    /// other than `except`, which is unavailable to the requests of `except`.
+    pub fn unused_reserved_gas(&self, slot: Slot, except: Option<&Address>) -> u64 {
+        let mut reserved = HashMap::<Address, u64>::new();
+        for reservation in self.active(slot).filter(|r| Some(&r.subscriber) != except) {
+            *reserved.entry(reservation.subscriber).or_default() += reservation.gas_per_slot;
+        }
+
+        let used = self.used.get(slot);
+        reserved
+            .into_iter()
+            .map(|(subscriber, gas)| {
+                let used = used.and_then(|used| used.get(&subscriber)).copied().unwrap_or(0);
+                gas.saturating_sub(used)
+            })
+            .sum()
+    }
+
+    /// Records gas committed to a subscriber in the given slot. Gas committed to
+    /// signers without a reservation in the slot is not tracked.
+    pub fn record_usage(&mut self, slot: Slot, subscriber: Address, gas: u64) {
+        if self.reserved_gas(slot, &subscriber) > 0 {
+            *self.used.get_or_default(slot).entry(subscriber).or_default() += gas;
+        }
+    }
+
+    /// Releases the reservations of the given slot, returning their usage so that
+    /// the slot can be charged to the subscribers, and prunes the ended reservations.
+    pub fn release(&mut self, slot: Slot) -> Vec<ReservationUsage> {
+        let used = self.used.advance(slot).remove(&slot).unwrap_or_default();
+        let usage = self
+            .active(slot)
+            .map(|r| ReservationUsage {
+                subscriber: r.subscriber,
+                reserved: r.gas_per_slot,
+                used: used.get(&r.subscriber).copied().unwrap_or(0).min(r.gas_per_slot),
+                price: r.price_per_slot,
+            })
+            .collect();
+
+        self.reservations.retain(|r| r.end_slot > slot);
+        usage
+    }
+
+    fn active(&self, slot: Slot) -> impl Iterator<Item = &Reservation> {
+        self.reservations.iter().filter(move |r| r.is_active(slot))
+    }
+
+    /// Returns the highest total of reserved gas over the slots between `start` and `end`.
+    /// It can only increase at the start of a reservation, so only those slots are checked.
+    fn peak_reserved_gas(&self, start: Slot, end: Slot) -> u64 {
+        let starts =
+            self.reservations.iter().map(|r| r.start_slot).filter(|s| (start..=end).contains(s));
+        std::iter::once(start)
+            .chain(starts)
+            .map(|slot| self.active(slot).map(|r| r.gas_per_slot).sum::<u64>())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(
+        signer: Address,
+        gas_per_slot: u64,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> ReservationRequest {
+        ReservationRequest { gas_per_slot, start_slot, end_slot, signer: Some(signer) }
+    }
+
+    #[test]
+    fn test_reserve_and_release() {
+        let opts = ReservationOpts { max_reserved_gas_per_slot: 1_000_000, ..Default::default() };
+        let mut book = ReservationBook::new(opts);
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        assert_eq!(
+            ReservationBook::default().reserve(&request(alice, 1, 10, 20), 0, 30_000_000),
+            Err(ReservationError::Disabled)
+        );
+        assert_eq!(
+            book.reserve(&request(alice, 1, 10, 20), 10, 30_000_000),
+            Err(ReservationError::InvalidPeriod(10, 20))
+        );
+
+        let reservation = book.reserve(&request(alice, 600_000, 10, 20), 5, 30_000_000).unwrap();
+        assert_eq!(reservation.price_per_slot, U256::from(600_000u64 * 1_000_000_000));
+
+        // The periods overlap from slot 15, where only 400k gas is left
+        assert_eq!(
+            book.reserve(&request(bob, 500_000, 15, 25), 5, 30_000_000),
+            Err(ReservationError::InsufficientCapacity(400_000))
+        );
+        book.reserve(&request(bob, 400_000, 15, 25), 5, 30_000_000).unwrap();
+
+        // The unused gas of the others is withheld from each subscriber
+        book.record_usage(15, alice, 100_000);
+        assert_eq!(book.unused_reserved_gas(15, Some(&bob)), 500_000);
+        assert_eq!(book.unused_reserved_gas(15, Some(&alice)), 400_000);
+        assert_eq!(book.unused_reserved_gas(15, None), 900_000);
+        assert_eq!(book.unused_reserved_gas(12, Some(&alice)), 0);
+
+        let usage = book.release(15);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].used, 100_000);
+        assert_eq!(usage[1].used, 0);
+
+        // Reservations are pruned once their period ends
+        book.release(20);
+        assert_eq!(book.reservations().len(), 1);
+        assert_eq!(book.reserved_gas(21, &bob), 400_000);
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The subdirectory in which the files compacted by a [RetentionPolicy] are archived.
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// A retention policy for the timestamped files that the sidecar writes to a directory,
+/// named `<prefix>-<unix timestamp>.json`, e.g. the voucher settlement batches.
+///
+/// Files older than the retention period are compacted into a single archive in the
+/// [ARCHIVE_DIR] subdirectory, then removed, so that the directory does not grow unboundedly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// The directory of the files.
+    pub dir: PathBuf,
+    /// The prefix of the names of the files.
+    pub prefix: &'static str,
+    /// How long the files are kept before being archived.
+    pub retain: Duration,
+}
+
+/// The outcome of a compaction by a [RetentionPolicy].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// The number of files compacted into the archive.
+    pub compacted: usize,
+    /// The archive the files were compacted into, if any.
+    pub archive: Option<PathBuf>,
+}
+
+impl RetentionPolicy {
+    /// Compacts the files older than the retention period at `now` into a single archive,
+    /// keyed by file name, and removes them. The files are only removed once the archive
+    /// is written.
+    pub fn compact(&self, now: SystemTime) -> io::Result<CompactionReport> {
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let cutoff = now.saturating_sub(self.retain.as_secs());
+
+        let mut expired = BTreeMap::new();
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            match self.timestamp(&path) {
+                Some(timestamp) if timestamp < cutoff => {
+                    expired.insert(timestamp, path);
+                }
+                _ => {}
+            }
+        }
+
+        let (Some(first), Some(last)) = (expired.keys().next(), expired.keys().last()) else {
+            return Ok(Default::default());
+        };
+
+        let mut contents = BTreeMap::new();
+        for path in expired.values() {
+            let file: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            contents.insert(name, file);
+        }
+
+        let archive_dir = self.dir.join(ARCHIVE_DIR);
+        let archive = archive_dir.join(format!("{}-{first}-{last}.json", self.prefix));
+        fs::create_dir_all(&archive_dir)?;
+        fs::write(&archive, serde_json::to_vec(&contents)?)?;
+
+        for path in expired.values() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(CompactionReport { compacted: expired.len(), archive: Some(archive) })
+    }
+
+    /// Returns the timestamp of a file subject to the policy, if it is one.
+    fn timestamp(&self, path: &Path) -> Option<u64> {
+        let name = path.file_name()?.to_str()?;
+        name.strip_prefix(self.prefix)?.strip_prefix('-')?.strip_suffix(".json")?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_expired_files() {
+        let dir = std::env::temp_dir().join(format!("bolt-retention-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let policy = RetentionPolicy {
+            dir: dir.clone(),
+            prefix: "vouchers",
+            retain: Duration::from_secs(100),
+        };
+        for timestamp in [1_000, 1_050, 1_950] {
+            fs::write(dir.join(format!("vouchers-{timestamp}.json")), "[]").unwrap();
+        }
+        fs::write(dir.join("other-1000.json"), "[]").unwrap();
+
+        let now = UNIX_EPOCH + Duration::from_secs(2_000);
+        let report = policy.compact(now).unwrap();
+        assert_eq!(report.compacted, 2);
+
+        let archive = report.archive.unwrap();
+        assert_eq!(archive, dir.join(ARCHIVE_DIR).join("vouchers-1000-1050.json"));
+        let archived: BTreeMap<String, serde_json::Value> =
+            serde_json::from_str(&fs::read_to_string(archive).unwrap()).unwrap();
+        assert_eq!(archived.len(), 2);
+
+        // Recent and unrelated files are kept, and nothing is left to compact
+        assert!(dir.join("vouchers-1950.json").exists());
+        assert!(dir.join("other-1000.json").exists());
+        assert!(!dir.join("vouchers-1000.json").exists());
+        assert_eq!(policy.compact(now).unwrap(), CompactionReport::default());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
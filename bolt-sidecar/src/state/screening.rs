@@ -0,0 +1,334 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::Address;
+use parking_lot::Mutex;
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::{
+    config::{ScreeningFailMode, ScreeningOpts},
+    primitives::{InclusionRequest, TransactionExt},
+    telemetry::ApiMetrics,
+};
+
+use super::ValidationError;
+
+/// The result of the screening of an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreeningVerdict {
+    /// The address is not flagged.
+    Clear,
+    /// The address is flagged, with the label of the list or category that flagged it.
+    Flagged(String),
+}
+
+/// An error while screening an address, e.g. an unavailable screening service.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct ScreeningError(pub String);
+
+/// A source of compliance verdicts on addresses, such as an operator-provided list or
+/// an external screening service.
+///
+/// The sidecar screens the addresses of every commitment request with a single screener,
+/// through a cache. Downstream forks can implement this trait to plug their own provider.
+#[async_trait::async_trait]
+pub trait AddressScreener: fmt::Debug + Send + Sync {
+    /// The name of the screener, used in logs, metrics and rejections.
+    fn name(&self) -> &'static str;
+
+    /// Screens a single address.
+    async fn screen(&self, address: Address) -> Result<ScreeningVerdict, ScreeningError>;
+}
+
+/// Screens addresses against a fixed list of flagged addresses.
+#[derive(Debug, Clone, Default)]
+pub struct ListScreener {
+    flagged: HashSet<Address>,
+}
+
+impl ListScreener {
+    /// Creates a screener flagging the given addresses.
+    pub fn new(flagged: impl IntoIterator<Item = Address>) -> Self {
+        Self { flagged: flagged.into_iter().collect() }
+    }
+
+    /// Reads the flagged addresses from a file, one per line. Empty lines and lines starting
+    /// with `#` are ignored.
+    pub fn from_file(path: &Path) -> eyre::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let flagged = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                Address::from_str(line).map_err(|err| {
+                    eyre::eyre!("Invalid address {line} in {}: {err}", path.display())
+                })
+            })
+            .collect::<eyre::Result<HashSet<_>>>()?;
+
+        Ok(Self { flagged })
+    }
+}
+
+#[async_trait::async_trait]
+impl AddressScreener for ListScreener {
+    fn name(&self) -> &'static str {
+        "list"
+    }
+
+    async fn screen(&self, address: Address) -> Result<ScreeningVerdict, ScreeningError> {
+        if self.flagged.contains(&address) {
+            Ok(ScreeningVerdict::Flagged("screening list".to_string()))
+        } else {
+            Ok(ScreeningVerdict::Clear)
+        }
+    }
+}
+
+/// The response of a screening service to `GET <url>/<address>`.
+#[derive(Debug, Deserialize)]
+struct ScreeningResponse {
+    flagged: bool,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// Screens addresses with an external service, queried with `GET <url>/<address>`.
+#[derive(Debug, Clone)]
+pub struct HttpScreener {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl HttpScreener {
+    /// Creates a screener querying the service at the given URL.
+    pub fn new(url: Url) -> Self {
+        Self { url, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl AddressScreener for HttpScreener {
+    fn name(&self) -> &'static str {
+        "service"
+    }
+
+    async fn screen(&self, address: Address) -> Result<ScreeningVerdict, ScreeningError> {
+        let url = format!("{}/{address}", self.url.as_str().trim_end_matches('/'));
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| ScreeningError(err.to_string()))?;
+
+        let body = response.bytes().await.map_err(|err| ScreeningError(err.to_string()))?;
+        let response: ScreeningResponse =
+            serde_json::from_slice(&body).map_err(|err| ScreeningError(err.to_string()))?;
+
+        Ok(match response.flagged {
+            true => ScreeningVerdict::Flagged(response.label.unwrap_or_else(|| "flagged".into())),
+            false => ScreeningVerdict::Clear,
+        })
+    }
+}
+
+/// The compliance screening of the addresses of commitment requests: their signer, and the
+/// senders and recipients of their transactions.
+///
+/// Verdicts are cached for a while. Addresses that can't be screened are not cached, and
+/// their request is rejected or accepted according to the [ScreeningFailMode].
+#[derive(Debug, Clone)]
+pub struct Screening {
+    screener: Arc<dyn AddressScreener>,
+    fail_mode: ScreeningFailMode,
+    cache_ttl: Duration,
+    timeout: Duration,
+    cache: Arc<Mutex<HashMap<Address, (ScreeningVerdict, Instant)>>>,
+}
+
+impl Screening {
+    /// Creates the screening of the given screener, configured by the options.
+    pub fn new(screener: Arc<dyn AddressScreener>, opts: &ScreeningOpts) -> Self {
+        Self {
+            screener,
+            fail_mode: opts.screening_fail_mode,
+            cache_ttl: Duration::from_secs(opts.screening_cache_ttl_secs),
+            timeout: Duration::from_millis(opts.screening_timeout_ms),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Creates the screening configured by the options, if enabled.
+    pub fn from_opts(opts: &ScreeningOpts) -> eyre::Result<Option<Self>> {
+        let screener: Arc<dyn AddressScreener> =
+            match (&opts.screening_list_path, &opts.screening_url) {
+                (Some(path), _) => Arc::new(ListScreener::from_file(path)?),
+                (None, Some(url)) => Arc::new(HttpScreener::new(url.clone())),
+                (None, None) => return Ok(None),
+            };
+
+        Ok(Some(Self::new(screener, opts)))
+    }
+
+    /// Screens every address of the request, rejecting it if any is flagged, or if any can't
+    /// be screened and the screening fails closed.
+    pub async fn check(&self, request: &InclusionRequest) -> Result<(), ValidationError> {
+        let mut addresses = Vec::with_capacity(1 + 2 * request.txs.len());
+        addresses.extend(request.signer());
+        for tx in request.txs.iter() {
+            addresses.extend(tx.sender().copied());
+            addresses.extend(tx.tx_kind().to().copied());
+        }
+
+        let mut seen = HashSet::new();
+        for address in addresses.into_iter().filter(|address| seen.insert(*address)) {
+            match self.verdict(address).await {
+                Ok(ScreeningVerdict::Clear) => {}
+                Ok(ScreeningVerdict::Flagged(label)) => {
+                    return Err(ValidationError::Screened {
+                        address,
+                        screener: self.screener.name(),
+                        label,
+                    });
+                }
+                Err(err) if self.fail_mode == ScreeningFailMode::Open => {
+                    warn!(%address, screener = self.screener.name(), %err, "Failed to screen address, failing open");
+                }
+                Err(err) => {
+                    warn!(%address, screener = self.screener.name(), %err, "Failed to screen address, failing closed");
+                    return Err(ValidationError::ScreeningUnavailable(self.screener.name()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the verdict on an address, from the cache if fresh.
+    async fn verdict(&self, address: Address) -> Result<ScreeningVerdict, ScreeningError> {
+        let name = self.screener.name();
+
+        if let Some((verdict, at)) = self.cache.lock().get(&address) {
+            if at.elapsed() < self.cache_ttl {
+                ApiMetrics::increment_addresses_screened(name, "cached");
+                return Ok(verdict.clone());
+            }
+        }
+
+        let verdict = match tokio::time::timeout(self.timeout, self.screener.screen(address)).await
+        {
+            Ok(Ok(verdict)) => verdict,
+            Ok(Err(err)) => {
+                ApiMetrics::increment_addresses_screened(name, "error");
+                return Err(err);
+            }
+            Err(_) => {
+                ApiMetrics::increment_addresses_screened(name, "error");
+                return Err(ScreeningError(format!("timed out after {:?}", self.timeout)));
+            }
+        };
+
+        let result = match verdict {
+            ScreeningVerdict::Clear => "clear",
+            ScreeningVerdict::Flagged(_) => "flagged",
+        };
+        ApiMetrics::increment_addresses_screened(name, result);
+
+        self.cache.lock().insert(address, (verdict.clone(), Instant::now()));
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// A screener counting its calls, failing for every address but the zero one.
+    #[derive(Debug, Default)]
+    struct FlakyScreener {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AddressScreener for FlakyScreener {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn screen(&self, address: Address) -> Result<ScreeningVerdict, ScreeningError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if address == Address::ZERO {
+                Ok(ScreeningVerdict::Clear)
+            } else {
+                Err(ScreeningError("unavailable".to_string()))
+            }
+        }
+    }
+
+    fn request(signer: Address) -> InclusionRequest {
+        InclusionRequest {
+            slot: 10,
+            max_slot: None,
+            top: false,
+            partial: false,
+            batch: false,
+            txs: Vec::new(),
+            signature: None,
+            signer: Some(signer),
+            voucher: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_screening() {
+        let flagged = Address::with_last_byte(1);
+        let screening =
+            Screening::new(Arc::new(ListScreener::new([flagged])), &ScreeningOpts::default());
+
+        assert!(screening.check(&request(Address::with_last_byte(2))).await.is_ok());
+
+        let err = screening.check(&request(flagged)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::Screened { address, screener: "list", .. } if address == flagged
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_screening_cache_and_fail_mode() {
+        let screener = Arc::new(FlakyScreener::default());
+        let closed = Screening::new(screener.clone(), &ScreeningOpts::default());
+
+        // Verdicts are cached, errors are not
+        closed.check(&request(Address::ZERO)).await.unwrap();
+        closed.check(&request(Address::ZERO)).await.unwrap();
+        assert_eq!(screener.calls.load(Ordering::Relaxed), 1);
+
+        let unavailable = request(Address::with_last_byte(1));
+        assert!(matches!(
+            closed.check(&unavailable).await,
+            Err(ValidationError::ScreeningUnavailable("flaky"))
+        ));
+        assert!(closed.check(&unavailable).await.is_err());
+        assert_eq!(screener.calls.load(Ordering::Relaxed), 3);
+
+        let opts =
+            ScreeningOpts { screening_fail_mode: ScreeningFailMode::Open, ..Default::default() };
+        let open = Screening::new(screener, &opts);
+        assert!(open.check(&unavailable).await.is_ok());
+    }
+}
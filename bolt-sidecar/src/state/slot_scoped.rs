@@ -0,0 +1,113 @@
+use std::collections::{btree_map, BTreeMap};
+
+use crate::primitives::Slot;
+
+/// In-memory state scoped to slots, torn down as the chain advances.
+///
+/// Every cache keyed by slot should use this instead of pruning its entries by hand:
+/// [advance](SlotScoped::advance) drops the state of every slot up to the new head at once.
+#[derive(Debug, Clone)]
+pub struct SlotScoped<T> {
+    entries: BTreeMap<Slot, T>,
+    head: Slot,
+}
+
+impl<T> Default for SlotScoped<T> {
+    fn default() -> Self {
+        Self { entries: BTreeMap::new(), head: 0 }
+    }
+}
+
+impl<T> SlotScoped<T> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last head slot the cache was advanced to.
+    pub fn head(&self) -> Slot {
+        self.head
+    }
+
+    /// Returns the state of the given slot, if any.
+    pub fn get(&self, slot: Slot) -> Option<&T> {
+        self.entries.get(&slot)
+    }
+
+    /// Returns a mutable reference to the state of the given slot, if any.
+    pub fn get_mut(&mut self, slot: Slot) -> Option<&mut T> {
+        self.entries.get_mut(&slot)
+    }
+
+    /// Returns a mutable reference to the state of the given slot, initializing it
+    /// with its default value if there is none.
+    pub fn get_or_default(&mut self, slot: Slot) -> &mut T
+    where
+        T: Default,
+    {
+        self.entries.entry(slot).or_default()
+    }
+
+    /// Sets the state of the given slot, returning the previous one if any.
+    pub fn insert(&mut self, slot: Slot, value: T) -> Option<T> {
+        self.entries.insert(slot, value)
+    }
+
+    /// Removes and returns the state of the given slot, if any.
+    pub fn remove(&mut self, slot: Slot) -> Option<T> {
+        self.entries.remove(&slot)
+    }
+
+    /// Returns an iterator over the states, by ascending slot.
+    pub fn iter(&self) -> btree_map::Iter<'_, Slot, T> {
+        self.entries.iter()
+    }
+
+    /// Returns a mutable iterator over the states, by ascending slot.
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, Slot, T> {
+        self.entries.iter_mut()
+    }
+
+    /// Returns the number of slots with state.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no slot has state.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Advances the cache to the given head slot, dropping the state of every slot up to
+    /// and including it. Returns the dropped states, by ascending slot.
+    pub fn advance(&mut self, head: Slot) -> BTreeMap<Slot, T> {
+        self.head = head;
+
+        let kept = self.entries.split_off(&head.saturating_add(1));
+        std::mem::replace(&mut self.entries, kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_scoped_teardown() {
+        let mut scoped = SlotScoped::new();
+        for slot in 10..15 {
+            scoped.insert(slot, slot * 2);
+        }
+        *scoped.get_or_default(20) += 1;
+
+        let dropped = scoped.advance(12);
+        assert_eq!(dropped.into_keys().collect::<Vec<_>>(), vec![10, 11, 12]);
+        assert_eq!(scoped.head(), 12);
+        assert_eq!(scoped.get(12), None);
+        assert_eq!(scoped.get(13), Some(&26));
+        assert_eq!(scoped.iter().map(|(slot, _)| *slot).collect::<Vec<_>>(), vec![13, 14, 20]);
+
+        assert!(scoped.advance(20).contains_key(&20));
+        assert!(scoped.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::{cmp::Reverse, collections::BTreeMap, time::Duration};
+
+use crate::primitives::InclusionRequest;
+
+use super::CommitmentDeadline;
+
+/// A validated top-of-block request waiting for the auction of its target slot to close.
+#[derive(Debug)]
+pub struct TobBid<R> {
+    /// The validated request.
+    pub request: InclusionRequest,
+    /// The price of the bid: the total tip of its transactions at the basefee of the head.
+    pub price: u128,
+    /// Where to send the outcome of the bid.
+    pub responder: R,
+}
+
+/// The auctions of the single top-of-block bundle of the upcoming proposal slots.
+///
+/// Competing top-of-block requests are collected until the cutoff of their target slot,
+/// set at a fixed time into the previous slot. Only the highest-paying one that is still
+/// valid at the cutoff is committed to.
+#[derive(Debug)]
+pub struct TobAuction<R> {
+    /// The time into the previous slot at which the auction of a slot closes.
+    cutoff: Duration,
+    /// The cutoff of the auction of the next slot.
+    deadline: CommitmentDeadline,
+    /// The last slot whose auction closed.
+    closed_through: u64,
+    /// The bids of the open auctions, by target slot, in the order they were placed.
+    bids: BTreeMap<u64, Vec<TobBid<R>>>,
+}
+
+impl<R> TobAuction<R> {
+    /// Creates the auctions with the given cutoff into the slot before each proposal slot.
+    pub fn new(cutoff: Duration) -> Self {
+        Self {
+            cutoff,
+            deadline: CommitmentDeadline::new(0, cutoff),
+            closed_through: 0,
+            bids: BTreeMap::new(),
+        }
+    }
+
+    /// Starts counting down to the cutoff of the auction of the slot after the given one,
+    /// which just started, and removes the bids of the auctions that missed their cutoff.
+    pub fn start_slot(&mut self, slot: u64) -> Vec<TobBid<R>> {
+        self.deadline = CommitmentDeadline::new(slot + 1, self.cutoff);
+
+        let open = self.bids.split_off(&(slot + 1));
+        let missed = std::mem::replace(&mut self.bids, open);
+        missed.into_values().flatten().collect()
+    }
+
+    /// Waits for the cutoff of the auction of the next slot, returning the slot.
+    pub async fn wait_cutoff(&mut self) -> Option<u64> {
+        self.deadline.wait().await
+    }
+
+    /// Returns true if the auction of the given slot is closed.
+    pub fn is_closed(&self, slot: u64) -> bool {
+        slot <= self.closed_through
+    }
+
+    /// Places a bid in the auction of the target slot of its request.
+    pub fn place(&mut self, bid: TobBid<R>) {
+        self.bids.entry(bid.request.slot).or_default().push(bid);
+    }
+
+    /// Closes the auction of the given slot, returning its bids from the highest to the
+    /// lowest price. Ties go to the bid placed first.
+    pub fn close(&mut self, slot: u64) -> Vec<TobBid<R>> {
+        self.closed_through = self.closed_through.max(slot);
+
+        let mut bids = self.bids.remove(&slot).unwrap_or_default();
+        bids.sort_by_key(|bid| Reverse(bid.price));
+        bids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(slot: u64, price: u128, id: u32) -> TobBid<u32> {
+        let request = InclusionRequest {
+            slot,
+            max_slot: None,
+            top: true,
+            partial: false,
+            batch: false,
+            txs: Vec::new(),
+            signature: None,
+            signer: None,
+            voucher: None,
+        };
+        TobBid { request, price, responder: id }
+    }
+
+    #[tokio::test]
+    async fn test_tob_auction() {
+        let mut auction = TobAuction::new(Duration::from_secs(4));
+        auction.start_slot(9);
+
+        auction.place(bid(10, 100, 1));
+        auction.place(bid(10, 300, 2));
+        auction.place(bid(10, 300, 3));
+        auction.place(bid(11, 50, 4));
+        assert!(!auction.is_closed(10));
+
+        // Highest price first, ties to the first bid
+        let bids = auction.close(10);
+        assert_eq!(bids.iter().map(|b| b.responder).collect::<Vec<_>>(), [2, 3, 1]);
+        assert!(auction.is_closed(10));
+        assert!(!auction.is_closed(11));
+
+        // The bids of a slot whose cutoff was missed are returned when the slot starts
+        let missed = auction.start_slot(11);
+        assert_eq!(missed.iter().map(|b| b.responder).collect::<Vec<_>>(), [4]);
+        assert!(auction.close(11).is_empty());
+    }
+}
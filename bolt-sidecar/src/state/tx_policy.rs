@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use alloy::primitives::{Address, TxHash};
+use thiserror::Error;
+
+use crate::{
+    config::{tx_policy::CommitmentTxType, TxPolicyOpts},
+    primitives::{FullTransaction, InclusionRequest, TransactionExt},
+};
+
+/// The reason why a transaction is refused by the [TxPolicy] of the operator.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TxPolicyViolation {
+    /// The type of the transaction is not committed to.
+    #[error("Transaction {0} has disallowed type {1}")]
+    DisallowedType(TxHash, &'static str),
+    /// The transaction creates a contract.
+    #[error("Transaction {0} creates a contract, which is disallowed")]
+    ContractCreation(TxHash),
+    /// The calldata of the transaction is too large.
+    #[error("Transaction {tx_hash} has {size} bytes of calldata, max is {max}")]
+    CalldataTooLarge { tx_hash: TxHash, size: usize, max: usize },
+    /// The transaction is sent to a denied address.
+    #[error("Transaction {0} is sent to denied address {1}")]
+    DeniedRecipient(TxHash, Address),
+}
+
+impl TxPolicyViolation {
+    /// Returns the tag of the violation as a string, mainly for metrics purposes
+    pub const fn to_tag_str(&self) -> &'static str {
+        match self {
+            Self::DisallowedType(_, _) => "disallowed_type",
+            Self::ContractCreation(_) => "contract_creation",
+            Self::CalldataTooLarge { .. } => "calldata_too_large",
+            Self::DeniedRecipient(_, _) => "denied_recipient",
+        }
+    }
+}
+
+/// The policy of the operator on the transactions it commits to, from the [TxPolicyOpts].
+#[derive(Debug, Clone, Default)]
+pub struct TxPolicy {
+    disallowed_tx_types: HashSet<CommitmentTxType>,
+    disallow_contract_creation: bool,
+    max_calldata_bytes: Option<usize>,
+    denied_to_addresses: HashSet<Address>,
+}
+
+impl TxPolicy {
+    /// Creates the policy from the options.
+    pub fn new(opts: &TxPolicyOpts) -> Self {
+        Self {
+            disallowed_tx_types: opts.disallowed_tx_types.iter().copied().collect(),
+            disallow_contract_creation: opts.disallow_contract_creation,
+            max_calldata_bytes: opts.max_calldata_bytes.map(|max| max.get()),
+            denied_to_addresses: opts.denied_to_addresses.iter().copied().collect(),
+        }
+    }
+
+    /// Checks every transaction of the request, returning the first violation found.
+    pub fn check(&self, request: &InclusionRequest) -> Result<(), TxPolicyViolation> {
+        request.txs.iter().try_for_each(|tx| self.check_transaction(tx))
+    }
+
+    /// Checks a single transaction against the policy.
+    pub fn check_transaction(&self, tx: &FullTransaction) -> Result<(), TxPolicyViolation> {
+        let tx_hash = *tx.hash();
+
+        // Transaction types that can't be committed to are rejected by decoding already
+        if let Some(tx_type) = CommitmentTxType::from_tx_type(tx.tx_type()) {
+            if self.disallowed_tx_types.contains(&tx_type) {
+                return Err(TxPolicyViolation::DisallowedType(tx_hash, tx_type.as_str()));
+            }
+        }
+
+        match tx.tx_kind().to() {
+            None if self.disallow_contract_creation => {
+                return Err(TxPolicyViolation::ContractCreation(tx_hash));
+            }
+            Some(to) if self.denied_to_addresses.contains(to) => {
+                return Err(TxPolicyViolation::DeniedRecipient(tx_hash, *to));
+            }
+            _ => {}
+        }
+
+        if let Some(max) = self.max_calldata_bytes {
+            let size = tx.input().len();
+            if size > max {
+                return Err(TxPolicyViolation::CalldataTooLarge { tx_hash, size, max });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+        eips::eip2718::Encodable2718,
+        network::TxSignerSync,
+        primitives::{Bytes, TxKind},
+        signers::local::PrivateKeySigner,
+    };
+
+    use super::*;
+
+    fn transaction(to: TxKind, input: usize) -> FullTransaction {
+        let signer = PrivateKeySigner::random();
+        let mut tx = TxEip1559 {
+            chain_id: 1,
+            gas_limit: 100_000,
+            max_fee_per_gas: 20_000_000_000,
+            to,
+            input: Bytes::from(vec![1; input]),
+            ..Default::default()
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        let raw = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
+
+        FullTransaction::decode_enveloped(raw).unwrap()
+    }
+
+    #[test]
+    fn test_tx_policy() {
+        let denied = Address::with_last_byte(1);
+        let transfer = transaction(TxKind::Call(Address::with_last_byte(2)), 0);
+        let creation = transaction(TxKind::Create, 64);
+        let to_denied = transaction(TxKind::Call(denied), 0);
+
+        // Everything is accepted by default
+        let policy = TxPolicy::default();
+        for tx in [&transfer, &creation, &to_denied] {
+            assert_eq!(policy.check_transaction(tx), Ok(()));
+        }
+
+        let policy = TxPolicy::new(&TxPolicyOpts {
+            disallowed_tx_types: vec![CommitmentTxType::Eip4844],
+            disallow_contract_creation: true,
+            max_calldata_bytes: NonZero::new(32),
+            denied_to_addresses: vec![denied],
+        });
+        assert_eq!(policy.check_transaction(&transfer), Ok(()));
+        assert_eq!(
+            policy.check_transaction(&creation),
+            Err(TxPolicyViolation::ContractCreation(*creation.hash()))
+        );
+        assert_eq!(
+            policy.check_transaction(&to_denied),
+            Err(TxPolicyViolation::DeniedRecipient(*to_denied.hash(), denied))
+        );
+
+        let large = transaction(TxKind::Call(Address::with_last_byte(2)), 33);
+        assert_eq!(
+            policy.check_transaction(&large),
+            Err(TxPolicyViolation::CalldataTooLarge { tx_hash: *large.hash(), size: 33, max: 32 })
+        );
+
+        let policy = TxPolicy::new(&TxPolicyOpts {
+            disallowed_tx_types: vec![CommitmentTxType::Eip1559],
+            ..Default::default()
+        });
+        assert_eq!(
+            policy.check_transaction(&transfer),
+            Err(TxPolicyViolation::DisallowedType(*transfer.hash(), "eip1559"))
+        );
+    }
+}
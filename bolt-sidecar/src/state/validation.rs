@@ -0,0 +1,648 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::Deref,
+    sync::Arc,
+};
+
+use alloy::{
+    eips::eip4844::MAX_BLOBS_PER_BLOCK,
+    primitives::{Address, U256},
+};
+use reth_primitives::{revm_primitives::EnvKzgSettings, PooledTransactionsElement};
+use tracing::{debug, trace};
+
+use crate::{
+    builder::BlockTemplate,
+    common::{calculate_max_basefee, max_transaction_cost, validate_transaction},
+    config::limits::LimitsOpts,
+    primitives::{AccountState, InclusionRequest, Slot},
+};
+
+use super::{
+    MempoolConflict, PricingEngine, ProposalAttributes, ReservationBook, SlotScoped, TxPolicy,
+    ValidationError, VoucherLedger,
+};
+
+/// Other values used for validation.
+#[derive(Debug, Clone)]
+pub struct ValidationParams {
+    /// The gas limit of a block, which no single transaction can exceed.
+    pub block_gas_limit: u64,
+    /// The maximum size of a transaction input, in bytes.
+    pub max_tx_input_bytes: usize,
+    /// The maximum size of the init code of a contract creation, in bytes.
+    pub max_init_code_byte_size: usize,
+}
+
+impl Default for ValidationParams {
+    fn default() -> Self {
+        Self {
+            block_gas_limit: 30_000_000,
+            max_tx_input_bytes: 4 * 32 * 1024,
+            max_init_code_byte_size: 2 * 24576,
+        }
+    }
+}
+
+/// A read-only view of the execution state, handed to each [CommitmentValidator]
+/// of the [ValidationPipeline].
+#[derive(Debug)]
+pub struct ValidationContext<'a> {
+    /// The chain ID of the chain (constant).
+    pub chain_id: u64,
+    /// The latest slot number.
+    pub slot: u64,
+    /// The basefee at the head block.
+    pub basefee: u128,
+    /// The blob basefee at the head block.
+    pub blob_basefee: u128,
+    /// The limits set for the sidecar.
+    pub limits: &'a LimitsOpts,
+    /// Other values used for validation.
+    pub params: &'a ValidationParams,
+    /// The block templates by target slot number.
+    pub block_templates: &'a SlotScoped<BlockTemplate>,
+    /// The canonical account states at the head block. These are only guaranteed to
+    /// contain the senders of the request for validators that
+    /// [require them](CommitmentValidator::requires_account_states).
+    pub account_states: &'a HashMap<Address, AccountState>,
+    /// The KZG settings for validating blobs.
+    pub kzg_settings: &'a EnvKzgSettings,
+    /// The blockspace reservations of the upcoming slots.
+    pub reservations: &'a ReservationBook,
+    /// The execution attributes of the upcoming proposal slots, when known.
+    pub proposal_attributes: &'a SlotScoped<ProposalAttributes>,
+    /// The lowest contested nonce of each sender, as of the last mempool scan.
+    pub mempool_conflicts: &'a HashMap<Address, MempoolConflict>,
+    /// The payment channels of the payers of inclusion requests.
+    pub vouchers: &'a VoucherLedger,
+    /// The pricing of inclusion commitments.
+    pub pricing: &'a PricingEngine,
+}
+
+impl ValidationContext<'_> {
+    /// Returns the block template for the given slot, if any.
+    pub fn block_template(&self, slot: Slot) -> Option<&BlockTemplate> {
+        self.block_templates.get(slot)
+    }
+
+    /// Returns the maximum basefee that can be reached by the target slot, which is
+    /// its exact basefee if the attributes of the proposal are known.
+    pub fn max_basefee(&self, target_slot: Slot) -> Result<u128, ValidationError> {
+        if let Some(attributes) = self.proposal_attributes.get(target_slot) {
+            return Ok(attributes.basefee);
+        }
+
+        let slot_diff = target_slot.saturating_sub(self.slot);
+        calculate_max_basefee(self.basefee, slot_diff)
+            .ok_or(ValidationError::MaxBaseFeeCalcOverflow)
+    }
+
+    /// Returns the maximum blob basefee that can be reached by the target slot, which is
+    /// its exact blob basefee if the attributes of the proposal are known.
+    pub fn max_blob_basefee(&self, target_slot: Slot) -> Result<u128, ValidationError> {
+        if let Some(attributes) = self.proposal_attributes.get(target_slot) {
+            return Ok(attributes.blob_basefee);
+        }
+
+        let slot_diff = target_slot.saturating_sub(self.slot);
+        calculate_max_basefee(self.blob_basefee, slot_diff)
+            .ok_or(ValidationError::MaxBaseFeeCalcOverflow)
+    }
+
+    /// Returns the gas limit of the block at the target slot, which is the gas limit of
+    /// its parent if the attributes of the proposal are known.
+    pub fn block_gas_limit(&self, target_slot: Slot) -> u64 {
+        self.proposal_attributes
+            .get(target_slot)
+            .map_or(self.params.block_gas_limit, |attributes| attributes.gas_limit)
+    }
+
+    /// Returns the gas already committed in the target slot of the request, and the max gas
+    /// that can be committed in it for the signer of the request.
+    ///
+    /// The gas reserved for other subscribers and not used yet is not available to the
+    /// request, and neither is the gas of the block reserved for content other than
+    /// commitments.
+    pub fn committed_gas(&self, request: &InclusionRequest) -> (u64, u64) {
+        let committed_gas = self.block_template(request.slot).map_or(0, |t| t.committed_gas());
+        let reserved_gas =
+            self.reservations.unused_reserved_gas(request.slot, request.signer.as_ref());
+        let max_committed_gas = self
+            .limits
+            .committable_gas(self.block_gas_limit(request.slot))
+            .saturating_sub(reserved_gas);
+
+        (committed_gas, max_committed_gas)
+    }
+}
+
+/// A single step of the commitment [ValidationPipeline].
+///
+/// Validators are run in the order in which they are registered, and the first
+/// one to return an error rejects the request. Downstream forks can implement this
+/// trait to add their own policies on top of the built-in ones.
+pub trait CommitmentValidator: fmt::Debug + Send + Sync {
+    /// The name of the validator, used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Returns true if the validator needs the account states of the request senders.
+    /// The pipeline will fetch them before running the validator.
+    fn requires_account_states(&self) -> bool {
+        false
+    }
+
+    /// Validates the request against the given context.
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError>;
+}
+
+/// An ordered list of [CommitmentValidator]s that every commitment request must pass.
+#[derive(Debug, Clone)]
+pub struct ValidationPipeline {
+    validators: Vec<Arc<dyn CommitmentValidator>>,
+}
+
+impl Default for ValidationPipeline {
+    /// The built-in pipeline: signature, quota, pricing and state validation.
+    fn default() -> Self {
+        Self::empty()
+            .with_validator(SignatureValidator)
+            .with_validator(QuotaValidator)
+            .with_validator(PricingValidator)
+            .with_validator(StateValidator)
+    }
+}
+
+impl ValidationPipeline {
+    /// Creates a pipeline without any validators.
+    pub fn empty() -> Self {
+        Self { validators: Vec::new() }
+    }
+
+    /// Appends a validator to the end of the pipeline.
+    pub fn with_validator<V: CommitmentValidator + 'static>(mut self, validator: V) -> Self {
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Inserts a validator before the built-in state validation, so that it can reject
+    /// requests without incurring any account state lookups.
+    pub fn with_validator_before_state<V: CommitmentValidator + 'static>(
+        self,
+        validator: V,
+    ) -> Self {
+        self.with_shared_validator_before_state(Arc::new(validator))
+    }
+
+    /// Inserts a shared validator before the built-in state validation.
+    pub(crate) fn with_shared_validator_before_state(
+        mut self,
+        validator: Arc<dyn CommitmentValidator>,
+    ) -> Self {
+        let index = self
+            .validators
+            .iter()
+            .position(|v| v.requires_account_states())
+            .unwrap_or(self.validators.len());
+        self.validators.insert(index, validator);
+        self
+    }
+
+    /// Removes the validators with the given name from the pipeline.
+    pub fn without_validator(mut self, name: &str) -> Self {
+        self.validators.retain(|v| v.name() != name);
+        self
+    }
+
+    /// Returns the names of the registered validators, in order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.validators.iter().map(|v| v.name()).collect()
+    }
+
+    /// Returns an iterator over the registered validators, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn CommitmentValidator>> {
+        self.validators.iter()
+    }
+}
+
+/// Checks that the request and its transactions are signed for the expected chain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignatureValidator;
+
+impl CommitmentValidator for SignatureValidator {
+    fn name(&self) -> &'static str {
+        "signature"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        if request.signer().is_none() || request.txs.iter().any(|tx| tx.sender().is_none()) {
+            return Err(ValidationError::RecoverSigner);
+        }
+
+        if !request.validate_chain_id(ctx.chain_id) {
+            return Err(ValidationError::ChainIdMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Only accepts requests signed by one of the given addresses.
+///
+/// Not part of the default pipeline: operators that only serve known gateways
+/// can register it at startup.
+#[derive(Debug, Clone, Default)]
+pub struct WhitelistValidator {
+    allowed: HashSet<Address>,
+}
+
+impl WhitelistValidator {
+    /// Creates a validator that only accepts requests from the given signers.
+    pub fn new(allowed: impl IntoIterator<Item = Address>) -> Self {
+        Self { allowed: allowed.into_iter().collect() }
+    }
+}
+
+impl CommitmentValidator for WhitelistValidator {
+    fn name(&self) -> &'static str {
+        "whitelist"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        _ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        let signer = request.signer().ok_or(ValidationError::RecoverSigner)?;
+
+        if !self.allowed.contains(&signer) {
+            return Err(ValidationError::SignerNotWhitelisted(signer));
+        }
+
+        Ok(())
+    }
+}
+
+/// Rejects transactions spending or building on a nonce that is contested in the mempool
+/// by a higher-tip transaction, since they would likely be invalid at execution time.
+///
+/// Not part of the default pipeline: it is registered when mempool scanning is enabled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MempoolValidator;
+
+impl CommitmentValidator for MempoolValidator {
+    fn name(&self) -> &'static str {
+        "mempool"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        for tx in request.txs.iter() {
+            let sender = tx.sender().ok_or(ValidationError::RecoverSigner)?;
+
+            if let Some(conflict) = ctx.mempool_conflicts.get(sender) {
+                if tx.nonce() >= conflict.nonce {
+                    return Err(ValidationError::MempoolConflict(*sender, conflict.nonce));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Requires the request to carry a payment voucher of its signer covering its price on top
+/// of the previous vouchers, without exceeding the unsettled limit of the payer.
+///
+/// Not part of the default pipeline: it is registered when vouchers are enabled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VoucherValidator;
+
+impl CommitmentValidator for VoucherValidator {
+    fn name(&self) -> &'static str {
+        "voucher"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        ctx.vouchers.check(request, ctx.chain_id)?;
+        Ok(())
+    }
+}
+
+/// Refuses the transactions that the operator doesn't commit to, by type, contract creation,
+/// calldata size or recipient, according to its [TxPolicy].
+///
+/// Not part of the default pipeline: it is registered when a transaction policy is configured.
+#[derive(Debug, Clone, Default)]
+pub struct TxPolicyValidator {
+    policy: TxPolicy,
+}
+
+impl TxPolicyValidator {
+    /// Creates a validator enforcing the given policy.
+    pub fn new(policy: TxPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl CommitmentValidator for TxPolicyValidator {
+    fn name(&self) -> &'static str {
+        "tx_policy"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        _ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        self.policy.check(request)?;
+        Ok(())
+    }
+}
+
+/// Enforces the per-slot commitment limits and the per-transaction size limits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuotaValidator;
+
+impl CommitmentValidator for QuotaValidator {
+    fn name(&self) -> &'static str {
+        "quota"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        let template = ctx.block_template(request.slot);
+
+        // Check if there is room for more commitments
+        if let Some(template) = template {
+            if template.transactions_len() >= ctx.limits.max_commitments_per_slot.get() {
+                return Err(ValidationError::MaxCommitmentsReachedForSlot(
+                    ctx.slot,
+                    ctx.limits.max_commitments_per_slot.get(),
+                ));
+            }
+        }
+
+        // Check if the committed gas exceeds the maximum
+        let (template_committed_gas, max_committed_gas) = ctx.committed_gas(request);
+        if template_committed_gas + request.gas_limit() >= max_committed_gas {
+            return Err(ValidationError::MaxCommittedGasReachedForSlot(ctx.slot, max_committed_gas));
+        }
+
+        // Check if the transaction size exceeds the maximum
+        if !request.validate_tx_size_limit(ctx.params.max_tx_input_bytes) {
+            return Err(ValidationError::TransactionSizeTooHigh);
+        }
+
+        // Check if the transaction is a contract creation and the init code size exceeds the
+        // maximum
+        if !request.validate_init_code_limit(ctx.params.max_init_code_byte_size) {
+            return Err(ValidationError::TransactionSizeTooHigh);
+        }
+
+        // Check if the gas limit is higher than the maximum block gas limit
+        if request.gas_limit() > ctx.block_gas_limit(request.slot) {
+            return Err(ValidationError::GasLimitTooHigh);
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the fees of the request cover the maximum basefee at the target slot,
+/// and pay at least the min priority fee quoted by the [PricingEngine] for the slot.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PricingValidator;
+
+impl CommitmentValidator for PricingValidator {
+    fn name(&self) -> &'static str {
+        "pricing"
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        // Ensure max_priority_fee_per_gas is less than max_fee_per_gas
+        if !request.validate_max_priority_fee() {
+            return Err(ValidationError::MaxPriorityFeePerGasTooHigh);
+        }
+
+        // Calculate the max possible basefee given the slot diff
+        let max_basefee = ctx.max_basefee(request.slot)?;
+
+        debug!(target_slot = request.slot, basefee = ctx.basefee, %max_basefee, "Validating basefee");
+
+        // Validate the base fee
+        if !request.validate_basefee(max_basefee) {
+            return Err(ValidationError::BaseFeeTooLow(max_basefee));
+        }
+
+        // Ensure max_priority_fee_per_gas is greater than or equal to the quoted min priority
+        // fee, which grows with the gas already committed in the slot
+        let (committed_gas, max_committed_gas) = ctx.committed_gas(request);
+        let quote = ctx.pricing.quote(
+            request.slot,
+            ctx.limits.min_priority_fee.get(),
+            max_basefee,
+            committed_gas,
+            max_committed_gas,
+        );
+        if !request.validate_min_priority_fee(max_basefee, quote.min_priority_fee) {
+            return Err(ValidationError::MaxPriorityFeePerGasTooLow(quote));
+        }
+
+        Ok(())
+    }
+}
+
+/// Simulates the request against the account states of its senders, including any
+/// previously committed transactions, and validates its blobs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateValidator;
+
+impl CommitmentValidator for StateValidator {
+    fn name(&self) -> &'static str {
+        "state"
+    }
+
+    fn requires_account_states(&self) -> bool {
+        true
+    }
+
+    fn validate(
+        &self,
+        request: &InclusionRequest,
+        ctx: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        let target_slot = request.slot;
+
+        if target_slot < ctx.slot {
+            debug!(%target_slot, slot = ctx.slot, "Target slot lower than current slot");
+            return Err(ValidationError::SlotTooLow(ctx.slot));
+        }
+
+        // Validate each transaction in the request against the account state,
+        // keeping track of the nonce and balance diffs, including:
+        // - any existing state in the account trie
+        // - any previously committed transactions
+        // - any previous transaction in the same request
+        //
+        // NOTE: it's also possible for a request to contain multiple transactions
+        // from different senders, in this case each sender will have its own nonce
+        // and balance diffs that will be applied to the account state.
+        let mut bundle_nonce_diff_map = HashMap::new();
+        let mut bundle_balance_diff_map = HashMap::new();
+        for tx in request.txs.iter() {
+            let sender = tx.sender().expect("Recovered sender");
+
+            // From previous preconfirmations requests retrieve
+            // - the nonce difference from the account state.
+            // - the balance difference from the account state.
+            // - the highest slot number for which the user has requested a preconfirmation.
+            //
+            // If the templates do not exist, or this is the first request for this sender,
+            // its diffs will be zero.
+            let (nonce_diff, balance_diff, highest_slot_for_account) =
+                ctx.block_templates.iter().fold(
+                    (0, U256::ZERO, 0),
+                    |(nonce_diff_acc, balance_diff_acc, highest_slot), (slot, block_template)| {
+                        let (nonce_diff, balance_diff, slot) = block_template
+                            .get_diff(sender)
+                            .map(|(nonce, balance)| (nonce, balance, *slot))
+                            .unwrap_or((0, U256::ZERO, 0));
+
+                        (
+                            nonce_diff_acc + nonce_diff,
+                            balance_diff_acc.saturating_add(balance_diff),
+                            u64::max(highest_slot, slot),
+                        )
+                    },
+                );
+
+            if target_slot < highest_slot_for_account {
+                debug!(%target_slot, %highest_slot_for_account, "There is a request for a higher slot");
+                return Err(ValidationError::SlotTooLow(highest_slot_for_account));
+            }
+
+            trace!(?sender, nonce_diff, %balance_diff, "Applying diffs to account state");
+
+            let Some(account_state) = ctx.account_states.get(sender).copied() else {
+                return Err(ValidationError::Internal(format!(
+                    "Missing account state for {sender}"
+                )));
+            };
+
+            debug!(?account_state, ?nonce_diff, ?balance_diff, "Validating transaction");
+
+            let sender_nonce_diff = bundle_nonce_diff_map.entry(sender).or_insert(0);
+            let sender_balance_diff = bundle_balance_diff_map.entry(sender).or_insert(U256::ZERO);
+
+            // Apply the diffs to this account according to the info fetched from the templates
+            // and the current bundle diffs for this sender.
+            let account_state_with_diffs = AccountState {
+                transaction_count: account_state
+                    .transaction_count
+                    .saturating_add(nonce_diff)
+                    .saturating_add(*sender_nonce_diff),
+
+                balance: account_state
+                    .balance
+                    .saturating_sub(balance_diff)
+                    .saturating_sub(*sender_balance_diff),
+
+                has_code: account_state.has_code,
+            };
+
+            // Validate the transaction against the account state with existing diffs
+            validate_transaction(&account_state_with_diffs, tx)?;
+
+            // Check EIP-4844-specific limits
+            if let Some(transaction) = tx.as_eip4844() {
+                if let Some(template) = ctx.block_template(target_slot) {
+                    if template.blob_count() >= MAX_BLOBS_PER_BLOCK {
+                        return Err(ValidationError::Eip4844Limit);
+                    }
+                }
+
+                let PooledTransactionsElement::BlobTransaction(ref blob_transaction) = tx.deref()
+                else {
+                    unreachable!("EIP-4844 transaction should be a blob transaction")
+                };
+
+                // Calculate max possible increase in blob basefee
+                let max_blob_basefee = ctx.max_blob_basefee(target_slot)?;
+
+                debug!(%max_blob_basefee, blob_basefee = blob_transaction.transaction.max_fee_per_blob_gas, "Validating blob basefee");
+                if blob_transaction.transaction.max_fee_per_blob_gas < max_blob_basefee {
+                    return Err(ValidationError::BlobBaseFeeTooLow(max_blob_basefee));
+                }
+
+                // Validate blob against KZG settings
+                transaction.validate_blob(&blob_transaction.sidecar, ctx.kzg_settings.get())?;
+            }
+
+            // Increase the bundle nonce and balance diffs for this sender for the next iteration
+            *sender_nonce_diff += 1;
+            *sender_balance_diff += max_transaction_cost(tx);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct RejectAll;
+
+    impl CommitmentValidator for RejectAll {
+        fn name(&self) -> &'static str {
+            "reject_all"
+        }
+
+        fn validate(
+            &self,
+            _request: &InclusionRequest,
+            _ctx: &ValidationContext<'_>,
+        ) -> Result<(), ValidationError> {
+            Err(ValidationError::Rejected("reject_all", "no".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_pipeline_ordering() {
+        let pipeline = ValidationPipeline::default();
+        assert_eq!(pipeline.names(), vec!["signature", "quota", "pricing", "state"]);
+
+        let pipeline = pipeline
+            .with_validator_before_state(WhitelistValidator::new([Address::ZERO]))
+            .with_validator(RejectAll);
+        assert_eq!(
+            pipeline.names(),
+            vec!["signature", "quota", "pricing", "whitelist", "state", "reject_all"]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    config::VoucherOpts,
+    primitives::{InclusionRequest, PaymentVoucher},
+};
+
+/// Possible errors when paying an inclusion request with a voucher.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VoucherError {
+    /// The request carries no voucher.
+    #[error("Missing payment voucher")]
+    Missing,
+    /// The voucher signature is invalid.
+    #[error("Invalid payment voucher signature")]
+    Signature,
+    /// The voucher is not signed by the signer of the request.
+    #[error("Payment voucher signed by {0}, not by the request signer")]
+    WrongPayer(Address),
+    /// The voucher pays another operator.
+    #[error("Payment voucher pays {0}, not this sidecar")]
+    WrongPayee(Address),
+    /// The voucher does not cover the price of the request.
+    #[error("Payment voucher amount too low, need at least {0} wei")]
+    Underpaid(U256),
+    /// The voucher exceeds the amount that can be owed before settlement.
+    #[error("Payment voucher exceeds the unsettled limit of {0} wei, retry after settlement")]
+    UnsettledLimitExceeded(U256),
+}
+
+/// The latest voucher of a payer to settle on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherSettlement {
+    /// The payer of the channel.
+    pub payer: Address,
+    /// The amount settled by the previous settlement of the channel.
+    pub previously_settled: U256,
+    /// The latest voucher of the payer, which supersedes the previous ones.
+    pub voucher: PaymentVoucher,
+}
+
+/// The off-chain state of the payment channel of a payer.
+#[derive(Debug, Clone)]
+struct Channel {
+    /// The latest voucher accepted from the payer.
+    latest: PaymentVoucher,
+    /// The cumulative amount already settled on-chain.
+    settled: U256,
+}
+
+/// The payment channels of the payers of inclusion requests, debited with the signed
+/// vouchers of the accepted requests and settled periodically.
+#[derive(Debug, Default)]
+pub struct VoucherLedger {
+    /// The voucher options.
+    opts: VoucherOpts,
+    /// The payee of the vouchers, i.e. the commitment signer of the sidecar.
+    payee: Address,
+    /// The channel of each payer.
+    channels: HashMap<Address, Channel>,
+}
+
+impl VoucherLedger {
+    /// Creates an empty ledger with the given options, for vouchers paying `payee`.
+    pub fn new(opts: VoucherOpts, payee: Address) -> Self {
+        Self { opts, payee, channels: HashMap::new() }
+    }
+
+    /// Returns true if inclusion requests must be paid with vouchers.
+    pub fn enabled(&self) -> bool {
+        self.opts.enabled()
+    }
+
+    /// Returns the price in wei of the given amount of gas.
+    pub fn price(&self, gas: u64) -> U256 {
+        U256::from(gas) * U256::from(self.opts.voucher_price_per_gas)
+    }
+
+    /// Returns the cumulative amount paid by the payer so far.
+    pub fn paid(&self, payer: &Address) -> U256 {
+        self.channels.get(payer).map_or(U256::ZERO, |channel| channel.latest.amount)
+    }
+
+    /// Checks that the voucher of the request pays for it on top of the previous vouchers
+    /// of its signer, without debiting it. Returns the payer.
+    pub fn check(
+        &self,
+        request: &InclusionRequest,
+        chain_id: u64,
+    ) -> Result<Address, VoucherError> {
+        let voucher = request.voucher.as_ref().ok_or(VoucherError::Missing)?;
+        if voucher.payee != self.payee {
+            return Err(VoucherError::WrongPayee(voucher.payee));
+        }
+
+        let payer = voucher.recover_payer(chain_id).map_err(|_| VoucherError::Signature)?;
+        if Some(payer) != request.signer {
+            return Err(VoucherError::WrongPayer(payer));
+        }
+
+        let required = self.paid(&payer) + self.price(request.gas_limit());
+        if voucher.amount < required {
+            return Err(VoucherError::Underpaid(required));
+        }
+
+        let settled = self.channels.get(&payer).map_or(U256::ZERO, |channel| channel.settled);
+        let max_unsettled = U256::from(self.opts.max_unsettled_voucher_amount);
+        if voucher.amount - settled > max_unsettled {
+            return Err(VoucherError::UnsettledLimitExceeded(max_unsettled));
+        }
+
+        Ok(payer)
+    }
+
+    /// Debits the channel of the payer with the voucher of an accepted request. Vouchers
+    /// that do not exceed the latest one, e.g. of rolled over requests, are ignored.
+    pub fn debit(&mut self, payer: Address, voucher: &PaymentVoucher) {
+        let channel = self
+            .channels
+            .entry(payer)
+            .or_insert_with(|| Channel { latest: voucher.clone(), settled: U256::ZERO });
+
+        if voucher.amount > channel.latest.amount {
+            channel.latest = voucher.clone();
+        }
+    }
+
+    /// Returns the latest vouchers of the channels with unsettled payments.
+    pub fn pending_settlements(&self) -> Vec<VoucherSettlement> {
+        self.channels
+            .iter()
+            .filter(|(_, channel)| channel.latest.amount > channel.settled)
+            .map(|(payer, channel)| VoucherSettlement {
+                payer: *payer,
+                previously_settled: channel.settled,
+                voucher: channel.latest.clone(),
+            })
+            .collect()
+    }
+
+    /// Marks the given settlements as settled, lifting the unsettled amount of their
+    /// channels by the settled amounts.
+    pub fn mark_settled(&mut self, settlements: &[VoucherSettlement]) {
+        for settlement in settlements {
+            if let Some(channel) = self.channels.get_mut(&settlement.payer) {
+                channel.settled = channel.settled.max(settlement.voucher.amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+
+    fn voucher(signer: &PrivateKeySigner, payee: Address, amount: u64) -> PaymentVoucher {
+        let amount = U256::from(amount);
+        let digest = PaymentVoucher::signing_digest(payee, amount, 1);
+        let signature = signer.sign_hash_sync(&digest).unwrap();
+        PaymentVoucher { payee, amount, signature }
+    }
+
+    #[test]
+    fn test_voucher_ledger() {
+        let opts = VoucherOpts {
+            voucher_price_per_gas: 1,
+            max_unsettled_voucher_amount: 50_000,
+            ..Default::default()
+        };
+        let payee = Address::repeat_byte(1);
+        let mut ledger = VoucherLedger::new(opts, payee);
+
+        let payer = PrivateKeySigner::random();
+        let mut request = InclusionRequest {
+            slot: 10,
+            max_slot: None,
+            top: false,
+            partial: false,
+            batch: false,
+            txs: Vec::new(),
+            signature: None,
+            signer: Some(payer.address()),
+            voucher: None,
+        };
+        assert_eq!(ledger.check(&request, 1), Err(VoucherError::Missing));
+
+        // Requests without transactions are free, the voucher only needs to be valid
+        request.voucher = Some(voucher(&payer, payee, 30_000));
+        assert_eq!(ledger.check(&request, 1), Ok(payer.address()));
+        ledger.debit(payer.address(), request.voucher.as_ref().unwrap());
+        assert_eq!(ledger.paid(&payer.address()), U256::from(30_000));
+
+        request.voucher = Some(voucher(&payer, payee, 20_000));
+        let paid = U256::from(30_000);
+        assert_eq!(ledger.check(&request, 1), Err(VoucherError::Underpaid(paid)));
+
+        request.voucher = Some(voucher(&payer, Address::ZERO, 40_000));
+        assert_eq!(ledger.check(&request, 1), Err(VoucherError::WrongPayee(Address::ZERO)));
+
+        request.voucher = Some(voucher(&PrivateKeySigner::random(), payee, 40_000));
+        assert!(matches!(ledger.check(&request, 1), Err(VoucherError::WrongPayer(_))));
+
+        request.voucher = Some(voucher(&payer, payee, 60_000));
+        let max_unsettled = U256::from(50_000);
+        assert_eq!(
+            ledger.check(&request, 1),
+            Err(VoucherError::UnsettledLimitExceeded(max_unsettled))
+        );
+
+        // Settling the channel lifts the unsettled limit
+        let settlements = ledger.pending_settlements();
+        assert_eq!(settlements.len(), 1);
+        assert_eq!(settlements[0].voucher.amount, U256::from(30_000));
+        ledger.mark_settled(&settlements);
+        assert!(ledger.pending_settlements().is_empty());
+        assert_eq!(ledger.check(&request, 1), Ok(payer.address()));
+    }
+}
@@ -0,0 +1,158 @@
+use std::{sync::LazyLock, time::Duration};
+
+use opentelemetry::trace::TraceContextExt;
+use prometheus_client::{
+    encoding::{text::encode, EncodeLabelSet},
+    metrics::{exemplar::HistogramWithExemplars, family::Family},
+    registry::Registry,
+};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use super::metrics::{
+    HTTP_REQUESTS_DURATION_SECONDS, PROPOSAL_STAGE_DURATION_SECONDS, RELAY_CALL_DURATION_SECONDS,
+};
+
+/// The path on the metrics port serving the latency histograms with their exemplars.
+pub const EXEMPLARS_PATH: &str = "/metrics/exemplars";
+
+/// The content type of the OpenMetrics text format, the only one that carries exemplars.
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The bucket bounds of the latency histograms, in seconds.
+const LATENCY_BUCKETS: [f64; 12] =
+    [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// A latency histogram whose observations carry the trace they were made in.
+type LatencyHistogram = HistogramWithExemplars<TraceLabels>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct HttpRequestLabels {
+    method: String,
+    path: String,
+    status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ProposalStageLabels {
+    stage: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct RelayCallLabels {
+    relay: String,
+    endpoint: String,
+    status: String,
+}
+
+/// The labels of an exemplar: the id of the trace of the observation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct TraceLabels {
+    trace_id: String,
+}
+
+/// The latency histograms with exemplars, next to the ones of the Prometheus recorder which
+/// can't carry them.
+struct Exemplars {
+    registry: Registry,
+    http_requests: Family<HttpRequestLabels, LatencyHistogram, fn() -> LatencyHistogram>,
+    proposal_stages: Family<ProposalStageLabels, LatencyHistogram, fn() -> LatencyHistogram>,
+    relay_calls: Family<RelayCallLabels, LatencyHistogram, fn() -> LatencyHistogram>,
+}
+
+static EXEMPLARS: LazyLock<Exemplars> = LazyLock::new(|| {
+    let mut registry = Registry::default();
+    let http_requests = Family::new_with_constructor(latency_histogram as fn() -> _);
+    let proposal_stages = Family::new_with_constructor(latency_histogram as fn() -> _);
+    let relay_calls = Family::new_with_constructor(latency_histogram as fn() -> _);
+
+    registry.register(
+        HTTP_REQUESTS_DURATION_SECONDS,
+        "Duration of the HTTP requests, with the trace of the request as exemplar",
+        http_requests.clone(),
+    );
+    registry.register(
+        PROPOSAL_STAGE_DURATION_SECONDS,
+        "Duration of the proposal stages, with the trace of the stage as exemplar",
+        proposal_stages.clone(),
+    );
+    registry.register(
+        RELAY_CALL_DURATION_SECONDS,
+        "Duration of the calls to the relays, with the trace of the call as exemplar",
+        relay_calls.clone(),
+    );
+
+    Exemplars { registry, http_requests, proposal_stages, relay_calls }
+});
+
+fn latency_histogram() -> LatencyHistogram {
+    HistogramWithExemplars::new(LATENCY_BUCKETS.into_iter())
+}
+
+/// Returns the id of the trace of the current span, if it is exported.
+fn current_trace() -> Option<TraceLabels> {
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| TraceLabels { trace_id: span_context.trace_id().to_string() })
+}
+
+/// Records the duration of an HTTP request, with the current trace as exemplar.
+pub(super) fn observe_http_request(duration: Duration, method: &str, path: &str, status: &str) {
+    let labels = HttpRequestLabels {
+        method: method.to_string(),
+        path: path.to_string(),
+        status: status.to_string(),
+    };
+    EXEMPLARS.http_requests.get_or_create(&labels).observe(duration.as_secs_f64(), current_trace());
+}
+
+/// Records the duration of a proposal stage, with the current trace as exemplar.
+pub(super) fn observe_proposal_stage(stage: &str, duration: Duration) {
+    let labels = ProposalStageLabels { stage: stage.to_string() };
+    EXEMPLARS
+        .proposal_stages
+        .get_or_create(&labels)
+        .observe(duration.as_secs_f64(), current_trace());
+}
+
+/// Records the duration of a call to a relay endpoint, with the current trace as exemplar.
+pub(super) fn observe_relay_call(duration: Duration, relay: &str, endpoint: &str, status: &str) {
+    let labels = RelayCallLabels {
+        relay: relay.to_string(),
+        endpoint: endpoint.to_string(),
+        status: status.to_string(),
+    };
+    EXEMPLARS.relay_calls.get_or_create(&labels).observe(duration.as_secs_f64(), current_trace());
+}
+
+/// Renders the latency histograms with their exemplars in the OpenMetrics text format.
+pub fn render() -> String {
+    let mut buf = String::new();
+    // Writing to a string can't fail
+    let _ = encode(&mut buf, &EXEMPLARS.registry);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_exemplars() {
+        observe_proposal_stage("get_header", Duration::from_millis(20));
+        observe_http_request(Duration::from_millis(3), "POST", "/", "200");
+        observe_relay_call(Duration::from_millis(80), "http://relay", "get_header", "200");
+
+        // Observations outside of an exported trace have no exemplar
+        let rendered = render();
+        assert!(rendered
+            .contains("bolt_sidecar_proposal_stage_duration_seconds_bucket{stage=\"get_header\""));
+        assert!(rendered.contains("bolt_sidecar_http_requests_duration_seconds_count"));
+        assert!(rendered.contains(
+            "bolt_sidecar_relay_call_duration_seconds_bucket{relay=\"http://relay\",endpoint=\"get_header\""
+        ));
+        assert!(!rendered.contains("trace_id"));
+        assert!(rendered.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, info_span, warn, Span};
+
+use super::ApiMetrics;
+use crate::config::LatencyBudgetOpts;
+
+/// A stage of the proposal-critical path, in the order in which they happen during a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProposalStage {
+    /// Building the local fallback payload once the commitment deadline ticks.
+    CommitmentDeadline,
+    /// Signing the constraints of an accepted commitment request.
+    ConstraintSigning,
+    /// Submitting constraints to the Constraints API until it acknowledges them.
+    ConstraintsSubmission,
+    /// Serving a `get_header` request, including the `get_header_with_proofs` round-trip.
+    GetHeader,
+    /// Serving a `get_payload` request after the proposer signed a header.
+    GetPayload,
+}
+
+impl ProposalStage {
+    /// Returns the stage name as used in metric labels and logs.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStage::CommitmentDeadline => "commitment_deadline",
+            ProposalStage::ConstraintSigning => "constraint_signing",
+            ProposalStage::ConstraintsSubmission => "constraints_submission",
+            ProposalStage::GetHeader => "get_header",
+            ProposalStage::GetPayload => "get_payload",
+        }
+    }
+}
+
+impl fmt::Display for ProposalStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Times a single [ProposalStage] against its configured latency budget.
+///
+/// The timer starts on creation and is consumed by [StageTimer::finish], which records
+/// the elapsed time and warns if the budget was exceeded. Each stage is traced in its own
+/// span, whose trace is attached to the recorded duration as exemplar.
+#[derive(Debug)]
+pub struct StageTimer {
+    stage: ProposalStage,
+    slot: u64,
+    budget: Duration,
+    start: Instant,
+    span: Span,
+}
+
+impl StageTimer {
+    /// Start timing the given stage for the given slot.
+    pub fn start(stage: ProposalStage, slot: u64, budgets: &LatencyBudgetOpts) -> Self {
+        let span = info_span!("proposal_stage", stage = stage.as_str(), slot);
+        Self { stage, slot, budget: budgets.budget(stage), start: Instant::now(), span }
+    }
+
+    /// Returns the time elapsed since the stage started.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Stop the timer, record the stage duration and return it.
+    pub fn finish(self) -> Duration {
+        let elapsed = self.start.elapsed();
+        self.span.in_scope(|| ApiMetrics::observe_proposal_stage(self.stage.as_str(), elapsed));
+
+        if elapsed > self.budget {
+            ApiMetrics::increment_latency_budget_exceeded(self.stage.as_str());
+            warn!(
+                slot = self.slot,
+                stage = %self.stage,
+                ?elapsed,
+                budget = ?self.budget,
+                "Proposal stage exceeded its latency budget"
+            );
+        } else {
+            debug!(slot = self.slot, stage = %self.stage, ?elapsed, "Proposal stage completed");
+        }
+
+        elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_stage_budgets() {
+        let budgets = LatencyBudgetOpts { get_header_budget_ms: 0, ..Default::default() };
+
+        let timer = StageTimer::start(ProposalStage::GetHeader, 1, &budgets);
+        assert_eq!(timer.budget, Duration::ZERO);
+
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(timer.finish() > Duration::ZERO);
+
+        let timer = StageTimer::start(ProposalStage::GetPayload, 1, &budgets);
+        assert_eq!(timer.budget, Duration::from_millis(1_000));
+    }
+}
@@ -0,0 +1,105 @@
+use tracing_subscriber::{
+    filter::{EnvFilter, ParseError},
+    reload, Registry,
+};
+
+/// The default directive applied when `RUST_LOG` is not set.
+const DEFAULT_DIRECTIVE: &str = "bolt_sidecar=info";
+
+/// Noisy dependencies that are always capped to the `error` level,
+/// unless explicitly overridden by the operator.
+const QUIET_DIRECTIVES: [&str; 2] = ["reqwest=error", "alloy_transport_http=error"];
+
+/// Errors that can occur when changing the tracing filter at runtime.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum LogFilterError {
+    #[error("Invalid filter directives: {0}")]
+    Parse(#[from] ParseError),
+    #[error("Failed to reload the tracing filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// Builds the startup tracing filter from the `RUST_LOG` environment variable.
+pub(crate) fn env_filter() -> Result<EnvFilter, ParseError> {
+    let mut filter = EnvFilter::builder()
+        .with_default_directive(DEFAULT_DIRECTIVE.parse()?)
+        .from_env_lossy();
+
+    for directive in QUIET_DIRECTIVES {
+        filter = filter.add_directive(directive.parse()?);
+    }
+
+    Ok(filter)
+}
+
+/// Parses a comma-separated list of directives (in the same format as `RUST_LOG`)
+/// into a filter. The noisy dependencies are quieted unless they are overridden.
+pub(crate) fn parse_filter(directives: &str) -> Result<EnvFilter, ParseError> {
+    let mut filter = EnvFilter::builder().parse(directives)?;
+
+    for directive in QUIET_DIRECTIVES {
+        let target = directive.split('=').next().unwrap_or_default();
+        if !directives.contains(target) {
+            filter = filter.add_directive(directive.parse()?);
+        }
+    }
+
+    Ok(filter)
+}
+
+/// A handle to the tracing filter of the sidecar, used to adjust
+/// the global level and per-module directives at runtime.
+#[derive(Debug, Clone)]
+pub struct LogFilterHandle {
+    inner: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogFilterHandle {
+    /// Wraps a reload handle of the tracing filter.
+    pub(crate) fn new(inner: reload::Handle<EnvFilter, Registry>) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the directives of the filter currently in use.
+    pub fn current(&self) -> Result<String, LogFilterError> {
+        Ok(self.inner.with_current(|filter| filter.to_string())?)
+    }
+
+    /// Replaces the filter with the given directives, e.g.
+    /// `info,bolt_sidecar::client::constraints_client=debug`.
+    pub fn set(&self, directives: &str) -> Result<(), LogFilterError> {
+        let filter = parse_filter(directives)?;
+        self.inner.reload(filter)?;
+        Ok(())
+    }
+
+    /// Restores the filter that was in use at startup.
+    pub fn reset(&self) -> Result<(), LogFilterError> {
+        self.inner.reload(env_filter()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_log_filter() {
+        let (_layer, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        let handle = LogFilterHandle::new(handle);
+
+        handle.set("warn,bolt_sidecar::client::constraints_client=debug").unwrap();
+        let current = handle.current().unwrap();
+        assert!(current.contains("bolt_sidecar::client::constraints_client=debug"));
+        assert!(current.contains("reqwest=error"));
+
+        handle.set("reqwest=trace").unwrap();
+        let current = handle.current().unwrap();
+        assert!(current.contains("reqwest=trace"));
+        assert!(!current.contains("reqwest=error"));
+
+        assert!(handle.set("bolt_sidecar=notalevel").is_err());
+    }
+}
@@ -3,6 +3,7 @@ use std::time::Duration;
 use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
 use reth_primitives::TxType;
 
+use super::exemplars;
 use crate::primitives::transaction::tx_type_str;
 
 //  Counters ----------------------------------------------------------------
@@ -12,10 +13,18 @@ const HTTP_REQUESTS_TOTAL: &str = "bolt_sidecar_http_requests_total";
 const LOCAL_BLOCKS_PROPOSED: &str = "bolt_sidecar_local_blocks_proposed";
 /// Counter for the number of remote blocks proposed.
 const REMOTE_BLOCKS_PROPOSED: &str = "bolt_sidecar_remote_blocks_proposed";
+/// Counter for the number of remote payloads that failed to be fetched after signing their header.
+const REMOTE_PAYLOAD_FAILURES: &str = "bolt_sidecar_remote_payload_failures";
+/// Counter for the number of slots whose constraints the standby took over from the primary.
+const FAILOVER_TAKEOVERS: &str = "bolt_sidecar_failover_takeovers";
+/// Counter for the number of bids of the self-builder selected over the relay bids.
+const SELF_BUILT_BIDS_SELECTED: &str = "bolt_sidecar_self_built_bids_selected";
 /// Counter for the number of inclusion commitments received.
 const INCLUSION_COMMITMENTS_RECEIVED: &str = "bolt_sidecar_inclusion_commitments_received";
 /// Counter for the number of inclusion commitments accepted.
 const INCLUSION_COMMITMENTS_ACCEPTED: &str = "bolt_sidecar_inclusion_commitments_accepted";
+/// Counter for the number of inclusion requests simulated.
+const INCLUSION_SIMULATIONS: &str = "bolt_sidecar_inclusion_simulations";
 /// Counter for the number of transactions preconfirmed
 const TRANSACTIONS_PRECONFIRMED: &str = "bolt_sidecar_transactions_preconfirmed";
 /// Counter for the number of validation errors; to spot most the most common ones
@@ -24,14 +33,51 @@ const VALIDATION_ERRORS: &str = "bolt_sidecar_validation_errors";
 /// We call it "gross" because in the case of PBS, it doesn't mean the proposer will
 /// get all of this as revenue.
 const GROSS_TIP_REVENUE: &str = "bolt_sidecar_gross_tip_revenue";
+/// Counter for the number of times a proposal stage exceeded its latency budget
+const LATENCY_BUDGET_EXCEEDED: &str = "bolt_sidecar_latency_budget_exceeded";
+/// Counter for the number of delegations refused by relays
+const DELEGATIONS_REFUSED: &str = "bolt_sidecar_delegations_refused";
+/// Counter for the number of requests rate-limited by relays
+const RELAY_RATE_LIMITED: &str = "bolt_sidecar_relay_rate_limited";
+/// Counter for the number of committed transactions included in an earlier block than their
+/// target slot, e.g. by another proposer
+const TRANSACTIONS_INCLUDED_ELSEWHERE: &str = "bolt_sidecar_transactions_included_elsewhere";
+/// Counter for the number of commitments whose target slot ended before its head was received
+const COMMITMENTS_EXPIRED: &str = "bolt_sidecar_commitments_expired";
+/// Counter for the number of committed transactions contested by a higher-tip transaction in
+/// the mempool
+const MEMPOOL_CONFLICTS: &str = "bolt_sidecar_mempool_conflicts";
+/// Counter for the number of requests shed under load ahead of a proposal
+const SHED_REQUESTS: &str = "bolt_sidecar_shed_requests";
+/// Counter for the number of relay responses parsed only by tolerating deviations from the
+/// builder-specs
+const RELAY_RESPONSES_TOLERATED: &str = "bolt_sidecar_relay_responses_tolerated";
+/// Counter for the number of addresses screened for compliance, by screener and result
+const ADDRESSES_SCREENED: &str = "bolt_sidecar_addresses_screened";
+/// Counter for the number of fee recipients of registrations and relay bids checked against
+/// the expected ones, by relay, source and result
+const FEE_RECIPIENT_CHECKS: &str = "bolt_sidecar_fee_recipient_checks";
+/// Counter for the number of decryption keys of encrypted requests not revealed before the
+/// commitment deadline of their slot
+const DECRYPTION_KEYS_WITHHELD: &str = "bolt_sidecar_decryption_keys_withheld";
 
 //  Gauges ------------------------------------------------------------------
 /// Gauge for the latest slot number
 const LATEST_HEAD: &str = "bolt_sidecar_latest_head";
+/// Gauge for whether requests to a relay are held back by its rate limit
+const RELAY_THROTTLED: &str = "bolt_sidecar_relay_throttled";
+/// Gauge for the adaptive timeout of the calls to each relay endpoint, in seconds
+const RELAY_TIMEOUT_SECONDS: &str = "bolt_sidecar_relay_timeout_seconds";
 
 //  Histograms --------------------------------------------------------------
 /// Histogram for the total duration of HTTP requests in seconds.
-const HTTP_REQUESTS_DURATION_SECONDS: &str = "bolt_sidecar_http_requests_duration_seconds";
+pub(super) const HTTP_REQUESTS_DURATION_SECONDS: &str =
+    "bolt_sidecar_http_requests_duration_seconds";
+/// Histogram for the duration of each stage of the proposal-critical path in seconds.
+pub(super) const PROPOSAL_STAGE_DURATION_SECONDS: &str =
+    "bolt_sidecar_proposal_stage_duration_seconds";
+/// Histogram for the duration of the calls to each relay endpoint in seconds.
+pub(super) const RELAY_CALL_DURATION_SECONDS: &str = "bolt_sidecar_relay_call_duration_seconds";
 
 /// Metrics for the commitments API.
 #[derive(Debug, Clone, Copy)]
@@ -44,20 +90,48 @@ impl ApiMetrics {
         describe_counter!(HTTP_REQUESTS_TOTAL, "Total number of HTTP requests received");
         describe_counter!(LOCAL_BLOCKS_PROPOSED, "Local blocks proposed");
         describe_counter!(REMOTE_BLOCKS_PROPOSED, "Remote blocks proposed");
+        describe_counter!(REMOTE_PAYLOAD_FAILURES, "Remote payloads failed to be fetched");
+        describe_counter!(FAILOVER_TAKEOVERS, "Slots taken over from the primary sidecar");
+        describe_counter!(SELF_BUILT_BIDS_SELECTED, "Bids of the self-builder selected");
         describe_counter!(INCLUSION_COMMITMENTS_ACCEPTED, "Inclusion commitments");
         describe_counter!(INCLUSION_COMMITMENTS_ACCEPTED, "Inclusion commitments accepted");
+        describe_counter!(INCLUSION_SIMULATIONS, "Inclusion requests simulated");
         describe_counter!(TRANSACTIONS_PRECONFIRMED, "Transactions preconfirmed");
         describe_counter!(VALIDATION_ERRORS, "Validation errors");
         describe_counter!(GROSS_TIP_REVENUE, "Gross tip revenue");
+        describe_counter!(LATENCY_BUDGET_EXCEEDED, "Proposal stages that exceeded their budget");
+        describe_counter!(DELEGATIONS_REFUSED, "Delegations refused by relays");
+        describe_counter!(RELAY_RATE_LIMITED, "Requests rate-limited by relays");
+        describe_counter!(
+            TRANSACTIONS_INCLUDED_ELSEWHERE,
+            "Committed transactions included before their target slot"
+        );
+        describe_counter!(COMMITMENTS_EXPIRED, "Commitments expired at the end of their slot");
+        describe_counter!(MEMPOOL_CONFLICTS, "Committed transactions contested in the mempool");
+        describe_counter!(SHED_REQUESTS, "Requests shed under load ahead of a proposal");
+        describe_counter!(RELAY_RESPONSES_TOLERATED, "Relay responses deviating from the specs");
+        describe_counter!(ADDRESSES_SCREENED, "Addresses screened for compliance");
+        describe_counter!(FEE_RECIPIENT_CHECKS, "Fee recipients checked against the expected ones");
+        describe_counter!(DECRYPTION_KEYS_WITHHELD, "Decryption keys withheld past the deadline");
 
         // Gauges
         describe_gauge!(LATEST_HEAD, "Latest slot number");
+        describe_gauge!(RELAY_THROTTLED, "Whether requests to a relay are rate-limited");
+        describe_gauge!(RELAY_TIMEOUT_SECONDS, "Adaptive timeout of the calls to a relay endpoint");
 
         // Histograms
         describe_histogram!(
             HTTP_REQUESTS_DURATION_SECONDS,
             "Total duration of HTTP requests in seconds"
         );
+        describe_histogram!(
+            PROPOSAL_STAGE_DURATION_SECONDS,
+            "Duration of each proposal-critical stage in seconds"
+        );
+        describe_histogram!(
+            RELAY_CALL_DURATION_SECONDS,
+            "Duration of the calls to each relay endpoint in seconds"
+        );
     }
 
     /// Counters ----------------------------------------------------------------
@@ -78,6 +152,18 @@ impl ApiMetrics {
         counter!(REMOTE_BLOCKS_PROPOSED).increment(1);
     }
 
+    pub fn increment_remote_payload_failures() {
+        counter!(REMOTE_PAYLOAD_FAILURES).increment(1);
+    }
+
+    pub fn increment_failover_takeovers() {
+        counter!(FAILOVER_TAKEOVERS).increment(1);
+    }
+
+    pub fn increment_self_built_bids_selected() {
+        counter!(SELF_BUILT_BIDS_SELECTED).increment(1);
+    }
+
     pub fn increment_inclusion_commitments_received() {
         counter!(INCLUSION_COMMITMENTS_RECEIVED).increment(1);
     }
@@ -86,6 +172,10 @@ impl ApiMetrics {
         counter!(INCLUSION_COMMITMENTS_ACCEPTED).increment(1);
     }
 
+    pub fn increment_inclusion_simulations() {
+        counter!(INCLUSION_SIMULATIONS).increment(1);
+    }
+
     pub fn increment_gross_tip_revenue(mut tip: u128) {
         // If the tip is too large, we need to split it into multiple u64 parts
         if tip > u64::MAX as u128 {
@@ -113,19 +203,101 @@ impl ApiMetrics {
         counter!(VALIDATION_ERRORS, &[("type", err_type)]).increment(1);
     }
 
+    pub fn increment_latency_budget_exceeded(stage: &'static str) {
+        counter!(LATENCY_BUDGET_EXCEEDED, &[("stage", stage)]).increment(1);
+    }
+
+    pub fn increment_delegations_refused(relay: String) {
+        counter!(DELEGATIONS_REFUSED, &[("relay", relay)]).increment(1);
+    }
+
+    pub fn increment_relay_rate_limited(relay: String) {
+        counter!(RELAY_RATE_LIMITED, &[("relay", relay)]).increment(1);
+    }
+
+    pub fn increment_relay_responses_tolerated(relay: String, endpoint: &'static str) {
+        counter!(
+            RELAY_RESPONSES_TOLERATED,
+            &[("relay", relay), ("endpoint", endpoint.to_string())]
+        )
+        .increment(1);
+    }
+
+    pub fn increment_addresses_screened(screener: &'static str, result: &'static str) {
+        counter!(ADDRESSES_SCREENED, &[("screener", screener), ("result", result)]).increment(1);
+    }
+
+    pub fn increment_fee_recipient_checks(relay: String, source: &'static str, expected: bool) {
+        let result = if expected { "match" } else { "mismatch" };
+        counter!(
+            FEE_RECIPIENT_CHECKS,
+            &[("relay", relay), ("source", source.to_string()), ("result", result.to_string())]
+        )
+        .increment(1);
+    }
+
+    pub fn increment_transactions_included_elsewhere(count: u64) {
+        counter!(TRANSACTIONS_INCLUDED_ELSEWHERE).increment(count);
+    }
+
+    pub fn increment_commitments_expired(count: u64) {
+        counter!(COMMITMENTS_EXPIRED).increment(count);
+    }
+
+    pub fn increment_mempool_conflicts() {
+        counter!(MEMPOOL_CONFLICTS).increment(1);
+    }
+
+    pub fn increment_shed_requests(reason: &'static str) {
+        counter!(SHED_REQUESTS, &[("reason", reason)]).increment(1);
+    }
+
+    pub fn increment_decryption_keys_withheld() {
+        counter!(DECRYPTION_KEYS_WITHHELD).increment(1);
+    }
+
     /// Gauges ----------------------------------------------------------------
 
     pub fn set_latest_head(slot: u32) {
         gauge!(LATEST_HEAD).set(slot);
     }
 
+    pub fn set_relay_throttled(relay: String, throttled: bool) {
+        gauge!(RELAY_THROTTLED, &[("relay", relay)]).set(if throttled { 1.0 } else { 0.0 });
+    }
+
+    pub fn set_relay_timeout(relay: String, endpoint: &'static str, timeout: Duration) {
+        gauge!(RELAY_TIMEOUT_SECONDS, &[("relay", relay), ("endpoint", endpoint.to_string())])
+            .set(timeout.as_secs_f64());
+    }
+
     /// Mixed ----------------------------------------------------------------
 
     /// Observes the duration of an HTTP request by storing it in a histogram,
     /// and incrementing the total number of HTTP requests received.
     pub fn observe_http_request(duration: Duration, method: String, path: String, status: String) {
+        exemplars::observe_http_request(duration, &method, &path, &status);
         let labels = [("method", method), ("path", path), ("status", status)];
         counter!(HTTP_REQUESTS_TOTAL, &labels).increment(1);
         histogram!(HTTP_REQUESTS_DURATION_SECONDS, &labels,).record(duration.as_secs_f64());
     }
+
+    /// Histograms --------------------------------------------------------------
+
+    pub fn observe_proposal_stage(stage: &'static str, duration: Duration) {
+        exemplars::observe_proposal_stage(stage, duration);
+        histogram!(PROPOSAL_STAGE_DURATION_SECONDS, &[("stage", stage)])
+            .record(duration.as_secs_f64());
+    }
+
+    pub fn observe_relay_call(
+        duration: Duration,
+        relay: String,
+        endpoint: &'static str,
+        status: String,
+    ) {
+        exemplars::observe_relay_call(duration, &relay, endpoint, &status);
+        let labels = [("relay", relay), ("endpoint", endpoint.to_string()), ("status", status)];
+        histogram!(RELAY_CALL_DURATION_SECONDS, &labels).record(duration.as_secs_f64());
+    }
 }
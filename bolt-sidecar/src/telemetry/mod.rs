@@ -1,46 +1,129 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
+use axum::{http::header::CONTENT_TYPE, routing::get, Router};
 use eyre::{bail, Result};
-use metrics_exporter_prometheus::PrometheusBuilder;
-use tracing::info;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace::Config as TraceConfig, Resource};
+use reqwest::Url;
+use tracing::{error, info, Level, Subscriber};
 use tracing_subscriber::{
-    fmt::Layer as FmtLayer, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
-    Registry,
+    filter::Targets, fmt::Layer as FmtLayer, layer::SubscriberExt, registry::LookupSpan, reload,
+    util::SubscriberInitExt, Layer, Registry,
 };
 
 mod metrics;
 pub use metrics::ApiMetrics;
 
+mod exemplars;
+pub use exemplars::EXEMPLARS_PATH;
+
+mod latency;
+pub use latency::{ProposalStage, StageTimer};
+
+mod log_filter;
+pub(crate) use log_filter::parse_filter;
+pub use log_filter::{LogFilterError, LogFilterHandle};
+
+/// The name of the service in the exported traces.
+const SERVICE_NAME: &str = "bolt-sidecar";
+
+/// The interval at which the histograms of the Prometheus recorder are drained.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Initialize the tracing stack and Prometheus metrics recorder.
 ///
+/// Traces are exported to the OTLP endpoint if given. The metrics are served in the Prometheus
+/// format at `/metrics`, and the latency histograms with the traces of their observations as
+/// exemplars in the OpenMetrics format at [EXEMPLARS_PATH].
+///
+/// Returns a [LogFilterHandle] that can be used to change the tracing filter at runtime.
+///
 /// **This function should be called at the beginning of the program.**
-pub fn init_telemetry_stack(metrics_port: Option<u16>) -> Result<()> {
-    // 1. Initialize tracing to stdout
-    let std_layer = FmtLayer::default().with_writer(std::io::stdout).with_filter(
-        EnvFilter::builder()
-            .with_default_directive("bolt_sidecar=info".parse()?)
-            .from_env_lossy()
-            .add_directive("reqwest=error".parse()?)
-            .add_directive("alloy_transport_http=error".parse()?),
-    );
-    Registry::default().with(std_layer).try_init()?;
-
-    // 2. Initialize metrics recorder and start the Prometheus server
+pub fn init_telemetry_stack(
+    metrics_port: Option<u16>,
+    otlp_endpoint: Option<&Url>,
+) -> Result<LogFilterHandle> {
+    // 1. Initialize tracing to stdout, with a reloadable filter, and to the OTLP endpoint
+    let (filter, filter_handle) = reload::Layer::new(log_filter::env_filter()?);
+    let std_layer = FmtLayer::default().with_writer(std::io::stdout).with_filter(filter);
+    let otlp_layer = otlp_endpoint.map(otlp_layer).transpose()?;
+    Registry::default().with(std_layer).with(otlp_layer).try_init()?;
+
+    if let Some(endpoint) = otlp_endpoint {
+        info!("Exporting traces to OTLP endpoint: {}", endpoint);
+    }
+
+    // 2. Initialize metrics recorder and start the metrics server
     if let Some(metrics_port) = metrics_port {
-        let prometheus_addr = SocketAddr::from(([0, 0, 0, 0], metrics_port));
-        let builder = PrometheusBuilder::new().with_http_listener(prometheus_addr);
-
-        if let Err(e) = builder.install() {
-            bail!("failed to install Prometheus recorder: {:?}", e);
-        } else {
-            info!(
-                "Telemetry initialized. Serving Prometheus metrics at: http://{}",
-                prometheus_addr
-            );
-        }
+        let metrics_addr = SocketAddr::from(([0, 0, 0, 0], metrics_port));
+
+        let handle = match PrometheusBuilder::new().install_recorder() {
+            Ok(handle) => handle,
+            Err(e) => bail!("failed to install Prometheus recorder: {:?}", e),
+        };
+        serve_metrics(metrics_addr, handle)?;
+
+        info!("Telemetry initialized. Serving Prometheus metrics at: http://{}", metrics_addr);
 
         ApiMetrics::describe_all();
     };
 
+    Ok(LogFilterHandle::new(filter_handle))
+}
+
+/// Returns the layer exporting the spans of the sidecar to the OTLP endpoint in batches.
+fn otlp_layer<S>(endpoint: &Url) -> Result<impl Layer<S>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint.as_str());
+    let resource = Resource::new([KeyValue::new("service.name", SERVICE_NAME)]);
+    let provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(TraceConfig::default().with_resource(resource))
+        .install_batch(runtime::Tokio)?;
+
+    let tracer = provider.tracer(SERVICE_NAME);
+    opentelemetry::global::set_tracer_provider(provider);
+
+    // Only the spans of the sidecar are exported, not the ones of its dependencies
+    let targets = Targets::new().with_target("bolt_sidecar", Level::INFO);
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer).with_filter(targets))
+}
+
+/// Serves the Prometheus metrics and the latency histograms with exemplars on the address,
+/// and drains the histograms of the recorder in the background.
+fn serve_metrics(addr: SocketAddr, handle: PrometheusHandle) -> Result<()> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+
+    let render_handle = handle.clone();
+    let router = Router::new()
+        .route("/metrics", get(move || std::future::ready(render_handle.render())))
+        .route(
+            EXEMPLARS_PATH,
+            get(|| async {
+                ([(CONTENT_TYPE, exemplars::OPENMETRICS_CONTENT_TYPE)], exemplars::render())
+            }),
+        );
+
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router).await {
+            error!(?err, "Metrics server failed");
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut upkeep = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            upkeep.tick().await;
+            handle.run_upkeep();
+        }
+    });
+
     Ok(())
 }
@@ -3,7 +3,7 @@ use std::env;
 use alloy::{
     eips::eip2718::Encodable2718,
     network::{EthereumWallet, TransactionBuilder},
-    primitives::{Address, U256},
+    primitives::{bytes, Address, Bytes, B256, U256},
     rpc::types::TransactionRequest,
     signers::{
         k256::{ecdsa::SigningKey as K256SigningKey, SecretKey as K256SecretKey},
@@ -15,6 +15,14 @@ use alloy_node_bindings::{Anvil, AnvilInstance};
 use blst::min_pk::SecretKey;
 use clap::Parser;
 use ethereum_consensus::crypto::{PublicKey, Signature};
+use lighthouse_eth2_keystore::{
+    encrypt,
+    json_keystore::{
+        Aes128Ctr, ChecksumModule, Cipher, CipherModule, Crypto, EmptyMap, EmptyString, Kdf,
+        KdfModule, Pbkdf2, Prf, Sha256Checksum,
+    },
+    IV_SIZE, SALT_SIZE,
+};
 use rand::Rng;
 use secp256k1::Message;
 use tracing::warn;
@@ -23,8 +31,9 @@ use crate::{
     common::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
     crypto::{ecdsa::SignableECDSA, SignableBLS},
     primitives::{
-        CommitmentRequest, ConstraintsMessage, DelegationMessage, FullTransaction,
-        InclusionRequest, RevocationMessage, SignedConstraints, SignedDelegation, SignedRevocation,
+        serialize_txs, timelock::U2048, CommitmentRequest, ConstraintsMessage, DelegationMessage,
+        FullTransaction, InclusionRequest, RevocationMessage, SignedConstraints, SignedDelegation,
+        SignedRevocation, TimeLockPuzzle,
     },
     signer::local::LocalSigner,
     ChainConfig, Opts,
@@ -45,6 +54,17 @@ const BEACON_API_URL: &str = "http://remotebeast:3500";
 /// NOTE: this DNS is only available through the Chainbound Tailnet
 const ENGINE_API_URL: &str = "http://remotebeast:8551";
 
+/// A signed legacy transfer to the `0xdead` address, EIP-2718 encoded.
+pub(crate) const TEST_LEGACY_TX: Bytes = bytes!("f8678085019dc6838082520894deaddeaddeaddeaddeaddeaddeaddeaddeaddead38808360306ca06664c078fa60bd3ece050903dd295949908dd9686ec8871fa558f868e031cd39a00ed4f0b122b32b73f19230fabe6a726e2d07f84eda5beaa42a1ae1271bdee39f");
+
+/// A signed legacy contract creation, EIP-2718 encoded.
+pub(crate) const TEST_CONTRACT_CREATION_TX: Bytes = bytes!("f86481d8088302088a808090435b8080556001015a6161a8106001578718e5bb3abd109fa0ea5ad6553fb67639cec694e6697ac7b718bd7044fcdf5608fa64f6058e67db93a03953b5792d7d9ef7fc602fbe260e7a290760e8adc634f99ab1896e2c0d55afcb");
+
+/// Decodes [TEST_LEGACY_TX].
+pub(crate) fn test_legacy_transaction() -> FullTransaction {
+    FullTransaction::decode_enveloped(TEST_LEGACY_TX).unwrap()
+}
+
 /// Check if the test execution client is reachable by sending a GET request to it.
 pub(crate) async fn try_get_execution_api_url() -> Option<&'static str> {
     if reqwest::get(EXECUTION_API_URL).await.is_ok() {
@@ -133,6 +153,56 @@ pub(crate) fn default_test_transaction(sender: Address, nonce: Option<u64>) -> T
         .with_max_fee_per_gas(20_000_000_000)
 }
 
+/// Encrypt the transactions of an encrypted inclusion request with the given key, with a
+/// cheap PBKDF2 key derivation.
+pub(crate) fn encrypt_txs(txs: &[FullTransaction], key: &B256) -> Crypto {
+    let plain_text = serialize_txs(txs, serde_json::value::Serializer).unwrap().to_string();
+
+    let salt = rand::thread_rng().gen::<[u8; SALT_SIZE]>().to_vec();
+    let iv = rand::thread_rng().gen::<[u8; IV_SIZE]>().to_vec().into();
+    let kdf = Kdf::Pbkdf2(Pbkdf2 { dklen: 32, c: 1024, prf: Prf::HmacSha256, salt: salt.into() });
+    let cipher = Cipher::Aes128Ctr(Aes128Ctr { iv });
+
+    let (cipher_text, checksum) =
+        encrypt(plain_text.as_bytes(), key.as_slice(), &kdf, &cipher).unwrap();
+
+    Crypto {
+        kdf: KdfModule { function: kdf.function(), params: kdf, message: EmptyString },
+        checksum: ChecksumModule {
+            function: Sha256Checksum::function(),
+            params: EmptyMap,
+            message: checksum.to_vec().into(),
+        },
+        cipher: CipherModule {
+            function: cipher.function(),
+            params: cipher,
+            message: cipher_text.into(),
+        },
+    }
+}
+
+/// Two Mersenne primes, 2^607 - 1 and 2^521 - 1, whose product is the modulus of the time-lock
+/// puzzles of the tests.
+pub(crate) fn test_timelock_primes() -> (U2048, U2048) {
+    let one = U2048::from(1);
+    ((one << 607) - one, (one << 521) - one)
+}
+
+/// Lock the key in a time-lock puzzle of the given number of squarings, computing its solution
+/// with the factorization of the modulus like clients do.
+pub(crate) fn lock_key(key: &B256, squarings: u64) -> TimeLockPuzzle {
+    let one = U2048::from(1);
+    let (p, q) = test_timelock_primes();
+    let (modulus, phi) = (p * q, (p - one) * (q - one));
+
+    let base = U2048::from(3);
+    let exponent = U2048::from(2).pow_mod(U2048::from(squarings), phi);
+    let solution = base.pow_mod(exponent, modulus);
+
+    let puzzle = TimeLockPuzzle { modulus, base, squarings, locked_key: B256::ZERO };
+    TimeLockPuzzle { locked_key: puzzle.unlock(&solution) ^ *key, ..puzzle }
+}
+
 /// Arbitrary bytes that can be signed with both ECDSA and BLS keys
 pub(crate) struct TestSignableData {
     pub data: [u8; 32],
@@ -177,7 +247,17 @@ pub(crate) async fn create_signed_commitment_request(
         let full_tx = FullTransaction::decode_enveloped(raw_encoded.as_slice())?;
         full_txs.push(full_tx);
     }
-    let mut request = InclusionRequest { txs: full_txs, slot, signature: None, signer: None };
+    let mut request = InclusionRequest {
+        txs: full_txs,
+        slot,
+        max_slot: None,
+        top: false,
+        partial: false,
+        batch: false,
+        signature: None,
+        signer: None,
+        voucher: None,
+    };
 
     request.recover_signers()?;
 
@@ -267,7 +347,8 @@ async fn generate_test_data_kurtosis() {
     let transactions = random_constraints(1);
 
     // Prepare a ConstraintsMessage
-    let constraints_msg = ConstraintsMessage { pubkey: pk, slot: 32, top: true, transactions };
+    let constraints_msg =
+        ConstraintsMessage { pubkey: pk, slot: 32, top: true, index: None, transactions };
 
     let digest = SignableBLS::digest(&constraints_msg);
 
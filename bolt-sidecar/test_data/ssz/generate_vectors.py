@@ -0,0 +1,177 @@
+#!/usr/bin/env python3
+"""Generates the SSZ reference vectors of `test_ssz_reference_vectors` in
+`src/primitives/ssz.rs`.
+
+The encoding and merkleization follow the SSZ specification of the consensus specs
+(https://github.com/ethereum/consensus-specs/blob/v1.4.0/ssz/simple-serialize.md), written
+from scratch with the standard library only, so that the vectors don't depend on the Rust
+implementation they check. Requires Python 3.8+, run with:
+
+    python3 test_data/ssz/generate_vectors.py
+"""
+
+from hashlib import sha256
+
+# Constants of the containers, see `src/primitives/ssz.rs`
+MAX_TRANSACTIONS_PER_MESSAGE = 128
+MAX_BYTES_PER_TRANSACTION = 1073741824
+# The index of a top-of-block bundle is a list of at most one uint64
+MAX_INDICES = 1
+
+VALIDATOR_PUBKEY = bytes.fromhex(
+    "8a37d5942b2919e4e77f7784805146da013bd4cd0c77eee5f689873980a23c70"
+    "570dfd08abc3b267003b32d2e1c015eb"
+)
+DELEGATEE_PUBKEY = bytes.fromhex(
+    "8a5985a8000d845913dad7651ea42f30b71b561cf759189f3390ddfa726d1112"
+    "b182af8547a8393af24116173832442f"
+)
+# The point at infinity
+SIGNATURE = bytes([0xC0]) + bytes(95)
+# The transaction of `test_legacy_transaction`, see `src/test_util.rs`
+TRANSACTION = bytes.fromhex(
+    "f8678085019dc6838082520894deaddeaddeaddeaddeaddeaddeaddeaddeaddead38808360306ca06664c078"
+    "fa60bd3ece050903dd295949908dd9686ec8871fa558f868e031cd39a00ed4f0b122b32b73f19230fabe6a72"
+    "6e2d07f84eda5beaa42a1ae1271bdee39f"
+)
+
+REVOCATION_ACTION = 1
+CONSTRAINTS_SLOT = 165
+
+
+# Serialization -------------------------------------------------------------
+
+
+def uint(value, size):
+    return value.to_bytes(size, "little")
+
+
+def container(fields):
+    """Serializes a container from its fields, given as `(bytes, is_variable)` pairs."""
+    fixed_len = sum(4 if variable else len(data) for data, variable in fields)
+    fixed, variable_parts = b"", b""
+    for data, variable in fields:
+        if variable:
+            fixed += uint(fixed_len + len(variable_parts), 4)
+            variable_parts += data
+        else:
+            fixed += data
+    return fixed + variable_parts
+
+
+def list_of_variable(items):
+    """Serializes a list of variable-size items, preceded by their offsets."""
+    return container([(item, True) for item in items])
+
+
+# Merkleization -------------------------------------------------------------
+
+
+def hash_nodes(a, b):
+    return sha256(a + b).digest()
+
+
+def next_pow_of_two(n):
+    return 1 if n <= 1 else 1 << (n - 1).bit_length()
+
+
+def merkleize(chunks, limit=None):
+    size = next_pow_of_two(len(chunks) if limit is None else limit)
+    nodes = chunks + [bytes(32)] * (size - len(chunks))
+    while len(nodes) > 1:
+        nodes = [hash_nodes(nodes[i], nodes[i + 1]) for i in range(0, len(nodes), 2)]
+    return nodes[0]
+
+
+def mix_in_length(root, length):
+    return hash_nodes(root, uint(length, 32))
+
+
+def pack(data):
+    data += bytes(-len(data) % 32)
+    return [data[i : i + 32] for i in range(0, len(data), 32)]
+
+
+def root_of_bytes_vector(data):
+    return merkleize(pack(data))
+
+
+def root_of_bytes_list(data, limit):
+    return mix_in_length(merkleize(pack(data), (limit + 31) // 32), len(data))
+
+
+def root_of_uint(value, size):
+    return pack(uint(value, size))[0]
+
+
+def root_of_uint64_list(values, limit):
+    data = b"".join(uint(value, 8) for value in values)
+    return mix_in_length(merkleize(pack(data), (limit * 8 + 31) // 32), len(values))
+
+
+def root_of_transactions(transactions):
+    roots = [root_of_bytes_list(tx, MAX_BYTES_PER_TRANSACTION) for tx in transactions]
+    return mix_in_length(merkleize(roots, MAX_TRANSACTIONS_PER_MESSAGE), len(transactions))
+
+
+# Vectors -------------------------------------------------------------------
+
+
+def revocation():
+    message = container(
+        [(uint(REVOCATION_ACTION, 1), False), (VALIDATOR_PUBKEY, False), (DELEGATEE_PUBKEY, False)]
+    )
+    encoded = container([(message, False), (SIGNATURE, False)])
+
+    message_root = merkleize(
+        [
+            root_of_uint(REVOCATION_ACTION, 1),
+            root_of_bytes_vector(VALIDATOR_PUBKEY),
+            root_of_bytes_vector(DELEGATEE_PUBKEY),
+        ]
+    )
+    root = merkleize([message_root, root_of_bytes_vector(SIGNATURE)])
+    return encoded, message_root, root
+
+
+def constraints(top, index):
+    """The constraints of `TRANSACTION`, with the index of its bundle if not None."""
+    indices = [] if index is None else [index]
+    encoded = container(
+        [
+            (VALIDATOR_PUBKEY, False),
+            (uint(CONSTRAINTS_SLOT, 8), False),
+            (uint(top, 1), False),
+            (b"".join(uint(i, 8) for i in indices), True),
+            (list_of_variable([TRANSACTION]), True),
+        ]
+    )
+
+    root = merkleize(
+        [
+            root_of_bytes_vector(VALIDATOR_PUBKEY),
+            root_of_uint(CONSTRAINTS_SLOT, 8),
+            root_of_uint(top, 1),
+            root_of_uint64_list(indices, MAX_INDICES),
+            root_of_transactions([TRANSACTION]),
+        ]
+    )
+    return encoded, root
+
+
+def main():
+    encoded, message_root, root = revocation()
+    print("SignedRevocation")
+    print(f"  bytes:        {encoded.hex()}")
+    print(f"  message root: {message_root.hex()}")
+    print(f"  root:         {root.hex()}")
+
+    for name, top, index in [("ConstraintsMessage", 0, None), ("Indexed ConstraintsMessage", 1, 2)]:
+        encoded, root = constraints(top, index)
+        print(name)
+        print(f"  bytes:        {encoded.hex()}")
+        print(f"  root:         {root.hex()}")
+
+
+if __name__ == "__main__":
+    main()